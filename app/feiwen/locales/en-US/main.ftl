@@ -26,6 +26,49 @@ query-validation-range = Maximum must be greater than or equal to minimum
 query-validation-value = Select a valid value
 query-validation-selection = Select at least one item
 query-validation-sort-field = Select a sort field
+query-validation-search-relation = Full-text search only supports "contains"
+query-saved-title = Saved Queries
+query-saved-description = Click a query to load it into the builder and run it
+query-saved-name-placeholder = Query name
+query-saved-save = Save as New
+query-saved-overwrite = Update
+query-saved-rename = Rename
+query-saved-copy = Copy JSON
+query-saved-import = Import
+query-saved-export = Export
+query-saved-empty = Name the current query and save it to reuse it later.
+query-saved-count = { $count } matches
+query-saved-count-unknown = Count unavailable
+query-saved-export-results = Export Results
+query-export-results = Export Results
+query-export-running = Exporting…
+query-export-columns = Columns
+query-export-done = Exported { $count } novels to { $path }
+query-text-placeholder = Query, e.g. tag:甜文 -tag:虐 words>=100000 author:"某某" sort:-replies
+query-text-apply = Apply
+query-text-from-builder = Show Builder as Text
+query-side-detail = Details
+query-side-analytics = Tag Analytics
+query-analytics-empty = Search to analyze the tags of the results
+query-analytics-loading = Analyzing…
+query-analytics-no-novels = No novels in the results
+query-analytics-summary = { $novels } novels · { $tags } tags · { $untagged } untagged
+query-analytics-frequency = Tag Frequency
+query-analytics-no-tags = None of the novels in the results has tags
+query-analytics-co-occurrence = Tags Together
+query-analytics-co-occurrence-empty = At least two tags are needed to show pairs
+query-analytics-co-occurrence-caption = Novels carrying each pair of the { $count } most common tags
+query-analytics-less = Less
+query-analytics-more = More
+query-analytics-together = Together
+query-analytics-distribution = Words and Replies by Tag
+query-analytics-distribution-hint = Min / lower quartile / median / upper quartile / max
+query-analytics-tag = Tag
+query-analytics-novels = Novels
+query-analytics-words = Words
+query-analytics-replies = Replies
+query-analytics-authors = Top Authors by Words
+query-analytics-author = Author
 
 tags-select-retry = Retry
 
@@ -40,11 +83,14 @@ fetch-page-title = Feiwen · Fetch Task
 fetch-section-config = Fetch Config
 fetch-section-status = Task Status
 fetch-section-page-logs = Page Logs
+fetch-section-history = Run History
 fetch-section-actions = Actions
 fetch-field-url = Entry URL
 fetch-field-start-page = Start Page
 fetch-field-end-page = End Page
 fetch-field-cookie = Cookie
+fetch-field-concurrency = Concurrency
+fetch-field-rate-limit = Requests per Minute
 fetch-cookie-hidden = Cookie is hidden
 fetch-summary-open = View Details
 fetch-snapshot-title = Run Snapshot
@@ -78,22 +124,25 @@ fetch-state-parse-error = Parse Error
 
 fetch-action-interrupt = Interrupt
 fetch-action-resume-interrupted = Resume
-fetch-action-retry-failed = Retry Failed Page
+fetch-action-retry-failed = Retry Failed Pages
 
 fetch-stat-current-page = Current Page
 fetch-stat-completed-pages = Completed
 fetch-stat-total = Total Saved
-fetch-stat-next-page = Next Page
+fetch-stat-remaining-pages = Remaining
 fetch-stat-failed-page = Failed Page
+fetch-stat-failed-pages = Failed Pages
 fetch-stat-error-kind = Error Type
 fetch-stat-error-detail = Error Detail
-fetch-failed-stop-note = The task stops here and will not silently continue to the next page.
+fetch-failed-stop-note = A database error stops the task; the remaining pages are not fetched.
+fetch-failed-pages-note = The other pages are done; { $count } pages still failed after retrying and can be retried on their own.
 
 fetch-error-kind-database = Database Error
 fetch-error-kind-network = Network Error
 fetch-error-kind-parse = Parse Error
 fetch-error-kind-other = Other Error
 fetch-error-invalid-page-range = Start page cannot be greater than end page
+fetch-error-invalid-concurrency = Concurrency must be between 1 and 8
 
 fetch-log-status-running = Fetching
 fetch-log-status-success = Success
@@ -105,3 +154,12 @@ fetch-log-column-elapsed = Time
 fetch-log-column-detail = Detail
 fetch-log-message-running = Requesting and parsing...
 fetch-log-message-success = Parsed and saved
+
+fetch-history-empty = No fetch runs yet
+fetch-history-status-running = Running
+fetch-history-status-interrupted = Interrupted
+fetch-history-status-failed = Has Failures
+fetch-history-status-succeeded = Done
+fetch-history-pages = { $done } / { $count } pages done · { $failed } failed
+fetch-history-resume = Resume
+fetch-history-retry-failed = Retry Failed
@@ -26,6 +26,49 @@ query-validation-range = 最大值必须大于或等于最小值
 query-validation-value = 请选择有效项
 query-validation-selection = 请选择至少一项
 query-validation-sort-field = 请选择排序字段
+query-validation-search-relation = 全文搜索只支持“包含”
+query-saved-title = 已保存查询
+query-saved-description = 点击查询即可载入构建器并执行
+query-saved-name-placeholder = 查询名称
+query-saved-save = 另存为新查询
+query-saved-overwrite = 更新
+query-saved-rename = 重命名
+query-saved-copy = 复制 JSON
+query-saved-import = 导入
+query-saved-export = 导出
+query-saved-empty = 为当前查询命名并保存,之后即可直接复用。
+query-saved-count = { $count } 条匹配
+query-saved-count-unknown = 无法统计数量
+query-saved-export-results = 导出结果
+query-export-results = 导出结果
+query-export-running = 正在导出…
+query-export-columns = 选择列
+query-export-done = 已导出 { $count } 部小说到 { $path }
+query-text-placeholder = 查询语句,例如 tag:甜文 -tag:虐 words>=100000 author:"某某" sort:-replies
+query-text-apply = 应用
+query-text-from-builder = 将构建器转为文本
+query-side-detail = 作品详情
+query-side-analytics = 标签分析
+query-analytics-empty = 搜索后即可分析当前结果的标签
+query-analytics-loading = 正在分析…
+query-analytics-no-novels = 当前结果没有作品
+query-analytics-summary = { $novels } 部作品 · { $tags } 个标签 · { $untagged } 部无标签
+query-analytics-frequency = 标签出现次数
+query-analytics-no-tags = 当前结果中的作品都没有标签
+query-analytics-co-occurrence = 标签共现
+query-analytics-co-occurrence-empty = 至少需要两个标签才能查看共现
+query-analytics-co-occurrence-caption = 最常见的 { $count } 个标签两两同时出现的作品数
+query-analytics-less = 较少
+query-analytics-more = 较多
+query-analytics-together = 同时出现
+query-analytics-distribution = 各标签字数与回复分布
+query-analytics-distribution-hint = 最少 / 下四分位 / 中位数 / 上四分位 / 最多
+query-analytics-tag = 标签
+query-analytics-novels = 作品
+query-analytics-words = 字数
+query-analytics-replies = 回复
+query-analytics-authors = 作者产出
+query-analytics-author = 作者
 
 tags-select-retry = 重试
 
@@ -40,11 +83,14 @@ fetch-page-title = 飞文 · 抓取任务
 fetch-section-config = 抓取配置
 fetch-section-status = 任务状态
 fetch-section-page-logs = 页面日志
+fetch-section-history = 抓取记录
 fetch-section-actions = 操作区
 fetch-field-url = 入口链接
 fetch-field-start-page = 起始页
 fetch-field-end-page = 结束页
 fetch-field-cookie = Cookie
+fetch-field-concurrency = 并发数
+fetch-field-rate-limit = 每分钟请求上限
 fetch-cookie-hidden = Cookie 已隐藏
 fetch-summary-open = 查看详情
 fetch-snapshot-title = 本次运行快照
@@ -78,22 +124,25 @@ fetch-state-parse-error = 解析错误
 
 fetch-action-interrupt = 中断
 fetch-action-resume-interrupted = 从中断处继续
-fetch-action-retry-failed = 从失败页重试
+fetch-action-retry-failed = 重试失败页
 
 fetch-stat-current-page = 当前页
 fetch-stat-completed-pages = 已完成
 fetch-stat-total = 入库总数
-fetch-stat-next-page = 下次继续页
+fetch-stat-remaining-pages = 剩余页数
 fetch-stat-failed-page = 失败页
+fetch-stat-failed-pages = 失败页数
 fetch-stat-error-kind = 错误类型
 fetch-stat-error-detail = 错误详情
-fetch-failed-stop-note = 失败后不会继续静默抓取下一页。
+fetch-failed-stop-note = 数据库出错后任务会停下，不会继续抓取其余页面。
+fetch-failed-pages-note = 其余页面已抓完，共 { $count } 页重试后仍失败，可只重试这些页。
 
 fetch-error-kind-database = 数据库错误
 fetch-error-kind-network = 网络错误
 fetch-error-kind-parse = 解析错误
 fetch-error-kind-other = 其他错误
 fetch-error-invalid-page-range = 起始页不能大于结束页
+fetch-error-invalid-concurrency = 并发数需在 1 到 8 之间
 
 fetch-log-status-running = 抓取中
 fetch-log-status-success = 成功
@@ -105,3 +154,12 @@ fetch-log-column-elapsed = 耗时
 fetch-log-column-detail = 详情
 fetch-log-message-running = 正在请求并解析中...
 fetch-log-message-success = 解析成功，入库
+
+fetch-history-empty = 还没有抓取记录
+fetch-history-status-running = 运行中
+fetch-history-status-interrupted = 已中断
+fetch-history-status-failed = 有失败
+fetch-history-status-succeeded = 已完成
+fetch-history-pages = 完成 { $done } / { $count } 页 · 失败 { $failed } 页
+fetch-history-resume = 继续
+fetch-history-retry-failed = 重试失败页
@@ -38,6 +38,18 @@ pub(crate) enum FeiwenError {
     WordCountParse,
     #[error("count uint 解析错误,{}",.0)]
     CountUintParse(String),
+    #[error("查询名称不能为空")]
+    SavedQueryNameEmpty,
+    #[error("已存在同名查询:{}",.0)]
+    SavedQueryNameTaken(String),
+    #[error("保存的查询不存在")]
+    SavedQueryMissing,
+    #[error("查询格式错误:{}",.0)]
+    SavedQueryFormat(#[from] serde_json::Error),
+    #[error("不支持的查询导出版本:{}",.0)]
+    SavedQueryVersion(u32),
+    #[error("抓取记录不存在")]
+    FetchRunMissing,
     #[error("log file not found")]
     LogFileNotFound,
 }
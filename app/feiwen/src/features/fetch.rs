@@ -1,13 +1,18 @@
 use crate::{
     fetch::{FetchErrorKind, FetchPageError},
     foundation::{I18n, IconName},
-    store::database,
+    store::{
+        database::{self, DatabasePhase, DatabaseResource},
+        fetch_run::{FetchRunRecord, FetchRunStatus},
+        history,
+    },
 };
 use async_compat::Compat;
+use fluent_bundle::FluentArgs;
 use gpui::prelude::FluentBuilder as _;
 use gpui::*;
 use gpui_component::{
-    ActiveTheme, Icon, Sizable, StyledExt,
+    ActiveTheme, Disableable, Icon, Sizable, StyledExt,
     button::{Button, ButtonVariants},
     h_flex,
     input::{Input, InputContentType, InputState},
@@ -29,18 +34,16 @@ mod runner;
 pub(crate) use form::FetchRequest;
 pub(crate) use run::{FetchPageLog, FetchProgress, FetchRun};
 
-use form::{FetchDraft, FetchValidator};
-use run::{
-    FetchFailure, FetchMessage, FetchPageLogStatus, FetchStatus, resume_page_after_interrupt,
-    retry_page_after_failure,
-};
-use runner::Runner;
+use form::{FetchDraft, FetchValidator, MAX_CONCURRENCY};
+use run::{FetchFailure, FetchMessage, FetchPageLogStatus, FetchStatus};
+use runner::{RunPages, Runner};
 
 const LOG_PAGE_COLUMN: f32 = 72.;
 const LOG_STATUS_COLUMN: f32 = 120.;
 const LOG_INSERTED_COLUMN: f32 = 96.;
 const LOG_ELAPSED_COLUMN: f32 = 96.;
 const LOG_DETAIL_COLUMN: f32 = 520.;
+const HISTORY_RUNS: usize = 20;
 
 pub(crate) struct FetchView {
     task_state: Store<FetchRun>,
@@ -50,12 +53,19 @@ pub(crate) struct FetchView {
     start_page: Entity<IntegerInputState<u32>>,
     end_page: Entity<IntegerInputState<u32>>,
     cookie_input: Entity<InputState>,
+    concurrency: Entity<IntegerInputState<u32>>,
+    requests_per_minute: Entity<IntegerInputState<u32>>,
     _form_controls: (
         FormInput,
         FormIntegerInput<u32>,
         FormIntegerInput<u32>,
         FormInput,
+        FormIntegerInput<u32>,
+        FormIntegerInput<u32>,
     ),
+    /// The latest stored runs, newest first.
+    history: Vec<FetchRunRecord>,
+    history_task: Option<Task<()>>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -113,10 +123,33 @@ impl FetchView {
             window,
             cx,
         );
+        let concurrency_control = FormIntegerInput::new(
+            &form,
+            FetchDraft::CONCURRENCY,
+            move |window, cx| {
+                IntegerInputState::new(window, cx)
+                    .min(1)
+                    .max(MAX_CONCURRENCY)
+                    .step(1)
+            },
+            window,
+            cx,
+        )
+        .expect("bind fetch concurrency");
+        let rate_control = FormIntegerInput::new(
+            &form,
+            FetchDraft::REQUESTS_PER_MINUTE,
+            move |window, cx| IntegerInputState::new(window, cx).min(1).step(10),
+            window,
+            cx,
+        )
+        .expect("bind fetch rate limit");
         let url_input = (*url_control).clone();
         let start_page = (*start_control).clone();
         let end_page = (*end_control).clone();
         let cookie_input = (*cookie_control).clone();
+        let concurrency = (*concurrency_control).clone();
+        let requests_per_minute = (*rate_control).clone();
         let log_table = cx.new(|cx| {
             TableState::new(
                 FetchLogTableDelegate {
@@ -136,6 +169,25 @@ impl FetchView {
                 cx.notify();
             }),
             cx.observe(&form, |_, _, cx| cx.notify()),
+            // A run that just ended has a new status in the history.
+            task_state.observe_select(
+                cx,
+                |state: &FetchRun| state.is_running(),
+                |view, running, cx| {
+                    if !*running {
+                        view.reload_history(cx);
+                    }
+                },
+            ),
+            database::store(cx).observe_select(
+                cx,
+                |resource: &DatabaseResource| resource.phase(),
+                |view, phase, cx| {
+                    if *phase == DatabasePhase::Ready {
+                        view.reload_history(cx);
+                    }
+                },
+            ),
         ];
 
         Self {
@@ -146,7 +198,18 @@ impl FetchView {
             start_page,
             end_page,
             cookie_input,
-            _form_controls: (url_control, start_control, end_control, cookie_control),
+            concurrency,
+            requests_per_minute,
+            _form_controls: (
+                url_control,
+                start_control,
+                end_control,
+                cookie_control,
+                concurrency_control,
+                rate_control,
+            ),
+            history: Vec::new(),
+            history_task: None,
             _subscriptions,
         }
     }
@@ -178,17 +241,56 @@ impl FetchView {
             .read(cx, |state| state.titlebar_summary(i18n))
     }
 
-    fn resume_fetch(&mut self, cx: &mut Context<Self>) {
-        self.start_fetch_from(RunMode::ResumeInterrupted, cx);
-    }
-
-    fn retry_failed_page(&mut self, cx: &mut Context<Self>) {
-        self.start_fetch_from(RunMode::RetryFailed, cx);
-    }
-
     fn interrupt_fetch(&mut self, cx: &mut Context<Self>) {
+        let run_id = self.task_state.read(cx, FetchRun::run_id);
         self.task_state
             .update(cx, |state| state.transition(FetchMessage::Interrupt));
+        let (Some(run_id), Ok(pool)) = (run_id, database::ready_pool(cx)) else {
+            return;
+        };
+        // Detached, so a history reload can't cancel it.
+        cx.spawn(async move |this, cx| {
+            let stored = cx
+                .background_spawn(async move {
+                    let conn = pool.get()?;
+                    FetchRunRecord::set_status(
+                        &conn,
+                        run_id,
+                        FetchRunStatus::Interrupted,
+                        history::unix_now(),
+                    )
+                })
+                .await;
+            if let Err(err) = stored {
+                event!(Level::ERROR, run_id, error = %err, "failed to store interrupted fetch run");
+            }
+            let _ = this.update(cx, |view, cx| view.reload_history(cx));
+        })
+        .detach();
+    }
+
+    fn reload_history(&mut self, cx: &mut Context<Self>) {
+        let Ok(pool) = database::ready_pool(cx) else {
+            return;
+        };
+        self.history_task = Some(cx.spawn(async move |this, cx| {
+            let runs = cx
+                .background_spawn(async move {
+                    let conn = pool.get()?;
+                    FetchRunRecord::recent(&conn, HISTORY_RUNS)
+                })
+                .await;
+            let _ = this.update(cx, |view, cx| {
+                match runs {
+                    Ok(runs) => view.history = runs,
+                    Err(err) => {
+                        event!(Level::ERROR, error = %err, "failed to load fetch run history");
+                    }
+                }
+                view.history_task = None;
+                cx.notify();
+            });
+        }));
     }
 
     fn load_snapshot_into_form(&mut self, cx: &mut Context<Self>) {
@@ -255,60 +357,45 @@ impl FetchView {
             }
         };
 
-        let (request, clear_logs) = {
-            let state = self.task_state.read(cx, |state| {
-                (
-                    state.snapshot().cloned(),
-                    state.last_success_page(),
-                    state.failed_page(),
-                )
-            });
-            let start_page = match mode {
-                RunMode::Fresh => fresh_request.as_ref().unwrap().start_page,
-                RunMode::ResumeInterrupted => {
-                    let snapshot = state.0.as_ref().expect("interrupted run has snapshot");
-                    match resume_page_after_interrupt(
-                        state.1,
-                        snapshot.start_page,
-                        snapshot.end_page,
-                    ) {
-                        Some(page) => page,
-                        None => {
-                            event!(
-                                Level::INFO,
-                                mode = mode.label(),
-                                "no interrupted fetch page to resume"
-                            );
-                            return;
-                        }
-                    }
-                }
-                RunMode::RetryFailed => {
-                    let snapshot = state.0.as_ref().expect("failed run has snapshot");
-                    match retry_page_after_failure(state.2, snapshot.start_page, snapshot.end_page)
-                    {
-                        Some(page) => page,
-                        None => {
-                            event!(
-                                Level::INFO,
-                                mode = mode.label(),
-                                "no failed fetch page to retry"
-                            );
-                            return;
-                        }
+        let (request, pages, clear_logs) = match mode {
+            RunMode::Fresh => (
+                fresh_request.expect("fresh run has a request"),
+                RunPages::Fresh,
+                true,
+            ),
+            RunMode::Resume(run_id) | RunMode::RetryFailed(run_id) => {
+                let run = match FetchRunRecord::load(&conn, run_id) {
+                    Ok(run) => run,
+                    Err(err) => {
+                        event!(
+                            Level::ERROR,
+                            mode = mode.label(),
+                            run_id,
+                            error = %err,
+                            "failed to load fetch run"
+                        );
+                        return;
                     }
-                }
-            };
-            let request = fresh_request.clone().unwrap_or_else(|| {
-                let snapshot = state.0.as_ref().unwrap();
-                FetchRequest {
-                    url: snapshot.url.clone(),
-                    start_page,
-                    end_page: snapshot.end_page,
-                    cookie: snapshot.cookie.clone(),
-                }
-            });
-            (request, matches!(mode, RunMode::Fresh))
+                };
+                // Runs don't store the cookie: the current run's snapshot
+                // still has it, older runs take the one in the form.
+                let (current, snapshot_cookie) = self.task_state.read(cx, |state| {
+                    (
+                        state.run_id() == Some(run_id),
+                        state.snapshot().map(|snapshot| snapshot.cookie.clone()),
+                    )
+                });
+                let cookie = snapshot_cookie
+                    .filter(|_| current)
+                    .unwrap_or_else(|| FetchDraft::COOKIE.get(&self.form, cx));
+                let request = FetchRequest::from_plan(run.plan.clone(), cookie);
+                let pages = if matches!(mode, RunMode::Resume(_)) {
+                    RunPages::Resume(run)
+                } else {
+                    RunPages::RetryFailed(run)
+                };
+                (request, pages, !current)
+            }
         };
         event!(
             Level::INFO,
@@ -316,6 +403,8 @@ impl FetchView {
             start_page = request.start_page,
             end_page = request.end_page,
             has_cookie = !request.cookie.is_empty(),
+            concurrency = request.concurrency,
+            requests_per_minute = request.requests_per_minute,
             clear_logs,
             "fetch task scheduled"
         );
@@ -329,7 +418,7 @@ impl FetchView {
                 has_cookie = !runner_request.cookie.is_empty()
             );
             let mut runner = Runner::new(runner_request, owner, conn, cx);
-            Compat::new(async move { runner.run().await })
+            Compat::new(async move { runner.run(pages).await })
                 .instrument(span)
                 .await;
         });
@@ -346,16 +435,18 @@ impl FetchView {
 #[derive(Clone, Copy)]
 enum RunMode {
     Fresh,
-    ResumeInterrupted,
-    RetryFailed,
+    /// Fetch the pages a stored run hasn't finished.
+    Resume(i32),
+    /// Fetch again only the pages a stored run gave up on.
+    RetryFailed(i32),
 }
 
 impl RunMode {
     fn label(self) -> &'static str {
         match self {
             Self::Fresh => "fresh",
-            Self::ResumeInterrupted => "resume_interrupted",
-            Self::RetryFailed => "retry_failed",
+            Self::Resume(_) => "resume",
+            Self::RetryFailed(_) => "retry_failed",
         }
     }
 }
@@ -386,7 +477,15 @@ impl Render for FetchView {
                             .child(self.render_form_panel(is_running, cx))
                             .child(self.render_status_panel(cx)),
                     )
-                    .child(self.render_logs_panel(cx)),
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_h_0()
+                            .flex()
+                            .gap_3()
+                            .child(self.render_history_panel(is_running, cx))
+                            .child(self.render_logs_panel(cx)),
+                    ),
             )
     }
 }
@@ -400,6 +499,8 @@ impl FetchView {
             field_end_page,
             field_cookie,
             cookie_hidden,
+            field_concurrency,
+            field_rate_limit,
         ) = {
             let i18n = cx.global::<I18n>();
             (
@@ -409,6 +510,8 @@ impl FetchView {
                 i18n.t("fetch-field-end-page"),
                 i18n.t("fetch-field-cookie"),
                 i18n.t("fetch-cookie-hidden"),
+                i18n.t("fetch-field-concurrency"),
+                i18n.t("fetch-field-rate-limit"),
             )
         };
         let field_color = cx.theme().foreground;
@@ -458,6 +561,39 @@ impl FetchView {
                             .child(IntegerInput::new(&self.end_page)),
                     ),
             )
+            .child(
+                div()
+                    .flex()
+                    .gap_3()
+                    .child(
+                        div()
+                            .flex_1()
+                            .flex()
+                            .flex_col()
+                            .gap_2()
+                            .child(field_label(
+                                IconName::Layers,
+                                field_concurrency,
+                                field_color,
+                                cx,
+                            ))
+                            .child(IntegerInput::new(&self.concurrency)),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .flex()
+                            .flex_col()
+                            .gap_2()
+                            .child(field_label(
+                                IconName::Timer,
+                                field_rate_limit,
+                                field_color,
+                                cx,
+                            ))
+                            .child(IntegerInput::new(&self.requests_per_minute)),
+                    ),
+            )
             .child(field_label(IconName::Cookie, field_cookie, field_color, cx))
             .child(Input::new(&self.cookie_input))
             .child(
@@ -608,14 +744,15 @@ impl FetchView {
     }
 
     fn render_interrupted_status(&self, progress: &FetchProgress, cx: &mut Context<Self>) -> Div {
-        let (title, next_page_label, resume_label) = {
+        let (title, remaining_label, resume_label) = {
             let i18n = cx.global::<I18n>();
             (
                 i18n.t("fetch-state-interrupted-title"),
-                i18n.t("fetch-stat-next-page"),
+                i18n.t("fetch-stat-remaining-pages"),
                 i18n.t("fetch-action-resume-interrupted"),
             )
         };
+        let run_id = progress.run_id;
         status_layout(
             div()
                 .flex()
@@ -630,13 +767,8 @@ impl FetchView {
                 .child(
                     Label::new(format!(
                         "{} {}",
-                        next_page_label,
-                        resume_page_after_interrupt(
-                            progress.last_success_page,
-                            progress.start_page,
-                            progress.end_page
-                        )
-                        .unwrap_or(progress.end_page)
+                        remaining_label,
+                        progress.remaining_pages()
                     ))
                     .text_sm()
                     .text_color(cx.theme().muted_foreground),
@@ -650,7 +782,12 @@ impl FetchView {
                             .warning()
                             .icon(IconName::CirclePlay)
                             .label(resume_label)
-                            .on_click(cx.listener(|this, _, _, cx| this.resume_fetch(cx))),
+                            .disabled(run_id.is_none())
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                if let Some(run_id) = run_id {
+                                    this.start_fetch_from(RunMode::Resume(run_id), cx);
+                                }
+                            })),
                     )
                     .child(
                         Button::new("fetch-load-snapshot")
@@ -675,16 +812,38 @@ impl FetchView {
             retry_label,
         ) = {
             let i18n = cx.global::<I18n>();
+            // A fatal error left pages unfetched, so the run is resumed;
+            // otherwise only the pages that failed are tried again.
+            let (stop_note, retry_label) = if failure.fatal {
+                (
+                    i18n.t("fetch-failed-stop-note"),
+                    i18n.t("fetch-action-resume-interrupted"),
+                )
+            } else {
+                let mut args = FluentArgs::new();
+                args.set("count", failure.progress.failed_pages());
+                (
+                    i18n.t_with_args("fetch-failed-pages-note", &args),
+                    i18n.t("fetch-action-retry-failed"),
+                )
+            };
             (
                 i18n.t("fetch-state-failed-title"),
                 i18n.t("fetch-stat-failed-page"),
                 i18n.t("fetch-stat-error-kind"),
                 error_kind_label(failure.kind, i18n),
                 i18n.t("fetch-stat-error-detail"),
-                i18n.t("fetch-failed-stop-note"),
-                i18n.t("fetch-action-retry-failed"),
+                stop_note,
+                retry_label,
             )
         };
+        let retry = failure.progress.run_id.map(|run_id| {
+            if failure.fatal {
+                RunMode::Resume(run_id)
+            } else {
+                RunMode::RetryFailed(run_id)
+            }
+        });
         status_layout(
             div()
                 .flex()
@@ -729,7 +888,12 @@ impl FetchView {
                             .danger()
                             .icon(IconName::RotateCcw)
                             .label(retry_label)
-                            .on_click(cx.listener(|this, _, _, cx| this.retry_failed_page(cx))),
+                            .disabled(retry.is_none())
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                if let Some(mode) = retry {
+                                    this.start_fetch_from(mode, cx);
+                                }
+                            })),
                     )
                     .child(
                         Button::new("fetch-load-snapshot")
@@ -757,6 +921,153 @@ impl FetchView {
             .child(metrics_grid(progress, cx))
     }
 
+    fn render_history_panel(&self, is_running: bool, cx: &mut Context<Self>) -> Div {
+        let section_history = cx.global::<I18n>().t("fetch-section-history");
+        let body = if self.history.is_empty() {
+            div().p_3().child(
+                Label::new(cx.global::<I18n>().t("fetch-history-empty"))
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground),
+            )
+        } else {
+            v_flex().children(
+                self.history
+                    .iter()
+                    .map(|run| self.render_history_row(run, is_running, cx))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        div()
+            .w(px(360.))
+            .flex_none()
+            .min_h_0()
+            .flex()
+            .flex_col()
+            .border_1()
+            .border_color(cx.theme().border)
+            .bg(cx.theme().tokens.background.background)
+            .rounded_lg()
+            .child(
+                div()
+                    .px_3()
+                    .py_2()
+                    .border_b_1()
+                    .border_color(cx.theme().border)
+                    .child(section_title(IconName::History, section_history, cx)),
+            )
+            .child(
+                div()
+                    .id("fetch-history")
+                    .flex_1()
+                    .min_h_0()
+                    .overflow_y_scroll()
+                    .child(body),
+            )
+    }
+
+    fn render_history_row(
+        &self,
+        run: &FetchRunRecord,
+        is_running: bool,
+        cx: &mut Context<Self>,
+    ) -> Div {
+        let i18n = cx.global::<I18n>();
+        let (status, color) = match run.status {
+            FetchRunStatus::Running => (i18n.t("fetch-history-status-running"), cx.theme().primary),
+            FetchRunStatus::Interrupted => (
+                i18n.t("fetch-history-status-interrupted"),
+                cx.theme().warning,
+            ),
+            FetchRunStatus::Failed => (i18n.t("fetch-history-status-failed"), cx.theme().danger),
+            FetchRunStatus::Succeeded => {
+                (i18n.t("fetch-history-status-succeeded"), cx.theme().success)
+            }
+        };
+        let mut args = FluentArgs::new();
+        args.set("done", run.done_pages);
+        args.set("count", run.page_count());
+        args.set("failed", run.failed_pages.len());
+        let pages = i18n.t_with_args("fetch-history-pages", &args);
+        let (resume_label, retry_label) = (
+            i18n.t("fetch-history-resume"),
+            i18n.t("fetch-history-retry-failed"),
+        );
+        let unfinished = run
+            .page_count()
+            .saturating_sub(run.done_pages)
+            .saturating_sub(run.failed_pages.len() as u32);
+        let run_id = run.id;
+        v_flex()
+            .gap_1()
+            .px_3()
+            .py_2()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(status).text_sm().font_medium().text_color(color))
+                    .child(
+                        Label::new(format!(
+                            "#{} · {}–{}",
+                            run.id, run.plan.start_page, run.plan.end_page
+                        ))
+                        .text_sm(),
+                    ),
+            )
+            .child(
+                Label::new(run.plan.url.clone())
+                    .text_xs()
+                    .truncate()
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .child(
+                Label::new(pages)
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .when(run.status != FetchRunStatus::Succeeded, |this| {
+                this.child(
+                    h_flex()
+                        .gap_2()
+                        .when(unfinished > 0, |this| {
+                            this.child(
+                                Button::new(ElementId::NamedInteger(
+                                    "fetch-history-resume".into(),
+                                    run_id as u64,
+                                ))
+                                .xsmall()
+                                .icon(IconName::CirclePlay)
+                                .label(resume_label)
+                                .disabled(is_running)
+                                .on_click(cx.listener(
+                                    move |this, _, _, cx| {
+                                        this.start_fetch_from(RunMode::Resume(run_id), cx);
+                                    },
+                                )),
+                            )
+                        })
+                        .when(!run.failed_pages.is_empty(), |this| {
+                            this.child(
+                                Button::new(ElementId::NamedInteger(
+                                    "fetch-history-retry".into(),
+                                    run_id as u64,
+                                ))
+                                .xsmall()
+                                .icon(IconName::RotateCcw)
+                                .label(retry_label)
+                                .disabled(is_running)
+                                .on_click(cx.listener(
+                                    move |this, _, _, cx| {
+                                        this.start_fetch_from(RunMode::RetryFailed(run_id), cx);
+                                    },
+                                )),
+                            )
+                        }),
+                )
+            })
+    }
+
     fn render_logs_panel(&self, cx: &mut Context<Self>) -> Div {
         let section_logs = cx.global::<I18n>().t("fetch-section-page-logs");
         div()
@@ -849,18 +1160,20 @@ fn status_title(icon: IconName, label: String, color: Hsla) -> Div {
         .child(Label::new(label).text_lg().font_medium().text_color(color))
 }
 
+/// Pages that are done either way, saved or given up on.
 fn progress_percent(progress: &FetchProgress) -> f32 {
     let page_count = progress.page_count().max(1) as f32;
-    let completed = progress.completed_pages().min(progress.page_count()) as f32;
-    (completed / page_count * 100.0).clamp(0.0, 100.0)
+    let tried = (progress.completed_pages() + progress.failed_pages()).min(progress.page_count());
+    (tried as f32 / page_count * 100.0).clamp(0.0, 100.0)
 }
 
 fn metrics_grid(progress: &FetchProgress, cx: &mut Context<FetchView>) -> Div {
-    let (current_page_label, completed_pages_label, total_label) = {
+    let (current_page_label, completed_pages_label, failed_pages_label, total_label) = {
         let i18n = cx.global::<I18n>();
         (
             i18n.t("fetch-stat-current-page"),
             i18n.t("fetch-stat-completed-pages"),
+            i18n.t("fetch-stat-failed-pages"),
             i18n.t("fetch-stat-total"),
         )
     };
@@ -879,6 +1192,16 @@ fn metrics_grid(progress: &FetchProgress, cx: &mut Context<FetchView>) -> Div {
             cx.theme().success,
             cx,
         ))
+        .child(metric_card(
+            failed_pages_label,
+            progress.failed_pages().to_string(),
+            if progress.failed_pages() > 0 {
+                cx.theme().danger
+            } else {
+                cx.theme().muted_foreground
+            },
+            cx,
+        ))
         .child(metric_card(
             total_label,
             progress.total.to_string(),
@@ -1100,16 +1423,18 @@ mod tests {
 
     #[test]
     fn progress_percent_clamps_to_component_range() {
-        let progress = |last_success_page| FetchProgress {
+        let progress = |completed, failed| FetchProgress {
             start_page: 3,
             end_page: 6,
+            run_id: None,
             current_page: 3,
-            last_success_page,
+            completed,
+            failed,
             total: 0,
         };
 
-        assert_eq!(progress_percent(&progress(None)), 0.0);
-        assert_eq!(progress_percent(&progress(Some(4))), 50.0);
-        assert_eq!(progress_percent(&progress(Some(10))), 100.0);
+        assert_eq!(progress_percent(&progress(0, 0)), 0.0);
+        assert_eq!(progress_percent(&progress(1, 1)), 50.0);
+        assert_eq!(progress_percent(&progress(10, 2)), 100.0);
     }
 }
@@ -1,11 +1,20 @@
 use gpui_form::{FormSchema, ValidationMessage, ValidationRequest, ValidationSink, Validator};
 
+use crate::store::fetch_run::FetchRunPlan;
+
+/// More parallel requests than this mostly gets the session blocked.
+pub(super) const MAX_CONCURRENCY: u32 = 8;
+
 #[derive(Clone, PartialEq, Eq)]
 pub(crate) struct FetchRequest {
     pub(super) url: String,
     pub(super) start_page: u32,
     pub(super) end_page: u32,
     pub(super) cookie: String,
+    /// Pages fetched at the same time.
+    pub(super) concurrency: u32,
+    /// Requests sent to the site's host per minute, across all pages.
+    pub(super) requests_per_minute: u32,
 }
 
 impl std::fmt::Debug for FetchRequest {
@@ -15,6 +24,8 @@ impl std::fmt::Debug for FetchRequest {
             .field("start_page", &self.start_page)
             .field("end_page", &self.end_page)
             .field("cookie_set", &!self.cookie.is_empty())
+            .field("concurrency", &self.concurrency)
+            .field("requests_per_minute", &self.requests_per_minute)
             .finish()
     }
 }
@@ -26,6 +37,9 @@ pub(super) struct FetchDraft {
     pub(super) start_page: u32,
     pub(super) end_page: u32,
     pub(super) cookie: String,
+    #[form(validate(on_change, on_submit))]
+    pub(super) concurrency: u32,
+    pub(super) requests_per_minute: u32,
 }
 
 impl Default for FetchDraft {
@@ -35,6 +49,8 @@ impl Default for FetchDraft {
             start_page: 1,
             end_page: 1,
             cookie: String::new(),
+            concurrency: 3,
+            requests_per_minute: 30,
         }
     }
 }
@@ -46,6 +62,8 @@ impl From<FetchDraft> for FetchRequest {
             start_page: value.start_page,
             end_page: value.end_page,
             cookie: value.cookie,
+            concurrency: value.concurrency,
+            requests_per_minute: value.requests_per_minute,
         }
     }
 }
@@ -57,6 +75,35 @@ impl From<&FetchRequest> for FetchDraft {
             start_page: value.start_page,
             end_page: value.end_page,
             cookie: value.cookie.clone(),
+            concurrency: value.concurrency,
+            requests_per_minute: value.requests_per_minute,
+        }
+    }
+}
+
+impl From<&FetchRequest> for FetchRunPlan {
+    fn from(value: &FetchRequest) -> Self {
+        Self {
+            url: value.url.clone(),
+            start_page: value.start_page,
+            end_page: value.end_page,
+            concurrency: value.concurrency,
+            requests_per_minute: value.requests_per_minute,
+        }
+    }
+}
+
+impl FetchRequest {
+    /// The request a stored run was started with, using `cookie` since
+    /// runs don't keep theirs.
+    pub(super) fn from_plan(plan: FetchRunPlan, cookie: String) -> Self {
+        Self {
+            url: plan.url,
+            start_page: plan.start_page,
+            end_page: plan.end_page,
+            cookie,
+            concurrency: plan.concurrency,
+            requests_per_minute: plan.requests_per_minute,
         }
     }
 }
@@ -76,6 +123,14 @@ impl Validator<FetchDraft> for FetchValidator {
                 ValidationMessage::key("fetch-error-invalid-page-range"),
             );
         }
+        if request.includes(&FetchDraft::CONCURRENCY)
+            && !(1..=MAX_CONCURRENCY).contains(&model.concurrency)
+        {
+            out.at(FetchDraft::CONCURRENCY).error(
+                "concurrency",
+                ValidationMessage::key("fetch-error-invalid-concurrency"),
+            );
+        }
     }
 }
 
@@ -90,6 +145,8 @@ mod tests {
             start_page: 3,
             end_page: 8,
             cookie: "secret".to_owned(),
+            concurrency: 4,
+            requests_per_minute: 20,
         };
 
         let restored = FetchDraft::from(&request);
@@ -31,17 +31,42 @@ pub(crate) struct FetchPageLog {
 pub(crate) struct FetchProgress {
     pub(super) start_page: u32,
     pub(super) end_page: u32,
+    /// The stored run, once the runner has opened it.
+    pub(super) run_id: Option<i32>,
+    /// The page started most recently; others may still be in flight.
     pub(super) current_page: u32,
-    pub(super) last_success_page: Option<u32>,
+    /// Pages saved, earlier sessions of the same run included.
+    pub(super) completed: u32,
+    /// Pages that used up their retries.
+    pub(super) failed: u32,
     pub(super) total: i64,
 }
 
 impl FetchProgress {
+    fn new(request: &FetchRequest) -> Self {
+        Self {
+            start_page: request.start_page,
+            end_page: request.end_page,
+            run_id: None,
+            current_page: request.start_page,
+            completed: 0,
+            failed: 0,
+            total: 0,
+        }
+    }
+
     pub(super) fn completed_pages(&self) -> u32 {
-        self.last_success_page
-            .filter(|page| *page >= self.start_page)
-            .map(|page| page - self.start_page + 1)
-            .unwrap_or(0)
+        self.completed
+    }
+
+    pub(super) fn failed_pages(&self) -> u32 {
+        self.failed
+    }
+
+    pub(super) fn remaining_pages(&self) -> u32 {
+        self.page_count()
+            .saturating_sub(self.completed)
+            .saturating_sub(self.failed)
     }
 
     pub(super) fn page_count(&self) -> u32 {
@@ -52,9 +77,13 @@ impl FetchProgress {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct FetchFailure {
     pub(super) progress: FetchProgress,
+    /// The failed page shown, the last one when several failed.
     pub(super) page: u32,
     pub(super) kind: FetchErrorKind,
     pub(super) message: String,
+    /// Whether the error stopped the run, rather than the run finishing
+    /// with some pages failed.
+    pub(super) fatal: bool,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -75,6 +104,8 @@ pub(crate) enum FetchRun {
         snapshot: FetchRequest,
         progress: FetchProgress,
         logs: Vec<FetchPageLog>,
+        /// The latest page that used up its retries.
+        last_failure: Option<FetchPageError>,
         task: Task<()>,
     },
     Interrupted {
@@ -124,13 +155,8 @@ impl FetchRun {
             Self::Interrupted { progress, .. } => Some(format!(
                 "{} · {} {} · {} {}",
                 i18n.t("fetch-state-interrupted-title"),
-                i18n.t("fetch-stat-next-page"),
-                resume_page_after_interrupt(
-                    progress.last_success_page,
-                    progress.start_page,
-                    progress.end_page
-                )
-                .unwrap_or(progress.end_page),
+                i18n.t("fetch-stat-remaining-pages"),
+                progress.remaining_pages(),
                 i18n.t("fetch-stat-completed-pages"),
                 progress.completed_pages()
             )),
@@ -157,56 +183,66 @@ impl FetchRun {
             .unwrap_or_else(|| i18n.t("fetch-state-idle-title"))
     }
 
-    fn begin_run(&mut self, request: FetchRequest, total: i64, clear_logs: bool, task: Task<()>) {
-        let run_start_page = request.start_page;
-        let previous = std::mem::take(self);
-        let (snapshot, logs, last_success_page) = if clear_logs {
-            (request.clone(), Vec::new(), None)
-        } else {
-            match previous {
-                Self::Interrupted {
-                    snapshot,
-                    progress,
-                    logs,
-                }
-                | Self::Succeeded {
-                    snapshot,
-                    progress,
-                    logs,
-                } => (snapshot, logs, progress.last_success_page),
-                Self::Failed {
-                    snapshot,
-                    failure,
-                    logs,
-                } => (snapshot, logs, failure.progress.last_success_page),
-                Self::Idle => (request.clone(), Vec::new(), None),
-                Self::Running { .. } => unreachable!("running reentry is rejected by Transition"),
+    fn begin_run(&mut self, request: FetchRequest, clear_logs: bool, task: Task<()>) {
+        let logs = match std::mem::take(self) {
+            Self::Interrupted { logs, .. }
+            | Self::Failed { logs, .. }
+            | Self::Succeeded { logs, .. }
+                if !clear_logs =>
+            {
+                logs
             }
+            Self::Running { .. } => unreachable!("running reentry is rejected by Transition"),
+            _ => Vec::new(),
         };
         event!(
             Level::INFO,
-            start_page = snapshot.start_page,
-            end_page = snapshot.end_page,
-            run_start_page,
-            ?last_success_page,
-            total,
+            start_page = request.start_page,
+            end_page = request.end_page,
+            concurrency = request.concurrency,
+            requests_per_minute = request.requests_per_minute,
             clear_logs,
             "fetch run state started"
         );
         *self = Self::Running {
-            progress: FetchProgress {
-                start_page: snapshot.start_page,
-                end_page: snapshot.end_page,
-                current_page: run_start_page,
-                last_success_page,
-                total,
-            },
-            snapshot,
+            progress: FetchProgress::new(&request),
+            snapshot: request,
             logs,
+            last_failure: None,
             task,
         };
     }
 
+    fn open(
+        &mut self,
+        run_id: i32,
+        completed: u32,
+        failed: u32,
+        earlier_failure: Option<FetchPageError>,
+        total: i64,
+    ) {
+        event!(
+            Level::INFO,
+            run_id,
+            completed,
+            failed,
+            total,
+            "fetch run opened"
+        );
+        if let Self::Running {
+            progress,
+            last_failure,
+            ..
+        } = self
+        {
+            progress.run_id = Some(run_id);
+            progress.completed = completed;
+            progress.failed = failed;
+            progress.total = total;
+            *last_failure = earlier_failure;
+        }
+    }
+
     fn interrupt(&mut self) {
         event!(Level::INFO, "interrupting fetch run");
         if let Self::Running {
@@ -218,8 +254,9 @@ impl FetchRun {
         {
             event!(
                 Level::INFO,
-                current_page = progress.current_page,
-                last_success_page = ?progress.last_success_page,
+                run_id = ?progress.run_id,
+                completed = progress.completed,
+                failed = progress.failed,
                 total = progress.total,
                 "fetch run interrupted"
             );
@@ -255,8 +292,7 @@ impl FetchRun {
             "fetch page succeeded"
         );
         if let Self::Running { progress, .. } = self {
-            progress.current_page = page;
-            progress.last_success_page = Some(page);
+            progress.completed += 1;
             progress.total = total;
         }
         self.upsert_log(FetchPageLog {
@@ -268,6 +304,33 @@ impl FetchRun {
         });
     }
 
+    fn mark_page_failed(&mut self, error: FetchPageError, elapsed_ms: u128) {
+        event!(
+            Level::WARN,
+            page = error.page,
+            kind = %error.kind,
+            message = %error.message,
+            elapsed_ms,
+            "fetch page failed"
+        );
+        self.upsert_log(FetchPageLog {
+            page: error.page,
+            status: FetchPageLogStatus::Failed,
+            inserted: None,
+            elapsed_ms: Some(elapsed_ms),
+            message: error.message.clone(),
+        });
+        if let Self::Running {
+            progress,
+            last_failure,
+            ..
+        } = self
+        {
+            progress.failed += 1;
+            *last_failure = Some(error);
+        }
+    }
+
     fn mark_failed(&mut self, error: FetchPageError, elapsed_ms: Option<u128>) {
         event!(
             Level::ERROR,
@@ -319,19 +382,45 @@ impl FetchRun {
                 page: error.page,
                 kind: error.kind,
                 message: error.message,
+                fatal: true,
             },
             logs,
         };
     }
 
-    fn mark_succeeded(&mut self) {
-        if let Self::Running {
+    /// Ends a run that went through all its pages: failed if any page used
+    /// up its retries, succeeded otherwise.
+    fn finish(&mut self) {
+        let Self::Running {
             snapshot,
             progress,
             logs,
+            last_failure,
             ..
         } = std::mem::take(self)
-        {
+        else {
+            return;
+        };
+        if let Some(error) = last_failure.filter(|_| progress.failed > 0) {
+            event!(
+                Level::WARN,
+                run_id = ?progress.run_id,
+                completed = progress.completed,
+                failed = progress.failed,
+                "fetch run finished with failed pages"
+            );
+            *self = Self::Failed {
+                snapshot,
+                failure: FetchFailure {
+                    progress,
+                    page: error.page,
+                    kind: error.kind,
+                    message: error.message,
+                    fatal: false,
+                },
+                logs,
+            };
+        } else {
             event!(
                 Level::INFO,
                 start_page = progress.start_page,
@@ -358,23 +447,17 @@ impl FetchRun {
         }
     }
 
-    pub(super) fn last_success_page(&self) -> Option<u32> {
+    /// The stored run behind the current state, if it got that far.
+    pub(super) fn run_id(&self) -> Option<i32> {
         match self {
             Self::Running { progress, .. }
             | Self::Interrupted { progress, .. }
-            | Self::Succeeded { progress, .. } => progress.last_success_page,
-            Self::Failed { failure, .. } => failure.progress.last_success_page,
+            | Self::Succeeded { progress, .. } => progress.run_id,
+            Self::Failed { failure, .. } => failure.progress.run_id,
             Self::Idle => None,
         }
     }
 
-    pub(super) fn failed_page(&self) -> Option<u32> {
-        match self {
-            Self::Failed { failure, .. } => Some(failure.page),
-            _ => None,
-        }
-    }
-
     pub(super) fn snapshot(&self) -> Option<&FetchRequest> {
         match self {
             Self::Idle => None,
@@ -408,11 +491,8 @@ impl FetchRun {
     fn reject(&mut self, request: Option<FetchRequest>, error: FetchPageError) {
         if let Some(request) = request {
             let progress = FetchProgress {
-                start_page: request.start_page,
-                end_page: request.end_page,
                 current_page: error.page,
-                last_success_page: None,
-                total: 0,
+                ..FetchProgress::new(&request)
             };
             let mut logs = Vec::new();
             upsert_page_log(
@@ -432,6 +512,7 @@ impl FetchRun {
                     page: error.page,
                     kind: error.kind,
                     message: error.message,
+                    fatal: true,
                 },
                 logs,
             };
@@ -463,6 +544,15 @@ pub(super) enum FetchMessage {
         request: Option<FetchRequest>,
         error: FetchPageError,
     },
+    /// The runner has created or reopened the stored run.
+    Opened {
+        run_id: i32,
+        completed: u32,
+        /// Pages that failed in earlier sessions and aren't being retried.
+        failed: u32,
+        last_failure: Option<FetchPageError>,
+        total: i64,
+    },
     Interrupt,
     PageStarted(u32),
     PageSucceeded {
@@ -471,11 +561,18 @@ pub(super) enum FetchMessage {
         total: i64,
         elapsed_ms: u128,
     },
+    /// A page used up its retries; the run goes on with the others.
+    PageFailed {
+        error: FetchPageError,
+        elapsed_ms: u128,
+    },
+    /// An error that stops the whole run.
     Failed {
         error: FetchPageError,
         elapsed_ms: Option<u128>,
     },
-    Succeeded,
+    /// Every page has been tried.
+    Finished,
 }
 
 impl Transition<FetchMessage> for &mut FetchRun {
@@ -488,8 +585,15 @@ impl Transition<FetchMessage> for &mut FetchRun {
                 clear_logs,
                 task,
             } if !self.is_running() => {
-                self.begin_run(request, 0, clear_logs, task);
+                self.begin_run(request, clear_logs, task);
             }
+            FetchMessage::Opened {
+                run_id,
+                completed,
+                failed,
+                last_failure,
+                total,
+            } if self.is_running() => self.open(run_id, completed, failed, last_failure, total),
             FetchMessage::Interrupt if self.is_running() => self.interrupt(),
             FetchMessage::Rejected { request, error } if !self.is_running() => {
                 self.reject(request, error);
@@ -501,10 +605,13 @@ impl Transition<FetchMessage> for &mut FetchRun {
                 total,
                 elapsed_ms,
             } if self.is_running() => self.mark_page_succeeded(page, inserted, total, elapsed_ms),
+            FetchMessage::PageFailed { error, elapsed_ms } if self.is_running() => {
+                self.mark_page_failed(error, elapsed_ms)
+            }
             FetchMessage::Failed { error, elapsed_ms } if self.is_running() => {
                 self.mark_failed(error, elapsed_ms)
             }
-            FetchMessage::Succeeded if self.is_running() => self.mark_succeeded(),
+            FetchMessage::Finished if self.is_running() => self.finish(),
             _ => tracing::debug!("ignored fetch transition"),
         }
     }
@@ -514,27 +621,6 @@ pub(super) fn page_count(start_page: u32, end_page: u32) -> u32 {
     end_page.saturating_sub(start_page).saturating_add(1)
 }
 
-pub(super) fn resume_page_after_interrupt(
-    last_success_page: Option<u32>,
-    start_page: u32,
-    end_page: u32,
-) -> Option<u32> {
-    let next_page = last_success_page
-        .map(|page| page.saturating_add(1))
-        .unwrap_or(start_page);
-    (next_page <= end_page).then_some(next_page.max(start_page))
-}
-
-pub(super) fn retry_page_after_failure(
-    failed_page: Option<u32>,
-    start_page: u32,
-    end_page: u32,
-) -> Option<u32> {
-    failed_page
-        .filter(|page| *page >= start_page && *page <= end_page)
-        .or(Some(start_page).filter(|page| *page <= end_page))
-}
-
 #[cfg(test)]
 mod tests {
     use gpui::{AppContext as _, Task, TestAppContext};
@@ -550,15 +636,59 @@ mod tests {
             start_page: 1,
             end_page: 10,
             cookie: String::new(),
+            concurrency: 3,
+            requests_per_minute: 30,
+        }
+    }
+
+    fn page_error(page: u32) -> FetchPageError {
+        FetchPageError {
+            page,
+            kind: FetchErrorKind::Network,
+            message: "timed out".to_owned(),
         }
     }
 
     #[test]
-    fn resumes_and_retries_use_the_frozen_range() {
-        assert_eq!(resume_page_after_interrupt(Some(3), 1, 10), Some(4));
-        assert_eq!(resume_page_after_interrupt(Some(10), 1, 10), None);
-        assert_eq!(retry_page_after_failure(Some(6), 1, 10), Some(6));
-        assert_eq!(retry_page_after_failure(Some(20), 1, 10), Some(1));
+    fn failed_pages_let_the_run_go_on_and_end_it_failed() {
+        let mut run = FetchRun::default();
+        run.transition(FetchMessage::Start {
+            request: request("test"),
+            clear_logs: true,
+            task: Task::ready(()),
+        });
+        run.transition(FetchMessage::Opened {
+            run_id: 4,
+            completed: 5,
+            failed: 0,
+            last_failure: None,
+            total: 100,
+        });
+        run.transition(FetchMessage::PageFailed {
+            error: page_error(7),
+            elapsed_ms: 10,
+        });
+        run.transition(FetchMessage::PageSucceeded {
+            page: 6,
+            inserted: 20,
+            total: 120,
+            elapsed_ms: 10,
+        });
+        let FetchStatus::Running(progress) = run.status() else {
+            panic!("a failed page should not stop the run");
+        };
+        assert_eq!(
+            (progress.completed_pages(), progress.failed_pages()),
+            (6, 1)
+        );
+        assert_eq!(progress.remaining_pages(), 3);
+
+        run.transition(FetchMessage::Finished);
+        let FetchStatus::Failed(failure) = run.status() else {
+            panic!("a run with failed pages should end failed");
+        };
+        assert_eq!((failure.page, failure.fatal), (7, false));
+        assert_eq!(run.run_id(), Some(4));
     }
 
     #[test]
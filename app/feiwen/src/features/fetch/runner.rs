@@ -1,19 +1,49 @@
-use std::time::Instant;
+use std::{
+    future::{Future as _, poll_fn},
+    pin::Pin,
+    sync::Arc,
+    task::Poll,
+    time::{Duration, Instant},
+};
 
+use async_compat::Compat;
 use duckdb::DuckdbConnectionManager;
-use gpui::{AsyncApp, WeakEntity};
+use gpui::{AsyncApp, Task, WeakEntity};
 use gpui_operation::Transition;
 use r2d2::PooledConnection;
 use reqwest::Client;
 use tracing::{Level, event};
 
 use crate::{
-    fetch::{self, FetchPageError},
-    store::service::Novel,
+    errors::FeiwenResult,
+    fetch::{self, FetchPageError, limiter::HostRateLimiter},
+    store::{
+        fetch_run::{FetchRunPlan, FetchRunRecord, FetchRunStatus, PageOutcome},
+        history,
+        service::Novel,
+    },
 };
 
 use super::{FetchView, form::FetchRequest, run::FetchMessage};
 
+/// Which pages of which stored run to fetch.
+pub(super) enum RunPages {
+    /// A new run over the request's whole range.
+    Fresh,
+    /// The pages of an earlier run that never finished.
+    Resume(FetchRunRecord),
+    /// Only the pages an earlier run gave up on.
+    RetryFailed(FetchRunRecord),
+}
+
+/// A page's fetch after all its tries.
+struct PageFetch {
+    page: u32,
+    attempts: u32,
+    elapsed: Duration,
+    result: Result<Vec<Novel>, FetchPageError>,
+}
+
 pub(super) struct Runner<'a> {
     request: FetchRequest,
     owner: WeakEntity<FetchView>,
@@ -36,105 +66,259 @@ impl Runner<'_> {
         }
     }
 
-    pub(super) async fn run(&mut self) {
+    pub(super) async fn run(&mut self, pages: RunPages) {
         event!(
             Level::INFO,
             start_page = self.request.start_page,
             end_page = self.request.end_page,
+            concurrency = self.request.concurrency,
+            requests_per_minute = self.request.requests_per_minute,
             has_cookie = !self.request.cookie.is_empty(),
             "fetch runner started"
         );
-        let mut total = match Novel::count(&self.conn) {
-            Ok(total) => total,
+        let (run_id, pages, mut total) = match self.open(pages) {
+            Ok(opened) => opened,
             Err(err) => {
-                event!(
-                    Level::ERROR,
-                    error = %err,
-                    "failed to count novels before fetch run"
-                );
+                event!(Level::ERROR, error = %err, "failed to open fetch run");
                 self.mark_failed(FetchPageError::new(self.request.start_page, err), None);
                 return;
             }
         };
-        event!(Level::INFO, total, "initial novel count loaded");
 
+        // One stamp for the whole run, so the snapshots it records line up.
+        let fetched_at = history::unix_now();
         let client = Client::new();
-        for page in self.request.start_page..=self.request.end_page {
-            self.update_state(FetchMessage::PageStarted(page));
-            let started_at = Instant::now();
-            let novels =
-                match fetch::fetch_page(&self.request.url, page, &self.request.cookie, &client)
-                    .await
-                {
-                    Ok(novels) => novels,
-                    Err(err) => {
-                        event!(
-                            Level::ERROR,
-                            page,
-                            error = %err,
-                            elapsed_ms = started_at.elapsed().as_millis(),
-                            "failed to fetch page data"
-                        );
-                        self.mark_failed(
-                            FetchPageError::new(page, err),
-                            Some(started_at.elapsed().as_millis()),
-                        );
-                        return;
-                    }
+        let limiter = Arc::new(HostRateLimiter::per_minute(
+            self.request.requests_per_minute,
+        ));
+        let concurrency = self.request.concurrency.max(1) as usize;
+        let mut pending = pages.into_iter();
+        let mut in_flight = Vec::with_capacity(concurrency);
+        loop {
+            while in_flight.len() < concurrency {
+                let Some(page) = pending.next() else {
+                    break;
                 };
-
-            let inserted = novels.len();
-            for novel in novels {
-                if let Err(err) = novel.save(&mut self.conn) {
-                    event!(
-                        Level::ERROR,
-                        page,
-                        error = %err,
-                        elapsed_ms = started_at.elapsed().as_millis(),
-                        "failed to save fetched novel"
-                    );
-                    self.mark_failed(
-                        FetchPageError::new(page, err),
-                        Some(started_at.elapsed().as_millis()),
-                    );
-                    return;
-                }
-                self.cx.update(crate::store::catalog::invalidate);
+                self.update_state(FetchMessage::PageStarted(page));
+                in_flight.push(self.spawn_page(page, &client, &limiter));
             }
-
-            match Novel::count(&self.conn) {
-                Ok(next_total) => total = next_total,
-                Err(err) => {
-                    event!(
-                        Level::ERROR,
-                        page,
-                        error = %err,
-                        elapsed_ms = started_at.elapsed().as_millis(),
-                        "failed to count novels after page fetch"
-                    );
-                    self.mark_failed(
-                        FetchPageError::new(page, err),
-                        Some(started_at.elapsed().as_millis()),
-                    );
-                    return;
+            if in_flight.is_empty() {
+                break;
+            }
+            let fetch = first_finished(&mut in_flight).await;
+            let elapsed_ms = fetch.elapsed.as_millis();
+            let fatal = match fetch.result {
+                Ok(novels) => {
+                    match self.save_page(run_id, fetch.page, fetch.attempts, novels, fetched_at) {
+                        Ok((inserted, next_total)) => {
+                            total = next_total;
+                            self.update_state(FetchMessage::PageSucceeded {
+                                page: fetch.page,
+                                inserted,
+                                total,
+                                elapsed_ms,
+                            });
+                            None
+                        }
+                        Err(error) => Some(error),
+                    }
                 }
+                Err(error) if !error.kind.is_fatal() => self
+                    .record_failed_page(run_id, fetch.attempts, error, elapsed_ms)
+                    .err(),
+                Err(error) => Some(error),
+            };
+            if let Some(error) = fatal {
+                self.set_status(run_id, FetchRunStatus::Failed);
+                self.mark_failed(error, Some(elapsed_ms));
+                return;
             }
-            self.update_state(FetchMessage::PageSucceeded {
-                page,
-                inserted,
-                total,
-                elapsed_ms: started_at.elapsed().as_millis(),
-            });
         }
 
+        let failed = FetchRunRecord::load(&self.conn, run_id)
+            .map(|run| !run.failed_pages.is_empty())
+            .unwrap_or(true);
+        self.set_status(
+            run_id,
+            if failed {
+                FetchRunStatus::Failed
+            } else {
+                FetchRunStatus::Succeeded
+            },
+        );
         event!(
             Level::INFO,
+            run_id,
             start_page = self.request.start_page,
             end_page = self.request.end_page,
             total,
+            failed,
             "fetch runner completed"
         );
-        self.update_state(FetchMessage::Succeeded);
+        self.update_state(FetchMessage::Finished);
+    }
+
+    /// Creates or reopens the stored run and works out the pages to fetch.
+    fn open(&mut self, pages: RunPages) -> FeiwenResult<(i32, Vec<u32>, i64)> {
+        let now = history::unix_now();
+        let (run_id, pages, completed, earlier_failures) = match pages {
+            RunPages::Fresh => {
+                let run_id =
+                    FetchRunRecord::create(&self.conn, &FetchRunPlan::from(&self.request), now)?;
+                let pages = (self.request.start_page..=self.request.end_page).collect();
+                (run_id, pages, 0, Vec::new())
+            }
+            RunPages::Resume(run) => {
+                let pages = run.remaining_pages(&self.conn)?;
+                (run.id, pages, run.done_pages, run.failed_pages)
+            }
+            RunPages::RetryFailed(run) => {
+                let pages = run.failed_pages.iter().map(|failed| failed.page).collect();
+                (run.id, pages, run.done_pages, Vec::new())
+            }
+        };
+        FetchRunRecord::set_status(&self.conn, run_id, FetchRunStatus::Running, now)?;
+        let total = Novel::count(&self.conn)?;
+        event!(
+            Level::INFO,
+            run_id,
+            pages = pages.len(),
+            completed,
+            total,
+            "fetch run opened"
+        );
+        self.update_state(FetchMessage::Opened {
+            run_id,
+            completed,
+            failed: earlier_failures.len() as u32,
+            last_failure: earlier_failures.last().map(|failed| FetchPageError {
+                page: failed.page,
+                kind: failed.kind,
+                message: failed.message.clone(),
+            }),
+            total,
+        });
+        Ok((run_id, pages, total))
+    }
+
+    /// Fetches `page` in the background, waiting for the host's rate limit
+    /// before each try and backing off between tries.
+    fn spawn_page(
+        &mut self,
+        page: u32,
+        client: &Client,
+        limiter: &Arc<HostRateLimiter>,
+    ) -> Task<PageFetch> {
+        let executor = self.cx.background_executor().clone();
+        let (url, cookie) = (self.request.url.clone(), self.request.cookie.clone());
+        let (client, limiter) = (client.clone(), limiter.clone());
+        self.cx.background_spawn(Compat::new(async move {
+            let started_at = Instant::now();
+            let mut attempts = 0;
+            loop {
+                attempts += 1;
+                let wait = limiter.reserve(&url);
+                if !wait.is_zero() {
+                    executor.timer(wait).await;
+                }
+                let error = match fetch::fetch_page(&url, page, &cookie, &client).await {
+                    Ok(novels) => {
+                        return PageFetch {
+                            page,
+                            attempts,
+                            elapsed: started_at.elapsed(),
+                            result: Ok(novels),
+                        };
+                    }
+                    Err(err) => FetchPageError::new(page, err),
+                };
+                let Some(backoff) = error.kind.retry_policy().backoff(attempts) else {
+                    return PageFetch {
+                        page,
+                        attempts,
+                        elapsed: started_at.elapsed(),
+                        result: Err(error),
+                    };
+                };
+                event!(
+                    Level::WARN,
+                    page,
+                    attempts,
+                    kind = %error.kind,
+                    message = %error.message,
+                    backoff_ms = backoff.as_millis(),
+                    "retrying fetch page"
+                );
+                executor.timer(backoff).await;
+            }
+        }))
+    }
+
+    /// Saves a fetched page and returns how many novels it had and the new
+    /// novel count.
+    fn save_page(
+        &mut self,
+        run_id: i32,
+        page: u32,
+        attempts: u32,
+        novels: Vec<Novel>,
+        fetched_at: i64,
+    ) -> Result<(usize, i64), FetchPageError> {
+        let inserted = novels.len();
+        for novel in novels {
+            novel
+                .save(&mut self.conn, fetched_at)
+                .map_err(|err| FetchPageError::new(page, err))?;
+            self.cx.update(crate::store::catalog::invalidate);
+        }
+        FetchRunRecord::record_page(
+            &self.conn,
+            run_id,
+            page,
+            attempts,
+            PageOutcome::Succeeded { inserted },
+            history::unix_now(),
+        )
+        .map_err(|err| FetchPageError::new(page, err))?;
+        let total = Novel::count(&self.conn).map_err(|err| FetchPageError::new(page, err))?;
+        Ok((inserted, total))
+    }
+
+    fn record_failed_page(
+        &mut self,
+        run_id: i32,
+        attempts: u32,
+        error: FetchPageError,
+        elapsed_ms: u128,
+    ) -> Result<(), FetchPageError> {
+        FetchRunRecord::record_page(
+            &self.conn,
+            run_id,
+            error.page,
+            attempts,
+            PageOutcome::Failed {
+                kind: error.kind,
+                message: &error.message,
+            },
+            history::unix_now(),
+        )
+        .map_err(|err| FetchPageError::new(error.page, err))?;
+        self.update_state(FetchMessage::PageFailed { error, elapsed_ms });
+        Ok(())
+    }
+
+    fn set_status(&mut self, run_id: i32, status: FetchRunStatus) {
+        if let Err(err) =
+            FetchRunRecord::set_status(&self.conn, run_id, status, history::unix_now())
+        {
+            event!(
+                Level::ERROR,
+                run_id,
+                ?status,
+                error = %err,
+                "failed to store fetch run status"
+            );
+        }
     }
 
     fn mark_failed(&mut self, error: FetchPageError, elapsed_ms: Option<u128>) {
@@ -155,3 +339,18 @@ impl Runner<'_> {
         });
     }
 }
+
+/// Waits for whichever page task finishes first and takes it out of
+/// `tasks`, which must not be empty.
+async fn first_finished(tasks: &mut Vec<Task<PageFetch>>) -> PageFetch {
+    poll_fn(|cx| {
+        for index in 0..tasks.len() {
+            if let Poll::Ready(fetch) = Pin::new(&mut tasks[index]).poll(cx) {
+                tasks.swap_remove(index);
+                return Poll::Ready(fetch);
+            }
+        }
+        Poll::Pending
+    })
+    .await
+}
@@ -2,30 +2,41 @@ use super::fetch::FetchRun;
 use crate::app::{RouterType, Workspace, WorkspaceEvent};
 use crate::{
     foundation::I18n,
-    store::{catalog, database, service::Novel},
+    store::{catalog, database, query::QuerySpec, service::Novel},
 };
 use advanced::{AdvancedQueryController, QueryDraft};
+use analytics::TagAnalyticsPanel;
+use detail::NovelDetail;
+use export::ResultExport;
 use fluent_bundle::FluentArgs;
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::{
-    ActiveTheme, Disableable,
+    ActiveTheme, Disableable, Sizable,
     alert::Alert,
-    button::Button,
+    button::{Button, ButtonVariants},
+    h_flex,
     label::Label,
     resizable::{h_resizable, resizable_panel, v_resizable},
-    table::{DataTable, TableState},
+    table::{DataTable, TableEvent, TableState},
     v_flex,
 };
 use gpui_operation::Transition;
 use gpui_store::Store;
 use results_table::ResultsTableDelegate;
+use saved::SavedQueries;
 use std::time::Instant;
+use text::QueryTextBar;
 use tracing::{Level, event};
 
 pub(crate) mod advanced;
+mod analytics;
+mod detail;
+mod export;
 mod form;
 mod results_table;
+mod saved;
+mod text;
 
 #[derive(Default)]
 enum QueryRun {
@@ -44,6 +55,14 @@ enum QueryRun {
     },
 }
 
+/// What the panel under the results table shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum SidePanel {
+    #[default]
+    Detail,
+    Analytics,
+}
+
 impl QueryRun {
     fn is_searching(&self) -> bool {
         matches!(self, Self::Running { .. })
@@ -130,6 +149,14 @@ pub(crate) struct QueryView {
     fetch_task: Store<FetchRun>,
     advanced: AdvancedQueryController,
     results_table: Entity<TableState<ResultsTableDelegate>>,
+    detail: NovelDetail,
+    analytics: TagAnalyticsPanel,
+    side_panel: SidePanel,
+    export: ResultExport,
+    /// The query behind the rows in the results table, for exporting them.
+    results_spec: Option<QuerySpec>,
+    saved: SavedQueries,
+    query_text: QueryTextBar,
     search: QueryRun,
     _subscriptions: Vec<Subscription>,
 }
@@ -141,7 +168,7 @@ impl QueryView {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
-        let _subscriptions = vec![
+        let mut _subscriptions = vec![
             fetch_task.observe(cx, |_, _, cx| cx.notify()),
             catalog::store(cx).observe_select_in(
                 cx,
@@ -157,7 +184,10 @@ impl QueryView {
                 |state: &catalog::QueryCatalogState| state.operation.data().cloned(),
                 |view, options, window, cx| {
                     if let Some(options) = options.clone() {
+                        view.query_text.update_options(options.clone());
                         view.advanced.update_options(options, window, cx);
+                        // A reloaded catalog means the novels changed.
+                        view.saved.reload(cx);
                     }
                     cx.notify();
                 },
@@ -165,16 +195,52 @@ impl QueryView {
         ];
         let options = catalog::data(cx).unwrap_or_default();
         let search = QueryRun::Idle;
+        let (query_text, query_text_subscription) = QueryTextBar::new(options.clone(), window, cx);
+        _subscriptions.push(query_text_subscription);
+        let mut saved = SavedQueries::new(window, cx);
+        if database::is_ready(cx) {
+            saved.reload(cx);
+        }
+        let results_table = cx.new(|cx| {
+            TableState::new(ResultsTableDelegate::new(), window, cx)
+                .col_resizable(true)
+                .col_movable(true)
+                .row_selectable(true)
+        });
+        _subscriptions.push(cx.subscribe_in(
+            &results_table,
+            window,
+            |view, table, event: &TableEvent, _, cx| match event {
+                TableEvent::SelectRow(row) => {
+                    let novel = table.read(cx).delegate().novel_at(*row).cloned();
+                    match novel {
+                        Some(novel) => {
+                            view.side_panel = SidePanel::Detail;
+                            view.detail.show(novel, cx);
+                        }
+                        None => view.detail.clear(),
+                    }
+                    cx.notify();
+                }
+                TableEvent::ClearSelection => {
+                    view.detail.clear();
+                    cx.notify();
+                }
+                _ => {}
+            },
+        ));
         Self {
             workspace,
             fetch_task,
             advanced: AdvancedQueryController::new(options, window, cx),
-            results_table: cx.new(|cx| {
-                TableState::new(ResultsTableDelegate::new(), window, cx)
-                    .col_resizable(true)
-                    .col_movable(true)
-                    .row_selectable(true)
-            }),
+            results_table,
+            detail: NovelDetail::new(),
+            analytics: TagAnalyticsPanel::new(),
+            side_panel: SidePanel::default(),
+            export: ResultExport::new(),
+            results_spec: None,
+            saved,
+            query_text,
             search,
             _subscriptions,
         }
@@ -219,6 +285,7 @@ impl Render for QueryView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let error_label = cx.global::<I18n>().t("query-error-title");
         let catalog_disabled = catalog::phase(cx) != gpui_operation::refresh::Phase::Ready;
+        let can_search = self.can_search(cx);
         div()
             .flex_1()
             .p_2()
@@ -233,8 +300,16 @@ impl Render for QueryView {
                 this.child(status)
             })
             .child(self.render_status(error_label, cx))
+            .child(self.query_text.render(can_search, cx))
             .child(
                 h_resizable("query-main")
+                    .child(
+                        resizable_panel()
+                            .size(px(240.))
+                            .size_range(px(180.)..px(420.))
+                            .flex_none()
+                            .child(self.saved.render(self.export.is_busy(), cx)),
+                    )
                     .child(
                         resizable_panel()
                             .size(px(560.))
@@ -250,7 +325,13 @@ impl Render for QueryView {
                                     .size_range(px(150.)..px(420.))
                                     .child(self.advanced.render_sorts(cx)),
                             )
-                            .child(resizable_panel().child(self.render_results_table(cx))),
+                            .child(resizable_panel().child(self.render_results_table(cx)))
+                            .child(
+                                resizable_panel()
+                                    .size(px(200.))
+                                    .size_range(px(120.)..px(640.))
+                                    .child(self.render_side_panel(cx)),
+                            ),
                     ),
             )
     }
@@ -335,10 +416,12 @@ impl QueryView {
                 this.finish_search(result, window, cx)
             });
         });
+        let results_spec = spec.clone();
         let effect = self
             .search
             .transition(QueryMessage::Start { snapshot, task });
         self.apply_query_effect(effect, cx);
+        self.results_spec = Some(results_spec);
         self.results_table.update(cx, |table, cx| {
             table.delegate_mut().set_loading(true);
             table.refresh(cx);
@@ -347,6 +430,84 @@ impl QueryView {
         cx.notify();
     }
 
+    /// The builder's current query, or `None` after logging why it does
+    /// not validate or compile.
+    fn current_spec(&mut self, cx: &mut Context<Self>) -> Option<QuerySpec> {
+        let prepared = match self.advanced.prepare(cx) {
+            Ok(prepared) => prepared,
+            Err(err) => {
+                event!(Level::ERROR, error = %err, "query validation failed");
+                cx.notify();
+                return None;
+            }
+        };
+        match prepared.into_parts().1.to_spec() {
+            Ok(spec) => Some(spec),
+            Err(err) => {
+                event!(Level::ERROR, error = %err, "query compilation failed");
+                cx.notify();
+                None
+            }
+        }
+    }
+
+    fn save_query(&mut self, cx: &mut Context<Self>) {
+        if let Some(spec) = self.current_spec(cx) {
+            self.saved.save_new(spec, cx);
+        }
+    }
+
+    fn overwrite_saved_query(&mut self, cx: &mut Context<Self>) {
+        if let Some(spec) = self.current_spec(cx) {
+            self.saved.overwrite_selected(spec, cx);
+        }
+    }
+
+    /// Loads a saved query into the builder and searches with it.
+    fn run_saved_query(&mut self, id: i32, window: &mut Window, cx: &mut Context<Self>) {
+        if self.is_searching() {
+            return;
+        }
+        let Some(spec) = self.saved.select(id, window, cx) else {
+            return;
+        };
+        self.advanced
+            .load_draft(QueryDraft::from_spec(&spec), window, cx);
+        self.start_search(cx);
+    }
+
+    /// Loads the query bar's text into the builder and searches with it.
+    fn apply_query_text(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.can_search(cx) {
+            return;
+        }
+        let Some(spec) = self.query_text.parse(cx) else {
+            cx.notify();
+            return;
+        };
+        self.advanced
+            .load_draft(QueryDraft::from_spec(&spec), window, cx);
+        self.start_search(cx);
+    }
+
+    fn export_results(&mut self, cx: &mut Context<Self>) {
+        if let Some(spec) = self.results_spec.clone() {
+            self.export.start(spec, "feiwen-results", cx);
+        }
+    }
+
+    fn export_saved_query(&mut self, cx: &mut Context<Self>) {
+        if let Some((name, spec)) = self.saved.selected_query() {
+            self.export.start(spec, &name, cx);
+        }
+    }
+
+    fn show_query_text(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(spec) = self.current_spec(cx) {
+            self.query_text.show(&spec, window, cx);
+        }
+    }
+
     fn finish_search(
         &mut self,
         result: Result<SearchResult, QueryProblem>,
@@ -364,11 +525,16 @@ impl QueryView {
     fn apply_query_effect(&mut self, effect: QueryEffect, cx: &mut Context<Self>) {
         match effect {
             QueryEffect::None => {}
-            QueryEffect::ClearResults => self.set_results_table(Vec::new(), false, cx),
+            QueryEffect::ClearResults => {
+                self.results_spec = None;
+                self.analytics.clear();
+                self.set_results_table(Vec::new(), false, cx);
+            }
             QueryEffect::ShowResults(novels) => {
                 let count = novels.len();
                 let table_started_at = Instant::now();
                 self.set_results_table(novels, false, cx);
+                self.refresh_analytics(cx);
                 event!(
                     Level::INFO,
                     result_count = count,
@@ -380,8 +546,15 @@ impl QueryView {
     }
 
     fn set_results_table(&mut self, novels: Vec<Novel>, loading: bool, cx: &mut Context<Self>) {
+        self.detail.clear();
+        let search_terms = self
+            .results_spec
+            .as_ref()
+            .map(QuerySpec::search_terms)
+            .unwrap_or_default();
         self.results_table.update(cx, |table, cx| {
             table.delegate_mut().set_novels(novels);
+            table.delegate_mut().set_search_terms(search_terms);
             table.delegate_mut().set_loading(loading);
             table.refresh(cx);
             cx.notify();
@@ -419,12 +592,72 @@ impl QueryView {
         cx.notify();
     }
 
-    fn render_results_table(&self, _cx: &mut Context<Self>) -> impl IntoElement {
+    fn render_results_table(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let has_results = matches!(self.search, QueryRun::Succeeded { .. });
         v_flex()
             .size_full()
+            .child(self.export.render(has_results, cx))
             .child(DataTable::new(&self.results_table))
     }
 
+    fn show_side_panel(&mut self, panel: SidePanel, cx: &mut Context<Self>) {
+        self.side_panel = panel;
+        self.refresh_analytics(cx);
+        cx.notify();
+    }
+
+    /// Loads the tag analytics for the current results while they are on
+    /// screen.
+    fn refresh_analytics(&mut self, cx: &mut Context<Self>) {
+        if self.side_panel != SidePanel::Analytics
+            || !matches!(self.search, QueryRun::Succeeded { .. })
+        {
+            return;
+        }
+        if let Some(spec) = &self.results_spec {
+            let filter = spec.filter.clone();
+            self.analytics.show(&filter, cx);
+        }
+    }
+
+    fn render_side_panel(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let i18n = cx.global::<I18n>();
+        let tabs = [
+            (SidePanel::Detail, i18n.t("query-side-detail")),
+            (SidePanel::Analytics, i18n.t("query-side-analytics")),
+        ];
+        v_flex()
+            .size_full()
+            .child(
+                h_flex()
+                    .gap_1()
+                    .px_2()
+                    .pt_1()
+                    .children(tabs.into_iter().enumerate().map(|(index, (panel, label))| {
+                        Button::new(ElementId::NamedInteger(
+                            "query-side-panel".into(),
+                            index as u64,
+                        ))
+                        .small()
+                        .label(label)
+                        .map(|button| {
+                            if panel == self.side_panel {
+                                button.primary()
+                            } else {
+                                button.ghost()
+                            }
+                        })
+                        .on_click(
+                            cx.listener(move |this, _, _, cx| this.show_side_panel(panel, cx)),
+                        )
+                    })),
+            )
+            .child(div().flex_1().min_h_0().map(|this| match self.side_panel {
+                SidePanel::Detail => this.child(self.detail.render(cx)),
+                SidePanel::Analytics => this.child(self.analytics.render(cx)),
+            }))
+    }
+
     fn render_fetch_summary(&self, cx: &mut Context<Self>) -> Option<Div> {
         let i18n = cx.global::<I18n>();
         let summary = self.fetch_task.read(cx, |task| {
@@ -2,7 +2,9 @@ use crate::{
     errors::FeiwenResult,
     foundation::field_matches_query,
     store::{
-        query::{AuthorRef, BoolField, NumberField, SortExpr, TextField},
+        annotation::ReadStatus,
+        query::{AuthorRef, BoolField, NumberField, SortExpr, TextField, TrendMetric, TrendWindow},
+        query_language::{AuthorNames, Completion, CompletionKind, quote_value},
         service::Tag,
     },
 };
@@ -13,6 +15,8 @@ use std::collections::HashMap;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum FieldKind {
+    /// Full-text search over title, description, latest chapter and author.
+    FullText,
     Title,
     Description,
     LatestChapterTitle,
@@ -22,6 +26,12 @@ pub(crate) enum FieldKind {
     ReadCount,
     ReplyCount,
     IsLimit,
+    Growth(TrendMetric, TrendWindow),
+    Updated(TrendWindow),
+    ReadStatus(ReadStatus),
+    Rating,
+    Note,
+    Hidden,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -88,8 +98,23 @@ pub(crate) enum SortField {
     ReplyCount,
     AuthorId,
     IsLimit,
+    Growth(TrendMetric, TrendWindow),
+    ReadStatus,
+    Rating,
+    Relevance,
 }
 
+/// The windows the builder offers for growth and update conditions. The
+/// query language accepts any number of days.
+const TREND_WINDOWS: [TrendWindow; 3] = [
+    TrendWindow::LastFetch,
+    TrendWindow::Days(7),
+    TrendWindow::Days(30),
+];
+
+const TREND_METRICS: [TrendMetric; 3] =
+    [TrendMetric::Words, TrendMetric::Reads, TrendMetric::Replies];
+
 #[derive(Clone)]
 pub(super) struct SelectChoice<T: Copy + Eq + 'static> {
     label: &'static str,
@@ -130,6 +155,7 @@ impl FieldKind {
             Self::Title => Some(TextField::Title),
             Self::Description => Some(TextField::Description),
             Self::LatestChapterTitle => Some(TextField::LatestChapter),
+            Self::Note => Some(TextField::Note),
             _ => None,
         }
     }
@@ -139,6 +165,15 @@ impl FieldKind {
             Self::WordCount => Some(NumberField::WordCount),
             Self::ReadCount => Some(NumberField::ReadCount),
             Self::ReplyCount => Some(NumberField::ReplyCount),
+            Self::Rating => Some(NumberField::Rating),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn bool_field(self) -> Option<BoolField> {
+        match self {
+            Self::IsLimit => Some(BoolField::IsLimit),
+            Self::Hidden => Some(BoolField::Hidden),
             _ => None,
         }
     }
@@ -163,6 +198,10 @@ impl SortField {
             Self::ReplyCount => "回复数",
             Self::AuthorId => "作者 ID",
             Self::IsLimit => "是否受限",
+            Self::Growth(metric, window) => growth_label(metric, window),
+            Self::ReadStatus => "阅读状态",
+            Self::Rating => "评分",
+            Self::Relevance => "相关度",
         }
     }
 
@@ -178,10 +217,60 @@ impl SortField {
             Self::ReplyCount => SortExpr::Number(NumberField::ReplyCount),
             Self::AuthorId => SortExpr::Number(NumberField::AuthorId),
             Self::IsLimit => SortExpr::Bool(BoolField::IsLimit),
+            Self::Growth(metric, window) => SortExpr::Growth { metric, window },
+            Self::ReadStatus => SortExpr::ReadStatus,
+            Self::Rating => SortExpr::Number(NumberField::Rating),
+            Self::Relevance => SortExpr::Relevance,
         }
     }
 }
 
+fn growth_label(metric: TrendMetric, window: TrendWindow) -> &'static str {
+    match (metric, window) {
+        (TrendMetric::Words, TrendWindow::LastFetch) => "字数增长（自上次抓取）",
+        (TrendMetric::Words, TrendWindow::Days(7)) => "字数增长（近 7 天）",
+        (TrendMetric::Words, TrendWindow::Days(30)) => "字数增长（近 30 天）",
+        (TrendMetric::Words, TrendWindow::Days(_)) => "字数增长",
+        (TrendMetric::Reads, TrendWindow::LastFetch) => "阅读数增长（自上次抓取）",
+        (TrendMetric::Reads, TrendWindow::Days(7)) => "阅读数增长（近 7 天）",
+        (TrendMetric::Reads, TrendWindow::Days(30)) => "阅读数增长（近 30 天）",
+        (TrendMetric::Reads, TrendWindow::Days(_)) => "阅读数增长",
+        (TrendMetric::Replies, TrendWindow::LastFetch) => "回复数增长（自上次抓取）",
+        (TrendMetric::Replies, TrendWindow::Days(7)) => "回复数增长（近 7 天）",
+        (TrendMetric::Replies, TrendWindow::Days(30)) => "回复数增长（近 30 天）",
+        (TrendMetric::Replies, TrendWindow::Days(_)) => "回复数增长",
+    }
+}
+
+fn updated_label(window: TrendWindow) -> &'static str {
+    match window {
+        TrendWindow::LastFetch => "自上次抓取有更新",
+        TrendWindow::Days(7) => "近 7 天有更新",
+        TrendWindow::Days(30) => "近 30 天有更新",
+        TrendWindow::Days(_) => "有更新",
+    }
+}
+
+fn status_label(status: ReadStatus) -> &'static str {
+    match status {
+        ReadStatus::Unread => "阅读状态为未读",
+        ReadStatus::Reading => "阅读状态为在读",
+        ReadStatus::Read => "阅读状态为已读",
+        ReadStatus::Dropped => "阅读状态为弃文",
+    }
+}
+
+fn growth_choices<T: Copy + Eq + 'static>(
+    value: impl Fn(TrendMetric, TrendWindow) -> T,
+) -> impl Iterator<Item = SelectChoice<T>> {
+    TREND_METRICS.into_iter().flat_map(move |metric| {
+        TREND_WINDOWS
+            .into_iter()
+            .map(|window| SelectChoice::new(growth_label(metric, window), value(metric, window)))
+            .collect::<Vec<_>>()
+    })
+}
+
 #[derive(Clone, Default, PartialEq, Eq)]
 pub(crate) struct QueryOptions {
     pub(super) tags: Vec<TagOption>,
@@ -253,6 +342,41 @@ impl QueryOptions {
         }
         items
     }
+
+    pub(crate) fn author_names(&self) -> AuthorNames {
+        AuthorNames::new(
+            self.authors
+                .iter()
+                .map(|option| (&option.author, option.name.as_str())),
+        )
+    }
+
+    /// Catalog tags or authors matching the value being typed, as pairs of
+    /// label and the text that replaces the value.
+    pub(crate) fn suggestions(
+        &self,
+        completion: &Completion,
+        limit: usize,
+    ) -> Vec<(String, String)> {
+        match completion.kind {
+            CompletionKind::Tag => self
+                .tags
+                .iter()
+                .filter(|tag| field_matches_query(&tag.name, &completion.prefix))
+                .take(limit)
+                .map(|tag| (tag.name.clone(), quote_value(&tag.name)))
+                .collect(),
+            CompletionKind::Author => {
+                let names = self.author_names();
+                self.authors
+                    .iter()
+                    .filter(|author| field_matches_query(&author.name, &completion.prefix))
+                    .take(limit)
+                    .map(|author| (author.name.clone(), names.spell(&author.author)))
+                    .collect()
+            }
+        }
+    }
 }
 
 fn load_author_rows(conn: &Connection) -> FeiwenResult<Vec<AuthorOptionRow>> {
@@ -345,7 +469,8 @@ fn option_matches(title: &str, description: &str, query: &str) -> bool {
 }
 
 pub(super) fn field_items() -> FieldSelectItems {
-    SearchableVec::new(vec![
+    let mut items = vec![
+        SelectChoice::new("全文搜索", FieldKind::FullText),
         SelectChoice::new("标题", FieldKind::Title),
         SelectChoice::new("简介", FieldKind::Description),
         SelectChoice::new("最新章节标题", FieldKind::LatestChapterTitle),
@@ -355,7 +480,24 @@ pub(super) fn field_items() -> FieldSelectItems {
         SelectChoice::new("阅读数", FieldKind::ReadCount),
         SelectChoice::new("回复数", FieldKind::ReplyCount),
         SelectChoice::new("是否受限", FieldKind::IsLimit),
-    ])
+    ];
+    items.extend(growth_choices(FieldKind::Growth));
+    items.extend(
+        TREND_WINDOWS
+            .into_iter()
+            .map(|window| SelectChoice::new(updated_label(window), FieldKind::Updated(window))),
+    );
+    items.extend(
+        ReadStatus::ALL
+            .into_iter()
+            .map(|status| SelectChoice::new(status_label(status), FieldKind::ReadStatus(status))),
+    );
+    items.extend([
+        SelectChoice::new("评分", FieldKind::Rating),
+        SelectChoice::new("笔记", FieldKind::Note),
+        SelectChoice::new("已隐藏", FieldKind::Hidden),
+    ]);
+    SearchableVec::new(items)
 }
 
 pub(super) fn text_relation_items() -> Vec<SelectChoice<TextRelation>> {
@@ -415,7 +557,7 @@ pub(super) fn author_relation_items() -> Vec<SelectChoice<AuthorRelation>> {
 }
 
 pub(super) fn sort_field_items() -> Vec<SelectChoice<SortField>> {
-    vec![
+    let mut items = vec![
         SelectChoice::new("标题", SortField::Title),
         SelectChoice::new("作者名称", SortField::AuthorName),
         SelectChoice::new("作品 ID", SortField::NovelId),
@@ -426,7 +568,14 @@ pub(super) fn sort_field_items() -> Vec<SelectChoice<SortField>> {
         SelectChoice::new("回复数", SortField::ReplyCount),
         SelectChoice::new("作者 ID", SortField::AuthorId),
         SelectChoice::new("是否受限", SortField::IsLimit),
-    ]
+    ];
+    items.extend(growth_choices(SortField::Growth));
+    items.extend([
+        SelectChoice::new("阅读状态", SortField::ReadStatus),
+        SelectChoice::new("评分", SortField::Rating),
+        SelectChoice::new("相关度", SortField::Relevance),
+    ]);
+    items
 }
 
 pub(super) fn sort_direction_items() -> Vec<SelectChoice<crate::store::query::SortDirection>> {
@@ -478,6 +627,7 @@ mod query_options_load_tests {
                 })
                 .collect::<HashSet<_>>(),
             is_limit: false,
+            annotation: Default::default(),
         }
     }
 
@@ -492,7 +642,7 @@ mod query_options_load_tests {
             }),
             &["rust", "gpui"],
         )
-        .save(&mut conn)
+        .save(&mut conn, 0)
         .unwrap();
         novel(
             2,
@@ -502,14 +652,14 @@ mod query_options_load_tests {
             }),
             &["rust"],
         )
-        .save(&mut conn)
+        .save(&mut conn, 0)
         .unwrap();
         novel(
             3,
             Author::Anonymous("匿名".to_owned()),
             &["rust", "匿名标签"],
         )
-        .save(&mut conn)
+        .save(&mut conn, 0)
         .unwrap();
 
         let options = QueryOptions::load(&conn).unwrap();
@@ -0,0 +1,410 @@
+use fluent_bundle::FluentArgs;
+use gpui::{
+    AnyElement, Context, Div, IntoElement, ParentElement as _, SharedString, Styled as _, Task,
+    div, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Sizable as _, StyledExt as _, h_flex, label::Label,
+    scroll::ScrollableElement as _, v_flex,
+};
+use gpui_heatmap::{MatrixHeatmap, MatrixHeatmapLabels, MatrixHeatmapSeries};
+use tracing::{Level, event};
+
+use super::QueryView;
+use crate::{
+    foundation::I18n,
+    store::{
+        analytics::{self, Distribution, TagAnalytics},
+        database,
+        query::FilterExpr,
+    },
+};
+
+/// How many of the most common tags the frequency and distribution lists show.
+const LISTED_TAGS: usize = 30;
+const BAR_WIDTH: f32 = 160.;
+const MATRIX_CELL: f32 = 16.;
+const NAME_WIDTH: f32 = 120.;
+const COUNT_WIDTH: f32 = 56.;
+
+/// Tag and author statistics over every novel the current results' filter
+/// matches.
+pub(super) struct TagAnalyticsPanel {
+    /// The filter the statistics are for, once asked for.
+    filter: Option<FilterExpr>,
+    analytics: Option<TagAnalytics>,
+    problem: Option<String>,
+    _load: Option<Task<()>>,
+}
+
+impl TagAnalyticsPanel {
+    pub(super) fn new() -> Self {
+        Self {
+            filter: None,
+            analytics: None,
+            problem: None,
+            _load: None,
+        }
+    }
+
+    /// Loads the statistics for `filter` unless they are already shown.
+    pub(super) fn show(&mut self, filter: &FilterExpr, cx: &mut Context<QueryView>) {
+        if self.filter.as_ref() == Some(filter) {
+            return;
+        }
+        self.filter = Some(filter.clone());
+        self.analytics = None;
+        self.problem = None;
+        let pool = match database::ready_pool(cx) {
+            Ok(pool) => pool,
+            Err(problem) => {
+                self.problem = Some(problem.to_string());
+                cx.notify();
+                return;
+            }
+        };
+        let filter = filter.clone();
+        self._load = Some(cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_spawn({
+                    let filter = filter.clone();
+                    async move {
+                        let conn = pool.get().map_err(|error| error.to_string())?;
+                        analytics::tag_analytics(&conn, &filter).map_err(|error| error.to_string())
+                    }
+                })
+                .await;
+            let _ = this.update(cx, |view, cx| {
+                let panel = &mut view.analytics;
+                if panel.filter.as_ref() != Some(&filter) {
+                    return;
+                }
+                match result {
+                    Ok(analytics) => {
+                        event!(
+                            Level::INFO,
+                            novels = analytics.novels,
+                            tags = analytics.tags.len(),
+                            "feiwen tag analytics loaded"
+                        );
+                        panel.analytics = Some(analytics);
+                    }
+                    Err(error) => {
+                        event!(Level::ERROR, error = %error, "failed to load tag analytics");
+                        panel.problem = Some(error);
+                    }
+                }
+                cx.notify();
+            });
+        }));
+        cx.notify();
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.filter = None;
+        self.analytics = None;
+        self.problem = None;
+        self._load = None;
+    }
+
+    pub(super) fn render(&self, cx: &mut Context<QueryView>) -> impl IntoElement {
+        let i18n = cx.global::<I18n>();
+        if let Some(problem) = &self.problem {
+            return div()
+                .p_2()
+                .text_xs()
+                .text_color(cx.theme().danger)
+                .child(problem.clone())
+                .into_any_element();
+        }
+        let Some(analytics) = &self.analytics else {
+            let hint = if self.filter.is_some() {
+                i18n.t("query-analytics-loading")
+            } else {
+                i18n.t("query-analytics-empty")
+            };
+            return centered_hint(hint, cx);
+        };
+        if analytics.novels == 0 {
+            return centered_hint(i18n.t("query-analytics-no-novels"), cx);
+        }
+
+        let mut args = FluentArgs::new();
+        args.set("novels", analytics.novels);
+        args.set("tags", analytics.tags.len());
+        args.set("untagged", analytics.untagged);
+        let summary = i18n.t_with_args("query-analytics-summary", &args);
+
+        v_flex()
+            .size_full()
+            .min_h_0()
+            .gap_3()
+            .p_2()
+            .overflow_y_scrollbar()
+            .child(
+                Label::new(summary)
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .child(section(
+                i18n.t("query-analytics-frequency"),
+                render_frequency(analytics, cx),
+                cx,
+            ))
+            .child(section(
+                i18n.t("query-analytics-co-occurrence"),
+                render_co_occurrence(analytics, cx),
+                cx,
+            ))
+            .child(section(
+                i18n.t("query-analytics-distribution"),
+                render_distributions(analytics, cx),
+                cx,
+            ))
+            .child(section(
+                i18n.t("query-analytics-authors"),
+                render_authors(analytics, cx),
+                cx,
+            ))
+            .into_any_element()
+    }
+}
+
+fn section(title: String, body: AnyElement, cx: &Context<QueryView>) -> Div {
+    v_flex()
+        .gap_1()
+        .child(
+            Label::new(title)
+                .text_sm()
+                .font_semibold()
+                .text_color(cx.theme().foreground),
+        )
+        .child(body)
+}
+
+fn centered_hint(hint: String, cx: &Context<QueryView>) -> AnyElement {
+    div()
+        .size_full()
+        .flex()
+        .items_center()
+        .justify_center()
+        .text_sm()
+        .text_color(cx.theme().muted_foreground)
+        .child(hint)
+        .into_any_element()
+}
+
+fn render_frequency(analytics: &TagAnalytics, cx: &Context<QueryView>) -> AnyElement {
+    if analytics.tags.is_empty() {
+        return small_hint(cx.global::<I18n>().t("query-analytics-no-tags"), cx);
+    }
+    let most = analytics.tags[0].novels;
+    let color = cx.theme().chart_1;
+    v_flex()
+        .gap_1()
+        .text_xs()
+        .children(analytics.tags.iter().take(LISTED_TAGS).map(|tag| {
+            h_flex()
+                .gap_2()
+                .child(name_cell(tag.name.clone()))
+                .child(
+                    div()
+                        .w(px(BAR_WIDTH))
+                        .h(px(8.))
+                        .rounded_sm()
+                        .bg(cx.theme().secondary)
+                        .child(
+                            div()
+                                .h_full()
+                                .w(px(bar_width(tag.novels, most)))
+                                .rounded_sm()
+                                .bg(color),
+                        ),
+                )
+                .child(div().text_color(cx.theme().muted_foreground).child(format!(
+                    "{} · {}",
+                    tag.novels,
+                    share(tag.novels, analytics.novels)
+                )))
+        }))
+        .into_any_element()
+}
+
+fn render_co_occurrence(analytics: &TagAnalytics, cx: &Context<QueryView>) -> AnyElement {
+    let i18n = cx.global::<I18n>();
+    let tags = analytics.matrix_tags();
+    if tags.len() < 2 {
+        return small_hint(i18n.t("query-analytics-co-occurrence-empty"), cx);
+    }
+    let names = tags
+        .iter()
+        .map(|tag| SharedString::from(tag.name.clone()))
+        .collect::<Vec<_>>();
+    let series =
+        match MatrixHeatmapSeries::try_new(names.clone(), names, analytics.co_occurrence.clone()) {
+            Ok(series) => series,
+            Err(error) => {
+                event!(Level::ERROR, error = %error, "invalid tag co-occurrence matrix");
+                return small_hint(error.to_string(), cx);
+            }
+        };
+    let mut args = FluentArgs::new();
+    args.set("count", tags.len());
+    let caption = i18n.t_with_args("query-analytics-co-occurrence-caption", &args);
+    let labels = MatrixHeatmapLabels {
+        less: i18n.t("query-analytics-less").into(),
+        more: i18n.t("query-analytics-more").into(),
+        value: i18n.t("query-analytics-together").into(),
+    };
+    MatrixHeatmap::new(
+        "query-analytics-co-occurrence",
+        series,
+        labels,
+        caption.clone(),
+    )
+    .caption(caption)
+    .with_size(px(MATRIX_CELL))
+    .into_any_element()
+}
+
+fn render_distributions(analytics: &TagAnalytics, cx: &Context<QueryView>) -> AnyElement {
+    let i18n = cx.global::<I18n>();
+    if analytics.tags.is_empty() {
+        return small_hint(i18n.t("query-analytics-no-tags"), cx);
+    }
+    let muted = cx.theme().muted_foreground;
+    v_flex()
+        .gap_1()
+        .text_xs()
+        .child(
+            h_flex()
+                .gap_2()
+                .text_color(muted)
+                .child(name_cell(i18n.t("query-analytics-tag")))
+                .child(
+                    div()
+                        .w(px(COUNT_WIDTH))
+                        .child(i18n.t("query-analytics-novels")),
+                )
+                .child(div().flex_1().child(i18n.t("query-analytics-words")))
+                .child(div().flex_1().child(i18n.t("query-analytics-replies"))),
+        )
+        .child(
+            div()
+                .text_color(muted)
+                .child(i18n.t("query-analytics-distribution-hint")),
+        )
+        .children(analytics.tags.iter().take(LISTED_TAGS).map(|tag| {
+            h_flex()
+                .gap_2()
+                .child(name_cell(tag.name.clone()))
+                .child(div().w(px(COUNT_WIDTH)).child(tag.novels.to_string()))
+                .child(div().flex_1().child(distribution_text(Some(tag.words))))
+                .child(div().flex_1().child(distribution_text(tag.replies)))
+        }))
+        .into_any_element()
+}
+
+fn render_authors(analytics: &TagAnalytics, cx: &Context<QueryView>) -> AnyElement {
+    let i18n = cx.global::<I18n>();
+    let muted = cx.theme().muted_foreground;
+    v_flex()
+        .gap_1()
+        .text_xs()
+        .child(
+            h_flex()
+                .gap_2()
+                .text_color(muted)
+                .child(name_cell(i18n.t("query-analytics-author")))
+                .child(
+                    div()
+                        .w(px(COUNT_WIDTH))
+                        .child(i18n.t("query-analytics-novels")),
+                )
+                .child(div().flex_1().child(i18n.t("query-analytics-words"))),
+        )
+        .children(analytics.authors.iter().map(|author| {
+            h_flex()
+                .gap_2()
+                .child(name_cell(author.name.clone()))
+                .child(div().w(px(COUNT_WIDTH)).child(author.novels.to_string()))
+                .child(div().flex_1().child(author.words.to_string()))
+        }))
+        .into_any_element()
+}
+
+fn name_cell(name: String) -> Div {
+    div()
+        .w(px(NAME_WIDTH))
+        .flex_none()
+        .overflow_hidden()
+        .text_ellipsis()
+        .whitespace_nowrap()
+        .child(name)
+}
+
+fn small_hint(hint: String, cx: &Context<QueryView>) -> AnyElement {
+    div()
+        .text_xs()
+        .text_color(cx.theme().muted_foreground)
+        .child(hint)
+        .into_any_element()
+}
+
+/// The bar for `novels` when the most common tag fills the whole width; any
+/// tag at all keeps a sliver.
+fn bar_width(novels: usize, most: usize) -> f32 {
+    if most == 0 {
+        return 0.;
+    }
+    (novels as f32 / most as f32 * BAR_WIDTH).max(2.)
+}
+
+fn share(part: usize, whole: usize) -> String {
+    if whole == 0 {
+        return "-".to_owned();
+    }
+    format!("{:.1}%", part as f64 * 100. / whole as f64)
+}
+
+/// Minimum, quartiles and maximum, the way the distribution hint lists them.
+fn distribution_text(distribution: Option<Distribution>) -> String {
+    distribution.map_or_else(
+        || "-".to_owned(),
+        |distribution| {
+            format!(
+                "{} / {} / {} / {} / {}",
+                distribution.min,
+                distribution.lower_quartile,
+                distribution.median,
+                distribution.upper_quartile,
+                distribution.max
+            )
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_and_shares_scale_to_the_most_common_tag() {
+        assert_eq!(bar_width(50, 100), BAR_WIDTH / 2.);
+        assert_eq!(bar_width(1, 10_000), 2.);
+        assert_eq!(bar_width(0, 0), 0.);
+        assert_eq!(share(1, 3), "33.3%");
+        assert_eq!(share(1, 0), "-");
+        assert_eq!(
+            distribution_text(Some(Distribution {
+                min: 1,
+                lower_quartile: 2,
+                median: 4,
+                upper_quartile: 6,
+                max: 8,
+            })),
+            "1 / 2 / 4 / 6 / 8"
+        );
+        assert_eq!(distribution_text(None), "-");
+    }
+}
@@ -0,0 +1,297 @@
+use gpui::{
+    Context, ElementId, InteractiveElement as _, IntoElement, ParentElement as _,
+    StatefulInteractiveElement as _, Styled as _, Task, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Sizable as _, StyledExt as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    label::Label,
+    tooltip::Tooltip,
+    v_flex,
+};
+use tracing::{Level, event};
+
+use super::QueryView;
+use crate::store::{
+    database,
+    history::{self, NovelSnapshot},
+    query::TrendMetric,
+    service::Novel,
+    types::Author,
+};
+
+const CHART_HEIGHT: f32 = 96.;
+const MIN_BAR_HEIGHT: f32 = 4.;
+const METRICS: [TrendMetric; 3] = [TrendMetric::Words, TrendMetric::Reads, TrendMetric::Replies];
+
+/// The selected result and the counts every fetch recorded for it.
+pub(super) struct NovelDetail {
+    novel: Option<Novel>,
+    history: Vec<NovelSnapshot>,
+    problem: Option<String>,
+    metric: TrendMetric,
+    _load: Option<Task<()>>,
+}
+
+impl NovelDetail {
+    pub(super) fn new() -> Self {
+        Self {
+            novel: None,
+            history: Vec::new(),
+            problem: None,
+            metric: TrendMetric::Replies,
+            _load: None,
+        }
+    }
+
+    pub(super) fn show(&mut self, novel: Novel, cx: &mut Context<QueryView>) {
+        let novel_id = novel.title.id;
+        self.novel = Some(novel);
+        self.history.clear();
+        self.problem = None;
+        let pool = match database::ready_pool(cx) {
+            Ok(pool) => pool,
+            Err(problem) => {
+                self.problem = Some(problem.to_string());
+                cx.notify();
+                return;
+            }
+        };
+        self._load = Some(cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_spawn(async move {
+                    let conn = pool.get().map_err(|error| error.to_string())?;
+                    history::novel_history(&conn, novel_id).map_err(|error| error.to_string())
+                })
+                .await;
+            let _ = this.update(cx, |view, cx| {
+                let detail = &mut view.detail;
+                if detail
+                    .novel
+                    .as_ref()
+                    .is_none_or(|novel| novel.title.id != novel_id)
+                {
+                    return;
+                }
+                match result {
+                    Ok(history) => detail.history = history,
+                    Err(error) => {
+                        event!(Level::ERROR, novel_id, error = %error, "failed to load novel history");
+                        detail.problem = Some(error);
+                    }
+                }
+                cx.notify();
+            });
+        }));
+        cx.notify();
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.novel = None;
+        self.history.clear();
+        self.problem = None;
+        self._load = None;
+    }
+
+    pub(super) fn render(&self, cx: &mut Context<QueryView>) -> impl IntoElement {
+        let Some(novel) = &self.novel else {
+            return div()
+                .size_full()
+                .flex()
+                .items_center()
+                .justify_center()
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child("选择一条结果查看抓取趋势")
+                .into_any_element();
+        };
+        let author = match &novel.author {
+            Author::Known(title) => title.name.clone(),
+            Author::Anonymous(name) => name.clone(),
+        };
+
+        v_flex()
+            .size_full()
+            .gap_2()
+            .p_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Label::new(novel.title.name.clone())
+                            .text_sm()
+                            .font_semibold()
+                            .truncate(),
+                    )
+                    .child(
+                        Label::new(author)
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .gap_3()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(format!("字数 {}", novel.count.word_count))
+                    .child(format!("阅读 {}", count_text(novel.count.read_count)))
+                    .child(format!("回复 {}", count_text(novel.count.reply_count)))
+                    .child(format!("最新章节 {}", novel.latest_chapter.name)),
+            )
+            .child(
+                h_flex()
+                    .gap_1()
+                    .children(METRICS.into_iter().enumerate().map(|(index, metric)| {
+                        Button::new(ElementId::NamedInteger(
+                            "novel-trend-metric".into(),
+                            index as u64,
+                        ))
+                        .small()
+                        .label(metric_label(metric))
+                        .map(|button| {
+                            if metric == self.metric {
+                                button.primary()
+                            } else {
+                                button.ghost()
+                            }
+                        })
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.detail.metric = metric;
+                            cx.notify();
+                        }))
+                    })),
+            )
+            .child(self.render_trend(cx))
+            .into_any_element()
+    }
+
+    fn render_trend(&self, cx: &mut Context<QueryView>) -> impl IntoElement {
+        if let Some(problem) = &self.problem {
+            return div()
+                .text_xs()
+                .text_color(cx.theme().danger)
+                .child(problem.clone())
+                .into_any_element();
+        }
+        let points = self
+            .history
+            .iter()
+            .filter_map(|snapshot| Some((snapshot.fetched_at, snapshot.metric(self.metric)?)))
+            .collect::<Vec<_>>();
+        let (Some(first), Some(last)) = (points.first(), points.last()) else {
+            return hint("还没有这部作品的抓取记录", cx);
+        };
+        if points.len() < 2 {
+            return hint("再抓取一次后即可看到趋势", cx);
+        }
+        let now = history::unix_now();
+        let min = points
+            .iter()
+            .map(|(_, value)| *value)
+            .min()
+            .unwrap_or_default();
+        let max = points
+            .iter()
+            .map(|(_, value)| *value)
+            .max()
+            .unwrap_or_default();
+        let color = cx.theme().chart_1;
+        let label = metric_label(self.metric);
+
+        v_flex()
+            .gap_1()
+            .child(
+                h_flex()
+                    .w_full()
+                    .h(px(CHART_HEIGHT))
+                    .items_end()
+                    .gap(px(2.))
+                    .children(points.iter().map(|(fetched_at, value)| {
+                        let tooltip = format!("{}:{label} {value}", fetched_ago(*fetched_at, now));
+                        div()
+                            .id(ElementId::NamedInteger(
+                                "novel-trend-point".into(),
+                                *fetched_at as u64,
+                            ))
+                            .flex_1()
+                            .h(px(bar_height(*value, min, max)))
+                            .bg(color.opacity(0.5))
+                            .tooltip(move |window, cx| {
+                                Tooltip::new(tooltip.clone()).build(window, cx)
+                            })
+                    })),
+            )
+            .child(
+                h_flex()
+                    .justify_between()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(fetched_ago(first.0, now))
+                    .child(format!("{label} {:+}", last.1 - first.1))
+                    .child(fetched_ago(last.0, now)),
+            )
+            .into_any_element()
+    }
+}
+
+fn hint(text: &'static str, cx: &Context<QueryView>) -> gpui::AnyElement {
+    div()
+        .text_xs()
+        .text_color(cx.theme().muted_foreground)
+        .child(text)
+        .into_any_element()
+}
+
+fn metric_label(metric: TrendMetric) -> &'static str {
+    match metric {
+        TrendMetric::Words => "字数",
+        TrendMetric::Reads => "阅读",
+        TrendMetric::Replies => "回复",
+    }
+}
+
+fn count_text(value: Option<i32>) -> String {
+    value.map_or_else(|| "-".to_owned(), |value| value.to_string())
+}
+
+/// Scales `value` between the smallest and largest recorded values; a flat
+/// history draws half-height bars.
+fn bar_height(value: i32, min: i32, max: i32) -> f32 {
+    if max <= min {
+        return CHART_HEIGHT / 2.;
+    }
+    let ratio = (value - min) as f32 / (max - min) as f32;
+    MIN_BAR_HEIGHT + ratio * (CHART_HEIGHT - MIN_BAR_HEIGHT)
+}
+
+fn fetched_ago(fetched_at: i64, now: i64) -> String {
+    match (now - fetched_at).max(0) / history::DAY_SECONDS {
+        0 => "今天".to_owned(),
+        days => format!("{days} 天前"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_span_the_recorded_range() {
+        assert_eq!(bar_height(10, 10, 20), MIN_BAR_HEIGHT);
+        assert_eq!(bar_height(20, 10, 20), CHART_HEIGHT);
+        assert_eq!(bar_height(7, 7, 7), CHART_HEIGHT / 2.);
+    }
+
+    #[test]
+    fn fetch_ages_count_whole_days() {
+        let now = 10 * history::DAY_SECONDS;
+        assert_eq!(fetched_ago(now - 60, now), "今天");
+        assert_eq!(
+            fetched_ago(now - 3 * history::DAY_SECONDS - 60, now),
+            "3 天前"
+        );
+        assert_eq!(fetched_ago(now + 60, now), "今天");
+    }
+}
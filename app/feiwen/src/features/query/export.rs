@@ -0,0 +1,281 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use fluent_bundle::FluentArgs;
+use gpui::{
+    Context, ElementId, IntoElement, ParentElement as _, Styled as _, Task,
+    prelude::FluentBuilder as _,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _,
+    alert::Alert,
+    button::{Button, ButtonVariants as _, Toggle, ToggleGroup, ToggleVariants as _},
+    h_flex,
+    label::Label,
+    v_flex,
+};
+use tracing::{Level, event};
+
+use super::QueryView;
+use crate::{
+    errors::FeiwenResult,
+    foundation::I18n,
+    store::{
+        DbConn, database,
+        export::{ExportColumn, ExportFormat, export_records},
+        query::QuerySpec,
+    },
+};
+
+/// The export controls above the results table. The chosen format and
+/// columns apply both to the current results and to a saved query's.
+pub(super) struct ResultExport {
+    format: ExportFormat,
+    columns: Vec<ExportColumn>,
+    choosing_columns: bool,
+    task: Option<Task<()>>,
+    /// How the last export went: the novel count and file, or the error.
+    outcome: Option<Result<(usize, PathBuf), String>>,
+}
+
+impl ResultExport {
+    pub(super) fn new() -> Self {
+        Self {
+            format: ExportFormat::default(),
+            columns: ExportColumn::DEFAULT.to_vec(),
+            choosing_columns: false,
+            task: None,
+            outcome: None,
+        }
+    }
+
+    pub(super) fn is_busy(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// Asks where to write and exports every novel `spec` matches there,
+    /// reading them again from the database rather than from the table.
+    pub(super) fn start(&mut self, spec: QuerySpec, file_stem: &str, cx: &mut Context<QueryView>) {
+        if self.is_busy() || self.columns.is_empty() {
+            return;
+        }
+        let pool = match database::ready_pool(cx) {
+            Ok(pool) => pool,
+            Err(problem) => {
+                event!(Level::INFO, error = %problem, "export database gate closed");
+                return;
+            }
+        };
+        let (format, columns) = (self.format, self.columns.clone());
+        let directory = dirs_next::download_dir()
+            .or_else(dirs_next::home_dir)
+            .unwrap_or_else(std::env::temp_dir);
+        let file_name = format!(
+            "{}.{}",
+            file_stem.replace(['/', '\\'], "-"),
+            format.extension()
+        );
+        let prompt = cx.prompt_for_new_path(&directory, Some(&file_name));
+        self.task = Some(cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = prompt.await else {
+                let _ = this.update(cx, |view, cx| {
+                    view.export.task = None;
+                    cx.notify();
+                });
+                return;
+            };
+            let result = cx
+                .background_spawn(async move {
+                    write_file(&pool, &spec, &columns, format, &path).map(|count| (count, path))
+                })
+                .await;
+            let _ = this.update(cx, |view, cx| view.export.finish(result, cx));
+        }));
+        cx.notify();
+    }
+
+    fn finish(&mut self, result: FeiwenResult<(usize, PathBuf)>, cx: &mut Context<QueryView>) {
+        self.task = None;
+        self.outcome = Some(match result {
+            Ok((count, path)) => {
+                event!(Level::INFO, count, path = %path.display(), "feiwen export finished");
+                Ok((count, path))
+            }
+            Err(error) => {
+                event!(Level::ERROR, error = %error, "feiwen export failed");
+                Err(error.to_string())
+            }
+        });
+        cx.notify();
+    }
+
+    fn set_format(&mut self, checked: &[bool]) {
+        if let Some(format) = ExportFormat::ALL
+            .into_iter()
+            .zip(checked)
+            .find(|(format, checked)| **checked && *format != self.format)
+            .map(|(format, _)| format)
+        {
+            self.format = format;
+        }
+    }
+
+    fn set_columns(&mut self, checked: &[bool]) {
+        self.columns = ExportColumn::ALL
+            .into_iter()
+            .zip(checked)
+            .filter(|(_, checked)| **checked)
+            .map(|(column, _)| column)
+            .collect();
+    }
+
+    pub(super) fn render(
+        &self,
+        has_results: bool,
+        cx: &mut Context<QueryView>,
+    ) -> impl IntoElement {
+        let i18n = cx.global::<I18n>();
+        let busy = self.is_busy();
+        let export = if busy {
+            i18n.t("query-export-running")
+        } else {
+            i18n.t("query-export-results")
+        };
+        let columns = i18n.t("query-export-columns");
+        let error_title = i18n.t("query-error-title");
+        let done = match &self.outcome {
+            Some(Ok((count, path))) => {
+                let mut args = FluentArgs::new();
+                args.set("count", *count);
+                args.set("path", path.display().to_string());
+                Some(i18n.t_with_args("query-export-done", &args))
+            }
+            _ => None,
+        };
+
+        v_flex()
+            .gap_1()
+            .pb_1()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        ToggleGroup::new("query-export-format")
+                            .segmented()
+                            .outline()
+                            .children(ExportFormat::ALL.into_iter().enumerate().map(
+                                |(index, format)| {
+                                    Toggle::new(ElementId::NamedInteger(
+                                        "query-export-format-choice".into(),
+                                        index as u64,
+                                    ))
+                                    .label(format.label())
+                                    .checked(format == self.format)
+                                },
+                            ))
+                            .on_click(cx.listener(|this, checked: &Vec<bool>, _, cx| {
+                                this.export.set_format(checked);
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Button::new("query-export-columns")
+                            .ghost()
+                            .label(format!("{columns} ({})", self.columns.len()))
+                            .on_click(cx.listener(|this, _, _, cx| {
+                                this.export.choosing_columns = !this.export.choosing_columns;
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Button::new("query-export-results")
+                            .label(export)
+                            .loading(busy)
+                            .disabled(busy || !has_results || self.columns.is_empty())
+                            .on_click(cx.listener(|this, _, _, cx| this.export_results(cx))),
+                    )
+                    .when_some(done, |this, done| {
+                        this.child(
+                            Label::new(done)
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    }),
+            )
+            .when(self.choosing_columns, |this| {
+                this.child(
+                    ToggleGroup::new("query-export-column-choices")
+                        .outline()
+                        .flex_wrap()
+                        .children(ExportColumn::ALL.into_iter().enumerate().map(
+                            |(index, column)| {
+                                Toggle::new(ElementId::NamedInteger(
+                                    "query-export-column-choice".into(),
+                                    index as u64,
+                                ))
+                                .label(column.label())
+                                .checked(self.columns.contains(&column))
+                            },
+                        ))
+                        .on_click(cx.listener(|this, checked: &Vec<bool>, _, cx| {
+                            this.export.set_columns(checked);
+                            cx.notify();
+                        })),
+                )
+            })
+            .when_some(
+                self.outcome
+                    .as_ref()
+                    .and_then(|outcome| outcome.clone().err()),
+                |this, problem| {
+                    this.child(Alert::error("query-export-problem", problem).title(error_title))
+                },
+            )
+    }
+}
+
+/// Writes the export to `path`, leaving no half-written file behind when
+/// it fails.
+fn write_file(
+    pool: &DbConn,
+    spec: &QuerySpec,
+    columns: &[ExportColumn],
+    format: ExportFormat,
+    path: &Path,
+) -> FeiwenResult<usize> {
+    let conn = pool.get()?;
+    let file = File::create(path)?;
+    export_records(&conn, spec, columns, format, BufWriter::new(file)).inspect_err(|_| {
+        let _ = std::fs::remove_file(path);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_follows_the_newly_checked_toggle() {
+        let mut export = ResultExport::new();
+
+        export.set_format(&[true, false, true]);
+        assert_eq!(export.format, ExportFormat::Xlsx);
+        // Clicking the checked toggle again unchecks it; the format stays.
+        export.set_format(&[false, false, false]);
+        assert_eq!(export.format, ExportFormat::Xlsx);
+    }
+
+    #[test]
+    fn columns_keep_their_canonical_order() {
+        let mut export = ResultExport::new();
+        let mut checked = [false; ExportColumn::ALL.len()];
+        checked[12] = true;
+        checked[0] = true;
+
+        export.set_columns(&checked);
+        assert_eq!(export.columns, vec![ExportColumn::Id, ExportColumn::TagIds]);
+    }
+}
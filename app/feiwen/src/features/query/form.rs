@@ -9,11 +9,12 @@ use super::advanced::options::{
     AuthorRelation, BoolRelation, FieldKind, GroupRelation, NumberRelation, SortField,
     TagsRelation, TextRelation,
 };
-#[cfg(test)]
-use crate::store::query::SortExpr;
-use crate::store::query::{
-    AuthorPredicate, AuthorRef, BoolField, FilterExpr, NumberOp, Predicate, QuerySpec,
-    SortDirection, SortSpec, TagsPredicate, TextField, TextOp,
+use crate::store::{
+    annotation::ReadStatus,
+    query::{
+        AuthorPredicate, AuthorRef, BoolField, FilterExpr, NumberField, NumberOp, Predicate,
+        QuerySpec, SortDirection, SortExpr, SortSpec, TagsPredicate, TextField, TextOp,
+    },
 };
 
 #[derive(Clone, Debug, PartialEq, FormSchema)]
@@ -79,6 +80,7 @@ pub(crate) struct NumberConditionDraft {
 
 #[derive(Clone, Debug, PartialEq, FormSchema)]
 pub(crate) struct BoolConditionDraft {
+    pub(crate) field: FieldKind,
     pub(crate) relation: Option<BoolRelation>,
     pub(crate) value: Option<bool>,
 }
@@ -130,26 +132,41 @@ impl FilterNodeDraft {
 impl ConditionField {
     pub(crate) fn for_field(field: FieldKind) -> Self {
         match field {
-            FieldKind::Title | FieldKind::Description | FieldKind::LatestChapterTitle => {
-                Self::Text(TextConditionDraft {
-                    field,
-                    relation: None,
-                    value: String::new(),
-                })
-            }
-            FieldKind::WordCount | FieldKind::ReadCount | FieldKind::ReplyCount => {
-                Self::Number(NumberConditionDraft {
-                    field,
-                    relation: None,
-                    single: String::new(),
-                    min: String::new(),
-                    max: String::new(),
-                })
-            }
-            FieldKind::IsLimit => Self::Bool(BoolConditionDraft {
+            // Search only has the one relation.
+            FieldKind::FullText => Self::Text(TextConditionDraft {
+                field,
+                relation: Some(TextRelation::Contains),
+                value: String::new(),
+            }),
+            FieldKind::Title
+            | FieldKind::Description
+            | FieldKind::LatestChapterTitle
+            | FieldKind::Note => Self::Text(TextConditionDraft {
+                field,
+                relation: None,
+                value: String::new(),
+            }),
+            FieldKind::WordCount
+            | FieldKind::ReadCount
+            | FieldKind::ReplyCount
+            | FieldKind::Rating
+            | FieldKind::Growth(..) => Self::Number(NumberConditionDraft {
+                field,
+                relation: None,
+                single: String::new(),
+                min: String::new(),
+                max: String::new(),
+            }),
+            FieldKind::IsLimit | FieldKind::Hidden => Self::Bool(BoolConditionDraft {
+                field,
                 relation: None,
                 value: Some(false),
             }),
+            FieldKind::Updated(_) | FieldKind::ReadStatus(_) => Self::Bool(BoolConditionDraft {
+                field,
+                relation: None,
+                value: Some(true),
+            }),
             FieldKind::Tags => Self::Tags(TagsConditionDraft {
                 relation: None,
                 values: Vec::new(),
@@ -168,7 +185,7 @@ impl ConditionField {
             Self::Unselected => None,
             Self::Text(value) => Some(value.field),
             Self::Number(value) => Some(value.field),
-            Self::Bool(_) => Some(FieldKind::IsLimit),
+            Self::Bool(value) => Some(value.field),
             Self::Tags(_) => Some(FieldKind::Tags),
             Self::Author(_) => Some(FieldKind::Author),
         }
@@ -261,6 +278,15 @@ fn validate_condition(
                     );
                 return;
             }
+            if value.field == FieldKind::FullText && value.relation != Some(TextRelation::Contains)
+            {
+                out.at(path.clone().then(TextConditionDraft::RELATION))
+                    .error(
+                        "condition_relation_unsupported",
+                        ValidationMessage::key("query-validation-search-relation"),
+                    );
+                return;
+            }
             if value.value.trim().is_empty() {
                 out.at(path.then(TextConditionDraft::VALUE)).error(
                     "condition_value_required",
@@ -407,7 +433,6 @@ impl QueryDraft {
         })
     }
 
-    #[cfg(test)]
     pub(crate) fn from_spec(spec: &QuerySpec) -> Self {
         Self {
             filters: FilterGroupDraft::from_expr(&spec.filter),
@@ -434,7 +459,6 @@ impl FilterGroupDraft {
         })
     }
 
-    #[cfg(test)]
     fn from_expr(expr: &FilterExpr) -> Self {
         let (negated, expr) = match expr {
             FilterExpr::Not(inner)
@@ -465,7 +489,6 @@ impl FilterNodeDraft {
         }
     }
 
-    #[cfg(test)]
     fn from_expr(expr: &FilterExpr) -> Self {
         if matches!(expr, FilterExpr::All(_) | FilterExpr::Any(_))
             || matches!(expr, FilterExpr::Not(inner) if matches!(inner.as_ref(), FilterExpr::All(_) | FilterExpr::Any(_)))
@@ -502,6 +525,12 @@ impl ConditionField {
     fn to_expr(&self) -> Result<FilterExpr, String> {
         let predicate = match self {
             Self::Unselected => return Err("请选择字段".to_owned()),
+            Self::Text(value) if value.field == FieldKind::FullText => {
+                if value.relation != Some(TextRelation::Contains) {
+                    return Err("全文搜索只支持包含".to_owned());
+                }
+                Predicate::Search(non_empty(&value.value, "请输入文本")?)
+            }
             Self::Text(value) => Predicate::Text {
                 field: value
                     .field
@@ -515,19 +544,40 @@ impl ConditionField {
                 },
                 value: non_empty(&value.value, "请输入文本")?,
             },
-            Self::Number(value) => Predicate::Number {
-                field: value
-                    .field
-                    .number_field()
-                    .ok_or_else(|| "字段与数字条件不匹配".to_owned())?,
-                op: value.to_op()?,
+            Self::Number(value) => match value.field {
+                FieldKind::Growth(metric, window) => Predicate::Growth {
+                    metric,
+                    window,
+                    op: value.to_op()?,
+                },
+                field => Predicate::Number {
+                    field: field
+                        .number_field()
+                        .ok_or_else(|| "字段与数字条件不匹配".to_owned())?,
+                    op: value.to_op()?,
+                },
             },
             Self::Bool(value) => {
                 value.relation.ok_or_else(|| "请选择条件".to_owned())?;
-                Predicate::Bool {
-                    field: BoolField::IsLimit,
-                    value: value.value.ok_or_else(|| "请选择有效项".to_owned())?,
-                }
+                let expected = value.value.ok_or_else(|| "请选择有效项".to_owned())?;
+                let predicate = match value.field {
+                    FieldKind::Updated(window) => Predicate::Updated(window),
+                    FieldKind::ReadStatus(status) => Predicate::ReadStatus(status),
+                    field => {
+                        return Ok(FilterExpr::Predicate(Predicate::Bool {
+                            field: field
+                                .bool_field()
+                                .ok_or_else(|| "字段与是否条件不匹配".to_owned())?,
+                            value: expected,
+                        }));
+                    }
+                };
+                let predicate = FilterExpr::Predicate(predicate);
+                return Ok(if expected {
+                    predicate
+                } else {
+                    FilterExpr::Not(Box::new(predicate))
+                });
             }
             Self::Tags(value) => Predicate::Tags(value.to_predicate()?),
             Self::Author(value) => return value.to_expr(),
@@ -535,7 +585,6 @@ impl ConditionField {
         Ok(FilterExpr::Predicate(predicate))
     }
 
-    #[cfg(test)]
     fn from_expr(expr: &FilterExpr) -> Self {
         match expr {
             FilterExpr::Predicate(Predicate::Text { field, op, value }) => {
@@ -557,6 +606,7 @@ impl ConditionField {
                         TextField::Title => FieldKind::Title,
                         TextField::Description => FieldKind::Description,
                         TextField::LatestChapter => FieldKind::LatestChapterTitle,
+                        TextField::Note => FieldKind::Note,
                         TextField::AuthorName => unreachable!(),
                     },
                     relation: Some(match op {
@@ -568,13 +618,19 @@ impl ConditionField {
                     value: value.clone(),
                 })
             }
+            FilterExpr::Predicate(Predicate::Search(query)) => Self::Text(TextConditionDraft {
+                field: FieldKind::FullText,
+                relation: Some(TextRelation::Contains),
+                value: query.clone(),
+            }),
             FilterExpr::Predicate(Predicate::Number { field, op }) => {
                 let (relation, single, min, max) = NumberConditionDraft::from_op(*op);
                 Self::Number(NumberConditionDraft {
                     field: match field {
-                        crate::store::query::NumberField::WordCount => FieldKind::WordCount,
-                        crate::store::query::NumberField::ReadCount => FieldKind::ReadCount,
-                        crate::store::query::NumberField::ReplyCount => FieldKind::ReplyCount,
+                        NumberField::WordCount => FieldKind::WordCount,
+                        NumberField::ReadCount => FieldKind::ReadCount,
+                        NumberField::ReplyCount => FieldKind::ReplyCount,
+                        NumberField::Rating => FieldKind::Rating,
                         _ => FieldKind::WordCount,
                     },
                     relation: Some(relation),
@@ -583,12 +639,38 @@ impl ConditionField {
                     max,
                 })
             }
-            FilterExpr::Predicate(Predicate::Bool { value, .. }) => {
+            FilterExpr::Predicate(Predicate::Growth { metric, window, op }) => {
+                let (relation, single, min, max) = NumberConditionDraft::from_op(*op);
+                Self::Number(NumberConditionDraft {
+                    field: FieldKind::Growth(*metric, *window),
+                    relation: Some(relation),
+                    single,
+                    min,
+                    max,
+                })
+            }
+            FilterExpr::Predicate(Predicate::Bool { field, value }) => {
                 Self::Bool(BoolConditionDraft {
+                    field: match field {
+                        BoolField::IsLimit => FieldKind::IsLimit,
+                        BoolField::Hidden => FieldKind::Hidden,
+                    },
                     relation: Some(BoolRelation::Is),
                     value: Some(*value),
                 })
             }
+            FilterExpr::Predicate(Predicate::Updated(window)) => Self::Bool(BoolConditionDraft {
+                field: FieldKind::Updated(*window),
+                relation: Some(BoolRelation::Is),
+                value: Some(true),
+            }),
+            FilterExpr::Predicate(Predicate::ReadStatus(status)) => {
+                Self::Bool(BoolConditionDraft {
+                    field: FieldKind::ReadStatus(*status),
+                    relation: Some(BoolRelation::Is),
+                    value: Some(true),
+                })
+            }
             FilterExpr::Predicate(Predicate::Tags(predicate)) => {
                 Self::Tags(TagsConditionDraft::from_predicate(predicate))
             }
@@ -623,7 +705,6 @@ impl NumberConditionDraft {
         })
     }
 
-    #[cfg(test)]
     fn from_op(op: NumberOp) -> (NumberRelation, String, String, String) {
         match op {
             NumberOp::Eq(value) => (
@@ -689,7 +770,6 @@ impl TagsConditionDraft {
         })
     }
 
-    #[cfg(test)]
     fn from_predicate(predicate: &TagsPredicate) -> Self {
         let (relation, values) = match predicate {
             TagsPredicate::Intersects(values) => (TagsRelation::Intersects, values),
@@ -764,7 +844,6 @@ impl AuthorConditionDraft {
         }
     }
 
-    #[cfg(test)]
     fn from_predicate(predicate: &AuthorPredicate) -> Self {
         match predicate {
             AuthorPredicate::Is(author) => Self {
@@ -806,32 +885,29 @@ impl SortDraft {
         })
     }
 
-    #[cfg(test)]
     fn from_spec(spec: &SortSpec) -> Self {
+        let field = match spec.expr {
+            SortExpr::Text(TextField::Title) => Some(SortField::Title),
+            SortExpr::Text(TextField::AuthorName) => Some(SortField::AuthorName),
+            SortExpr::Number(NumberField::NovelId) => Some(SortField::NovelId),
+            SortExpr::Number(NumberField::LatestChapterId) => Some(SortField::LatestChapterId),
+            SortExpr::Text(TextField::LatestChapter) => Some(SortField::LatestChapterTitle),
+            // The form has no description, note or hidden sort; the user
+            // picks another field.
+            SortExpr::Text(TextField::Description | TextField::Note)
+            | SortExpr::Bool(BoolField::Hidden) => None,
+            SortExpr::Number(NumberField::WordCount) => Some(SortField::WordCount),
+            SortExpr::Number(NumberField::ReadCount) => Some(SortField::ReadCount),
+            SortExpr::Number(NumberField::ReplyCount) => Some(SortField::ReplyCount),
+            SortExpr::Number(NumberField::AuthorId) => Some(SortField::AuthorId),
+            SortExpr::Bool(BoolField::IsLimit) => Some(SortField::IsLimit),
+            SortExpr::Growth { metric, window } => Some(SortField::Growth(metric, window)),
+            SortExpr::ReadStatus => Some(SortField::ReadStatus),
+            SortExpr::Number(NumberField::Rating) => Some(SortField::Rating),
+            SortExpr::Relevance => Some(SortField::Relevance),
+        };
         Self {
-            field: Some(match spec.expr {
-                SortExpr::Text(TextField::Title) => SortField::Title,
-                SortExpr::Text(TextField::AuthorName) => SortField::AuthorName,
-                SortExpr::Number(crate::store::query::NumberField::NovelId) => SortField::NovelId,
-                SortExpr::Number(crate::store::query::NumberField::LatestChapterId) => {
-                    SortField::LatestChapterId
-                }
-                SortExpr::Text(TextField::LatestChapter) => SortField::LatestChapterTitle,
-                SortExpr::Text(TextField::Description) => {
-                    unreachable!("query form does not produce description sort expressions")
-                }
-                SortExpr::Number(crate::store::query::NumberField::WordCount) => {
-                    SortField::WordCount
-                }
-                SortExpr::Number(crate::store::query::NumberField::ReadCount) => {
-                    SortField::ReadCount
-                }
-                SortExpr::Number(crate::store::query::NumberField::ReplyCount) => {
-                    SortField::ReplyCount
-                }
-                SortExpr::Number(crate::store::query::NumberField::AuthorId) => SortField::AuthorId,
-                SortExpr::Bool(BoolField::IsLimit) => SortField::IsLimit,
-            }),
+            field,
             direction: Some(spec.direction),
         }
     }
@@ -856,6 +932,7 @@ fn parse_i32(value: &str) -> Result<i32, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::store::query::{TrendMetric, TrendWindow};
     use gpui::{AppContext as _, TestAppContext};
     use gpui_form::{Form, ResolveError, ValidationTrigger};
 
@@ -891,6 +968,86 @@ mod tests {
         assert_eq!(QueryDraft::from_spec(&spec), draft);
     }
 
+    #[test]
+    fn growth_and_update_conditions_round_trip_through_query_spec() {
+        let spec = QuerySpec {
+            filter: FilterExpr::All(vec![
+                FilterExpr::Predicate(Predicate::Growth {
+                    metric: TrendMetric::Replies,
+                    window: TrendWindow::Days(7),
+                    op: NumberOp::Gt(10),
+                }),
+                FilterExpr::Not(Box::new(FilterExpr::Predicate(Predicate::Updated(
+                    TrendWindow::LastFetch,
+                )))),
+            ]),
+            sorts: vec![SortSpec {
+                expr: SortExpr::Growth {
+                    metric: TrendMetric::Reads,
+                    window: TrendWindow::Days(30),
+                },
+                direction: SortDirection::Desc,
+            }],
+        };
+
+        assert_eq!(QueryDraft::from_spec(&spec).to_spec(), Ok(spec));
+    }
+
+    #[test]
+    fn search_conditions_round_trip_through_query_spec() {
+        let spec = QuerySpec {
+            filter: FilterExpr::All(vec![FilterExpr::Predicate(Predicate::Search(
+                "穿书 csxt".to_owned(),
+            ))]),
+            sorts: vec![SortSpec {
+                expr: SortExpr::Relevance,
+                direction: SortDirection::Desc,
+            }],
+        };
+        assert_eq!(QueryDraft::from_spec(&spec).to_spec(), Ok(spec));
+
+        let mut draft = ConditionField::for_field(FieldKind::FullText);
+        if let ConditionField::Text(value) = &mut draft {
+            value.value = "系统".to_owned();
+            value.relation = Some(TextRelation::StartsWith);
+        }
+        assert!(draft.to_expr().is_err());
+    }
+
+    #[test]
+    fn annotation_conditions_round_trip_through_query_spec() {
+        let spec = QuerySpec {
+            filter: FilterExpr::All(vec![
+                FilterExpr::Predicate(Predicate::ReadStatus(ReadStatus::Reading)),
+                FilterExpr::Predicate(Predicate::Number {
+                    field: NumberField::Rating,
+                    op: NumberOp::Gte(4),
+                }),
+                FilterExpr::Predicate(Predicate::Text {
+                    field: TextField::Note,
+                    op: TextOp::Contains,
+                    value: "慢热".to_owned(),
+                }),
+                FilterExpr::Predicate(Predicate::Bool {
+                    field: BoolField::Hidden,
+                    value: true,
+                }),
+            ]),
+            sorts: vec![
+                SortSpec {
+                    expr: SortExpr::ReadStatus,
+                    direction: SortDirection::Asc,
+                },
+                SortSpec {
+                    expr: SortExpr::Number(NumberField::Rating),
+                    direction: SortDirection::Desc,
+                },
+            ],
+        };
+
+        assert_eq!(QueryDraft::from_spec(&spec).to_spec(), Ok(spec));
+    }
+
     #[test]
     fn author_is_not_and_condition_negation_round_trip_without_changing_the_form_shape() {
         let draft = QueryDraft {
@@ -1,14 +1,32 @@
-use crate::store::{service::Novel, types::Author};
-use gpui::{App, Context, IntoElement, ParentElement, Styled, Window, div};
+use crate::store::{
+    annotation::{Annotation, ReadStatus},
+    database,
+    search_index::{self, SearchTerm, Snippet},
+    service::Novel,
+    types::Author,
+};
+use gpui::{
+    App, AppContext, Context, Div, Entity, InteractiveElement, IntoElement, ParentElement,
+    StatefulInteractiveElement, Styled, Subscription, Window, div, prelude::FluentBuilder,
+};
 use gpui_component::{
-    ActiveTheme, StyledExt,
+    ActiveTheme, Sizable, StyledExt,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::{Input, InputEvent, InputState},
     label::Label,
     link::Link,
+    rating::Rating,
     table::{Column, ColumnFixed, ColumnSort, TableDelegate, TableState},
     tag::Tag as TagComponent,
 };
+use tracing::{Level, event};
 
 const SITE_ORIGIN: &str = "https://xn--pxtr7m.com";
+/// Characters of a description kept around its first search match.
+const DESCRIPTION_SNIPPET_WIDTH: usize = 48;
+/// Short fields keep this many characters, which is usually all of them.
+const FIELD_SNIPPET_WIDTH: usize = 32;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum ResultColumn {
@@ -21,10 +39,14 @@ enum ResultColumn {
     IsLimit,
     LatestChapter,
     Tags,
+    ReadStatus,
+    Rating,
+    Note,
+    Hidden,
 }
 
 impl ResultColumn {
-    const ALL: [Self; 9] = [
+    const ALL: [Self; 13] = [
         Self::Title,
         Self::Description,
         Self::Author,
@@ -34,13 +56,27 @@ impl ResultColumn {
         Self::IsLimit,
         Self::LatestChapter,
         Self::Tags,
+        Self::ReadStatus,
+        Self::Rating,
+        Self::Note,
+        Self::Hidden,
     ];
 }
 
+/// The note cell being edited; it saves on enter or when it loses focus.
+struct NoteEditor {
+    novel_id: i32,
+    input: Entity<InputState>,
+    _subscription: Subscription,
+}
+
 pub(crate) struct ResultsTableDelegate {
     novels: Vec<Novel>,
     original_novels: Vec<Novel>,
     loading: bool,
+    note_editor: Option<NoteEditor>,
+    /// Terms of the query's searches, marked in the text cells.
+    search_terms: Vec<SearchTerm>,
 }
 
 impl ResultsTableDelegate {
@@ -49,12 +85,28 @@ impl ResultsTableDelegate {
             novels: Vec::new(),
             original_novels: Vec::new(),
             loading: false,
+            note_editor: None,
+            search_terms: Vec::new(),
+        }
+    }
+
+    pub(crate) fn set_search_terms(&mut self, terms: Vec<SearchTerm>) {
+        self.search_terms = terms;
+    }
+
+    /// `text` cut around its first search match with the matches marked,
+    /// or `None` when nothing searched occurs in it.
+    fn snippet(&self, text: &str, width: usize) -> Option<Snippet> {
+        if self.search_terms.is_empty() {
+            return None;
         }
+        search_index::snippet(text, &self.search_terms, width)
     }
 
     pub(crate) fn set_novels(&mut self, novels: Vec<Novel>) {
         self.original_novels = novels.clone();
         self.novels = novels;
+        self.note_editor = None;
     }
 
     pub(crate) fn set_loading(&mut self, loading: bool) {
@@ -89,13 +141,98 @@ impl ResultsTableDelegate {
                 .width(180.)
                 .sortable(),
             ResultColumn::Tags => Column::new("tags", "标签").width(360.),
+            ResultColumn::ReadStatus => Column::new("read_status", "状态")
+                .width(72.)
+                .text_center()
+                .sortable(),
+            ResultColumn::Rating => Column::new("rating", "评分").width(112.).sortable(),
+            ResultColumn::Note => Column::new("note", "笔记").width(200.),
+            ResultColumn::Hidden => Column::new("hidden", "隐藏").width(72.).text_center(),
         }
     }
 
-    fn novel_at(&self, row_ix: usize) -> Option<&Novel> {
+    pub(crate) fn novel_at(&self, row_ix: usize) -> Option<&Novel> {
         self.novels.get(row_ix)
     }
 
+    /// Applies `edit` to the novel's annotation in both row orders and
+    /// stores the result in the background.
+    fn annotate(
+        &mut self,
+        novel_id: i32,
+        edit: impl Fn(&mut Annotation),
+        cx: &mut Context<TableState<Self>>,
+    ) {
+        let mut annotation = None;
+        for novel in self
+            .novels
+            .iter_mut()
+            .chain(self.original_novels.iter_mut())
+            .filter(|novel| novel.title.id == novel_id)
+        {
+            edit(&mut novel.annotation);
+            annotation = Some(novel.annotation.clone());
+        }
+        let Some(annotation) = annotation else {
+            return;
+        };
+        cx.notify();
+        let pool = match database::ready_pool(cx) {
+            Ok(pool) => pool,
+            Err(problem) => {
+                event!(Level::ERROR, novel_id, error = %problem, "annotation database gate closed");
+                return;
+            }
+        };
+        cx.spawn(async move |_, cx| {
+            let result = cx
+                .background_spawn(async move { annotation.save(&*pool.get()?, novel_id) })
+                .await;
+            if let Err(error) = result {
+                event!(Level::ERROR, novel_id, error = %error, "failed to save annotation");
+            }
+        })
+        .detach();
+    }
+
+    fn edit_note(
+        &mut self,
+        novel_id: i32,
+        window: &mut Window,
+        cx: &mut Context<TableState<Self>>,
+    ) {
+        let Some(novel) = self.novels.iter().find(|novel| novel.title.id == novel_id) else {
+            return;
+        };
+        let note = novel.annotation.note.clone();
+        let input = cx.new(|cx| InputState::new(window, cx).default_value(note));
+        input.update(cx, |input, cx| input.focus(window, cx));
+        let subscription =
+            cx.subscribe_in(&input, window, |table, _, event: &InputEvent, _, cx| {
+                if matches!(event, InputEvent::PressEnter { .. } | InputEvent::Blur) {
+                    table.delegate_mut().finish_note(cx);
+                }
+            });
+        self.note_editor = Some(NoteEditor {
+            novel_id,
+            input,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+
+    fn finish_note(&mut self, cx: &mut Context<TableState<Self>>) {
+        let Some(editor) = self.note_editor.take() else {
+            return;
+        };
+        let note = editor.input.read(cx).value().trim().to_owned();
+        self.annotate(
+            editor.novel_id,
+            |annotation| annotation.note.clone_from(&note),
+            cx,
+        );
+    }
+
     fn author_label(novel: &Novel) -> String {
         match &novel.author {
             Author::Anonymous(name) => name.clone(),
@@ -122,7 +259,13 @@ impl ResultsTableDelegate {
             Some(ResultColumn::LatestChapter) => {
                 self.sort_by(descending, |novel| novel.latest_chapter.name.clone())
             }
-            Some(ResultColumn::Tags) | None => {}
+            Some(ResultColumn::ReadStatus) => {
+                self.sort_by(descending, |novel| novel.annotation.status)
+            }
+            Some(ResultColumn::Rating) => {
+                self.sort_by_missing_last(descending, |novel| novel.annotation.rating)
+            }
+            Some(ResultColumn::Tags | ResultColumn::Note | ResultColumn::Hidden) | None => {}
         }
     }
 
@@ -208,7 +351,7 @@ impl TableDelegate for ResultsTableDelegate {
         row_ix: usize,
         col_ix: usize,
         _: &mut Window,
-        _cx: &mut Context<TableState<Self>>,
+        cx: &mut Context<TableState<Self>>,
     ) -> impl IntoElement {
         let Some(novel) = self.novel_at(row_ix) else {
             return div().into_any_element();
@@ -216,22 +359,30 @@ impl TableDelegate for ResultsTableDelegate {
         match ResultColumn::ALL[col_ix] {
             ResultColumn::Title => Link::new(("novel-title-link", novel.title.id as u64))
                 .href(novel_url(novel.title.id))
-                .child(
-                    Label::new(novel.title.name.clone())
+                .child(match self.snippet(&novel.title.name, FIELD_SNIPPET_WIDTH) {
+                    Some(snippet) => highlighted(snippet, cx).font_medium().into_any_element(),
+                    None => Label::new(novel.title.name.clone())
                         .text_sm()
                         .font_medium()
-                        .truncate(),
-                )
-                .into_any_element(),
-            ResultColumn::Description => Label::new(novel.desc.clone())
-                .text_sm()
-                .truncate()
+                        .truncate()
+                        .into_any_element(),
+                })
                 .into_any_element(),
+            ResultColumn::Description => {
+                match self.snippet(&novel.desc, DESCRIPTION_SNIPPET_WIDTH) {
+                    Some(snippet) => highlighted(snippet, cx).into_any_element(),
+                    None => Label::new(novel.desc.clone())
+                        .text_sm()
+                        .truncate()
+                        .into_any_element(),
+                }
+            }
             ResultColumn::Author => {
-                let label = Label::new(Self::author_label(novel))
-                    .text_sm()
-                    .truncate()
-                    .into_any_element();
+                let name = Self::author_label(novel);
+                let label = match self.snippet(&name, FIELD_SNIPPET_WIDTH) {
+                    Some(snippet) => highlighted(snippet, cx).into_any_element(),
+                    None => Label::new(name).text_sm().truncate().into_any_element(),
+                };
                 match author_url(&novel.author) {
                     Some(url) => Link::new(author_link_id(novel))
                         .href(url)
@@ -251,10 +402,15 @@ impl TableDelegate for ResultsTableDelegate {
                 };
                 tag.into_any_element()
             }
-            ResultColumn::LatestChapter => Label::new(novel.latest_chapter.name.clone())
-                .text_sm()
-                .truncate()
-                .into_any_element(),
+            ResultColumn::LatestChapter => {
+                match self.snippet(&novel.latest_chapter.name, FIELD_SNIPPET_WIDTH) {
+                    Some(snippet) => highlighted(snippet, cx).into_any_element(),
+                    None => Label::new(novel.latest_chapter.name.clone())
+                        .text_sm()
+                        .truncate()
+                        .into_any_element(),
+                }
+            }
             ResultColumn::Tags => {
                 let mut tags = novel
                     .tags
@@ -273,6 +429,85 @@ impl TableDelegate for ResultsTableDelegate {
                     )
                     .into_any_element()
             }
+            ResultColumn::ReadStatus => {
+                let novel_id = novel.title.id;
+                let next = next_status(novel.annotation.status);
+                Button::new(("novel-read-status", novel_id as u64))
+                    .ghost()
+                    .xsmall()
+                    .label(novel.annotation.status.label())
+                    .on_click(cx.listener(move |table, _, _, cx| {
+                        table.delegate_mut().annotate(
+                            novel_id,
+                            |annotation| annotation.status = next,
+                            cx,
+                        )
+                    }))
+                    .into_any_element()
+            }
+            ResultColumn::Rating => {
+                let novel_id = novel.title.id;
+                let current = novel.annotation.rating;
+                Rating::new(("novel-rating", novel_id as u64))
+                    .xsmall()
+                    .max(5)
+                    .value(current.map_or(0, usize::from))
+                    .on_click(cx.listener(move |table, value: &usize, _, cx| {
+                        let rating = picked_rating(current, *value);
+                        table.delegate_mut().annotate(
+                            novel_id,
+                            |annotation| annotation.rating = rating,
+                            cx,
+                        )
+                    }))
+                    .into_any_element()
+            }
+            ResultColumn::Note => {
+                let novel_id = novel.title.id;
+                if let Some(editor) = self
+                    .note_editor
+                    .as_ref()
+                    .filter(|editor| editor.novel_id == novel_id)
+                {
+                    return Input::new(&editor.input).xsmall().into_any_element();
+                }
+                let note = novel.annotation.note.clone();
+                let empty = note.is_empty();
+                div()
+                    .id(("novel-note", novel_id as u64))
+                    .size_full()
+                    .cursor_pointer()
+                    .child(
+                        Label::new(if empty {
+                            "添加笔记".to_owned()
+                        } else {
+                            note
+                        })
+                        .text_sm()
+                        .truncate()
+                        .when(empty, |label| label.text_color(cx.theme().muted_foreground)),
+                    )
+                    .on_click(cx.listener(move |table, _, window, cx| {
+                        table.delegate_mut().edit_note(novel_id, window, cx)
+                    }))
+                    .into_any_element()
+            }
+            ResultColumn::Hidden => {
+                let novel_id = novel.title.id;
+                let hidden = novel.annotation.hidden;
+                Button::new(("novel-hidden", novel_id as u64))
+                    .ghost()
+                    .xsmall()
+                    .label(if hidden { "取消隐藏" } else { "隐藏" })
+                    .on_click(cx.listener(move |table, _, _, cx| {
+                        table.delegate_mut().annotate(
+                            novel_id,
+                            |annotation| annotation.hidden = !hidden,
+                            cx,
+                        )
+                    }))
+                    .into_any_element()
+            }
         }
     }
 
@@ -291,6 +526,51 @@ impl TableDelegate for ResultsTableDelegate {
     }
 }
 
+fn next_status(status: ReadStatus) -> ReadStatus {
+    let index = ReadStatus::ALL
+        .iter()
+        .position(|known| *known == status)
+        .unwrap_or_default();
+    ReadStatus::ALL[(index + 1) % ReadStatus::ALL.len()]
+}
+
+/// Clicking the current rating again clears it.
+fn picked_rating(current: Option<u8>, clicked: usize) -> Option<u8> {
+    let clicked = u8::try_from(clicked)
+        .ok()
+        .filter(|value| (1..=5).contains(value))?;
+    (current != Some(clicked)).then_some(clicked)
+}
+
+/// A snippet on one line with its matches on a highlighted background.
+fn highlighted(snippet: Snippet, cx: &App) -> Div {
+    let mut pieces: Vec<(String, bool)> = Vec::new();
+    let mut end = 0;
+    for range in &snippet.highlights {
+        if range.start > end {
+            pieces.push((snippet.text[end..range.start].to_owned(), false));
+        }
+        pieces.push((snippet.text[range.clone()].to_owned(), true));
+        end = range.end;
+    }
+    if end < snippet.text.len() {
+        pieces.push((snippet.text[end..].to_owned(), false));
+    }
+    h_flex()
+        .overflow_hidden()
+        .whitespace_nowrap()
+        .text_sm()
+        .children(pieces.into_iter().map(|(text, marked)| {
+            div()
+                .when(marked, |piece| {
+                    piece
+                        .bg(cx.theme().warning.opacity(0.3))
+                        .text_color(cx.theme().foreground)
+                })
+                .child(text)
+        }))
+}
+
 fn number_cell(value: Option<i32>) -> impl IntoElement {
     Label::new(
         value
@@ -407,6 +687,16 @@ mod tests {
         assert_eq!(delegate.novel_ids(), vec![3, 1, 2]);
     }
 
+    #[test]
+    fn status_cycles_and_repeated_rating_clears() {
+        assert_eq!(next_status(ReadStatus::Unread), ReadStatus::Reading);
+        assert_eq!(next_status(ReadStatus::Dropped), ReadStatus::Unread);
+        assert_eq!(picked_rating(None, 4), Some(4));
+        assert_eq!(picked_rating(Some(4), 2), Some(2));
+        assert_eq!(picked_rating(Some(4), 4), None);
+        assert_eq!(picked_rating(None, 0), None);
+    }
+
     #[test]
     fn author_link_id_includes_novel_and_author_ids() {
         let mut first = novel_with_read_count(1, Some(10));
@@ -444,6 +734,7 @@ mod tests {
             },
             tags: Default::default(),
             is_limit: false,
+            annotation: Default::default(),
         }
     }
 
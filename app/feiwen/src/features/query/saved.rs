@@ -0,0 +1,444 @@
+use duckdb::Connection;
+use fluent_bundle::FluentArgs;
+use gpui::{
+    AppContext as _, ClipboardItem, Context, ElementId, Entity, InteractiveElement as _,
+    IntoElement, ParentElement as _, PathPromptOptions, StatefulInteractiveElement as _,
+    Styled as _, Task, Window, div, prelude::FluentBuilder as _,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, IconName, StyledExt as _,
+    alert::Alert,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    scroll::ScrollableElement as _,
+    v_flex,
+};
+use tracing::{Level, event};
+
+use super::QueryView;
+use crate::{
+    errors::FeiwenResult,
+    foundation::{I18n, assets::IconName as FeiwenIconName},
+    store::{
+        DbConn, database,
+        query::{QuerySpec, count_records},
+        saved_query::SavedQuery,
+    },
+};
+
+const EXPORT_FILE_NAME: &str = "feiwen-queries.json";
+
+struct SavedQueryEntry {
+    query: SavedQuery,
+    /// `None` when counting failed; the query stays runnable.
+    count: Option<usize>,
+}
+
+/// The refreshed list after an operation, and the query it produced.
+struct SavedQueryLoad {
+    entries: Vec<SavedQueryEntry>,
+    select: Option<i32>,
+}
+
+/// The saved query sidebar. Every operation runs in the background and
+/// reloads the list with fresh match counts; one runs at a time.
+pub(super) struct SavedQueries {
+    name_input: Entity<InputState>,
+    entries: Vec<SavedQueryEntry>,
+    selected: Option<i32>,
+    problem: Option<String>,
+    task: Option<Task<()>>,
+    reload_pending: bool,
+}
+
+impl SavedQueries {
+    pub(super) fn new(window: &mut Window, cx: &mut Context<QueryView>) -> Self {
+        let placeholder = cx.global::<I18n>().t("query-saved-name-placeholder");
+        Self {
+            name_input: cx.new(|cx| InputState::new(window, cx).placeholder(placeholder)),
+            entries: Vec::new(),
+            selected: None,
+            problem: None,
+            task: None,
+            reload_pending: false,
+        }
+    }
+
+    /// Recounts every saved query, for example after a fetch changed the
+    /// stored novels.
+    pub(super) fn reload(&mut self, cx: &mut Context<QueryView>) {
+        if self.task.is_some() {
+            self.reload_pending = true;
+            return;
+        }
+        self.run(|_| Ok(None), cx);
+    }
+
+    pub(super) fn save_new(&mut self, spec: QuerySpec, cx: &mut Context<QueryView>) {
+        let name = self.name(cx);
+        self.run(
+            move |conn| SavedQuery::create(conn, &name, &spec).map(|query| Some(query.id)),
+            cx,
+        );
+    }
+
+    pub(super) fn overwrite_selected(&mut self, spec: QuerySpec, cx: &mut Context<QueryView>) {
+        let Some(id) = self.selected else {
+            return;
+        };
+        self.run(
+            move |conn| SavedQuery::replace_spec(conn, id, &spec).map(|()| Some(id)),
+            cx,
+        );
+    }
+
+    /// Selects a query and puts its name in the name field; the caller
+    /// loads the returned spec into the builder.
+    pub(super) fn select(
+        &mut self,
+        id: i32,
+        window: &mut Window,
+        cx: &mut Context<QueryView>,
+    ) -> Option<QuerySpec> {
+        let query = &self
+            .entries
+            .iter()
+            .find(|entry| entry.query.id == id)?
+            .query;
+        let (name, spec) = (query.name.clone(), query.spec.clone());
+        self.selected = Some(id);
+        self.name_input
+            .update(cx, |input, cx| input.set_value(name, window, cx));
+        Some(spec)
+    }
+
+    /// The selected query's name and spec.
+    pub(super) fn selected_query(&self) -> Option<(String, QuerySpec)> {
+        self.entries
+            .iter()
+            .find(|entry| Some(entry.query.id) == self.selected)
+            .map(|entry| (entry.query.name.clone(), entry.query.spec.clone()))
+    }
+
+    fn name(&self, cx: &Context<QueryView>) -> String {
+        self.name_input.read(cx).value().trim().to_string()
+    }
+
+    fn rename_selected(&mut self, cx: &mut Context<QueryView>) {
+        let Some(id) = self.selected else {
+            return;
+        };
+        let name = self.name(cx);
+        self.run(
+            move |conn| SavedQuery::rename(conn, id, &name).map(|()| Some(id)),
+            cx,
+        );
+    }
+
+    fn delete(&mut self, id: i32, cx: &mut Context<QueryView>) {
+        self.run(move |conn| SavedQuery::delete(conn, id).map(|()| None), cx);
+    }
+
+    fn copy_selected(&self, cx: &mut Context<QueryView>) {
+        let Some(entry) = self
+            .entries
+            .iter()
+            .find(|entry| Some(entry.query.id) == self.selected)
+        else {
+            return;
+        };
+        let text = SavedQuery::export(std::slice::from_ref(&entry.query));
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
+    }
+
+    fn export(&mut self, cx: &mut Context<QueryView>) {
+        let directory = dirs_next::download_dir()
+            .or_else(dirs_next::home_dir)
+            .unwrap_or_else(std::env::temp_dir);
+        let prompt = cx.prompt_for_new_path(&directory, Some(EXPORT_FILE_NAME));
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = prompt.await else {
+                return;
+            };
+            let _ = this.update(cx, |view, cx| {
+                view.saved.run(
+                    move |conn| {
+                        let text = SavedQuery::export(&SavedQuery::list(conn)?);
+                        std::fs::write(&path, text)?;
+                        Ok(None)
+                    },
+                    cx,
+                );
+            });
+        })
+        .detach();
+    }
+
+    fn import(&mut self, cx: &mut Context<QueryView>) {
+        let prompt = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: None,
+        });
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(paths))) = prompt.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let _ = this.update(cx, |view, cx| {
+                view.saved.run(
+                    move |conn| {
+                        let text = std::fs::read_to_string(&path)?;
+                        let imported = SavedQuery::import(conn, &text)?;
+                        Ok(imported.first().map(|query| query.id))
+                    },
+                    cx,
+                );
+            });
+        })
+        .detach();
+    }
+
+    fn run<F>(&mut self, operation: F, cx: &mut Context<QueryView>)
+    where
+        F: FnOnce(&mut Connection) -> FeiwenResult<Option<i32>> + Send + 'static,
+    {
+        if self.task.is_some() {
+            event!(
+                Level::INFO,
+                "ignored saved query operation while one is running"
+            );
+            return;
+        }
+        let pool = match database::ready_pool(cx) {
+            Ok(pool) => pool,
+            Err(problem) => {
+                event!(Level::INFO, error = %problem, "saved query database gate closed");
+                return;
+            }
+        };
+        let task = cx.spawn(async move |this, cx| {
+            let result = cx
+                .background_spawn(async move { apply(&pool, operation) })
+                .await;
+            let _ = this.update(cx, |view, cx| view.saved.finish(result, cx));
+        });
+        self.task = Some(task);
+        cx.notify();
+    }
+
+    fn finish(&mut self, result: FeiwenResult<SavedQueryLoad>, cx: &mut Context<QueryView>) {
+        self.task = None;
+        match result {
+            Ok(load) => {
+                self.entries = load.entries;
+                self.selected = load
+                    .select
+                    .or(self.selected)
+                    .filter(|id| self.entries.iter().any(|entry| entry.query.id == *id));
+                self.problem = None;
+            }
+            Err(error) => {
+                event!(Level::ERROR, error = %error, "saved query operation failed");
+                self.problem = Some(error.to_string());
+            }
+        }
+        if std::mem::take(&mut self.reload_pending) {
+            self.reload(cx);
+        }
+        cx.notify();
+    }
+
+    pub(super) fn render(&self, exporting: bool, cx: &mut Context<QueryView>) -> impl IntoElement {
+        let i18n = cx.global::<I18n>();
+        let busy = self.task.is_some() || !database::is_ready(cx);
+        let has_selection = self.selected.is_some();
+        let title = i18n.t("query-saved-title");
+        let description = i18n.t("query-saved-description");
+        let save = i18n.t("query-saved-save");
+        let overwrite = i18n.t("query-saved-overwrite");
+        let rename = i18n.t("query-saved-rename");
+        let copy = i18n.t("query-saved-copy");
+        let import = i18n.t("query-saved-import");
+        let export = i18n.t("query-saved-export");
+        let export_results = i18n.t("query-saved-export-results");
+        let empty = i18n.t("query-saved-empty");
+        let error_title = i18n.t("query-error-title");
+        let counting = i18n.t("query-saved-count-unknown");
+        let rows = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let count = entry.count.map_or_else(
+                    || counting.clone(),
+                    |count| {
+                        let mut args = FluentArgs::new();
+                        args.set("count", count);
+                        i18n.t_with_args("query-saved-count", &args)
+                    },
+                );
+                (entry.query.id, entry.query.name.clone(), count)
+            })
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .size_full()
+            .gap_3()
+            .p_3()
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(Label::new(title).font_semibold())
+                    .child(
+                        Label::new(description)
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground),
+                    ),
+            )
+            .child(Input::new(&self.name_input))
+            .child(
+                h_flex()
+                    .flex_wrap()
+                    .gap_1()
+                    .child(
+                        Button::new("query-saved-save")
+                            .icon(IconName::Plus)
+                            .label(save)
+                            .disabled(busy)
+                            .on_click(cx.listener(|this, _, _, cx| this.save_query(cx))),
+                    )
+                    .child(
+                        Button::new("query-saved-overwrite")
+                            .ghost()
+                            .label(overwrite)
+                            .disabled(busy || !has_selection)
+                            .on_click(cx.listener(|this, _, _, cx| this.overwrite_saved_query(cx))),
+                    )
+                    .child(
+                        Button::new("query-saved-rename")
+                            .ghost()
+                            .label(rename)
+                            .disabled(busy || !has_selection)
+                            .on_click(cx.listener(|this, _, _, cx| this.saved.rename_selected(cx))),
+                    )
+                    .child(
+                        Button::new("query-saved-copy")
+                            .ghost()
+                            .label(copy)
+                            .disabled(!has_selection)
+                            .on_click(cx.listener(|this, _, _, cx| this.saved.copy_selected(cx))),
+                    )
+                    .child(
+                        Button::new("query-saved-import")
+                            .ghost()
+                            .label(import)
+                            .disabled(busy)
+                            .on_click(cx.listener(|this, _, _, cx| this.saved.import(cx))),
+                    )
+                    .child(
+                        Button::new("query-saved-export")
+                            .ghost()
+                            .label(export)
+                            .disabled(busy || self.entries.is_empty())
+                            .on_click(cx.listener(|this, _, _, cx| this.saved.export(cx))),
+                    )
+                    .child(
+                        Button::new("query-saved-export-results")
+                            .ghost()
+                            .label(export_results)
+                            .disabled(exporting || !has_selection || !database::is_ready(cx))
+                            .on_click(cx.listener(|this, _, _, cx| this.export_saved_query(cx))),
+                    ),
+            )
+            .when_some(self.problem.clone(), |this, problem| {
+                this.child(Alert::error("query-saved-problem", problem).title(error_title))
+            })
+            .child(
+                div()
+                    .flex_1()
+                    .min_h_0()
+                    .overflow_y_scrollbar()
+                    .when(rows.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .py_4()
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(empty),
+                        )
+                    })
+                    .children(rows.into_iter().map(|(id, name, count)| {
+                        let selected = self.selected == Some(id);
+                        h_flex()
+                            .id(ElementId::NamedInteger("query-saved-row".into(), id as u64))
+                            .justify_between()
+                            .gap_2()
+                            .px_2()
+                            .py_1()
+                            .rounded_md()
+                            .cursor_pointer()
+                            .when(selected, |this| {
+                                this.bg(cx.theme().tokens.accent.background.opacity(0.35))
+                            })
+                            .hover(|style| {
+                                style.bg(cx.theme().tokens.accent.background.opacity(0.18))
+                            })
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.run_saved_query(id, window, cx);
+                            }))
+                            .child(
+                                v_flex().min_w_0().child(Label::new(name).text_sm()).child(
+                                    Label::new(count)
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground),
+                                ),
+                            )
+                            .child(
+                                Button::new(ElementId::NamedInteger(
+                                    "query-saved-delete".into(),
+                                    id as u64,
+                                ))
+                                .ghost()
+                                .icon(FeiwenIconName::Trash)
+                                .disabled(busy)
+                                .on_click(cx.listener(
+                                    move |this, _, _, cx| {
+                                        cx.stop_propagation();
+                                        this.saved.delete(id, cx);
+                                    },
+                                )),
+                            )
+                    })),
+            )
+    }
+}
+
+fn apply<F>(pool: &DbConn, operation: F) -> FeiwenResult<SavedQueryLoad>
+where
+    F: FnOnce(&mut Connection) -> FeiwenResult<Option<i32>>,
+{
+    let mut conn = pool.get()?;
+    let select = operation(&mut conn)?;
+    let entries = SavedQuery::list(&conn)?
+        .into_iter()
+        .map(|query| {
+            let count = count_records(&conn, &query.spec)
+                .inspect_err(|error| {
+                    event!(
+                        Level::WARN,
+                        error = %error,
+                        name = %query.name,
+                        "saved query count failed"
+                    );
+                })
+                .ok();
+            SavedQueryEntry { query, count }
+        })
+        .collect();
+    Ok(SavedQueryLoad { entries, select })
+}
@@ -0,0 +1,226 @@
+use gpui::{
+    AppContext as _, Context, ElementId, Entity, IntoElement, ParentElement as _, Styled as _,
+    Subscription, Window, div, prelude::FluentBuilder as _,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    v_flex,
+};
+
+use super::{QueryView, advanced::QueryOptions};
+use crate::{
+    foundation::I18n,
+    store::{
+        query::QuerySpec,
+        query_language::{self, AuthorNames, Completion, ParseError},
+    },
+};
+
+const SUGGESTION_LIMIT: usize = 8;
+
+/// The query language input above the builder. Applying it replaces the
+/// builder's query; the builder's query can be shown here as text.
+pub(super) struct QueryTextBar {
+    input: Entity<InputState>,
+    options: QueryOptions,
+    authors: AuthorNames,
+    /// The last failed parse, with the text it failed on.
+    problem: Option<(String, ParseError)>,
+    completion: Option<Completion>,
+    suggestions: Vec<(String, String)>,
+}
+
+impl QueryTextBar {
+    pub(super) fn new(
+        options: QueryOptions,
+        window: &mut Window,
+        cx: &mut Context<QueryView>,
+    ) -> (Self, Subscription) {
+        let placeholder = cx.global::<I18n>().t("query-text-placeholder");
+        let input = cx.new(|cx| InputState::new(window, cx).placeholder(placeholder));
+        let subscription = cx.subscribe_in(
+            &input,
+            window,
+            |view, _, event: &InputEvent, window, cx| match event {
+                InputEvent::Change => {
+                    view.query_text.refresh_completion(cx);
+                    cx.notify();
+                }
+                InputEvent::PressEnter { .. } => view.apply_query_text(window, cx),
+                _ => {}
+            },
+        );
+        let authors = options.author_names();
+        let bar = Self {
+            input,
+            options,
+            authors,
+            problem: None,
+            completion: None,
+            suggestions: Vec::new(),
+        };
+        (bar, subscription)
+    }
+
+    pub(super) fn update_options(&mut self, options: QueryOptions) {
+        self.authors = options.author_names();
+        self.options = options;
+    }
+
+    /// Parses the input, keeping the error for display when it fails.
+    pub(super) fn parse(&mut self, cx: &Context<QueryView>) -> Option<QuerySpec> {
+        let text = self.input.read(cx).value().to_string();
+        match query_language::parse(&text, &self.authors) {
+            Ok(spec) => {
+                self.problem = None;
+                Some(spec)
+            }
+            Err(error) => {
+                self.problem = Some((text, error));
+                None
+            }
+        }
+    }
+
+    pub(super) fn show(
+        &mut self,
+        spec: &QuerySpec,
+        window: &mut Window,
+        cx: &mut Context<QueryView>,
+    ) {
+        let text = query_language::format(spec, &self.authors);
+        self.set_text(text, window, cx);
+    }
+
+    fn refresh_completion(&mut self, cx: &Context<QueryView>) {
+        let input = self.input.read(cx);
+        let text = input.value().to_string();
+        if self
+            .problem
+            .as_ref()
+            .is_some_and(|(failed, _)| *failed != text)
+        {
+            self.problem = None;
+        }
+        self.completion = query_language::completion_at(&text, input.cursor());
+        self.suggestions = self
+            .completion
+            .as_ref()
+            .map(|completion| self.options.suggestions(completion, SUGGESTION_LIMIT))
+            .unwrap_or_default();
+    }
+
+    /// Replaces the value being typed with a suggestion.
+    fn accept(&mut self, index: usize, window: &mut Window, cx: &mut Context<QueryView>) {
+        let (Some(completion), Some((_, replacement))) =
+            (&self.completion, self.suggestions.get(index))
+        else {
+            return;
+        };
+        let text = self.input.read(cx).value().to_string();
+        let Some(before) = text.get(..completion.span.start) else {
+            return;
+        };
+        let after = &text[completion.span.end..];
+        let text = format!("{before}{replacement}{after}");
+        self.set_text(text, window, cx);
+    }
+
+    fn set_text(&mut self, text: String, window: &mut Window, cx: &mut Context<QueryView>) {
+        self.input
+            .update(cx, |input, cx| input.set_value(text, window, cx));
+        self.problem = None;
+        self.completion = None;
+        self.suggestions.clear();
+        cx.notify();
+    }
+
+    pub(super) fn render(&self, can_apply: bool, cx: &mut Context<QueryView>) -> impl IntoElement {
+        let i18n = cx.global::<I18n>();
+        let apply = i18n.t("query-text-apply");
+        let from_builder = i18n.t("query-text-from-builder");
+
+        v_flex()
+            .gap_1()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(div().flex_1().child(Input::new(&self.input)))
+                    .child(
+                        Button::new("query-text-apply")
+                            .label(apply)
+                            .disabled(!can_apply)
+                            .on_click(
+                                cx.listener(|this, _, window, cx| {
+                                    this.apply_query_text(window, cx)
+                                }),
+                            ),
+                    )
+                    .child(
+                        Button::new("query-text-from-builder")
+                            .ghost()
+                            .label(from_builder)
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.show_query_text(window, cx)),
+                            ),
+                    ),
+            )
+            .when_some(self.problem.as_ref(), |this, (text, error)| {
+                this.child(render_problem(text, error, cx))
+            })
+            .when(!self.suggestions.is_empty(), |this| {
+                this.child(
+                    h_flex()
+                        .flex_wrap()
+                        .gap_1()
+                        .children(self.suggestions.iter().enumerate().map(
+                            |(index, (label, _))| {
+                                Button::new(ElementId::NamedInteger(
+                                    "query-text-suggestion".into(),
+                                    index as u64,
+                                ))
+                                .ghost()
+                                .small()
+                                .label(label.clone())
+                                .on_click(cx.listener(
+                                    move |this, _, window, cx| {
+                                        this.query_text.accept(index, window, cx)
+                                    },
+                                ))
+                            },
+                        )),
+                )
+            })
+    }
+}
+
+/// The error message followed by the query with the failing part marked.
+fn render_problem(text: &str, error: &ParseError, cx: &Context<QueryView>) -> impl IntoElement {
+    let span = error.span.clone();
+    let before = text.get(..span.start).unwrap_or(text).to_owned();
+    let marked = text.get(span.clone()).unwrap_or_default();
+    // A missing value has an empty span; mark the gap where it belongs.
+    let marked = if marked.is_empty() { " " } else { marked }.to_owned();
+    let after = text.get(span.end..).unwrap_or_default().to_owned();
+
+    h_flex()
+        .gap_2()
+        .text_xs()
+        .child(div().text_color(cx.theme().danger).child(error.to_string()))
+        .child(
+            h_flex()
+                .font_family(cx.theme().mono_font_family.clone())
+                .text_color(cx.theme().muted_foreground)
+                .child(before)
+                .child(
+                    div()
+                        .bg(cx.theme().danger.opacity(0.2))
+                        .text_color(cx.theme().danger)
+                        .child(marked),
+                )
+                .child(after),
+        )
+}
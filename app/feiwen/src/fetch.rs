@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{fmt, time::Duration};
 
 use reqwest::Client;
 
@@ -11,6 +11,7 @@ use tracing::{Level, event};
 use self::parse_novel::parse_page;
 
 mod get_content;
+pub(crate) mod limiter;
 pub(crate) mod parse_novel;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -30,6 +31,69 @@ impl FetchErrorKind {
             FetchErrorKind::Other => "other",
         }
     }
+
+    pub(crate) const ALL: [Self; 4] = [Self::Database, Self::Network, Self::Parse, Self::Other];
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.label() == value)
+    }
+
+    /// A database error stops the whole run; retrying other pages would
+    /// fail the same way.
+    pub(crate) fn is_fatal(self) -> bool {
+        matches!(self, Self::Database)
+    }
+
+    pub(crate) fn retry_policy(self) -> RetryPolicy {
+        match self {
+            Self::Database => RetryPolicy::NEVER,
+            Self::Network => RetryPolicy {
+                attempts: 5,
+                base: Duration::from_secs(1),
+                max: Duration::from_secs(60),
+            },
+            // Block and login pages mostly show up while the site throttles
+            // us, so these wait longer before trying again.
+            Self::Parse => RetryPolicy {
+                attempts: 3,
+                base: Duration::from_secs(10),
+                max: Duration::from_secs(120),
+            },
+            Self::Other => RetryPolicy {
+                attempts: 2,
+                base: Duration::from_secs(2),
+                max: Duration::from_secs(2),
+            },
+        }
+    }
+}
+
+/// How many times a page is tried for one kind of error, and how long to
+/// wait in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RetryPolicy {
+    /// Tries in total, the first one included.
+    pub(crate) attempts: u32,
+    pub(crate) base: Duration,
+    pub(crate) max: Duration,
+}
+
+impl RetryPolicy {
+    const NEVER: Self = Self {
+        attempts: 1,
+        base: Duration::ZERO,
+        max: Duration::ZERO,
+    };
+
+    /// The wait after the `attempt`-th try failed (counting from 1), which
+    /// doubles each time up to `max`; `None` once the tries are used up.
+    pub(crate) fn backoff(self, attempt: u32) -> Option<Duration> {
+        (attempt < self.attempts).then(|| {
+            self.base
+                .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+                .min(self.max)
+        })
+    }
 }
 
 impl fmt::Display for FetchErrorKind {
@@ -101,6 +165,8 @@ pub(crate) async fn fetch_page(
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::{FetchErrorKind, FetchPageError};
 
     #[test]
@@ -115,4 +181,24 @@ mod tests {
         assert_eq!(err.kind, FetchErrorKind::Parse);
         assert_eq!(err.message, "parse failed");
     }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap_and_stops_after_the_last_attempt() {
+        let network = FetchErrorKind::Network.retry_policy();
+
+        assert_eq!(network.backoff(1), Some(Duration::from_secs(1)));
+        assert_eq!(network.backoff(2), Some(Duration::from_secs(2)));
+        assert_eq!(network.backoff(4), Some(Duration::from_secs(8)));
+        assert_eq!(network.backoff(5), None);
+        assert_eq!(
+            FetchErrorKind::Parse.retry_policy().backoff(2),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(FetchErrorKind::Database.retry_policy().backoff(1), None);
+        assert!(FetchErrorKind::Database.is_fatal());
+        assert_eq!(
+            FetchErrorKind::parse("network"),
+            Some(FetchErrorKind::Network)
+        );
+    }
 }
@@ -0,0 +1,76 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Spaces out requests to the same host, however many pages are in flight.
+pub(crate) struct HostRateLimiter {
+    interval: Duration,
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostRateLimiter {
+    pub(crate) fn per_minute(requests: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests.max(1),
+            next_slot: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Books the next free slot for `url`'s host and returns how long to
+    /// wait for it.
+    pub(crate) fn reserve(&self, url: &str) -> Duration {
+        let now = Instant::now();
+        self.reserve_at(&host(url), now)
+            .saturating_duration_since(now)
+    }
+
+    fn reserve_at(&self, host: &str, now: Instant) -> Instant {
+        let mut next_slot = self
+            .next_slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let slot = next_slot
+            .get(host)
+            .copied()
+            .filter(|slot| *slot > now)
+            .unwrap_or(now);
+        next_slot.insert(host.to_owned(), slot + self.interval);
+        slot
+    }
+}
+
+/// Unparsable urls share one bucket, so they are still limited.
+fn host(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_to_one_host_queue_up_while_other_hosts_go_at_once() {
+        let limiter = HostRateLimiter::per_minute(30);
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve_at("a.test", now), now);
+        assert_eq!(
+            limiter.reserve_at("a.test", now),
+            now + Duration::from_secs(2)
+        );
+        assert_eq!(
+            limiter.reserve_at("a.test", now),
+            now + Duration::from_secs(4)
+        );
+        assert_eq!(limiter.reserve_at("b.test", now), now);
+        // A host that has been quiet for a while is not held back.
+        let later = now + Duration::from_secs(60);
+        assert_eq!(limiter.reserve_at("a.test", later), later);
+        assert_eq!(host("https://a.test/books?page=2"), "a.test");
+    }
+}
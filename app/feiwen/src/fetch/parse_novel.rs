@@ -12,6 +12,7 @@ use scraper::{Html, Selector};
 use crate::{
     errors::{FeiwenError, FeiwenResult},
     store::{
+        annotation::Annotation,
         service::Novel,
         types::{Author, Title},
     },
@@ -80,6 +81,7 @@ fn parse_novel(doc: Html) -> FeiwenResult<Novel> {
         count,
         tags,
         is_limit,
+        annotation: Annotation::default(),
     })
 }
 
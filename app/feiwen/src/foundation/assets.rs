@@ -11,7 +11,9 @@ define_lucide_icons!(
         Database => "database",
         EyeOff => "eye-off",
         FileText => "file-text",
+        History => "history",
         Info => "info",
+        Layers => "layers",
         Link => "link",
         List => "list",
         LoaderCircle => "loader-circle",
@@ -20,6 +22,7 @@ define_lucide_icons!(
         RotateCcw => "rotate-ccw",
         Search => "search",
         Settings => "settings",
+        Timer => "timer",
         Trash => "trash",
         TriangleAlert => "triangle-alert",
     }
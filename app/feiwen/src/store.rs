@@ -1,4 +1,7 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
 
 use crate::{
     APP_NAME,
@@ -8,9 +11,17 @@ use duckdb::DuckdbConnectionManager;
 use r2d2::Pool;
 use tracing::{Level, event};
 
+pub(crate) mod analytics;
+pub(crate) mod annotation;
 pub(crate) mod catalog;
 pub(crate) mod database;
+pub(crate) mod export;
+pub(crate) mod fetch_run;
+pub(crate) mod history;
 pub(crate) mod query;
+pub(crate) mod query_language;
+pub(crate) mod saved_query;
+pub(crate) mod search_index;
 pub(crate) mod service;
 pub(crate) mod types;
 
@@ -29,9 +40,18 @@ pub(crate) fn establish_connection_at(url_path: &Path) -> FeiwenResult<DbConn> {
     event!(Level::INFO, db_path = %url_path.display(), "opening feiwen database");
     let created = check_data_file(url_path)?;
     let pool = open_connection_at(url_path)?;
-    let conn = pool.get()?;
+    let mut conn = pool.get()?;
     initialize_schema(&conn)?;
-    event!(Level::INFO, db_path = %url_path.display(), created, "database ready");
+    let abandoned_runs = fetch_run::FetchRunRecord::interrupt_abandoned(&conn)?;
+    let reindexed = search_index::rebuild_if_stale(&mut conn)?;
+    event!(
+        Level::INFO,
+        db_path = %url_path.display(),
+        created,
+        abandoned_runs,
+        reindexed,
+        "database ready"
+    );
     Ok(pool)
 }
 
@@ -43,29 +63,73 @@ pub(crate) fn open_connection_at(url_path: &Path) -> FeiwenResult<DbConn> {
     Ok(pool)
 }
 
+/// The tables every feiwen database has had. A file without them is not a
+/// feiwen database, so it is never upgraded.
+const CATALOG_TABLES: &[&str] = &["novel", "tag", "novel_tag"];
+/// Tables added by later features, which `initialize_schema` adds to an
+/// older database.
+const FEATURE_TABLES: &[&str] = &[
+    "saved_query",
+    "novel_snapshot",
+    "novel_annotation",
+    "fetch_run",
+    "fetch_run_page",
+    "novel_search_token",
+    "novel_search_pinyin",
+];
+const SCHEMA_INDEXES: &[&str] = &[
+    "idx_novel_is_limit",
+    "idx_novel_reply_count",
+    "idx_novel_tag_tag_id_novel_id",
+    "idx_novel_search_token_novel_id",
+    "idx_novel_search_pinyin_novel_id",
+];
+
+/// Brings a database written by an older version up to the current schema
+/// and indexes the novels it already holds. Returns whether it was a feiwen
+/// database to upgrade at all.
+pub(crate) fn upgrade_schema(pool: &DbConn) -> FeiwenResult<bool> {
+    let mut conn = pool.get()?;
+    let tables = schema_names(
+        &conn,
+        "SELECT table_name FROM information_schema.tables WHERE table_schema = 'main'",
+    )?;
+    if !CATALOG_TABLES.iter().all(|table| tables.contains(*table)) {
+        return Ok(false);
+    }
+    initialize_schema(&conn)?;
+    let reindexed = search_index::rebuild_if_stale(&mut conn)?;
+    event!(Level::INFO, reindexed, "database schema upgraded");
+    Ok(true)
+}
+
 pub(crate) fn validate_schema(pool: &DbConn) -> FeiwenResult<()> {
     let conn = pool.get()?;
-    let table_count = conn.query_row(
-        "SELECT count(*) FROM information_schema.tables \
-         WHERE table_schema = 'main' AND table_name IN ('novel', 'tag', 'novel_tag')",
-        [],
-        |row| row.get::<_, i64>(0),
+    let tables = schema_names(
+        &conn,
+        "SELECT table_name FROM information_schema.tables WHERE table_schema = 'main'",
     )?;
-    let index_count = conn.query_row(
-        "SELECT count(*) FROM duckdb_indexes() \
-         WHERE schema_name = 'main' AND index_name IN (\
-             'idx_novel_is_limit', \
-             'idx_novel_reply_count', \
-             'idx_novel_tag_tag_id_novel_id'\
-         )",
-        [],
-        |row| row.get::<_, i64>(0),
+    let indexes = schema_names(
+        &conn,
+        "SELECT index_name FROM duckdb_indexes() WHERE schema_name = 'main'",
     )?;
-    if table_count != 3 || index_count != 3 {
+    let missing = CATALOG_TABLES
+        .iter()
+        .chain(FEATURE_TABLES)
+        .filter(|table| !tables.contains(**table))
+        .chain(
+            SCHEMA_INDEXES
+                .iter()
+                .filter(|index| !indexes.contains(**index)),
+        )
+        .copied()
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
         return Err(std::io::Error::new(
             std::io::ErrorKind::InvalidData,
             format!(
-                "database schema is incomplete: expected 3 tables and 3 indexes, found {table_count} tables and {index_count} indexes"
+                "database schema is incomplete: missing {}",
+                missing.join(", ")
             ),
         )
         .into());
@@ -73,6 +137,14 @@ pub(crate) fn validate_schema(pool: &DbConn) -> FeiwenResult<()> {
     Ok(())
 }
 
+fn schema_names(conn: &duckdb::Connection, query: &str) -> FeiwenResult<HashSet<String>> {
+    let mut statement = conn.prepare(query)?;
+    let names = statement
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<_, _>>()?;
+    Ok(names)
+}
+
 pub(crate) fn get_data_url() -> FeiwenResult<PathBuf> {
     let data_path = dirs_next::config_dir()
         .ok_or(FeiwenError::DbPath)?
@@ -122,6 +194,75 @@ pub(crate) fn initialize_schema(conn: &duckdb::Connection) -> FeiwenResult<()> {
         CREATE INDEX IF NOT EXISTS idx_novel_is_limit ON novel(is_limit);
         CREATE INDEX IF NOT EXISTS idx_novel_reply_count ON novel(reply_count);
         CREATE INDEX IF NOT EXISTS idx_novel_tag_tag_id_novel_id ON novel_tag(tag_id, novel_id);
+
+        CREATE SEQUENCE IF NOT EXISTS saved_query_id_seq;
+
+        CREATE TABLE IF NOT EXISTS saved_query (
+            id INTEGER PRIMARY KEY DEFAULT nextval('saved_query_id_seq'),
+            name VARCHAR NOT NULL,
+            spec VARCHAR NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS novel_snapshot (
+            novel_id INTEGER NOT NULL,
+            fetched_at BIGINT NOT NULL,
+            word_count INTEGER NOT NULL,
+            read_count INTEGER,
+            reply_count INTEGER,
+            latest_chapter_id INTEGER NOT NULL,
+            latest_chapter_name VARCHAR NOT NULL,
+            PRIMARY KEY (novel_id, fetched_at)
+        );
+
+        CREATE TABLE IF NOT EXISTS novel_annotation (
+            novel_id INTEGER PRIMARY KEY,
+            status VARCHAR NOT NULL DEFAULT 'unread',
+            rating INTEGER CHECK (rating BETWEEN 1 AND 5),
+            note VARCHAR NOT NULL DEFAULT '',
+            hidden BOOLEAN NOT NULL DEFAULT FALSE
+        );
+
+        CREATE SEQUENCE IF NOT EXISTS fetch_run_id_seq;
+
+        CREATE TABLE IF NOT EXISTS fetch_run (
+            id INTEGER PRIMARY KEY DEFAULT nextval('fetch_run_id_seq'),
+            url VARCHAR NOT NULL,
+            start_page INTEGER NOT NULL,
+            end_page INTEGER NOT NULL,
+            concurrency INTEGER NOT NULL,
+            requests_per_minute INTEGER NOT NULL,
+            status VARCHAR NOT NULL,
+            started_at BIGINT NOT NULL,
+            updated_at BIGINT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS fetch_run_page (
+            run_id INTEGER NOT NULL,
+            page INTEGER NOT NULL,
+            succeeded BOOLEAN NOT NULL,
+            attempts INTEGER NOT NULL,
+            inserted INTEGER,
+            error_kind VARCHAR,
+            message VARCHAR NOT NULL DEFAULT '',
+            PRIMARY KEY (run_id, page)
+        );
+
+        CREATE TABLE IF NOT EXISTS novel_search_token (
+            novel_id INTEGER NOT NULL,
+            field VARCHAR NOT NULL,
+            token VARCHAR NOT NULL,
+            hits INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS novel_search_pinyin (
+            novel_id INTEGER NOT NULL,
+            field VARCHAR NOT NULL,
+            full_key VARCHAR NOT NULL,
+            initials VARCHAR NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_novel_search_token_novel_id ON novel_search_token(novel_id);
+        CREATE INDEX IF NOT EXISTS idx_novel_search_pinyin_novel_id ON novel_search_pinyin(novel_id);
         "#,
     )?;
     event!(Level::INFO, "feiwen duckdb schema ready");
@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+
+use duckdb::Connection;
+
+use crate::{
+    errors::FeiwenResult,
+    store::query::{FilterExpr, NovelRecord, QuerySpec, for_each_record},
+};
+
+/// How many of the most common tags the co-occurrence matrix covers.
+pub(crate) const MATRIX_TAGS: usize = 20;
+/// How many authors the output ranking keeps.
+pub(crate) const TOP_AUTHORS: usize = 20;
+
+/// The shape of the novels a filter matches: which tags they carry, which
+/// tags go together, how long and how discussed each tag's novels are, and
+/// who wrote the most.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct TagAnalytics {
+    pub(crate) novels: usize,
+    /// Novels without any tag.
+    pub(crate) untagged: usize,
+    /// Every tag the novels carry, most common first.
+    pub(crate) tags: Vec<TagStats>,
+    /// How many novels carry both of two of the first [`Self::matrix_tags`],
+    /// row by row. A tag is not counted against itself, so the diagonal is
+    /// zero.
+    pub(crate) co_occurrence: Vec<u64>,
+    /// The authors with the most words among the novels, most first.
+    pub(crate) authors: Vec<AuthorOutput>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TagStats {
+    pub(crate) name: String,
+    pub(crate) novels: usize,
+    pub(crate) words: Distribution,
+    /// `None` when none of the tag's novels has a reply count.
+    pub(crate) replies: Option<Distribution>,
+}
+
+/// The quartiles of a set of counts, each the nearest-rank value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Distribution {
+    pub(crate) min: i32,
+    pub(crate) lower_quartile: i32,
+    pub(crate) median: i32,
+    pub(crate) upper_quartile: i32,
+    pub(crate) max: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AuthorOutput {
+    pub(crate) author_id: Option<i32>,
+    pub(crate) name: String,
+    pub(crate) novels: usize,
+    pub(crate) words: i64,
+}
+
+impl TagAnalytics {
+    /// The tags the co-occurrence matrix covers, in its row order.
+    pub(crate) fn matrix_tags(&self) -> &[TagStats] {
+        &self.tags[..self.tags.len().min(MATRIX_TAGS)]
+    }
+}
+
+impl Distribution {
+    /// `None` for no values.
+    fn of(mut values: Vec<i32>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_unstable();
+        let rank = |fraction: f64| {
+            let rank = (fraction * values.len() as f64).ceil() as usize;
+            values[rank.clamp(1, values.len()) - 1]
+        };
+        Some(Self {
+            min: values[0],
+            lower_quartile: rank(0.25),
+            median: rank(0.5),
+            upper_quartile: rank(0.75),
+            max: values[values.len() - 1],
+        })
+    }
+}
+
+/// Reads every novel `filter` matches, hidden ones included only when the
+/// filter asks about them, as the results table does.
+pub(crate) fn tag_analytics(conn: &Connection, filter: &FilterExpr) -> FeiwenResult<TagAnalytics> {
+    let spec = QuerySpec {
+        filter: filter.clone(),
+        sorts: Vec::new(),
+    };
+    let mut tally = Tally::default();
+    for_each_record(conn, &spec, |record| {
+        tally.add(&record);
+        Ok(())
+    })?;
+    Ok(tally.finish())
+}
+
+#[derive(Default)]
+struct Tally {
+    novels: usize,
+    untagged: usize,
+    tag_index: HashMap<String, usize>,
+    tags: Vec<TagValues>,
+    /// The tag indices of each novel that has more than one tag.
+    tag_sets: Vec<Vec<usize>>,
+    authors: HashMap<(Option<i32>, String), (usize, i64)>,
+}
+
+struct TagValues {
+    name: String,
+    words: Vec<i32>,
+    replies: Vec<i32>,
+}
+
+impl Tally {
+    fn add(&mut self, record: &NovelRecord) {
+        self.novels += 1;
+        if record.tags.is_empty() {
+            self.untagged += 1;
+        }
+        let mut tag_set = Vec::with_capacity(record.tags.len());
+        for tag in &record.tags {
+            let index = *self.tag_index.entry(tag.clone()).or_insert_with(|| {
+                self.tags.push(TagValues {
+                    name: tag.clone(),
+                    words: Vec::new(),
+                    replies: Vec::new(),
+                });
+                self.tags.len() - 1
+            });
+            let values = &mut self.tags[index];
+            values.words.push(record.word_count);
+            values.replies.extend(record.reply_count);
+            tag_set.push(index);
+        }
+        if tag_set.len() > 1 {
+            self.tag_sets.push(tag_set);
+        }
+        let output = self
+            .authors
+            .entry((record.author_id, record.author_name.clone()))
+            .or_default();
+        output.0 += 1;
+        output.1 += i64::from(record.word_count);
+    }
+
+    fn finish(self) -> TagAnalytics {
+        let mut order = (0..self.tags.len()).collect::<Vec<_>>();
+        order.sort_by(|left, right| {
+            let (left, right) = (&self.tags[*left], &self.tags[*right]);
+            right
+                .words
+                .len()
+                .cmp(&left.words.len())
+                .then_with(|| left.name.cmp(&right.name))
+        });
+
+        // Where each of the most common tags sits in the matrix.
+        let mut matrix_position = vec![None; self.tags.len()];
+        for (position, index) in order.iter().take(MATRIX_TAGS).enumerate() {
+            matrix_position[*index] = Some(position);
+        }
+        let size = order.len().min(MATRIX_TAGS);
+        let mut co_occurrence = vec![0; size * size];
+        for tag_set in &self.tag_sets {
+            let positions = tag_set
+                .iter()
+                .filter_map(|index| matrix_position[*index])
+                .collect::<Vec<_>>();
+            for row in &positions {
+                for column in &positions {
+                    if row != column {
+                        co_occurrence[row * size + column] += 1;
+                    }
+                }
+            }
+        }
+
+        let mut tags = self.tags.into_iter().map(Some).collect::<Vec<_>>();
+        let tags = order
+            .into_iter()
+            .filter_map(|index| tags[index].take())
+            .filter_map(|values| {
+                Some(TagStats {
+                    name: values.name,
+                    novels: values.words.len(),
+                    words: Distribution::of(values.words)?,
+                    replies: Distribution::of(values.replies),
+                })
+            })
+            .collect();
+
+        let mut authors = self
+            .authors
+            .into_iter()
+            .map(|((author_id, name), (novels, words))| AuthorOutput {
+                author_id,
+                name,
+                novels,
+                words,
+            })
+            .collect::<Vec<_>>();
+        authors.sort_by(|left, right| {
+            right
+                .words
+                .cmp(&left.words)
+                .then_with(|| right.novels.cmp(&left.novels))
+                .then_with(|| left.name.cmp(&right.name))
+                .then_with(|| left.author_id.cmp(&right.author_id))
+        });
+        authors.truncate(TOP_AUTHORS);
+
+        TagAnalytics {
+            novels: self.novels,
+            untagged: self.untagged,
+            tags,
+            co_occurrence,
+            authors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use duckdb::params;
+
+    use super::*;
+    use crate::store::{
+        annotation::Annotation,
+        initialize_schema,
+        query::{NumberField, NumberOp, Predicate},
+    };
+
+    fn insert(
+        conn: &Connection,
+        id: i32,
+        author: (Option<i32>, &str),
+        words: i32,
+        replies: Option<i32>,
+        tags: &[&str],
+    ) {
+        conn.execute(
+            r#"
+            INSERT INTO novel (
+                id, name, "desc", is_limit, latest_chapter_name, latest_chapter_id,
+                word_count, read_count, reply_count, author_id, author_name
+            )
+            VALUES (?, ?, '', FALSE, '', 0, ?, NULL, ?, ?, ?)
+            "#,
+            params![
+                id,
+                format!("novel {id}"),
+                words,
+                replies,
+                author.0,
+                author.1
+            ],
+        )
+        .unwrap();
+        for tag in tags {
+            conn.execute(
+                "INSERT INTO tag (id, name) VALUES (NULL, ?) ON CONFLICT DO NOTHING",
+                params![tag],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO novel_tag (novel_id, tag_id) VALUES (?, ?)",
+                params![id, tag],
+            )
+            .unwrap();
+        }
+    }
+
+    fn connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        insert(&conn, 1, (Some(1), "甲"), 100, Some(5), &["甜文", "HE"]);
+        insert(
+            &conn,
+            2,
+            (Some(1), "甲"),
+            300,
+            None,
+            &["甜文", "HE", "穿书"],
+        );
+        insert(&conn, 3, (Some(2), "乙"), 200, Some(9), &["甜文"]);
+        insert(&conn, 4, (None, "丙"), 50, Some(1), &[]);
+        insert(&conn, 5, (Some(2), "乙"), 900, Some(2), &["甜文", "HE"]);
+        Annotation {
+            hidden: true,
+            ..Annotation::default()
+        }
+        .save(&conn, 5)
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn analytics_cover_the_filtered_novels_without_hidden_ones() {
+        let conn = connection();
+        let analytics = tag_analytics(&conn, &FilterExpr::default()).unwrap();
+
+        assert_eq!(analytics.novels, 4);
+        assert_eq!(analytics.untagged, 1);
+        let names = analytics
+            .tags
+            .iter()
+            .map(|tag| (tag.name.as_str(), tag.novels))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![("甜文", 3), ("HE", 2), ("穿书", 1)]);
+        assert_eq!(
+            analytics.tags[0].words,
+            Distribution {
+                min: 100,
+                lower_quartile: 100,
+                median: 200,
+                upper_quartile: 300,
+                max: 300,
+            }
+        );
+        assert_eq!(
+            analytics.tags[0].replies.map(|replies| replies.max),
+            Some(9)
+        );
+        assert_eq!(analytics.tags[2].replies, None);
+        assert_eq!(analytics.co_occurrence, vec![0, 2, 1, 2, 0, 1, 1, 1, 0]);
+        assert_eq!(
+            analytics.authors,
+            vec![
+                AuthorOutput {
+                    author_id: Some(1),
+                    name: "甲".to_owned(),
+                    novels: 2,
+                    words: 400,
+                },
+                AuthorOutput {
+                    author_id: Some(2),
+                    name: "乙".to_owned(),
+                    novels: 1,
+                    words: 200,
+                },
+                AuthorOutput {
+                    author_id: None,
+                    name: "丙".to_owned(),
+                    novels: 1,
+                    words: 50,
+                },
+            ]
+        );
+
+        let long = FilterExpr::Predicate(Predicate::Number {
+            field: NumberField::WordCount,
+            op: NumberOp::Gte(200),
+        });
+        let analytics = tag_analytics(&conn, &long).unwrap();
+        assert_eq!(analytics.novels, 2);
+        assert_eq!(analytics.tags[0].name, "甜文");
+        assert_eq!(analytics.co_occurrence.len(), 9);
+        assert_eq!(analytics.authors[0].words, 300);
+    }
+
+    #[test]
+    fn quartiles_use_nearest_ranks() {
+        assert_eq!(Distribution::of(Vec::new()), None);
+        assert_eq!(
+            Distribution::of(vec![7]),
+            Some(Distribution {
+                min: 7,
+                lower_quartile: 7,
+                median: 7,
+                upper_quartile: 7,
+                max: 7,
+            })
+        );
+        assert_eq!(
+            Distribution::of(vec![8, 1, 6, 2, 4, 3, 7, 5]),
+            Some(Distribution {
+                min: 1,
+                lower_quartile: 2,
+                median: 4,
+                upper_quartile: 6,
+                max: 8,
+            })
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use duckdb::{Connection, params};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::FeiwenResult;
+
+/// How far the user got with a novel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ReadStatus {
+    #[default]
+    Unread,
+    Reading,
+    Read,
+    Dropped,
+}
+
+impl ReadStatus {
+    pub(crate) const ALL: [Self; 4] = [Self::Unread, Self::Reading, Self::Read, Self::Dropped];
+
+    /// The value stored in `novel_annotation.status`, also used by the
+    /// query language.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Unread => "unread",
+            Self::Reading => "reading",
+            Self::Read => "read",
+            Self::Dropped => "dropped",
+        }
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str().eq_ignore_ascii_case(value))
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Unread => "未读",
+            Self::Reading => "在读",
+            Self::Read => "已读",
+            Self::Dropped => "弃文",
+        }
+    }
+}
+
+/// What the user keeps about a novel. It lives apart from the scraped
+/// columns, so fetching a novel again never touches it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Annotation {
+    pub(crate) status: ReadStatus,
+    /// One to five stars.
+    pub(crate) rating: Option<u8>,
+    pub(crate) note: String,
+    /// Hidden novels stay out of results unless a query asks about them.
+    pub(crate) hidden: bool,
+}
+
+impl Annotation {
+    pub(crate) fn load(conn: &Connection, novel_id: i32) -> FeiwenResult<Self> {
+        let mut statement = conn.prepare(
+            "SELECT status, rating, note, hidden FROM novel_annotation WHERE novel_id = ?",
+        )?;
+        let mut rows = statement.query_map(params![novel_id], |row| {
+            Ok(Self {
+                status: ReadStatus::parse(&row.get::<_, String>(0)?).unwrap_or_default(),
+                rating: row.get(1)?,
+                note: row.get(2)?,
+                hidden: row.get(3)?,
+            })
+        })?;
+        Ok(rows.next().transpose()?.unwrap_or_default())
+    }
+
+    /// Stores the annotation; an empty one removes the novel's row.
+    pub(crate) fn save(&self, conn: &Connection, novel_id: i32) -> FeiwenResult<()> {
+        if *self == Self::default() {
+            conn.execute(
+                "DELETE FROM novel_annotation WHERE novel_id = ?",
+                params![novel_id],
+            )?;
+            return Ok(());
+        }
+        conn.execute(
+            r#"
+            INSERT INTO novel_annotation (novel_id, status, rating, note, hidden)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT (novel_id) DO UPDATE SET
+                status = excluded.status,
+                rating = excluded.rating,
+                note = excluded.note,
+                hidden = excluded.hidden
+            "#,
+            params![
+                novel_id,
+                self.status.as_str(),
+                self.rating,
+                self.note,
+                self.hidden
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::initialize_schema;
+
+    #[test]
+    fn annotations_round_trip_and_empty_ones_leave_no_row() {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        let annotation = Annotation {
+            status: ReadStatus::Reading,
+            rating: Some(4),
+            note: "慢热".to_owned(),
+            hidden: false,
+        };
+
+        annotation.save(&conn, 1).unwrap();
+        assert_eq!(Annotation::load(&conn, 1).unwrap(), annotation);
+        assert_eq!(Annotation::load(&conn, 2).unwrap(), Annotation::default());
+
+        Annotation::default().save(&conn, 1).unwrap();
+        let rows = conn
+            .query_row("SELECT count(*) FROM novel_annotation", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .unwrap();
+        assert_eq!(rows, 0);
+    }
+
+    #[test]
+    fn ratings_outside_one_to_five_are_rejected() {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        let annotation = Annotation {
+            rating: Some(6),
+            ..Annotation::default()
+        };
+
+        assert!(annotation.save(&conn, 1).is_err());
+    }
+}
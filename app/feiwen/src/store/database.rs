@@ -7,7 +7,10 @@ use gpui::{App, AppContext, Task};
 use gpui_operation::Transition;
 use gpui_store::Store;
 
-use super::{DbConn, establish_connection_at, get_data_url, open_connection_at, validate_schema};
+use super::{
+    DbConn, establish_connection_at, get_data_url, open_connection_at, upgrade_schema,
+    validate_schema,
+};
 
 pub(crate) type DatabaseStore = Store<DatabaseResource>;
 
@@ -355,6 +358,8 @@ fn reopen(path: &Path) -> Result<DatabaseReady, DatabaseProblem> {
     }
     let pool = open_connection_at(path)
         .map_err(|error| DatabaseProblem::at(DatabaseProblemKind::Reopen, error))?;
+    upgrade_schema(&pool)
+        .map_err(|error| DatabaseProblem::at(DatabaseProblemKind::Reopen, error))?;
     validate_schema(&pool)
         .map_err(|error| DatabaseProblem::at(DatabaseProblemKind::Validate, error))?;
     Ok(DatabaseReady {
@@ -954,6 +959,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn reopen_upgrades_a_database_from_before_search() {
+        let directory = TestDirectory::new("reopen-upgrade");
+        let live = directory.0.join("data.duckdb");
+        create_live_with_row(&live);
+        let pool = open_connection_at(&live).unwrap();
+        pool.get()
+            .unwrap()
+            .execute_batch("DROP TABLE novel_search_token; DROP TABLE novel_search_pinyin;")
+            .unwrap();
+        checkpoint(&pool).unwrap();
+        drop(pool);
+
+        let reopened = reopen(&live).unwrap();
+        let conn = reopened.pool.get().unwrap();
+        let indexed = conn
+            .query_row("SELECT count(*) FROM novel_search_pinyin", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .unwrap();
+        assert!(indexed > 0);
+        drop(conn);
+        drop(reopened);
+        assert_live_row(&live);
+    }
+
     #[test]
     fn backup_and_rebuild_preserves_backup_and_installs_empty_schema() {
         let directory = TestDirectory::new("rebuild");
@@ -0,0 +1,373 @@
+use std::io::Write;
+
+use duckdb::Connection;
+use serde_json::Value;
+
+use crate::{
+    errors::FeiwenResult,
+    store::query::{NovelRecord, QuerySpec, for_each_record},
+};
+
+mod xlsx;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    #[default]
+    Csv,
+    JsonLines,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub(crate) const ALL: [Self; 3] = [Self::Csv, Self::JsonLines, Self::Xlsx];
+
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::JsonLines => "jsonl",
+            Self::Xlsx => "xlsx",
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::JsonLines => "JSON Lines",
+            Self::Xlsx => "Excel",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ExportColumn {
+    Id,
+    Title,
+    Description,
+    AuthorId,
+    AuthorName,
+    WordCount,
+    ReadCount,
+    ReplyCount,
+    IsLimit,
+    LatestChapterId,
+    LatestChapterName,
+    Tags,
+    TagIds,
+    ReadStatus,
+    Rating,
+    Note,
+    Hidden,
+}
+
+impl ExportColumn {
+    pub(crate) const ALL: [Self; 17] = [
+        Self::Id,
+        Self::Title,
+        Self::Description,
+        Self::AuthorId,
+        Self::AuthorName,
+        Self::WordCount,
+        Self::ReadCount,
+        Self::ReplyCount,
+        Self::IsLimit,
+        Self::LatestChapterId,
+        Self::LatestChapterName,
+        Self::Tags,
+        Self::TagIds,
+        Self::ReadStatus,
+        Self::Rating,
+        Self::Note,
+        Self::Hidden,
+    ];
+
+    /// What a new export starts with: everything but the long text and the
+    /// ids only scripts care about.
+    pub(crate) const DEFAULT: [Self; 11] = [
+        Self::Id,
+        Self::Title,
+        Self::AuthorName,
+        Self::WordCount,
+        Self::ReadCount,
+        Self::ReplyCount,
+        Self::IsLimit,
+        Self::LatestChapterName,
+        Self::Tags,
+        Self::ReadStatus,
+        Self::Rating,
+    ];
+
+    /// The header row in CSV and Excel and the key in JSON Lines.
+    pub(crate) fn header(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Title => "title",
+            Self::Description => "description",
+            Self::AuthorId => "author_id",
+            Self::AuthorName => "author_name",
+            Self::WordCount => "word_count",
+            Self::ReadCount => "read_count",
+            Self::ReplyCount => "reply_count",
+            Self::IsLimit => "is_limit",
+            Self::LatestChapterId => "latest_chapter_id",
+            Self::LatestChapterName => "latest_chapter_name",
+            Self::Tags => "tags",
+            Self::TagIds => "tag_ids",
+            Self::ReadStatus => "read_status",
+            Self::Rating => "rating",
+            Self::Note => "note",
+            Self::Hidden => "hidden",
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Id => "ID",
+            Self::Title => "标题",
+            Self::Description => "简介",
+            Self::AuthorId => "作者 ID",
+            Self::AuthorName => "作者",
+            Self::WordCount => "字数",
+            Self::ReadCount => "阅读",
+            Self::ReplyCount => "回复",
+            Self::IsLimit => "受限",
+            Self::LatestChapterId => "最新章节 ID",
+            Self::LatestChapterName => "最新章节",
+            Self::Tags => "标签",
+            Self::TagIds => "标签 ID",
+            Self::ReadStatus => "阅读状态",
+            Self::Rating => "评分",
+            Self::Note => "笔记",
+            Self::Hidden => "隐藏",
+        }
+    }
+
+    fn value(self, record: &NovelRecord) -> Value {
+        match self {
+            Self::Id => record.id.into(),
+            Self::Title => record.title.as_str().into(),
+            Self::Description => record.desc.as_str().into(),
+            Self::AuthorId => record.author_id.into(),
+            Self::AuthorName => record.author_name.as_str().into(),
+            Self::WordCount => record.word_count.into(),
+            Self::ReadCount => record.read_count.into(),
+            Self::ReplyCount => record.reply_count.into(),
+            Self::IsLimit => record.is_limit.into(),
+            Self::LatestChapterId => record.latest_chapter_id.into(),
+            Self::LatestChapterName => record.latest_chapter_name.as_str().into(),
+            Self::Tags => record.tags.clone().into(),
+            // In tag order, so the n-th id belongs to the n-th tag.
+            Self::TagIds => record
+                .tags
+                .iter()
+                .map(|tag| Value::from(record.tag_ids.get(tag).copied().flatten()))
+                .collect(),
+            Self::ReadStatus => record.annotation.status.as_str().into(),
+            Self::Rating => record.annotation.rating.into(),
+            Self::Note => record.annotation.note.as_str().into(),
+            Self::Hidden => record.annotation.hidden.into(),
+        }
+    }
+}
+
+/// Writes every novel `spec` matches to `writer`, reading them from the
+/// database one row at a time. Returns how many novels were written.
+pub(crate) fn export_records(
+    conn: &Connection,
+    spec: &QuerySpec,
+    columns: &[ExportColumn],
+    format: ExportFormat,
+    mut writer: impl Write,
+) -> FeiwenResult<usize> {
+    let mut count = 0;
+    match format {
+        ExportFormat::Csv => {
+            // A byte order mark, so Excel opens the file as UTF-8.
+            writer.write_all("\u{feff}".as_bytes())?;
+            write_csv_row(&mut writer, columns.iter().map(|column| column.header()))?;
+            for_each_record(conn, spec, |record| {
+                let cells = columns
+                    .iter()
+                    .map(|column| flat_text(column.value(&record)))
+                    .collect::<Vec<_>>();
+                write_csv_row(&mut writer, cells.iter().map(String::as_str))?;
+                count += 1;
+                Ok(())
+            })?;
+            writer.flush()?;
+        }
+        ExportFormat::JsonLines => {
+            for_each_record(conn, spec, |record| {
+                // Written key by key so the keys keep the chosen column order.
+                writer.write_all(b"{")?;
+                for (index, column) in columns.iter().enumerate() {
+                    if index > 0 {
+                        writer.write_all(b",")?;
+                    }
+                    serde_json::to_writer(&mut writer, column.header())?;
+                    writer.write_all(b":")?;
+                    serde_json::to_writer(&mut writer, &column.value(&record))?;
+                }
+                writer.write_all(b"}\n")?;
+                count += 1;
+                Ok(())
+            })?;
+            writer.flush()?;
+        }
+        ExportFormat::Xlsx => {
+            let mut sheet = xlsx::SheetWriter::new(writer)?;
+            sheet.write_row(
+                columns
+                    .iter()
+                    .map(|column| Value::from(column.header()))
+                    .collect(),
+            )?;
+            for_each_record(conn, spec, |record| {
+                sheet.write_row(columns.iter().map(|column| column.value(&record)).collect())?;
+                count += 1;
+                Ok(())
+            })?;
+            sheet.finish()?;
+        }
+    }
+    Ok(count)
+}
+
+/// One cell of text: lists are joined with commas and missing values are
+/// left empty.
+fn flat_text(value: Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text,
+        Value::Array(items) => items
+            .into_iter()
+            .map(flat_text)
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+fn write_csv_row<'a>(
+    writer: &mut impl Write,
+    cells: impl Iterator<Item = &'a str>,
+) -> std::io::Result<()> {
+    for (index, cell) in cells.enumerate() {
+        if index > 0 {
+            writer.write_all(b",")?;
+        }
+        if cell.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", cell.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(cell.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use duckdb::params;
+
+    use super::*;
+    use crate::store::{
+        annotation::{Annotation, ReadStatus},
+        initialize_schema,
+    };
+
+    fn export(format: ExportFormat, columns: &[ExportColumn]) -> Vec<u8> {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        conn.execute(
+            r#"
+            INSERT INTO novel (
+                id, name, "desc", is_limit, latest_chapter_name, latest_chapter_id,
+                word_count, read_count, reply_count, author_id, author_name
+            )
+            VALUES (7, '雪,夜"行"', '第一行' || chr(10) || '第二行', FALSE, '终章', 30,
+                    120000, 800, NULL, 3, '某某')
+            "#,
+            [],
+        )
+        .unwrap();
+        for (tag_id, tag) in [(Some(11), "甜文"), (None, "HE")] {
+            conn.execute(
+                "INSERT INTO tag (id, name) VALUES (?, ?)",
+                params![tag_id, tag],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO novel_tag (novel_id, tag_id) VALUES (7, ?)",
+                params![tag],
+            )
+            .unwrap();
+        }
+        Annotation {
+            status: ReadStatus::Read,
+            rating: Some(5),
+            ..Annotation::default()
+        }
+        .save(&conn, 7)
+        .unwrap();
+
+        let mut output = Vec::new();
+        let count =
+            export_records(&conn, &QuerySpec::default(), columns, format, &mut output).unwrap();
+        assert_eq!(count, 1);
+        output
+    }
+
+    #[test]
+    fn csv_quotes_cells_and_joins_lists() {
+        let output = export(
+            ExportFormat::Csv,
+            &[
+                ExportColumn::Title,
+                ExportColumn::Description,
+                ExportColumn::Tags,
+                ExportColumn::TagIds,
+                ExportColumn::ReplyCount,
+            ],
+        );
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\u{feff}title,description,tags,tag_ids,reply_count\r\n\
+             \"雪,夜\"\"行\"\"\",\"第一行\n第二行\",\"HE, 甜文\",\", 11\",\r\n"
+        );
+    }
+
+    #[test]
+    fn json_lines_keep_value_types() {
+        let output = export(
+            ExportFormat::JsonLines,
+            &[
+                ExportColumn::Id,
+                ExportColumn::AuthorId,
+                ExportColumn::ReplyCount,
+                ExportColumn::TagIds,
+                ExportColumn::ReadStatus,
+                ExportColumn::Rating,
+                ExportColumn::Hidden,
+            ],
+        );
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"id\":7,\"author_id\":3,\"reply_count\":null,\"tag_ids\":[null,11],\
+             \"read_status\":\"read\",\"rating\":5,\"hidden\":false}\n"
+        );
+    }
+
+    #[test]
+    fn xlsx_is_a_zip_holding_the_sheet() {
+        let output = export(ExportFormat::Xlsx, &[ExportColumn::Id, ExportColumn::Title]);
+
+        assert!(output.starts_with(b"PK\x03\x04"));
+        let text = String::from_utf8_lossy(&output);
+        assert!(text.contains("xl/worksheets/sheet1.xml"));
+        assert!(text.contains(
+            "<c t=\"inlineStr\"><is><t xml:space=\"preserve\">雪,夜&quot;行&quot;</t></is></c>"
+        ));
+        assert!(text.contains("<c><v>7</v></c>"));
+    }
+}
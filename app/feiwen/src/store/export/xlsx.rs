@@ -0,0 +1,258 @@
+//! Just enough of the Office Open XML format for one sheet of plain values.
+//!
+//! The workbook is a zip whose entries are stored uncompressed, and the sheet
+//! entry is streamed with a trailing data descriptor, so rows go straight to
+//! the output without being buffered.
+
+use std::io::{self, Write};
+
+use serde_json::Value;
+
+use super::flat_text;
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#;
+
+const ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#;
+
+const WORKBOOK: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="feiwen" sheetId="1" r:id="rId1"/></sheets></workbook>"#;
+
+const WORKBOOK_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#;
+
+const SHEET_HEAD: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#;
+
+const SHEET_TAIL: &str = "</sheetData></worksheet>";
+
+pub(super) struct SheetWriter<W: Write> {
+    zip: ZipWriter<W>,
+    rows: usize,
+}
+
+impl<W: Write> SheetWriter<W> {
+    pub(super) fn new(writer: W) -> io::Result<Self> {
+        let mut zip = ZipWriter {
+            writer,
+            offset: 0,
+            entries: Vec::new(),
+            current: None,
+        };
+        for (name, content) in [
+            ("[Content_Types].xml", CONTENT_TYPES),
+            ("_rels/.rels", ROOT_RELS),
+            ("xl/workbook.xml", WORKBOOK),
+            ("xl/_rels/workbook.xml.rels", WORKBOOK_RELS),
+        ] {
+            zip.start_entry(name)?;
+            zip.write_data(content.as_bytes())?;
+            zip.finish_entry()?;
+        }
+        zip.start_entry("xl/worksheets/sheet1.xml")?;
+        zip.write_data(SHEET_HEAD.as_bytes())?;
+        Ok(Self { zip, rows: 0 })
+    }
+
+    pub(super) fn write_row(&mut self, cells: Vec<Value>) -> io::Result<()> {
+        self.rows += 1;
+        let mut row = format!("<row r=\"{}\">", self.rows);
+        for cell in cells {
+            match cell {
+                Value::Null => row.push_str("<c/>"),
+                Value::Bool(value) => {
+                    row.push_str(if value {
+                        "<c t=\"b\"><v>1</v></c>"
+                    } else {
+                        "<c t=\"b\"><v>0</v></c>"
+                    });
+                }
+                Value::Number(value) => row.push_str(&format!("<c><v>{value}</v></c>")),
+                other => {
+                    row.push_str("<c t=\"inlineStr\"><is><t xml:space=\"preserve\">");
+                    push_escaped(&mut row, &flat_text(other));
+                    row.push_str("</t></is></c>");
+                }
+            }
+        }
+        row.push_str("</row>");
+        self.zip.write_data(row.as_bytes())
+    }
+
+    pub(super) fn finish(mut self) -> io::Result<()> {
+        self.zip.write_data(SHEET_TAIL.as_bytes())?;
+        self.zip.finish_entry()?;
+        self.zip.finish()
+    }
+}
+
+/// Escapes XML markup and drops the control characters XML can't carry.
+fn push_escaped(output: &mut String, text: &str) {
+    for ch in text.chars() {
+        match ch {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\t' | '\n' | '\r' => output.push(ch),
+            ch if ch.is_control() => {}
+            ch => output.push(ch),
+        }
+    }
+}
+
+struct ZipEntry {
+    name: &'static str,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+struct ZipWriter<W: Write> {
+    writer: W,
+    /// Bytes written so far, which is where the next header starts.
+    offset: u32,
+    entries: Vec<ZipEntry>,
+    current: Option<ZipEntry>,
+}
+
+/// Sizes and CRCs follow the data, and names are UTF-8.
+const ENTRY_FLAGS: u16 = 0x0808;
+/// 1980-01-01, the earliest date a zip can hold.
+const DOS_DATE: u16 = 0x0021;
+
+impl<W: Write> ZipWriter<W> {
+    fn start_entry(&mut self, name: &'static str) -> io::Result<()> {
+        self.current = Some(ZipEntry {
+            name,
+            crc: 0,
+            size: 0,
+            offset: self.offset,
+        });
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend(0x0403_4b50_u32.to_le_bytes());
+        header.extend(20_u16.to_le_bytes());
+        header.extend(ENTRY_FLAGS.to_le_bytes());
+        header.extend(0_u16.to_le_bytes()); // stored
+        header.extend(0_u16.to_le_bytes()); // time
+        header.extend(DOS_DATE.to_le_bytes());
+        header.extend([0; 12]); // crc and sizes, in the data descriptor
+        header.extend((name.len() as u16).to_le_bytes());
+        header.extend(0_u16.to_le_bytes());
+        header.extend(name.as_bytes());
+        self.write_raw(&header)
+    }
+
+    fn write_data(&mut self, data: &[u8]) -> io::Result<()> {
+        let entry = self.current.as_mut().expect("zip entry started");
+        entry.crc = crc32_update(entry.crc, data);
+        entry.size = u32::try_from(data.len())
+            .ok()
+            .and_then(|len| entry.size.checked_add(len))
+            .ok_or_else(too_large)?;
+        self.write_raw(data)
+    }
+
+    fn finish_entry(&mut self) -> io::Result<()> {
+        let entry = self.current.take().expect("zip entry started");
+        let mut descriptor = Vec::with_capacity(16);
+        descriptor.extend(0x0807_4b50_u32.to_le_bytes());
+        descriptor.extend(entry.crc.to_le_bytes());
+        descriptor.extend(entry.size.to_le_bytes());
+        descriptor.extend(entry.size.to_le_bytes());
+        self.entries.push(entry);
+        self.write_raw(&descriptor)
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        let directory_offset = self.offset;
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            directory.extend(0x0201_4b50_u32.to_le_bytes());
+            directory.extend(20_u16.to_le_bytes());
+            directory.extend(20_u16.to_le_bytes());
+            directory.extend(ENTRY_FLAGS.to_le_bytes());
+            directory.extend(0_u16.to_le_bytes());
+            directory.extend(0_u16.to_le_bytes());
+            directory.extend(DOS_DATE.to_le_bytes());
+            directory.extend(entry.crc.to_le_bytes());
+            directory.extend(entry.size.to_le_bytes());
+            directory.extend(entry.size.to_le_bytes());
+            directory.extend((entry.name.len() as u16).to_le_bytes());
+            directory.extend([0; 12]); // extra, comment, disk and attributes
+            directory.extend(entry.offset.to_le_bytes());
+            directory.extend(entry.name.as_bytes());
+        }
+        let directory_size = u32::try_from(directory.len()).map_err(|_| too_large())?;
+        let entries = self.entries.len() as u16;
+        directory.extend(0x0605_4b50_u32.to_le_bytes());
+        directory.extend([0; 4]); // disk numbers
+        directory.extend(entries.to_le_bytes());
+        directory.extend(entries.to_le_bytes());
+        directory.extend(directory_size.to_le_bytes());
+        directory.extend(directory_offset.to_le_bytes());
+        directory.extend(0_u16.to_le_bytes());
+        self.write_raw(&directory)?;
+        self.writer.flush()
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.offset = u32::try_from(bytes.len())
+            .ok()
+            .and_then(|len| self.offset.checked_add(len))
+            .ok_or_else(too_large)?;
+        self.writer.write_all(bytes)
+    }
+}
+
+fn too_large() -> io::Error {
+    io::Error::other("导出文件超过 4 GB,请改用 CSV 或 JSON Lines")
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    !data.iter().fold(!crc, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_matches_the_check_value_across_chunks() {
+        assert_eq!(crc32_update(0, b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32_update(crc32_update(0, b"1234"), b"56789"),
+            0xCBF4_3926
+        );
+    }
+
+    #[test]
+    fn cells_escape_markup_and_drop_control_characters() {
+        let mut output = String::new();
+        push_escaped(&mut output, "<a & \"b\">\u{1}\n");
+        assert_eq!(output, "&lt;a &amp; &quot;b&quot;&gt;\n");
+    }
+}
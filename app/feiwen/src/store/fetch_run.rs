@@ -0,0 +1,367 @@
+use std::collections::HashSet;
+
+use duckdb::{Connection, params};
+
+use crate::{
+    errors::{FeiwenError, FeiwenResult},
+    fetch::FetchErrorKind,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FetchRunStatus {
+    Running,
+    Interrupted,
+    Failed,
+    Succeeded,
+}
+
+impl FetchRunStatus {
+    const ALL: [Self; 4] = [
+        Self::Running,
+        Self::Interrupted,
+        Self::Failed,
+        Self::Succeeded,
+    ];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Interrupted => "interrupted",
+            Self::Failed => "failed",
+            Self::Succeeded => "succeeded",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == value)
+            .unwrap_or(Self::Interrupted)
+    }
+}
+
+/// What a run was asked to fetch. The cookie is a secret and is never
+/// stored; resuming a run uses the cookie currently in the form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FetchRunPlan {
+    pub(crate) url: String,
+    pub(crate) start_page: u32,
+    pub(crate) end_page: u32,
+    pub(crate) concurrency: u32,
+    pub(crate) requests_per_minute: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FailedPage {
+    pub(crate) page: u32,
+    pub(crate) kind: FetchErrorKind,
+    pub(crate) attempts: u32,
+    pub(crate) message: String,
+}
+
+pub(crate) enum PageOutcome<'a> {
+    Succeeded {
+        inserted: usize,
+    },
+    Failed {
+        kind: FetchErrorKind,
+        message: &'a str,
+    },
+}
+
+/// A fetch run as stored, with how far it got.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FetchRunRecord {
+    pub(crate) id: i32,
+    pub(crate) plan: FetchRunPlan,
+    pub(crate) status: FetchRunStatus,
+    /// Unix seconds.
+    pub(crate) started_at: i64,
+    pub(crate) updated_at: i64,
+    pub(crate) done_pages: u32,
+    /// Pages whose last attempt failed, in page order.
+    pub(crate) failed_pages: Vec<FailedPage>,
+}
+
+impl FetchRunRecord {
+    pub(crate) fn create(conn: &Connection, plan: &FetchRunPlan, now: i64) -> FeiwenResult<i32> {
+        let id = conn.query_row(
+            r#"
+            INSERT INTO fetch_run (
+                url, start_page, end_page, concurrency, requests_per_minute,
+                status, started_at, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id
+            "#,
+            params![
+                plan.url,
+                plan.start_page,
+                plan.end_page,
+                plan.concurrency,
+                plan.requests_per_minute,
+                FetchRunStatus::Running.as_str(),
+                now,
+                now
+            ],
+            |row| row.get(0),
+        )?;
+        Ok(id)
+    }
+
+    pub(crate) fn load(conn: &Connection, id: i32) -> FeiwenResult<Self> {
+        load_runs(conn, "WHERE id = ?", params![id])?
+            .pop()
+            .ok_or(FeiwenError::FetchRunMissing)
+    }
+
+    /// The latest runs, newest first.
+    pub(crate) fn recent(conn: &Connection, limit: usize) -> FeiwenResult<Vec<Self>> {
+        load_runs(conn, "ORDER BY id DESC LIMIT ?", params![limit as i64])
+    }
+
+    pub(crate) fn set_status(
+        conn: &Connection,
+        id: i32,
+        status: FetchRunStatus,
+        now: i64,
+    ) -> FeiwenResult<()> {
+        conn.execute(
+            "UPDATE fetch_run SET status = ?, updated_at = ? WHERE id = ?",
+            params![status.as_str(), now, id],
+        )?;
+        Ok(())
+    }
+
+    /// Runs still marked running when the app starts were cut off by a
+    /// crash or quit; they can be resumed like interrupted ones.
+    pub(crate) fn interrupt_abandoned(conn: &Connection) -> FeiwenResult<usize> {
+        Ok(conn.execute(
+            "UPDATE fetch_run SET status = ? WHERE status = ?",
+            params![
+                FetchRunStatus::Interrupted.as_str(),
+                FetchRunStatus::Running.as_str()
+            ],
+        )?)
+    }
+
+    /// Stores how a page went; a later attempt replaces an earlier one.
+    pub(crate) fn record_page(
+        conn: &Connection,
+        id: i32,
+        page: u32,
+        attempts: u32,
+        outcome: PageOutcome<'_>,
+        now: i64,
+    ) -> FeiwenResult<()> {
+        let (succeeded, inserted, kind, message) = match outcome {
+            PageOutcome::Succeeded { inserted } => (true, Some(inserted as i64), None, ""),
+            PageOutcome::Failed { kind, message } => (false, None, Some(kind.label()), message),
+        };
+        conn.execute(
+            r#"
+            INSERT INTO fetch_run_page (run_id, page, succeeded, attempts, inserted, error_kind, message)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (run_id, page) DO UPDATE SET
+                succeeded = excluded.succeeded,
+                attempts = excluded.attempts,
+                inserted = excluded.inserted,
+                error_kind = excluded.error_kind,
+                message = excluded.message
+            "#,
+            params![id, page, succeeded, attempts, inserted, kind, message],
+        )?;
+        conn.execute(
+            "UPDATE fetch_run SET updated_at = ? WHERE id = ?",
+            params![now, id],
+        )?;
+        Ok(())
+    }
+
+    /// Pages in the range that no attempt has finished yet, in order.
+    pub(crate) fn remaining_pages(&self, conn: &Connection) -> FeiwenResult<Vec<u32>> {
+        let mut stmt = conn.prepare("SELECT page FROM fetch_run_page WHERE run_id = ?")?;
+        let finished = stmt
+            .query_map(params![self.id], |row| row.get::<_, i64>(0))?
+            .map(|page| page.map(|page| page as u32))
+            .collect::<Result<HashSet<_>, _>>()?;
+        Ok((self.plan.start_page..=self.plan.end_page)
+            .filter(|page| !finished.contains(page))
+            .collect())
+    }
+
+    pub(crate) fn page_count(&self) -> u32 {
+        self.plan
+            .end_page
+            .saturating_sub(self.plan.start_page)
+            .saturating_add(1)
+    }
+}
+
+fn load_runs(
+    conn: &Connection,
+    clause: &str,
+    params: &[&dyn duckdb::ToSql],
+) -> FeiwenResult<Vec<FetchRunRecord>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT
+            id, url, start_page, end_page, concurrency, requests_per_minute,
+            status, started_at, updated_at,
+            (SELECT count(*) FROM fetch_run_page p WHERE p.run_id = fetch_run.id AND p.succeeded)
+        FROM fetch_run
+        {clause}
+        "#
+    ))?;
+    let runs = stmt
+        .query_map(params, |row| {
+            Ok(FetchRunRecord {
+                id: row.get(0)?,
+                plan: FetchRunPlan {
+                    url: row.get(1)?,
+                    start_page: row.get::<_, i64>(2)? as u32,
+                    end_page: row.get::<_, i64>(3)? as u32,
+                    concurrency: row.get::<_, i64>(4)? as u32,
+                    requests_per_minute: row.get::<_, i64>(5)? as u32,
+                },
+                status: FetchRunStatus::parse(&row.get::<_, String>(6)?),
+                started_at: row.get(7)?,
+                updated_at: row.get(8)?,
+                done_pages: row.get::<_, i64>(9)? as u32,
+                failed_pages: Vec::new(),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT page, error_kind, attempts, message
+        FROM fetch_run_page
+        WHERE run_id = ? AND NOT succeeded
+        ORDER BY page
+        "#,
+    )?;
+    runs.into_iter()
+        .map(|mut run| -> FeiwenResult<FetchRunRecord> {
+            run.failed_pages = stmt
+                .query_map(params![run.id], |row| {
+                    Ok(FailedPage {
+                        page: row.get::<_, i64>(0)? as u32,
+                        kind: row
+                            .get::<_, Option<String>>(1)?
+                            .as_deref()
+                            .and_then(FetchErrorKind::parse)
+                            .unwrap_or(FetchErrorKind::Other),
+                        attempts: row.get::<_, i64>(2)? as u32,
+                        message: row.get(3)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(run)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::initialize_schema;
+
+    fn connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        conn
+    }
+
+    fn plan() -> FetchRunPlan {
+        FetchRunPlan {
+            url: "https://example.test/books".to_owned(),
+            start_page: 1,
+            end_page: 5,
+            concurrency: 3,
+            requests_per_minute: 60,
+        }
+    }
+
+    #[test]
+    fn runs_track_done_failed_and_remaining_pages() {
+        let conn = connection();
+        let id = FetchRunRecord::create(&conn, &plan(), 100).unwrap();
+
+        FetchRunRecord::record_page(
+            &conn,
+            id,
+            1,
+            1,
+            PageOutcome::Succeeded { inserted: 20 },
+            101,
+        )
+        .unwrap();
+        FetchRunRecord::record_page(
+            &conn,
+            id,
+            3,
+            5,
+            PageOutcome::Failed {
+                kind: FetchErrorKind::Network,
+                message: "timed out",
+            },
+            102,
+        )
+        .unwrap();
+
+        let run = FetchRunRecord::load(&conn, id).unwrap();
+        assert_eq!(run.plan, plan());
+        assert_eq!(run.status, FetchRunStatus::Running);
+        assert_eq!((run.started_at, run.updated_at), (100, 102));
+        assert_eq!(run.done_pages, 1);
+        assert_eq!(
+            run.failed_pages,
+            vec![FailedPage {
+                page: 3,
+                kind: FetchErrorKind::Network,
+                attempts: 5,
+                message: "timed out".to_owned(),
+            }]
+        );
+        assert_eq!(run.remaining_pages(&conn).unwrap(), vec![2, 4, 5]);
+
+        // A successful retry clears the failure.
+        FetchRunRecord::record_page(
+            &conn,
+            id,
+            3,
+            1,
+            PageOutcome::Succeeded { inserted: 20 },
+            103,
+        )
+        .unwrap();
+        let run = FetchRunRecord::load(&conn, id).unwrap();
+        assert_eq!(run.done_pages, 2);
+        assert!(run.failed_pages.is_empty());
+    }
+
+    #[test]
+    fn abandoned_runs_become_interrupted_and_recent_lists_newest_first() {
+        let conn = connection();
+        let first = FetchRunRecord::create(&conn, &plan(), 100).unwrap();
+        let second = FetchRunRecord::create(&conn, &plan(), 200).unwrap();
+        FetchRunRecord::set_status(&conn, first, FetchRunStatus::Succeeded, 150).unwrap();
+
+        assert_eq!(FetchRunRecord::interrupt_abandoned(&conn).unwrap(), 1);
+        let runs = FetchRunRecord::recent(&conn, 10).unwrap();
+        assert_eq!(
+            runs.iter()
+                .map(|run| (run.id, run.status))
+                .collect::<Vec<_>>(),
+            vec![
+                (second, FetchRunStatus::Interrupted),
+                (first, FetchRunStatus::Succeeded)
+            ]
+        );
+        assert!(matches!(
+            FetchRunRecord::load(&conn, 99),
+            Err(FeiwenError::FetchRunMissing)
+        ));
+    }
+}
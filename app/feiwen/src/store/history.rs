@@ -0,0 +1,142 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use duckdb::{Connection, params};
+
+use crate::{errors::FeiwenResult, store::query::TrendMetric};
+
+pub(crate) const DAY_SECONDS: i64 = 24 * 60 * 60;
+
+/// A novel's counts as one fetch saw them. Every fetch run stamps the novels
+/// it saves with the time it started, so a novel listed on two pages of the
+/// same run keeps a single snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct NovelSnapshot {
+    pub(crate) fetched_at: i64,
+    pub(crate) word_count: i32,
+    pub(crate) read_count: Option<i32>,
+    pub(crate) reply_count: Option<i32>,
+    pub(crate) latest_chapter_id: i32,
+    pub(crate) latest_chapter_name: String,
+}
+
+impl NovelSnapshot {
+    pub(crate) fn metric(&self, metric: TrendMetric) -> Option<i32> {
+        match metric {
+            TrendMetric::Words => Some(self.word_count),
+            TrendMetric::Reads => self.read_count,
+            TrendMetric::Replies => self.reply_count,
+        }
+    }
+}
+
+pub(crate) fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+pub(crate) fn record_snapshot(
+    conn: &Connection,
+    novel_id: i32,
+    snapshot: &NovelSnapshot,
+) -> FeiwenResult<()> {
+    conn.execute(
+        r#"
+        INSERT INTO novel_snapshot (
+            novel_id,
+            fetched_at,
+            word_count,
+            read_count,
+            reply_count,
+            latest_chapter_id,
+            latest_chapter_name
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (novel_id, fetched_at) DO UPDATE SET
+            word_count = excluded.word_count,
+            read_count = excluded.read_count,
+            reply_count = excluded.reply_count,
+            latest_chapter_id = excluded.latest_chapter_id,
+            latest_chapter_name = excluded.latest_chapter_name
+        "#,
+        params![
+            novel_id,
+            snapshot.fetched_at,
+            snapshot.word_count,
+            snapshot.read_count,
+            snapshot.reply_count,
+            snapshot.latest_chapter_id,
+            snapshot.latest_chapter_name,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Every snapshot of one novel, oldest first.
+pub(crate) fn novel_history(conn: &Connection, novel_id: i32) -> FeiwenResult<Vec<NovelSnapshot>> {
+    let mut statement = conn.prepare(
+        r#"
+        SELECT
+            fetched_at,
+            word_count,
+            read_count,
+            reply_count,
+            latest_chapter_id,
+            latest_chapter_name
+        FROM novel_snapshot
+        WHERE novel_id = ?
+        ORDER BY fetched_at ASC
+        "#,
+    )?;
+    let rows = statement.query_map(params![novel_id], |row| {
+        Ok(NovelSnapshot {
+            fetched_at: row.get(0)?,
+            word_count: row.get(1)?,
+            read_count: row.get(2)?,
+            reply_count: row.get(3)?,
+            latest_chapter_id: row.get(4)?,
+            latest_chapter_name: row.get(5)?,
+        })
+    })?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::initialize_schema;
+
+    fn snapshot(fetched_at: i64, reply_count: Option<i32>) -> NovelSnapshot {
+        NovelSnapshot {
+            fetched_at,
+            word_count: 1000,
+            read_count: Some(100),
+            reply_count,
+            latest_chapter_id: 1,
+            latest_chapter_name: "chapter".to_owned(),
+        }
+    }
+
+    #[test]
+    fn history_is_oldest_first_and_one_snapshot_per_fetch() {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        record_snapshot(&conn, 1, &snapshot(200, Some(20))).unwrap();
+        record_snapshot(&conn, 1, &snapshot(100, Some(10))).unwrap();
+        record_snapshot(&conn, 1, &snapshot(200, Some(25))).unwrap();
+        record_snapshot(&conn, 2, &snapshot(100, None)).unwrap();
+
+        let history = novel_history(&conn, 1).unwrap();
+
+        assert_eq!(
+            history,
+            vec![snapshot(100, Some(10)), snapshot(200, Some(25))]
+        );
+        assert_eq!(history[1].metric(TrendMetric::Replies), Some(25));
+        assert_eq!(
+            novel_history(&conn, 2).unwrap()[0].metric(TrendMetric::Replies),
+            None
+        );
+    }
+}
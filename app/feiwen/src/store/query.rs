@@ -4,8 +4,16 @@ use duckdb::{
     Connection, Error as DuckdbError, params_from_iter,
     types::{Type, Value},
 };
-
-use crate::errors::FeiwenResult;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::FeiwenResult,
+    store::{
+        annotation::{Annotation, ReadStatus},
+        history,
+        search_index::{self, SearchField, SearchTerm, SearchToken},
+    },
+};
 
 const ID: &str = "id";
 const TITLE: &str = "name";
@@ -18,14 +26,20 @@ const READ_COUNT: &str = "read_count";
 const REPLY_COUNT: &str = "reply_count";
 const AUTHOR_ID: &str = "author_id";
 const AUTHOR_NAME: &str = "author_name";
+const READ_STATUS: &str = "read_status";
+const RATING: &str = "rating";
+const NOTE: &str = "note";
+const HIDDEN: &str = "hidden";
 
-#[derive(Debug, Clone, Default, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct QuerySpec {
     pub(crate) filter: FilterExpr,
     pub(crate) sorts: Vec<SortSpec>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum FilterExpr {
     All(Vec<FilterExpr>),
     Any(Vec<FilterExpr>),
@@ -39,7 +53,8 @@ impl Default for FilterExpr {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum Predicate {
     Text {
         field: TextField,
@@ -56,17 +71,33 @@ pub(crate) enum Predicate {
     },
     Tags(TagsPredicate),
     Author(AuthorPredicate),
+    /// How much a count changed over `window`, compared with `op`.
+    Growth {
+        metric: TrendMetric,
+        window: TrendWindow,
+        op: NumberOp,
+    },
+    /// The latest chapter differs from the one recorded at the start of
+    /// `window`.
+    Updated(TrendWindow),
+    ReadStatus(ReadStatus),
+    /// Every term of the search occurs in the title, description, latest
+    /// chapter or author name, or in the title's or author's pinyin.
+    Search(String),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum TextField {
     Title,
     Description,
     LatestChapter,
     AuthorName,
+    Note,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum TextOp {
     Contains,
     StartsWith,
@@ -74,7 +105,8 @@ pub(crate) enum TextOp {
     Equals,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum NumberField {
     NovelId,
     LatestChapterId,
@@ -82,9 +114,11 @@ pub(crate) enum NumberField {
     ReadCount,
     ReplyCount,
     AuthorId,
+    Rating,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum NumberOp {
     Eq(i32),
     Ne(i32),
@@ -95,12 +129,35 @@ pub(crate) enum NumberOp {
     Between { min: i32, max: i32 },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TrendMetric {
+    Words,
+    Reads,
+    Replies,
+}
+
+/// The stretch of history a growth is measured over. The baseline is the
+/// snapshot at the start of the window; a novel first seen inside the window
+/// is measured from its first snapshot, and one never snapshotted has no
+/// growth at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TrendWindow {
+    Days(u16),
+    /// Since the fetch before the novel's latest one.
+    LastFetch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum BoolField {
     IsLimit,
+    Hidden,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum TagsPredicate {
     Intersects(std::collections::HashSet<String>),
     ContainsAll(std::collections::HashSet<String>),
@@ -110,13 +167,15 @@ pub(crate) enum TagsPredicate {
     IsNotEmpty,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum AuthorRef {
     Id(i32),
     Name(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum AuthorPredicate {
     Is(AuthorRef),
     IsNot(AuthorRef),
@@ -124,23 +183,33 @@ pub(crate) enum AuthorPredicate {
     NotIn(Vec<AuthorRef>),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct SortSpec {
     pub(crate) expr: SortExpr,
     pub(crate) direction: SortDirection,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum SortDirection {
     Asc,
     Desc,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum SortExpr {
     Number(NumberField),
     Text(TextField),
     Bool(BoolField),
+    Growth {
+        metric: TrendMetric,
+        window: TrendWindow,
+    },
+    /// Unread first, then reading, read and dropped.
+    ReadStatus,
+    /// How well a novel matches the query's searches.
+    Relevance,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -158,6 +227,7 @@ pub(crate) struct NovelRecord {
     pub(crate) author_name: String,
     pub(crate) tags: Vec<String>,
     pub(crate) tag_ids: HashMap<String, Option<i32>>,
+    pub(crate) annotation: Annotation,
 }
 
 struct QueryStatement {
@@ -167,6 +237,8 @@ struct QueryStatement {
 
 struct QueryBuilder {
     params: Vec<Value>,
+    /// Unix seconds that day windows are counted back from.
+    now: i64,
 }
 
 impl QuerySpec {
@@ -177,10 +249,30 @@ impl QuerySpec {
     pub(crate) fn sort_count(&self) -> usize {
         self.sorts.len()
     }
+
+    /// The terms of the searches novels have to match, for highlighting.
+    pub(crate) fn search_terms(&self) -> Vec<SearchTerm> {
+        self.filter.search_terms()
+    }
 }
 
 pub(crate) fn query_records(conn: &Connection, spec: &QuerySpec) -> FeiwenResult<Vec<NovelRecord>> {
-    let statement = build_query(spec);
+    let mut records = Vec::new();
+    for_each_record(conn, spec, |record| {
+        records.push(record);
+        Ok(())
+    })?;
+    Ok(records)
+}
+
+/// Hands the novels `spec` matches to `visit` one at a time, in result
+/// order, so large result sets never sit in memory together.
+pub(crate) fn for_each_record(
+    conn: &Connection,
+    spec: &QuerySpec,
+    mut visit: impl FnMut(NovelRecord) -> FeiwenResult<()>,
+) -> FeiwenResult<()> {
+    let statement = build_query(spec, history::unix_now());
     let mut prepared = conn.prepare(&statement.sql)?;
     let rows = prepared.query_map(params_from_iter(statement.params.iter()), |row| {
         let tags = string_list(row.get(11)?, 11)?;
@@ -205,16 +297,42 @@ pub(crate) fn query_records(conn: &Connection, spec: &QuerySpec) -> FeiwenResult
             author_name: row.get(10)?,
             tags,
             tag_ids,
+            annotation: Annotation {
+                status: ReadStatus::parse(&row.get::<_, String>(13)?).unwrap_or_default(),
+                rating: row.get(14)?,
+                note: row.get(15)?,
+                hidden: row.get(16)?,
+            },
         })
     })?;
 
-    rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    for row in rows {
+        visit(row?)?;
+    }
+    Ok(())
 }
 
-fn build_query(spec: &QuerySpec) -> QueryStatement {
-    let mut builder = QueryBuilder { params: Vec::new() };
-    let filter = builder.filter(&spec.filter);
-    let order_by = builder.order_by(&spec.sorts);
+/// Counts the novels `spec` matches without loading them.
+pub(crate) fn count_records(conn: &Connection, spec: &QuerySpec) -> FeiwenResult<usize> {
+    let statement = build_query(spec, history::unix_now());
+    let count = conn.query_row(
+        &format!("SELECT count(*) FROM ({})", statement.sql),
+        params_from_iter(statement.params.iter()),
+        |row| row.get::<_, i64>(0),
+    )?;
+    Ok(count as usize)
+}
+
+fn build_query(spec: &QuerySpec, now: i64) -> QueryStatement {
+    let mut builder = QueryBuilder {
+        params: Vec::new(),
+        now,
+    };
+    let mut filter = builder.filter(&spec.filter);
+    if !spec.filter.mentions(BoolField::Hidden) {
+        filter = format!("NOT {HIDDEN} AND ({filter})");
+    }
+    let order_by = builder.order_by(spec);
     QueryStatement {
         sql: format!(
             r#"
@@ -238,10 +356,15 @@ fn build_query(spec: &QuerySpec) -> QueryStatement {
                     COALESCE(
                         list(tag.id ORDER BY nt.tag_id) FILTER (WHERE nt.tag_id IS NOT NULL),
                         []::INTEGER[]
-                    ) AS tag_ids
+                    ) AS tag_ids,
+                    COALESCE(na.status, 'unread') AS read_status,
+                    na.rating,
+                    COALESCE(na.note, '') AS note,
+                    COALESCE(na.hidden, FALSE) AS hidden
                 FROM novel n
                 LEFT JOIN novel_tag nt ON nt.novel_id = n.id
                 LEFT JOIN tag ON tag.name = nt.tag_id
+                LEFT JOIN novel_annotation na ON na.novel_id = n.id
                 GROUP BY
                     n.id,
                     n.name,
@@ -253,7 +376,11 @@ fn build_query(spec: &QuerySpec) -> QueryStatement {
                     n.read_count,
                     n.reply_count,
                     n.author_id,
-                    n.author_name
+                    n.author_name,
+                    na.status,
+                    na.rating,
+                    na.note,
+                    na.hidden
             )
             SELECT
                 id,
@@ -268,7 +395,11 @@ fn build_query(spec: &QuerySpec) -> QueryStatement {
                 author_id,
                 author_name,
                 tags,
-                tag_ids
+                tag_ids,
+                read_status,
+                rating,
+                note,
+                hidden
             FROM novel_query
             WHERE {filter}
             {order_by}
@@ -288,6 +419,30 @@ impl FilterExpr {
             FilterExpr::Predicate(_) => 1,
         }
     }
+
+    fn mentions(&self, field: BoolField) -> bool {
+        match self {
+            FilterExpr::All(filters) | FilterExpr::Any(filters) => {
+                filters.iter().any(|filter| filter.mentions(field))
+            }
+            FilterExpr::Not(filter) => filter.mentions(field),
+            FilterExpr::Predicate(Predicate::Bool { field: known, .. }) => *known == field,
+            FilterExpr::Predicate(_) => false,
+        }
+    }
+
+    /// The terms of the searches a novel has to match; negated searches
+    /// have nothing to rank or highlight.
+    fn search_terms(&self) -> Vec<SearchTerm> {
+        match self {
+            FilterExpr::All(filters) | FilterExpr::Any(filters) => {
+                filters.iter().flat_map(Self::search_terms).collect()
+            }
+            FilterExpr::Not(_) => Vec::new(),
+            FilterExpr::Predicate(Predicate::Search(query)) => search_index::search_terms(query),
+            FilterExpr::Predicate(_) => Vec::new(),
+        }
+    }
 }
 
 impl QueryBuilder {
@@ -321,6 +476,19 @@ impl QueryBuilder {
             }
             Predicate::Tags(predicate) => self.tags(predicate),
             Predicate::Author(predicate) => self.author(predicate),
+            Predicate::Growth { metric, window, op } => {
+                let growth = self.growth(*metric, *window);
+                self.compare(&growth, *op)
+            }
+            Predicate::Updated(window) => {
+                let baseline = self.baseline(LATEST_CHAPTER_ID, *window);
+                format!("{LATEST_CHAPTER_ID} <> {baseline}")
+            }
+            Predicate::ReadStatus(status) => {
+                let param = self.push_text(status.as_str());
+                format!("{READ_STATUS} = {param}")
+            }
+            Predicate::Search(query) => self.search(query),
         }
     }
 
@@ -336,7 +504,10 @@ impl QueryBuilder {
     }
 
     fn number(&mut self, field: NumberField, op: NumberOp) -> String {
-        let column = field.column();
+        self.compare(field.column(), op)
+    }
+
+    fn compare(&mut self, column: &str, op: NumberOp) -> String {
         match op {
             NumberOp::Eq(value) => format!("{column} = {}", self.push_i32(value)),
             NumberOp::Ne(value) => format!("{column} <> {}", self.push_i32(value)),
@@ -417,21 +588,186 @@ impl QueryBuilder {
         }
     }
 
-    fn order_by(&mut self, sorts: &[SortSpec]) -> String {
+    /// The current value of `metric` minus its value at the start of
+    /// `window`.
+    fn growth(&mut self, metric: TrendMetric, window: TrendWindow) -> String {
+        let column = metric.column();
+        let baseline = self.baseline(column, window);
+        format!("({column} - {baseline})")
+    }
+
+    /// `column` as recorded in the novel's baseline snapshot for `window`.
+    /// Only aggregates are correlated so the subqueries stay cheap to
+    /// decorrelate.
+    fn baseline(&mut self, column: &str, window: TrendWindow) -> String {
+        let fetched_at = match window {
+            TrendWindow::Days(days) => {
+                let cutoff = self.push_i64(self.now - i64::from(days) * history::DAY_SECONDS);
+                format!(
+                    "SELECT COALESCE(max(t.fetched_at) FILTER (WHERE t.fetched_at <= {cutoff}), min(t.fetched_at)) \
+                     FROM novel_snapshot t WHERE t.novel_id = novel_query.id"
+                )
+            }
+            TrendWindow::LastFetch => "SELECT max(t.fetched_at) FROM novel_snapshot t \
+                 WHERE t.novel_id = novel_query.id AND t.fetched_at < (\
+                     SELECT max(u.fetched_at) FROM novel_snapshot u WHERE u.novel_id = novel_query.id\
+                 )"
+            .to_owned(),
+        };
+        format!(
+            "(SELECT s.{column} FROM novel_snapshot s \
+             WHERE s.novel_id = novel_query.id AND s.fetched_at = ({fetched_at}))"
+        )
+    }
+
+    /// Every term has its tokens in the index and occurs as written in one
+    /// of the searched columns, or, for a term of plain letters, in a
+    /// pinyin key.
+    fn search(&mut self, query: &str) -> String {
+        let terms = search_index::search_terms(query);
+        if terms.is_empty() {
+            return bool_sql(true).to_owned();
+        }
+        terms
+            .iter()
+            .map(|term| format!("({})", self.search_term(term)))
+            .collect::<Vec<_>>()
+            .join(" AND ")
+    }
+
+    fn search_term(&mut self, term: &SearchTerm) -> String {
+        let tokens = term
+            .tokens
+            .iter()
+            .map(|token| {
+                let matches = self.token_match(token);
+                format!(
+                    "EXISTS (SELECT 1 FROM novel_search_token t \
+                     WHERE t.novel_id = novel_query.id AND {matches})"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let columns = [TITLE, DESCRIPTION, LATEST_CHAPTER_NAME, AUTHOR_NAME]
+            .into_iter()
+            .map(|column| {
+                let param = self.push_text(&term.text);
+                format!("contains(lower({column}), {param})")
+            })
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let text = format!("{tokens} AND ({columns})");
+        if !term.pinyin {
+            return text;
+        }
+        let pinyin = self.pinyin_match(term);
+        format!(
+            "({text}) OR EXISTS (SELECT 1 FROM novel_search_pinyin p \
+             WHERE p.novel_id = novel_query.id AND {pinyin})"
+        )
+    }
+
+    fn token_match(&mut self, token: &SearchToken) -> String {
+        let param = self.push_text(&token.text);
+        if token.prefix {
+            format!("starts_with(t.token, {param})")
+        } else {
+            format!("t.token = {param}")
+        }
+    }
+
+    fn any_token(&mut self, term: &SearchTerm) -> String {
+        let tokens = term
+            .tokens
+            .iter()
+            .map(|token| self.token_match(token))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        format!("({tokens})")
+    }
+
+    fn pinyin_match(&mut self, term: &SearchTerm) -> String {
+        let full_key = self.push_text(&term.text);
+        let initials = self.push_text(&term.text);
+        format!("(contains(p.full_key, {full_key}) OR contains(p.initials, {initials}))")
+    }
+
+    /// Sums, over the searches' terms, each matching token's field weight
+    /// times its rarity across novels times a saturating count of its
+    /// hits, plus half the field weight for a pinyin match. `None` when
+    /// nothing is searched.
+    fn relevance(&mut self, filter: &FilterExpr) -> Option<String> {
+        let terms = filter.search_terms();
+        if terms.is_empty() {
+            return None;
+        }
+        let weights = SearchField::ALL
+            .iter()
+            .map(|field| format!("WHEN '{}' THEN {:.1}", field.as_str(), field.weight()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let scores = terms
+            .iter()
+            .map(|term| {
+                // The frequency subquery comes first in the SQL, so it takes
+                // the first copy of the token parameters.
+                let frequency_tokens = self.any_token(term);
+                let tokens = self.any_token(term);
+                let mut score = format!(
+                    "COALESCE((SELECT sum(\
+                         (CASE t.field {weights} ELSE 1.0 END) \
+                         * ln(1 + n.total / f.novels) \
+                         * t.hits / (t.hits + 1.2)) \
+                     FROM novel_search_token t \
+                     JOIN (SELECT t.token, count(DISTINCT t.novel_id) AS novels \
+                           FROM novel_search_token t WHERE {frequency_tokens} \
+                           GROUP BY t.token) f ON f.token = t.token \
+                     CROSS JOIN (SELECT count(*) AS total FROM novel) n \
+                     WHERE t.novel_id = novel_query.id AND {tokens}), 0)"
+                );
+                if term.pinyin {
+                    let pinyin = self.pinyin_match(term);
+                    score.push_str(&format!(
+                        " + COALESCE((SELECT max(CASE p.field {weights} ELSE 1.0 END) / 2 \
+                         FROM novel_search_pinyin p \
+                         WHERE p.novel_id = novel_query.id AND {pinyin}), 0)"
+                    ));
+                }
+                score
+            })
+            .collect::<Vec<_>>()
+            .join(" + ");
+        Some(format!("({scores})"))
+    }
+
+    /// Sorts by `spec`'s sorts, or by relevance when it searches and has
+    /// none, breaking ties by id. Relevance without a search sorts nothing.
+    fn order_by(&mut self, spec: &QuerySpec) -> String {
+        let sorts = &spec.sorts;
         if sorts.is_empty() {
-            return "ORDER BY id ASC".to_owned();
+            return match self.relevance(&spec.filter) {
+                Some(relevance) => format!("ORDER BY {relevance} DESC, id ASC"),
+                None => "ORDER BY id ASC".to_owned(),
+            };
         }
         let mut sort_sql = sorts
             .iter()
-            .map(|sort| {
-                format!(
-                    "{} {} NULLS LAST",
-                    sort.expr.column(),
+            .filter_map(|sort| {
+                let expr = match &sort.expr {
+                    SortExpr::Number(field) => field.column().to_owned(),
+                    SortExpr::Text(field) => field.column().to_owned(),
+                    SortExpr::Bool(field) => field.column().to_owned(),
+                    SortExpr::Growth { metric, window } => self.growth(*metric, *window),
+                    SortExpr::ReadStatus => status_order(),
+                    SortExpr::Relevance => self.relevance(&spec.filter)?,
+                };
+                Some(format!(
+                    "{expr} {} NULLS LAST",
                     match sort.direction {
                         SortDirection::Asc => "ASC",
                         SortDirection::Desc => "DESC",
                     }
-                )
+                ))
             })
             .collect::<Vec<_>>();
         if !sorts
@@ -459,6 +795,11 @@ impl QueryBuilder {
         "?".to_owned()
     }
 
+    fn push_i64(&mut self, value: i64) -> String {
+        self.params.push(Value::BigInt(value));
+        "?".to_owned()
+    }
+
     fn push_bool(&mut self, value: bool) -> String {
         self.params.push(Value::Boolean(value));
         "?".to_owned()
@@ -477,6 +818,7 @@ impl TextField {
             TextField::Description => DESCRIPTION,
             TextField::LatestChapter => LATEST_CHAPTER_NAME,
             TextField::AuthorName => AUTHOR_NAME,
+            TextField::Note => NOTE,
         }
     }
 }
@@ -490,6 +832,7 @@ impl NumberField {
             NumberField::ReadCount => READ_COUNT,
             NumberField::ReplyCount => REPLY_COUNT,
             NumberField::AuthorId => AUTHOR_ID,
+            NumberField::Rating => RATING,
         }
     }
 }
@@ -498,20 +841,31 @@ impl BoolField {
     fn column(self) -> &'static str {
         match self {
             BoolField::IsLimit => IS_LIMIT,
+            BoolField::Hidden => HIDDEN,
         }
     }
 }
 
-impl SortExpr {
-    fn column(&self) -> &'static str {
+impl TrendMetric {
+    fn column(self) -> &'static str {
         match self {
-            SortExpr::Number(field) => field.column(),
-            SortExpr::Text(field) => field.column(),
-            SortExpr::Bool(field) => field.column(),
+            TrendMetric::Words => WORD_COUNT,
+            TrendMetric::Reads => READ_COUNT,
+            TrendMetric::Replies => REPLY_COUNT,
         }
     }
 }
 
+fn status_order() -> String {
+    let arms = ReadStatus::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, status)| format!("WHEN '{}' THEN {index}", status.as_str()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("CASE {READ_STATUS} {arms} END")
+}
+
 fn bool_sql(value: bool) -> &'static str {
     if value { "TRUE" } else { "FALSE" }
 }
@@ -616,6 +970,7 @@ mod tests {
                 ("rust".to_owned(), Some(1)),
                 ("systems".to_owned(), Some(2)),
             ]),
+            annotation: Annotation::default(),
         }
     }
 
@@ -667,6 +1022,7 @@ mod tests {
                 )
                 .unwrap();
             }
+            record.annotation.save(conn, record.id).unwrap();
         }
     }
 
@@ -730,6 +1086,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn count_records_counts_filtered_rows_regardless_of_sorting() {
+        let conn = connection();
+        insert_records(&conn, &[record(1), record(2), record(3)]);
+        let spec = QuerySpec {
+            filter: FilterExpr::Predicate(Predicate::Number {
+                field: NumberField::WordCount,
+                op: NumberOp::Gte(2000),
+            }),
+            sorts: vec![SortSpec {
+                expr: SortExpr::Text(TextField::AuthorName),
+                direction: SortDirection::Desc,
+            }],
+        };
+        assert_eq!(count_records(&conn, &spec).unwrap(), 2);
+        assert_eq!(count_records(&conn, &QuerySpec::default()).unwrap(), 3);
+    }
+
     #[test]
     fn text_predicates_cover_all_text_operators() {
         for (op, value) in [
@@ -903,6 +1277,210 @@ mod tests {
         assert_eq!(query_ids(spec, vec![one, two, three]), vec![2, 3, 1]);
     }
 
+    #[test]
+    fn growth_and_updated_compare_against_window_baselines() {
+        let conn = connection();
+        insert_records(&conn, &[record(1), record(2), record(3)]);
+        let now = history::unix_now();
+        let snapshot = |id: i32, days_ago: i64, reply_count: i32, chapter_id: i32| {
+            history::record_snapshot(
+                &conn,
+                id,
+                &history::NovelSnapshot {
+                    fetched_at: now - days_ago * history::DAY_SECONDS,
+                    word_count: id * 1000,
+                    read_count: Some(id * 100),
+                    reply_count: Some(reply_count),
+                    latest_chapter_id: chapter_id,
+                    latest_chapter_name: format!("chapter {chapter_id}"),
+                },
+            )
+            .unwrap();
+        };
+        snapshot(1, 10, 5, 9);
+        snapshot(1, 1, 10, 10);
+        snapshot(2, 10, 20, 20);
+        snapshot(2, 1, 20, 20);
+        // First seen inside every window: no earlier fetch to compare with.
+        snapshot(3, 2, 30, 30);
+
+        let ids = |filter: Predicate, sorts: Vec<SortSpec>| {
+            query_records(
+                &conn,
+                &QuerySpec {
+                    filter: FilterExpr::Predicate(filter),
+                    sorts,
+                },
+            )
+            .unwrap()
+            .into_iter()
+            .map(|record| record.id)
+            .collect::<Vec<_>>()
+        };
+        let week_growth = |op| Predicate::Growth {
+            metric: TrendMetric::Replies,
+            window: TrendWindow::Days(7),
+            op,
+        };
+
+        assert_eq!(ids(week_growth(NumberOp::Gt(0)), Vec::new()), vec![1]);
+        assert_eq!(ids(week_growth(NumberOp::Eq(0)), Vec::new()), vec![2, 3]);
+        assert_eq!(
+            ids(Predicate::Updated(TrendWindow::LastFetch), Vec::new()),
+            vec![1]
+        );
+        assert_eq!(
+            ids(
+                week_growth(NumberOp::Gte(0)),
+                vec![SortSpec {
+                    expr: SortExpr::Growth {
+                        metric: TrendMetric::Replies,
+                        window: TrendWindow::LastFetch,
+                    },
+                    direction: SortDirection::Asc,
+                }],
+            ),
+            vec![2, 1, 3]
+        );
+    }
+
+    #[test]
+    fn annotations_filter_sort_and_hide_records() {
+        let mut reading = record(1);
+        reading.annotation = Annotation {
+            status: ReadStatus::Reading,
+            rating: Some(4),
+            note: "slow start".to_owned(),
+            hidden: false,
+        };
+        let mut dropped = record(2);
+        dropped.annotation = Annotation {
+            status: ReadStatus::Dropped,
+            rating: Some(2),
+            ..Annotation::default()
+        };
+        let mut hidden = record(3);
+        hidden.annotation.hidden = true;
+        let records = || vec![reading.clone(), dropped.clone(), hidden.clone(), record(4)];
+        let spec = |filter: FilterExpr, sorts: Vec<SortSpec>| QuerySpec { filter, sorts };
+
+        assert_eq!(
+            query_ids(spec(FilterExpr::default(), Vec::new()), records()),
+            vec![1, 2, 4]
+        );
+        assert_eq!(
+            query_ids(
+                spec(
+                    FilterExpr::Predicate(Predicate::Bool {
+                        field: BoolField::Hidden,
+                        value: true,
+                    }),
+                    Vec::new()
+                ),
+                records()
+            ),
+            vec![3]
+        );
+        assert_eq!(
+            query_ids(
+                spec(
+                    FilterExpr::Predicate(Predicate::ReadStatus(ReadStatus::Unread)),
+                    Vec::new()
+                ),
+                records()
+            ),
+            vec![4]
+        );
+        assert_eq!(
+            query_ids(
+                spec(
+                    FilterExpr::Any(vec![
+                        FilterExpr::Predicate(Predicate::Number {
+                            field: NumberField::Rating,
+                            op: NumberOp::Gte(3),
+                        }),
+                        FilterExpr::Predicate(Predicate::Text {
+                            field: TextField::Note,
+                            op: TextOp::Contains,
+                            value: "slow".to_owned(),
+                        }),
+                    ]),
+                    Vec::new()
+                ),
+                records()
+            ),
+            vec![1]
+        );
+        assert_eq!(
+            query_ids(
+                spec(
+                    FilterExpr::default(),
+                    vec![SortSpec {
+                        expr: SortExpr::ReadStatus,
+                        direction: SortDirection::Desc,
+                    }]
+                ),
+                records()
+            ),
+            vec![2, 1, 4]
+        );
+    }
+
+    #[test]
+    fn search_matches_every_term_and_ranks_by_relevance() {
+        let mut conn = connection();
+        let mut in_title = record(1);
+        in_title.title = "穿书之后我成了系统".to_owned();
+        in_title.desc = "甜文".to_owned();
+        let mut in_description = record(2);
+        in_description.title = "重生".to_owned();
+        in_description.desc = "一本穿书文,穿书后和系统斗智斗勇".to_owned();
+        let mut unrelated = record(3);
+        unrelated.title = "无关".to_owned();
+        insert_records(&conn, &[in_title, in_description, unrelated]);
+        search_index::rebuild(&mut conn).unwrap();
+
+        let ids = |filter: FilterExpr, sorts: Vec<SortSpec>| {
+            query_records(&conn, &QuerySpec { filter, sorts })
+                .unwrap()
+                .into_iter()
+                .map(|record| record.id)
+                .collect::<Vec<_>>()
+        };
+        let search = |query: &str| FilterExpr::Predicate(Predicate::Search(query.to_owned()));
+
+        assert_eq!(ids(search("穿书"), Vec::new()), vec![1, 2]);
+        assert_eq!(ids(search("穿书 系统"), Vec::new()), vec![1, 2]);
+        assert_eq!(ids(search("书文 AUTHOR"), Vec::new()), vec![2]);
+        assert_eq!(ids(search("书系"), Vec::new()), Vec::<i32>::new());
+        assert_eq!(ids(search("chuanshu"), Vec::new()), vec![1]);
+        assert_eq!(ids(search("RUS"), Vec::new()), Vec::<i32>::new());
+        assert_eq!(
+            ids(FilterExpr::Not(Box::new(search("穿书"))), Vec::new()),
+            vec![3]
+        );
+        assert_eq!(
+            ids(
+                search("穿书"),
+                vec![SortSpec {
+                    expr: SortExpr::Relevance,
+                    direction: SortDirection::Asc,
+                }]
+            ),
+            vec![2, 1]
+        );
+        assert_eq!(
+            ids(
+                FilterExpr::default(),
+                vec![SortSpec {
+                    expr: SortExpr::Relevance,
+                    direction: SortDirection::Desc,
+                }]
+            ),
+            vec![1, 2, 3]
+        );
+    }
+
     #[test]
     fn injection_shaped_strings_are_plain_values() {
         let injection = "' OR 1=1 --";
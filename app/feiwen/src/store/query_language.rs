@@ -0,0 +1,1433 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use thiserror::Error;
+
+use crate::store::{
+    annotation::ReadStatus,
+    query::{
+        AuthorPredicate, AuthorRef, BoolField, FilterExpr, NumberField, NumberOp, Predicate,
+        QuerySpec, SortDirection, SortExpr, SortSpec, TagsPredicate, TextField, TextOp,
+        TrendMetric, TrendWindow,
+    },
+};
+
+/// Field names accepted by the query language. The first name of a field is
+/// the one the formatter writes.
+const FIELDS: &[(&str, Field)] = &[
+    ("title", Field::Text(TextField::Title)),
+    ("description", Field::Text(TextField::Description)),
+    ("chapter", Field::Text(TextField::LatestChapter)),
+    ("author_name", Field::Text(TextField::AuthorName)),
+    ("id", Field::Number(NumberField::NovelId)),
+    ("chapter_id", Field::Number(NumberField::LatestChapterId)),
+    ("words", Field::Number(NumberField::WordCount)),
+    ("word", Field::Number(NumberField::WordCount)),
+    ("reads", Field::Number(NumberField::ReadCount)),
+    ("read", Field::Number(NumberField::ReadCount)),
+    ("replies", Field::Number(NumberField::ReplyCount)),
+    ("reply", Field::Number(NumberField::ReplyCount)),
+    ("author_id", Field::Number(NumberField::AuthorId)),
+    ("limit", Field::Bool(BoolField::IsLimit)),
+    ("tag", Field::Tags),
+    ("tags", Field::Tags),
+    ("author", Field::Author),
+    ("updated", Field::Updated),
+    ("status", Field::ReadStatus),
+    ("rating", Field::Number(NumberField::Rating)),
+    ("note", Field::Text(TextField::Note)),
+    ("hidden", Field::Bool(BoolField::Hidden)),
+    ("search", Field::Search),
+    ("relevance", Field::Relevance),
+    ("sort", Field::Sort),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Text(TextField),
+    Number(NumberField),
+    Bool(BoolField),
+    Tags,
+    Author,
+    /// `replies@7d`: how much a count grew over a window.
+    Growth(TrendMetric, TrendWindow),
+    Updated,
+    ReadStatus,
+    /// Full-text search over the indexed text.
+    Search,
+    /// How well novels match the searches; sort only.
+    Relevance,
+    Sort,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Colon,
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    Prefix,
+    Suffix,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{kind}")]
+pub(crate) struct ParseError {
+    /// Byte range of the offending text; empty when something is missing.
+    pub(crate) span: Range<usize>,
+    pub(crate) kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub(crate) enum ParseErrorKind {
+    #[error("未知字段 {0}")]
+    UnknownField(String),
+    #[error("字段 {0} 只能用于排序")]
+    SortOnlyField(&'static str),
+    #[error("字段 {0} 不能用于排序")]
+    NotSortable(&'static str),
+    #[error("字段 {field} 不支持运算符 {operator}")]
+    UnsupportedOperator {
+        field: &'static str,
+        operator: &'static str,
+    },
+    #[error("无效的运算符")]
+    InvalidOperator,
+    #[error("缺少值")]
+    MissingValue,
+    #[error("引号未闭合")]
+    UnterminatedQuote,
+    #[error("{0} 不是有效数字")]
+    InvalidNumber(String),
+    #[error("最大值必须大于或等于最小值")]
+    InvalidRange,
+    #[error("{0} 不是 true 或 false")]
+    InvalidBool(String),
+    #[error("{0} 不是有效的时间范围,请写 last 或天数加 d,如 7d")]
+    InvalidWindow(String),
+    #[error("{0} 不是有效的阅读状态,请写 unread、reading、read 或 dropped")]
+    InvalidStatus(String),
+    #[error("不能在同一列表中混用 , 和 |")]
+    MixedSeparators,
+    #[error("字段 {0} 不接受这种列表")]
+    UnexpectedList(&'static str),
+    #[error("有多位作者名为 {0},请改用 #作者 ID")]
+    AmbiguousAuthor(String),
+    #[error("排序只能写在最外层")]
+    MisplacedSort,
+    #[error("{0} 后缺少条件")]
+    DanglingKeyword(&'static str),
+    #[error("多余的右括号")]
+    UnexpectedClose,
+    #[error("括号未闭合")]
+    UnclosedGroup,
+}
+
+/// What the value under the cursor names, for completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CompletionKind {
+    Tag,
+    Author,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Completion {
+    pub(crate) kind: CompletionKind,
+    /// The value being typed, quotes included; a suggestion replaces it.
+    pub(crate) span: Range<usize>,
+    pub(crate) prefix: String,
+}
+
+/// Maps author names to the references stored in queries. A name shared by
+/// several authors resolves to nothing and has to be written as `#id`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AuthorNames {
+    by_name: HashMap<String, Option<AuthorRef>>,
+    names: HashMap<AuthorRef, String>,
+}
+
+impl AuthorNames {
+    pub(crate) fn new<'a>(authors: impl IntoIterator<Item = (&'a AuthorRef, &'a str)>) -> Self {
+        let mut directory = Self::default();
+        for (author, name) in authors {
+            directory
+                .by_name
+                .entry(name.to_owned())
+                .and_modify(|known| {
+                    if known.as_ref() != Some(author) {
+                        *known = None;
+                    }
+                })
+                .or_insert_with(|| Some(author.clone()));
+            directory.names.insert(author.clone(), name.to_owned());
+        }
+        directory
+    }
+
+    fn resolve(&self, name: &str) -> Result<AuthorRef, ParseErrorKind> {
+        match self.by_name.get(name) {
+            Some(Some(author)) => Ok(author.clone()),
+            Some(None) => Err(ParseErrorKind::AmbiguousAuthor(name.to_owned())),
+            None => Ok(AuthorRef::Name(name.to_owned())),
+        }
+    }
+
+    /// How `author` is written so that parsing it gives it back: its name
+    /// when that is unambiguous, otherwise `#id` or `@name`.
+    pub(crate) fn spell(&self, author: &AuthorRef) -> String {
+        match author {
+            AuthorRef::Id(id) => match self.names.get(author) {
+                Some(name) if self.resolve(name).as_ref() == Ok(author) => quote_value(name),
+                _ => format!("#{id}"),
+            },
+            AuthorRef::Name(name) if self.resolve(name).as_ref() == Ok(author) => quote_value(name),
+            AuthorRef::Name(name) => format!("@{}", quote(name)),
+        }
+    }
+}
+
+/// Parses a query such as `tag:甜文 -tag:虐 words>=100000 author:"某某" sort:-replies`.
+///
+/// Terms are joined with AND; `OR` separates alternatives, `-` or `NOT`
+/// negates and parentheses group. A bare word searches titles and
+/// `search:` searches the full-text index. `sort:` terms are only allowed at
+/// the top level and apply in the order written.
+pub(crate) fn parse(text: &str, authors: &AuthorNames) -> Result<QuerySpec, ParseError> {
+    Parser::new(text, authors).query()
+}
+
+/// Finds the tag or author value around `cursor`, even when the rest of the
+/// query does not parse yet.
+pub(crate) fn completion_at(text: &str, cursor: usize) -> Option<Completion> {
+    let authors = AuthorNames::default();
+    let mut parser = Parser::new(text, &authors);
+    let _ = parser.query();
+    parser
+        .completions
+        .into_iter()
+        .find(|completion| completion.span.start <= cursor && cursor <= completion.span.end)
+}
+
+/// Writes `spec` in the query language. Parsing the result gives back the
+/// same query, except that one-element "any of" tag and author lists come
+/// back as their single-value forms, which match the same novels.
+pub(crate) fn format(spec: &QuerySpec, authors: &AuthorNames) -> String {
+    let mut parts = Vec::new();
+    match &spec.filter {
+        FilterExpr::All(children) => {
+            parts.extend(children.iter().map(|child| format_expr(child, authors)));
+        }
+        FilterExpr::Any(children) => parts.push(format_any(children, authors)),
+        other => parts.push(format_expr(other, authors)),
+    }
+    parts.extend(spec.sorts.iter().map(format_sort));
+    parts.join(" ")
+}
+
+/// Quotes a value unless it can be written bare.
+pub(crate) fn quote_value(value: &str) -> String {
+    let bare = !value.is_empty()
+        && !matches!(value, "none" | "any")
+        && !value.starts_with(['#', '@', '-'])
+        && !value
+            .chars()
+            .any(|character| character.is_whitespace() || "\"\\(),|".contains(character));
+    if bare { value.to_owned() } else { quote(value) }
+}
+
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for character in value.chars() {
+        if matches!(character, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(character);
+    }
+    quoted.push('"');
+    quoted
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    authors: &'a AuthorNames,
+    completions: Vec<Completion>,
+}
+
+/// One value as written, with quotes removed.
+struct Atom {
+    text: String,
+    span: Range<usize>,
+    quoted: bool,
+}
+
+struct List {
+    atoms: Vec<Atom>,
+    /// The separator used and where it first appears.
+    separator: Option<(char, Range<usize>)>,
+}
+
+enum Term {
+    Filter(FilterExpr),
+    Sort(SortSpec),
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str, authors: &'a AuthorNames) -> Self {
+        Self {
+            text,
+            pos: 0,
+            authors,
+            completions: Vec::new(),
+        }
+    }
+
+    fn query(&mut self) -> Result<QuerySpec, ParseError> {
+        let mut sorts = Vec::new();
+        let filter = self.group(None, Some(&mut sorts))?;
+        Ok(QuerySpec { filter, sorts })
+    }
+
+    /// Parses terms up to the closing parenthesis of the group opened at
+    /// `open`, or to the end of the text for the top level.
+    fn group(
+        &mut self,
+        open: Option<usize>,
+        mut sorts: Option<&mut Vec<SortSpec>>,
+    ) -> Result<FilterExpr, ParseError> {
+        let mut alternatives = Vec::new();
+        let mut current = Vec::new();
+        let mut any = false;
+        // A keyword still waiting for the term after it.
+        let mut pending: Option<(&'static str, Range<usize>)> = None;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => {
+                    if let Some(open) = open {
+                        return Err(self.error(open..open + 1, ParseErrorKind::UnclosedGroup));
+                    }
+                    break;
+                }
+                Some(')') => {
+                    if open.is_none() {
+                        let span = self.pos..self.pos + 1;
+                        return Err(self.error(span, ParseErrorKind::UnexpectedClose));
+                    }
+                    self.pos += 1;
+                    break;
+                }
+                _ => {}
+            }
+            if let Some(span) = self.keyword("OR") {
+                if let Some((keyword, span)) = pending.take() {
+                    return Err(self.error(span, ParseErrorKind::DanglingKeyword(keyword)));
+                }
+                if !current.is_empty() {
+                    alternatives.push(std::mem::take(&mut current));
+                    pending = Some(("OR", span));
+                } else if any {
+                    return Err(self.error(span, ParseErrorKind::DanglingKeyword("OR")));
+                }
+                // Otherwise this is a leading OR, which writes a group of
+                // fewer than two alternatives.
+                any = true;
+                continue;
+            }
+            if let Some(span) = self.keyword("AND") {
+                if current.is_empty() || pending.is_some() {
+                    return Err(self.error(span, ParseErrorKind::DanglingKeyword("AND")));
+                }
+                pending = Some(("AND", span));
+                continue;
+            }
+            pending = None;
+            match self.unary(sorts.is_some())? {
+                Term::Filter(filter) => current.push(filter),
+                Term::Sort(sort) => {
+                    if let Some(sorts) = sorts.as_deref_mut() {
+                        sorts.push(sort);
+                    }
+                }
+            }
+        }
+        if let Some((keyword, span)) = pending {
+            return Err(self.error(span, ParseErrorKind::DanglingKeyword(keyword)));
+        }
+        if !any {
+            return Ok(FilterExpr::All(current));
+        }
+        if !current.is_empty() {
+            alternatives.push(current);
+        }
+        Ok(FilterExpr::Any(
+            alternatives
+                .into_iter()
+                .map(|mut terms| {
+                    if terms.len() == 1 {
+                        terms.remove(0)
+                    } else {
+                        FilterExpr::All(terms)
+                    }
+                })
+                .collect(),
+        ))
+    }
+
+    fn unary(&mut self, top_level: bool) -> Result<Term, ParseError> {
+        let start = self.pos;
+        let negated = if self.keyword("NOT").is_some() {
+            true
+        } else if self.peek() == Some('-')
+            && self.peek_at(1).is_some_and(|next| !next.is_whitespace())
+        {
+            self.pos += 1;
+            true
+        } else {
+            false
+        };
+        if negated {
+            self.skip_whitespace();
+            if matches!(self.peek(), None | Some(')')) {
+                return Err(self.error(start..self.pos, ParseErrorKind::DanglingKeyword("NOT")));
+            }
+            return match self.unary(false)? {
+                Term::Filter(filter) => Ok(Term::Filter(FilterExpr::Not(Box::new(filter)))),
+                Term::Sort(_) => unreachable!("sorts are rejected below the top level"),
+            };
+        }
+        if self.peek() == Some('(') {
+            self.pos += 1;
+            return self.group(Some(start), None).map(Term::Filter);
+        }
+        self.term(top_level)
+    }
+
+    fn term(&mut self, top_level: bool) -> Result<Term, ParseError> {
+        let start = self.pos;
+        if self.peek() == Some('"') {
+            let atom = self.atom(false, None)?;
+            return Ok(Term::Filter(title_contains(atom.text)));
+        }
+        let name = self.field_name();
+        if name.is_empty()
+            || !self
+                .peek()
+                .is_some_and(|character| ":=!<>^$".contains(character))
+        {
+            self.pos = start;
+            let word = self
+                .take_while(|character| !character.is_whitespace() && !"()".contains(character));
+            return Ok(Term::Filter(title_contains(word.to_owned())));
+        }
+        let name_span = start..self.pos;
+        let field = self.field(name, name_span.clone())?;
+        let operator_start = self.pos;
+        let operator = self.operator()?;
+        let operator_span = operator_start..self.pos;
+        let supported = match field {
+            Field::Text(_) => matches!(
+                operator,
+                Operator::Colon | Operator::Eq | Operator::Prefix | Operator::Suffix
+            ),
+            Field::Number(_) | Field::Growth(..) => {
+                !matches!(operator, Operator::Prefix | Operator::Suffix)
+            }
+            Field::Bool(_) | Field::Updated | Field::ReadStatus | Field::Sort => {
+                matches!(operator, Operator::Colon | Operator::Eq)
+            }
+            Field::Search | Field::Relevance => operator == Operator::Colon,
+            Field::Tags => matches!(operator, Operator::Colon | Operator::Eq | Operator::Lte),
+            Field::Author => matches!(operator, Operator::Colon | Operator::Eq | Operator::Ne),
+        };
+        if !supported {
+            return Err(self.unsupported(field, operator, operator_span));
+        }
+        let predicate = match field {
+            Field::Text(field) => {
+                let op = match operator {
+                    Operator::Colon => TextOp::Contains,
+                    Operator::Eq => TextOp::Equals,
+                    Operator::Prefix => TextOp::StartsWith,
+                    _ => TextOp::EndsWith,
+                };
+                let value = self.atom(false, None)?.text;
+                Predicate::Text { field, op, value }
+            }
+            Field::Number(number) => {
+                if !filterable(number) {
+                    return Err(
+                        self.error(name_span, ParseErrorKind::SortOnlyField(field_name(field)))
+                    );
+                }
+                let atom = self.atom(false, None)?;
+                let op = self.number_op(field, operator, operator_span, atom)?;
+                Predicate::Number { field: number, op }
+            }
+            Field::Growth(metric, window) => {
+                let atom = self.atom(false, None)?;
+                let op = self.number_op(field, operator, operator_span, atom)?;
+                Predicate::Growth { metric, window, op }
+            }
+            Field::Updated => {
+                let atom = self.atom(false, None)?;
+                let window = parse_window(&atom.text).ok_or_else(|| {
+                    self.error(atom.span, ParseErrorKind::InvalidWindow(atom.text))
+                })?;
+                Predicate::Updated(window)
+            }
+            Field::ReadStatus => {
+                let atom = self.atom(false, None)?;
+                let status = ReadStatus::parse(&atom.text).ok_or_else(|| {
+                    self.error(atom.span, ParseErrorKind::InvalidStatus(atom.text))
+                })?;
+                Predicate::ReadStatus(status)
+            }
+            Field::Bool(field) => {
+                let atom = self.atom(false, None)?;
+                let value = match atom.text.to_ascii_lowercase().as_str() {
+                    "true" | "yes" => true,
+                    "false" | "no" => false,
+                    _ => {
+                        return Err(self.error(atom.span, ParseErrorKind::InvalidBool(atom.text)));
+                    }
+                };
+                Predicate::Bool { field, value }
+            }
+            Field::Search => Predicate::Search(self.atom(false, None)?.text),
+            Field::Relevance => {
+                return Err(self.error(name_span, ParseErrorKind::SortOnlyField(field_name(field))));
+            }
+            Field::Tags => Predicate::Tags(self.tags(operator)?),
+            Field::Author => Predicate::Author(self.author(operator == Operator::Ne)?),
+            Field::Sort => {
+                let sort = self.sort()?;
+                if !top_level {
+                    return Err(self.error(start..self.pos, ParseErrorKind::MisplacedSort));
+                }
+                return Ok(Term::Sort(sort));
+            }
+        };
+        Ok(Term::Filter(FilterExpr::Predicate(predicate)))
+    }
+
+    fn operator(&mut self) -> Result<Operator, ParseError> {
+        const OPERATORS: &[(&str, Operator)] = &[
+            ("!=", Operator::Ne),
+            ("<=", Operator::Lte),
+            (">=", Operator::Gte),
+            ("^=", Operator::Prefix),
+            ("$=", Operator::Suffix),
+            (":", Operator::Colon),
+            ("=", Operator::Eq),
+            ("<", Operator::Lt),
+            (">", Operator::Gt),
+        ];
+        let rest = &self.text[self.pos..];
+        let (text, operator) = OPERATORS
+            .iter()
+            .find(|(text, _)| rest.starts_with(text))
+            .ok_or_else(|| self.error(self.pos..self.pos + 1, ParseErrorKind::InvalidOperator))?;
+        self.pos += text.len();
+        Ok(*operator)
+    }
+
+    /// `words:1000..5000` is an inclusive range; the other operators compare
+    /// with a single number.
+    fn number_op(
+        &self,
+        field: Field,
+        operator: Operator,
+        operator_span: Range<usize>,
+        atom: Atom,
+    ) -> Result<NumberOp, ParseError> {
+        let number = |text: &str| {
+            text.parse::<i32>().map_err(|_| {
+                self.error(
+                    atom.span.clone(),
+                    ParseErrorKind::InvalidNumber(text.to_owned()),
+                )
+            })
+        };
+        if let Some((min, max)) = atom.text.split_once("..") {
+            if !matches!(operator, Operator::Colon | Operator::Eq) {
+                return Err(self.unsupported(field, operator, operator_span));
+            }
+            let (min, max) = (number(min)?, number(max)?);
+            if min > max {
+                return Err(self.error(atom.span.clone(), ParseErrorKind::InvalidRange));
+            }
+            return Ok(NumberOp::Between { min, max });
+        }
+        let value = number(&atom.text)?;
+        Ok(match operator {
+            Operator::Ne => NumberOp::Ne(value),
+            Operator::Lt => NumberOp::Lt(value),
+            Operator::Lte => NumberOp::Lte(value),
+            Operator::Gt => NumberOp::Gt(value),
+            Operator::Gte => NumberOp::Gte(value),
+            _ => NumberOp::Eq(value),
+        })
+    }
+
+    /// `tag:a,b` needs all of them, `tag:a|b` any, `tag=a,b` exactly those
+    /// and `tag<=a,b` none besides them. `tag:none` and `tag:any` test for
+    /// untagged and tagged novels.
+    fn tags(&mut self, operator: Operator) -> Result<TagsPredicate, ParseError> {
+        let list = self.list(Some(CompletionKind::Tag))?;
+        if let [atom] = list.atoms.as_slice()
+            && operator == Operator::Colon
+            && !atom.quoted
+        {
+            match atom.text.as_str() {
+                "none" => return Ok(TagsPredicate::IsEmpty),
+                "any" => return Ok(TagsPredicate::IsNotEmpty),
+                _ => {}
+            }
+        }
+        let any = match list.separator {
+            Some(('|', _)) if operator == Operator::Colon => true,
+            Some(('|', span)) => {
+                return Err(self.error(span, ParseErrorKind::UnexpectedList("tag")));
+            }
+            _ => false,
+        };
+        let values = list
+            .atoms
+            .into_iter()
+            .map(|atom| atom.text)
+            .collect::<HashSet<_>>();
+        Ok(match operator {
+            Operator::Colon if any => TagsPredicate::Intersects(values),
+            Operator::Colon => TagsPredicate::ContainsAll(values),
+            Operator::Eq => TagsPredicate::Equals(values),
+            _ => TagsPredicate::ContainedBy(values),
+        })
+    }
+
+    /// `author:name` or `author:a|b`, negated with `!=`. `#id` names an
+    /// author by id and `@name` an author without one.
+    fn author(&mut self, negated: bool) -> Result<AuthorPredicate, ParseError> {
+        let mut authors = Vec::new();
+        let mut listed = self.empty_list();
+        if !listed {
+            loop {
+                authors.push(self.author_ref()?);
+                match self.peek() {
+                    Some('|') => {
+                        self.pos += 1;
+                        listed = true;
+                    }
+                    Some(',') => {
+                        let span = self.pos..self.pos + 1;
+                        return Err(self.error(span, ParseErrorKind::UnexpectedList("author")));
+                    }
+                    _ => break,
+                }
+            }
+        }
+        Ok(match (listed, negated) {
+            (false, false) => AuthorPredicate::Is(authors.remove(0)),
+            (false, true) => AuthorPredicate::IsNot(authors.remove(0)),
+            (true, false) => AuthorPredicate::In(authors),
+            (true, true) => AuthorPredicate::NotIn(authors),
+        })
+    }
+
+    fn author_ref(&mut self) -> Result<AuthorRef, ParseError> {
+        match self.peek() {
+            Some('#') => {
+                self.pos += 1;
+                let atom = self.atom(true, None)?;
+                atom.text.parse().map(AuthorRef::Id).map_err(|_| {
+                    self.error(atom.span, ParseErrorKind::InvalidNumber(atom.text.clone()))
+                })
+            }
+            Some('@') => {
+                self.pos += 1;
+                Ok(AuthorRef::Name(
+                    self.atom(true, Some(CompletionKind::Author))?.text,
+                ))
+            }
+            _ => {
+                let atom = self.atom(true, Some(CompletionKind::Author))?;
+                self.authors
+                    .resolve(&atom.text)
+                    .map_err(|kind| self.error(atom.span, kind))
+            }
+        }
+    }
+
+    /// `sort:field` ascending or `sort:-field` descending.
+    fn sort(&mut self) -> Result<SortSpec, ParseError> {
+        let direction = match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                SortDirection::Desc
+            }
+            Some('+') => {
+                self.pos += 1;
+                SortDirection::Asc
+            }
+            _ => SortDirection::Asc,
+        };
+        let start = self.pos;
+        let name = self.field_name();
+        let span = start..self.pos;
+        if name.is_empty() {
+            return Err(self.error(span, ParseErrorKind::MissingValue));
+        }
+        let field = self.field(name, span.clone())?;
+        let expr = match field {
+            Field::Text(TextField::Description | TextField::Note)
+            | Field::Bool(BoolField::Hidden)
+            | Field::Tags
+            | Field::Author
+            | Field::Updated
+            | Field::Search
+            | Field::Sort => {
+                return Err(self.error(span, ParseErrorKind::NotSortable(field_name(field))));
+            }
+            Field::Text(field) => SortExpr::Text(field),
+            Field::Number(field) => SortExpr::Number(field),
+            Field::Bool(field) => SortExpr::Bool(field),
+            Field::Growth(metric, window) => SortExpr::Growth { metric, window },
+            Field::ReadStatus => SortExpr::ReadStatus,
+            Field::Relevance => SortExpr::Relevance,
+        };
+        Ok(SortSpec { expr, direction })
+    }
+
+    fn field_name(&mut self) -> &'a str {
+        self.take_while(|character| character.is_ascii_alphanumeric() || "_@".contains(character))
+    }
+
+    /// Resolves a field name, including growth names such as `replies@7d`.
+    fn field(&self, name: &str, span: Range<usize>) -> Result<Field, ParseError> {
+        let unknown = || self.error(span.clone(), ParseErrorKind::UnknownField(name.to_owned()));
+        let Some((base, window)) = name.split_once('@') else {
+            return lookup_field(name).ok_or_else(unknown);
+        };
+        let metric = match lookup_field(base) {
+            Some(Field::Number(NumberField::WordCount)) => TrendMetric::Words,
+            Some(Field::Number(NumberField::ReadCount)) => TrendMetric::Reads,
+            Some(Field::Number(NumberField::ReplyCount)) => TrendMetric::Replies,
+            _ => return Err(unknown()),
+        };
+        let window_span = span.start + base.len() + 1..span.end;
+        let window = parse_window(window).ok_or_else(|| {
+            self.error(
+                window_span,
+                ParseErrorKind::InvalidWindow(window.to_owned()),
+            )
+        })?;
+        Ok(Field::Growth(metric, window))
+    }
+
+    /// Values separated by `,` or `|`, or `()` for none.
+    fn list(&mut self, completion: Option<CompletionKind>) -> Result<List, ParseError> {
+        let mut list = List {
+            atoms: Vec::new(),
+            separator: None,
+        };
+        if self.empty_list() {
+            return Ok(list);
+        }
+        loop {
+            list.atoms.push(self.atom(true, completion)?);
+            let Some(next) = self.peek().filter(|next| matches!(next, ',' | '|')) else {
+                break;
+            };
+            let span = self.pos..self.pos + 1;
+            match &list.separator {
+                Some((used, _)) if *used != next => {
+                    return Err(self.error(span, ParseErrorKind::MixedSeparators));
+                }
+                Some(_) => {}
+                None => list.separator = Some((next, span)),
+            }
+            self.pos += 1;
+        }
+        Ok(list)
+    }
+
+    fn empty_list(&mut self) -> bool {
+        let empty = self.text[self.pos..].starts_with("()");
+        if empty {
+            self.pos += 2;
+        }
+        empty
+    }
+
+    /// Reads one value. Bare values end at whitespace or a parenthesis, and
+    /// inside lists also at `,` and `|`.
+    fn atom(
+        &mut self,
+        in_list: bool,
+        completion: Option<CompletionKind>,
+    ) -> Result<Atom, ParseError> {
+        let start = self.pos;
+        let atom = if self.peek() == Some('"') {
+            self.pos += 1;
+            let mut text = String::new();
+            let mut escaped = false;
+            loop {
+                let Some(character) = self.peek() else {
+                    self.complete(completion, start..self.pos, &text);
+                    return Err(self.error(start..self.pos, ParseErrorKind::UnterminatedQuote));
+                };
+                self.pos += character.len_utf8();
+                match character {
+                    _ if escaped => {
+                        text.push(character);
+                        escaped = false;
+                    }
+                    '\\' => escaped = true,
+                    '"' => break,
+                    _ => text.push(character),
+                }
+            }
+            Atom {
+                text,
+                span: start..self.pos,
+                quoted: true,
+            }
+        } else {
+            let ends = |character: char| {
+                character.is_whitespace()
+                    || "()".contains(character)
+                    || (in_list && ",|".contains(character))
+            };
+            let text = self.take_while(|character| !ends(character));
+            Atom {
+                text: text.to_owned(),
+                span: start..self.pos,
+                quoted: false,
+            }
+        };
+        self.complete(completion, atom.span.clone(), &atom.text);
+        if atom.text.is_empty() && !atom.quoted {
+            return Err(self.error(atom.span, ParseErrorKind::MissingValue));
+        }
+        Ok(atom)
+    }
+
+    fn complete(&mut self, kind: Option<CompletionKind>, span: Range<usize>, prefix: &str) {
+        if let Some(kind) = kind {
+            self.completions.push(Completion {
+                kind,
+                span,
+                prefix: prefix.to_owned(),
+            });
+        }
+    }
+
+    /// Consumes `keyword` when it stands alone as a word.
+    fn keyword(&mut self, keyword: &str) -> Option<Range<usize>> {
+        let rest = &self.text[self.pos..];
+        let after = rest.strip_prefix(keyword)?;
+        if !after
+            .chars()
+            .next()
+            .is_none_or(|next| next.is_whitespace() || "()".contains(next))
+        {
+            return None;
+        }
+        let span = self.pos..self.pos + keyword.len();
+        self.pos = span.end;
+        Some(span)
+    }
+
+    fn take_while(&mut self, keep: impl Fn(char) -> bool) -> &'a str {
+        let text = self.text;
+        let rest = &text[self.pos..];
+        let end = rest
+            .char_indices()
+            .find(|(_, character)| !keep(*character))
+            .map_or(rest.len(), |(index, _)| index);
+        self.pos += end;
+        &rest[..end]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.text[self.pos..].chars().nth(offset)
+    }
+
+    fn error(&self, span: Range<usize>, kind: ParseErrorKind) -> ParseError {
+        ParseError { span, kind }
+    }
+
+    fn unsupported(&self, field: Field, operator: Operator, span: Range<usize>) -> ParseError {
+        self.error(
+            span,
+            ParseErrorKind::UnsupportedOperator {
+                field: field_name(field),
+                operator: operator_text(operator),
+            },
+        )
+    }
+}
+
+fn title_contains(value: String) -> FilterExpr {
+    FilterExpr::Predicate(Predicate::Text {
+        field: TextField::Title,
+        op: TextOp::Contains,
+        value,
+    })
+}
+
+/// Number fields the advanced builder can filter on; the others only sort.
+fn filterable(field: NumberField) -> bool {
+    matches!(
+        field,
+        NumberField::WordCount
+            | NumberField::ReadCount
+            | NumberField::ReplyCount
+            | NumberField::Rating
+    )
+}
+
+/// `last` for the fetch before the latest one, or a number of days such
+/// as `7d`.
+fn parse_window(text: &str) -> Option<TrendWindow> {
+    if text.eq_ignore_ascii_case("last") {
+        return Some(TrendWindow::LastFetch);
+    }
+    let days = text.strip_suffix(['d', 'D'])?;
+    if !days.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    days.parse()
+        .ok()
+        .filter(|days| *days > 0)
+        .map(TrendWindow::Days)
+}
+
+fn window_text(window: TrendWindow) -> String {
+    match window {
+        TrendWindow::LastFetch => "last".to_owned(),
+        TrendWindow::Days(days) => format!("{days}d"),
+    }
+}
+
+fn metric_field(metric: TrendMetric) -> NumberField {
+    match metric {
+        TrendMetric::Words => NumberField::WordCount,
+        TrendMetric::Reads => NumberField::ReadCount,
+        TrendMetric::Replies => NumberField::ReplyCount,
+    }
+}
+
+fn lookup_field(name: &str) -> Option<Field> {
+    FIELDS
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(name))
+        .map(|(_, field)| *field)
+}
+
+/// The name a field is written with; growth fields go by their count's
+/// name.
+fn field_name(field: Field) -> &'static str {
+    let field = match field {
+        Field::Growth(metric, _) => Field::Number(metric_field(metric)),
+        field => field,
+    };
+    FIELDS
+        .iter()
+        .find(|(_, known)| *known == field)
+        .map(|(name, _)| *name)
+        .expect("every field has a name")
+}
+
+fn operator_text(operator: Operator) -> &'static str {
+    match operator {
+        Operator::Colon => ":",
+        Operator::Eq => "=",
+        Operator::Ne => "!=",
+        Operator::Lt => "<",
+        Operator::Lte => "<=",
+        Operator::Gt => ">",
+        Operator::Gte => ">=",
+        Operator::Prefix => "^=",
+        Operator::Suffix => "$=",
+    }
+}
+
+fn format_any(children: &[FilterExpr], authors: &AuthorNames) -> String {
+    match children {
+        [] => "OR".to_owned(),
+        [child] => format!("OR {}", format_expr(child, authors)),
+        children => children
+            .iter()
+            .map(|child| format_expr(child, authors))
+            .collect::<Vec<_>>()
+            .join(" OR "),
+    }
+}
+
+fn format_expr(expr: &FilterExpr, authors: &AuthorNames) -> String {
+    match expr {
+        FilterExpr::All(children) => format!(
+            "({})",
+            children
+                .iter()
+                .map(|child| format_expr(child, authors))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        FilterExpr::Any(children) => format!("({})", format_any(children, authors)),
+        FilterExpr::Not(inner) => format!("-{}", format_expr(inner, authors)),
+        FilterExpr::Predicate(predicate) => format_predicate(predicate, authors),
+    }
+}
+
+fn format_predicate(predicate: &Predicate, authors: &AuthorNames) -> String {
+    match predicate {
+        Predicate::Text { field, op, value } => {
+            let operator = match op {
+                TextOp::Contains => ":",
+                TextOp::Equals => "=",
+                TextOp::StartsWith => "^=",
+                TextOp::EndsWith => "$=",
+            };
+            format!(
+                "{}{operator}{}",
+                field_name(Field::Text(*field)),
+                text_value(value)
+            )
+        }
+        Predicate::Number { field, op } => format_number(field_name(Field::Number(*field)), op),
+        Predicate::Growth { metric, window, op } => {
+            format_number(&growth_name(*metric, *window), op)
+        }
+        Predicate::Updated(window) => format!("updated:{}", window_text(*window)),
+        Predicate::ReadStatus(status) => format!("status:{}", status.as_str()),
+        Predicate::Search(query) => format!("search:{}", text_value(query)),
+        Predicate::Bool { field, value } => {
+            format!("{}:{value}", field_name(Field::Bool(*field)))
+        }
+        Predicate::Tags(predicate) => match predicate {
+            TagsPredicate::ContainsAll(values) => format!("tag:{}", tag_list(values, ",")),
+            TagsPredicate::Intersects(values) => format!("tag:{}", tag_list(values, "|")),
+            TagsPredicate::Equals(values) => format!("tag={}", tag_list(values, ",")),
+            TagsPredicate::ContainedBy(values) => format!("tag<={}", tag_list(values, ",")),
+            TagsPredicate::IsEmpty => "tag:none".to_owned(),
+            TagsPredicate::IsNotEmpty => "tag:any".to_owned(),
+        },
+        Predicate::Author(predicate) => match predicate {
+            AuthorPredicate::Is(author) => format!("author:{}", authors.spell(author)),
+            AuthorPredicate::IsNot(author) => format!("author!={}", authors.spell(author)),
+            AuthorPredicate::In(list) => format!("author:{}", author_list(list, authors)),
+            AuthorPredicate::NotIn(list) => format!("author!={}", author_list(list, authors)),
+        },
+    }
+}
+
+fn format_number(name: &str, op: &NumberOp) -> String {
+    match op {
+        NumberOp::Eq(value) => format!("{name}={value}"),
+        NumberOp::Ne(value) => format!("{name}!={value}"),
+        NumberOp::Lt(value) => format!("{name}<{value}"),
+        NumberOp::Lte(value) => format!("{name}<={value}"),
+        NumberOp::Gt(value) => format!("{name}>{value}"),
+        NumberOp::Gte(value) => format!("{name}>={value}"),
+        NumberOp::Between { min, max } => format!("{name}:{min}..{max}"),
+    }
+}
+
+fn growth_name(metric: TrendMetric, window: TrendWindow) -> String {
+    format!(
+        "{}@{}",
+        field_name(Field::Number(metric_field(metric))),
+        window_text(window)
+    )
+}
+
+/// Text values only end at whitespace or a parenthesis, so `,` and `|`
+/// stay bare.
+fn text_value(value: &str) -> String {
+    let bare = !value.is_empty()
+        && !value
+            .chars()
+            .any(|character| character.is_whitespace() || "\"()".contains(character));
+    if bare { value.to_owned() } else { quote(value) }
+}
+
+fn tag_list(values: &HashSet<String>, separator: &str) -> String {
+    if values.is_empty() {
+        return "()".to_owned();
+    }
+    let mut values = values
+        .iter()
+        .map(|value| quote_value(value))
+        .collect::<Vec<_>>();
+    values.sort();
+    values.join(separator)
+}
+
+fn author_list(list: &[AuthorRef], authors: &AuthorNames) -> String {
+    if list.is_empty() {
+        return "()".to_owned();
+    }
+    list.iter()
+        .map(|author| authors.spell(author))
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+fn format_sort(sort: &SortSpec) -> String {
+    let name = match sort.expr {
+        SortExpr::Text(field) => field_name(Field::Text(field)).to_owned(),
+        SortExpr::Number(field) => field_name(Field::Number(field)).to_owned(),
+        SortExpr::Bool(field) => field_name(Field::Bool(field)).to_owned(),
+        SortExpr::Growth { metric, window } => growth_name(metric, window),
+        SortExpr::ReadStatus => field_name(Field::ReadStatus).to_owned(),
+        SortExpr::Relevance => field_name(Field::Relevance).to_owned(),
+    };
+    let direction = match sort.direction {
+        SortDirection::Asc => "",
+        SortDirection::Desc => "-",
+    };
+    format!("sort:{direction}{name}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(values: &[&str]) -> HashSet<String> {
+        values.iter().map(|value| (*value).to_owned()).collect()
+    }
+
+    fn tag(predicate: TagsPredicate) -> FilterExpr {
+        FilterExpr::Predicate(Predicate::Tags(predicate))
+    }
+
+    fn words(op: NumberOp) -> FilterExpr {
+        FilterExpr::Predicate(Predicate::Number {
+            field: NumberField::WordCount,
+            op,
+        })
+    }
+
+    fn authors() -> AuthorNames {
+        let known = [
+            (AuthorRef::Id(7), "某某"),
+            (AuthorRef::Id(8), "同名"),
+            (AuthorRef::Id(9), "同名"),
+            (AuthorRef::Name("匿名".to_owned()), "匿名"),
+        ];
+        AuthorNames::new(known.iter().map(|(author, name)| (author, *name)))
+    }
+
+    #[test]
+    fn parses_the_power_user_example() {
+        let spec = parse(
+            r#"tag:甜文 -tag:虐 words>=100000 author:"某某" sort:-reply"#,
+            &authors(),
+        )
+        .unwrap();
+        assert_eq!(
+            spec,
+            QuerySpec {
+                filter: FilterExpr::All(vec![
+                    tag(TagsPredicate::ContainsAll(tags(&["甜文"]))),
+                    FilterExpr::Not(Box::new(tag(TagsPredicate::ContainsAll(tags(&["虐"]))))),
+                    words(NumberOp::Gte(100_000)),
+                    FilterExpr::Predicate(Predicate::Author(AuthorPredicate::Is(AuthorRef::Id(7)))),
+                ]),
+                sorts: vec![SortSpec {
+                    expr: SortExpr::Number(NumberField::ReplyCount),
+                    direction: SortDirection::Desc,
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn growth_fields_take_a_window_after_the_count() {
+        let spec = parse("reply@7d>10 updated:LAST sort:-reads@30d", &authors()).unwrap();
+        assert_eq!(
+            spec,
+            QuerySpec {
+                filter: FilterExpr::All(vec![
+                    FilterExpr::Predicate(Predicate::Growth {
+                        metric: TrendMetric::Replies,
+                        window: TrendWindow::Days(7),
+                        op: NumberOp::Gt(10),
+                    }),
+                    FilterExpr::Predicate(Predicate::Updated(TrendWindow::LastFetch)),
+                ]),
+                sorts: vec![SortSpec {
+                    expr: SortExpr::Growth {
+                        metric: TrendMetric::Reads,
+                        window: TrendWindow::Days(30),
+                    },
+                    direction: SortDirection::Desc,
+                }],
+            }
+        );
+        assert_eq!(
+            format(&spec, &authors()),
+            "replies@7d>10 updated:last sort:-reads@30d"
+        );
+    }
+
+    #[test]
+    fn annotation_fields_filter_and_sort() {
+        let spec = parse(
+            "status:READING rating>=4 note:慢热 -hidden:true sort:status sort:-rating",
+            &authors(),
+        )
+        .unwrap();
+        assert_eq!(
+            spec,
+            QuerySpec {
+                filter: FilterExpr::All(vec![
+                    FilterExpr::Predicate(Predicate::ReadStatus(ReadStatus::Reading)),
+                    FilterExpr::Predicate(Predicate::Number {
+                        field: NumberField::Rating,
+                        op: NumberOp::Gte(4),
+                    }),
+                    FilterExpr::Predicate(Predicate::Text {
+                        field: TextField::Note,
+                        op: TextOp::Contains,
+                        value: "慢热".to_owned(),
+                    }),
+                    FilterExpr::Not(Box::new(FilterExpr::Predicate(Predicate::Bool {
+                        field: BoolField::Hidden,
+                        value: true,
+                    }))),
+                ]),
+                sorts: vec![
+                    SortSpec {
+                        expr: SortExpr::ReadStatus,
+                        direction: SortDirection::Asc,
+                    },
+                    SortSpec {
+                        expr: SortExpr::Number(NumberField::Rating),
+                        direction: SortDirection::Desc,
+                    },
+                ],
+            }
+        );
+        assert_eq!(
+            format(&spec, &authors()),
+            "status:reading rating>=4 note:慢热 -hidden:true sort:status sort:-rating"
+        );
+    }
+
+    #[test]
+    fn groups_alternatives_and_bare_words() {
+        let spec = parse("完结 (tag:a|b OR words:10..20 limit:no) NOT ()", &authors()).unwrap();
+        assert_eq!(
+            spec.filter,
+            FilterExpr::All(vec![
+                title_contains("完结".to_owned()),
+                FilterExpr::Any(vec![
+                    tag(TagsPredicate::Intersects(tags(&["a", "b"]))),
+                    FilterExpr::All(vec![
+                        words(NumberOp::Between { min: 10, max: 20 }),
+                        FilterExpr::Predicate(Predicate::Bool {
+                            field: BoolField::IsLimit,
+                            value: false,
+                        }),
+                    ]),
+                ]),
+                FilterExpr::Not(Box::new(FilterExpr::All(Vec::new()))),
+            ])
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_text() {
+        let error = |text: &str| parse(text, &authors()).unwrap_err();
+
+        assert_eq!(
+            error("tag:a bogus>=3"),
+            ParseError {
+                span: 6..11,
+                kind: ParseErrorKind::UnknownField("bogus".to_owned()),
+            }
+        );
+        assert_eq!(error("words>=lots").span, 7..11);
+        assert_eq!(error("words:9..1").kind, ParseErrorKind::InvalidRange);
+        assert_eq!(error("title<3").span, 5..6);
+        assert_eq!(error("id=3").kind, ParseErrorKind::SortOnlyField("id"));
+        assert_eq!(error("tag:a,b|c").span, 7..8);
+        assert_eq!(error("author:同名").span, 7..13);
+        assert_eq!(error("(tag:a").span, 0..1);
+        assert_eq!(error("tag:a)").kind, ParseErrorKind::UnexpectedClose);
+        assert_eq!(error("tag:a OR").span, 6..8);
+        assert_eq!(
+            error(r#"title:"open"#).kind,
+            ParseErrorKind::UnterminatedQuote
+        );
+        assert_eq!(error("(sort:words)").kind, ParseErrorKind::MisplacedSort);
+        assert_eq!(
+            error("replies@week>1"),
+            ParseError {
+                span: 8..12,
+                kind: ParseErrorKind::InvalidWindow("week".to_owned()),
+            }
+        );
+        assert_eq!(error("id@7d>1").span, 0..5);
+        assert_eq!(error("updated:0d").span, 8..10);
+        assert_eq!(
+            error("sort:updated").kind,
+            ParseErrorKind::NotSortable("updated")
+        );
+        assert_eq!(
+            error("status:done"),
+            ParseError {
+                span: 7..11,
+                kind: ParseErrorKind::InvalidStatus("done".to_owned()),
+            }
+        );
+        assert_eq!(error("sort:note").kind, ParseErrorKind::NotSortable("note"));
+        assert_eq!(
+            error("sort:search").kind,
+            ParseErrorKind::NotSortable("search")
+        );
+        assert_eq!(
+            error("relevance:1").kind,
+            ParseErrorKind::SortOnlyField("relevance")
+        );
+        assert_eq!(
+            error("tag:"),
+            ParseError {
+                span: 4..4,
+                kind: ParseErrorKind::MissingValue,
+            }
+        );
+    }
+
+    #[test]
+    fn formatted_queries_parse_back_to_the_same_tree() {
+        let authors = authors();
+        let specs = [
+            QuerySpec::default(),
+            QuerySpec {
+                filter: FilterExpr::Any(vec![
+                    FilterExpr::Predicate(Predicate::Text {
+                        field: TextField::Description,
+                        op: TextOp::StartsWith,
+                        value: "a \"quoted\" (value), x|y".to_owned(),
+                    }),
+                    FilterExpr::Not(Box::new(FilterExpr::Any(vec![tag(TagsPredicate::IsEmpty)]))),
+                    FilterExpr::All(vec![
+                        tag(TagsPredicate::Equals(tags(&["none", "两个 词"]))),
+                        tag(TagsPredicate::ContainedBy(HashSet::new())),
+                    ]),
+                ]),
+                sorts: vec![SortSpec {
+                    expr: SortExpr::Text(TextField::AuthorName),
+                    direction: SortDirection::Asc,
+                }],
+            },
+            QuerySpec {
+                filter: FilterExpr::All(vec![
+                    FilterExpr::Any(Vec::new()),
+                    FilterExpr::Not(Box::new(FilterExpr::Not(Box::new(words(NumberOp::Ne(-3)))))),
+                    FilterExpr::Predicate(Predicate::Author(AuthorPredicate::NotIn(vec![
+                        AuthorRef::Id(7),
+                        AuthorRef::Id(8),
+                        AuthorRef::Name("匿名".to_owned()),
+                        AuthorRef::Name("某某".to_owned()),
+                    ]))),
+                    FilterExpr::Predicate(Predicate::Author(AuthorPredicate::IsNot(
+                        AuthorRef::Id(404),
+                    ))),
+                    tag(TagsPredicate::IsNotEmpty),
+                ]),
+                sorts: vec![
+                    SortSpec {
+                        expr: SortExpr::Number(NumberField::NovelId),
+                        direction: SortDirection::Desc,
+                    },
+                    SortSpec {
+                        expr: SortExpr::Bool(BoolField::IsLimit),
+                        direction: SortDirection::Asc,
+                    },
+                ],
+            },
+            QuerySpec {
+                filter: FilterExpr::All(vec![
+                    FilterExpr::Predicate(Predicate::Growth {
+                        metric: TrendMetric::Replies,
+                        window: TrendWindow::Days(7),
+                        op: NumberOp::Gt(10),
+                    }),
+                    FilterExpr::Predicate(Predicate::Growth {
+                        metric: TrendMetric::Words,
+                        window: TrendWindow::LastFetch,
+                        op: NumberOp::Between { min: -5, max: 5 },
+                    }),
+                    FilterExpr::Not(Box::new(FilterExpr::Predicate(Predicate::Updated(
+                        TrendWindow::Days(30),
+                    )))),
+                ]),
+                sorts: vec![SortSpec {
+                    expr: SortExpr::Growth {
+                        metric: TrendMetric::Reads,
+                        window: TrendWindow::LastFetch,
+                    },
+                    direction: SortDirection::Desc,
+                }],
+            },
+            QuerySpec {
+                filter: FilterExpr::All(vec![
+                    FilterExpr::Predicate(Predicate::Search("穿书 系统".to_owned())),
+                    FilterExpr::Predicate(Predicate::Search("csxt".to_owned())),
+                ]),
+                sorts: vec![SortSpec {
+                    expr: SortExpr::Relevance,
+                    direction: SortDirection::Desc,
+                }],
+            },
+        ];
+        for spec in specs {
+            let text = format(&spec, &authors);
+            assert_eq!(parse(&text, &authors), Ok(spec), "{text}");
+        }
+    }
+
+    #[test]
+    fn completion_finds_the_value_being_typed() {
+        assert_eq!(
+            completion_at("words>3 tag:甜文,虐", 22),
+            Some(Completion {
+                kind: CompletionKind::Tag,
+                span: 19..22,
+                prefix: "虐".to_owned(),
+            })
+        );
+        assert_eq!(
+            completion_at(r#"author:"某"#, 11),
+            Some(Completion {
+                kind: CompletionKind::Author,
+                span: 7..11,
+                prefix: "某".to_owned(),
+            })
+        );
+        assert_eq!(
+            completion_at("tag:", 4).map(|completion| completion.span),
+            Some(4..4)
+        );
+        assert_eq!(completion_at("title:甜", 9), None);
+    }
+}
@@ -0,0 +1,285 @@
+use duckdb::{Connection, params};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::{FeiwenError, FeiwenResult},
+    store::query::QuerySpec,
+};
+
+const EXPORT_VERSION: u32 = 1;
+
+/// A named advanced query, stored as its `QuerySpec` JSON.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SavedQuery {
+    pub(crate) id: i32,
+    pub(crate) name: String,
+    pub(crate) spec: QuerySpec,
+}
+
+/// The shareable export shape. Ids are local to one database and stay out.
+#[derive(Serialize, Deserialize)]
+struct SavedQueryExport {
+    version: u32,
+    queries: Vec<ExportedQuery>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedQuery {
+    name: String,
+    query: QuerySpec,
+}
+
+impl SavedQuery {
+    pub(crate) fn list(conn: &Connection) -> FeiwenResult<Vec<Self>> {
+        let mut stmt = conn.prepare("SELECT id, name, spec FROM saved_query ORDER BY name, id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+        rows.map(|row| -> FeiwenResult<Self> {
+            let (id, name, spec) = row?;
+            Ok(Self {
+                id,
+                name,
+                spec: serde_json::from_str(&spec)?,
+            })
+        })
+        .collect()
+    }
+
+    pub(crate) fn create(
+        conn: &mut Connection,
+        name: &str,
+        spec: &QuerySpec,
+    ) -> FeiwenResult<Self> {
+        let name = valid_name(name)?;
+        let tx = conn.transaction()?;
+        ensure_name_free(&tx, &name, None)?;
+        let id = insert(&tx, &name, spec)?;
+        tx.commit()?;
+        Ok(Self {
+            id,
+            name,
+            spec: spec.clone(),
+        })
+    }
+
+    pub(crate) fn rename(conn: &mut Connection, id: i32, name: &str) -> FeiwenResult<()> {
+        let name = valid_name(name)?;
+        let tx = conn.transaction()?;
+        ensure_name_free(&tx, &name, Some(id))?;
+        let changed = tx.execute(
+            "UPDATE saved_query SET name = ? WHERE id = ?",
+            params![name, id],
+        )?;
+        if changed == 0 {
+            return Err(FeiwenError::SavedQueryMissing);
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub(crate) fn replace_spec(conn: &Connection, id: i32, spec: &QuerySpec) -> FeiwenResult<()> {
+        let changed = conn.execute(
+            "UPDATE saved_query SET spec = ? WHERE id = ?",
+            params![serde_json::to_string(spec)?, id],
+        )?;
+        if changed == 0 {
+            return Err(FeiwenError::SavedQueryMissing);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn delete(conn: &Connection, id: i32) -> FeiwenResult<()> {
+        let changed = conn.execute("DELETE FROM saved_query WHERE id = ?", params![id])?;
+        if changed == 0 {
+            return Err(FeiwenError::SavedQueryMissing);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn export(queries: &[Self]) -> String {
+        let export = SavedQueryExport {
+            version: EXPORT_VERSION,
+            queries: queries
+                .iter()
+                .map(|query| ExportedQuery {
+                    name: query.name.clone(),
+                    query: query.spec.clone(),
+                })
+                .collect(),
+        };
+        serde_json::to_string_pretty(&export).expect("saved query export serializes")
+    }
+
+    /// Adds every query of an export. A name that is already taken gets a
+    /// numbered suffix instead of replacing the existing query.
+    pub(crate) fn import(conn: &mut Connection, text: &str) -> FeiwenResult<Vec<Self>> {
+        let export: SavedQueryExport = serde_json::from_str(text)?;
+        if export.version > EXPORT_VERSION {
+            return Err(FeiwenError::SavedQueryVersion(export.version));
+        }
+        let tx = conn.transaction()?;
+        let mut imported = Vec::with_capacity(export.queries.len());
+        for query in export.queries {
+            let base = valid_name(&query.name)?;
+            let mut name = base.clone();
+            let mut suffix = 2;
+            while name_taken(&tx, &name, None)? {
+                name = format!("{base} ({suffix})");
+                suffix += 1;
+            }
+            let id = insert(&tx, &name, &query.query)?;
+            imported.push(Self {
+                id,
+                name,
+                spec: query.query,
+            });
+        }
+        tx.commit()?;
+        Ok(imported)
+    }
+}
+
+fn valid_name(name: &str) -> FeiwenResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(FeiwenError::SavedQueryNameEmpty);
+    }
+    Ok(name.to_owned())
+}
+
+fn insert(conn: &Connection, name: &str, spec: &QuerySpec) -> FeiwenResult<i32> {
+    conn.query_row(
+        "INSERT INTO saved_query (name, spec) VALUES (?, ?) RETURNING id",
+        params![name, serde_json::to_string(spec)?],
+        |row| row.get(0),
+    )
+    .map_err(Into::into)
+}
+
+fn name_taken(conn: &Connection, name: &str, except: Option<i32>) -> FeiwenResult<bool> {
+    let count = conn.query_row(
+        "SELECT count(*) FROM saved_query WHERE name = ? AND id IS DISTINCT FROM ?",
+        params![name, except],
+        |row| row.get::<_, i64>(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn ensure_name_free(conn: &Connection, name: &str, except: Option<i32>) -> FeiwenResult<()> {
+    if name_taken(conn, name, except)? {
+        return Err(FeiwenError::SavedQueryNameTaken(name.to_owned()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::store::{
+        initialize_schema,
+        query::{
+            FilterExpr, NumberField, NumberOp, Predicate, SortDirection, SortExpr, SortSpec,
+            TagsPredicate,
+        },
+    };
+
+    fn connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        conn
+    }
+
+    fn long_finished() -> QuerySpec {
+        QuerySpec {
+            filter: FilterExpr::All(vec![
+                FilterExpr::Predicate(Predicate::Number {
+                    field: NumberField::WordCount,
+                    op: NumberOp::Gte(300_000),
+                }),
+                FilterExpr::Predicate(Predicate::Tags(TagsPredicate::ContainsAll(HashSet::from(
+                    ["完结".to_owned()],
+                )))),
+            ]),
+            sorts: vec![SortSpec {
+                expr: SortExpr::Number(NumberField::ReplyCount),
+                direction: SortDirection::Desc,
+            }],
+        }
+    }
+
+    #[test]
+    fn saved_queries_are_named_uniquely_and_round_trip_their_spec() {
+        let mut conn = connection();
+        let saved = SavedQuery::create(&mut conn, "  长篇完结 ", &long_finished()).unwrap();
+        assert_eq!(saved.name, "长篇完结");
+        assert!(matches!(
+            SavedQuery::create(&mut conn, "长篇完结", &QuerySpec::default()),
+            Err(FeiwenError::SavedQueryNameTaken(name)) if name == "长篇完结"
+        ));
+        assert!(matches!(
+            SavedQuery::create(&mut conn, " ", &QuerySpec::default()),
+            Err(FeiwenError::SavedQueryNameEmpty)
+        ));
+        let other = SavedQuery::create(&mut conn, "all", &QuerySpec::default()).unwrap();
+
+        SavedQuery::rename(&mut conn, saved.id, "长篇完结").unwrap();
+        assert!(matches!(
+            SavedQuery::rename(&mut conn, other.id, "长篇完结"),
+            Err(FeiwenError::SavedQueryNameTaken(_))
+        ));
+        SavedQuery::rename(&mut conn, saved.id, "long finished").unwrap();
+        SavedQuery::replace_spec(&conn, other.id, &long_finished()).unwrap();
+
+        let names: Vec<_> = SavedQuery::list(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|query| (query.name, query.spec))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("all".to_owned(), long_finished()),
+                ("long finished".to_owned(), long_finished()),
+            ]
+        );
+
+        SavedQuery::delete(&conn, other.id).unwrap();
+        assert!(matches!(
+            SavedQuery::delete(&conn, other.id),
+            Err(FeiwenError::SavedQueryMissing)
+        ));
+        assert_eq!(SavedQuery::list(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn imports_keep_existing_queries_and_number_taken_names() {
+        let mut source = connection();
+        SavedQuery::create(&mut source, "长篇完结", &long_finished()).unwrap();
+        SavedQuery::create(&mut source, "all", &QuerySpec::default()).unwrap();
+        let export = SavedQuery::export(&SavedQuery::list(&source).unwrap());
+
+        let mut target = connection();
+        SavedQuery::create(&mut target, "长篇完结", &QuerySpec::default()).unwrap();
+        let imported = SavedQuery::import(&mut target, &export).unwrap();
+        let names: Vec<_> = imported.iter().map(|query| query.name.as_str()).collect();
+        assert_eq!(names, ["all", "长篇完结 (2)"]);
+        assert_eq!(imported[1].spec, long_finished());
+        assert_eq!(SavedQuery::list(&target).unwrap().len(), 3);
+
+        assert!(matches!(
+            SavedQuery::import(&mut target, r#"{"version": 9, "queries": []}"#),
+            Err(FeiwenError::SavedQueryVersion(9))
+        ));
+        assert!(matches!(
+            SavedQuery::import(&mut target, "long finished"),
+            Err(FeiwenError::SavedQueryFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,441 @@
+//! The token index behind full-text search.
+//!
+//! DuckDB has no FTS5, so novels are tokenized here. Runs of letters and
+//! digits become lowercased words. CJK text becomes every character plus
+//! every overlapping pair, so a search of any length finds its candidates
+//! through whole tokens. Titles and author names also keep their pinyin
+//! spelling and initials, the same keys the option pickers match in memory.
+
+use std::{collections::HashMap, ops::Range};
+
+use duckdb::{Connection, params};
+
+use crate::{errors::FeiwenResult, foundation::search::pinyin_search_keys};
+
+/// Most characters of context a snippet keeps before its first match; a
+/// narrow snippet keeps a third of its width.
+const SNIPPET_LEAD: usize = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SearchField {
+    Title,
+    Author,
+    LatestChapter,
+    Description,
+}
+
+impl SearchField {
+    pub(crate) const ALL: [Self; 4] = [
+        Self::Title,
+        Self::Author,
+        Self::LatestChapter,
+        Self::Description,
+    ];
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Author => "author",
+            Self::LatestChapter => "chapter",
+            Self::Description => "description",
+        }
+    }
+
+    /// How much a match in this field counts towards relevance.
+    pub(crate) fn weight(self) -> f64 {
+        match self {
+            Self::Title => 3.0,
+            Self::Author => 2.0,
+            Self::LatestChapter | Self::Description => 1.0,
+        }
+    }
+
+    fn has_pinyin(self) -> bool {
+        matches!(self, Self::Title | Self::Author)
+    }
+}
+
+/// The text of one novel that goes into the index.
+pub(crate) struct IndexedNovel<'a> {
+    pub(crate) id: i32,
+    pub(crate) title: &'a str,
+    pub(crate) author: &'a str,
+    pub(crate) latest_chapter: &'a str,
+    pub(crate) description: &'a str,
+}
+
+impl IndexedNovel<'_> {
+    fn field(&self, field: SearchField) -> &str {
+        match field {
+            SearchField::Title => self.title,
+            SearchField::Author => self.author,
+            SearchField::LatestChapter => self.latest_chapter,
+            SearchField::Description => self.description,
+        }
+    }
+}
+
+/// One whitespace-separated part of a search. A novel matches a search
+/// when it matches every term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SearchTerm {
+    /// The lowercased term; a hit must contain it as written.
+    pub(crate) text: String,
+    pub(crate) tokens: Vec<SearchToken>,
+    /// Only ASCII letters, so it may be a pinyin spelling or initials.
+    pub(crate) pinyin: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SearchToken {
+    pub(crate) text: String,
+    /// Words match indexed words they start; CJK tokens match exactly.
+    pub(crate) prefix: bool,
+}
+
+/// Part of a text with the matched ranges marked, in bytes of `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Snippet {
+    pub(crate) text: String,
+    pub(crate) highlights: Vec<Range<usize>>,
+}
+
+enum Run {
+    Word(String),
+    Cjk(Vec<char>),
+}
+
+/// Splits `query` into terms, dropping the ones with nothing to search
+/// for, such as lone punctuation.
+pub(crate) fn search_terms(query: &str) -> Vec<SearchTerm> {
+    query
+        .split_whitespace()
+        .filter_map(|term| {
+            let text = term.to_lowercase();
+            let tokens = runs(&text)
+                .into_iter()
+                .flat_map(|run| match run {
+                    Run::Word(word) => vec![SearchToken {
+                        text: word,
+                        prefix: true,
+                    }],
+                    Run::Cjk(chars) if chars.len() == 1 => vec![SearchToken {
+                        text: chars[0].to_string(),
+                        prefix: false,
+                    }],
+                    Run::Cjk(chars) => chars
+                        .windows(2)
+                        .map(|pair| SearchToken {
+                            text: pair.iter().collect(),
+                            prefix: false,
+                        })
+                        .collect(),
+                })
+                .collect::<Vec<_>>();
+            if tokens.is_empty() {
+                return None;
+            }
+            let pinyin = text.chars().all(|ch| ch.is_ascii_alphabetic());
+            Some(SearchTerm {
+                text,
+                tokens,
+                pinyin,
+            })
+        })
+        .collect()
+}
+
+/// The indexed tokens of `text` with how often each occurs.
+fn index_tokens(text: &str) -> HashMap<String, i32> {
+    let mut tokens = HashMap::new();
+    for run in runs(&text.to_lowercase()) {
+        match run {
+            Run::Word(word) => *tokens.entry(word).or_default() += 1,
+            Run::Cjk(chars) => {
+                for ch in &chars {
+                    *tokens.entry(ch.to_string()).or_default() += 1;
+                }
+                for pair in chars.windows(2) {
+                    *tokens.entry(pair.iter().collect()).or_default() += 1;
+                }
+            }
+        }
+    }
+    tokens
+}
+
+fn runs(text: &str) -> Vec<Run> {
+    let mut runs = Vec::new();
+    for ch in text.chars() {
+        if is_cjk(ch) {
+            match runs.last_mut() {
+                Some(Run::Cjk(chars)) => chars.push(ch),
+                _ => runs.push(Run::Cjk(vec![ch])),
+            }
+        } else if ch.is_alphanumeric() {
+            match runs.last_mut() {
+                Some(Run::Word(word)) => word.push(ch),
+                _ => runs.push(Run::Word(ch.to_string())),
+            }
+        } else {
+            runs.push(Run::Word(String::new()));
+        }
+    }
+    runs.retain(|run| !matches!(run, Run::Word(word) if word.is_empty()));
+    runs
+}
+
+fn is_cjk(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{3040}'..='\u{30FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{AC00}'..='\u{D7AF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2FA1F}'
+    )
+}
+
+/// Replaces the novel's index rows. Call it in the transaction that saves
+/// the novel so the index never lags behind.
+pub(crate) fn index_novel(conn: &Connection, novel: &IndexedNovel<'_>) -> FeiwenResult<()> {
+    conn.execute(
+        "DELETE FROM novel_search_token WHERE novel_id = ?",
+        params![novel.id],
+    )?;
+    conn.execute(
+        "DELETE FROM novel_search_pinyin WHERE novel_id = ?",
+        params![novel.id],
+    )?;
+    let mut insert_token = conn.prepare_cached(
+        "INSERT INTO novel_search_token (novel_id, field, token, hits) VALUES (?, ?, ?, ?)",
+    )?;
+    let mut insert_pinyin = conn.prepare_cached(
+        "INSERT INTO novel_search_pinyin (novel_id, field, full_key, initials) \
+         VALUES (?, ?, ?, ?)",
+    )?;
+    for field in SearchField::ALL {
+        let text = novel.field(field);
+        for (token, hits) in index_tokens(text) {
+            insert_token.execute(params![novel.id, field.as_str(), token, hits])?;
+        }
+        // Every novel gets its pinyin rows, even empty ones, so a novel
+        // without them has never been indexed.
+        if field.has_pinyin() {
+            let (full_key, initials) = pinyin_search_keys(text);
+            insert_pinyin.execute(params![novel.id, field.as_str(), full_key, initials])?;
+        }
+    }
+    Ok(())
+}
+
+/// Indexes every stored novel from scratch and returns how many there
+/// were.
+pub(crate) fn rebuild(conn: &mut Connection) -> FeiwenResult<usize> {
+    let tx = conn.transaction()?;
+    tx.execute_batch("DELETE FROM novel_search_token; DELETE FROM novel_search_pinyin;")?;
+    let novels = {
+        let mut statement = tx.prepare(
+            r#"SELECT id, name, author_name, latest_chapter_name, "desc" FROM novel ORDER BY id"#,
+        )?;
+        statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i32>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    for (id, title, author, latest_chapter, description) in &novels {
+        index_novel(
+            &tx,
+            &IndexedNovel {
+                id: *id,
+                title,
+                author,
+                latest_chapter,
+                description,
+            },
+        )?;
+    }
+    tx.commit()?;
+    Ok(novels.len())
+}
+
+/// Rebuilds the index when some stored novel was never indexed, as in a
+/// database from before search existed. Returns whether it rebuilt.
+pub(crate) fn rebuild_if_stale(conn: &mut Connection) -> FeiwenResult<bool> {
+    let missing = conn.query_row(
+        "SELECT count(*) FROM novel \
+         WHERE id NOT IN (SELECT novel_id FROM novel_search_pinyin)",
+        [],
+        |row| row.get::<_, i64>(0),
+    )?;
+    if missing == 0 {
+        return Ok(false);
+    }
+    rebuild(conn)?;
+    Ok(true)
+}
+
+/// The part of `text` around the first match of any term, at most `width`
+/// characters plus ellipses, or `None` when no term occurs in it.
+pub(crate) fn snippet(text: &str, terms: &[SearchTerm], width: usize) -> Option<Snippet> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let lowered = chars
+        .iter()
+        .map(|ch| ch.to_lowercase().next().unwrap_or(*ch))
+        .collect::<Vec<_>>();
+    let mut matches = Vec::new();
+    for term in terms {
+        let needle = term.text.chars().collect::<Vec<_>>();
+        if needle.is_empty() {
+            continue;
+        }
+        matches.extend(
+            lowered
+                .windows(needle.len())
+                .enumerate()
+                .filter(|(_, window)| *window == needle.as_slice())
+                .map(|(start, _)| start..start + needle.len()),
+        );
+    }
+    let first = matches.iter().map(|range| range.start).min()?;
+
+    let start = if chars.len() <= width {
+        0
+    } else {
+        first
+            .saturating_sub(SNIPPET_LEAD.min(width / 3))
+            .min(chars.len() - width)
+    };
+    let end = (start + width).min(chars.len());
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    // Byte offset in `snippet` of each kept character, plus the end.
+    let mut offsets = Vec::with_capacity(end - start + 1);
+    for ch in &chars[start..end] {
+        offsets.push(snippet.len());
+        snippet.push(*ch);
+    }
+    offsets.push(snippet.len());
+    if end < chars.len() {
+        snippet.push('…');
+    }
+
+    matches.sort_by_key(|range| range.start);
+    let mut highlights: Vec<Range<usize>> = Vec::new();
+    for range in matches {
+        let (from, to) = (range.start.max(start), range.end.min(end));
+        if from >= to {
+            continue;
+        }
+        let bytes = offsets[from - start]..offsets[to - start];
+        match highlights.last_mut() {
+            Some(last) if last.end >= bytes.start => last.end = last.end.max(bytes.end),
+            _ => highlights.push(bytes),
+        }
+    }
+    Some(Snippet {
+        text: snippet,
+        highlights,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::initialize_schema;
+
+    fn tokens(query: &str) -> Vec<(String, bool)> {
+        search_terms(query)
+            .into_iter()
+            .flat_map(|term| term.tokens)
+            .map(|token| (token.text, token.prefix))
+            .collect()
+    }
+
+    #[test]
+    fn terms_split_words_and_cjk_pairs() {
+        assert_eq!(
+            tokens("Rust 重生之路 甜 !!"),
+            vec![
+                ("rust".to_owned(), true),
+                ("重生".to_owned(), false),
+                ("生之".to_owned(), false),
+                ("之路".to_owned(), false),
+                ("甜".to_owned(), false),
+            ]
+        );
+        let terms = search_terms("csyw 穿书");
+        assert!(terms[0].pinyin);
+        assert!(!terms[1].pinyin);
+
+        let indexed = index_tokens("重生 重生abc");
+        assert_eq!(indexed["重生"], 2);
+        assert_eq!(indexed["重"], 2);
+        assert_eq!(indexed["abc"], 1);
+    }
+
+    #[test]
+    fn snippets_center_on_the_first_match_and_merge_highlights() {
+        let terms = search_terms("穿书 书");
+        let snippet = snippet("很久很久以前有一个人穿书了", &terms, 8).unwrap();
+        assert_eq!(snippet.text, "…前有一个人穿书了");
+        let highlighted = snippet
+            .highlights
+            .iter()
+            .map(|range| &snippet.text[range.clone()])
+            .collect::<Vec<_>>();
+        assert_eq!(highlighted, vec!["穿书"]);
+
+        let snippet = snippet_of("Hello RUST world", "rust");
+        assert_eq!(&snippet.text[snippet.highlights[0].clone()], "RUST");
+        assert_eq!(super::snippet("nothing here", &terms, 8), None);
+    }
+
+    fn snippet_of(text: &str, query: &str) -> Snippet {
+        snippet(text, &search_terms(query), 40).unwrap()
+    }
+
+    #[test]
+    fn rebuild_indexes_novels_saved_before_search_existed() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        conn.execute(
+            r#"INSERT INTO novel (
+                id, name, "desc", is_limit, latest_chapter_name,
+                latest_chapter_id, word_count, author_name
+            ) VALUES (1, '穿书', 'desc', false, 'chapter', 1, 1, '作者')"#,
+            [],
+        )
+        .unwrap();
+
+        assert!(rebuild_if_stale(&mut conn).unwrap());
+        assert!(!rebuild_if_stale(&mut conn).unwrap());
+        let (full_key, initials) = conn
+            .query_row(
+                "SELECT full_key, initials FROM novel_search_pinyin \
+                 WHERE novel_id = 1 AND field = 'title'",
+                [],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            )
+            .unwrap();
+        assert_eq!((full_key.as_str(), initials.as_str()), ("chuanshu", "cs"));
+        let pairs = conn
+            .query_row(
+                "SELECT count(*) FROM novel_search_token WHERE novel_id = 1 AND token = '穿书'",
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap();
+        assert_eq!(pairs, 1);
+    }
+}
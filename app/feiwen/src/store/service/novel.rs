@@ -5,7 +5,10 @@ use gpui_component::{ActiveTheme, StyledExt, label::Label};
 use crate::{
     errors::FeiwenResult,
     store::{
+        annotation::Annotation,
+        history::{self, NovelSnapshot},
         query::{NovelRecord, QuerySpec, query_records},
+        search_index::{self, IndexedNovel},
         types::{Author, NovelCount, Title},
     },
 };
@@ -21,6 +24,8 @@ pub(crate) struct Novel {
     pub(crate) count: NovelCount,
     pub(crate) tags: std::collections::HashSet<Tag>,
     pub(crate) is_limit: bool,
+    /// Not written by [`Novel::save`]; see [`Annotation`].
+    pub(crate) annotation: Annotation,
 }
 
 impl RenderOnce for Novel {
@@ -41,7 +46,9 @@ impl RenderOnce for Novel {
 }
 
 impl Novel {
-    pub(crate) fn save(self, conn: &mut Connection) -> FeiwenResult<()> {
+    /// Upserts the novel and records what this fetch saw of it. `fetched_at`
+    /// is the start of the fetch run, in unix seconds.
+    pub(crate) fn save(self, conn: &mut Connection, fetched_at: i64) -> FeiwenResult<()> {
         let old_counts = load_existing_counts(conn, self.title.id)?;
         let read_count = self
             .count
@@ -99,6 +106,28 @@ impl Novel {
                 author_name,
             ],
         )?;
+        search_index::index_novel(
+            &tx,
+            &IndexedNovel {
+                id: self.title.id,
+                title: &self.title.name,
+                author: &author_name,
+                latest_chapter: &self.latest_chapter.name,
+                description: &self.desc,
+            },
+        )?;
+        history::record_snapshot(
+            &tx,
+            self.title.id,
+            &NovelSnapshot {
+                fetched_at,
+                word_count: self.count.word_count,
+                read_count,
+                reply_count,
+                latest_chapter_id: self.latest_chapter.id,
+                latest_chapter_name: self.latest_chapter.name,
+            },
+        )?;
 
         for tag in &self.tags {
             tx.execute(
@@ -178,6 +207,7 @@ impl NovelRecord {
                     Tag { name, id }
                 })
                 .collect(),
+            annotation: self.annotation,
         }
     }
 }
@@ -188,6 +218,7 @@ mod tests {
 
     use super::*;
     use crate::store::{
+        annotation::ReadStatus,
         initialize_schema,
         query::{
             AuthorPredicate, AuthorRef, BoolField, FilterExpr, NumberField, Predicate,
@@ -229,6 +260,7 @@ mod tests {
                 })
                 .collect(),
             is_limit: false,
+            annotation: Annotation::default(),
         }
     }
 
@@ -243,7 +275,7 @@ mod tests {
         let mut novel = novel(id, title, &format!("author-{id}"), tags);
         novel.is_limit = is_limit;
         novel.count.reply_count = reply_count;
-        novel.save(conn).unwrap();
+        novel.save(conn, 0).unwrap();
     }
 
     fn sorted_ids(results: Vec<Novel>) -> Vec<i32> {
@@ -258,7 +290,7 @@ mod tests {
         let mut updated = novel(1, "updated", "author-1", &["rust", "gpui"]);
         updated.count.read_count = None;
         updated.count.reply_count = None;
-        updated.save(&mut conn).unwrap();
+        updated.save(&mut conn, 0).unwrap();
 
         let (name, read_count, reply_count) = conn
             .query_row(
@@ -291,6 +323,54 @@ mod tests {
         assert!(tags.iter().any(|tag| tag.name == "gpui"));
     }
 
+    #[test]
+    fn refetching_keeps_annotations() {
+        let mut conn = connection();
+        save_novel(&mut conn, 1, "first", &[], false, Some(10));
+        let annotation = Annotation {
+            status: ReadStatus::Read,
+            rating: Some(5),
+            note: "note".to_owned(),
+            hidden: false,
+        };
+        annotation.save(&conn, 1).unwrap();
+
+        save_novel(&mut conn, 1, "refetched", &[], false, Some(20));
+
+        let novel = Novel::query(&QuerySpec::default(), &conn)
+            .unwrap()
+            .remove(0);
+        assert_eq!(novel.title.name, "refetched");
+        assert_eq!(novel.annotation, annotation);
+    }
+
+    #[test]
+    fn save_records_a_snapshot_per_fetch_with_carried_counts() {
+        let mut conn = connection();
+        novel(1, "first", "author-1", &[])
+            .save(&mut conn, 100)
+            .unwrap();
+        let mut refetched = novel(1, "first", "author-1", &[]);
+        refetched.count.word_count = 1500;
+        refetched.count.reply_count = None;
+        refetched.clone().save(&mut conn, 200).unwrap();
+        refetched.save(&mut conn, 200).unwrap();
+
+        let history = history::novel_history(&conn, 1).unwrap();
+
+        assert_eq!(
+            history
+                .iter()
+                .map(|snapshot| (
+                    snapshot.fetched_at,
+                    snapshot.word_count,
+                    snapshot.reply_count
+                ))
+                .collect::<Vec<_>>(),
+            vec![(100, 1000, Some(10)), (200, 1500, Some(10))]
+        );
+    }
+
     #[test]
     fn query_pushes_tag_limit_and_reply_sort_semantics() {
         let mut conn = connection();
@@ -496,11 +576,11 @@ mod tests {
     fn query_author_not_in_keeps_anonymous_authors_when_ids_do_not_match() {
         let mut conn = connection();
         novel(1, "known", "known-author", &["tag"])
-            .save(&mut conn)
+            .save(&mut conn, 0)
             .unwrap();
         let mut anonymous = novel(2, "anonymous", "anonymous-author", &["tag"]);
         anonymous.author = Author::Anonymous("anonymous-author".to_owned());
-        anonymous.save(&mut conn).unwrap();
+        anonymous.save(&mut conn, 0).unwrap();
 
         let spec = QuerySpec {
             filter: FilterExpr::Predicate(Predicate::Author(AuthorPredicate::NotIn(vec![
@@ -542,7 +622,7 @@ mod tests {
 
         let mut anonymous = novel(3, "anonymous", "author' OR 1=1 --", &["tag"]);
         anonymous.author = Author::Anonymous("author' OR 1=1 --".to_owned());
-        anonymous.save(&mut conn).unwrap();
+        anonymous.save(&mut conn, 0).unwrap();
 
         let spec = QuerySpec {
             filter: FilterExpr::Predicate(Predicate::Author(AuthorPredicate::Is(AuthorRef::Name(
@@ -5,6 +5,8 @@ gpui-form-error-required = This field is required.
 button-send = Send
 button-cancel = Cancel
 button-clear-response = Clear Response
+button-pin-response = Pin Response
+button-unpin-response = Unpin
 button-save-response = Save Response
 button-add = Add
 button-confirm = Confirm
@@ -14,6 +16,30 @@ button-change-file = Change File
 button-clear-file = Clear File
 button-move-up = Move Up
 button-move-down = Move Down
+button-clear-variables = Clear Variables
+button-connect = Connect
+button-disconnect = Disconnect
+button-ping = Ping
+button-clear-log = Clear Log
+button-stop-reconnecting = Stop Reconnecting
+button-add-cookie = Add Cookie
+button-save-cookie = Save Cookie
+button-cancel-edit = Cancel Edit
+button-edit-cookie = Edit
+button-delete-cookie = Delete
+button-delete-domain-cookies = Delete All
+button-open-request = Open…
+button-save-request = Save…
+button-import-har = Import HAR…
+button-export-har = Export HAR…
+button-discard-changes = Discard Changes
+button-run = Run
+button-stop = Stop
+button-previous-match = Previous
+button-next-match = Next
+button-copy-path = Copy path
+button-load-protos = Load .proto…
+button-reflect = Use Reflection
 
 field-method = Method
 field-url = URL
@@ -27,14 +53,47 @@ field-password = Password
 field-token = Token
 field-location = Location
 field-timeout-ms = Timeout (ms)
+field-grant-type = Grant Type
+field-token-url = Token URL
+field-authorization-url = Authorization URL
+field-redirect-uri = Redirect URI
+field-client-id = Client ID
+field-client-secret = Client Secret
+field-scope = Scope
+field-access-key-id = Access Key ID
+field-secret-access-key = Secret Access Key
+field-session-token = Session Token
+field-region = Region
+field-service = Service Name
+field-variable = Variable
+field-check-target = Header or JSONPath
+field-check-expected = Expected
+field-runner-iterations = Iterations
+field-runner-delay-ms = Delay between requests (ms)
+field-runner-concurrency = Concurrent workers
+field-runner-duration-s = Duration (s)
+field-runner-request-limit = Request count
 
 tab-params = Params
 tab-authorization = Authorization
 tab-headers = Headers
 tab-body = Body
+tab-tests = Tests
 tab-settings = Settings
 tab-response-body = Body
 tab-response-headers = Headers
+tab-response-tests = Tests
+tab-response-connection = Connection
+tab-response-timing = Timing
+tab-response-diff = Diff
+tab-runner-collection = Collection
+tab-runner-load = Load
+
+mode-http = HTTP
+mode-runner = Runner
+mode-websocket = WebSocket
+mode-grpc = gRPC
+mode-cookies = Cookies
 
 response-title = Response
 response-empty = Send a request to inspect its response.
@@ -61,6 +120,11 @@ response-view-base64 = Base64
 response-view-image = Image
 response-view-audio = Audio
 response-view-pdf = PDF
+response-view-events = Events
+response-view-protobuf = Protobuf
+response-view-msgpack = MessagePack
+response-view-cbor = CBOR
+response-view-form = Form
 
 response-media-loading = Preparing media…
 response-media-play = Play
@@ -81,15 +145,47 @@ response-pdf-encrypted = Encrypted PDFs are not supported.
 response-pdf-too-large = The PDF exceeds the safe preview limits.
 response-pdf-render-failed = The PDF page could not be rendered.
 
+response-events-count = Events: { $count }
+response-events-last-id = Last event ID: { $id }
+response-events-reconnecting = Reconnecting in { $delay } ms
+response-events-dropped = { $count } earlier events were dropped.
+response-events-empty = No events have been received yet.
+response-events-id = ID
+response-events-type = Event
+response-events-data = Data
+response-events-retry = Retry (ms)
+
 response-preview-truncated = The preview is truncated. Save the response to inspect all bytes.
 response-decoding-unsupported = This content encoding is not supported. The encoded bytes are preserved.
 response-viewer-mode-unavailable = This view is not available for the response.
 response-viewer-invalid-json = The response is not valid JSON. Showing bounded text instead.
 response-viewer-invalid-image = The response could not be decoded as a supported image.
 response-image-too-large = The image exceeds the safe preview limits.
+response-protobuf-message-type = Message type
+response-protobuf-type-required = Choose a message type to decode the body, or send the type in a messageType content-type parameter.
+response-protobuf-too-large = The body is too large to decode as protobuf.
+response-protobuf-invalid = The body could not be decoded as the chosen message type.
+response-document-too-large = The body is too large to decode.
+response-msgpack-invalid = The body is not valid MessagePack.
+response-cbor-invalid = The body is not valid CBOR.
 response-save-complete = The response was saved.
 response-save-failed = The response could not be saved.
 
+response-tests-empty = This request has no enabled assertions or extractions.
+response-tests-running = Running tests…
+response-tests-summary = { $passed } of { $total } assertions passed
+response-tests-result = Result
+response-tests-assertion = Assertion
+response-tests-detail = Details
+response-tests-variable = Variable
+response-tests-value = Value
+response-tests-pass = Pass
+response-tests-fail = Fail
+response-tests-actual = Actual: { $actual }
+response-tests-missing = Nothing matched.
+response-tests-body-unavailable = The response body could not be read.
+response-tests-body-not-json = The response body is not JSON.
+
 request-problem-transport = The request could not connect to the server.
 request-problem-timeout = The request timed out.
 request-problem-redirect = The redirect chain could not be completed.
@@ -100,6 +196,14 @@ request-problem-storage = The response could not be stored safely.
 request-problem-too-large-encoded = The encoded response is too large ({ $observed } bytes; limit { $limit } bytes).
 request-problem-too-large-stored = The decoded response is too large ({ $observed } bytes; limit { $limit } bytes).
 request-problem-internal = The request ended because of an internal error.
+request-problem-auth-token-endpoint = The OAuth 2.0 token endpoint could not be reached.
+request-problem-auth-token-rejected = The OAuth 2.0 token endpoint rejected the request.
+request-problem-auth-token-invalid = The OAuth 2.0 token endpoint returned an unusable token.
+request-problem-auth-denied = Authorization was denied in the browser.
+request-problem-auth-callback = The browser authorization did not return to HTTP Client.
+request-problem-ca-certificate = The CA certificate bundle could not be loaded.
+request-problem-client-identity = The client certificate or key could not be loaded.
+request-problem-proxy = The proxy settings could not be used.
 
 params-invalid-url-disabled = Enter a valid absolute HTTP or HTTPS URL to edit query parameters.
 
@@ -108,6 +212,7 @@ body-form-data = form-data
 body-urlencoded = x-www-form-urlencoded
 body-text = Text
 body-binary = binary
+body-graphql = GraphQL
 
 text-format-plain = Plain Text
 text-format-json = JSON
@@ -116,6 +221,37 @@ text-format-html = HTML
 text-format-xml = XML
 text-format-css = CSS
 
+graphql-query = Query
+graphql-variables = Variables (JSON)
+graphql-operation = Operation
+graphql-fetch-schema = Fetch Schema
+graphql-schema-none = No schema loaded; completion offers keywords only.
+graphql-schema-loading = Fetching schema…
+graphql-schema-loaded = Schema loaded with { $count } types.
+graphql-schema-prepare = Enter a valid request URL and authorization before fetching the schema.
+graphql-schema-authorization = Send the request once to authorize it, then fetch the schema again.
+graphql-schema-status = The schema request returned HTTP { $status }.
+graphql-schema-not-json = The schema response is not JSON.
+graphql-schema-rejected = The server rejected introspection: { $message }
+graphql-schema-missing = The schema response has no schema.
+graphql-schema-malformed = The schema response is malformed.
+graphql-expected-definition = Expected an operation or fragment.
+graphql-expected-name = Expected a name.
+graphql-expected-punctuator = Expected “{ $punctuator }”.
+graphql-expected-value = Expected a value.
+graphql-expected-end = Unexpected end of the document.
+graphql-anonymous-operation = An anonymous operation must be the only operation in the document.
+graphql-duplicate-operation = There is more than one operation named “{ $name }”.
+graphql-duplicate-fragment = There is more than one fragment named “{ $name }”.
+graphql-unknown-fragment = Unknown fragment “{ $name }”.
+graphql-undefined-variable = Variable “${ $name }” is not declared by its operation.
+graphql-unsupported-operation = The schema does not support { $operation } operations.
+graphql-unknown-type = Unknown type “{ $name }”.
+graphql-unknown-field = Type “{ $type }” has no field “{ $field }”.
+graphql-unknown-argument = Field “{ $field }” has no argument “{ $argument }”.
+graphql-selection-required = Field “{ $field }” of type “{ $type }” needs a selection of subfields.
+graphql-selection-not-allowed = Field “{ $field }” of type “{ $type }” has no subfields.
+
 multipart-text = Text
 multipart-file = File
 multipart-file-not-selected = No file selected
@@ -124,6 +260,13 @@ auth-none = No Auth
 auth-basic = Basic Auth
 auth-bearer = Bearer Token
 auth-api-key = API Key
+auth-digest = Digest Auth
+auth-oauth2 = OAuth 2.0
+auth-aws-sigv4 = AWS Signature
+auth-oauth2-grant-client-credentials = Client Credentials
+auth-oauth2-grant-password = Password Credentials
+auth-oauth2-grant-authorization-code = Authorization Code (PKCE)
+auth-oauth2-redirect-help = Sending opens the browser and waits on this local address for the authorization response.
 auth-location-header = Header
 auth-location-query = Query Params
 auth-generated-override = Generated authorization takes precedence over conflicting request values.
@@ -132,7 +275,88 @@ body-content-type-override = This explicit header takes precedence over the Cont
 
 settings-follow-redirects = Follow redirects
 settings-follow-original-method = Preserve the original method when following redirects
+settings-reconnect-event-stream = Reconnect event streams with Last-Event-ID
+settings-send-cookies = Send cookies from the cookie jar
 settings-timeout-help = Set to 0 for no timeout.
+settings-http-version = HTTP version
+settings-ca-certificate = CA certificates (PEM)
+settings-client-certificate = Client certificate (PEM, .p12 or .pfx)
+settings-client-key = Client key (PEM)
+settings-client-certificate-password = Client certificate password
+settings-accept-invalid-certificates = Accept invalid TLS certificates
+settings-accept-invalid-certificates-warning = Certificate and host name checks are off. Anyone on the network path can read or change this request.
+settings-proxy-url = Proxy URL
+settings-proxy-url-placeholder = http://proxy.example.com:8080
+settings-proxy-username = Proxy username
+settings-proxy-password = Proxy password
+settings-proxy-bypass = Bypass proxy for
+settings-proxy-bypass-placeholder = localhost, .internal.example.com, 10.0.0.0/8
+http-version-automatic = Automatic
+http-version-http1 = HTTP/1.1 only
+http-version-http2 = HTTP/2 only
+
+response-connection-protocol = Protocol
+response-connection-tls = TLS version
+response-connection-plain = Not encrypted
+response-certificate-subject = Subject
+response-certificate-issuer = Issuer
+response-certificate-alt-names = Alternative names
+response-certificate-not-before = Valid from
+response-certificate-not-after = Valid until
+response-certificate-serial = Serial number
+response-certificate-sha256 = SHA-256 fingerprint
+response-certificate-position = Certificate { $position } of { $count }
+response-certificate-unverified = Certificate verification was disabled for this request.
+response-timing-empty = Timing appears once the response head arrives.
+response-timing-total = Total
+response-timing-reused = Reused connection
+response-timing-dns = DNS lookup
+response-timing-connect = TCP connect
+response-timing-connect-tls = TCP connect + TLS handshake
+response-timing-tls = TLS handshake
+response-timing-request-write = Request write
+response-timing-waiting = Waiting (TTFB)
+response-timing-download = Content download
+response-layout-source = Source
+response-layout-tree = Tree
+response-filter-placeholder = Filter with JSONPath ($.items[0]) or jq (.items[] | .id)
+response-filter-running = Filtering…
+response-filter-results = Results: { $count }
+response-filter-invalid = Invalid filter at character { $position }
+response-search-placeholder = Search the response
+response-search-count = { $current } of { $total }
+response-search-none = No matches
+response-diff-no-pin = Pin a response to compare later responses with it.
+response-diff-pinned-current = This is the pinned response. Send again to compare.
+response-diff-waiting = The comparison appears once the response completes.
+response-diff-running = Comparing with the pinned response…
+response-diff-failed = The responses could not be compared.
+response-diff-status = Status changed from { $before } to { $after }
+response-diff-status-same = Status is unchanged
+response-diff-headers = Headers
+response-diff-headers-same = Headers are identical.
+response-diff-body = Body
+response-diff-body-same = Bodies are identical.
+response-diff-body-binary = Binary bodies differ ({ $before } → { $after }).
+response-diff-path = Path
+response-diff-pinned = Pinned
+response-diff-current = Current
+response-diff-truncated = Only the first differences are shown.
+response-diff-skipped = { $count } unchanged lines
+
+checks-assertions = Assertions
+checks-extractions = Extract Variables
+checks-variables = Variables
+checks-variables-hint = Use {"{{"}name{"}}"} in the URL, headers, body or authorization to insert a variable.
+checks-variables-empty = No variables have been extracted yet.
+checks-kind-status = Status is
+checks-kind-header-present = Header exists
+checks-kind-header-matches = Header matches
+checks-kind-json-equals = JSONPath equals
+checks-kind-json-matches = JSONPath matches
+checks-kind-time-below = Response time below (ms)
+checks-source-json-path = JSONPath
+checks-source-header = Header
 
 request-url-invalid = Enter a valid absolute URL with a host.
 request-url-scheme-invalid = Only HTTP and HTTPS URLs are supported.
@@ -143,8 +367,199 @@ request-multipart-name-required = Enter a name for this multipart part.
 request-multipart-name-invalid = Multipart names cannot contain line breaks or null characters.
 request-file-required = Select a file.
 request-file-unavailable = The selected file is unavailable, unreadable, or not a regular file.
+request-client-certificate-required = Select the client certificate this key belongs to.
+request-proxy-url-invalid = Enter an absolute http:// or https:// proxy URL.
 request-file-name-invalid = File names must not be empty or contain line breaks or null characters.
 request-basic-username-colon = Basic Auth usernames cannot contain a colon.
 request-auth-value-invalid = Enter a value that can be used in an HTTP authorization header.
 request-api-key-name-required = Enter an API key name.
 request-api-key-name-invalid = Enter a valid API key name for the selected location.
+request-oauth2-redirect-loopback = Enter an http:// redirect URI on localhost or a loopback address.
+request-aws-scope-invalid = Use only letters, digits, periods, hyphens, and underscores.
+request-check-status-invalid = Enter a status code, a range such as 200-299, or a class such as 2xx.
+request-check-pattern-invalid = Enter a valid regular expression.
+request-check-json-path-invalid = Enter a JSONPath such as $.data.items[0].id.
+request-check-duration-invalid = Enter a positive number of milliseconds.
+request-variable-name-invalid = Start with a letter or underscore and use only letters, digits, “_”, “-” and “.”.
+request-graphql-query-required = Enter a GraphQL query.
+request-graphql-variables-invalid = Variables must be a JSON object.
+request-graphql-operation-required = Choose the operation to run.
+request-graphql-operation-unknown = The document has no operation with this name.
+request-prepare-url = The URL is not valid once its variables are filled in.
+request-prepare-header = A header is not valid once its variables are filled in.
+request-prepare-media-type = The body media type is not valid.
+request-prepare-auth = The authorization is not valid once its variables are filled in.
+request-prepare-file = A selected file is unavailable or unreadable.
+request-prepare-check = An assertion or extraction is not valid once its variables are filled in.
+request-prepare-graphql = The GraphQL body is not valid once its variables are filled in.
+request-prepare-connection = The connection settings are not valid once their variables are filled in.
+request-prepare-unknown-variables = These variables have no value: { $names }
+request-prepare-internal = The request could not be prepared.
+
+websocket-url-placeholder = ws://localhost:8080/socket
+websocket-protocols-placeholder = Subprotocols, comma separated
+websocket-headers-placeholder = One handshake header per line, e.g. Authorization: Bearer …
+websocket-filter-placeholder = Filter messages
+websocket-filter-hide-control = Hide ping, pong and close
+websocket-state-disconnected = Disconnected
+websocket-state-connecting = Connecting…
+websocket-state-open = Connected
+websocket-state-open-protocol = Connected using { $protocol }
+websocket-state-closing = Closing…
+websocket-format-text = Text
+websocket-format-json = JSON
+websocket-format-binary = Binary (hex)
+websocket-direction-all = All
+websocket-direction-sent = Sent
+websocket-direction-received = Received
+websocket-kind-text = Text
+websocket-kind-binary = Binary
+websocket-kind-ping = Ping
+websocket-kind-pong = Pong
+websocket-kind-close = Close
+websocket-close-detail = { $code } { $reason }
+websocket-close-no-status = No status code
+websocket-log-time = Time
+websocket-log-direction = Direction
+websocket-log-kind = Kind
+websocket-log-payload = Payload
+websocket-log-empty = Connect and send a message to see the log.
+websocket-log-no-match = No messages match the filter.
+websocket-log-dropped = { $count } older messages were dropped.
+websocket-url-required = Enter a WebSocket URL.
+websocket-url-invalid = Enter a valid absolute URL with a host.
+websocket-url-scheme = Only ws:// and wss:// URLs are supported.
+websocket-header-invalid = Header on line { $line } must be a valid “Name: value” pair.
+websocket-header-reserved = Header on line { $line } is set by the WebSocket handshake.
+websocket-protocol-invalid = Subprotocols must be comma-separated tokens.
+websocket-message-json-invalid = The message is not valid JSON (line { $line }, column { $column }).
+websocket-message-hex-invalid = Binary messages must be pairs of hexadecimal digits.
+websocket-problem-connect = Could not connect to the server.
+websocket-problem-tls = The secure connection could not be established.
+websocket-problem-rejected = The server refused the upgrade with status { $status }.
+websocket-problem-handshake = The server's handshake response was invalid.
+websocket-problem-protocol = The server violated the WebSocket protocol.
+websocket-problem-connection = The connection was lost.
+
+grpc-target-placeholder = localhost:50051 or https://api.example.com
+grpc-metadata-placeholder = One metadata entry per line, e.g. authorization: Bearer …
+grpc-deadline-ms = Deadline (ms)
+grpc-service-placeholder = Service
+grpc-method-placeholder = Method
+grpc-method-unary = { $method }
+grpc-method-server-streaming = { $method } (server streaming)
+grpc-method-client-streaming = { $method } (client streaming)
+grpc-method-bidirectional = { $method } (bidirectional streaming)
+grpc-method-unsupported = Only unary and server-streaming methods can be called.
+grpc-schema-empty = Load .proto files or use server reflection to list services.
+grpc-schema-loading = Loading services…
+grpc-schema-files = Services from { $count } .proto files
+grpc-schema-reflection = Services reflected from { $host }
+grpc-response-empty = Send a call to see the response messages.
+grpc-call-running = Calling…
+grpc-call-status = { $name } ({ $code })
+grpc-call-status-message = { $name } ({ $code }): { $message }
+grpc-call-messages = Messages: { $count }
+grpc-call-messages-hidden = { $count } later messages are not shown.
+grpc-metadata-name = Metadata
+grpc-metadata-value = Value
+grpc-target-required = Enter a server address.
+grpc-target-invalid = Enter a valid server address with a host.
+grpc-target-scheme = Only http:// and https:// addresses are supported.
+grpc-metadata-invalid = Metadata on line { $line } must be a valid “name: value” pair.
+grpc-metadata-reserved = Metadata on line { $line } is set by the call.
+grpc-message-json-invalid = The request message does not match its type (line { $line }, column { $column }).
+grpc-message-undecodable = A response message does not match the method’s output type.
+grpc-schema-compile = The .proto files could not be compiled: { $detail }
+grpc-schema-invalid = The descriptors are inconsistent: { $detail }
+grpc-schema-no-services = The descriptors define no services.
+grpc-reflection-unsupported = The server does not support reflection.
+grpc-reflection-status = Reflection failed with status { $code }.
+grpc-reflection-server = The server reported a reflection error: { $message }
+grpc-reflection-invalid = The server sent an unreadable reflection response.
+grpc-problem-connect = Could not connect to the server.
+grpc-problem-deadline = The call exceeded its deadline.
+grpc-problem-http-status = The server answered with HTTP status { $status }.
+grpc-problem-not-grpc = The server did not answer with a gRPC response.
+grpc-problem-compressed = The server sent a compressed message, which is not supported.
+grpc-problem-too-large = A response message exceeds 4 MiB.
+grpc-problem-truncated = The response ended in the middle of a message.
+grpc-problem-connection = The connection was lost during the call.
+grpc-problem-missing-status = The call ended without a gRPC status.
+
+cookies-filter-placeholder = Filter by domain
+cookies-domain-placeholder = Domain (.example.com matches subdomains)
+cookies-path-placeholder = Path (default /)
+cookies-name-placeholder = Name
+cookies-value-placeholder = Value
+cookies-expires-placeholder = Expires (YYYY-MM-DD HH:MM)
+cookies-empty = Cookies set by responses appear here.
+cookies-no-match = No domains match the filter.
+cookies-domain-count = { $count } cookies
+cookies-enabled = Enabled
+cookies-name = Name
+cookies-value = Value
+cookies-path = Path
+cookies-expires = Expires
+cookies-flags = Flags
+cookies-session = Session
+cookies-host-only = Host only
+cookies-secure = Secure
+cookies-http-only = HttpOnly
+cookies-domain-required = Enter a cookie domain.
+cookies-name-required = Enter a cookie name.
+cookies-name-invalid = Cookie names cannot contain spaces, “=”, “;” or “,”.
+cookies-value-invalid = Cookie values cannot contain “;” or control characters.
+cookies-path-invalid = Cookie paths must start with “/”.
+cookies-expires-invalid = Enter the expiry as YYYY-MM-DD HH:MM, or leave it blank for a session cookie.
+
+saved-request-read-failed = The request file could not be read.
+saved-request-write-failed = The request could not be saved.
+saved-request-malformed = The file is not a saved request.
+saved-request-newer-version = The request was saved by a newer version of this app.
+har-imported = Imported { $count } requests.
+har-imported-skipped = Imported { $count } requests; skipped { $skipped } entries that are not HTTP requests.
+har-exported = Exported { $count } requests.
+har-read-failed = The HAR file could not be read.
+har-write-failed = The HAR file could not be written.
+har-malformed = The file is not a HAR archive.
+har-no-requests = The HAR file contains no HTTP requests to import.
+request-tab-untitled = Untitled
+request-tab-unsaved-title = Close tab with unsaved changes?
+request-tab-unsaved-message = “{ $name }” has changes that are not saved. Closing the tab discards them.
+
+runner-choose-folder = Choose Folder
+runner-reload-folder = Reload
+runner-no-folder = Choose a folder of saved requests to run them in file name order.
+runner-folder-unreadable = The folder could not be read.
+runner-folder-empty = The folder contains no saved requests.
+runner-folder-summary = { $count } requests in { $folder }
+runner-data-file = Data file
+runner-choose-data-file = Choose CSV
+runner-clear-data-file = Remove
+runner-data-rows = { $file }: { $count } rows
+runner-data-unreadable = The data file could not be read.
+runner-data-not-utf8 = The data file is not UTF-8 text.
+runner-data-missing-header = The data file needs a header row naming its variables.
+runner-data-invalid-column = A header in the data file is not a valid variable name.
+runner-data-ragged-row = A row in the data file has a different number of fields than the header.
+runner-data-unterminated-quote = The data file ends inside a quoted field.
+runner-records-empty = Run the collection to see each request’s result.
+runner-summary = { $passed } passed, { $failed } failed
+runner-column-iteration = Iteration
+runner-column-request = Request
+runner-column-status = Status
+runner-column-time = Time
+runner-column-checks = Checks
+runner-column-result = Result
+runner-step-unreadable = The saved request could not be loaded.
+runner-step-invalid = The request is invalid after substituting variables.
+runner-load-target = Request
+runner-load-limits-help = The run stops at whichever limit comes first. Set a limit to 0 to turn it off.
+runner-load-unbounded = Set a duration, a request count or both.
+runner-load-empty = Start a load run to see throughput and latency.
+runner-load-requests = Requests
+runner-load-throughput = Throughput
+runner-load-throughput-value = { $value } req/s
+runner-load-error-rate = Error rate
+runner-load-histogram = Latency distribution
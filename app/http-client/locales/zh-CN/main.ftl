@@ -5,6 +5,8 @@ gpui-form-error-required = 此字段为必填项。
 button-send = 发送
 button-cancel = 取消
 button-clear-response = 清除响应
+button-pin-response = 固定响应
+button-unpin-response = 取消固定
 button-save-response = 保存响应
 button-add = 添加
 button-confirm = 确认
@@ -14,6 +16,30 @@ button-change-file = 更换文件
 button-clear-file = 清除文件
 button-move-up = 上移
 button-move-down = 下移
+button-clear-variables = 清除变量
+button-connect = 连接
+button-disconnect = 断开
+button-ping = Ping
+button-clear-log = 清空日志
+button-stop-reconnecting = 停止重连
+button-add-cookie = 添加 Cookie
+button-save-cookie = 保存 Cookie
+button-cancel-edit = 取消编辑
+button-edit-cookie = 编辑
+button-delete-cookie = 删除
+button-delete-domain-cookies = 全部删除
+button-open-request = 打开…
+button-save-request = 保存…
+button-import-har = 导入 HAR…
+button-export-har = 导出 HAR…
+button-discard-changes = 放弃更改
+button-run = 运行
+button-stop = 停止
+button-previous-match = 上一个
+button-next-match = 下一个
+button-copy-path = 复制路径
+button-load-protos = 加载 .proto…
+button-reflect = 使用反射
 
 field-method = 方法
 field-url = 链接
@@ -27,14 +53,47 @@ field-password = 密码
 field-token = 令牌
 field-location = 位置
 field-timeout-ms = 超时（毫秒）
+field-grant-type = 授权类型
+field-token-url = 令牌地址
+field-authorization-url = 授权地址
+field-redirect-uri = 重定向地址
+field-client-id = 客户端 ID
+field-client-secret = 客户端密钥
+field-scope = 权限范围
+field-access-key-id = 访问密钥 ID
+field-secret-access-key = 秘密访问密钥
+field-session-token = 会话令牌
+field-region = 区域
+field-service = 服务名称
+field-variable = 变量
+field-check-target = 响应头或 JSONPath
+field-check-expected = 期望值
+field-runner-iterations = 迭代次数
+field-runner-delay-ms = 请求间隔（毫秒）
+field-runner-concurrency = 并发数
+field-runner-duration-s = 持续时间（秒）
+field-runner-request-limit = 请求数
 
 tab-params = 参数
 tab-authorization = 授权
 tab-headers = 请求头
 tab-body = 请求体
+tab-tests = 测试
 tab-settings = 设置
 tab-response-body = 响应体
 tab-response-headers = 响应头
+tab-response-tests = 测试
+tab-response-connection = 连接
+tab-response-timing = 耗时
+tab-response-diff = 对比
+tab-runner-collection = 集合
+tab-runner-load = 压测
+
+mode-http = HTTP
+mode-runner = 运行器
+mode-websocket = WebSocket
+mode-grpc = gRPC
+mode-cookies = Cookie
 
 response-title = 响应
 response-empty = 发送请求后可在此查看响应。
@@ -61,6 +120,11 @@ response-view-base64 = Base64
 response-view-image = 图片
 response-view-audio = 音频
 response-view-pdf = PDF
+response-view-events = 事件
+response-view-protobuf = Protobuf
+response-view-msgpack = MessagePack
+response-view-cbor = CBOR
+response-view-form = 表单
 
 response-media-loading = 正在准备媒体…
 response-media-play = 播放
@@ -81,15 +145,47 @@ response-pdf-encrypted = 暂不支持加密 PDF。
 response-pdf-too-large = PDF 超出安全预览限制。
 response-pdf-render-failed = 无法渲染 PDF 页面。
 
+response-events-count = 事件：{ $count }
+response-events-last-id = 最后事件 ID：{ $id }
+response-events-reconnecting = { $delay } 毫秒后重新连接
+response-events-dropped = 已丢弃 { $count } 个较早的事件。
+response-events-empty = 尚未收到任何事件。
+response-events-id = ID
+response-events-type = 事件
+response-events-data = 数据
+response-events-retry = 重试（毫秒）
+
 response-preview-truncated = 预览已截断。请保存响应以查看全部字节。
 response-decoding-unsupported = 不支持该内容编码，已保留编码后的字节。
 response-viewer-mode-unavailable = 当前响应无法使用该视图。
 response-viewer-invalid-json = 响应不是有效 JSON，已改为显示受限文本。
 response-viewer-invalid-image = 无法将响应解码为受支持的图片。
 response-image-too-large = 图片超出安全预览限制。
+response-protobuf-message-type = 消息类型
+response-protobuf-type-required = 请选择用于解码响应体的消息类型，或在 Content-Type 的 messageType 参数中指定类型。
+response-protobuf-too-large = 响应体过大，无法按 Protobuf 解码。
+response-protobuf-invalid = 无法按所选消息类型解码响应体。
+response-document-too-large = 响应体过大，无法解码。
+response-msgpack-invalid = 响应体不是有效的 MessagePack。
+response-cbor-invalid = 响应体不是有效的 CBOR。
 response-save-complete = 响应已保存。
 response-save-failed = 无法保存响应。
 
+response-tests-empty = 此请求没有启用的断言或变量提取。
+response-tests-running = 正在运行测试…
+response-tests-summary = { $total } 个断言中通过 { $passed } 个
+response-tests-result = 结果
+response-tests-assertion = 断言
+response-tests-detail = 详情
+response-tests-variable = 变量
+response-tests-value = 值
+response-tests-pass = 通过
+response-tests-fail = 失败
+response-tests-actual = 实际值：{ $actual }
+response-tests-missing = 没有匹配的内容。
+response-tests-body-unavailable = 无法读取响应体。
+response-tests-body-not-json = 响应体不是 JSON。
+
 request-problem-transport = 请求无法连接到服务器。
 request-problem-timeout = 请求已超时。
 request-problem-redirect = 无法完成重定向链。
@@ -100,6 +196,14 @@ request-problem-storage = 无法安全保存响应。
 request-problem-too-large-encoded = 编码后的响应过大（{ $observed } 字节，上限 { $limit } 字节）。
 request-problem-too-large-stored = 解码后的响应过大（{ $observed } 字节，上限 { $limit } 字节）。
 request-problem-internal = 请求因内部错误结束。
+request-problem-auth-token-endpoint = 无法连接 OAuth 2.0 令牌端点。
+request-problem-auth-token-rejected = OAuth 2.0 令牌端点拒绝了请求。
+request-problem-auth-token-invalid = OAuth 2.0 令牌端点返回的令牌不可用。
+request-problem-auth-denied = 浏览器中的授权被拒绝。
+request-problem-auth-callback = 浏览器授权未返回到 HTTP Client。
+request-problem-ca-certificate = 无法加载 CA 证书包。
+request-problem-client-identity = 无法加载客户端证书或私钥。
+request-problem-proxy = 无法使用代理设置。
 
 params-invalid-url-disabled = 请输入有效的 HTTP 或 HTTPS 绝对链接后再编辑查询参数。
 
@@ -108,6 +212,7 @@ body-form-data = form-data
 body-urlencoded = x-www-form-urlencoded
 body-text = 文本
 body-binary = 二进制文件
+body-graphql = GraphQL
 
 text-format-plain = 纯文本
 text-format-json = JSON
@@ -116,6 +221,37 @@ text-format-html = HTML
 text-format-xml = XML
 text-format-css = CSS
 
+graphql-query = 查询
+graphql-variables = 变量（JSON）
+graphql-operation = 操作
+graphql-fetch-schema = 获取 Schema
+graphql-schema-none = 尚未加载 Schema，补全仅提供关键字。
+graphql-schema-loading = 正在获取 Schema…
+graphql-schema-loaded = Schema 已加载，共 { $count } 个类型。
+graphql-schema-prepare = 获取 Schema 前请填写有效的请求 URL 和授权。
+graphql-schema-authorization = 请先发送一次请求完成授权，再重新获取 Schema。
+graphql-schema-status = Schema 请求返回了 HTTP { $status }。
+graphql-schema-not-json = Schema 响应不是 JSON。
+graphql-schema-rejected = 服务器拒绝了内省请求：{ $message }
+graphql-schema-missing = Schema 响应中没有 Schema。
+graphql-schema-malformed = Schema 响应格式不正确。
+graphql-expected-definition = 此处应为操作或片段。
+graphql-expected-name = 此处应为名称。
+graphql-expected-punctuator = 此处应为“{ $punctuator }”。
+graphql-expected-value = 此处应为值。
+graphql-expected-end = 文档意外结束。
+graphql-anonymous-operation = 匿名操作必须是文档中唯一的操作。
+graphql-duplicate-operation = 存在多个名为“{ $name }”的操作。
+graphql-duplicate-fragment = 存在多个名为“{ $name }”的片段。
+graphql-unknown-fragment = 未知片段“{ $name }”。
+graphql-undefined-variable = 变量“${ $name }”未在其操作中声明。
+graphql-unsupported-operation = 该 Schema 不支持 { $operation } 操作。
+graphql-unknown-type = 未知类型“{ $name }”。
+graphql-unknown-field = 类型“{ $type }”没有字段“{ $field }”。
+graphql-unknown-argument = 字段“{ $field }”没有参数“{ $argument }”。
+graphql-selection-required = 类型为“{ $type }”的字段“{ $field }”需要选择子字段。
+graphql-selection-not-allowed = 类型为“{ $type }”的字段“{ $field }”没有子字段。
+
 multipart-text = 文本
 multipart-file = 文件
 multipart-file-not-selected = 未选择文件
@@ -124,6 +260,13 @@ auth-none = 无授权
 auth-basic = 基本身份验证
 auth-bearer = Bearer 令牌
 auth-api-key = API 密钥
+auth-digest = 摘要认证
+auth-oauth2 = OAuth 2.0
+auth-aws-sigv4 = AWS 签名
+auth-oauth2-grant-client-credentials = 客户端凭据
+auth-oauth2-grant-password = 密码凭据
+auth-oauth2-grant-authorization-code = 授权码（PKCE）
+auth-oauth2-redirect-help = 发送时会打开浏览器，并在此本地地址等待授权结果。
 auth-location-header = 请求头
 auth-location-query = 查询参数
 auth-generated-override = 生成的授权信息优先于请求中冲突的值。
@@ -132,7 +275,88 @@ body-content-type-override = 此显式请求头优先于请求体生成的 Conte
 
 settings-follow-redirects = 跟随重定向
 settings-follow-original-method = 跟随重定向时保留原请求方法
+settings-reconnect-event-stream = 使用 Last-Event-ID 自动重连事件流
+settings-send-cookies = 发送 Cookie 库中的 Cookie
 settings-timeout-help = 设为 0 表示不限时。
+settings-http-version = HTTP 版本
+settings-ca-certificate = CA 证书（PEM）
+settings-client-certificate = 客户端证书（PEM、.p12 或 .pfx）
+settings-client-key = 客户端私钥（PEM）
+settings-client-certificate-password = 客户端证书密码
+settings-accept-invalid-certificates = 接受无效的 TLS 证书
+settings-accept-invalid-certificates-warning = 证书和主机名校验已关闭，网络路径上的任何人都可以读取或篡改此请求。
+settings-proxy-url = 代理 URL
+settings-proxy-url-placeholder = http://proxy.example.com:8080
+settings-proxy-username = 代理用户名
+settings-proxy-password = 代理密码
+settings-proxy-bypass = 不使用代理的地址
+settings-proxy-bypass-placeholder = localhost, .internal.example.com, 10.0.0.0/8
+http-version-automatic = 自动
+http-version-http1 = 仅 HTTP/1.1
+http-version-http2 = 仅 HTTP/2
+
+response-connection-protocol = 协议
+response-connection-tls = TLS 版本
+response-connection-plain = 未加密
+response-certificate-subject = 主体
+response-certificate-issuer = 颁发者
+response-certificate-alt-names = 备用名称
+response-certificate-not-before = 生效时间
+response-certificate-not-after = 到期时间
+response-certificate-serial = 序列号
+response-certificate-sha256 = SHA-256 指纹
+response-certificate-position = 证书 { $position }/{ $count }
+response-certificate-unverified = 此请求已关闭证书校验。
+response-timing-empty = 收到响应头后显示耗时。
+response-timing-total = 总计
+response-timing-reused = 复用连接
+response-timing-dns = DNS 查询
+response-timing-connect = TCP 连接
+response-timing-connect-tls = TCP 连接 + TLS 握手
+response-timing-tls = TLS 握手
+response-timing-request-write = 发送请求
+response-timing-waiting = 等待响应（TTFB）
+response-timing-download = 下载内容
+response-layout-source = 源码
+response-layout-tree = 树形
+response-filter-placeholder = 使用 JSONPath（$.items[0]）或 jq（.items[] | .id）过滤
+response-filter-running = 正在过滤…
+response-filter-results = { $count } 个结果
+response-filter-invalid = 过滤表达式在第 { $position } 个字符处无效
+response-search-placeholder = 搜索响应内容
+response-search-count = 第 { $current } 个，共 { $total } 个
+response-search-none = 无匹配
+response-diff-no-pin = 固定一个响应后，可与之后的响应进行对比。
+response-diff-pinned-current = 当前就是已固定的响应，重新发送后即可对比。
+response-diff-waiting = 响应完成后显示对比结果。
+response-diff-running = 正在与固定的响应对比…
+response-diff-failed = 无法对比这两个响应。
+response-diff-status = 状态码由 { $before } 变为 { $after }
+response-diff-status-same = 状态码未变化
+response-diff-headers = 响应头
+response-diff-headers-same = 响应头完全相同。
+response-diff-body = 响应体
+response-diff-body-same = 响应体完全相同。
+response-diff-body-binary = 二进制响应体不同（{ $before } → { $after }）。
+response-diff-path = 路径
+response-diff-pinned = 已固定
+response-diff-current = 当前
+response-diff-truncated = 仅显示前面的差异。
+response-diff-skipped = { $count } 行未变化
+
+checks-assertions = 断言
+checks-extractions = 提取变量
+checks-variables = 变量
+checks-variables-hint = 在链接、请求头、请求体或授权中使用 {"{{"}name{"}}"} 插入变量。
+checks-variables-empty = 尚未提取任何变量。
+checks-kind-status = 状态码为
+checks-kind-header-present = 响应头存在
+checks-kind-header-matches = 响应头匹配
+checks-kind-json-equals = JSONPath 等于
+checks-kind-json-matches = JSONPath 匹配
+checks-kind-time-below = 响应时间低于（毫秒）
+checks-source-json-path = JSONPath
+checks-source-header = 响应头
 
 request-url-invalid = 请输入带主机名的有效绝对链接。
 request-url-scheme-invalid = 仅支持 HTTP 和 HTTPS 链接。
@@ -143,8 +367,199 @@ request-multipart-name-required = 请输入该 multipart 部分的名称。
 request-multipart-name-invalid = multipart 名称不能包含换行或空字符。
 request-file-required = 请选择文件。
 request-file-unavailable = 所选文件不可用、不可读或不是普通文件。
+request-client-certificate-required = 请选择该私钥对应的客户端证书。
+request-proxy-url-invalid = 请输入绝对的 http:// 或 https:// 代理 URL。
 request-file-name-invalid = 文件名不能为空，也不能包含换行或空字符。
 request-basic-username-colon = 基本身份验证用户名不能包含冒号。
 request-auth-value-invalid = 请输入可用于 HTTP 授权请求头的值。
 request-api-key-name-required = 请输入 API 密钥名称。
 request-api-key-name-invalid = 请输入适用于所选位置的有效 API 密钥名称。
+request-oauth2-redirect-loopback = 请输入位于 localhost 或回环地址的 http:// 重定向地址。
+request-aws-scope-invalid = 只能使用字母、数字、句点、连字符和下划线。
+request-check-status-invalid = 请输入状态码、范围（如 200-299）或类别（如 2xx）。
+request-check-pattern-invalid = 请输入有效的正则表达式。
+request-check-json-path-invalid = 请输入 JSONPath，例如 $.data.items[0].id。
+request-check-duration-invalid = 请输入正数毫秒值。
+request-variable-name-invalid = 以字母或下划线开头，只能使用字母、数字、“_”、“-”和“.”。
+request-graphql-query-required = 请输入 GraphQL 查询。
+request-graphql-variables-invalid = 变量必须是 JSON 对象。
+request-graphql-operation-required = 请选择要执行的操作。
+request-graphql-operation-unknown = 文档中没有这个名称的操作。
+request-prepare-url = 填入变量后 URL 无效。
+request-prepare-header = 填入变量后有请求头无效。
+request-prepare-media-type = 请求体的媒体类型无效。
+request-prepare-auth = 填入变量后认证信息无效。
+request-prepare-file = 所选文件不可用或不可读。
+request-prepare-check = 填入变量后有断言或提取规则无效。
+request-prepare-graphql = 填入变量后 GraphQL 请求体无效。
+request-prepare-connection = 填入变量后连接设置无效。
+request-prepare-unknown-variables = 以下变量没有值：{ $names }
+request-prepare-internal = 无法准备请求。
+
+websocket-url-placeholder = ws://localhost:8080/socket
+websocket-protocols-placeholder = 子协议，用逗号分隔
+websocket-headers-placeholder = 每行一个握手请求头，例如 Authorization: Bearer …
+websocket-filter-placeholder = 筛选消息
+websocket-filter-hide-control = 隐藏 Ping、Pong 和关闭帧
+websocket-state-disconnected = 未连接
+websocket-state-connecting = 正在连接…
+websocket-state-open = 已连接
+websocket-state-open-protocol = 已连接，子协议 { $protocol }
+websocket-state-closing = 正在关闭…
+websocket-format-text = 文本
+websocket-format-json = JSON
+websocket-format-binary = 二进制（十六进制）
+websocket-direction-all = 全部
+websocket-direction-sent = 发送
+websocket-direction-received = 接收
+websocket-kind-text = 文本
+websocket-kind-binary = 二进制
+websocket-kind-ping = Ping
+websocket-kind-pong = Pong
+websocket-kind-close = 关闭
+websocket-close-detail = { $code } { $reason }
+websocket-close-no-status = 无状态码
+websocket-log-time = 时间
+websocket-log-direction = 方向
+websocket-log-kind = 类型
+websocket-log-payload = 内容
+websocket-log-empty = 连接并发送消息后在此查看日志。
+websocket-log-no-match = 没有符合筛选条件的消息。
+websocket-log-dropped = 已丢弃 { $count } 条较早的消息。
+websocket-url-required = 请输入 WebSocket 链接。
+websocket-url-invalid = 请输入带主机名的有效绝对链接。
+websocket-url-scheme = 仅支持 ws:// 和 wss:// 链接。
+websocket-header-invalid = 第 { $line } 行的请求头必须是有效的“名称: 值”。
+websocket-header-reserved = 第 { $line } 行的请求头由 WebSocket 握手设置。
+websocket-protocol-invalid = 子协议必须是以逗号分隔的标记。
+websocket-message-json-invalid = 消息不是有效的 JSON（第 { $line } 行，第 { $column } 列）。
+websocket-message-hex-invalid = 二进制消息必须是成对的十六进制数字。
+websocket-problem-connect = 无法连接到服务器。
+websocket-problem-tls = 无法建立安全连接。
+websocket-problem-rejected = 服务器以状态码 { $status } 拒绝了升级。
+websocket-problem-handshake = 服务器的握手响应无效。
+websocket-problem-protocol = 服务器违反了 WebSocket 协议。
+websocket-problem-connection = 连接已断开。
+
+grpc-target-placeholder = localhost:50051 或 https://api.example.com
+grpc-metadata-placeholder = 每行一条元数据，例如 authorization: Bearer …
+grpc-deadline-ms = 截止时间（毫秒）
+grpc-service-placeholder = 服务
+grpc-method-placeholder = 方法
+grpc-method-unary = { $method }
+grpc-method-server-streaming = { $method }（服务端流）
+grpc-method-client-streaming = { $method }（客户端流）
+grpc-method-bidirectional = { $method }（双向流）
+grpc-method-unsupported = 仅支持调用一元方法和服务端流方法。
+grpc-schema-empty = 加载 .proto 文件或使用服务端反射以列出服务。
+grpc-schema-loading = 正在加载服务…
+grpc-schema-files = 服务来自 { $count } 个 .proto 文件
+grpc-schema-reflection = 服务通过反射获取自 { $host }
+grpc-response-empty = 发送调用以查看响应消息。
+grpc-call-running = 正在调用…
+grpc-call-status = { $name }（{ $code }）
+grpc-call-status-message = { $name }（{ $code }）：{ $message }
+grpc-call-messages = 消息：{ $count }
+grpc-call-messages-hidden = 后续 { $count } 条消息未显示。
+grpc-metadata-name = 元数据
+grpc-metadata-value = 值
+grpc-target-required = 请输入服务器地址。
+grpc-target-invalid = 请输入包含主机的有效服务器地址。
+grpc-target-scheme = 仅支持 http:// 和 https:// 地址。
+grpc-metadata-invalid = 第 { $line } 行的元数据必须是有效的“name: value”对。
+grpc-metadata-reserved = 第 { $line } 行的元数据由调用自动设置。
+grpc-message-json-invalid = 请求消息与其类型不匹配（第 { $line } 行，第 { $column } 列）。
+grpc-message-undecodable = 响应消息与方法的输出类型不匹配。
+grpc-schema-compile = 无法编译 .proto 文件：{ $detail }
+grpc-schema-invalid = 描述符不一致：{ $detail }
+grpc-schema-no-services = 描述符中没有定义服务。
+grpc-reflection-unsupported = 服务器不支持反射。
+grpc-reflection-status = 反射失败，状态码为 { $code }。
+grpc-reflection-server = 服务器报告了反射错误：{ $message }
+grpc-reflection-invalid = 服务器返回了无法读取的反射响应。
+grpc-problem-connect = 无法连接到服务器。
+grpc-problem-deadline = 调用超过了截止时间。
+grpc-problem-http-status = 服务器返回了 HTTP 状态码 { $status }。
+grpc-problem-not-grpc = 服务器返回的不是 gRPC 响应。
+grpc-problem-compressed = 服务器发送了压缩消息，暂不支持。
+grpc-problem-too-large = 响应消息超过 4 MiB。
+grpc-problem-truncated = 响应在消息中途结束。
+grpc-problem-connection = 调用期间连接已断开。
+grpc-problem-missing-status = 调用结束时没有返回 gRPC 状态。
+
+cookies-filter-placeholder = 按域名筛选
+cookies-domain-placeholder = 域名（.example.com 匹配子域名）
+cookies-path-placeholder = 路径（默认 /）
+cookies-name-placeholder = 名称
+cookies-value-placeholder = 值
+cookies-expires-placeholder = 过期时间（YYYY-MM-DD HH:MM）
+cookies-empty = 响应设置的 Cookie 会显示在这里。
+cookies-no-match = 没有匹配筛选条件的域名。
+cookies-domain-count = { $count } 个 Cookie
+cookies-enabled = 启用
+cookies-name = 名称
+cookies-value = 值
+cookies-path = 路径
+cookies-expires = 过期时间
+cookies-flags = 属性
+cookies-session = 会话
+cookies-host-only = 仅主机
+cookies-secure = Secure
+cookies-http-only = HttpOnly
+cookies-domain-required = 请输入 Cookie 域名。
+cookies-name-required = 请输入 Cookie 名称。
+cookies-name-invalid = Cookie 名称不能包含空格、“=”、“;”或“,”。
+cookies-value-invalid = Cookie 值不能包含“;”或控制字符。
+cookies-path-invalid = Cookie 路径必须以“/”开头。
+cookies-expires-invalid = 请按 YYYY-MM-DD HH:MM 输入过期时间，留空则为会话 Cookie。
+
+saved-request-read-failed = 无法读取请求文件。
+saved-request-write-failed = 无法保存请求。
+saved-request-malformed = 该文件不是已保存的请求。
+saved-request-newer-version = 该请求由更新版本的应用保存。
+har-imported = 已导入 { $count } 个请求。
+har-imported-skipped = 已导入 { $count } 个请求；跳过了 { $skipped } 个非 HTTP 请求的条目。
+har-exported = 已导出 { $count } 个请求。
+har-read-failed = 无法读取 HAR 文件。
+har-write-failed = 无法写入 HAR 文件。
+har-malformed = 该文件不是 HAR 归档。
+har-no-requests = HAR 文件中没有可导入的 HTTP 请求。
+request-tab-untitled = 未命名
+request-tab-unsaved-title = 关闭有未保存更改的标签页？
+request-tab-unsaved-message = “{ $name }”有未保存的更改，关闭标签页将丢弃这些更改。
+
+runner-choose-folder = 选择文件夹
+runner-reload-folder = 重新加载
+runner-no-folder = 选择一个保存了请求的文件夹，按文件名顺序运行。
+runner-folder-unreadable = 无法读取该文件夹。
+runner-folder-empty = 该文件夹中没有已保存的请求。
+runner-folder-summary = { $folder } 中共 { $count } 个请求
+runner-data-file = 数据文件
+runner-choose-data-file = 选择 CSV
+runner-clear-data-file = 移除
+runner-data-rows = { $file }：{ $count } 行
+runner-data-unreadable = 无法读取数据文件。
+runner-data-not-utf8 = 数据文件不是 UTF-8 文本。
+runner-data-missing-header = 数据文件需要一行表头来命名变量。
+runner-data-invalid-column = 数据文件的表头中有无效的变量名。
+runner-data-ragged-row = 数据文件中某一行的字段数与表头不一致。
+runner-data-unterminated-quote = 数据文件在引号字段内结束。
+runner-records-empty = 运行集合后在此查看每个请求的结果。
+runner-summary = 通过 { $passed } 个，失败 { $failed } 个
+runner-column-iteration = 迭代
+runner-column-request = 请求
+runner-column-status = 状态
+runner-column-time = 耗时
+runner-column-checks = 检查
+runner-column-result = 结果
+runner-step-unreadable = 无法加载已保存的请求。
+runner-step-invalid = 替换变量后请求无效。
+runner-load-target = 请求
+runner-load-limits-help = 先达到的限制会结束运行。设为 0 表示不限制。
+runner-load-unbounded = 请设置持续时间、请求数或两者。
+runner-load-empty = 开始压测后在此查看吞吐量和延迟。
+runner-load-requests = 请求数
+runner-load-throughput = 吞吐量
+runner-load-throughput-value = { $value } 次/秒
+runner-load-error-rate = 错误率
+runner-load-histogram = 延迟分布
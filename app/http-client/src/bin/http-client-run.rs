@@ -0,0 +1,6 @@
+//! Sends saved requests from a terminal without the desktop app, so CI can
+//! run request files on machines with no display.
+
+fn main() {
+    std::process::exit(http_client::run_headless(std::env::args().skip(1)));
+}
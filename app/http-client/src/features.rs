@@ -1,3 +1,10 @@
+pub(crate) mod cookies;
+pub(crate) mod grpc;
 pub(crate) mod request;
+pub(crate) mod websocket;
+pub(crate) mod workspace;
 
-pub(crate) use request::RequestView;
+pub(crate) use cookies::CookieManagerView;
+pub(crate) use grpc::GrpcView;
+pub(crate) use request::{RequestSessionView, RunnerView};
+pub(crate) use websocket::WebSocketView;
@@ -0,0 +1,504 @@
+use std::collections::BTreeMap;
+
+use fluent_bundle::FluentArgs;
+use gpui::{
+    App, AppContext as _, Context, Entity, IntoElement, ParentElement as _, Render, SharedString,
+    Styled as _, Subscription, Window, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    checkbox::Checkbox,
+    input::{Input, InputState},
+    label::Label,
+    scroll::ScrollableElement as _,
+    table::{Table, TableBody, TableCell, TableHead, TableHeader, TableRow},
+    v_flex,
+};
+use time::UtcOffset;
+
+use self::form::{CookieFields, CookieFormError, format_domain, format_expires, parse_cookie};
+use crate::features::request::{Cookie, CookieJar, CookieKey};
+use crate::foundation::I18n;
+
+mod form;
+
+/// Lists the shared jar per domain and edits single cookies. The jar is
+/// read again on every render, so cookies stored by a running request show
+/// up the next time the view paints.
+pub(crate) struct CookieManagerView {
+    jar: CookieJar,
+    filter: Entity<InputState>,
+    domain: Entity<InputState>,
+    path: Entity<InputState>,
+    name: Entity<InputState>,
+    value: Entity<InputState>,
+    expires: Entity<InputState>,
+    secure: bool,
+    http_only: bool,
+    /// The cookie the editor replaces on save, or `None` when adding.
+    editing: Option<CookieKey>,
+    notice: Option<CookieFormError>,
+    offset: UtcOffset,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl CookieManagerView {
+    pub(crate) fn new(jar: CookieJar, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let placeholders = {
+            let i18n = cx.global::<I18n>();
+            [
+                i18n.t("cookies-filter-placeholder"),
+                i18n.t("cookies-domain-placeholder"),
+                i18n.t("cookies-path-placeholder"),
+                i18n.t("cookies-name-placeholder"),
+                i18n.t("cookies-value-placeholder"),
+                i18n.t("cookies-expires-placeholder"),
+            ]
+        };
+        let [filter, domain, path, name, value, expires] = placeholders
+            .map(|placeholder| cx.new(|cx| InputState::new(window, cx).placeholder(placeholder)));
+        let subscriptions = vec![cx.observe(&filter, |_, _, cx| cx.notify())];
+
+        Self {
+            jar,
+            filter,
+            domain,
+            path,
+            name,
+            value,
+            expires,
+            secure: false,
+            http_only: false,
+            editing: None,
+            notice: None,
+            offset: UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn edit(&mut self, cookie: &Cookie, window: &mut Window, cx: &mut Context<Self>) {
+        let expires = cookie
+            .expires
+            .map(|expires| format_expires(expires, self.offset))
+            .unwrap_or_default();
+        self.fill(
+            [
+                format_domain(cookie),
+                cookie.path.clone(),
+                cookie.name.clone(),
+                cookie.value.clone(),
+                expires,
+            ],
+            window,
+            cx,
+        );
+        self.secure = cookie.secure;
+        self.http_only = cookie.http_only;
+        self.editing = Some(cookie.key());
+        self.notice = None;
+        cx.notify();
+    }
+
+    fn reset(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.fill(Default::default(), window, cx);
+        self.secure = false;
+        self.http_only = false;
+        self.editing = None;
+        self.notice = None;
+        cx.notify();
+    }
+
+    fn fill(&self, values: [String; 5], window: &mut Window, cx: &mut Context<Self>) {
+        let inputs = [
+            &self.domain,
+            &self.path,
+            &self.name,
+            &self.value,
+            &self.expires,
+        ];
+        for (input, value) in inputs.into_iter().zip(values) {
+            input.update(cx, |state, cx| state.set_value(value, window, cx));
+        }
+    }
+
+    fn save(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (domain, path, name, value, expires) = (
+            self.domain.read(cx).value(),
+            self.path.read(cx).value(),
+            self.name.read(cx).value(),
+            self.value.read(cx).value(),
+            self.expires.read(cx).value(),
+        );
+        let fields = CookieFields {
+            domain: &domain,
+            path: &path,
+            name: &name,
+            value: &value,
+            expires: &expires,
+            secure: self.secure,
+            http_only: self.http_only,
+        };
+        match parse_cookie(&fields, self.offset) {
+            Ok(mut cookie) => {
+                if let Some(previous) = self.editing.as_ref().and_then(|key| self.find(key)) {
+                    cookie.enabled = previous.enabled;
+                }
+                self.jar.upsert(self.editing.as_ref(), cookie);
+                self.reset(window, cx);
+            }
+            Err(error) => {
+                self.notice = Some(error);
+                cx.notify();
+            }
+        }
+    }
+
+    fn find(&self, key: &CookieKey) -> Option<Cookie> {
+        self.jar
+            .cookies()
+            .into_iter()
+            .find(|cookie| cookie.key() == *key)
+    }
+
+    fn remove(&mut self, key: &CookieKey, window: &mut Window, cx: &mut Context<Self>) {
+        self.jar.remove(key);
+        if self.editing.as_ref() == Some(key) {
+            self.reset(window, cx);
+        }
+        cx.notify();
+    }
+
+    fn remove_domain(&mut self, domain: &str, window: &mut Window, cx: &mut Context<Self>) {
+        self.jar.remove_domain(domain);
+        if self
+            .editing
+            .as_ref()
+            .is_some_and(|key| key.domain == domain)
+        {
+            self.reset(window, cx);
+        }
+        cx.notify();
+    }
+
+    fn render_editor(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let (secure_label, http_only_label, save_label, cancel_label) = {
+            let i18n = cx.global::<I18n>();
+            (
+                i18n.t("cookies-secure"),
+                i18n.t("cookies-http-only"),
+                if self.editing.is_some() {
+                    i18n.t("button-save-cookie")
+                } else {
+                    i18n.t("button-add-cookie")
+                },
+                i18n.t("button-cancel-edit"),
+            )
+        };
+        let notice = self
+            .notice
+            .map(|notice| cx.global::<I18n>().t(notice_key(notice)));
+
+        v_flex()
+            .p_2()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(div().w(px(200.)).child(Input::new(&self.domain)))
+                    .child(div().w(px(120.)).child(Input::new(&self.path)))
+                    .child(div().w(px(160.)).child(Input::new(&self.name)))
+                    .child(div().flex_1().child(Input::new(&self.value))),
+            )
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(div().w(px(200.)).child(Input::new(&self.expires)))
+                    .child(
+                        Checkbox::new("cookies-secure")
+                            .label(secure_label)
+                            .checked(self.secure)
+                            .on_click(cx.listener(|this, checked, _, cx| {
+                                this.secure = *checked;
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Checkbox::new("cookies-http-only")
+                            .label(http_only_label)
+                            .checked(self.http_only)
+                            .on_click(cx.listener(|this, checked, _, cx| {
+                                this.http_only = *checked;
+                                cx.notify();
+                            })),
+                    )
+                    .when_some(notice, |this, notice| {
+                        this.child(Label::new(notice).text_sm().text_color(cx.theme().danger))
+                    })
+                    .child(div().flex_1())
+                    .when(self.editing.is_some(), |this| {
+                        this.child(
+                            Button::new("cookies-cancel-edit")
+                                .label(cancel_label)
+                                .on_click(
+                                    cx.listener(|this, _, window, cx| this.reset(window, cx)),
+                                ),
+                        )
+                    })
+                    .child(
+                        Button::new("cookies-save")
+                            .primary()
+                            .label(save_label)
+                            .on_click(cx.listener(|this, _, window, cx| this.save(window, cx))),
+                    ),
+            )
+    }
+
+    fn render_domains(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let query = self.filter.read(cx).value().trim().to_ascii_lowercase();
+        let mut domains = BTreeMap::<String, Vec<Cookie>>::new();
+        for cookie in self.jar.cookies() {
+            if query.is_empty() || cookie.domain.contains(&query) {
+                domains
+                    .entry(cookie.domain.clone())
+                    .or_default()
+                    .push(cookie);
+            }
+        }
+
+        let body = if domains.is_empty() {
+            let key = if query.is_empty() {
+                "cookies-empty"
+            } else {
+                "cookies-no-match"
+            };
+            div()
+                .flex_1()
+                .min_h(px(0.))
+                .flex()
+                .items_center()
+                .justify_center()
+                .child(Label::new(cx.global::<I18n>().t(key)))
+                .into_any_element()
+        } else {
+            let groups = domains
+                .into_iter()
+                .enumerate()
+                .map(|(index, (domain, cookies))| self.domain_group(index, domain, cookies, cx))
+                .collect::<Vec<_>>();
+            div()
+                .flex_1()
+                .min_h(px(0.))
+                .overflow_scrollbar()
+                .child(v_flex().p_2().gap_4().children(groups))
+                .into_any_element()
+        };
+
+        v_flex()
+            .flex_1()
+            .min_h(px(0.))
+            .overflow_hidden()
+            .child(div().p_2().child(Input::new(&self.filter)))
+            .child(body)
+    }
+
+    fn domain_group(
+        &self,
+        index: usize,
+        domain: String,
+        cookies: Vec<Cookie>,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let (count, delete_label, headers) = {
+            let i18n = cx.global::<I18n>();
+            let mut args = FluentArgs::new();
+            args.set("count", cookies.len());
+            (
+                i18n.t_with_args("cookies-domain-count", &args),
+                i18n.t("button-delete-domain-cookies"),
+                [
+                    i18n.t("cookies-enabled"),
+                    i18n.t("cookies-name"),
+                    i18n.t("cookies-value"),
+                    i18n.t("cookies-path"),
+                    i18n.t("cookies-expires"),
+                    i18n.t("cookies-flags"),
+                ],
+            )
+        };
+        let [enabled, name, value, path, expires, flags] = headers;
+        let rows = cookies
+            .iter()
+            .enumerate()
+            .map(|(row, cookie)| self.cookie_row(index, row, cookie, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .gap_1()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(Label::new(domain.clone()).font_semibold())
+                    .child(
+                        Label::new(count)
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .child(div().flex_1())
+                    .child(
+                        Button::new(SharedString::from(format!("cookies-delete-domain-{index}")))
+                            .danger()
+                            .small()
+                            .label(delete_label)
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.remove_domain(&domain, window, cx);
+                            })),
+                    ),
+            )
+            .child(
+                Table::new()
+                    .small()
+                    .child(
+                        TableHeader::new().child(
+                            TableRow::new()
+                                .child(TableHead::new().w(px(70.)).child(Label::new(enabled)))
+                                .child(TableHead::new().w(px(160.)).child(Label::new(name)))
+                                .child(TableHead::new().child(Label::new(value)))
+                                .child(TableHead::new().w(px(120.)).child(Label::new(path)))
+                                .child(TableHead::new().w(px(140.)).child(Label::new(expires)))
+                                .child(TableHead::new().w(px(140.)).child(Label::new(flags)))
+                                .child(TableHead::new().w(px(140.))),
+                        ),
+                    )
+                    .child(TableBody::new().children(rows)),
+            )
+    }
+
+    fn cookie_row(
+        &self,
+        group: usize,
+        row: usize,
+        cookie: &Cookie,
+        cx: &mut Context<Self>,
+    ) -> TableRow {
+        let id = format!("{group}-{row}");
+        let flags = cookie_flags(cookie, cx);
+        let (expires, edit_label, delete_label) = {
+            let i18n = cx.global::<I18n>();
+            let expires = cookie.expires.map_or_else(
+                || i18n.t("cookies-session"),
+                |expires| format_expires(expires, self.offset),
+            );
+            (
+                expires,
+                i18n.t("button-edit-cookie"),
+                i18n.t("button-delete-cookie"),
+            )
+        };
+        let mono = cx.theme().mono_font_family.clone();
+        let toggled = cookie.key();
+        let edited = cookie.clone();
+        let removed = cookie.key();
+
+        TableRow::new()
+            .child(
+                TableCell::new().w(px(70.)).child(
+                    Checkbox::new(SharedString::from(format!("cookies-enabled-{id}")))
+                        .checked(cookie.enabled)
+                        .on_click(cx.listener(move |this, checked, _, cx| {
+                            this.jar.set_enabled(&toggled, *checked);
+                            cx.notify();
+                        })),
+                ),
+            )
+            .child(
+                TableCell::new()
+                    .w(px(160.))
+                    .child(Label::new(cookie.name.clone()).font_family(mono.clone())),
+            )
+            .child(TableCell::new().child(Label::new(cookie.value.clone()).font_family(mono)))
+            .child(
+                TableCell::new()
+                    .w(px(120.))
+                    .child(Label::new(cookie.path.clone())),
+            )
+            .child(
+                TableCell::new()
+                    .w(px(140.))
+                    .child(Label::new(expires).text_xs()),
+            )
+            .child(
+                TableCell::new()
+                    .w(px(140.))
+                    .child(Label::new(flags).text_xs()),
+            )
+            .child(
+                TableCell::new().w(px(140.)).child(
+                    div()
+                        .flex()
+                        .gap_1()
+                        .child(
+                            Button::new(SharedString::from(format!("cookies-edit-{id}")))
+                                .small()
+                                .label(edit_label)
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.edit(&edited, window, cx);
+                                })),
+                        )
+                        .child(
+                            Button::new(SharedString::from(format!("cookies-delete-{id}")))
+                                .small()
+                                .danger()
+                                .label(delete_label)
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.remove(&removed, window, cx);
+                                })),
+                        ),
+                ),
+            )
+    }
+}
+
+impl Render for CookieManagerView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let editor = self.render_editor(cx);
+        let domains = self.render_domains(cx);
+        v_flex()
+            .size_full()
+            .overflow_hidden()
+            .child(editor)
+            .child(domains)
+    }
+}
+
+fn cookie_flags(cookie: &Cookie, cx: &App) -> String {
+    let i18n = cx.global::<I18n>();
+    let mut flags = Vec::new();
+    if cookie.host_only {
+        flags.push(i18n.t("cookies-host-only"));
+    }
+    if cookie.secure {
+        flags.push(i18n.t("cookies-secure"));
+    }
+    if cookie.http_only {
+        flags.push(i18n.t("cookies-http-only"));
+    }
+    flags.join(", ")
+}
+
+const fn notice_key(error: CookieFormError) -> &'static str {
+    match error {
+        CookieFormError::DomainRequired => "cookies-domain-required",
+        CookieFormError::NameRequired => "cookies-name-required",
+        CookieFormError::InvalidName => "cookies-name-invalid",
+        CookieFormError::InvalidValue => "cookies-value-invalid",
+        CookieFormError::InvalidPath => "cookies-path-invalid",
+        CookieFormError::InvalidExpires => "cookies-expires-invalid",
+    }
+}
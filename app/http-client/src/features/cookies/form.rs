@@ -0,0 +1,221 @@
+use thiserror::Error;
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+use crate::features::request::Cookie;
+
+/// The raw text of the cookie editor.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CookieFields<'a> {
+    /// A leading dot makes a domain cookie; a bare host makes a host-only
+    /// one.
+    pub(crate) domain: &'a str,
+    pub(crate) path: &'a str,
+    pub(crate) name: &'a str,
+    pub(crate) value: &'a str,
+    /// `YYYY-MM-DD HH:MM` in the local offset, or blank for a session cookie.
+    pub(crate) expires: &'a str,
+    pub(crate) secure: bool,
+    pub(crate) http_only: bool,
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub(crate) enum CookieFormError {
+    #[error("cookie domain is required")]
+    DomainRequired,
+    #[error("cookie name is required")]
+    NameRequired,
+    #[error("cookie name contains a separator")]
+    InvalidName,
+    #[error("cookie value contains a separator")]
+    InvalidValue,
+    #[error("cookie path must start with a slash")]
+    InvalidPath,
+    #[error("cookie expiry is not a date")]
+    InvalidExpires,
+}
+
+pub(crate) fn parse_cookie(
+    fields: &CookieFields<'_>,
+    offset: UtcOffset,
+) -> Result<Cookie, CookieFormError> {
+    let domain = fields.domain.trim();
+    let host_only = !domain.starts_with('.');
+    let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+    if domain.is_empty() {
+        return Err(CookieFormError::DomainRequired);
+    }
+    let name = fields.name.trim();
+    if name.is_empty() {
+        return Err(CookieFormError::NameRequired);
+    }
+    if name
+        .chars()
+        .any(|char| matches!(char, '=' | ';' | ',') || char.is_whitespace() || char.is_control())
+    {
+        return Err(CookieFormError::InvalidName);
+    }
+    let value = fields.value.trim();
+    if value.chars().any(|char| char == ';' || char.is_control()) {
+        return Err(CookieFormError::InvalidValue);
+    }
+    let path = match fields.path.trim() {
+        "" => "/",
+        path if path.starts_with('/') => path,
+        _ => return Err(CookieFormError::InvalidPath),
+    };
+    let expires = match fields.expires.trim() {
+        "" => None,
+        raw => Some(parse_expires(raw, offset).ok_or(CookieFormError::InvalidExpires)?),
+    };
+    Ok(Cookie {
+        name: name.to_string(),
+        value: value.to_string(),
+        domain,
+        host_only,
+        path: path.to_string(),
+        expires,
+        secure: fields.secure,
+        http_only: fields.http_only,
+        enabled: true,
+    })
+}
+
+/// The editor text for a cookie's domain, the inverse of [`parse_cookie`].
+pub(crate) fn format_domain(cookie: &Cookie) -> String {
+    if cookie.host_only {
+        cookie.domain.clone()
+    } else {
+        format!(".{}", cookie.domain)
+    }
+}
+
+pub(crate) fn format_expires(expires: i64, offset: UtcOffset) -> String {
+    let Ok(at) = OffsetDateTime::from_unix_timestamp(expires) else {
+        return String::new();
+    };
+    let local = at.to_offset(offset);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        local.year(),
+        u8::from(local.month()),
+        local.day(),
+        local.hour(),
+        local.minute()
+    )
+}
+
+fn parse_expires(raw: &str, offset: UtcOffset) -> Option<i64> {
+    let (date, time) = raw.split_once(' ').unwrap_or((raw, "00:00"));
+    let mut date = date.split('-').map(str::parse::<u32>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    if date.next().is_some() {
+        return None;
+    }
+    let mut time = time.trim().split(':').map(str::parse::<u8>);
+    let (hour, minute) = (time.next()?.ok()?, time.next()?.ok()?);
+    if time.next().is_some() {
+        return None;
+    }
+    let date = Date::from_calendar_date(
+        i32::try_from(year).ok()?,
+        Month::try_from(u8::try_from(month).ok()?).ok()?,
+        u8::try_from(day).ok()?,
+    )
+    .ok()?;
+    let time = Time::from_hms(hour, minute, 0).ok()?;
+    Some(
+        PrimitiveDateTime::new(date, time)
+            .assume_offset(offset)
+            .unix_timestamp(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields<'a>(domain: &'a str, name: &'a str) -> CookieFields<'a> {
+        CookieFields {
+            domain,
+            name,
+            value: "v",
+            ..CookieFields::default()
+        }
+    }
+
+    #[test]
+    fn a_leading_dot_makes_a_domain_cookie_and_blank_fields_take_defaults() {
+        let cookie = parse_cookie(&fields(" .Example.TEST ", "sid"), UtcOffset::UTC).unwrap();
+        assert_eq!(cookie.domain, "example.test");
+        assert!(!cookie.host_only);
+        assert_eq!(cookie.path, "/");
+        assert_eq!(cookie.expires, None);
+        assert!(cookie.enabled);
+        assert_eq!(format_domain(&cookie), ".example.test");
+
+        let cookie = parse_cookie(&fields("example.test", "sid"), UtcOffset::UTC).unwrap();
+        assert!(cookie.host_only);
+        assert_eq!(format_domain(&cookie), "example.test");
+    }
+
+    #[test]
+    fn cookie_fields_reject_separators_and_relative_paths() {
+        assert_eq!(
+            parse_cookie(&fields(".", "sid"), UtcOffset::UTC).unwrap_err(),
+            CookieFormError::DomainRequired
+        );
+        assert_eq!(
+            parse_cookie(&fields("a.test", " "), UtcOffset::UTC).unwrap_err(),
+            CookieFormError::NameRequired
+        );
+        assert_eq!(
+            parse_cookie(&fields("a.test", "a=b"), UtcOffset::UTC).unwrap_err(),
+            CookieFormError::InvalidName
+        );
+        let value = CookieFields {
+            value: "a;b",
+            ..fields("a.test", "sid")
+        };
+        assert_eq!(
+            parse_cookie(&value, UtcOffset::UTC).unwrap_err(),
+            CookieFormError::InvalidValue
+        );
+        let path = CookieFields {
+            path: "docs",
+            ..fields("a.test", "sid")
+        };
+        assert_eq!(
+            parse_cookie(&path, UtcOffset::UTC).unwrap_err(),
+            CookieFormError::InvalidPath
+        );
+    }
+
+    #[test]
+    fn expiry_round_trips_through_the_local_offset() {
+        let offset = UtcOffset::from_hms(8, 0, 0).unwrap();
+        let dated = CookieFields {
+            expires: "2030-01-02 09:30",
+            ..fields("a.test", "sid")
+        };
+        let cookie = parse_cookie(&dated, offset).unwrap();
+        assert_eq!(cookie.expires, Some(1_893_547_800));
+        assert_eq!(format_expires(1_893_547_800, offset), "2030-01-02 09:30");
+
+        for raw in [
+            "2030-13-01",
+            "2030-01-02 25:00",
+            "tomorrow",
+            "2030-01-02 09:30:00",
+        ] {
+            let invalid = CookieFields {
+                expires: raw,
+                ..fields("a.test", "sid")
+            };
+            assert_eq!(
+                parse_cookie(&invalid, offset).unwrap_err(),
+                CookieFormError::InvalidExpires,
+                "{raw}"
+            );
+        }
+    }
+}
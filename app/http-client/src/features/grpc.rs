@@ -0,0 +1,926 @@
+use std::{path::PathBuf, time::Duration};
+
+use fluent_bundle::FluentArgs;
+use gpui::{
+    App, AppContext as _, Context, Entity, IntoElement, ParentElement as _, PathPromptOptions,
+    Render, SharedString, Styled as _, Subscription, Task, Window, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    input::{Input, InputState},
+    label::Label,
+    resizable::{h_resizable, resizable_panel},
+    scroll::ScrollableElement as _,
+    select::{Select, SelectEvent, SelectItem, SelectState},
+    table::{Table, TableBody, TableCell, TableHead, TableHeader, TableRow},
+    v_flex,
+};
+use gpui_form_gpui_component::{IntegerInput, IntegerInputState};
+use http::HeaderMap;
+use prost_reflect::{DescriptorPool, MethodDescriptor, ServiceDescriptor};
+use serde_json::Value;
+
+pub(crate) use self::schema::LoadedDescriptors;
+use self::{
+    call::{CallEvent, CallProblem, CallRequest, event_channel, grpc_client, run_call},
+    codec::{FrameError, GrpcStatus, MessageError, encode_json},
+    reflection::{ReflectionError, reflect},
+    schema::{MethodKind, SchemaError, compile_proto_files, request_template, services},
+    target::{TargetError, parse_metadata, parse_target},
+};
+use crate::foundation::I18n;
+
+mod call;
+mod codec;
+mod reflection;
+mod schema;
+mod target;
+
+/// Later messages of a long server stream are counted but not shown.
+const MAX_SHOWN_MESSAGES: usize = 500;
+
+/// Where the services on offer came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SchemaSource {
+    Files(usize),
+    Reflection(String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum CallState {
+    #[default]
+    Idle,
+    Calling,
+    Finished(GrpcStatus),
+}
+
+/// The last thing that went wrong, shown under the target line until the
+/// next attempt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum GrpcNotice {
+    Target(TargetError),
+    Schema(SchemaError),
+    Reflection(ReflectionError),
+    Message(MessageError),
+    Call(CallProblem),
+}
+
+#[derive(Clone)]
+struct ServiceOption {
+    value: SharedString,
+}
+
+impl SelectItem for ServiceOption {
+    type Value = SharedString;
+
+    fn title(&self) -> SharedString {
+        self.value.clone()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.value
+    }
+}
+
+type ServiceOptions = Vec<ServiceOption>;
+
+#[derive(Clone)]
+struct MethodOption {
+    value: SharedString,
+    title: SharedString,
+}
+
+impl SelectItem for MethodOption {
+    type Value = SharedString;
+
+    fn title(&self) -> SharedString {
+        self.title.clone()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.value
+    }
+}
+
+type MethodOptions = Vec<MethodOption>;
+
+pub(crate) struct GrpcView {
+    target: Entity<InputState>,
+    metadata: Entity<InputState>,
+    deadline_ms: Entity<IntegerInputState<u64>>,
+    composer: Entity<InputState>,
+    output: Entity<InputState>,
+    service_state: Entity<SelectState<ServiceOptions>>,
+    method_state: Entity<SelectState<MethodOptions>>,
+    services: Vec<ServiceDescriptor>,
+    service: Option<ServiceDescriptor>,
+    method: Option<MethodDescriptor>,
+    schema: Option<SchemaSource>,
+    loading_schema: bool,
+    schema_task: Option<Task<()>>,
+    call: CallState,
+    call_task: Option<Task<()>>,
+    response_metadata: HeaderMap,
+    messages: Vec<Value>,
+    message_count: usize,
+    notice: Option<GrpcNotice>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl GrpcView {
+    pub(crate) fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let (target_placeholder, metadata_placeholder) = {
+            let i18n = cx.global::<I18n>();
+            (
+                i18n.t("grpc-target-placeholder"),
+                i18n.t("grpc-metadata-placeholder"),
+            )
+        };
+        let target = cx.new(|cx| InputState::new(window, cx).placeholder(target_placeholder));
+        let metadata = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder(metadata_placeholder)
+        });
+        let deadline_ms = cx.new(|cx| IntegerInputState::new(window, cx).min(0));
+        let composer = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .code_editor("json")
+                .line_number(true)
+        });
+        let output = cx.new(|cx| {
+            InputState::new(window, cx)
+                .code_editor("json")
+                .line_number(true)
+                .searchable(true)
+                .replaceable(false)
+                .soft_wrap(false)
+                .scroll_beyond_last_line(Some(0))
+        });
+        let service_state = cx.new(|cx| SelectState::new(ServiceOptions::new(), None, window, cx));
+        let method_state = cx.new(|cx| SelectState::new(MethodOptions::new(), None, window, cx));
+
+        let subscriptions = vec![
+            cx.subscribe_in(
+                &service_state,
+                window,
+                |this, _, event: &SelectEvent<ServiceOptions>, window, cx| {
+                    let SelectEvent::Confirm(Some(service)) = event else {
+                        return;
+                    };
+                    this.select_service(service, window, cx);
+                },
+            ),
+            cx.subscribe_in(
+                &method_state,
+                window,
+                |this, _, event: &SelectEvent<MethodOptions>, window, cx| {
+                    let SelectEvent::Confirm(Some(method)) = event else {
+                        return;
+                    };
+                    this.select_method(method, window, cx);
+                },
+            ),
+        ];
+
+        Self {
+            target,
+            metadata,
+            deadline_ms,
+            composer,
+            output,
+            service_state,
+            method_state,
+            services: Vec::new(),
+            service: None,
+            method: None,
+            schema: None,
+            loading_schema: false,
+            schema_task: None,
+            call: CallState::Idle,
+            call_task: None,
+            response_metadata: HeaderMap::new(),
+            messages: Vec::new(),
+            message_count: 0,
+            notice: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn choose_proto_files(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let prompt = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: true,
+            prompt: None,
+        });
+        let owner = cx.entity().downgrade();
+        self.schema_task = Some(window.spawn(cx, async move |cx| {
+            let Ok(Ok(Some(paths))) = prompt.await else {
+                return;
+            };
+            if paths.is_empty() {
+                return;
+            }
+            let Ok(compile) = owner.update_in(cx, |this, _, cx| {
+                this.loading_schema = true;
+                this.notice = None;
+                cx.notify();
+                cx.background_spawn(load_proto_files(paths))
+            }) else {
+                return;
+            };
+            let (source, result) = compile.await;
+            let _ = owner.update_in(cx, |this, window, cx| {
+                this.finish_schema(source, result.map_err(GrpcNotice::Schema), window, cx);
+            });
+        }));
+    }
+
+    fn reflect_services(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let request = parse_target(&self.target.read(cx).value())
+            .and_then(|target| Ok((target, parse_metadata(&self.metadata.read(cx).value())?)));
+        let (target, metadata) = match request {
+            Ok(request) => request,
+            Err(error) => {
+                self.notice = Some(GrpcNotice::Target(error));
+                cx.notify();
+                return;
+            }
+        };
+        let client = match grpc_client() {
+            Ok(client) => client,
+            Err(problem) => {
+                self.notice = Some(GrpcNotice::Call(problem));
+                cx.notify();
+                return;
+            }
+        };
+        let source = SchemaSource::Reflection(target.host_str().unwrap_or_default().to_owned());
+        let reflection =
+            gpui_tokio::Tokio::spawn(
+                cx,
+                async move { reflect(&client, &target, &metadata).await },
+            );
+        let owner = cx.entity().downgrade();
+        self.schema_task = Some(window.spawn(cx, async move |cx| {
+            let result = match reflection.await {
+                Ok(result) => result.map_err(GrpcNotice::Reflection),
+                Err(_) => Err(GrpcNotice::Call(CallProblem::Connection)),
+            };
+            let _ = owner.update_in(cx, |this, window, cx| {
+                this.finish_schema(source, result, window, cx);
+            });
+        }));
+        self.loading_schema = true;
+        self.notice = None;
+        cx.notify();
+    }
+
+    /// Offers the services of a newly loaded pool and shares it with the
+    /// HTTP response viewer. A failed load keeps the previous services.
+    fn finish_schema(
+        &mut self,
+        source: SchemaSource,
+        result: Result<DescriptorPool, GrpcNotice>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.schema_task = None;
+        self.loading_schema = false;
+        let pool = match result {
+            Ok(pool) => pool,
+            Err(notice) => {
+                self.notice = Some(notice);
+                cx.notify();
+                return;
+            }
+        };
+        self.services = services(&pool);
+        cx.set_global(LoadedDescriptors(pool));
+        self.schema = Some(source);
+        self.service = None;
+        self.method = None;
+        let items = self
+            .services
+            .iter()
+            .map(|service| ServiceOption {
+                value: service.full_name().to_owned().into(),
+            })
+            .collect();
+        self.service_state.update(cx, |state, cx| {
+            state.set_items(items, window, cx);
+            state.set_selected_index(None, window, cx);
+        });
+        self.method_state.update(cx, |state, cx| {
+            state.set_items(MethodOptions::new(), window, cx);
+            state.set_selected_index(None, window, cx);
+        });
+        if let Some(first) = self
+            .services
+            .first()
+            .map(|service| service.full_name().to_owned())
+        {
+            let first = SharedString::from(first);
+            self.service_state.update(cx, |state, cx| {
+                state.set_selected_value(&first, window, cx);
+            });
+            self.select_service(&first, window, cx);
+        }
+        cx.notify();
+    }
+
+    fn select_service(&mut self, name: &SharedString, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(service) = self
+            .services
+            .iter()
+            .find(|service| service.full_name() == name.as_ref())
+            .cloned()
+        else {
+            return;
+        };
+        let methods: Vec<MethodDescriptor> = service.methods().collect();
+        let items = {
+            let i18n = cx.global::<I18n>();
+            methods
+                .iter()
+                .map(|method| {
+                    let mut args = FluentArgs::new();
+                    args.set("method", method.name().to_owned());
+                    MethodOption {
+                        value: method.name().to_owned().into(),
+                        title: i18n
+                            .t_with_args(method_kind_key(MethodKind::of(method)), &args)
+                            .into(),
+                    }
+                })
+                .collect()
+        };
+        self.method_state.update(cx, |state, cx| {
+            state.set_items(items, window, cx);
+            state.set_selected_index(None, window, cx);
+        });
+        self.service = Some(service);
+        self.method = None;
+        if let Some(first) = methods.first() {
+            let first = SharedString::from(first.name().to_owned());
+            self.method_state.update(cx, |state, cx| {
+                state.set_selected_value(&first, window, cx);
+            });
+            self.select_method(&first, window, cx);
+        }
+        cx.notify();
+    }
+
+    /// Switching methods replaces the request with a template of the new
+    /// input type.
+    fn select_method(&mut self, name: &SharedString, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(method) = self.service.as_ref().and_then(|service| {
+            service
+                .methods()
+                .find(|method| method.name() == name.as_ref())
+        }) else {
+            return;
+        };
+        if self.method.as_ref() == Some(&method) {
+            return;
+        }
+        let template = request_template(&method);
+        self.composer
+            .update(cx, |state, cx| state.set_value(template, window, cx));
+        self.method = Some(method);
+        cx.notify();
+    }
+
+    fn send(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.call == CallState::Calling {
+            return;
+        }
+        let Some(method) = self
+            .method
+            .clone()
+            .filter(|method| MethodKind::of(method).is_supported())
+        else {
+            return;
+        };
+        let target = parse_target(&self.target.read(cx).value());
+        let metadata = parse_metadata(&self.metadata.read(cx).value());
+        let (target, metadata) = match (target, metadata) {
+            (Ok(target), Ok(metadata)) => (target, metadata),
+            (Err(error), _) | (_, Err(error)) => {
+                self.notice = Some(GrpcNotice::Target(error));
+                cx.notify();
+                return;
+            }
+        };
+        let message = match encode_json(method.input(), &self.composer.read(cx).value()) {
+            Ok(message) => message,
+            Err(error) => {
+                self.notice = Some(GrpcNotice::Message(error));
+                cx.notify();
+                return;
+            }
+        };
+        let client = match grpc_client() {
+            Ok(client) => client,
+            Err(problem) => {
+                self.notice = Some(GrpcNotice::Call(problem));
+                cx.notify();
+                return;
+            }
+        };
+        let deadline_ms = self.deadline_ms.read(cx).value();
+        let request = CallRequest {
+            target,
+            method,
+            metadata,
+            message,
+            deadline: (deadline_ms > 0).then(|| Duration::from_millis(deadline_ms)),
+        };
+
+        let owner = cx.entity().downgrade();
+        self.call_task = Some(window.spawn(cx, async move |cx| {
+            let (sender, events) = event_channel();
+            let worker = gpui_tokio::Tokio::spawn(cx, run_call(client, request, sender));
+            let mut finished = false;
+            while let Ok(event) = events.recv().await {
+                finished = matches!(event, CallEvent::Finished(_));
+                if owner
+                    .update_in(cx, |this, window, cx| {
+                        this.handle_call_event(event, window, cx)
+                    })
+                    .is_err()
+                {
+                    return;
+                }
+                if finished {
+                    break;
+                }
+            }
+            let worker_result = worker.await;
+            if !finished {
+                tracing::debug!(
+                    operation = "grpc-call",
+                    worker_join_failed = worker_result.is_err(),
+                    "call worker ended without a terminal event"
+                );
+                let _ = owner.update_in(cx, |this, window, cx| {
+                    this.handle_call_event(
+                        CallEvent::Finished(Err(CallProblem::Connection)),
+                        window,
+                        cx,
+                    );
+                });
+            }
+        }));
+        self.call = CallState::Calling;
+        self.notice = None;
+        self.response_metadata.clear();
+        self.messages.clear();
+        self.message_count = 0;
+        self.output
+            .update(cx, |state, cx| state.set_value("", window, cx));
+        cx.notify();
+    }
+
+    fn cancel(&mut self, cx: &mut Context<Self>) {
+        if self.call_task.take().is_some() {
+            self.call = CallState::Idle;
+            cx.notify();
+        }
+    }
+
+    fn handle_call_event(&mut self, event: CallEvent, window: &mut Window, cx: &mut Context<Self>) {
+        match event {
+            CallEvent::Headers(headers) => self.response_metadata = headers,
+            CallEvent::Message(message) => {
+                self.message_count += 1;
+                if self.messages.len() < MAX_SHOWN_MESSAGES {
+                    self.messages.push(message);
+                    let source = format_messages(&self.messages);
+                    self.output
+                        .update(cx, |state, cx| state.set_value(source, window, cx));
+                }
+            }
+            CallEvent::Finished(result) => {
+                self.call_task = None;
+                match result {
+                    Ok(status) => self.call = CallState::Finished(status),
+                    Err(problem) => {
+                        self.call = CallState::Idle;
+                        self.notice = Some(GrpcNotice::Call(problem));
+                    }
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    fn render_target(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let loading = self.loading_schema;
+        let (load_label, reflect_label, deadline_label) = {
+            let i18n = cx.global::<I18n>();
+            (
+                i18n.t("button-load-protos"),
+                i18n.t("button-reflect"),
+                i18n.t("grpc-deadline-ms"),
+            )
+        };
+        let schema_status = schema_status(self.schema.as_ref(), self.loading_schema, cx);
+        let notice = self
+            .notice
+            .as_ref()
+            .map(|notice| notice_message(notice, cx));
+
+        v_flex()
+            .p_2()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(div().flex_1().child(Input::new(&self.target)))
+                    .child(Label::new(deadline_label).text_sm())
+                    .child(
+                        div()
+                            .w(px(120.))
+                            .child(IntegerInput::new(&self.deadline_ms)),
+                    )
+                    .child(
+                        Button::new("grpc-load-protos")
+                            .label(load_label)
+                            .disabled(loading)
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.choose_proto_files(window, cx)
+                            })),
+                    )
+                    .child(
+                        Button::new("grpc-reflect")
+                            .label(reflect_label)
+                            .disabled(loading)
+                            .on_click(
+                                cx.listener(|this, _, window, cx| {
+                                    this.reflect_services(window, cx)
+                                }),
+                            ),
+                    ),
+            )
+            .child(div().h(px(72.)).child(Input::new(&self.metadata).h_full()))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        Label::new(schema_status)
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .when_some(notice, |this, notice| {
+                        this.child(Label::new(notice).text_sm().text_color(cx.theme().danger))
+                    }),
+            )
+    }
+
+    fn render_request(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let kind = self.method.as_ref().map(MethodKind::of);
+        let unsupported = kind.is_some_and(|kind| !kind.is_supported());
+        let (send_label, cancel_label, unsupported_label, service_placeholder, method_placeholder) = {
+            let i18n = cx.global::<I18n>();
+            (
+                i18n.t("button-send"),
+                i18n.t("button-cancel"),
+                i18n.t("grpc-method-unsupported"),
+                i18n.t("grpc-service-placeholder"),
+                i18n.t("grpc-method-placeholder"),
+            )
+        };
+        let action = if self.call == CallState::Calling {
+            Button::new("grpc-cancel")
+                .danger()
+                .label(cancel_label)
+                .on_click(cx.listener(|this, _, _, cx| this.cancel(cx)))
+        } else {
+            Button::new("grpc-send")
+                .primary()
+                .label(send_label)
+                .disabled(!kind.is_some_and(MethodKind::is_supported))
+                .on_click(cx.listener(|this, _, window, cx| this.send(window, cx)))
+        };
+        v_flex()
+            .size_full()
+            .min_h(px(0.))
+            .p_2()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div().flex_1().child(
+                            Select::new(&self.service_state).placeholder(service_placeholder),
+                        ),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .child(Select::new(&self.method_state).placeholder(method_placeholder)),
+                    )
+                    .child(action),
+            )
+            .when(unsupported, |this| {
+                this.child(
+                    Label::new(unsupported_label)
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground),
+                )
+            })
+            .child(
+                div().flex_1().min_h(px(0.)).overflow_hidden().child(
+                    Input::new(&self.composer)
+                        .size_full()
+                        .font_family(cx.theme().mono_font_family.clone()),
+                ),
+            )
+    }
+
+    fn render_response(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let cx: &App = cx;
+        let i18n = cx.global::<I18n>();
+        let status = match &self.call {
+            CallState::Idle => None,
+            CallState::Calling => Some((i18n.t("grpc-call-running"), cx.theme().muted_foreground)),
+            CallState::Finished(status) => {
+                let mut args = FluentArgs::new();
+                args.set("name", status.code_name());
+                args.set("code", status.code);
+                let text = match &status.message {
+                    Some(message) => {
+                        args.set("message", message.clone());
+                        i18n.t_with_args("grpc-call-status-message", &args)
+                    }
+                    None => i18n.t_with_args("grpc-call-status", &args),
+                };
+                let color = if status.is_ok() {
+                    cx.theme().success
+                } else {
+                    cx.theme().danger
+                };
+                Some((text, color))
+            }
+        };
+        let count = (self.call != CallState::Idle).then(|| {
+            let mut args = FluentArgs::new();
+            args.set("count", self.message_count);
+            i18n.t_with_args("grpc-call-messages", &args)
+        });
+        let hidden = self.message_count.saturating_sub(self.messages.len());
+        let hidden = (hidden > 0).then(|| {
+            let mut args = FluentArgs::new();
+            args.set("count", hidden);
+            i18n.t_with_args("grpc-call-messages-hidden", &args)
+        });
+        let metadata_rows = self
+            .response_metadata
+            .iter()
+            .map(|(name, value)| {
+                TableRow::new()
+                    .child(
+                        TableCell::new()
+                            .w(px(200.))
+                            .child(Label::new(name.as_str().to_owned())),
+                    )
+                    .child(TableCell::new().child(Label::new(
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )))
+            })
+            .collect::<Vec<_>>();
+
+        let body = if self.call == CallState::Idle && self.messages.is_empty() {
+            div()
+                .flex_1()
+                .min_h(px(0.))
+                .flex()
+                .items_center()
+                .justify_center()
+                .child(Label::new(i18n.t("grpc-response-empty")))
+                .into_any_element()
+        } else {
+            div()
+                .flex_1()
+                .min_h(px(0.))
+                .overflow_hidden()
+                .bg(cx.theme().input_background())
+                .child(
+                    Input::new(&self.output)
+                        .disabled(true)
+                        .appearance(false)
+                        .size_full()
+                        .font_family(cx.theme().mono_font_family.clone()),
+                )
+                .into_any_element()
+        };
+
+        v_flex()
+            .size_full()
+            .min_h(px(0.))
+            .p_2()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .when_some(status, |this, (status, color)| {
+                        this.child(Label::new(status).text_sm().text_color(color))
+                    })
+                    .when_some(count, |this, count| {
+                        this.child(
+                            Label::new(count)
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    })
+                    .when_some(hidden, |this, hidden| {
+                        this.child(
+                            Label::new(hidden)
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground),
+                        )
+                    }),
+            )
+            .when(!metadata_rows.is_empty(), |this| {
+                this.child(
+                    div().max_h(px(160.)).overflow_scrollbar().child(
+                        Table::new()
+                            .small()
+                            .child(
+                                TableHeader::new().child(
+                                    TableRow::new()
+                                        .child(
+                                            TableHead::new()
+                                                .w(px(200.))
+                                                .child(Label::new(i18n.t("grpc-metadata-name"))),
+                                        )
+                                        .child(
+                                            TableHead::new()
+                                                .child(Label::new(i18n.t("grpc-metadata-value"))),
+                                        ),
+                                ),
+                            )
+                            .child(TableBody::new().children(metadata_rows)),
+                    ),
+                )
+            })
+            .child(body)
+    }
+}
+
+impl Render for GrpcView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let target = self.render_target(cx);
+        let request = self.render_request(cx);
+        let response = self.render_response(cx);
+        v_flex().size_full().overflow_hidden().child(target).child(
+            h_resizable("grpc-request-response")
+                .child(
+                    resizable_panel()
+                        .size(px(480.))
+                        .size_range(px(280.)..px(960.))
+                        .child(request),
+                )
+                .child(resizable_panel().child(response)),
+        )
+    }
+}
+
+/// Compiles on a background thread; the source is returned alongside so
+/// the view can describe what was loaded.
+async fn load_proto_files(
+    paths: Vec<PathBuf>,
+) -> (SchemaSource, Result<DescriptorPool, SchemaError>) {
+    (
+        SchemaSource::Files(paths.len()),
+        compile_proto_files(&paths),
+    )
+}
+
+fn format_messages(messages: &[Value]) -> String {
+    messages
+        .iter()
+        .map(|message| serde_json::to_string_pretty(message).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn schema_status(schema: Option<&SchemaSource>, loading: bool, cx: &App) -> String {
+    let i18n = cx.global::<I18n>();
+    let mut args = FluentArgs::new();
+    let key = match schema {
+        _ if loading => "grpc-schema-loading",
+        None => "grpc-schema-empty",
+        Some(SchemaSource::Files(count)) => {
+            args.set("count", *count);
+            "grpc-schema-files"
+        }
+        Some(SchemaSource::Reflection(host)) => {
+            args.set("host", host.clone());
+            "grpc-schema-reflection"
+        }
+    };
+    i18n.t_with_args(key, &args)
+}
+
+fn notice_message(notice: &GrpcNotice, cx: &App) -> String {
+    let i18n = cx.global::<I18n>();
+    let mut args = FluentArgs::new();
+    let key = match notice {
+        GrpcNotice::Target(TargetError::AddressRequired) => "grpc-target-required",
+        GrpcNotice::Target(TargetError::InvalidAddress) => "grpc-target-invalid",
+        GrpcNotice::Target(TargetError::UnsupportedScheme) => "grpc-target-scheme",
+        GrpcNotice::Target(TargetError::InvalidMetadata { line }) => {
+            args.set("line", *line);
+            "grpc-metadata-invalid"
+        }
+        GrpcNotice::Target(TargetError::ReservedMetadata { line }) => {
+            args.set("line", *line);
+            "grpc-metadata-reserved"
+        }
+        GrpcNotice::Schema(SchemaError::Compile(detail)) => {
+            args.set("detail", detail.clone());
+            "grpc-schema-compile"
+        }
+        GrpcNotice::Schema(SchemaError::Invalid(detail))
+        | GrpcNotice::Reflection(ReflectionError::InvalidDescriptor(detail)) => {
+            args.set("detail", detail.clone());
+            "grpc-schema-invalid"
+        }
+        GrpcNotice::Schema(SchemaError::NoServices)
+        | GrpcNotice::Reflection(ReflectionError::NoServices) => "grpc-schema-no-services",
+        GrpcNotice::Reflection(ReflectionError::Unsupported) => "grpc-reflection-unsupported",
+        GrpcNotice::Reflection(ReflectionError::Status(code)) => {
+            args.set("code", *code);
+            "grpc-reflection-status"
+        }
+        GrpcNotice::Reflection(ReflectionError::Server { message, .. }) => {
+            args.set("message", message.clone());
+            "grpc-reflection-server"
+        }
+        GrpcNotice::Reflection(ReflectionError::InvalidResponse) => "grpc-reflection-invalid",
+        GrpcNotice::Reflection(ReflectionError::Call(problem)) | GrpcNotice::Call(problem) => {
+            match problem {
+                CallProblem::Connect => "grpc-problem-connect",
+                CallProblem::Deadline => "grpc-problem-deadline",
+                CallProblem::HttpStatus(status) => {
+                    args.set("status", *status);
+                    "grpc-problem-http-status"
+                }
+                CallProblem::NotGrpc => "grpc-problem-not-grpc",
+                CallProblem::Frame(FrameError::Compressed) => "grpc-problem-compressed",
+                CallProblem::Frame(FrameError::TooLarge) => "grpc-problem-too-large",
+                CallProblem::Frame(FrameError::Truncated) => "grpc-problem-truncated",
+                CallProblem::Message(error) => message_error_key(*error, &mut args),
+                CallProblem::Connection => "grpc-problem-connection",
+                CallProblem::MissingStatus => "grpc-problem-missing-status",
+            }
+        }
+        GrpcNotice::Message(error) => message_error_key(*error, &mut args),
+    };
+    i18n.t_with_args(key, &args)
+}
+
+fn message_error_key(error: MessageError, args: &mut FluentArgs<'_>) -> &'static str {
+    match error {
+        MessageError::InvalidJson { line, column } => {
+            args.set("line", line);
+            args.set("column", column);
+            "grpc-message-json-invalid"
+        }
+        MessageError::Undecodable => "grpc-message-undecodable",
+    }
+}
+
+const fn method_kind_key(kind: MethodKind) -> &'static str {
+    match kind {
+        MethodKind::Unary => "grpc-method-unary",
+        MethodKind::ServerStreaming => "grpc-method-server-streaming",
+        MethodKind::ClientStreaming => "grpc-method-client-streaming",
+        MethodKind::Bidirectional => "grpc-method-bidirectional",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streamed_messages_are_listed_one_after_another() {
+        let messages = [serde_json::json!({"n": 1}), serde_json::json!({})];
+        assert_eq!(format_messages(&messages), "{\n  \"n\": 1\n}\n{}");
+    }
+}
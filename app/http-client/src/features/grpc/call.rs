@@ -0,0 +1,238 @@
+use std::{future::poll_fn, pin::Pin, time::Duration};
+
+use async_channel::{Receiver, Sender};
+use bytes::Bytes;
+use http::{
+    HeaderMap, HeaderValue, StatusCode,
+    header::{CONTENT_TYPE, TE},
+};
+use http_body::Body as HttpBody;
+use prost_reflect::MethodDescriptor;
+use serde_json::Value;
+use thiserror::Error;
+use url::Url;
+
+use super::{
+    codec::{FrameDecoder, FrameError, GrpcStatus, MessageError, decode_json, encode_frame},
+    target::method_url,
+};
+
+const CALL_EVENT_CAPACITY: usize = 64;
+
+/// A validated call, frozen when Send is pressed.
+pub(crate) struct CallRequest {
+    pub(crate) target: Url,
+    pub(crate) method: MethodDescriptor,
+    pub(crate) metadata: HeaderMap,
+    /// The encoded request message, without its length prefix.
+    pub(crate) message: Vec<u8>,
+    pub(crate) deadline: Option<Duration>,
+}
+
+pub(crate) enum CallEvent {
+    /// The server accepted the call and sent its response metadata.
+    Headers(HeaderMap),
+    Message(Value),
+    Finished(Result<GrpcStatus, CallProblem>),
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub(crate) enum CallProblem {
+    #[error("the server could not be reached")]
+    Connect,
+    #[error("the call exceeded its deadline")]
+    Deadline,
+    #[error("the server answered with HTTP status {0}")]
+    HttpStatus(u16),
+    #[error("the response is not a gRPC response")]
+    NotGrpc,
+    #[error(transparent)]
+    Frame(#[from] FrameError),
+    #[error(transparent)]
+    Message(#[from] MessageError),
+    #[error("the connection failed during the call")]
+    Connection,
+    #[error("the call ended without a status")]
+    MissingStatus,
+}
+
+pub(crate) fn event_channel() -> (Sender<CallEvent>, Receiver<CallEvent>) {
+    async_channel::bounded(CALL_EVENT_CAPACITY)
+}
+
+/// gRPC needs HTTP/2 end to end, so the client never falls back to
+/// HTTP/1.1 and never follows redirects.
+pub(crate) fn grpc_client() -> Result<reqwest::Client, CallProblem> {
+    reqwest::Client::builder()
+        .tls_backend_rustls()
+        .http2_prior_knowledge()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|_| CallProblem::Connect)
+}
+
+/// The response messages of an open call, followed by its status.
+pub(crate) struct ResponseStream {
+    headers: HeaderMap,
+    body: reqwest::Body,
+    decoder: FrameDecoder,
+    trailers: Option<HeaderMap>,
+    ended: bool,
+}
+
+impl ResponseStream {
+    pub(crate) fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The next response message, or `None` once the body has ended.
+    pub(crate) async fn next_message(&mut self) -> Result<Option<Bytes>, CallProblem> {
+        loop {
+            if let Some(message) = self.decoder.next_message()? {
+                return Ok(Some(message));
+            }
+            if self.ended {
+                self.decoder.finish()?;
+                return Ok(None);
+            }
+            match poll_fn(|cx| Pin::new(&mut self.body).poll_frame(cx)).await {
+                None => self.ended = true,
+                Some(Err(_)) => return Err(CallProblem::Connection),
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) => self.decoder.push(&data),
+                    Err(frame) => {
+                        if let Ok(trailers) = frame.into_trailers() {
+                            self.trailers = Some(trailers);
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// Reads the status once every message has been taken. A
+    /// trailers-only response carries it in the headers.
+    pub(crate) fn status(&self) -> Result<GrpcStatus, CallProblem> {
+        self.trailers
+            .as_ref()
+            .and_then(GrpcStatus::from_headers)
+            .or_else(|| GrpcStatus::from_headers(&self.headers))
+            .ok_or(CallProblem::MissingStatus)
+    }
+}
+
+/// Sends one framed request message and waits for the response headers.
+pub(crate) async fn open_call(
+    client: &reqwest::Client,
+    url: Url,
+    metadata: &HeaderMap,
+    message: &[u8],
+    deadline: Option<Duration>,
+) -> Result<ResponseStream, CallProblem> {
+    let mut request = client
+        .post(url)
+        .headers(metadata.clone())
+        .header(CONTENT_TYPE, HeaderValue::from_static("application/grpc"))
+        .header(TE, HeaderValue::from_static("trailers"))
+        .body(encode_frame(message));
+    if let Some(deadline) = deadline {
+        request = request.header("grpc-timeout", encode_timeout(deadline));
+    }
+    let response = request.send().await.map_err(|_| CallProblem::Connect)?;
+    let response = http::Response::<reqwest::Body>::from(response);
+    let (parts, body) = response.into_parts();
+    // A trailers-only error can arrive with any status and content type;
+    // only a body that carries messages has to be gRPC.
+    if !parts.headers.contains_key("grpc-status") {
+        if parts.status != StatusCode::OK {
+            return Err(CallProblem::HttpStatus(parts.status.as_u16()));
+        }
+        let is_grpc = parts
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/grpc"));
+        if !is_grpc {
+            return Err(CallProblem::NotGrpc);
+        }
+    }
+    Ok(ResponseStream {
+        headers: parts.headers,
+        body,
+        decoder: FrameDecoder::default(),
+        trailers: None,
+        ended: false,
+    })
+}
+
+/// Runs one call to completion, then emits exactly one `Finished` event
+/// unless its receiver has been dropped.
+pub(crate) async fn run_call(
+    client: reqwest::Client,
+    request: CallRequest,
+    events: Sender<CallEvent>,
+) {
+    let result = match request.deadline {
+        Some(deadline) => tokio::time::timeout(deadline, call(&client, request, &events))
+            .await
+            .unwrap_or(Err(CallProblem::Deadline)),
+        None => call(&client, request, &events).await,
+    };
+    let _ = events.send(CallEvent::Finished(result)).await;
+}
+
+async fn call(
+    client: &reqwest::Client,
+    request: CallRequest,
+    events: &Sender<CallEvent>,
+) -> Result<GrpcStatus, CallProblem> {
+    let service = request.method.parent_service();
+    let url = method_url(&request.target, service.full_name(), request.method.name());
+    let mut stream = open_call(
+        client,
+        url,
+        &request.metadata,
+        &request.message,
+        request.deadline,
+    )
+    .await?;
+    if events
+        .send(CallEvent::Headers(stream.headers().clone()))
+        .await
+        .is_err()
+    {
+        return stream.status();
+    }
+    let output = request.method.output();
+    while let Some(message) = stream.next_message().await? {
+        let message = decode_json(output.clone(), &message)?;
+        if events.send(CallEvent::Message(message)).await.is_err() {
+            break;
+        }
+    }
+    stream.status()
+}
+
+/// `grpc-timeout` allows at most eight digits, so long deadlines are sent
+/// in coarser units.
+fn encode_timeout(deadline: Duration) -> String {
+    const MAX_DIGITS: u64 = 99_999_999;
+    let millis = deadline.as_millis().max(1);
+    if millis <= u128::from(MAX_DIGITS) {
+        format!("{millis}m")
+    } else {
+        format!("{}S", deadline.as_secs().min(MAX_DIGITS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeouts_fit_the_header_grammar() {
+        assert_eq!(encode_timeout(Duration::from_micros(10)), "1m");
+        assert_eq!(encode_timeout(Duration::from_secs(5)), "5000m");
+        assert_eq!(encode_timeout(Duration::from_secs(200_000)), "200000S");
+    }
+}
@@ -0,0 +1,307 @@
+use bytes::{Buf as _, BufMut as _, Bytes, BytesMut};
+use http::HeaderMap;
+use prost::Message as _;
+use prost_reflect::{DynamicMessage, MessageDescriptor};
+use serde_json::Value;
+use thiserror::Error;
+
+/// The default receive limit of the reference gRPC implementations.
+pub(crate) const MAX_MESSAGE_BYTES: usize = 4 * 1024 * 1024;
+
+const FRAME_HEADER_BYTES: usize = 5;
+
+/// Names of the canonical status codes, indexed by code.
+const STATUS_NAMES: [&str; 17] = [
+    "OK",
+    "CANCELLED",
+    "UNKNOWN",
+    "INVALID_ARGUMENT",
+    "DEADLINE_EXCEEDED",
+    "NOT_FOUND",
+    "ALREADY_EXISTS",
+    "PERMISSION_DENIED",
+    "RESOURCE_EXHAUSTED",
+    "FAILED_PRECONDITION",
+    "ABORTED",
+    "OUT_OF_RANGE",
+    "UNIMPLEMENTED",
+    "INTERNAL",
+    "UNAVAILABLE",
+    "DATA_LOSS",
+    "UNAUTHENTICATED",
+];
+
+pub(crate) const UNIMPLEMENTED: u32 = 12;
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub(crate) enum FrameError {
+    #[error("compressed messages are not supported")]
+    Compressed,
+    #[error("message exceeds {MAX_MESSAGE_BYTES} bytes")]
+    TooLarge,
+    #[error("stream ended inside a message")]
+    Truncated,
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub(crate) enum MessageError {
+    #[error("request message is not valid JSON for its type at line {line}, column {column}")]
+    InvalidJson { line: usize, column: usize },
+    #[error("response message does not match the method's output type")]
+    Undecodable,
+}
+
+/// Prefixes an uncompressed message with its gRPC length header.
+pub(crate) fn encode_frame(message: &[u8]) -> Bytes {
+    let mut frame = BytesMut::with_capacity(FRAME_HEADER_BYTES + message.len());
+    frame.put_u8(0);
+    frame.put_u32(u32::try_from(message.len()).unwrap_or(u32::MAX));
+    frame.put_slice(message);
+    frame.freeze()
+}
+
+/// Splits a response body into messages as its chunks arrive.
+#[derive(Debug, Default)]
+pub(crate) struct FrameDecoder {
+    buffer: BytesMut,
+}
+
+impl FrameDecoder {
+    pub(crate) fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// The next complete message, or `None` until more bytes arrive.
+    pub(crate) fn next_message(&mut self) -> Result<Option<Bytes>, FrameError> {
+        let Some(header) = self.buffer.get(..FRAME_HEADER_BYTES) else {
+            return Ok(None);
+        };
+        if header[0] != 0 {
+            return Err(FrameError::Compressed);
+        }
+        let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        if length > MAX_MESSAGE_BYTES {
+            return Err(FrameError::TooLarge);
+        }
+        if self.buffer.len() < FRAME_HEADER_BYTES + length {
+            return Ok(None);
+        }
+        self.buffer.advance(FRAME_HEADER_BYTES);
+        Ok(Some(self.buffer.split_to(length).freeze()))
+    }
+
+    /// Fails when the body ended part way through a message.
+    pub(crate) fn finish(&self) -> Result<(), FrameError> {
+        if self.buffer.is_empty() {
+            Ok(())
+        } else {
+            Err(FrameError::Truncated)
+        }
+    }
+}
+
+/// Encodes protobuf JSON as `descriptor`. A blank editor sends the empty
+/// message.
+pub(crate) fn encode_json(
+    descriptor: MessageDescriptor,
+    source: &str,
+) -> Result<Vec<u8>, MessageError> {
+    let source = if source.trim().is_empty() {
+        "{}"
+    } else {
+        source
+    };
+    let invalid = |error: serde_json::Error| MessageError::InvalidJson {
+        line: error.line(),
+        column: error.column(),
+    };
+    let mut deserializer = serde_json::Deserializer::from_str(source);
+    let message = DynamicMessage::deserialize(descriptor, &mut deserializer).map_err(invalid)?;
+    deserializer.end().map_err(invalid)?;
+    Ok(message.encode_to_vec())
+}
+
+pub(crate) fn decode_json(
+    descriptor: MessageDescriptor,
+    bytes: &[u8],
+) -> Result<Value, MessageError> {
+    let message =
+        DynamicMessage::decode(descriptor, bytes).map_err(|_| MessageError::Undecodable)?;
+    serde_json::to_value(&message).map_err(|_| MessageError::Undecodable)
+}
+
+/// The outcome a server reports in `grpc-status` and `grpc-message`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct GrpcStatus {
+    pub(crate) code: u32,
+    pub(crate) message: Option<String>,
+}
+
+impl GrpcStatus {
+    /// Reads the status from trailers, or from the headers of a
+    /// trailers-only response.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let code = headers
+            .get("grpc-status")?
+            .to_str()
+            .ok()?
+            .trim()
+            .parse()
+            .ok()?;
+        let message = headers
+            .get("grpc-message")
+            .map(|value| percent_decode(value.as_bytes()))
+            .filter(|message| !message.is_empty());
+        Some(Self { code, message })
+    }
+
+    pub(crate) const fn is_ok(&self) -> bool {
+        self.code == 0
+    }
+
+    pub(crate) fn code_name(&self) -> &'static str {
+        STATUS_NAMES
+            .get(self.code as usize)
+            .copied()
+            .unwrap_or("UNKNOWN")
+    }
+}
+
+/// `grpc-message` percent-encodes everything outside printable ASCII.
+/// Malformed escapes are kept as written.
+fn percent_decode(raw: &[u8]) -> String {
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut index = 0;
+    while index < raw.len() {
+        let escaped = (raw[index] == b'%')
+            .then(|| raw.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|pair| std::str::from_utf8(pair).ok())
+            .and_then(|pair| u8::from_str_radix(pair, 16).ok());
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                index += 3;
+            }
+            None => {
+                bytes.push(raw[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_reflect::DescriptorPool;
+    use prost_types::{
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+        field_descriptor_proto::{Label, Type},
+    };
+
+    use super::*;
+
+    fn greeting() -> MessageDescriptor {
+        let field = |name: &str, number, kind: Type| FieldDescriptorProto {
+            name: Some(name.to_owned()),
+            number: Some(number),
+            label: Some(Label::Optional.into()),
+            r#type: Some(kind.into()),
+            json_name: Some(name.to_owned()),
+            ..Default::default()
+        };
+        let file = FileDescriptorProto {
+            name: Some("greet.proto".to_owned()),
+            package: Some("greet".to_owned()),
+            syntax: Some("proto3".to_owned()),
+            message_type: vec![DescriptorProto {
+                name: Some("Greeting".to_owned()),
+                field: vec![
+                    field("name", 1, Type::String),
+                    field("count", 2, Type::Int32),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_proto(file).unwrap();
+        pool.get_message_by_name("greet.Greeting").unwrap()
+    }
+
+    #[test]
+    fn frames_are_split_across_chunks() {
+        let mut body = encode_frame(b"one").to_vec();
+        body.extend_from_slice(&encode_frame(b""));
+        body.extend_from_slice(&encode_frame(b"three"));
+        assert_eq!(&body[..5], [0, 0, 0, 0, 3]);
+
+        let mut decoder = FrameDecoder::default();
+        let mut messages = Vec::new();
+        for chunk in body.chunks(4) {
+            decoder.push(chunk);
+            while let Some(message) = decoder.next_message().unwrap() {
+                messages.push(message);
+            }
+        }
+        assert_eq!(messages, ["one".as_bytes(), b"", b"three"]);
+        assert_eq!(decoder.finish(), Ok(()));
+
+        decoder.push(&encode_frame(b"cut")[..6]);
+        assert_eq!(decoder.next_message(), Ok(None));
+        assert_eq!(decoder.finish(), Err(FrameError::Truncated));
+    }
+
+    #[test]
+    fn compressed_and_oversized_frames_are_refused() {
+        let mut decoder = FrameDecoder::default();
+        decoder.push(&[1, 0, 0, 0, 0]);
+        assert_eq!(decoder.next_message(), Err(FrameError::Compressed));
+
+        let mut decoder = FrameDecoder::default();
+        decoder.push(&[0, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(decoder.next_message(), Err(FrameError::TooLarge));
+    }
+
+    #[test]
+    fn json_round_trips_through_the_wire_format() {
+        let bytes = encode_json(greeting(), r#"{"name": "gpui", "count": 2}"#).unwrap();
+        assert_eq!(
+            decode_json(greeting(), &bytes).unwrap(),
+            serde_json::json!({"name": "gpui", "count": 2})
+        );
+        assert!(encode_json(greeting(), "  ").unwrap().is_empty());
+        assert_eq!(
+            encode_json(greeting(), "{\n  \"missing\": 1\n}"),
+            Err(MessageError::InvalidJson {
+                line: 2,
+                column: 11
+            })
+        );
+        assert_eq!(
+            decode_json(greeting(), &[0xff]),
+            Err(MessageError::Undecodable)
+        );
+    }
+
+    #[test]
+    fn status_is_read_and_its_message_percent_decoded() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(GrpcStatus::from_headers(&headers), None);
+        headers.insert("grpc-status", "5".parse().unwrap());
+        headers.insert("grpc-message", "no %E2%9C%93 here%2".parse().unwrap());
+        let status = GrpcStatus::from_headers(&headers).unwrap();
+        assert_eq!(status.code_name(), "NOT_FOUND");
+        assert_eq!(status.message.as_deref(), Some("no ✓ here%2"));
+        assert!(!status.is_ok());
+        assert_eq!(
+            GrpcStatus {
+                code: 99,
+                message: None
+            }
+            .code_name(),
+            "UNKNOWN"
+        );
+    }
+}
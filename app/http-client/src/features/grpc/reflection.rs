@@ -0,0 +1,293 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use http::HeaderMap;
+use prost::Message as _;
+use prost_reflect::DescriptorPool;
+use prost_types::FileDescriptorProto;
+use thiserror::Error;
+use url::Url;
+
+use super::{
+    call::{CallProblem, open_call},
+    codec::UNIMPLEMENTED,
+    target::method_url,
+};
+
+/// The reflection service under its current name and the name servers
+/// shipped before it was stabilized. Both speak the same messages.
+const REFLECTION_SERVICES: [&str; 2] = [
+    "grpc.reflection.v1.ServerReflection",
+    "grpc.reflection.v1alpha.ServerReflection",
+];
+const REFLECTION_METHOD: &str = "ServerReflectionInfo";
+
+/// Stops following imports after this many files so a misbehaving server
+/// cannot keep the loader busy.
+const MAX_REFLECTED_FILES: usize = 512;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub(crate) enum ReflectionError {
+    #[error(transparent)]
+    Call(#[from] CallProblem),
+    #[error("the server does not support reflection")]
+    Unsupported,
+    #[error("the reflection call failed with status {0}")]
+    Status(u32),
+    #[error("the server answered {code}: {message}")]
+    Server { code: i32, message: String },
+    #[error("the server sent an unreadable reflection response")]
+    InvalidResponse,
+    #[error("the server's descriptors are inconsistent: {0}")]
+    InvalidDescriptor(String),
+    #[error("the server lists no services")]
+    NoServices,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ServerReflectionRequest {
+    #[prost(string, tag = "1")]
+    host: String,
+    #[prost(oneof = "MessageRequest", tags = "3, 4, 7")]
+    message_request: Option<MessageRequest>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum MessageRequest {
+    #[prost(string, tag = "3")]
+    FileByFilename(String),
+    #[prost(string, tag = "4")]
+    FileContainingSymbol(String),
+    #[prost(string, tag = "7")]
+    ListServices(String),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ServerReflectionResponse {
+    #[prost(oneof = "MessageResponse", tags = "4, 6, 7")]
+    message_response: Option<MessageResponse>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum MessageResponse {
+    #[prost(message, tag = "4")]
+    Files(FileDescriptorResponse),
+    #[prost(message, tag = "6")]
+    Services(ListServiceResponse),
+    #[prost(message, tag = "7")]
+    Error(ErrorResponse),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FileDescriptorResponse {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    file_descriptor_proto: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ListServiceResponse {
+    #[prost(message, repeated, tag = "1")]
+    service: Vec<ServiceResponse>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ServiceResponse {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ErrorResponse {
+    #[prost(int32, tag = "1")]
+    error_code: i32,
+    #[prost(string, tag = "2")]
+    error_message: String,
+}
+
+/// Builds a descriptor pool from what the server reports about itself:
+/// every listed service, the files defining them and their imports.
+pub(crate) async fn reflect(
+    client: &reqwest::Client,
+    target: &Url,
+    metadata: &HeaderMap,
+) -> Result<DescriptorPool, ReflectionError> {
+    let mut last_error = ReflectionError::Unsupported;
+    for service in REFLECTION_SERVICES {
+        let reflector = Reflector {
+            client,
+            url: method_url(target, service, REFLECTION_METHOD),
+            metadata,
+            host: target.host_str().unwrap_or_default().to_owned(),
+        };
+        match reflector.load().await {
+            Err(error @ ReflectionError::Unsupported) => last_error = error,
+            result => return result,
+        }
+    }
+    Err(last_error)
+}
+
+struct Reflector<'a> {
+    client: &'a reqwest::Client,
+    url: Url,
+    metadata: &'a HeaderMap,
+    host: String,
+}
+
+impl Reflector<'_> {
+    async fn load(&self) -> Result<DescriptorPool, ReflectionError> {
+        let MessageResponse::Services(list) = self
+            .ask(MessageRequest::ListServices(String::new()))
+            .await?
+        else {
+            return Err(ReflectionError::InvalidResponse);
+        };
+        let services: Vec<String> = list
+            .service
+            .into_iter()
+            .map(|service| service.name)
+            .filter(|name| !name.starts_with("grpc.reflection."))
+            .collect();
+        if services.is_empty() {
+            return Err(ReflectionError::NoServices);
+        }
+
+        let mut files = BTreeMap::new();
+        for service in services {
+            let response = self
+                .ask(MessageRequest::FileContainingSymbol(service))
+                .await?;
+            collect_files(response, &mut files)?;
+        }
+        // Imports are usually sent along with the file that needs them;
+        // ask for any that were not.
+        let mut requested = BTreeSet::new();
+        while let Some(missing) = missing_import(&files, &requested) {
+            if files.len() >= MAX_REFLECTED_FILES {
+                break;
+            }
+            requested.insert(missing.clone());
+            let response = self.ask(MessageRequest::FileByFilename(missing)).await?;
+            collect_files(response, &mut files)?;
+        }
+
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_protos(files.into_values())
+            .map_err(|error| ReflectionError::InvalidDescriptor(error.to_string()))?;
+        Ok(pool)
+    }
+
+    /// Sends one reflection request as its own call.
+    async fn ask(&self, request: MessageRequest) -> Result<MessageResponse, ReflectionError> {
+        let request = ServerReflectionRequest {
+            host: self.host.clone(),
+            message_request: Some(request),
+        };
+        let mut stream = open_call(
+            self.client,
+            self.url.clone(),
+            self.metadata,
+            &request.encode_to_vec(),
+            None,
+        )
+        .await?;
+        let mut first = None;
+        while let Some(message) = stream.next_message().await? {
+            first.get_or_insert(message);
+        }
+        let status = stream.status()?;
+        if status.code == UNIMPLEMENTED {
+            return Err(ReflectionError::Unsupported);
+        }
+        if !status.is_ok() {
+            return Err(ReflectionError::Status(status.code));
+        }
+        let response = first
+            .and_then(|message| ServerReflectionResponse::decode(message).ok())
+            .and_then(|response| response.message_response)
+            .ok_or(ReflectionError::InvalidResponse)?;
+        match response {
+            MessageResponse::Error(error) => Err(ReflectionError::Server {
+                code: error.error_code,
+                message: error.error_message,
+            }),
+            response => Ok(response),
+        }
+    }
+}
+
+fn collect_files(
+    response: MessageResponse,
+    files: &mut BTreeMap<String, FileDescriptorProto>,
+) -> Result<(), ReflectionError> {
+    let MessageResponse::Files(response) = response else {
+        return Err(ReflectionError::InvalidResponse);
+    };
+    for bytes in response.file_descriptor_proto {
+        let file = FileDescriptorProto::decode(bytes.as_slice())
+            .map_err(|_| ReflectionError::InvalidResponse)?;
+        files.insert(file.name().to_owned(), file);
+    }
+    Ok(())
+}
+
+/// The first import that is neither loaded nor already asked for.
+fn missing_import(
+    files: &BTreeMap<String, FileDescriptorProto>,
+    requested: &BTreeSet<String>,
+) -> Option<String> {
+    files
+        .values()
+        .flat_map(|file| &file.dependency)
+        .find(|name| !files.contains_key(*name) && !requested.contains(*name))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflection_messages_match_the_published_wire_format() {
+        let request = ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(MessageRequest::ListServices(String::new())),
+        };
+        assert_eq!(request.encode_to_vec(), [0x3a, 0x00]);
+
+        // list_services_response { service { name: "a.B" } }
+        let response = ServerReflectionResponse::decode(
+            &[0x32, 0x07, 0x0a, 0x05, 0x0a, 0x03, b'a', b'.', b'B'][..],
+        )
+        .unwrap();
+        assert_eq!(
+            response.message_response,
+            Some(MessageResponse::Services(ListServiceResponse {
+                service: vec![ServiceResponse {
+                    name: "a.B".to_owned()
+                }]
+            }))
+        );
+    }
+
+    #[test]
+    fn imports_are_requested_once() {
+        let file = |name: &str, dependency: &[&str]| FileDescriptorProto {
+            name: Some(name.to_owned()),
+            dependency: dependency.iter().map(|name| (*name).to_owned()).collect(),
+            ..Default::default()
+        };
+        let mut files = BTreeMap::new();
+        files.insert(
+            "a.proto".to_owned(),
+            file("a.proto", &["b.proto", "c.proto"]),
+        );
+        files.insert("b.proto".to_owned(), file("b.proto", &["c.proto"]));
+        let mut requested = BTreeSet::new();
+        assert_eq!(
+            missing_import(&files, &requested).as_deref(),
+            Some("c.proto")
+        );
+        requested.insert("c.proto".to_owned());
+        assert_eq!(missing_import(&files, &requested), None);
+    }
+}
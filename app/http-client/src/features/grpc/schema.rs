@@ -0,0 +1,188 @@
+use std::path::{Path, PathBuf};
+
+use gpui::Global;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, MethodDescriptor, SerializeOptions, ServiceDescriptor,
+};
+use thiserror::Error;
+
+/// The descriptors most recently loaded in the gRPC mode, shared with the
+/// response viewer so raw protobuf bodies can be decoded.
+#[derive(Clone)]
+pub(crate) struct LoadedDescriptors(pub(crate) DescriptorPool);
+
+impl Global for LoadedDescriptors {}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub(crate) enum SchemaError {
+    #[error("{0}")]
+    Compile(String),
+    #[error("descriptors are inconsistent: {0}")]
+    Invalid(String),
+    #[error("no services are defined")]
+    NoServices,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MethodKind {
+    Unary,
+    ServerStreaming,
+    ClientStreaming,
+    Bidirectional,
+}
+
+impl MethodKind {
+    pub(crate) fn of(method: &MethodDescriptor) -> Self {
+        match (method.is_client_streaming(), method.is_server_streaming()) {
+            (false, false) => Self::Unary,
+            (false, true) => Self::ServerStreaming,
+            (true, false) => Self::ClientStreaming,
+            (true, true) => Self::Bidirectional,
+        }
+    }
+
+    /// Calls send a single request message, so only methods that take one
+    /// can be invoked.
+    pub(crate) const fn is_supported(self) -> bool {
+        matches!(self, Self::Unary | Self::ServerStreaming)
+    }
+}
+
+/// Compiles `.proto` files. Each file's directory is an import root, and
+/// the well-known `google/protobuf` types are always available.
+pub(crate) fn compile_proto_files(files: &[PathBuf]) -> Result<DescriptorPool, SchemaError> {
+    let mut includes: Vec<&Path> = Vec::new();
+    for parent in files.iter().filter_map(|file| file.parent()) {
+        if !includes.contains(&parent) {
+            includes.push(parent);
+        }
+    }
+    let set = protox::compile(files, includes)
+        .map_err(|error| SchemaError::Compile(error.to_string()))?;
+    let pool = DescriptorPool::from_file_descriptor_set(set)
+        .map_err(|error| SchemaError::Invalid(error.to_string()))?;
+    if pool.services().next().is_none() {
+        return Err(SchemaError::NoServices);
+    }
+    Ok(pool)
+}
+
+/// Services in name order, skipping server reflection itself.
+pub(crate) fn services(pool: &DescriptorPool) -> Vec<ServiceDescriptor> {
+    let mut services: Vec<_> = pool
+        .services()
+        .filter(|service| !service.full_name().starts_with("grpc.reflection."))
+        .collect();
+    services.sort_by(|a, b| a.full_name().cmp(b.full_name()));
+    services
+}
+
+/// A request message with every field at its default value, as a
+/// starting point for editing.
+pub(crate) fn request_template(method: &MethodDescriptor) -> String {
+    let message = DynamicMessage::new(method.input());
+    let mut output = Vec::new();
+    let mut serializer = serde_json::Serializer::pretty(&mut output);
+    let options = SerializeOptions::new().skip_default_fields(false);
+    if message
+        .serialize_with_options(&mut serializer, &options)
+        .is_err()
+    {
+        return "{}".to_owned();
+    }
+    String::from_utf8(output).unwrap_or_else(|_| "{}".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::{
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, MethodDescriptorProto,
+        ServiceDescriptorProto,
+        field_descriptor_proto::{Label, Type},
+    };
+
+    use super::*;
+
+    fn pool() -> DescriptorPool {
+        let method = |name: &str, client: bool, server: bool| MethodDescriptorProto {
+            name: Some(name.to_owned()),
+            input_type: Some(".greet.Greeting".to_owned()),
+            output_type: Some(".greet.Greeting".to_owned()),
+            client_streaming: Some(client),
+            server_streaming: Some(server),
+            ..Default::default()
+        };
+        let file = FileDescriptorProto {
+            name: Some("greet.proto".to_owned()),
+            package: Some("greet".to_owned()),
+            syntax: Some("proto3".to_owned()),
+            message_type: vec![DescriptorProto {
+                name: Some("Greeting".to_owned()),
+                field: vec![FieldDescriptorProto {
+                    name: Some("name".to_owned()),
+                    number: Some(1),
+                    label: Some(Label::Optional.into()),
+                    r#type: Some(Type::String.into()),
+                    json_name: Some("name".to_owned()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            service: vec![
+                ServiceDescriptorProto {
+                    name: Some("Greeter".to_owned()),
+                    method: vec![
+                        method("SayHello", false, false),
+                        method("Subscribe", false, true),
+                        method("Upload", true, false),
+                        method("Chat", true, true),
+                    ],
+                    ..Default::default()
+                },
+                ServiceDescriptorProto {
+                    name: Some("Admin".to_owned()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_proto(file).unwrap();
+        pool
+    }
+
+    #[test]
+    fn services_are_sorted_and_methods_classified() {
+        let pool = pool();
+        let services = services(&pool);
+        let names: Vec<_> = services.iter().map(ServiceDescriptor::full_name).collect();
+        assert_eq!(names, ["greet.Admin", "greet.Greeter"]);
+        let kinds: Vec<_> = services[1]
+            .methods()
+            .map(|method| MethodKind::of(&method))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                MethodKind::Unary,
+                MethodKind::ServerStreaming,
+                MethodKind::ClientStreaming,
+                MethodKind::Bidirectional
+            ]
+        );
+        assert!(kinds[1].is_supported());
+        assert!(!kinds[2].is_supported());
+    }
+
+    #[test]
+    fn templates_list_every_field() {
+        let pool = pool();
+        let method = pool
+            .get_service_by_name("greet.Greeter")
+            .unwrap()
+            .methods()
+            .next()
+            .unwrap();
+        assert_eq!(request_template(&method), "{\n  \"name\": \"\"\n}");
+    }
+}
@@ -0,0 +1,135 @@
+use http::{HeaderMap, HeaderName, HeaderValue};
+use thiserror::Error;
+use url::Url;
+
+/// Metadata the call itself sets or that would change how the body is
+/// framed.
+static RESERVED_METADATA: [&str; 6] = [
+    "content-type",
+    "host",
+    "te",
+    "grpc-timeout",
+    "grpc-encoding",
+    "grpc-accept-encoding",
+];
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub(crate) enum TargetError {
+    #[error("server address is required")]
+    AddressRequired,
+    #[error("server address is invalid")]
+    InvalidAddress,
+    #[error("server address must use http or https")]
+    UnsupportedScheme,
+    #[error("metadata on line {line} is invalid")]
+    InvalidMetadata { line: usize },
+    #[error("metadata on line {line} is managed by the call")]
+    ReservedMetadata { line: usize },
+}
+
+/// Reads the server address. A bare `host:port` means plaintext HTTP/2,
+/// as with most gRPC command-line clients.
+pub(crate) fn parse_target(raw: &str) -> Result<Url, TargetError> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err(TargetError::AddressRequired);
+    }
+    let url = if raw.contains("://") {
+        Url::parse(raw)
+    } else {
+        Url::parse(&format!("http://{raw}"))
+    }
+    .map_err(|_| TargetError::InvalidAddress)?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(TargetError::UnsupportedScheme);
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(TargetError::InvalidAddress);
+    }
+    Ok(url)
+}
+
+/// Reads one `name: value` metadata entry per line. Blank lines are
+/// skipped and repeated names are sent as repeated entries.
+pub(crate) fn parse_metadata(raw: &str) -> Result<HeaderMap, TargetError> {
+    let mut metadata = HeaderMap::new();
+    for (index, line) in raw.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = || TargetError::InvalidMetadata { line: line_number };
+        let (name, value) = line.split_once(':').ok_or_else(invalid)?;
+        let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| invalid())?;
+        if RESERVED_METADATA.contains(&name.as_str()) {
+            return Err(TargetError::ReservedMetadata { line: line_number });
+        }
+        let value = HeaderValue::from_str(value.trim()).map_err(|_| invalid())?;
+        metadata.append(name, value);
+    }
+    Ok(metadata)
+}
+
+/// The request path of a method, `/package.Service/Method`, resolved
+/// against any path prefix on the server address.
+pub(crate) fn method_url(target: &Url, service: &str, method: &str) -> Url {
+    let mut url = target.clone();
+    let prefix = target.path().trim_end_matches('/');
+    url.set_path(&format!("{prefix}/{service}/{method}"));
+    url.set_query(None);
+    url.set_fragment(None);
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_default_to_plaintext() {
+        assert_eq!(parse_target(" "), Err(TargetError::AddressRequired));
+        assert_eq!(
+            parse_target("localhost:50051").unwrap().as_str(),
+            "http://localhost:50051/"
+        );
+        assert_eq!(
+            parse_target("https://api.example.test").unwrap().as_str(),
+            "https://api.example.test/"
+        );
+        assert_eq!(
+            parse_target("ws://localhost:50051"),
+            Err(TargetError::UnsupportedScheme)
+        );
+        assert_eq!(parse_target("http://"), Err(TargetError::InvalidAddress));
+    }
+
+    #[test]
+    fn metadata_lines_report_the_offending_line() {
+        let metadata =
+            parse_metadata("authorization: Bearer abc\n\nx-tag: one\nX-Tag: two").unwrap();
+        assert_eq!(metadata["authorization"], "Bearer abc");
+        assert_eq!(metadata.get_all("x-tag").iter().count(), 2);
+        assert_eq!(
+            parse_metadata("x-ok: 1\nno colon"),
+            Err(TargetError::InvalidMetadata { line: 2 })
+        );
+        assert_eq!(
+            parse_metadata("grpc-timeout: 1S"),
+            Err(TargetError::ReservedMetadata { line: 1 })
+        );
+    }
+
+    #[test]
+    fn method_paths_keep_the_address_prefix() {
+        let target = parse_target("https://edge.example.test/grpc/?x=1").unwrap();
+        assert_eq!(
+            method_url(&target, "greet.Greeter", "SayHello").as_str(),
+            "https://edge.example.test/grpc/greet.Greeter/SayHello"
+        );
+        let target = parse_target("localhost:50051").unwrap();
+        assert_eq!(
+            method_url(&target, "greet.Greeter", "SayHello").path(),
+            "/greet.Greeter/SayHello"
+        );
+    }
+}
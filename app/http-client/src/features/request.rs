@@ -1,12 +1,19 @@
-use std::time::Instant;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 
+use fluent_bundle::FluentArgs;
 use gpui::{
-    AppContext as _, Context, Entity, FocusHandle, InteractiveElement as _, IntoElement,
-    ParentElement, Pixels, Styled, Subscription, Window, div, prelude::FluentBuilder as _, px,
+    App, AppContext as _, Context, Entity, FocusHandle, InteractiveElement as _, IntoElement,
+    ParentElement, PathPromptOptions, Pixels, Styled, Subscription, Task, Window, div,
+    prelude::FluentBuilder as _, px,
 };
 use gpui_component::{
     ActiveTheme as _, Disableable as _,
     button::{Button, ButtonVariants as _},
+    h_flex,
     label::Label,
     resizable::{resizable_panel, v_resizable},
     select::SelectState,
@@ -16,56 +23,90 @@ use gpui_form::Form;
 use gpui_operation::Transition as _;
 
 use self::{
+    checks::{CheckReport, ResponseChecks, compile_checks, run_checks},
     controls::FormScalarSelect,
-    draft::{HttpClientTransportSettings, RequestDraft},
+    draft::{HttpClientTransportSettings, RequestDraft, RequestSettingsDraft},
+    har::{
+        ExchangeRecord, HAR_EXTENSION, HarError, HarNotice, RequestHistory, export_har, import_har,
+    },
     method::{HttpMethod, SelectHttpMethod},
-    prepared::{PreparedRequest, RequestPrepareError, compile_request},
+    prepared::{PreparedRequest, RequestCompileError, RequestPrepareError, compile_request},
     response::{
         ResponsePane, ResponseProjection, ResponseSaveProblem, ResponseViewWarning, ViewerMode,
-        initial_save_directory, project_response, resolved_viewer_mode, save_response,
-        suggested_response_name,
+        diff_responses, initial_save_directory, project_protobuf, project_response,
+        resolved_viewer_mode, save_response, suggested_response_name,
     },
     runtime::{HttpRunEffect, HttpRunMessage, RequestProblem, RequestRuntime},
+    saved::{SAVED_REQUEST_EXTENSION, SavedRequestError, open_request, save_request},
+    session::StoredTab,
     tab::RequestTabsView,
     transport::{HttpTransport, WorkerEvent},
     url_input::UrlInput,
     validation::RequestValidator,
+    variables::RequestVariables,
 };
 use crate::foundation::{I18n, validation_message};
 
+pub use self::runner::run_headless;
+pub(crate) use self::{
+    runner::RunnerView,
+    session::RequestSessionView,
+    transport::{Cookie, CookieJar, CookieKey},
+};
+
 mod auth;
 mod body;
+mod checks;
 mod controls;
 mod draft;
+mod graphql;
+mod har;
 mod headers;
+mod json_path;
 mod method;
 mod params;
 mod prepared;
 mod response;
+mod runner;
 mod runtime;
+mod saved;
+mod session;
 mod settings;
 mod tab;
 mod transport;
 mod url_input;
 mod validation;
+mod variables;
 
 pub(crate) struct RequestView {
     form: Entity<Form<RequestDraft>>,
     transport_settings: HttpClientTransportSettings,
+    variables: RequestVariables,
     method: FormScalarSelect<RequestDraft, SelectHttpMethod, HttpMethod>,
     url: UrlInput,
     tabs: Entity<RequestTabsView>,
     transport: HttpTransport,
     runtime: RequestRuntime,
     response_pane: ResponsePane,
+    pending_checks: Option<ResponseChecks>,
+    /// The saved request file this editor was opened from or saved to.
+    file: Option<PathBuf>,
+    file_problem: Option<SavedRequestError>,
+    file_task: Option<Task<()>>,
+    /// Why the last send could not start, when the form itself was valid.
+    send_problem: Option<RequestCompileError>,
+    history: RequestHistory,
+    har_notice: Option<HarNotice>,
+    har_task: Option<Task<()>>,
     _form_observer: Subscription,
     focus_handle: FocusHandle,
 }
 
 impl RequestView {
-    pub(crate) fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+    pub(crate) fn new(cookies: CookieJar, window: &mut Window, cx: &mut Context<Self>) -> Self {
         let form = cx.new(|_| Form::new(RequestDraft::default()).with_validator(RequestValidator));
         let transport_settings = HttpClientTransportSettings::default();
+        let variables = RequestVariables::default();
         let method = FormScalarSelect::new(
             &form,
             RequestDraft::METHOD,
@@ -74,40 +115,122 @@ impl RequestView {
             cx,
         );
         let url = UrlInput::new(&form, window, cx);
-        let tabs =
-            cx.new(|cx| RequestTabsView::new(form.clone(), transport_settings.clone(), window, cx));
+        let transport = HttpTransport::new(cookies);
+        let tabs = cx.new(|cx| {
+            RequestTabsView::new(
+                form.clone(),
+                transport_settings.clone(),
+                variables.clone(),
+                transport.clone(),
+                window,
+                cx,
+            )
+        });
         let form_observer = cx.observe(&form, |_, _, cx| cx.notify());
         let response_pane = ResponsePane::new(window, cx);
         Self {
             form,
             transport_settings,
+            variables,
             method,
             url,
             tabs,
-            transport: HttpTransport::new(),
+            transport,
             runtime: RequestRuntime::new(),
             response_pane,
+            pending_checks: None,
+            file: None,
+            file_problem: None,
+            file_task: None,
+            send_problem: None,
+            history: RequestHistory::default(),
+            har_notice: None,
+            har_task: None,
             _form_observer: form_observer,
             focus_handle: cx.focus_handle(),
         }
     }
 
+    fn form(&self) -> &Entity<Form<RequestDraft>> {
+        &self.form
+    }
+
+    fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    /// Whether the form differs from the request it was last opened or saved as.
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.form.read(cx).is_dirty()
+    }
+
+    fn stored_tab(&self, cx: &App) -> StoredTab {
+        StoredTab {
+            file: self.file.clone(),
+            saved: self.form.read(cx).baseline().clone(),
+            draft: RequestDraft::ROOT.get(&self.form, cx),
+        }
+    }
+
+    /// Reinstalls a stored tab, keeping its unsaved edits marked as such.
+    fn restore(&mut self, tab: StoredTab, cx: &mut App) {
+        self.file = tab.file;
+        self.form.update(cx, |form, cx| {
+            form.rebase(tab.saved, cx);
+            form.replace(tab.draft, cx);
+        });
+    }
+
+    /// Validates the form, substitutes the current variables and compiles
+    /// both the outgoing request and the checks to run on its response.
     pub(crate) fn prepare_request(
         &mut self,
         cx: &mut Context<Self>,
-    ) -> Result<PreparedRequest, RequestPrepareError> {
+    ) -> Result<(PreparedRequest, ResponseChecks), RequestPrepareError> {
         let prepared = self.form.update(cx, |form, cx| form.prepare(cx))?;
         let (_, draft) = prepared.into_parts();
-        compile_request(draft, &self.transport_settings).map_err(Into::into)
+        let draft = self.variables.resolve(draft)?;
+        let checks = compile_checks(&draft.assertions, &draft.extractions)?;
+        let request = compile_request(draft, &self.transport_settings)?;
+        Ok((request, checks))
     }
 
     fn start_request(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.send_request(false, window, cx);
+    }
+
+    /// Sends the current request. A resumed send reopens the previous event
+    /// stream from its last event id instead of starting a new response.
+    fn send_request(&mut self, resume: bool, window: &mut Window, cx: &mut Context<Self>) {
         if self.runtime.is_running() {
             return;
         }
-        let Ok(prepared) = self.prepare_request(cx) else {
-            return;
+        self.response_pane.event_stream.stop_reconnect();
+        let (mut prepared, checks) = match self.prepare_request(cx) {
+            Ok(prepared) => {
+                self.send_problem = None;
+                prepared
+            }
+            // Form errors are shown on their fields; anything found once
+            // the variables are filled in has no field of its own.
+            Err(error) => {
+                self.send_problem = match error {
+                    RequestPrepareError::Compile(problem) => Some(problem),
+                    RequestPrepareError::Invalid(_) => None,
+                };
+                cx.notify();
+                return;
+            }
         };
+        if resume
+            && let Some(id) = self.response_pane.event_stream.last_event_id()
+            && let Ok(id) = http::HeaderValue::from_str(id)
+        {
+            prepared
+                .headers
+                .insert(http::HeaderName::from_static("last-event-id"), id);
+        }
+        let record = ExchangeRecord::new(&prepared);
 
         let transport = self.transport.clone();
         let owner = cx.entity().downgrade();
@@ -154,7 +277,12 @@ impl RequestView {
 
         let effect = (&mut self.runtime).transition(HttpRunMessage::Start { task, started_at });
         if effect == HttpRunEffect::Started {
-            self.response_pane.reset_for_send(window, cx);
+            self.response_pane.reset_for_send(resume, window, cx);
+            self.history.begin(record);
+            if !checks.is_empty() {
+                self.response_pane.expect_checks();
+                self.pending_checks = Some(checks);
+            }
             cx.notify();
         }
     }
@@ -167,6 +295,10 @@ impl RequestView {
         let effect = (&mut self.runtime).transition(HttpRunMessage::Clear);
         if effect != HttpRunEffect::Ignored {
             self.response_pane.clear_projection();
+            self.response_pane.clear_checks();
+            self.response_pane.clear_diff();
+            self.response_pane.event_stream.clear();
+            self.pending_checks = None;
             cx.notify();
         }
     }
@@ -188,6 +320,11 @@ impl RequestView {
                 progress,
             },
             WorkerEvent::BodyProgress(progress) => HttpRunMessage::BodyProgress(progress),
+            WorkerEvent::EventStream(update) => {
+                self.response_pane.event_stream.apply(update);
+                cx.notify();
+                return;
+            }
             WorkerEvent::Finished {
                 result,
                 finished_after,
@@ -195,6 +332,10 @@ impl RequestView {
                 result,
                 finished_after,
             },
+            WorkerEvent::AuthorizationRequested(url) => {
+                cx.open_url(url.as_str());
+                return;
+            }
         };
         self.handle_run_message(message, Some(window), cx);
     }
@@ -210,13 +351,124 @@ impl RequestView {
             return;
         }
         if effect == HttpRunEffect::Ready
-            && let Some(window) = window
+            && let Some(response) = self.runtime.response()
+        {
+            self.history.complete(response.clone());
+        }
+        if let Some(window) = window
+            && matches!(effect, HttpRunEffect::Ready | HttpRunEffect::Failed)
         {
-            self.refresh_response_projection(window, cx);
+            if effect == HttpRunEffect::Ready {
+                self.refresh_response_projection(window, cx);
+                self.start_checks(window, cx);
+                self.refresh_response_diff(window, cx);
+            }
+            self.schedule_reconnect(window, cx);
+        }
+        if matches!(effect, HttpRunEffect::Failed | HttpRunEffect::Cancelled) {
+            self.pending_checks = None;
+        }
+        cx.notify();
+    }
+
+    /// Reopens an event stream that ended on its own once the server's
+    /// retry delay has passed, when the request settings ask for it.
+    fn schedule_reconnect(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let enabled = RequestDraft::ROOT
+            .then(RequestDraft::SETTINGS)
+            .then(RequestSettingsDraft::RECONNECT_EVENT_STREAM)
+            .get(&self.form, cx);
+        if !enabled {
+            return;
+        }
+        let Some(delay) = self
+            .response_pane
+            .event_stream
+            .reconnect_after(&self.runtime)
+        else {
+            return;
+        };
+        let owner = cx.entity().downgrade();
+        let task = window.spawn(cx, async move |cx| {
+            cx.background_executor().timer(delay).await;
+            let _ = owner.update_in(cx, |this, window, cx| this.send_request(true, window, cx));
+        });
+        self.response_pane
+            .event_stream
+            .schedule_reconnect(delay, task);
+    }
+
+    fn start_checks(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(checks) = self.pending_checks.take() else {
+            return;
+        };
+        let Some(response) = self.runtime.response().cloned() else {
+            self.response_pane.clear_checks();
+            return;
+        };
+        let evaluation = gpui_tokio::Tokio::spawn(cx, run_checks(checks, response));
+        let owner = cx.entity().downgrade();
+        let task = window.spawn(cx, async move |cx| {
+            let Ok(report) = evaluation.await else {
+                let _ = owner.update_in(cx, |this, _, cx| {
+                    this.response_pane.clear_checks();
+                    cx.notify();
+                });
+                return;
+            };
+            let _ = owner.update_in(cx, |this, _, cx| this.finish_checks(report, cx));
+        });
+        self.response_pane.install_checks_task(task);
+    }
+
+    fn finish_checks(&mut self, report: CheckReport, cx: &mut Context<Self>) {
+        for outcome in &report.extractions {
+            if let Ok(value) = &outcome.result {
+                self.variables.set(outcome.variable.clone(), value.clone());
+            }
         }
+        self.response_pane.finish_checks(report);
+        cx.notify();
+    }
+
+    fn pin_response(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(response) = self.runtime.response().cloned() else {
+            return;
+        };
+        self.response_pane.set_pinned_response(Some(response));
+        self.refresh_response_diff(window, cx);
         cx.notify();
     }
 
+    /// Compares the current response with the pinned one in the
+    /// background. Nothing runs while the pinned response is the current one.
+    fn refresh_response_diff(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (Some(pinned), Some(current)) = (
+            self.response_pane.pinned_response().cloned(),
+            self.runtime.response().cloned(),
+        ) else {
+            self.response_pane.clear_diff();
+            return;
+        };
+        if Arc::ptr_eq(&pinned, &current) {
+            self.response_pane.clear_diff();
+            return;
+        }
+        let comparison = gpui_tokio::Tokio::spawn(cx, diff_responses(pinned, current));
+        let owner = cx.entity().downgrade();
+        let task = window.spawn(cx, async move |cx| {
+            let diff = match comparison.await {
+                Ok(Ok(diff)) => Some(diff),
+                Ok(Err(_)) | Err(_) => None,
+            };
+            let _ = owner.update_in(cx, |this, _, cx| {
+                this.response_pane.finish_diff(diff);
+                cx.notify();
+            });
+        });
+        self.response_pane.install_diff_task(task);
+    }
+
     fn refresh_response_projection(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let Some(response) = self.runtime.response().cloned() else {
             self.response_pane.clear_projection();
@@ -248,15 +500,28 @@ impl RequestView {
                 cx.notify();
                 return;
             }
+            ViewerMode::Events => {
+                cx.notify();
+                return;
+            }
             ViewerMode::Auto
             | ViewerMode::Text
             | ViewerMode::Json
             | ViewerMode::Xml
             | ViewerMode::Hex
             | ViewerMode::Base64
-            | ViewerMode::Image => {}
+            | ViewerMode::Image
+            | ViewerMode::Protobuf
+            | ViewerMode::MessagePack
+            | ViewerMode::Cbor
+            | ViewerMode::Form => {}
         }
-        let projection = gpui_tokio::Tokio::spawn(cx, project_response(response, effective));
+        let projection = if effective == ViewerMode::Protobuf {
+            let message = self.response_pane.protobuf_message(&response, cx);
+            gpui_tokio::Tokio::spawn(cx, project_protobuf(response, message))
+        } else {
+            gpui_tokio::Tokio::spawn(cx, project_response(response, effective))
+        };
         let owner = cx.entity().downgrade();
         let task_token = token.clone();
         let task = window.spawn(cx, async move |cx| {
@@ -326,21 +591,202 @@ impl RequestView {
         cx.notify();
     }
 
+    fn start_request_save(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let draft = RequestDraft::ROOT.get(&self.form, cx);
+        let suggested = format!("request.{SAVED_REQUEST_EXTENSION}");
+        let prompt = cx.prompt_for_new_path(&initial_save_directory(), Some(&suggested));
+        let owner = cx.entity().downgrade();
+        self.file_task = Some(window.spawn(cx, async move |cx| {
+            let Ok(Ok(Some(target))) = prompt.await else {
+                return;
+            };
+            let result = save_request(&target, &draft);
+            let _ = owner.update_in(cx, |this, _, cx| {
+                match result {
+                    Ok(()) => {
+                        this.file = Some(target);
+                        this.file_problem = None;
+                        // Edits made while the prompt was open are still
+                        // unsaved, so only an unchanged form becomes clean.
+                        if RequestDraft::ROOT.get(&this.form, cx) == draft {
+                            this.form.update(cx, |form, cx| form.rebase(draft, cx));
+                        }
+                    }
+                    Err(problem) => this.file_problem = Some(problem),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn start_request_open(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let prompt = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: None,
+        });
+        let owner = cx.entity().downgrade();
+        self.file_task = Some(window.spawn(cx, async move |cx| {
+            let Ok(Ok(Some(paths))) = prompt.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let result = open_request(&path);
+            let _ = owner.update_in(cx, |this, _, cx| {
+                match result {
+                    Ok(draft) => {
+                        this.file = Some(path);
+                        this.file_problem = None;
+                        this.form.update(cx, |form, cx| form.rebase(draft, cx));
+                    }
+                    Err(problem) => this.file_problem = Some(problem),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    /// Turns a HAR capture into a collection: one saved request per entry,
+    /// written into a folder the user picks after the archive.
+    fn start_har_import(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let prompt = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: None,
+        });
+        let owner = cx.entity().downgrade();
+        self.har_task = Some(window.spawn(cx, async move |cx| {
+            let Ok(Ok(Some(paths))) = prompt.await else {
+                return;
+            };
+            let Some(archive) = paths.into_iter().next() else {
+                return;
+            };
+            let Ok(prompt) = owner.update_in(cx, |_, _, cx| {
+                cx.prompt_for_paths(PathPromptOptions {
+                    files: false,
+                    directories: true,
+                    multiple: false,
+                    prompt: None,
+                })
+            }) else {
+                return;
+            };
+            let Ok(Ok(Some(paths))) = prompt.await else {
+                return;
+            };
+            let Some(folder) = paths.into_iter().next() else {
+                return;
+            };
+            let Ok(import) = owner.update_in(cx, |_, _, cx| {
+                cx.background_spawn(async move { import_har(&archive, &folder) })
+            }) else {
+                return;
+            };
+            let result = import.await;
+            let _ = owner.update_in(cx, |this, _, cx| {
+                this.har_notice = Some(match result {
+                    Ok(summary) => HarNotice::Imported(summary),
+                    Err(error) => HarNotice::Failed(error),
+                });
+                cx.notify();
+            });
+        }));
+    }
+
+    fn start_har_export(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let entries = self.history.snapshot();
+        if entries.is_empty() {
+            return;
+        }
+        let suggested = format!("requests.{HAR_EXTENSION}");
+        let prompt = cx.prompt_for_new_path(&initial_save_directory(), Some(&suggested));
+        let owner = cx.entity().downgrade();
+        self.har_task = Some(window.spawn(cx, async move |cx| {
+            let Ok(Ok(Some(target))) = prompt.await else {
+                return;
+            };
+            let exported = gpui_tokio::Tokio::spawn(cx, export_har(entries, target));
+            let notice = match exported.await {
+                Ok(Ok(count)) => HarNotice::Exported(count),
+                Ok(Err(error)) => HarNotice::Failed(error),
+                Err(error) => HarNotice::Failed(HarError::Write(std::io::Error::other(error))),
+            };
+            let _ = owner.update_in(cx, |this, _, cx| {
+                this.har_notice = Some(notice);
+                cx.notify();
+            });
+        }));
+    }
+
     fn send_is_disabled(&self) -> bool {
         self.runtime.is_running() || self.response_pane.save_is_running()
     }
 }
 
+fn send_problem_message(problem: &RequestCompileError, i18n: &I18n) -> String {
+    let mut args = FluentArgs::new();
+    if let RequestCompileError::UnknownVariables { names } = problem {
+        args.set("names", names.join(", "));
+    }
+    i18n.t_with_args(problem.message_key(), &args)
+}
+
 impl gpui::Render for RequestView {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let send_label = {
+        let (send_label, open_label, save_label, import_label, export_label, file_problem) = {
             let i18n = cx.global::<I18n>();
-            i18n.t("button-send")
+            (
+                i18n.t("button-send"),
+                i18n.t("button-open-request"),
+                i18n.t("button-save-request"),
+                i18n.t("button-import-har"),
+                i18n.t("button-export-har"),
+                self.file_problem
+                    .as_ref()
+                    .map(|problem| i18n.t(problem.message_key())),
+            )
         };
+        let har_notice = self
+            .har_notice
+            .as_ref()
+            .map(|notice| (notice.message(cx.global::<I18n>()), notice.is_problem()));
         let url_error = RequestDraft::URL
             .errors(&self.form, cx)
             .first()
             .map(|issue| validation_message(issue.message(), cx));
+        let send_problem = self
+            .send_problem
+            .as_ref()
+            .map(|problem| send_problem_message(problem, cx.global::<I18n>()));
+
+        let file_buttons = h_flex()
+            .gap_2()
+            .child(
+                Button::new("request-open").label(open_label).on_click(
+                    cx.listener(|this, _, window, cx| this.start_request_open(window, cx)),
+                ),
+            )
+            .child(
+                Button::new("request-save").label(save_label).on_click(
+                    cx.listener(|this, _, window, cx| this.start_request_save(window, cx)),
+                ),
+            )
+            .child(
+                Button::new("request-import-har")
+                    .label(import_label)
+                    .on_click(cx.listener(|this, _, window, cx| this.start_har_import(window, cx))),
+            )
+            .child(
+                Button::new("request-export-har")
+                    .label(export_label)
+                    .disabled(self.history.is_empty())
+                    .on_click(cx.listener(|this, _, window, cx| this.start_har_export(window, cx))),
+            );
 
         let request_line = div()
             .flex()
@@ -355,6 +801,9 @@ impl gpui::Render for RequestView {
                     .child(self.url.element())
                     .when_some(url_error, |this, error| {
                         this.child(Label::new(error).text_xs().text_color(cx.theme().danger))
+                    })
+                    .when_some(send_problem, |this, problem| {
+                        this.child(Label::new(problem).text_xs().text_color(cx.theme().danger))
                     }),
             )
             .child(
@@ -371,7 +820,8 @@ impl gpui::Render for RequestView {
                         .label(cx.global::<I18n>().t("button-cancel"))
                         .on_click(cx.listener(|this, _, _, cx| this.cancel_request(cx))),
                 )
-            });
+            })
+            .child(file_buttons);
 
         let request_editor = div()
             .flex()
@@ -380,6 +830,26 @@ impl gpui::Render for RequestView {
             .min_h(px(0.))
             .overflow_hidden()
             .child(request_line)
+            .when_some(file_problem, |this, problem| {
+                this.child(
+                    Label::new(problem)
+                        .px_2()
+                        .text_xs()
+                        .text_color(cx.theme().danger),
+                )
+            })
+            .when_some(har_notice, |this, (message, is_problem)| {
+                this.child(
+                    Label::new(message)
+                        .px_2()
+                        .text_xs()
+                        .text_color(if is_problem {
+                            cx.theme().danger
+                        } else {
+                            cx.theme().muted_foreground
+                        }),
+                )
+            })
             .child(self.tabs.clone());
         let response = self.response_pane.render(&self.runtime, window, cx);
 
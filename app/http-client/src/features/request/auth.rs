@@ -11,14 +11,14 @@ use gpui_component::{
     select::{SelectItem, SelectState},
     v_flex,
 };
-use gpui_form::{DynamicPath, Form, FormEvent};
+use gpui_form::{DynamicPath, FieldDef, Form, FormEvent};
 use gpui_form_gpui_component::FormInput;
 
 use super::{
     controls::{FormCaseSelect, FormScalarSelect},
     draft::{
-        ApiKeyAuthDraft, ApiKeyLocation, BasicAuthDraft, BearerAuthDraft, RequestAuthDraft,
-        RequestDraft,
+        ApiKeyAuthDraft, ApiKeyLocation, AwsSigV4AuthDraft, BasicAuthDraft, BearerAuthDraft,
+        DigestAuthDraft, OAuth2AuthDraft, OAuth2Grant, RequestAuthDraft, RequestDraft,
     },
 };
 use crate::foundation::{I18n, validation_message};
@@ -29,6 +29,9 @@ enum AuthKind {
     Basic,
     Bearer,
     ApiKey,
+    Digest,
+    OAuth2,
+    AwsSigV4,
 }
 
 #[derive(Clone)]
@@ -55,6 +58,9 @@ fn auth_kind(value: &RequestAuthDraft) -> AuthKind {
         RequestAuthDraft::Basic(_) => AuthKind::Basic,
         RequestAuthDraft::Bearer(_) => AuthKind::Bearer,
         RequestAuthDraft::ApiKey(_) => AuthKind::ApiKey,
+        RequestAuthDraft::Digest(_) => AuthKind::Digest,
+        RequestAuthDraft::OAuth2(_) => AuthKind::OAuth2,
+        RequestAuthDraft::AwsSigV4(_) => AuthKind::AwsSigV4,
     }
 }
 
@@ -64,6 +70,9 @@ fn build_auth(kind: AuthKind) -> RequestAuthDraft {
         AuthKind::Basic => RequestAuthDraft::basic(),
         AuthKind::Bearer => RequestAuthDraft::bearer(),
         AuthKind::ApiKey => RequestAuthDraft::api_key(),
+        AuthKind::Digest => RequestAuthDraft::digest(),
+        AuthKind::OAuth2 => RequestAuthDraft::oauth2(),
+        AuthKind::AwsSigV4 => RequestAuthDraft::aws_sigv4(),
     }
 }
 
@@ -85,6 +94,56 @@ impl SelectItem for ApiKeyLocationOption {
     }
 }
 
+#[derive(Clone)]
+struct OAuth2GrantOption {
+    grant: OAuth2Grant,
+    title: SharedString,
+}
+
+impl SelectItem for OAuth2GrantOption {
+    type Value = OAuth2Grant;
+
+    fn title(&self) -> SharedString {
+        self.title.clone()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.grant
+    }
+}
+
+/// One bound text input of a worker-resolved scheme. Secret values are
+/// masked and get a reveal toggle.
+struct AuthTextField {
+    path: DynamicPath<RequestDraft, String>,
+    input: FormInput,
+    masked: bool,
+}
+
+impl AuthTextField {
+    fn bind(
+        form: &Entity<Form<RequestDraft>>,
+        path: DynamicPath<RequestDraft, String>,
+        masked: bool,
+        window: &mut Window,
+        cx: &mut Context<AuthView>,
+    ) -> Self {
+        let input = FormInput::try_new(
+            form,
+            path.clone(),
+            move |window, cx| InputState::new(window, cx).masked(masked),
+            window,
+            cx,
+        )
+        .expect("active auth text field must bind");
+        Self {
+            path,
+            input,
+            masked,
+        }
+    }
+}
+
 enum AuthFields {
     None,
     Basic {
@@ -105,6 +164,29 @@ enum AuthFields {
         location_path: DynamicPath<RequestDraft, ApiKeyLocation>,
         location: FormScalarSelect<RequestDraft, Vec<ApiKeyLocationOption>, ApiKeyLocation>,
     },
+    Digest {
+        username: AuthTextField,
+        password: AuthTextField,
+    },
+    OAuth2 {
+        grant_path: DynamicPath<RequestDraft, OAuth2Grant>,
+        grant: FormScalarSelect<RequestDraft, Vec<OAuth2GrantOption>, OAuth2Grant>,
+        token_url: AuthTextField,
+        client_id: AuthTextField,
+        client_secret: AuthTextField,
+        scope: AuthTextField,
+        username: AuthTextField,
+        password: AuthTextField,
+        authorization_url: AuthTextField,
+        redirect_uri: AuthTextField,
+    },
+    AwsSigV4 {
+        access_key_id: AuthTextField,
+        secret_access_key: AuthTextField,
+        session_token: AuthTextField,
+        region: AuthTextField,
+        service: AuthTextField,
+    },
 }
 
 pub(super) struct AuthView {
@@ -243,6 +325,80 @@ impl AuthView {
                     location,
                 }
             }
+            RequestAuthDraft::Digest(_) => {
+                let digest = RequestDraft::AUTH
+                    .case(RequestAuthDraft::DIGEST)
+                    .resolve(form, cx)
+                    .expect("the auth path must resolve")
+                    .expect("Digest payload must be active");
+                AuthFields::Digest {
+                    username: AuthTextField::bind(
+                        form,
+                        digest.clone().then(DigestAuthDraft::USERNAME),
+                        false,
+                        window,
+                        cx,
+                    ),
+                    password: AuthTextField::bind(
+                        form,
+                        digest.then(DigestAuthDraft::PASSWORD),
+                        true,
+                        window,
+                        cx,
+                    ),
+                }
+            }
+            RequestAuthDraft::OAuth2(_) => {
+                let oauth2 = RequestDraft::AUTH
+                    .case(RequestAuthDraft::O_AUTH2)
+                    .resolve(form, cx)
+                    .expect("the auth path must resolve")
+                    .expect("OAuth 2.0 payload must be active");
+                let grant_path = oauth2.clone().then(OAuth2AuthDraft::GRANT);
+                let grant_options = oauth2_grant_options(cx);
+                let grant = FormScalarSelect::try_new(
+                    form,
+                    grant_path.clone(),
+                    |window, cx| {
+                        SelectState::new(grant_options, Some(IndexPath::default()), window, cx)
+                    },
+                    window,
+                    cx,
+                )
+                .expect("active OAuth 2.0 grant must bind");
+                let mut text = |field: FieldDef<OAuth2AuthDraft, String>, masked| {
+                    AuthTextField::bind(form, oauth2.clone().then(field), masked, window, cx)
+                };
+                AuthFields::OAuth2 {
+                    token_url: text(OAuth2AuthDraft::TOKEN_URL, false),
+                    client_id: text(OAuth2AuthDraft::CLIENT_ID, false),
+                    client_secret: text(OAuth2AuthDraft::CLIENT_SECRET, true),
+                    scope: text(OAuth2AuthDraft::SCOPE, false),
+                    username: text(OAuth2AuthDraft::USERNAME, false),
+                    password: text(OAuth2AuthDraft::PASSWORD, true),
+                    authorization_url: text(OAuth2AuthDraft::AUTHORIZATION_URL, false),
+                    redirect_uri: text(OAuth2AuthDraft::REDIRECT_URI, false),
+                    grant_path,
+                    grant,
+                }
+            }
+            RequestAuthDraft::AwsSigV4(_) => {
+                let aws = RequestDraft::AUTH
+                    .case(RequestAuthDraft::AWS_SIG_V4)
+                    .resolve(form, cx)
+                    .expect("the auth path must resolve")
+                    .expect("AWS Signature payload must be active");
+                let mut text = |field: FieldDef<AwsSigV4AuthDraft, String>, masked| {
+                    AuthTextField::bind(form, aws.clone().then(field), masked, window, cx)
+                };
+                AuthFields::AwsSigV4 {
+                    access_key_id: text(AwsSigV4AuthDraft::ACCESS_KEY_ID, false),
+                    secret_access_key: text(AwsSigV4AuthDraft::SECRET_ACCESS_KEY, true),
+                    session_token: text(AwsSigV4AuthDraft::SESSION_TOKEN, true),
+                    region: text(AwsSigV4AuthDraft::REGION, false),
+                    service: text(AwsSigV4AuthDraft::SERVICE, false),
+                }
+            }
         }
     }
 
@@ -270,6 +426,26 @@ impl AuthView {
             .map(|issue| validation_message(issue.message(), cx))
     }
 
+    fn text_field(
+        &self,
+        label: String,
+        text: &AuthTextField,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        field().label(label).child(self.text_control(text, cx))
+    }
+
+    fn text_control(&self, text: &AuthTextField, cx: &mut Context<Self>) -> AnyElement {
+        let input = Input::new(&text.input);
+        let input = if text.masked {
+            input.content_type(InputContentType::Password).mask_toggle()
+        } else {
+            input
+        };
+        let error = self.error(&text.path, cx);
+        self.control_with_error(input, error, cx)
+    }
+
     fn control_with_error(
         &self,
         control: impl IntoElement,
@@ -294,6 +470,9 @@ fn auth_options(cx: &gpui::App) -> Vec<AuthOption> {
         (AuthKind::Basic, "auth-basic"),
         (AuthKind::Bearer, "auth-bearer"),
         (AuthKind::ApiKey, "auth-api-key"),
+        (AuthKind::Digest, "auth-digest"),
+        (AuthKind::OAuth2, "auth-oauth2"),
+        (AuthKind::AwsSigV4, "auth-aws-sigv4"),
     ]
     .into_iter()
     .map(|(kind, key)| AuthOption {
@@ -317,6 +496,27 @@ fn api_key_location_options(cx: &gpui::App) -> Vec<ApiKeyLocationOption> {
     .collect()
 }
 
+fn oauth2_grant_options(cx: &gpui::App) -> Vec<OAuth2GrantOption> {
+    let i18n = cx.global::<I18n>();
+    [
+        (
+            OAuth2Grant::ClientCredentials,
+            "auth-oauth2-grant-client-credentials",
+        ),
+        (OAuth2Grant::Password, "auth-oauth2-grant-password"),
+        (
+            OAuth2Grant::AuthorizationCode,
+            "auth-oauth2-grant-authorization-code",
+        ),
+    ]
+    .into_iter()
+    .map(|(grant, key)| OAuth2GrantOption {
+        grant,
+        title: i18n.t(key).into(),
+    })
+    .collect()
+}
+
 impl gpui::Render for AuthView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let (name, value, username, password, token, location_label, override_hint) = {
@@ -412,6 +612,100 @@ impl gpui::Render for AuthView {
                     .child(field().label(location_label).child(location.element()))
                     .into_any_element()
             }
+            AuthFields::Digest {
+                username: username_field,
+                password: password_field,
+            } => v_form()
+                .child(self.text_field(username, username_field, cx))
+                .child(self.text_field(password, password_field, cx))
+                .into_any_element(),
+            AuthFields::OAuth2 {
+                grant_path,
+                grant,
+                token_url,
+                client_id,
+                client_secret,
+                scope,
+                username: username_field,
+                password: password_field,
+                authorization_url,
+                redirect_uri,
+            } => {
+                let grant_value = grant_path
+                    .try_get(&self.form, cx)
+                    .unwrap_or(OAuth2Grant::ClientCredentials);
+                let i18n = cx.global::<I18n>();
+                let (
+                    grant_label,
+                    token_url_label,
+                    client_id_label,
+                    client_secret_label,
+                    scope_label,
+                    authorization_url_label,
+                    redirect_uri_label,
+                    redirect_help,
+                ) = (
+                    i18n.t("field-grant-type"),
+                    i18n.t("field-token-url"),
+                    i18n.t("field-client-id"),
+                    i18n.t("field-client-secret"),
+                    i18n.t("field-scope"),
+                    i18n.t("field-authorization-url"),
+                    i18n.t("field-redirect-uri"),
+                    i18n.t("auth-oauth2-redirect-help"),
+                );
+                v_form()
+                    .child(field().label(grant_label).child(grant.element()))
+                    .when(grant_value == OAuth2Grant::AuthorizationCode, |this| {
+                        this.child(self.text_field(authorization_url_label, authorization_url, cx))
+                    })
+                    .child(self.text_field(token_url_label, token_url, cx))
+                    .child(self.text_field(client_id_label, client_id, cx))
+                    .child(self.text_field(client_secret_label, client_secret, cx))
+                    .when(grant_value == OAuth2Grant::Password, |this| {
+                        this.child(self.text_field(username, username_field, cx))
+                            .child(self.text_field(password, password_field, cx))
+                    })
+                    .when(grant_value == OAuth2Grant::AuthorizationCode, |this| {
+                        this.child(
+                            field()
+                                .label(redirect_uri_label)
+                                .description(redirect_help)
+                                .child(self.text_control(redirect_uri, cx)),
+                        )
+                    })
+                    .child(self.text_field(scope_label, scope, cx))
+                    .into_any_element()
+            }
+            AuthFields::AwsSigV4 {
+                access_key_id,
+                secret_access_key,
+                session_token,
+                region,
+                service,
+            } => {
+                let i18n = cx.global::<I18n>();
+                let (
+                    access_key_id_label,
+                    secret_access_key_label,
+                    session_token_label,
+                    region_label,
+                    service_label,
+                ) = (
+                    i18n.t("field-access-key-id"),
+                    i18n.t("field-secret-access-key"),
+                    i18n.t("field-session-token"),
+                    i18n.t("field-region"),
+                    i18n.t("field-service"),
+                );
+                v_form()
+                    .child(self.text_field(access_key_id_label, access_key_id, cx))
+                    .child(self.text_field(secret_access_key_label, secret_access_key, cx))
+                    .child(self.text_field(session_token_label, session_token, cx))
+                    .child(self.text_field(region_label, region, cx))
+                    .child(self.text_field(service_label, service, cx))
+                    .into_any_element()
+            }
         };
 
         v_flex()
@@ -21,12 +21,16 @@ use crate::{
 };
 
 use self::{
-    binary::BinaryBodyView, form_data::FormDataView, http_text::HttpTextView,
+    binary::BinaryBodyView, form_data::FormDataView, graphql::GraphQlView, http_text::HttpTextView,
     x_form::UrlEncodedView,
 };
 
+pub(super) use self::binary::file_labels;
+pub(crate) use self::graphql::SchemaSource;
+
 mod binary;
 mod form_data;
+mod graphql;
 mod http_text;
 mod x_form;
 
@@ -37,6 +41,7 @@ pub(crate) enum BodyKind {
     UrlEncoded,
     Text,
     Binary,
+    GraphQl,
 }
 
 #[derive(Clone)]
@@ -65,11 +70,12 @@ enum ActiveBody {
     UrlEncoded(Entity<UrlEncodedView>),
     Text(Entity<HttpTextView>),
     Binary(Entity<BinaryBodyView>),
+    GraphQl(Entity<GraphQlView>),
 }
 
 impl ActiveBody {
     fn is_text(&self) -> bool {
-        matches!(self, Self::Text(_))
+        matches!(self, Self::Text(_) | Self::GraphQl(_))
     }
 
     fn element(&self) -> AnyElement {
@@ -79,6 +85,7 @@ impl ActiveBody {
             Self::UrlEncoded(view) => view.clone().into_any_element(),
             Self::Text(view) => view.clone().into_any_element(),
             Self::Binary(view) => view.clone().into_any_element(),
+            Self::GraphQl(view) => view.clone().into_any_element(),
         }
     }
 }
@@ -87,6 +94,7 @@ pub(crate) struct HttpBodyView {
     form: Entity<Form<RequestDraft>>,
     body_kind: BodyKind,
     body_select: FormCaseSelect<RequestDraft, BodyOptions, RequestBodyDraft, BodyKind>,
+    schema_source: SchemaSource,
     active: ActiveBody,
     _subscription: Subscription,
 }
@@ -94,6 +102,7 @@ pub(crate) struct HttpBodyView {
 impl HttpBodyView {
     pub(crate) fn new(
         form: Entity<Form<RequestDraft>>,
+        schema_source: SchemaSource,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
@@ -108,7 +117,7 @@ impl HttpBodyView {
             cx,
         );
         let initial_body_kind = body_kind(&RequestDraft::BODY.get(&form, cx));
-        let active = build_active_body(&form, initial_body_kind, window, cx);
+        let active = build_active_body(&form, initial_body_kind, &schema_source, window, cx);
         let subscription = cx.subscribe_in(
             &form,
             window,
@@ -122,7 +131,8 @@ impl HttpBodyView {
                 let kind = body_kind(&RequestDraft::BODY.get(&this.form, cx));
                 if kind != this.body_kind {
                     this.body_kind = kind;
-                    this.active = build_active_body(&this.form, kind, window, cx);
+                    this.active =
+                        build_active_body(&this.form, kind, &this.schema_source, window, cx);
                     cx.notify();
                 }
             },
@@ -131,6 +141,7 @@ impl HttpBodyView {
             form,
             body_kind: initial_body_kind,
             body_select,
+            schema_source,
             active,
             _subscription: subscription,
         }
@@ -182,6 +193,7 @@ fn body_kind(body: &RequestBodyDraft) -> BodyKind {
         RequestBodyDraft::UrlEncoded(_) => BodyKind::UrlEncoded,
         RequestBodyDraft::Text(_) => BodyKind::Text,
         RequestBodyDraft::Binary(_) => BodyKind::Binary,
+        RequestBodyDraft::GraphQl(_) => BodyKind::GraphQl,
     }
 }
 
@@ -192,6 +204,7 @@ fn body_for_kind(kind: BodyKind) -> RequestBodyDraft {
         BodyKind::UrlEncoded => RequestBodyDraft::url_encoded(),
         BodyKind::Text => RequestBodyDraft::text(),
         BodyKind::Binary => RequestBodyDraft::binary(),
+        BodyKind::GraphQl => RequestBodyDraft::graphql(),
     }
 }
 
@@ -203,6 +216,7 @@ fn body_options(cx: &gpui::App) -> BodyOptions {
         (BodyKind::UrlEncoded, "body-urlencoded"),
         (BodyKind::Text, "body-text"),
         (BodyKind::Binary, "body-binary"),
+        (BodyKind::GraphQl, "body-graphql"),
     ]
     .into_iter()
     .map(|(kind, key)| BodyOption {
@@ -215,6 +229,7 @@ fn body_options(cx: &gpui::App) -> BodyOptions {
 fn build_active_body<Owner: 'static>(
     form: &Entity<Form<RequestDraft>>,
     kind: BodyKind,
+    schema_source: &SchemaSource,
     window: &mut Window,
     cx: &mut Context<Owner>,
 ) -> ActiveBody {
@@ -253,6 +268,16 @@ fn build_active_body<Owner: 'static>(
             .map(|path| cx.new(|cx| BinaryBodyView::new(form.clone(), path, window, cx)))
             .map(ActiveBody::Binary)
             .unwrap_or(ActiveBody::None),
+        BodyKind::GraphQl => body
+            .case(RequestBodyDraft::GRAPH_QL)
+            .resolve(form, cx)
+            .ok()
+            .flatten()
+            .map(|path| {
+                cx.new(|cx| GraphQlView::new(form.clone(), path, schema_source.clone(), window, cx))
+            })
+            .map(ActiveBody::GraphQl)
+            .unwrap_or(ActiveBody::None),
     }
 }
 
@@ -267,9 +292,11 @@ mod tests {
     use crate::{
         features::request::{
             draft::{
-                BinaryBodyDraft, FormDataDraft, KeyValueDraft, MultipartPartDraft,
-                MultipartPartValueDraft, MultipartTextDraft, TextBodyDraft, UrlEncodedBodyDraft,
+                BinaryBodyDraft, FormDataDraft, GraphQlBodyDraft, KeyValueDraft,
+                MultipartPartDraft, MultipartPartValueDraft, MultipartTextDraft, TextBodyDraft,
+                UrlEncodedBodyDraft,
             },
+            transport::HttpTransport,
             validation::RequestValidator,
         },
         foundation::i18n::init_i18n,
@@ -289,6 +316,10 @@ mod tests {
             for body in [
                 RequestBodyDraft::None,
                 RequestBodyDraft::Text(TextBodyDraft::default()),
+                RequestBodyDraft::GraphQl(GraphQlBodyDraft {
+                    query: "{ a }".into(),
+                    ..GraphQlBodyDraft::default()
+                }),
                 RequestBodyDraft::UrlEncoded(UrlEncodedBodyDraft {
                     fields: vec![KeyValueDraft::default()],
                 }),
@@ -346,7 +377,12 @@ mod tests {
             let view_form = form.clone();
             let window = cx
                 .open_window(Default::default(), move |window, cx| {
-                    cx.new(|cx| HttpBodyView::new(view_form, window, cx))
+                    let schema_source = SchemaSource::new(
+                        HttpTransport::new_without_proxy(),
+                        Default::default(),
+                        Default::default(),
+                    );
+                    cx.new(|cx| HttpBodyView::new(view_form, schema_source, window, cx))
                 })
                 .expect("open Body test window");
             (form, window)
@@ -58,7 +58,7 @@ impl Render for BinaryBodyView {
     }
 }
 
-pub(super) fn file_labels(cx: &gpui::App) -> FilePathLabels {
+pub(crate) fn file_labels(cx: &gpui::App) -> FilePathLabels {
     let i18n = cx.global::<I18n>();
     FilePathLabels {
         select: i18n.t("button-select-file").into(),
@@ -0,0 +1,499 @@
+use std::{cell::RefCell, rc::Rc};
+
+use fluent_bundle::FluentArgs;
+use gpui::{
+    App, AppContext as _, Context, Entity, IntoElement, ParentElement as _, Render, SharedString,
+    Styled as _, Subscription, Task, Window, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _,
+    button::Button,
+    h_flex,
+    input::{CompletionProvider, Input, InputEvent, InputState, Rope},
+    label::Label,
+    select::{SelectItem, SelectState},
+    v_flex,
+};
+use gpui_form::{DynamicPath, Form};
+use gpui_form_gpui_component::FormInput;
+use lsp_types::{
+    CompletionContext, CompletionItem, CompletionItemKind, CompletionResponse, CompletionTextEdit,
+    Documentation, Position, TextEdit,
+};
+
+use crate::{
+    features::request::{
+        controls::FormScalarSelect,
+        draft::{GraphQlBodyDraft, HttpClientTransportSettings, RequestDraft},
+        graphql::{
+            self, CompletionKind, GraphQlDiagnostic, GraphQlProblem, GraphQlSchema,
+            IntrospectionError, SchemaFetchError, SyntaxExpectation,
+        },
+        response::problem_message,
+        transport::HttpTransport,
+        variables::RequestVariables,
+    },
+    foundation::I18n,
+};
+
+/// What a GraphQL body needs to introspect the endpoint it is sent to.
+#[derive(Clone)]
+pub(crate) struct SchemaSource {
+    transport: HttpTransport,
+    settings: HttpClientTransportSettings,
+    variables: RequestVariables,
+}
+
+impl SchemaSource {
+    pub(crate) fn new(
+        transport: HttpTransport,
+        settings: HttpClientTransportSettings,
+        variables: RequestVariables,
+    ) -> Self {
+        Self {
+            transport,
+            settings,
+            variables,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct OperationOption {
+    name: String,
+    title: SharedString,
+}
+
+impl SelectItem for OperationOption {
+    type Value = String;
+
+    fn title(&self) -> SharedString {
+        self.title.clone()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.name
+    }
+}
+
+type OperationOptions = Vec<OperationOption>;
+
+enum SchemaStatus {
+    None,
+    Loading(Task<()>),
+    Loaded(usize),
+    Failed(String),
+}
+
+pub(super) struct GraphQlView {
+    form: Entity<Form<RequestDraft>>,
+    source: SchemaSource,
+    operation_path: DynamicPath<RequestDraft, String>,
+    query: FormInput,
+    variables: FormInput,
+    operation_names: Vec<String>,
+    operation: Option<FormScalarSelect<RequestDraft, OperationOptions, String>>,
+    schema: Rc<RefCell<Option<GraphQlSchema>>>,
+    status: SchemaStatus,
+    diagnostics: Vec<GraphQlDiagnostic>,
+    _query_subscription: Subscription,
+}
+
+impl GraphQlView {
+    pub(super) fn new(
+        form: Entity<Form<RequestDraft>>,
+        graphql: DynamicPath<RequestDraft, GraphQlBodyDraft>,
+        source: SchemaSource,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let schema = Rc::new(RefCell::new(None));
+        let completions = Rc::new(GraphQlCompletions {
+            schema: schema.clone(),
+        });
+        let query = FormInput::try_new(
+            &form,
+            graphql.clone().then(GraphQlBodyDraft::QUERY),
+            move |window, cx| {
+                let mut state = InputState::new(window, cx)
+                    .multi_line(true)
+                    .code_editor("graphql")
+                    .line_number(true)
+                    .searchable(true);
+                state.lsp.completion_provider = Some(completions);
+                state
+            },
+            window,
+            cx,
+        )
+        .expect("the GraphQL case was resolved immediately before its controls were built");
+        let variables = FormInput::try_new(
+            &form,
+            graphql.clone().then(GraphQlBodyDraft::VARIABLES),
+            |window, cx| {
+                InputState::new(window, cx)
+                    .multi_line(true)
+                    .code_editor("json")
+                    .line_number(true)
+            },
+            window,
+            cx,
+        )
+        .expect("the GraphQL case was resolved immediately before its controls were built");
+        let query_subscription = cx.subscribe_in(
+            &*query,
+            window,
+            |this, _, event: &InputEvent, window, cx| {
+                if matches!(event, InputEvent::Change) {
+                    this.analyze(window, cx);
+                }
+            },
+        );
+        let mut this = Self {
+            form,
+            source,
+            operation_path: graphql.then(GraphQlBodyDraft::OPERATION_NAME),
+            query,
+            variables,
+            operation_names: Vec::new(),
+            operation: None,
+            schema,
+            status: SchemaStatus::None,
+            diagnostics: Vec::new(),
+            _query_subscription: query_subscription,
+        };
+        this.analyze(window, cx);
+        this
+    }
+
+    /// Re-reads the document after an edit: its diagnostics, and the
+    /// operation picker when the named operations changed.
+    fn analyze(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let query = self.query.read(cx).value();
+        self.diagnostics = graphql::validate(&query, self.schema.borrow().as_ref());
+        let operations = graphql::operations(&query);
+        let names = if operations.len() > 1 {
+            operations
+                .into_iter()
+                .filter_map(|operation| operation.name)
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if names != self.operation_names {
+            let options = names
+                .iter()
+                .map(|name| OperationOption {
+                    name: name.clone(),
+                    title: name.clone().into(),
+                })
+                .collect::<OperationOptions>();
+            self.operation = if options.is_empty() {
+                None
+            } else {
+                FormScalarSelect::try_new(
+                    &self.form,
+                    self.operation_path.clone(),
+                    move |window, cx| SelectState::new(options, None, window, cx),
+                    window,
+                    cx,
+                )
+                .ok()
+            };
+            self.operation_names = names;
+        }
+        cx.notify();
+    }
+
+    fn fetch_schema(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if matches!(self.status, SchemaStatus::Loading(_)) {
+            return;
+        }
+        let draft = self
+            .source
+            .variables
+            .resolve(RequestDraft::ROOT.get(&self.form, cx));
+        let prepared = match graphql::prepare_introspection(draft, &self.source.settings) {
+            Ok(prepared) => prepared,
+            Err(error) => {
+                self.status = SchemaStatus::Failed(schema_error_message(&error.into(), cx));
+                cx.notify();
+                return;
+            }
+        };
+        let fetch = gpui_tokio::Tokio::spawn(
+            cx,
+            graphql::fetch_schema(self.source.transport.clone(), prepared),
+        );
+        let owner = cx.entity().downgrade();
+        let task = window.spawn(cx, async move |cx| {
+            let result = fetch.await;
+            let _ = owner.update_in(cx, |this, window, cx| {
+                this.status = match result {
+                    Ok(Ok(schema)) => {
+                        let count = schema.types.len();
+                        *this.schema.borrow_mut() = Some(schema);
+                        SchemaStatus::Loaded(count)
+                    }
+                    Ok(Err(error)) => SchemaStatus::Failed(schema_error_message(&error, cx)),
+                    Err(_) => {
+                        SchemaStatus::Failed(cx.global::<I18n>().t("request-problem-internal"))
+                    }
+                };
+                this.analyze(window, cx);
+            });
+        });
+        self.status = SchemaStatus::Loading(task);
+        cx.notify();
+    }
+}
+
+impl Render for GraphQlView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let query = self.query.read(cx).value();
+        let (query_label, variables_label, operation_label, fetch_label, status) = {
+            let i18n = cx.global::<I18n>();
+            let status = match &self.status {
+                SchemaStatus::None => i18n.t("graphql-schema-none"),
+                SchemaStatus::Loading(_) => i18n.t("graphql-schema-loading"),
+                SchemaStatus::Loaded(count) => {
+                    let mut args = FluentArgs::new();
+                    args.set("count", *count);
+                    i18n.t_with_args("graphql-schema-loaded", &args)
+                }
+                SchemaStatus::Failed(message) => message.clone(),
+            };
+            (
+                i18n.t("graphql-query"),
+                i18n.t("graphql-variables"),
+                i18n.t("graphql-operation"),
+                i18n.t("graphql-fetch-schema"),
+                status,
+            )
+        };
+        let diagnostics = self
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let (line, column) = line_column(&query, diagnostic.range.start);
+                format!("{line}:{column} {}", problem_text(&diagnostic.problem, cx))
+            })
+            .collect::<Vec<_>>();
+        let failed = matches!(self.status, SchemaStatus::Failed(_));
+        let editor = |state: &FormInput| {
+            Input::new(state)
+                .size_full()
+                .font_family(cx.theme().mono_font_family.clone())
+        };
+
+        v_flex()
+            .p_2()
+            .gap_2()
+            .flex_1()
+            .min_h(px(0.))
+            .child(
+                h_flex()
+                    .flex_shrink_0()
+                    .gap_2()
+                    .child(
+                        Button::new("graphql-fetch-schema")
+                            .label(fetch_label)
+                            .disabled(matches!(self.status, SchemaStatus::Loading(_)))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.fetch_schema(window, cx);
+                            })),
+                    )
+                    .child(Label::new(status).text_sm().text_color(if failed {
+                        cx.theme().danger
+                    } else {
+                        cx.theme().muted_foreground
+                    }))
+                    .when_some(self.operation.as_ref(), |this, operation| {
+                        this.child(Label::new(operation_label).text_sm())
+                            .child(div().w(px(200.)).child(operation.element()))
+                    }),
+            )
+            .child(Label::new(query_label).text_sm())
+            .child(
+                div()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .overflow_hidden()
+                    .child(editor(&self.query)),
+            )
+            .children(
+                diagnostics
+                    .into_iter()
+                    .map(|message| Label::new(message).text_xs().text_color(cx.theme().danger)),
+            )
+            .child(Label::new(variables_label).text_sm())
+            .child(
+                div()
+                    .h(px(120.))
+                    .flex_shrink_0()
+                    .overflow_hidden()
+                    .child(editor(&self.variables)),
+            )
+    }
+}
+
+struct GraphQlCompletions {
+    schema: Rc<RefCell<Option<GraphQlSchema>>>,
+}
+
+impl CompletionProvider for GraphQlCompletions {
+    fn completions(
+        &self,
+        text: &Rope,
+        offset: usize,
+        _trigger: CompletionContext,
+        _window: &mut Window,
+        _cx: &mut Context<InputState>,
+    ) -> Task<anyhow::Result<CompletionResponse>> {
+        let source = text.to_string();
+        let (range, completions) =
+            graphql::complete(&source, offset, self.schema.borrow().as_ref());
+        let range = lsp_types::Range {
+            start: position(&source, range.start),
+            end: position(&source, range.end),
+        };
+        let items = completions
+            .into_iter()
+            .map(|completion| CompletionItem {
+                kind: Some(match completion.kind {
+                    CompletionKind::Keyword => CompletionItemKind::KEYWORD,
+                    CompletionKind::Field => CompletionItemKind::FIELD,
+                    CompletionKind::Argument => CompletionItemKind::PROPERTY,
+                    CompletionKind::Type => CompletionItemKind::CLASS,
+                }),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: completion.label.clone(),
+                })),
+                detail: completion.detail,
+                documentation: completion.documentation.map(Documentation::String),
+                label: completion.label,
+                ..CompletionItem::default()
+            })
+            .collect();
+        Task::ready(Ok(CompletionResponse::Array(items)))
+    }
+
+    fn is_completion_trigger(
+        &self,
+        _offset: usize,
+        new_text: &str,
+        _cx: &mut Context<InputState>,
+    ) -> bool {
+        new_text
+            .chars()
+            .any(|character| character == '_' || character.is_ascii_alphanumeric())
+    }
+}
+
+/// An LSP position, which counts columns in UTF-16 units.
+fn position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].encode_utf16().count() as u32,
+    }
+}
+
+/// One-based line and column, as shown next to a diagnostic.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+fn schema_error_message(error: &SchemaFetchError, cx: &App) -> String {
+    let i18n = cx.global::<I18n>();
+    match error {
+        SchemaFetchError::Prepare(_) => i18n.t("graphql-schema-prepare"),
+        SchemaFetchError::Request(problem) => problem_message(problem.kind(), cx),
+        SchemaFetchError::AuthorizationRequired => i18n.t("graphql-schema-authorization"),
+        SchemaFetchError::Status(status) => {
+            let mut args = FluentArgs::new();
+            args.set("status", status.as_u16());
+            i18n.t_with_args("graphql-schema-status", &args)
+        }
+        SchemaFetchError::NotJson => i18n.t("graphql-schema-not-json"),
+        SchemaFetchError::Introspection(IntrospectionError::Rejected(message)) => {
+            let mut args = FluentArgs::new();
+            args.set("message", message.clone());
+            i18n.t_with_args("graphql-schema-rejected", &args)
+        }
+        SchemaFetchError::Introspection(IntrospectionError::MissingSchema) => {
+            i18n.t("graphql-schema-missing")
+        }
+        SchemaFetchError::Introspection(IntrospectionError::Malformed) => {
+            i18n.t("graphql-schema-malformed")
+        }
+    }
+}
+
+fn problem_text(problem: &GraphQlProblem, cx: &App) -> String {
+    let i18n = cx.global::<I18n>();
+    let mut args = FluentArgs::new();
+    let key = match problem {
+        GraphQlProblem::Syntax(SyntaxExpectation::Definition) => "graphql-expected-definition",
+        GraphQlProblem::Syntax(SyntaxExpectation::Name) => "graphql-expected-name",
+        GraphQlProblem::Syntax(SyntaxExpectation::Punctuator(punctuator)) => {
+            args.set("punctuator", punctuator.to_string());
+            "graphql-expected-punctuator"
+        }
+        GraphQlProblem::Syntax(SyntaxExpectation::Value) => "graphql-expected-value",
+        GraphQlProblem::Syntax(SyntaxExpectation::End) => "graphql-expected-end",
+        GraphQlProblem::AnonymousOperationNotAlone => "graphql-anonymous-operation",
+        GraphQlProblem::DuplicateOperation(name) => {
+            args.set("name", name.clone());
+            "graphql-duplicate-operation"
+        }
+        GraphQlProblem::DuplicateFragment(name) => {
+            args.set("name", name.clone());
+            "graphql-duplicate-fragment"
+        }
+        GraphQlProblem::UnknownFragment(name) => {
+            args.set("name", name.clone());
+            "graphql-unknown-fragment"
+        }
+        GraphQlProblem::UndefinedVariable(name) => {
+            args.set("name", name.clone());
+            "graphql-undefined-variable"
+        }
+        GraphQlProblem::UnsupportedOperation(kind) => {
+            args.set("operation", kind.keyword());
+            "graphql-unsupported-operation"
+        }
+        GraphQlProblem::UnknownType(name) => {
+            args.set("name", name.clone());
+            "graphql-unknown-type"
+        }
+        GraphQlProblem::UnknownField { parent, field } => {
+            args.set("type", parent.clone());
+            args.set("field", field.clone());
+            "graphql-unknown-field"
+        }
+        GraphQlProblem::UnknownArgument { field, argument } => {
+            args.set("field", field.clone());
+            args.set("argument", argument.clone());
+            "graphql-unknown-argument"
+        }
+        GraphQlProblem::SelectionRequired { field, ty } => {
+            args.set("field", field.clone());
+            args.set("type", ty.clone());
+            "graphql-selection-required"
+        }
+        GraphQlProblem::SelectionNotAllowed { field, ty } => {
+            args.set("field", field.clone());
+            args.set("type", ty.clone());
+            "graphql-selection-not-allowed"
+        }
+    };
+    i18n.t_with_args(key, &args)
+}
@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use gpui::{
+    AnyElement, App, AppContext as _, Context, ElementId, Entity, IntoElement, ParentElement as _,
+    Render, SharedString, Styled as _, Window, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _,
+    button::Button,
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+    separator::Separator,
+    v_flex,
+};
+use gpui_form::{DynamicPath, Form, PathKey};
+
+use self::{assertions::AssertionsView, extractions::ExtractionsView};
+use super::{
+    draft::{AssertionKind, RequestDraft},
+    variables::RequestVariables,
+};
+use crate::foundation::{I18n, i18n::validation_message};
+
+mod assertions;
+mod evaluate;
+mod extractions;
+
+pub(crate) use evaluate::{
+    AssertionOutcome, CheckFailure, CheckReport, ExtractionOutcome, ResponseChecks, compile_checks,
+    parse_json_path, parse_pattern, parse_status_range, parse_time_limit, run_checks,
+};
+
+/// The request's Tests tab: assertions, extractions and the variables they
+/// have produced so far.
+pub(super) struct ChecksView {
+    assertions: Entity<AssertionsView>,
+    extractions: Entity<ExtractionsView>,
+    variables: RequestVariables,
+}
+
+impl ChecksView {
+    pub(super) fn new(
+        form: Entity<Form<RequestDraft>>,
+        variables: RequestVariables,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let assertions = cx.new(|cx| AssertionsView::new(form.clone(), window, cx));
+        let extractions = cx.new(|cx| ExtractionsView::new(form, window, cx));
+        Self {
+            assertions,
+            extractions,
+            variables,
+        }
+    }
+}
+
+impl Render for ChecksView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let entries = self.variables.entries();
+        let (title, hint, empty, clear_label) = {
+            let i18n = cx.global::<I18n>();
+            (
+                i18n.t("checks-variables"),
+                i18n.t("checks-variables-hint"),
+                i18n.t("checks-variables-empty"),
+                i18n.t("button-clear-variables"),
+            )
+        };
+        let clear = (!entries.is_empty()).then(|| {
+            Button::new("checks-clear-variables")
+                .label(clear_label)
+                .on_click(cx.listener(|this, _, _, cx| {
+                    this.variables.clear();
+                    cx.notify();
+                }))
+        });
+
+        v_flex()
+            .p_2()
+            .gap_3()
+            .child(self.assertions.clone())
+            .child(Separator::horizontal())
+            .child(self.extractions.clone())
+            .child(Separator::horizontal())
+            .child(
+                h_flex()
+                    .gap_2()
+                    .justify_between()
+                    .child(Label::new(title))
+                    .children(clear),
+            )
+            .child(
+                Label::new(hint)
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .when(entries.is_empty(), |this| {
+                this.child(
+                    Label::new(empty)
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground),
+                )
+            })
+            .children(entries.into_iter().map(|(name, value)| {
+                h_flex()
+                    .gap_2()
+                    .child(div().w(px(200.)).child(Label::new(name)))
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w(px(0.))
+                            .font_family(cx.theme().mono_font_family.clone())
+                            .child(Label::new(value)),
+                    )
+            }))
+    }
+}
+
+pub(super) const fn assertion_kind_key(kind: AssertionKind) -> &'static str {
+    match kind {
+        AssertionKind::StatusRange => "checks-kind-status",
+        AssertionKind::HeaderPresent => "checks-kind-header-present",
+        AssertionKind::HeaderMatches => "checks-kind-header-matches",
+        AssertionKind::JsonPathEquals => "checks-kind-json-equals",
+        AssertionKind::JsonPathMatches => "checks-kind-json-matches",
+        AssertionKind::ResponseTimeBelow => "checks-kind-time-below",
+    }
+}
+
+fn input_with_error(
+    input: Entity<InputState>,
+    error: Option<SharedString>,
+    cx: &App,
+) -> AnyElement {
+    v_flex()
+        .flex_1()
+        .gap_1()
+        .child(Input::new(&input).w_full())
+        .when_some(error, |this, error| {
+            this.child(Label::new(error).text_xs().text_color(cx.theme().danger))
+        })
+        .into_any_element()
+}
+
+fn visible_dynamic_error(
+    enabled: bool,
+    path: &DynamicPath<RequestDraft, String>,
+    form: &Entity<Form<RequestDraft>>,
+    cx: &App,
+) -> Option<SharedString> {
+    if !enabled {
+        return None;
+    }
+    path.try_errors(form, cx)
+        .unwrap_or_default()
+        .first()
+        .map(|issue| validation_message(issue.message(), cx))
+}
+
+fn child_id(scope: &'static str, key: &PathKey, role: &'static str) -> ElementId {
+    ElementId::NamedChild(
+        Arc::new(ElementId::from(key)),
+        format!("{scope}-{role}").into(),
+    )
+}
@@ -0,0 +1,327 @@
+use std::collections::{HashMap, HashSet};
+
+use gpui::{
+    AnyElement, Context, Entity, InteractiveElement as _, IntoElement, ParentElement as _, Render,
+    SharedString, Styled as _, Subscription, Window, div, px,
+};
+use gpui_component::{
+    IndexPath,
+    button::Button,
+    checkbox::Checkbox,
+    h_flex,
+    input::InputState,
+    label::Label,
+    select::{SelectItem, SelectState},
+    v_flex,
+};
+use gpui_form::{Form, FormEvent, ItemPath, PathKey, TotalItemsPath};
+use gpui_form_gpui_component::FormInput;
+
+use super::{assertion_kind_key, child_id, input_with_error, visible_dynamic_error};
+use crate::{
+    features::request::{
+        controls::FormScalarSelect,
+        draft::{AssertionDraft, AssertionKind, RequestDraft},
+    },
+    foundation::I18n,
+};
+
+#[derive(Clone)]
+struct AssertionKindOption {
+    kind: AssertionKind,
+    title: SharedString,
+}
+
+impl SelectItem for AssertionKindOption {
+    type Value = AssertionKind;
+
+    fn title(&self) -> SharedString {
+        self.title.clone()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.kind
+    }
+}
+
+type AssertionKindOptions = Vec<AssertionKindOption>;
+
+struct AssertionRow {
+    item: ItemPath<RequestDraft, AssertionDraft>,
+    kind: FormScalarSelect<RequestDraft, AssertionKindOptions, AssertionKind>,
+    target: FormInput,
+    expected: FormInput,
+}
+
+impl AssertionRow {
+    fn try_new(
+        form: &Entity<Form<RequestDraft>>,
+        item: ItemPath<RequestDraft, AssertionDraft>,
+        window: &mut Window,
+        cx: &mut Context<AssertionsView>,
+    ) -> Result<Self, gpui_form::ResolveError> {
+        let options = assertion_kind_options(cx);
+        let kind = FormScalarSelect::try_new(
+            form,
+            item.clone().then(AssertionDraft::KIND),
+            |window, cx| SelectState::new(options, Some(IndexPath::default()), window, cx),
+            window,
+            cx,
+        )?;
+        let target_placeholder = cx.global::<I18n>().t("field-check-target");
+        let expected_placeholder = cx.global::<I18n>().t("field-check-expected");
+        let target = FormInput::try_new(
+            form,
+            item.clone().then(AssertionDraft::TARGET),
+            move |window, cx| InputState::new(window, cx).placeholder(target_placeholder),
+            window,
+            cx,
+        )?;
+        let expected = FormInput::try_new(
+            form,
+            item.clone().then(AssertionDraft::EXPECTED),
+            move |window, cx| InputState::new(window, cx).placeholder(expected_placeholder),
+            window,
+            cx,
+        )?;
+        Ok(Self {
+            item,
+            kind,
+            target,
+            expected,
+        })
+    }
+}
+
+/// Owns native controls for the live assertion rows.
+pub(super) struct AssertionsView {
+    form: Entity<Form<RequestDraft>>,
+    rows: HashMap<PathKey, AssertionRow>,
+    order: Vec<PathKey>,
+    retry_scheduled: bool,
+    retry_attempted: HashSet<PathKey>,
+    _subscription: Subscription,
+}
+
+impl AssertionsView {
+    pub(super) fn new(
+        form: Entity<Form<RequestDraft>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe_in(
+            &form,
+            window,
+            |this, _, event: &FormEvent<RequestDraft>, window, cx| {
+                let FormEvent::ModelChanged(change) = event else {
+                    return;
+                };
+                let impact = change.impact(&RequestDraft::ASSERTIONS);
+                if impact.structure_changed() || impact.retired() {
+                    this.retry_attempted.clear();
+                    this.reconcile(window, cx);
+                    cx.notify();
+                }
+            },
+        );
+        let mut view = Self {
+            form,
+            rows: HashMap::new(),
+            order: Vec::new(),
+            retry_scheduled: false,
+            retry_attempted: HashSet::new(),
+            _subscription: subscription,
+        };
+        view.reconcile(window, cx);
+        view
+    }
+
+    fn reconcile(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let items = RequestDraft::ASSERTIONS.items(&self.form, cx);
+        let live = items.iter().map(ItemPath::key).collect::<HashSet<_>>();
+        self.rows.retain(|key, _| live.contains(key));
+        self.retry_attempted.retain(|key| live.contains(key));
+
+        let mut failed = Vec::new();
+        for item in &items {
+            let key = item.key();
+            if self.rows.contains_key(&key) {
+                continue;
+            }
+            match AssertionRow::try_new(&self.form, item.clone(), window, cx) {
+                Ok(row) => {
+                    self.rows.insert(key, row);
+                }
+                Err(_) => {
+                    tracing::warn!("failed to bind a live assertion row");
+                    failed.push(key);
+                }
+            }
+        }
+        self.order = items
+            .into_iter()
+            .map(|item| item.key())
+            .filter(|key| self.rows.contains_key(key))
+            .collect();
+        self.schedule_retry(failed, window, cx);
+    }
+
+    fn schedule_retry(
+        &mut self,
+        failed: Vec<PathKey>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut newly_failed = false;
+        for key in failed {
+            newly_failed |= self.retry_attempted.insert(key);
+        }
+        if !newly_failed || self.retry_scheduled {
+            return;
+        }
+        self.retry_scheduled = true;
+        cx.defer_in(window, |this, window, cx| {
+            this.retry_scheduled = false;
+            this.reconcile(window, cx);
+            cx.notify();
+        });
+    }
+
+    fn render_row(&self, key: &PathKey, cx: &mut Context<Self>) -> AnyElement {
+        let row = self
+            .rows
+            .get(key)
+            .expect("render order only contains successfully bound assertion rows");
+        let enabled_path = row.item.clone().then(AssertionDraft::ENABLED);
+        let enabled = enabled_path.try_get(&self.form, cx).unwrap_or(false);
+        let kind = row
+            .item
+            .clone()
+            .then(AssertionDraft::KIND)
+            .try_get(&self.form, cx)
+            .unwrap_or_default();
+        let target_error = visible_dynamic_error(
+            enabled,
+            &row.item.clone().then(AssertionDraft::TARGET),
+            &self.form,
+            cx,
+        );
+        let expected_error = visible_dynamic_error(
+            enabled,
+            &row.item.clone().then(AssertionDraft::EXPECTED),
+            &self.form,
+            cx,
+        );
+
+        let form = self.form.clone();
+        let checkbox = Checkbox::new(child_id("assertion", key, "enabled"))
+            .checked(enabled)
+            .on_click(move |checked, _, cx| {
+                let _ = enabled_path.try_set(&form, *checked, cx);
+            });
+
+        let delete_form = self.form.clone();
+        let delete_item = row.item.clone();
+        let delete = Button::new(child_id("assertion", key, "delete"))
+            .label(cx.global::<I18n>().t("button-delete"))
+            .on_click(move |_, _, cx| {
+                let _ = assertions_path().remove(&delete_form, delete_item.clone(), cx);
+            });
+
+        let position = self.order.iter().position(|candidate| candidate == key);
+        let previous = position
+            .and_then(|position| position.checked_sub(1))
+            .and_then(|position| self.rows.get(&self.order[position]))
+            .map(|row| row.item.clone());
+        let next = position
+            .and_then(|position| self.order.get(position + 1))
+            .and_then(|key| self.rows.get(key))
+            .map(|row| row.item.clone());
+        let up = previous.map(|previous| {
+            let form = self.form.clone();
+            let item = row.item.clone();
+            Button::new(child_id("assertion", key, "up"))
+                .label(cx.global::<I18n>().t("button-move-up"))
+                .on_click(move |_, _, cx| {
+                    let _ = assertions_path().move_before(&form, &item, &previous, cx);
+                })
+        });
+        let down = next.map(|next| {
+            let form = self.form.clone();
+            let item = row.item.clone();
+            Button::new(child_id("assertion", key, "down"))
+                .label(cx.global::<I18n>().t("button-move-down"))
+                .on_click(move |_, _, cx| {
+                    let _ = assertions_path().move_before(&form, &next, &item, cx);
+                })
+        });
+
+        // Fields the selected kind ignores keep their text but are hidden.
+        let target = kind
+            .uses_target()
+            .then(|| input_with_error((*row.target).clone(), target_error, cx));
+        let expected = kind
+            .uses_expected()
+            .then(|| input_with_error((*row.expected).clone(), expected_error, cx));
+
+        h_flex()
+            .id(child_id("assertion", key, "row"))
+            .w_full()
+            .items_start()
+            .gap_2()
+            .child(checkbox)
+            .child(div().w(px(200.)).child(row.kind.element()))
+            .children(target)
+            .children(expected)
+            .children(up)
+            .children(down)
+            .child(delete)
+            .into_any_element()
+    }
+}
+
+impl Render for AssertionsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (title, add_label) = {
+            let i18n = cx.global::<I18n>();
+            (i18n.t("checks-assertions"), i18n.t("button-add"))
+        };
+        let form = self.form.clone();
+        let add = Button::new("add-assertion")
+            .label(add_label)
+            .on_click(move |_, _, cx| {
+                let _ = assertions_path().append(&form, AssertionDraft::default(), cx);
+            });
+        let rows = self
+            .order
+            .iter()
+            .map(|key| self.render_row(key, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .justify_between()
+                    .child(Label::new(title))
+                    .child(add),
+            )
+            .children(rows)
+    }
+}
+
+fn assertion_kind_options(cx: &gpui::App) -> AssertionKindOptions {
+    let i18n = cx.global::<I18n>();
+    AssertionKind::ALL
+        .into_iter()
+        .map(|kind| AssertionKindOption {
+            kind,
+            title: i18n.t(assertion_kind_key(kind)).into(),
+        })
+        .collect()
+}
+
+fn assertions_path() -> TotalItemsPath<RequestDraft, AssertionDraft> {
+    RequestDraft::ROOT.then(RequestDraft::ASSERTIONS)
+}
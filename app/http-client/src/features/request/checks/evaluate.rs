@@ -0,0 +1,523 @@
+use std::{ops::RangeInclusive, sync::Arc, time::Duration};
+
+use http::HeaderName;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::features::request::{
+    draft::{AssertionDraft, AssertionKind, ExtractionDraft, ExtractionSource},
+    json_path::JsonPath,
+    prepared::{RequestCompileError, RequestFieldError, parse_header_name},
+    response::{CAPTURE_LIMIT_BYTES, ResponseData, escape_header_value},
+    variables::validate_variable_name,
+};
+
+/// Accepts `200`, `200-299` or a class such as `2xx`.
+pub(crate) fn parse_status_range(raw: &str) -> Result<RangeInclusive<u16>, RequestFieldError> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err(RequestFieldError::Required);
+    }
+    let status = |raw: &str| {
+        raw.trim()
+            .parse::<u16>()
+            .ok()
+            .filter(|status| (100..=599).contains(status))
+            .ok_or(RequestFieldError::InvalidStatusRange)
+    };
+    let range = if let Some(class) = raw.strip_suffix("xx").or_else(|| raw.strip_suffix("XX")) {
+        let start = match class.as_bytes() {
+            [digit @ b'1'..=b'5'] => u16::from(digit - b'0') * 100,
+            _ => return Err(RequestFieldError::InvalidStatusRange),
+        };
+        start..=start + 99
+    } else if let Some((start, end)) = raw.split_once('-') {
+        status(start)?..=status(end)?
+    } else {
+        let status = status(raw)?;
+        status..=status
+    };
+    if range.is_empty() {
+        return Err(RequestFieldError::InvalidStatusRange);
+    }
+    Ok(range)
+}
+
+pub(crate) fn parse_pattern(raw: &str) -> Result<Regex, RequestFieldError> {
+    Regex::new(raw).map_err(|_| RequestFieldError::InvalidPattern)
+}
+
+pub(crate) fn parse_json_path(raw: &str) -> Result<JsonPath, RequestFieldError> {
+    if raw.trim().is_empty() {
+        return Err(RequestFieldError::Required);
+    }
+    JsonPath::parse(raw).map_err(|_| RequestFieldError::InvalidJsonPath)
+}
+
+/// Parses a positive whole number of milliseconds.
+pub(crate) fn parse_time_limit(raw: &str) -> Result<Duration, RequestFieldError> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err(RequestFieldError::Required);
+    }
+    raw.parse::<u64>()
+        .ok()
+        .filter(|millis| *millis > 0)
+        .map(Duration::from_millis)
+        .ok_or(RequestFieldError::InvalidDuration)
+}
+
+/// An expected value is JSON when it parses as JSON and a bare string
+/// otherwise, so both `42` and `abc` can be typed without quoting.
+fn parse_expected_json(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_owned()))
+}
+
+/// Checks and extractions frozen at send time, evaluated once the response
+/// body has been captured completely.
+pub(crate) struct ResponseChecks {
+    assertions: Vec<(AssertionLabel, Check)>,
+    extractions: Vec<(String, ExtractionRule)>,
+}
+
+/// How an assertion was written, kept for the results table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct AssertionLabel {
+    pub(crate) kind: AssertionKind,
+    pub(crate) target: String,
+    pub(crate) expected: String,
+}
+
+enum Check {
+    Status(RangeInclusive<u16>),
+    HeaderPresent(HeaderName),
+    HeaderMatches(HeaderName, Regex),
+    JsonEquals(JsonPath, Value),
+    JsonMatches(JsonPath, Regex),
+    TimeBelow(Duration),
+}
+
+enum ExtractionRule {
+    JsonPath(JsonPath),
+    Header(HeaderName),
+}
+
+impl ResponseChecks {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.assertions.is_empty() && self.extractions.is_empty()
+    }
+
+    fn reads_body(&self) -> bool {
+        self.assertions
+            .iter()
+            .any(|(_, check)| matches!(check, Check::JsonEquals(..) | Check::JsonMatches(..)))
+            || self
+                .extractions
+                .iter()
+                .any(|(_, rule)| matches!(rule, ExtractionRule::JsonPath(_)))
+    }
+}
+
+pub(crate) fn compile_checks(
+    assertions: &[AssertionDraft],
+    extractions: &[ExtractionDraft],
+) -> Result<ResponseChecks, RequestCompileError> {
+    let invalid = |_| RequestCompileError::InvalidCheck;
+    let mut compiled_assertions = Vec::new();
+    for assertion in assertions.iter().filter(|assertion| assertion.enabled) {
+        let check = match assertion.kind {
+            AssertionKind::StatusRange => {
+                Check::Status(parse_status_range(&assertion.expected).map_err(invalid)?)
+            }
+            AssertionKind::HeaderPresent => {
+                Check::HeaderPresent(parse_header_name(assertion.target.trim()).map_err(invalid)?)
+            }
+            AssertionKind::HeaderMatches => Check::HeaderMatches(
+                parse_header_name(assertion.target.trim()).map_err(invalid)?,
+                parse_pattern(&assertion.expected).map_err(invalid)?,
+            ),
+            AssertionKind::JsonPathEquals => Check::JsonEquals(
+                parse_json_path(&assertion.target).map_err(invalid)?,
+                parse_expected_json(&assertion.expected),
+            ),
+            AssertionKind::JsonPathMatches => Check::JsonMatches(
+                parse_json_path(&assertion.target).map_err(invalid)?,
+                parse_pattern(&assertion.expected).map_err(invalid)?,
+            ),
+            AssertionKind::ResponseTimeBelow => {
+                Check::TimeBelow(parse_time_limit(&assertion.expected).map_err(invalid)?)
+            }
+        };
+        let label = AssertionLabel {
+            kind: assertion.kind,
+            target: assertion.target.clone(),
+            expected: assertion.expected.clone(),
+        };
+        compiled_assertions.push((label, check));
+    }
+
+    let mut compiled_extractions = Vec::new();
+    for extraction in extractions.iter().filter(|extraction| extraction.enabled) {
+        validate_variable_name(&extraction.variable).map_err(invalid)?;
+        let rule = match extraction.source {
+            ExtractionSource::JsonPath => {
+                ExtractionRule::JsonPath(parse_json_path(&extraction.expression).map_err(invalid)?)
+            }
+            ExtractionSource::Header => ExtractionRule::Header(
+                parse_header_name(extraction.expression.trim()).map_err(invalid)?,
+            ),
+        };
+        compiled_extractions.push((extraction.variable.clone(), rule));
+    }
+
+    Ok(ResponseChecks {
+        assertions: compiled_assertions,
+        extractions: compiled_extractions,
+    })
+}
+
+pub(crate) struct CheckReport {
+    pub(crate) assertions: Vec<AssertionOutcome>,
+    pub(crate) extractions: Vec<ExtractionOutcome>,
+}
+
+impl CheckReport {
+    pub(crate) fn passed(&self) -> usize {
+        self.assertions
+            .iter()
+            .filter(|outcome| outcome.result.is_ok())
+            .count()
+    }
+}
+
+pub(crate) struct AssertionOutcome {
+    pub(crate) label: AssertionLabel,
+    pub(crate) result: Result<(), CheckFailure>,
+}
+
+pub(crate) struct ExtractionOutcome {
+    pub(crate) variable: String,
+    pub(crate) result: Result<String, CheckFailure>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum CheckFailure {
+    /// The value exists but does not satisfy the assertion.
+    Mismatch { actual: String },
+    /// The header or JSONPath selected nothing.
+    Missing,
+    /// The captured body could not be read back.
+    BodyUnavailable,
+    /// The body is not JSON, or its content coding was not decoded.
+    BodyNotJson,
+}
+
+/// Evaluates every check against one completed response. The body is only
+/// read when a JSONPath check needs it.
+pub(crate) async fn run_checks(checks: ResponseChecks, response: Arc<ResponseData>) -> CheckReport {
+    let document = if checks.reads_body() {
+        Some(read_json(&response).await)
+    } else {
+        None
+    };
+    let json = |path: &JsonPath| select_first(document.as_ref(), path);
+
+    let assertions = checks
+        .assertions
+        .into_iter()
+        .map(|(label, check)| {
+            let result = match &check {
+                Check::Status(range) => {
+                    let status = response.head().status.as_u16();
+                    expect(range.contains(&status), || status.to_string())
+                }
+                Check::HeaderPresent(name) => {
+                    if response.head().headers.contains_key(name) {
+                        Ok(())
+                    } else {
+                        Err(CheckFailure::Missing)
+                    }
+                }
+                Check::HeaderMatches(name, pattern) => {
+                    let values = response
+                        .head()
+                        .headers
+                        .get_all(name)
+                        .iter()
+                        .map(escape_header_value)
+                        .collect::<Vec<_>>();
+                    match values.first() {
+                        None => Err(CheckFailure::Missing),
+                        Some(first) => {
+                            expect(values.iter().any(|value| pattern.is_match(value)), || {
+                                first.clone()
+                            })
+                        }
+                    }
+                }
+                Check::JsonEquals(path, expected) => {
+                    json(path).and_then(|value| expect(value == expected, || json_text(value)))
+                }
+                Check::JsonMatches(path, pattern) => json(path).and_then(|value| {
+                    let text = json_text(value);
+                    expect(pattern.is_match(&text), || text)
+                }),
+                Check::TimeBelow(limit) => {
+                    let elapsed = response.timing().completed_after;
+                    expect(elapsed < *limit, || format!("{} ms", elapsed.as_millis()))
+                }
+            };
+            AssertionOutcome { label, result }
+        })
+        .collect();
+
+    let extractions = checks
+        .extractions
+        .into_iter()
+        .map(|(variable, rule)| {
+            let result = match &rule {
+                ExtractionRule::JsonPath(path) => json(path).map(json_text),
+                ExtractionRule::Header(name) => response
+                    .head()
+                    .headers
+                    .get(name)
+                    .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+                    .ok_or(CheckFailure::Missing),
+            };
+            ExtractionOutcome { variable, result }
+        })
+        .collect();
+
+    CheckReport {
+        assertions,
+        extractions,
+    }
+}
+
+fn expect(passed: bool, actual: impl FnOnce() -> String) -> Result<(), CheckFailure> {
+    if passed {
+        Ok(())
+    } else {
+        Err(CheckFailure::Mismatch { actual: actual() })
+    }
+}
+
+fn select_first<'a>(
+    document: Option<&'a Result<Value, CheckFailure>>,
+    path: &JsonPath,
+) -> Result<&'a Value, CheckFailure> {
+    let document = document
+        .expect("JSON checks read the body first")
+        .as_ref()
+        .map_err(Clone::clone)?;
+    path.first(document).ok_or(CheckFailure::Missing)
+}
+
+async fn read_json(response: &Arc<ResponseData>) -> Result<Value, CheckFailure> {
+    let bytes = response
+        .read_lease()
+        .read_all_bounded(CAPTURE_LIMIT_BYTES)
+        .await
+        .map_err(|_| CheckFailure::BodyUnavailable)?;
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+    serde_json::from_slice(bytes).map_err(|_| CheckFailure::BodyNotJson)
+}
+
+/// Strings compare and extract without their JSON quotes; every other value
+/// uses its compact JSON text.
+fn json_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use http::{HeaderMap, HeaderValue, StatusCode, Version};
+    use url::Url;
+
+    use super::*;
+    use crate::features::request::response::{
+        BodyDecoding, CompletedBody, ResponseHead, ResponseSizes, ResponseTiming, StoredBody,
+    };
+
+    fn response(body: &'static [u8]) -> Arc<ResponseData> {
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        headers.append("x-trace", HeaderValue::from_static("first"));
+        headers.append("x-trace", HeaderValue::from_static("second-42"));
+        let len = body.len() as u64;
+        Arc::new(ResponseData::new(
+            ResponseHead::new(
+                StatusCode::CREATED,
+                Version::HTTP_11,
+                Url::parse("https://example.test/login").unwrap(),
+                headers,
+            ),
+            ResponseTiming {
+                head_after: Duration::from_millis(5),
+                completed_after: Duration::from_millis(120),
+            },
+            CompletedBody {
+                body: StoredBody::Memory(Bytes::from_static(body)),
+                body_decoding: BodyDecoding::Identity,
+                sizes: ResponseSizes {
+                    declared_encoded_bytes: Some(len),
+                    received_encoded_bytes: len,
+                    stored_body_bytes: len,
+                },
+            },
+        ))
+    }
+
+    fn assertion(kind: AssertionKind, target: &str, expected: &str) -> AssertionDraft {
+        AssertionDraft {
+            enabled: true,
+            kind,
+            target: target.into(),
+            expected: expected.into(),
+        }
+    }
+
+    fn extraction(variable: &str, source: ExtractionSource, expression: &str) -> ExtractionDraft {
+        ExtractionDraft {
+            enabled: true,
+            variable: variable.into(),
+            source,
+            expression: expression.into(),
+        }
+    }
+
+    #[test]
+    fn status_ranges_accept_single_codes_spans_and_classes() {
+        assert_eq!(parse_status_range("201"), Ok(201..=201));
+        assert_eq!(parse_status_range(" 200 - 299 "), Ok(200..=299));
+        assert_eq!(parse_status_range("4xx"), Ok(400..=499));
+        assert_eq!(parse_status_range(""), Err(RequestFieldError::Required));
+        for invalid in ["299-200", "6xx", "20", "abc", "2xxx"] {
+            assert_eq!(
+                parse_status_range(invalid),
+                Err(RequestFieldError::InvalidStatusRange),
+                "{invalid}"
+            );
+        }
+        assert_eq!(
+            parse_time_limit("0"),
+            Err(RequestFieldError::InvalidDuration)
+        );
+        assert_eq!(parse_time_limit("250"), Ok(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn disabled_rows_are_skipped_and_invalid_enabled_rows_fail_compilation() {
+        let mut disabled = assertion(AssertionKind::JsonPathEquals, "not a path", "");
+        disabled.enabled = false;
+        assert!(compile_checks(&[disabled], &[]).unwrap().is_empty());
+
+        assert_eq!(
+            compile_checks(
+                &[assertion(AssertionKind::HeaderMatches, "x-trace", "(")],
+                &[]
+            )
+            .err(),
+            Some(RequestCompileError::InvalidCheck)
+        );
+        assert_eq!(
+            compile_checks(
+                &[],
+                &[extraction("1bad", ExtractionSource::Header, "x-trace")]
+            )
+            .err(),
+            Some(RequestCompileError::InvalidCheck)
+        );
+    }
+
+    #[tokio::test]
+    async fn assertions_report_actual_values_and_extractions_read_the_body() {
+        let checks = compile_checks(
+            &[
+                assertion(AssertionKind::StatusRange, "", "2xx"),
+                assertion(AssertionKind::StatusRange, "", "200"),
+                assertion(AssertionKind::HeaderPresent, "Content-Type", ""),
+                assertion(AssertionKind::HeaderPresent, "x-missing", ""),
+                assertion(AssertionKind::HeaderMatches, "x-trace", r"-\d+$"),
+                assertion(AssertionKind::JsonPathEquals, "$.user.id", "42"),
+                assertion(AssertionKind::JsonPathEquals, "$.user.name", "ann"),
+                assertion(AssertionKind::JsonPathMatches, "$.token", "^t-"),
+                assertion(AssertionKind::JsonPathEquals, "$.absent", "1"),
+                assertion(AssertionKind::ResponseTimeBelow, "", "100"),
+            ],
+            &[
+                extraction("token", ExtractionSource::JsonPath, "$.token"),
+                extraction("trace", ExtractionSource::Header, "x-trace"),
+                extraction("absent", ExtractionSource::Header, "x-absent"),
+            ],
+        )
+        .unwrap();
+
+        let report = run_checks(
+            checks,
+            response(br#"{"token":"t-1","user":{"id":42,"name":"bob"}}"#),
+        )
+        .await;
+
+        let results = report
+            .assertions
+            .iter()
+            .map(|outcome| outcome.result.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            [
+                Ok(()),
+                Err(CheckFailure::Mismatch {
+                    actual: "201".into()
+                }),
+                Ok(()),
+                Err(CheckFailure::Missing),
+                Ok(()),
+                Ok(()),
+                Err(CheckFailure::Mismatch {
+                    actual: "bob".into()
+                }),
+                Ok(()),
+                Err(CheckFailure::Missing),
+                Err(CheckFailure::Mismatch {
+                    actual: "120 ms".into()
+                }),
+            ]
+        );
+        assert_eq!(report.passed(), 5);
+        assert_eq!(report.assertions[2].label.target, "Content-Type");
+
+        let extracted = report
+            .extractions
+            .iter()
+            .map(|outcome| (outcome.variable.as_str(), outcome.result.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            extracted,
+            [
+                ("token", Ok("t-1".to_owned())),
+                ("trace", Ok("first".to_owned())),
+                ("absent", Err(CheckFailure::Missing)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn json_checks_fail_without_a_json_body() {
+        let checks = compile_checks(
+            &[assertion(AssertionKind::JsonPathEquals, "$", "1")],
+            &[extraction("value", ExtractionSource::JsonPath, "$")],
+        )
+        .unwrap();
+
+        let report = run_checks(checks, response(b"<html>")).await;
+
+        assert_eq!(report.assertions[0].result, Err(CheckFailure::BodyNotJson));
+        assert_eq!(report.extractions[0].result, Err(CheckFailure::BodyNotJson));
+    }
+}
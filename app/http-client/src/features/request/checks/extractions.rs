@@ -0,0 +1,319 @@
+use std::collections::{HashMap, HashSet};
+
+use gpui::{
+    AnyElement, Context, Entity, InteractiveElement as _, IntoElement, ParentElement as _, Render,
+    SharedString, Styled as _, Subscription, Window, div, px,
+};
+use gpui_component::{
+    IndexPath,
+    button::Button,
+    checkbox::Checkbox,
+    h_flex,
+    input::InputState,
+    label::Label,
+    select::{SelectItem, SelectState},
+    v_flex,
+};
+use gpui_form::{Form, FormEvent, ItemPath, PathKey, TotalItemsPath};
+use gpui_form_gpui_component::FormInput;
+
+use super::{child_id, input_with_error, visible_dynamic_error};
+use crate::{
+    features::request::{
+        controls::FormScalarSelect,
+        draft::{ExtractionDraft, ExtractionSource, RequestDraft},
+    },
+    foundation::I18n,
+};
+
+#[derive(Clone)]
+struct ExtractionSourceOption {
+    source: ExtractionSource,
+    title: SharedString,
+}
+
+impl SelectItem for ExtractionSourceOption {
+    type Value = ExtractionSource;
+
+    fn title(&self) -> SharedString {
+        self.title.clone()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.source
+    }
+}
+
+type ExtractionSourceOptions = Vec<ExtractionSourceOption>;
+
+struct ExtractionRow {
+    item: ItemPath<RequestDraft, ExtractionDraft>,
+    variable: FormInput,
+    source: FormScalarSelect<RequestDraft, ExtractionSourceOptions, ExtractionSource>,
+    expression: FormInput,
+}
+
+impl ExtractionRow {
+    fn try_new(
+        form: &Entity<Form<RequestDraft>>,
+        item: ItemPath<RequestDraft, ExtractionDraft>,
+        window: &mut Window,
+        cx: &mut Context<ExtractionsView>,
+    ) -> Result<Self, gpui_form::ResolveError> {
+        let options = extraction_source_options(cx);
+        let source = FormScalarSelect::try_new(
+            form,
+            item.clone().then(ExtractionDraft::SOURCE),
+            |window, cx| SelectState::new(options, Some(IndexPath::default()), window, cx),
+            window,
+            cx,
+        )?;
+        let variable_placeholder = cx.global::<I18n>().t("field-variable");
+        let expression_placeholder = cx.global::<I18n>().t("field-check-target");
+        let variable = FormInput::try_new(
+            form,
+            item.clone().then(ExtractionDraft::VARIABLE),
+            move |window, cx| InputState::new(window, cx).placeholder(variable_placeholder),
+            window,
+            cx,
+        )?;
+        let expression = FormInput::try_new(
+            form,
+            item.clone().then(ExtractionDraft::EXPRESSION),
+            move |window, cx| InputState::new(window, cx).placeholder(expression_placeholder),
+            window,
+            cx,
+        )?;
+        Ok(Self {
+            item,
+            variable,
+            source,
+            expression,
+        })
+    }
+}
+
+/// Owns native controls for the live extraction rows.
+pub(super) struct ExtractionsView {
+    form: Entity<Form<RequestDraft>>,
+    rows: HashMap<PathKey, ExtractionRow>,
+    order: Vec<PathKey>,
+    retry_scheduled: bool,
+    retry_attempted: HashSet<PathKey>,
+    _subscription: Subscription,
+}
+
+impl ExtractionsView {
+    pub(super) fn new(
+        form: Entity<Form<RequestDraft>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe_in(
+            &form,
+            window,
+            |this, _, event: &FormEvent<RequestDraft>, window, cx| {
+                let FormEvent::ModelChanged(change) = event else {
+                    return;
+                };
+                let impact = change.impact(&RequestDraft::EXTRACTIONS);
+                if impact.structure_changed() || impact.retired() {
+                    this.retry_attempted.clear();
+                    this.reconcile(window, cx);
+                    cx.notify();
+                }
+            },
+        );
+        let mut view = Self {
+            form,
+            rows: HashMap::new(),
+            order: Vec::new(),
+            retry_scheduled: false,
+            retry_attempted: HashSet::new(),
+            _subscription: subscription,
+        };
+        view.reconcile(window, cx);
+        view
+    }
+
+    fn reconcile(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let items = RequestDraft::EXTRACTIONS.items(&self.form, cx);
+        let live = items.iter().map(ItemPath::key).collect::<HashSet<_>>();
+        self.rows.retain(|key, _| live.contains(key));
+        self.retry_attempted.retain(|key| live.contains(key));
+
+        let mut failed = Vec::new();
+        for item in &items {
+            let key = item.key();
+            if self.rows.contains_key(&key) {
+                continue;
+            }
+            match ExtractionRow::try_new(&self.form, item.clone(), window, cx) {
+                Ok(row) => {
+                    self.rows.insert(key, row);
+                }
+                Err(_) => {
+                    tracing::warn!("failed to bind a live extraction row");
+                    failed.push(key);
+                }
+            }
+        }
+        self.order = items
+            .into_iter()
+            .map(|item| item.key())
+            .filter(|key| self.rows.contains_key(key))
+            .collect();
+        self.schedule_retry(failed, window, cx);
+    }
+
+    fn schedule_retry(
+        &mut self,
+        failed: Vec<PathKey>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut newly_failed = false;
+        for key in failed {
+            newly_failed |= self.retry_attempted.insert(key);
+        }
+        if !newly_failed || self.retry_scheduled {
+            return;
+        }
+        self.retry_scheduled = true;
+        cx.defer_in(window, |this, window, cx| {
+            this.retry_scheduled = false;
+            this.reconcile(window, cx);
+            cx.notify();
+        });
+    }
+
+    fn render_row(&self, key: &PathKey, cx: &mut Context<Self>) -> AnyElement {
+        let row = self
+            .rows
+            .get(key)
+            .expect("render order only contains successfully bound extraction rows");
+        let enabled_path = row.item.clone().then(ExtractionDraft::ENABLED);
+        let enabled = enabled_path.try_get(&self.form, cx).unwrap_or(false);
+        let variable_error = visible_dynamic_error(
+            enabled,
+            &row.item.clone().then(ExtractionDraft::VARIABLE),
+            &self.form,
+            cx,
+        );
+        let expression_error = visible_dynamic_error(
+            enabled,
+            &row.item.clone().then(ExtractionDraft::EXPRESSION),
+            &self.form,
+            cx,
+        );
+
+        let form = self.form.clone();
+        let checkbox = Checkbox::new(child_id("extraction", key, "enabled"))
+            .checked(enabled)
+            .on_click(move |checked, _, cx| {
+                let _ = enabled_path.try_set(&form, *checked, cx);
+            });
+
+        let delete_form = self.form.clone();
+        let delete_item = row.item.clone();
+        let delete = Button::new(child_id("extraction", key, "delete"))
+            .label(cx.global::<I18n>().t("button-delete"))
+            .on_click(move |_, _, cx| {
+                let _ = extractions_path().remove(&delete_form, delete_item.clone(), cx);
+            });
+
+        let position = self.order.iter().position(|candidate| candidate == key);
+        let previous = position
+            .and_then(|position| position.checked_sub(1))
+            .and_then(|position| self.rows.get(&self.order[position]))
+            .map(|row| row.item.clone());
+        let next = position
+            .and_then(|position| self.order.get(position + 1))
+            .and_then(|key| self.rows.get(key))
+            .map(|row| row.item.clone());
+        let up = previous.map(|previous| {
+            let form = self.form.clone();
+            let item = row.item.clone();
+            Button::new(child_id("extraction", key, "up"))
+                .label(cx.global::<I18n>().t("button-move-up"))
+                .on_click(move |_, _, cx| {
+                    let _ = extractions_path().move_before(&form, &item, &previous, cx);
+                })
+        });
+        let down = next.map(|next| {
+            let form = self.form.clone();
+            let item = row.item.clone();
+            Button::new(child_id("extraction", key, "down"))
+                .label(cx.global::<I18n>().t("button-move-down"))
+                .on_click(move |_, _, cx| {
+                    let _ = extractions_path().move_before(&form, &next, &item, cx);
+                })
+        });
+
+        let variable = input_with_error((*row.variable).clone(), variable_error, cx);
+        let expression = input_with_error((*row.expression).clone(), expression_error, cx);
+
+        h_flex()
+            .id(child_id("extraction", key, "row"))
+            .w_full()
+            .items_start()
+            .gap_2()
+            .child(checkbox)
+            .child(variable)
+            .child(div().w(px(140.)).child(row.source.element()))
+            .child(expression)
+            .children(up)
+            .children(down)
+            .child(delete)
+            .into_any_element()
+    }
+}
+
+impl Render for ExtractionsView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (title, add_label) = {
+            let i18n = cx.global::<I18n>();
+            (i18n.t("checks-extractions"), i18n.t("button-add"))
+        };
+        let form = self.form.clone();
+        let add = Button::new("add-extraction")
+            .label(add_label)
+            .on_click(move |_, _, cx| {
+                let _ = extractions_path().append(&form, ExtractionDraft::default(), cx);
+            });
+        let rows = self
+            .order
+            .iter()
+            .map(|key| self.render_row(key, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .justify_between()
+                    .child(Label::new(title))
+                    .child(add),
+            )
+            .children(rows)
+    }
+}
+
+fn extraction_source_options(cx: &gpui::App) -> ExtractionSourceOptions {
+    let i18n = cx.global::<I18n>();
+    [
+        (ExtractionSource::JsonPath, "checks-source-json-path"),
+        (ExtractionSource::Header, "checks-source-header"),
+    ]
+    .into_iter()
+    .map(|(source, key)| ExtractionSourceOption {
+        source,
+        title: i18n.t(key).into(),
+    })
+    .collect()
+}
+
+fn extractions_path() -> TotalItemsPath<RequestDraft, ExtractionDraft> {
+    RequestDraft::ROOT.then(RequestDraft::EXTRACTIONS)
+}
@@ -475,7 +475,6 @@ pub(super) struct FormFilePathInput {
 }
 
 impl FormFilePathInput {
-    #[allow(dead_code)]
     pub(super) fn new<Root, Owner, Path>(
         form: &Entity<Form<Root>>,
         path: Path,
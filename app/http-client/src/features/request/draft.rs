@@ -1,13 +1,15 @@
 use std::{cell::Cell, path::PathBuf, rc::Rc};
 
 use gpui_form::FormSchema;
+use serde::{Deserialize, Serialize};
 
 use super::method::HttpMethod;
 
 /// The only editable business model for one request editing session.
 ///
 /// Collection identity deliberately belongs to `gpui-form`; draft rows do not carry IDs.
-#[derive(Clone, PartialEq, Eq, FormSchema)]
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct RequestDraft {
     pub(crate) method: HttpMethod,
     #[form(required)]
@@ -20,6 +22,10 @@ pub(crate) struct RequestDraft {
     pub(crate) auth: RequestAuthDraft,
     #[form(child)]
     pub(crate) settings: RequestSettingsDraft,
+    #[form(items)]
+    pub(crate) assertions: Vec<AssertionDraft>,
+    #[form(items)]
+    pub(crate) extractions: Vec<ExtractionDraft>,
 }
 
 impl Default for RequestDraft {
@@ -31,11 +37,14 @@ impl Default for RequestDraft {
             body: RequestBodyDraft::None,
             auth: RequestAuthDraft::None,
             settings: RequestSettingsDraft::default(),
+            assertions: Vec::new(),
+            extractions: Vec::new(),
         }
     }
 }
 
-#[derive(Clone, PartialEq, Eq, FormSchema)]
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct HeaderDraft {
     pub(crate) enabled: bool,
     pub(crate) name: String,
@@ -52,13 +61,16 @@ impl Default for HeaderDraft {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, FormSchema)]
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub(crate) enum RequestBodyDraft {
     None,
     FormData(FormDataDraft),
     UrlEncoded(UrlEncodedBodyDraft),
     Text(TextBodyDraft),
     Binary(BinaryBodyDraft),
+    #[serde(rename = "graphql")]
+    GraphQl(GraphQlBodyDraft),
 }
 
 impl RequestBodyDraft {
@@ -77,15 +89,21 @@ impl RequestBodyDraft {
     pub(crate) fn binary() -> Self {
         Self::Binary(BinaryBodyDraft::default())
     }
+
+    pub(crate) fn graphql() -> Self {
+        Self::GraphQl(GraphQlBodyDraft::default())
+    }
 }
 
-#[derive(Clone, Default, PartialEq, Eq, FormSchema)]
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct UrlEncodedBodyDraft {
     #[form(items)]
     pub(crate) fields: Vec<KeyValueDraft>,
 }
 
-#[derive(Clone, PartialEq, Eq, FormSchema)]
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct KeyValueDraft {
     pub(crate) enabled: bool,
     pub(crate) key: String,
@@ -102,7 +120,8 @@ impl Default for KeyValueDraft {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, FormSchema)]
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct TextBodyDraft {
     pub(crate) format: TextBodyFormat,
     pub(crate) content: String,
@@ -117,11 +136,13 @@ impl Default for TextBodyDraft {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum TextBodyFormat {
     #[default]
     PlainText,
     Json,
+    #[serde(rename = "javascript")]
     JavaScript,
     Html,
     Xml,
@@ -152,13 +173,15 @@ impl TextBodyFormat {
     }
 }
 
-#[derive(Clone, Default, PartialEq, Eq, FormSchema)]
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct FormDataDraft {
     #[form(items)]
     pub(crate) parts: Vec<MultipartPartDraft>,
 }
 
-#[derive(Clone, PartialEq, Eq, FormSchema)]
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct MultipartPartDraft {
     pub(crate) enabled: bool,
     pub(crate) name: String,
@@ -176,7 +199,8 @@ impl Default for MultipartPartDraft {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, FormSchema)]
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub(crate) enum MultipartPartValueDraft {
     Text(MultipartTextDraft),
     File(MultipartFileDraft),
@@ -192,28 +216,49 @@ impl MultipartPartValueDraft {
     }
 }
 
-#[derive(Clone, Default, PartialEq, Eq, FormSchema)]
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct MultipartTextDraft {
     pub(crate) value: String,
     pub(crate) content_type: Option<String>,
 }
 
-#[derive(Clone, Default, PartialEq, Eq, FormSchema)]
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct MultipartFileDraft {
     pub(crate) path: Option<PathBuf>,
 }
 
-#[derive(Clone, Default, PartialEq, Eq, FormSchema)]
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct BinaryBodyDraft {
     pub(crate) file: Option<PathBuf>,
 }
 
-#[derive(Clone, PartialEq, Eq, FormSchema)]
+/// A GraphQL operation sent as the standard JSON envelope.
+///
+/// `variables` is JSON object text and may be empty. `operation_name` picks
+/// the operation to run when the document has more than one.
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct GraphQlBodyDraft {
+    pub(crate) query: String,
+    pub(crate) variables: String,
+    pub(crate) operation_name: String,
+}
+
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub(crate) enum RequestAuthDraft {
     None,
     Basic(BasicAuthDraft),
     Bearer(BearerAuthDraft),
     ApiKey(ApiKeyAuthDraft),
+    Digest(DigestAuthDraft),
+    #[serde(rename = "oauth2")]
+    OAuth2(OAuth2AuthDraft),
+    #[serde(rename = "aws_sigv4")]
+    AwsSigV4(AwsSigV4AuthDraft),
 }
 
 impl RequestAuthDraft {
@@ -228,37 +273,129 @@ impl RequestAuthDraft {
     pub(crate) fn api_key() -> Self {
         Self::ApiKey(ApiKeyAuthDraft::default())
     }
+
+    pub(crate) fn digest() -> Self {
+        Self::Digest(DigestAuthDraft::default())
+    }
+
+    pub(crate) fn oauth2() -> Self {
+        Self::OAuth2(OAuth2AuthDraft::default())
+    }
+
+    pub(crate) fn aws_sigv4() -> Self {
+        Self::AwsSigV4(AwsSigV4AuthDraft::default())
+    }
 }
 
-#[derive(Clone, Default, PartialEq, Eq, FormSchema)]
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct BasicAuthDraft {
     pub(crate) username: String,
     pub(crate) password: String,
 }
 
-#[derive(Clone, Default, PartialEq, Eq, FormSchema)]
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct BearerAuthDraft {
     pub(crate) token: String,
 }
 
-#[derive(Clone, Default, PartialEq, Eq, FormSchema)]
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct ApiKeyAuthDraft {
     pub(crate) name: String,
     pub(crate) value: String,
     pub(crate) location: ApiKeyLocation,
 }
 
-#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub(crate) enum ApiKeyLocation {
     #[default]
     Header,
     Query,
 }
 
-#[derive(Clone, PartialEq, Eq, FormSchema)]
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct DigestAuthDraft {
+    #[form(required)]
+    pub(crate) username: String,
+    pub(crate) password: String,
+}
+
+/// OAuth 2.0 settings for every supported grant.
+///
+/// Grant-specific fields stay in one payload so switching the grant does not
+/// discard what was already typed for the shared client registration.
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct OAuth2AuthDraft {
+    pub(crate) grant: OAuth2Grant,
+    #[form(required)]
+    pub(crate) token_url: String,
+    #[form(required)]
+    pub(crate) client_id: String,
+    pub(crate) client_secret: String,
+    pub(crate) scope: String,
+    pub(crate) username: String,
+    pub(crate) password: String,
+    pub(crate) authorization_url: String,
+    pub(crate) redirect_uri: String,
+}
+
+impl Default for OAuth2AuthDraft {
+    fn default() -> Self {
+        Self {
+            grant: OAuth2Grant::ClientCredentials,
+            token_url: String::new(),
+            client_id: String::new(),
+            client_secret: String::new(),
+            scope: String::new(),
+            username: String::new(),
+            password: String::new(),
+            authorization_url: String::new(),
+            redirect_uri: "http://127.0.0.1/callback".into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OAuth2Grant {
+    #[default]
+    ClientCredentials,
+    Password,
+    AuthorizationCode,
+}
+
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct AwsSigV4AuthDraft {
+    #[form(required)]
+    pub(crate) access_key_id: String,
+    #[form(required)]
+    pub(crate) secret_access_key: String,
+    pub(crate) session_token: String,
+    #[form(required)]
+    pub(crate) region: String,
+    #[form(required)]
+    pub(crate) service: String,
+}
+
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
 pub(crate) struct RequestSettingsDraft {
     pub(crate) follow_redirects: bool,
     pub(crate) follow_original_method: bool,
+    /// Reopens a `text/event-stream` response with `Last-Event-ID` after it
+    /// ends or drops.
+    pub(crate) reconnect_event_stream: bool,
+    /// Adds matching cookies from the shared jar. Cookies the response sets
+    /// are stored either way.
+    pub(crate) send_cookies: bool,
+    #[form(child)]
+    pub(crate) connection: ConnectionSettingsDraft,
 }
 
 impl Default for RequestSettingsDraft {
@@ -266,10 +403,130 @@ impl Default for RequestSettingsDraft {
         Self {
             follow_redirects: true,
             follow_original_method: false,
+            reconnect_event_stream: false,
+            send_cookies: true,
+            connection: ConnectionSettingsDraft::default(),
         }
     }
 }
 
+/// TLS, proxy and protocol settings. Anything other than the defaults gives
+/// the request a client of its own instead of the shared one.
+#[derive(Clone, Default, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct ConnectionSettingsDraft {
+    pub(crate) http_version: HttpVersionPreference,
+    /// A PEM bundle trusted next to the system roots.
+    pub(crate) ca_certificate: Option<PathBuf>,
+    /// A PEM certificate chain, or a `.p12`/`.pfx` archive, for mutual TLS.
+    pub(crate) client_certificate: Option<PathBuf>,
+    /// The PEM private key when it is not part of the certificate file.
+    pub(crate) client_key: Option<PathBuf>,
+    /// Unlocks a PKCS#12 archive.
+    pub(crate) client_certificate_password: String,
+    pub(crate) accept_invalid_certificates: bool,
+    pub(crate) proxy_url: String,
+    pub(crate) proxy_username: String,
+    pub(crate) proxy_password: String,
+    /// Comma-separated hosts, domains and CIDR blocks that skip the proxy.
+    pub(crate) proxy_bypass: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum HttpVersionPreference {
+    /// HTTP/2 when ALPN offers it, HTTP/1.1 otherwise.
+    #[default]
+    Automatic,
+    Http1,
+    /// HTTP/2 only, with prior knowledge on cleartext connections.
+    Http2,
+}
+
+/// One check run against the completed response.
+///
+/// `target` and `expected` are interpreted by `kind`; fields a kind does not
+/// use are kept so switching kinds does not discard typed text.
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct AssertionDraft {
+    pub(crate) enabled: bool,
+    pub(crate) kind: AssertionKind,
+    pub(crate) target: String,
+    pub(crate) expected: String,
+}
+
+impl Default for AssertionDraft {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            kind: AssertionKind::StatusRange,
+            target: String::new(),
+            expected: "200-299".into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AssertionKind {
+    #[default]
+    StatusRange,
+    HeaderPresent,
+    HeaderMatches,
+    JsonPathEquals,
+    JsonPathMatches,
+    ResponseTimeBelow,
+}
+
+impl AssertionKind {
+    pub(crate) const ALL: [Self; 6] = [
+        Self::StatusRange,
+        Self::HeaderPresent,
+        Self::HeaderMatches,
+        Self::JsonPathEquals,
+        Self::JsonPathMatches,
+        Self::ResponseTimeBelow,
+    ];
+
+    pub(crate) const fn uses_target(self) -> bool {
+        !matches!(self, Self::StatusRange | Self::ResponseTimeBelow)
+    }
+
+    pub(crate) const fn uses_expected(self) -> bool {
+        !matches!(self, Self::HeaderPresent)
+    }
+}
+
+/// Copies one response value into a request variable after every send.
+#[derive(Clone, PartialEq, Eq, FormSchema, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct ExtractionDraft {
+    pub(crate) enabled: bool,
+    pub(crate) variable: String,
+    pub(crate) source: ExtractionSource,
+    pub(crate) expression: String,
+}
+
+impl Default for ExtractionDraft {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            variable: String::new(),
+            source: ExtractionSource::JsonPath,
+            expression: String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ExtractionSource {
+    #[default]
+    JsonPath,
+    Header,
+}
+
 /// Page-owned transport settings. This is intentionally not part of the Form or a Store.
 #[derive(Clone, Default)]
 pub(crate) struct HttpClientTransportSettings {
@@ -321,6 +578,14 @@ mod tests {
                     .then(RequestSettingsDraft::FOLLOW_ORIGINAL_METHOD)
                     .get(&form, cx)
             );
+            assert!(
+                !RequestDraft::ROOT
+                    .then(RequestDraft::SETTINGS)
+                    .then(RequestSettingsDraft::RECONNECT_EVENT_STREAM)
+                    .get(&form, cx)
+            );
+            assert!(RequestDraft::ASSERTIONS.items(&form, cx).is_empty());
+            assert!(RequestDraft::EXTRACTIONS.items(&form, cx).is_empty());
         });
     }
 
@@ -360,6 +625,25 @@ mod tests {
             RequestAuthDraft::bearer(),
             RequestAuthDraft::Bearer(BearerAuthDraft { token }) if token.is_empty()
         ));
+        assert!(matches!(
+            RequestAuthDraft::digest(),
+            RequestAuthDraft::Digest(DigestAuthDraft { username, password })
+                if username.is_empty() && password.is_empty()
+        ));
+        assert!(matches!(
+            RequestAuthDraft::oauth2(),
+            RequestAuthDraft::OAuth2(OAuth2AuthDraft {
+                grant: OAuth2Grant::ClientCredentials,
+                token_url,
+                redirect_uri,
+                ..
+            }) if token_url.is_empty() && redirect_uri == "http://127.0.0.1/callback"
+        ));
+        assert!(matches!(
+            RequestAuthDraft::aws_sigv4(),
+            RequestAuthDraft::AwsSigV4(AwsSigV4AuthDraft { access_key_id, session_token, .. })
+                if access_key_id.is_empty() && session_token.is_empty()
+        ));
         assert!(matches!(
             MultipartPartValueDraft::text(),
             MultipartPartValueDraft::Text(MultipartTextDraft {
@@ -0,0 +1,223 @@
+//! GraphQL over HTTP: the request envelope, schema introspection and the
+//! document analysis behind the GraphQL body editor.
+
+use std::{pin::pin, sync::Arc, time::Duration};
+
+use async_channel::Receiver;
+use futures_util::future::{Either, select};
+use http::StatusCode;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use super::{
+    draft::{GraphQlBodyDraft, HttpClientTransportSettings, RequestBodyDraft, RequestDraft},
+    method::HttpMethod,
+    prepared::{PreparedRequest, RequestCompileError, compile_request},
+    response::{CAPTURE_LIMIT_BYTES, CompletedBody, ResponseData, ResponseHead, ResponseTiming},
+    runtime::RequestProblem,
+    transport::{HttpTransport, WorkerEvent},
+};
+
+mod analysis;
+mod document;
+mod schema;
+
+pub(crate) use analysis::{CompletionKind, GraphQlDiagnostic, GraphQlProblem, complete, validate};
+pub(crate) use document::{SyntaxExpectation, operations};
+use schema::INTROSPECTION_QUERY;
+pub(crate) use schema::{GraphQlSchema, IntrospectionError};
+
+/// The `application/json` body every GraphQL server accepts over POST.
+pub(crate) fn envelope(
+    query: &str,
+    variables: Option<Map<String, Value>>,
+    operation_name: Option<&str>,
+) -> Vec<u8> {
+    let mut body = Map::new();
+    body.insert("query".into(), query.into());
+    if let Some(variables) = variables {
+        body.insert("variables".into(), Value::Object(variables));
+    }
+    if let Some(operation_name) = operation_name {
+        body.insert("operationName".into(), operation_name.into());
+    }
+    serde_json::to_vec(&body).expect("a JSON object always serializes")
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum SchemaFetchError {
+    #[error("the introspection request could not be prepared")]
+    Prepare(#[from] RequestCompileError),
+    #[error(transparent)]
+    Request(#[from] RequestProblem),
+    #[error("the introspection request needs browser authorization")]
+    AuthorizationRequired,
+    #[error("the introspection request returned HTTP {0}")]
+    Status(StatusCode),
+    #[error("the introspection response is not JSON")]
+    NotJson,
+    #[error(transparent)]
+    Introspection(#[from] IntrospectionError),
+}
+
+/// Turns the request being edited into an introspection query against the
+/// same endpoint, keeping its headers, authorization and settings.
+pub(crate) fn prepare_introspection(
+    draft: RequestDraft,
+    settings: &HttpClientTransportSettings,
+) -> Result<PreparedRequest, RequestCompileError> {
+    compile_request(
+        RequestDraft {
+            method: HttpMethod::Post,
+            body: RequestBodyDraft::GraphQl(GraphQlBodyDraft {
+                query: INTROSPECTION_QUERY.into(),
+                ..GraphQlBodyDraft::default()
+            }),
+            ..draft
+        },
+        settings,
+    )
+}
+
+/// Runs an introspection request and reads the schema from its response.
+///
+/// An authorization code grant that has no cached token would wait for the
+/// browser, so it fails instead; sending the request once authorizes it.
+pub(crate) async fn fetch_schema(
+    transport: HttpTransport,
+    prepared: PreparedRequest,
+) -> Result<GraphQlSchema, SchemaFetchError> {
+    let (sender, receiver) = HttpTransport::channel();
+    let collect = pin!(collect_response(receiver));
+    let worker = pin!(transport.run(prepared, sender));
+    let (head, completed) = match select(worker, collect).await {
+        Either::Left(((), collect)) => collect.await?,
+        Either::Right((result, _)) => result?,
+    };
+    let head = head.ok_or_else(RequestProblem::internal)?;
+    let status = head.status;
+    let response = Arc::new(ResponseData::new(
+        head,
+        ResponseTiming {
+            head_after: Duration::ZERO,
+            completed_after: Duration::ZERO,
+        },
+        completed,
+    ));
+    let bytes = response
+        .read_lease()
+        .read_all_bounded(CAPTURE_LIMIT_BYTES)
+        .await
+        .map_err(RequestProblem::response_body_read)?;
+    let Ok(json) = serde_json::from_slice::<Value>(&bytes) else {
+        return Err(if status.is_success() {
+            SchemaFetchError::NotJson
+        } else {
+            SchemaFetchError::Status(status)
+        });
+    };
+    match GraphQlSchema::from_response(&json) {
+        Err(IntrospectionError::MissingSchema) if !status.is_success() => {
+            Err(SchemaFetchError::Status(status))
+        }
+        result => Ok(result?),
+    }
+}
+
+async fn collect_response(
+    receiver: Receiver<WorkerEvent>,
+) -> Result<(Option<ResponseHead>, CompletedBody), SchemaFetchError> {
+    let mut head = None;
+    while let Ok(event) = receiver.recv().await {
+        match event {
+            WorkerEvent::HeadReceived { head: received, .. } => head = Some(received),
+            WorkerEvent::Finished { result, .. } => return Ok((head, result?)),
+            WorkerEvent::AuthorizationRequested(_) => {
+                return Err(SchemaFetchError::AuthorizationRequired);
+            }
+            WorkerEvent::BodyProgress(_) | WorkerEvent::EventStream(_) => {}
+        }
+    }
+    Err(RequestProblem::internal().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use http_client_test_server::{RespondSpec, ResponseBodySpec, ResponseFraming, TestServer};
+
+    use super::*;
+
+    fn respond(status: u16, body: ResponseBodySpec) -> RespondSpec {
+        RespondSpec {
+            status,
+            headers: Vec::new(),
+            body,
+            delay_before_headers_ms: 0,
+            chunk_size_bytes: 16 * 1024,
+            delay_between_chunks_ms: 0,
+            content_encoding: None,
+            framing: ResponseFraming::ContentLength,
+        }
+    }
+
+    fn introspection(server: &TestServer, spec: &RespondSpec) -> PreparedRequest {
+        let draft = RequestDraft {
+            url: server.respond_url(spec).unwrap(),
+            ..RequestDraft::default()
+        };
+        prepare_introspection(draft, &HttpClientTransportSettings::default()).unwrap()
+    }
+
+    #[test]
+    fn the_envelope_omits_absent_members() {
+        let mut variables = Map::new();
+        variables.insert("id".into(), 1.into());
+        assert_eq!(
+            serde_json::from_slice::<Value>(&envelope("{ a }", Some(variables), Some("A")))
+                .unwrap(),
+            serde_json::json!({ "query": "{ a }", "variables": { "id": 1 }, "operationName": "A" })
+        );
+        assert_eq!(envelope("{ a }", None, None), br#"{"query":"{ a }"}"#);
+    }
+
+    #[tokio::test]
+    async fn introspection_posts_to_the_request_endpoint_and_reads_the_schema() {
+        let server = TestServer::spawn().await.unwrap();
+        let prepared = introspection(
+            &server,
+            &respond(
+                200,
+                ResponseBodySpec::Json {
+                    value: schema::tests::introspection_response(),
+                },
+            ),
+        );
+        assert_eq!(prepared.method, http::Method::POST);
+        let schema = fetch_schema(HttpTransport::new_without_proxy(), prepared)
+            .await
+            .unwrap();
+        assert_eq!(schema.mutation_type.as_deref(), Some("Mutation"));
+
+        let prepared = introspection(&server, &respond(404, ResponseBodySpec::Empty));
+        assert!(matches!(
+            fetch_schema(HttpTransport::new_without_proxy(), prepared).await,
+            Err(SchemaFetchError::Status(StatusCode::NOT_FOUND))
+        ));
+
+        let prepared = introspection(
+            &server,
+            &respond(
+                200,
+                ResponseBodySpec::Json {
+                    value: serde_json::json!({ "errors": [{ "message": "disabled" }] }),
+                },
+            ),
+        );
+        assert!(matches!(
+            fetch_schema(HttpTransport::new_without_proxy(), prepared).await,
+            Err(SchemaFetchError::Introspection(IntrospectionError::Rejected(message)))
+                if message == "disabled"
+        ));
+        server.shutdown().await.unwrap();
+    }
+}
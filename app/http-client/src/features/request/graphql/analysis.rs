@@ -0,0 +1,668 @@
+use std::{collections::HashSet, ops::Range};
+
+use super::{
+    document::{
+        Definition, OperationKind, Selection, Spanned, SyntaxExpectation, Token, TokenKind, parse,
+        tokenize,
+    },
+    schema::{FieldLookup, GraphQlSchema},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum GraphQlProblem {
+    Syntax(SyntaxExpectation),
+    AnonymousOperationNotAlone,
+    DuplicateOperation(String),
+    DuplicateFragment(String),
+    UnknownFragment(String),
+    UndefinedVariable(String),
+    UnsupportedOperation(OperationKind),
+    UnknownType(String),
+    UnknownField { parent: String, field: String },
+    UnknownArgument { field: String, argument: String },
+    SelectionRequired { field: String, ty: String },
+    SelectionNotAllowed { field: String, ty: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct GraphQlDiagnostic {
+    pub(crate) range: Range<usize>,
+    pub(crate) problem: GraphQlProblem,
+}
+
+/// Checks a document on its own and, once a schema is known, against it.
+pub(crate) fn validate(source: &str, schema: Option<&GraphQlSchema>) -> Vec<GraphQlDiagnostic> {
+    let document = match parse(source) {
+        Ok(document) => document,
+        Err(error) => {
+            return vec![GraphQlDiagnostic {
+                range: error.range,
+                problem: GraphQlProblem::Syntax(error.expected),
+            }];
+        }
+    };
+    let mut out = Vec::new();
+    let mut report = |range: &Range<usize>, problem| {
+        out.push(GraphQlDiagnostic {
+            range: range.clone(),
+            problem,
+        });
+    };
+
+    let operation_count = document
+        .definitions
+        .iter()
+        .filter(|definition| matches!(definition, Definition::Operation { .. }))
+        .count();
+    let mut operation_names = HashSet::new();
+    let mut fragment_names = HashSet::new();
+    for definition in &document.definitions {
+        match definition {
+            Definition::Operation {
+                name: None,
+                keyword,
+                ..
+            } if operation_count > 1 => {
+                report(keyword, GraphQlProblem::AnonymousOperationNotAlone);
+            }
+            Definition::Operation {
+                name: Some(name), ..
+            } if !operation_names.insert(name.text.as_str()) => {
+                report(
+                    &name.range,
+                    GraphQlProblem::DuplicateOperation(name.text.clone()),
+                );
+            }
+            Definition::Fragment { name, .. } if !fragment_names.insert(name.text.as_str()) => {
+                report(
+                    &name.range,
+                    GraphQlProblem::DuplicateFragment(name.text.clone()),
+                );
+            }
+            _ => {}
+        }
+    }
+    for (index, variable) in &document.variable_uses {
+        if let Definition::Operation { variables, .. } = &document.definitions[*index]
+            && !variables
+                .iter()
+                .any(|defined| defined.text == variable.text)
+        {
+            report(
+                &variable.range,
+                GraphQlProblem::UndefinedVariable(variable.text.clone()),
+            );
+        }
+    }
+
+    let mut walker = SelectionWalker {
+        schema,
+        fragment_names: &fragment_names,
+        report: &mut report,
+    };
+    for definition in &document.definitions {
+        match definition {
+            Definition::Operation {
+                kind,
+                name,
+                keyword,
+                selections,
+                ..
+            } => {
+                let root = schema.map(|schema| root_type(schema, *kind));
+                if let Some(None) = root {
+                    let range = name.as_ref().map_or(keyword, |name| &name.range);
+                    (walker.report)(range, GraphQlProblem::UnsupportedOperation(*kind));
+                }
+                walker.walk(root.flatten(), selections);
+            }
+            Definition::Fragment {
+                type_condition,
+                selections,
+                ..
+            } => {
+                let parent = walker.type_condition(type_condition);
+                walker.walk(parent, selections);
+            }
+        }
+    }
+    out
+}
+
+struct SelectionWalker<'a, Report> {
+    schema: Option<&'a GraphQlSchema>,
+    fragment_names: &'a HashSet<&'a str>,
+    report: &'a mut Report,
+}
+
+impl<'a, Report> SelectionWalker<'a, Report>
+where
+    Report: FnMut(&Range<usize>, GraphQlProblem),
+{
+    /// Resolves a type condition, reporting it when the schema lacks it.
+    fn type_condition(&mut self, condition: &Spanned) -> Option<&'a str> {
+        let schema = self.schema?;
+        match schema.types.get_key_value(&condition.text) {
+            Some((name, _)) => Some(name),
+            None => {
+                (self.report)(
+                    &condition.range,
+                    GraphQlProblem::UnknownType(condition.text.clone()),
+                );
+                None
+            }
+        }
+    }
+
+    /// Walks one selection set. Without a known parent type only fragment
+    /// spreads are checked.
+    fn walk(&mut self, parent: Option<&'a str>, selections: &[Selection]) {
+        for selection in selections {
+            match selection {
+                Selection::FragmentSpread(name) => {
+                    if !self.fragment_names.contains(name.text.as_str()) {
+                        (self.report)(
+                            &name.range,
+                            GraphQlProblem::UnknownFragment(name.text.clone()),
+                        );
+                    }
+                }
+                Selection::InlineFragment {
+                    type_condition,
+                    selections,
+                } => {
+                    let parent = match type_condition {
+                        Some(condition) => self.type_condition(condition),
+                        None => parent,
+                    };
+                    self.walk(parent, selections);
+                }
+                Selection::Field {
+                    name,
+                    arguments,
+                    selections,
+                } => {
+                    let child = self.field(parent, name, arguments, selections.is_some());
+                    if let Some(selections) = selections {
+                        self.walk(child, selections);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks one field and returns its named type when it has fields of
+    /// its own.
+    fn field(
+        &mut self,
+        parent: Option<&'a str>,
+        name: &Spanned,
+        arguments: &[Spanned],
+        has_selections: bool,
+    ) -> Option<&'a str> {
+        let (schema, parent) = (self.schema?, parent?);
+        let field = match schema.field(parent, &name.text) {
+            Some(FieldLookup::Field(field)) => field,
+            Some(FieldLookup::TypeName | FieldLookup::Introspection) => return None,
+            None => {
+                (self.report)(
+                    &name.range,
+                    GraphQlProblem::UnknownField {
+                        parent: parent.to_owned(),
+                        field: name.text.clone(),
+                    },
+                );
+                return None;
+            }
+        };
+        for argument in arguments {
+            if !field.args.iter().any(|known| known.name == argument.text) {
+                (self.report)(
+                    &argument.range,
+                    GraphQlProblem::UnknownArgument {
+                        field: field.name.clone(),
+                        argument: argument.text.clone(),
+                    },
+                );
+            }
+        }
+        let (ty, named) = schema.types.get_key_value(field.ty.named())?;
+        match (named.kind.is_composite(), has_selections) {
+            (true, false) => (self.report)(
+                &name.range,
+                GraphQlProblem::SelectionRequired {
+                    field: field.name.clone(),
+                    ty: field.ty.to_string(),
+                },
+            ),
+            (false, true) => (self.report)(
+                &name.range,
+                GraphQlProblem::SelectionNotAllowed {
+                    field: field.name.clone(),
+                    ty: field.ty.to_string(),
+                },
+            ),
+            _ => {}
+        }
+        named.kind.is_composite().then_some(ty.as_str())
+    }
+}
+
+fn root_type(schema: &GraphQlSchema, kind: OperationKind) -> Option<&str> {
+    match kind {
+        OperationKind::Query => schema.query_type.as_deref(),
+        OperationKind::Mutation => schema.mutation_type.as_deref(),
+        OperationKind::Subscription => schema.subscription_type.as_deref(),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CompletionKind {
+    Keyword,
+    Field,
+    Argument,
+    Type,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct GraphQlCompletion {
+    pub(crate) label: String,
+    pub(crate) kind: CompletionKind,
+    /// The field or argument type.
+    pub(crate) detail: Option<String>,
+    pub(crate) documentation: Option<String>,
+}
+
+impl GraphQlCompletion {
+    fn keyword(label: &str) -> Self {
+        Self {
+            label: label.to_owned(),
+            kind: CompletionKind::Keyword,
+            detail: None,
+            documentation: None,
+        }
+    }
+}
+
+const DEFINITION_KEYWORDS: [&str; 4] = ["query", "mutation", "subscription", "fragment"];
+
+/// Suggestions for the name being typed at `offset`, and the range they
+/// replace.
+pub(crate) fn complete(
+    source: &str,
+    offset: usize,
+    schema: Option<&GraphQlSchema>,
+) -> (Range<usize>, Vec<GraphQlCompletion>) {
+    let before = &source[..offset];
+    let start = before
+        .rfind(|character: char| character != '_' && !character.is_ascii_alphanumeric())
+        .map_or(0, |index| index + 1);
+    let prefix = before[start..].to_ascii_lowercase();
+    let items = match completion_context(&before[..start], schema) {
+        CompletionContext::Definition => DEFINITION_KEYWORDS
+            .into_iter()
+            .map(GraphQlCompletion::keyword)
+            .collect(),
+        CompletionContext::Fields(parent) => schema
+            .and_then(|schema| schema.get(parent))
+            .into_iter()
+            .flat_map(|named| &named.fields)
+            .map(|field| GraphQlCompletion {
+                label: field.name.clone(),
+                kind: CompletionKind::Field,
+                detail: Some(field.ty.to_string()),
+                documentation: field.description.clone(),
+            })
+            .chain([GraphQlCompletion {
+                label: "__typename".into(),
+                kind: CompletionKind::Field,
+                detail: Some("String!".into()),
+                documentation: None,
+            }])
+            .collect(),
+        CompletionContext::Arguments { parent, field } => schema
+            .and_then(|schema| match schema.field(parent, field) {
+                Some(FieldLookup::Field(field)) => Some(&field.args),
+                _ => None,
+            })
+            .into_iter()
+            .flatten()
+            .map(|argument| GraphQlCompletion {
+                label: argument.name.clone(),
+                kind: CompletionKind::Argument,
+                detail: Some(argument.ty.to_string()),
+                documentation: argument.description.clone(),
+            })
+            .collect(),
+        CompletionContext::TypeCondition => schema
+            .into_iter()
+            .flat_map(|schema| &schema.types)
+            .filter(|(name, named)| named.kind.is_composite() && !name.starts_with("__"))
+            .map(|(name, _)| GraphQlCompletion {
+                label: name.clone(),
+                kind: CompletionKind::Type,
+                detail: None,
+                documentation: None,
+            })
+            .collect(),
+        CompletionContext::None => Vec::new(),
+    };
+    let items = items
+        .into_iter()
+        .filter(|item| item.label.to_ascii_lowercase().starts_with(&prefix))
+        .collect();
+    (start..offset, items)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum CompletionContext<'a> {
+    Definition,
+    Fields(&'a str),
+    Arguments { parent: &'a str, field: &'a str },
+    TypeCondition,
+    None,
+}
+
+enum Frame<'a> {
+    /// A selection set on the given type, or on a type the schema lacks.
+    Selection {
+        parent: Option<&'a str>,
+        last_field: Option<&'a str>,
+    },
+    Arguments {
+        parent: Option<&'a str>,
+        field: Option<&'a str>,
+    },
+    /// Variable definitions, directive arguments and nested input values.
+    Other,
+}
+
+/// Replays the tokens before the cursor to find what kind of name belongs
+/// there.
+fn completion_context<'a>(
+    before: &'a str,
+    schema: Option<&'a GraphQlSchema>,
+) -> CompletionContext<'a> {
+    let tokens = tokenize(before);
+    if in_comment_or_string(before, tokens.last()) {
+        return CompletionContext::None;
+    }
+    let mut stack = Vec::<Frame<'a>>::new();
+    // The type the next `{` opens, once a header or type condition names it.
+    let mut pending: Option<Option<&'a str>> = None;
+    for (index, token) in tokens.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|index| tokens[index]);
+        match (stack.last_mut(), token.kind) {
+            (None, TokenKind::Name) => {
+                if let Some(kind) = operation_kind(token.text) {
+                    pending = Some(schema.and_then(|schema| root_type(schema, kind)));
+                } else if previous.is_some_and(|previous| previous.is_name("on")) {
+                    pending = Some(known_type(schema, token.text));
+                }
+            }
+            (None, TokenKind::Punctuator(b'{')) => {
+                let parent = pending.take().unwrap_or_else(|| {
+                    schema.and_then(|schema| root_type(schema, OperationKind::Query))
+                });
+                stack.push(Frame::Selection {
+                    parent,
+                    last_field: None,
+                });
+            }
+            (Some(Frame::Selection { last_field, .. }), TokenKind::Name) => {
+                if previous.is_some_and(|previous| previous.is_name("on")) {
+                    pending = Some(known_type(schema, token.text));
+                } else if previous.is_some_and(|previous| previous.kind == TokenKind::Spread) {
+                    if token.text != "on" {
+                        *last_field = None;
+                        pending = None;
+                    }
+                } else if !previous.is_some_and(|previous| previous.is(b'@')) {
+                    *last_field = Some(token.text);
+                    pending = None;
+                }
+            }
+            (Some(Frame::Selection { parent, .. }), TokenKind::Spread) => {
+                pending = Some(*parent);
+            }
+            (Some(Frame::Selection { parent, last_field }), TokenKind::Punctuator(b'(')) => {
+                let frame = if previous.is_some_and(|previous| {
+                    previous.kind == TokenKind::Name && index >= 2 && tokens[index - 2].is(b'@')
+                }) {
+                    Frame::Other
+                } else {
+                    Frame::Arguments {
+                        parent: *parent,
+                        field: *last_field,
+                    }
+                };
+                stack.push(frame);
+            }
+            (Some(Frame::Selection { parent, last_field }), TokenKind::Punctuator(b'{')) => {
+                let child = match pending.take() {
+                    Some(condition) => condition,
+                    None => field_type(schema, *parent, *last_field),
+                };
+                *last_field = None;
+                stack.push(Frame::Selection {
+                    parent: child,
+                    last_field: None,
+                });
+            }
+            (Some(_), TokenKind::Punctuator(b'(' | b'[' | b'{'))
+            | (None, TokenKind::Punctuator(b'(')) => {
+                stack.push(Frame::Other);
+            }
+            (Some(_), TokenKind::Punctuator(b')' | b']' | b'}')) => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    let previous = tokens.last();
+    let after = |punctuator| previous.is_some_and(|previous| previous.is(punctuator));
+    if previous.is_some_and(|previous| previous.is_name("on")) {
+        return CompletionContext::TypeCondition;
+    }
+    match stack.last() {
+        None if previous.is_none() || after(b'}') => CompletionContext::Definition,
+        Some(Frame::Selection {
+            parent: Some(parent),
+            ..
+        }) if !after(b'@')
+            && !previous.is_some_and(|previous| previous.kind == TokenKind::Spread) =>
+        {
+            CompletionContext::Fields(parent)
+        }
+        Some(Frame::Arguments {
+            parent: Some(parent),
+            field: Some(field),
+        }) if !after(b':') && !after(b'$') => CompletionContext::Arguments { parent, field },
+        _ => CompletionContext::None,
+    }
+}
+
+fn operation_kind(keyword: &str) -> Option<OperationKind> {
+    match keyword {
+        "query" => Some(OperationKind::Query),
+        "mutation" => Some(OperationKind::Mutation),
+        "subscription" => Some(OperationKind::Subscription),
+        _ => None,
+    }
+}
+
+fn known_type<'a>(schema: Option<&'a GraphQlSchema>, name: &str) -> Option<&'a str> {
+    schema?
+        .types
+        .get_key_value(name)
+        .map(|(name, _)| name.as_str())
+}
+
+fn field_type<'a>(
+    schema: Option<&'a GraphQlSchema>,
+    parent: Option<&str>,
+    field: Option<&str>,
+) -> Option<&'a str> {
+    let schema = schema?;
+    match schema.field(parent?, field?)? {
+        FieldLookup::Field(field) => known_type(Some(schema), field.ty.named()),
+        FieldLookup::TypeName | FieldLookup::Introspection => None,
+    }
+}
+
+/// Comments and strings are skipped by the tokenizer, so the cursor is in
+/// one when the text after the last token opens a comment on the current
+/// line, or the last token is a string still running up to the cursor.
+fn in_comment_or_string(before: &str, last: Option<&Token<'_>>) -> bool {
+    let gap_start = last.map_or(0, |token| token.range().end);
+    let gap = &before[gap_start..];
+    let line = gap.rsplit(['\r', '\n']).next().unwrap_or(gap);
+    if line.contains('#') {
+        return true;
+    }
+    last.is_some_and(|token| {
+        token.kind == TokenKind::Literal
+            && token.text.starts_with('"')
+            && token.range().end == before.len()
+            && !is_closed_string(token.text)
+    })
+}
+
+fn is_closed_string(text: &str) -> bool {
+    if let Some(body) = text.strip_prefix("\"\"\"") {
+        return body.ends_with("\"\"\"") && !body.ends_with("\\\"\"\"");
+    }
+    let body = &text[1..];
+    let escapes = body
+        .strip_suffix('"')
+        .map(|inner| inner.len() - inner.trim_end_matches('\\').len());
+    escapes.is_some_and(|escapes| escapes % 2 == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::schema::tests::introspection_response, *};
+
+    fn schema() -> GraphQlSchema {
+        GraphQlSchema::from_response(&introspection_response()).unwrap()
+    }
+
+    fn problems(source: &str) -> Vec<(String, GraphQlProblem)> {
+        validate(source, Some(&schema()))
+            .into_iter()
+            .map(|diagnostic| (source[diagnostic.range].to_owned(), diagnostic.problem))
+            .collect()
+    }
+
+    fn labels(source: &str) -> Vec<String> {
+        let offset = source.find('|').expect("cursor marker");
+        let source = source.replacen('|', "", 1);
+        complete(&source, offset, Some(&schema()))
+            .1
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+    }
+
+    #[test]
+    fn documents_are_checked_against_the_schema() {
+        assert_eq!(
+            problems(
+                "query Q($id: ID!) { user(id: $id, limit: 1) { name friends missing } }
+                 subscription S { ticks }
+                 query Q { users { ...Parts ...Gone __typename id { x } } }
+                 fragment Parts on Person { id }
+                 mutation { rename(name: $name) { id } }"
+            ),
+            [
+                (
+                    "Q".to_owned(),
+                    GraphQlProblem::DuplicateOperation("Q".into())
+                ),
+                (
+                    "mutation".to_owned(),
+                    GraphQlProblem::AnonymousOperationNotAlone
+                ),
+                (
+                    "name".to_owned(),
+                    GraphQlProblem::UndefinedVariable("name".into())
+                ),
+                (
+                    "limit".to_owned(),
+                    GraphQlProblem::UnknownArgument {
+                        field: "user".into(),
+                        argument: "limit".into(),
+                    }
+                ),
+                (
+                    "friends".to_owned(),
+                    GraphQlProblem::SelectionRequired {
+                        field: "friends".into(),
+                        ty: "[User]".into(),
+                    }
+                ),
+                (
+                    "missing".to_owned(),
+                    GraphQlProblem::UnknownField {
+                        parent: "User".into(),
+                        field: "missing".into(),
+                    }
+                ),
+                (
+                    "S".to_owned(),
+                    GraphQlProblem::UnsupportedOperation(OperationKind::Subscription)
+                ),
+                (
+                    "Gone".to_owned(),
+                    GraphQlProblem::UnknownFragment("Gone".into())
+                ),
+                (
+                    "id".to_owned(),
+                    GraphQlProblem::SelectionNotAllowed {
+                        field: "id".into(),
+                        ty: "ID!".into(),
+                    }
+                ),
+                (
+                    "Person".to_owned(),
+                    GraphQlProblem::UnknownType("Person".into())
+                ),
+            ]
+        );
+        assert_eq!(
+            problems("{ user(id: 1) { ... on User { name(upper: true) } } }"),
+            []
+        );
+        assert_eq!(
+            validate("query { user(id: ) }", None),
+            [GraphQlDiagnostic {
+                range: 17..18,
+                problem: GraphQlProblem::Syntax(SyntaxExpectation::Value),
+            }]
+        );
+    }
+
+    #[test]
+    fn completion_follows_the_selection_path_to_the_cursor() {
+        assert_eq!(labels("|"), DEFINITION_KEYWORDS);
+        assert_eq!(labels("{ a }\nmu|"), ["mutation"]);
+        assert_eq!(labels("{ |"), ["user", "users", "__typename"]);
+        assert_eq!(
+            labels("query($id: ID!) { me: user(id: $id) { fr| } }"),
+            ["friends"]
+        );
+        assert_eq!(labels("{ users { friends { n| } } }"), ["name"]);
+        assert_eq!(labels("{ user(|) { id } }"), ["id"]);
+        assert_eq!(labels("{ user(id: \"x\") { name(u|) } }"), ["upper"]);
+        assert_eq!(labels("{ user(id: |) }"), Vec::<String>::new());
+        assert_eq!(labels("mutation { r| }"), ["rename"]);
+        assert_eq!(labels("{ users { ... on |"), ["Mutation", "Query", "User"]);
+        assert_eq!(
+            labels("fragment F on User { ... @include(if: true) { i| } }"),
+            ["id"]
+        );
+        assert_eq!(labels("{ users { # i|"), Vec::<String>::new());
+        assert_eq!(labels("{ user(id: \"i|"), Vec::<String>::new());
+    }
+}
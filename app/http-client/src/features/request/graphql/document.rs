@@ -0,0 +1,615 @@
+//! A small tolerant reader for GraphQL executable documents.
+//!
+//! Only what the editor needs is modelled: operations and fragments with
+//! their selection sets, field arguments and variable references, each with
+//! the byte range it came from.
+
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum OperationKind {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl OperationKind {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "query" => Some(Self::Query),
+            "mutation" => Some(Self::Mutation),
+            "subscription" => Some(Self::Subscription),
+            _ => None,
+        }
+    }
+
+    pub(crate) const fn keyword(self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Mutation => "mutation",
+            Self::Subscription => "subscription",
+        }
+    }
+}
+
+/// One operation as listed by the operation picker.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct OperationSummary {
+    pub(crate) kind: OperationKind,
+    pub(crate) name: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum TokenKind {
+    Name,
+    Punctuator(u8),
+    Spread,
+    /// A string or number literal.
+    Literal,
+    Invalid,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct Token<'a> {
+    pub(super) kind: TokenKind,
+    pub(super) text: &'a str,
+    pub(super) start: usize,
+}
+
+impl Token<'_> {
+    pub(super) fn range(&self) -> Range<usize> {
+        self.start..self.start + self.text.len()
+    }
+
+    pub(super) fn is(&self, punctuator: u8) -> bool {
+        self.kind == TokenKind::Punctuator(punctuator)
+    }
+
+    pub(super) fn is_name(&self, name: &str) -> bool {
+        self.kind == TokenKind::Name && self.text == name
+    }
+}
+
+/// Splits `source` into significant tokens. Whitespace, commas and comments
+/// are dropped; an unterminated string runs to the end of the source.
+pub(super) fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let start = index;
+        let kind = match bytes[index] {
+            b' ' | b'\t' | b'\r' | b'\n' | b',' => {
+                index += 1;
+                continue;
+            }
+            b'#' => {
+                while index < bytes.len() && !matches!(bytes[index], b'\r' | b'\n') {
+                    index += 1;
+                }
+                continue;
+            }
+            b'.' if bytes[index..].starts_with(b"...") => {
+                index += 3;
+                TokenKind::Spread
+            }
+            punctuator @ (b'!' | b'$' | b'&' | b'(' | b')' | b'[' | b']' | b'{' | b'}' | b':'
+            | b'=' | b'@' | b'|') => {
+                index += 1;
+                TokenKind::Punctuator(punctuator)
+            }
+            b'"' if bytes[index..].starts_with(b"\"\"\"") => {
+                index += 3;
+                loop {
+                    if index >= bytes.len() {
+                        break;
+                    }
+                    if bytes[index..].starts_with(b"\\\"\"\"") {
+                        index += 4;
+                    } else if bytes[index..].starts_with(b"\"\"\"") {
+                        index += 3;
+                        break;
+                    } else {
+                        index += 1;
+                    }
+                }
+                TokenKind::Literal
+            }
+            b'"' => {
+                index += 1;
+                while index < bytes.len() && !matches!(bytes[index], b'"' | b'\r' | b'\n') {
+                    index += if bytes[index] == b'\\' { 2 } else { 1 };
+                }
+                index = (index + 1).min(bytes.len());
+                TokenKind::Literal
+            }
+            b'-' | b'0'..=b'9' => {
+                index += 1;
+                while index < bytes.len()
+                    && (bytes[index].is_ascii_alphanumeric()
+                        || matches!(bytes[index], b'.' | b'+' | b'-'))
+                {
+                    index += 1;
+                }
+                TokenKind::Literal
+            }
+            byte if byte == b'_' || byte.is_ascii_alphabetic() => {
+                while index < bytes.len()
+                    && (bytes[index] == b'_' || bytes[index].is_ascii_alphanumeric())
+                {
+                    index += 1;
+                }
+                TokenKind::Name
+            }
+            _ => {
+                index += source[index..].chars().next().map_or(1, char::len_utf8);
+                TokenKind::Invalid
+            }
+        };
+        tokens.push(Token {
+            kind,
+            text: &source[start..index.min(source.len())],
+            start,
+        });
+    }
+    tokens
+}
+
+/// Lists the operations at the top level of `source`. This only looks at
+/// definition headers, so it keeps working while a body is half typed.
+pub(crate) fn operations(source: &str) -> Vec<OperationSummary> {
+    let tokens = tokenize(source);
+    let mut operations = Vec::new();
+    let mut depth = 0usize;
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = tokens[index];
+        if token.is(b'{') {
+            if depth == 0 {
+                operations.push(OperationSummary {
+                    kind: OperationKind::Query,
+                    name: None,
+                });
+            }
+            depth += 1;
+        } else if token.is(b'}') {
+            depth = depth.saturating_sub(1);
+        } else if depth == 0
+            && token.kind == TokenKind::Name
+            && let Some(kind) = OperationKind::from_keyword(token.text)
+        {
+            let name = tokens
+                .get(index + 1)
+                .filter(|next| next.kind == TokenKind::Name)
+                .map(|next| next.text.to_owned());
+            operations.push(OperationSummary { kind, name });
+            index = skip_header(&tokens, index);
+            depth += 1;
+        } else if depth == 0 && token.is_name("fragment") {
+            index = skip_header(&tokens, index);
+            depth += 1;
+        }
+        index += 1;
+    }
+    operations
+}
+
+/// Returns the index of the `{` that opens the selection set of the
+/// definition starting at `start`, so that brace is not read as shorthand.
+fn skip_header(tokens: &[Token<'_>], start: usize) -> usize {
+    let mut index = start;
+    while index < tokens.len() && !tokens[index].is(b'{') {
+        index = if tokens[index].is(b'(') {
+            skip_group(tokens, index)
+        } else {
+            index + 1
+        };
+    }
+    index
+}
+
+/// Returns the index after the group opened at `open`.
+fn skip_group(tokens: &[Token<'_>], open: usize) -> usize {
+    let mut depth = 0usize;
+    for (index, token) in tokens.iter().enumerate().skip(open) {
+        match token.kind {
+            TokenKind::Punctuator(b'(' | b'[' | b'{') => depth += 1,
+            TokenKind::Punctuator(b')' | b']' | b'}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return index + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Spanned {
+    pub(super) text: String,
+    pub(super) range: Range<usize>,
+}
+
+impl Spanned {
+    fn from_token(token: &Token<'_>) -> Self {
+        Self {
+            text: token.text.to_owned(),
+            range: token.range(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Definition {
+    Operation {
+        kind: OperationKind,
+        name: Option<Spanned>,
+        /// The operation keyword, or the opening brace of the shorthand form.
+        keyword: Range<usize>,
+        variables: Vec<Spanned>,
+        selections: Vec<Selection>,
+    },
+    Fragment {
+        name: Spanned,
+        type_condition: Spanned,
+        selections: Vec<Selection>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Selection {
+    Field {
+        name: Spanned,
+        arguments: Vec<Spanned>,
+        /// `None` for a leaf field without braces.
+        selections: Option<Vec<Selection>>,
+    },
+    FragmentSpread(Spanned),
+    InlineFragment {
+        type_condition: Option<Spanned>,
+        selections: Vec<Selection>,
+    },
+}
+
+/// Variables referenced anywhere in argument or directive values.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(super) struct Document {
+    pub(super) definitions: Vec<Definition>,
+    pub(super) variable_uses: Vec<(usize, Spanned)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SyntaxExpectation {
+    Definition,
+    Name,
+    Punctuator(char),
+    Value,
+    End,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SyntaxError {
+    pub(crate) range: Range<usize>,
+    pub(crate) expected: SyntaxExpectation,
+}
+
+pub(super) fn parse(source: &str) -> Result<Document, SyntaxError> {
+    let tokens = tokenize(source);
+    let mut parser = Parser {
+        tokens: &tokens,
+        index: 0,
+        end: source.len(),
+        document: Document::default(),
+    };
+    while parser.peek().is_some() {
+        let definition = parser.definition()?;
+        parser.document.definitions.push(definition);
+    }
+    Ok(parser.document)
+}
+
+struct Parser<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    index: usize,
+    end: usize,
+    document: Document,
+}
+
+impl<'a> Parser<'_, 'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.index).copied()
+    }
+
+    fn bump(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.index += usize::from(token.is_some());
+        token
+    }
+
+    fn error(&self, expected: SyntaxExpectation) -> SyntaxError {
+        SyntaxError {
+            range: self
+                .peek()
+                .map_or(self.end..self.end, |token| token.range()),
+            expected,
+        }
+    }
+
+    fn eat(&mut self, punctuator: u8) -> bool {
+        let matched = self.peek().is_some_and(|token| token.is(punctuator));
+        self.index += usize::from(matched);
+        matched
+    }
+
+    fn expect(&mut self, punctuator: u8) -> Result<(), SyntaxError> {
+        if self.eat(punctuator) {
+            Ok(())
+        } else {
+            Err(self.error(SyntaxExpectation::Punctuator(char::from(punctuator))))
+        }
+    }
+
+    fn name(&mut self) -> Result<Spanned, SyntaxError> {
+        match self.peek() {
+            Some(token) if token.kind == TokenKind::Name => {
+                self.index += 1;
+                Ok(Spanned::from_token(&token))
+            }
+            _ => Err(self.error(SyntaxExpectation::Name)),
+        }
+    }
+
+    fn definition(&mut self) -> Result<Definition, SyntaxError> {
+        let token = self.peek().expect("called with a token left");
+        if token.is(b'{') {
+            let selections = self.selection_set()?;
+            return Ok(Definition::Operation {
+                kind: OperationKind::Query,
+                name: None,
+                keyword: token.range(),
+                variables: Vec::new(),
+                selections,
+            });
+        }
+        if token.is_name("fragment") {
+            self.index += 1;
+            let name = self.name()?;
+            if !self.peek().is_some_and(|token| token.is_name("on")) {
+                return Err(self.error(SyntaxExpectation::Name));
+            }
+            self.index += 1;
+            let type_condition = self.name()?;
+            self.directives()?;
+            let selections = self.selection_set()?;
+            return Ok(Definition::Fragment {
+                name,
+                type_condition,
+                selections,
+            });
+        }
+        let Some(kind) = (token.kind == TokenKind::Name)
+            .then(|| OperationKind::from_keyword(token.text))
+            .flatten()
+        else {
+            return Err(self.error(SyntaxExpectation::Definition));
+        };
+        self.index += 1;
+        let name = match self.peek() {
+            Some(token) if token.kind == TokenKind::Name => Some(self.name()?),
+            _ => None,
+        };
+        let mut variables = Vec::new();
+        if self.eat(b'(') {
+            while !self.eat(b')') {
+                self.expect(b'$')?;
+                variables.push(self.name()?);
+                self.expect(b':')?;
+                self.type_reference()?;
+                if self.eat(b'=') {
+                    self.value(true)?;
+                }
+                self.directives()?;
+            }
+        }
+        self.directives()?;
+        let selections = self.selection_set()?;
+        Ok(Definition::Operation {
+            kind,
+            name,
+            keyword: token.range(),
+            variables,
+            selections,
+        })
+    }
+
+    fn type_reference(&mut self) -> Result<(), SyntaxError> {
+        if self.eat(b'[') {
+            self.type_reference()?;
+            self.expect(b']')?;
+        } else {
+            self.name()?;
+        }
+        self.eat(b'!');
+        Ok(())
+    }
+
+    fn directives(&mut self) -> Result<(), SyntaxError> {
+        while self.eat(b'@') {
+            self.name()?;
+            self.arguments()?;
+        }
+        Ok(())
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Spanned>, SyntaxError> {
+        let mut arguments = Vec::new();
+        if self.eat(b'(') {
+            while !self.eat(b')') {
+                arguments.push(self.name()?);
+                self.expect(b':')?;
+                self.value(false)?;
+            }
+        }
+        Ok(arguments)
+    }
+
+    fn value(&mut self, constant: bool) -> Result<(), SyntaxError> {
+        let Some(token) = self.peek() else {
+            return Err(self.error(SyntaxExpectation::Value));
+        };
+        match token.kind {
+            TokenKind::Punctuator(b'$') if !constant => {
+                self.index += 1;
+                let name = self.name()?;
+                let operation = self.document.definitions.len();
+                self.document.variable_uses.push((operation, name));
+            }
+            TokenKind::Punctuator(b'[') => {
+                self.index += 1;
+                while !self.eat(b']') {
+                    self.value(constant)?;
+                }
+            }
+            TokenKind::Punctuator(b'{') => {
+                self.index += 1;
+                while !self.eat(b'}') {
+                    self.name()?;
+                    self.expect(b':')?;
+                    self.value(constant)?;
+                }
+            }
+            TokenKind::Name | TokenKind::Literal => self.index += 1,
+            _ => return Err(self.error(SyntaxExpectation::Value)),
+        }
+        Ok(())
+    }
+
+    fn selection_set(&mut self) -> Result<Vec<Selection>, SyntaxError> {
+        self.expect(b'{')?;
+        let mut selections = Vec::new();
+        while !self.eat(b'}') {
+            selections.push(self.selection()?);
+        }
+        Ok(selections)
+    }
+
+    fn selection(&mut self) -> Result<Selection, SyntaxError> {
+        if self.eat_spread() {
+            match self.peek() {
+                Some(token) if token.kind == TokenKind::Name && token.text != "on" => {
+                    let name = self.name()?;
+                    self.directives()?;
+                    return Ok(Selection::FragmentSpread(name));
+                }
+                _ => {}
+            }
+            let type_condition = if self.peek().is_some_and(|token| token.is_name("on")) {
+                self.index += 1;
+                Some(self.name()?)
+            } else {
+                None
+            };
+            self.directives()?;
+            let selections = self.selection_set()?;
+            return Ok(Selection::InlineFragment {
+                type_condition,
+                selections,
+            });
+        }
+
+        let mut name = self.name()?;
+        if self.eat(b':') {
+            name = self.name()?;
+        }
+        let arguments = self.arguments()?;
+        self.directives()?;
+        let selections = if self.peek().is_some_and(|token| token.is(b'{')) {
+            Some(self.selection_set()?)
+        } else {
+            None
+        };
+        Ok(Selection::Field {
+            name,
+            arguments,
+            selections,
+        })
+    }
+
+    fn eat_spread(&mut self) -> bool {
+        let matched = self
+            .peek()
+            .is_some_and(|token| token.kind == TokenKind::Spread);
+        self.index += usize::from(matched);
+        matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations_are_listed_from_headers_even_while_a_body_is_incomplete() {
+        let source = r#"
+            # query Commented { ignored }
+            query Viewer($first: Int = 10) { viewer { name(format: "{") } }
+            fragment Parts on User { id }
+            mutation Rename { rename(name: "x") { id }
+            subscription { updates {"#;
+        assert_eq!(
+            operations(source),
+            [
+                OperationSummary {
+                    kind: OperationKind::Query,
+                    name: Some("Viewer".into()),
+                },
+                OperationSummary {
+                    kind: OperationKind::Mutation,
+                    name: Some("Rename".into()),
+                },
+            ]
+        );
+        assert_eq!(
+            operations("{ a } { b }"),
+            vec![
+                OperationSummary {
+                    kind: OperationKind::Query,
+                    name: None,
+                };
+                2
+            ]
+        );
+    }
+
+    #[test]
+    fn documents_parse_into_ranged_selections_and_report_the_first_syntax_error() {
+        let source = "query Q($id: ID!) { node(id: $id) { ... on User { name } ...Parts } }";
+        let document = parse(source).unwrap();
+        let [Definition::Operation { selections, .. }] = &document.definitions[..] else {
+            panic!("expected one operation");
+        };
+        let [
+            Selection::Field {
+                name,
+                arguments,
+                selections: Some(children),
+            },
+        ] = &selections[..]
+        else {
+            panic!("expected one field");
+        };
+        assert_eq!(name.text, "node");
+        assert_eq!(&source[arguments[0].range.clone()], "id");
+        assert!(matches!(children[1], Selection::FragmentSpread(ref name) if name.text == "Parts"));
+        assert_eq!(document.variable_uses[0].1.text, "id");
+
+        let error = parse("query { user(id: ) }").unwrap_err();
+        assert_eq!(error.expected, SyntaxExpectation::Value);
+        assert_eq!(error.range, 17..18);
+        let error = parse("{ user ").unwrap_err();
+        assert_eq!(error.expected, SyntaxExpectation::Name);
+        assert_eq!(error.range, 7..7);
+    }
+}
@@ -0,0 +1,382 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde_json::Value;
+use thiserror::Error;
+
+/// The standard introspection query, trimmed to what completion and
+/// validation read.
+pub(crate) const INTROSPECTION_QUERY: &str = "query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      kind
+      name
+      fields(includeDeprecated: true) {
+        name
+        description
+        args { name description type { ...TypeRef } }
+        type { ...TypeRef }
+      }
+    }
+  }
+}
+
+fragment TypeRef on __Type {
+  kind
+  name
+  ofType {
+    kind
+    name
+    ofType {
+      kind
+      name
+      ofType {
+        kind
+        name
+        ofType {
+          kind
+          name
+          ofType {
+            kind
+            name
+            ofType {
+              kind
+              name
+              ofType { kind name }
+            }
+          }
+        }
+      }
+    }
+  }
+}
+";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TypeKind {
+    Scalar,
+    Object,
+    Interface,
+    Union,
+    Enum,
+    InputObject,
+}
+
+impl TypeKind {
+    fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "SCALAR" => Some(Self::Scalar),
+            "OBJECT" => Some(Self::Object),
+            "INTERFACE" => Some(Self::Interface),
+            "UNION" => Some(Self::Union),
+            "ENUM" => Some(Self::Enum),
+            "INPUT_OBJECT" => Some(Self::InputObject),
+            _ => None,
+        }
+    }
+
+    /// Whether a field of this type needs a selection set.
+    pub(crate) const fn is_composite(self) -> bool {
+        matches!(self, Self::Object | Self::Interface | Self::Union)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TypeRef {
+    Named(String),
+    List(Box<TypeRef>),
+    NonNull(Box<TypeRef>),
+}
+
+impl TypeRef {
+    pub(crate) fn named(&self) -> &str {
+        match self {
+            Self::Named(name) => name,
+            Self::List(inner) | Self::NonNull(inner) => inner.named(),
+        }
+    }
+
+    fn parse(value: &Value) -> Result<Self, IntrospectionError> {
+        let of_type = || {
+            value
+                .get("ofType")
+                .filter(|inner| !inner.is_null())
+                .ok_or(IntrospectionError::Malformed)
+                .and_then(Self::parse)
+                .map(Box::new)
+        };
+        match value.get("kind").and_then(Value::as_str) {
+            Some("NON_NULL") => Ok(Self::NonNull(of_type()?)),
+            Some("LIST") => Ok(Self::List(of_type()?)),
+            Some(_) => string(value, "name").map(Self::Named),
+            None => Err(IntrospectionError::Malformed),
+        }
+    }
+}
+
+impl fmt::Display for TypeRef {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Named(name) => formatter.write_str(name),
+            Self::List(inner) => write!(formatter, "[{inner}]"),
+            Self::NonNull(inner) => write!(formatter, "{inner}!"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct InputValue {
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+    pub(crate) ty: TypeRef,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Field {
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+    pub(crate) args: Vec<InputValue>,
+    pub(crate) ty: TypeRef,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct NamedType {
+    pub(crate) kind: TypeKind,
+    pub(crate) fields: Vec<Field>,
+}
+
+/// The parts of an introspected schema the editor reads.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct GraphQlSchema {
+    pub(crate) query_type: Option<String>,
+    pub(crate) mutation_type: Option<String>,
+    pub(crate) subscription_type: Option<String>,
+    pub(crate) types: BTreeMap<String, NamedType>,
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub(crate) enum IntrospectionError {
+    #[error("the introspection response was rejected: {0}")]
+    Rejected(String),
+    #[error("the introspection response has no schema")]
+    MissingSchema,
+    #[error("the introspection response is malformed")]
+    Malformed,
+}
+
+impl GraphQlSchema {
+    /// Reads a standard GraphQL response to [`INTROSPECTION_QUERY`].
+    pub(crate) fn from_response(response: &Value) -> Result<Self, IntrospectionError> {
+        let schema = response
+            .pointer("/data/__schema")
+            .filter(|schema| !schema.is_null());
+        let Some(schema) = schema else {
+            let message = response
+                .pointer("/errors/0/message")
+                .and_then(Value::as_str);
+            return Err(match message {
+                Some(message) => IntrospectionError::Rejected(message.to_owned()),
+                None => IntrospectionError::MissingSchema,
+            });
+        };
+        let root = |key: &str| {
+            schema
+                .get(key)
+                .and_then(|root| root.get("name"))
+                .and_then(Value::as_str)
+                .map(str::to_owned)
+        };
+        let mut types = BTreeMap::new();
+        for raw in list(schema, "types")? {
+            let Some(kind) = raw
+                .get("kind")
+                .and_then(Value::as_str)
+                .and_then(TypeKind::parse)
+            else {
+                return Err(IntrospectionError::Malformed);
+            };
+            let fields = list(raw, "fields")?
+                .iter()
+                .map(|field| {
+                    Ok(Field {
+                        name: string(field, "name")?,
+                        description: description(field),
+                        args: list(field, "args")?
+                            .iter()
+                            .map(input_value)
+                            .collect::<Result<_, _>>()?,
+                        ty: TypeRef::parse(field.get("type").unwrap_or(&Value::Null))?,
+                    })
+                })
+                .collect::<Result<_, IntrospectionError>>()?;
+            types.insert(string(raw, "name")?, NamedType { kind, fields });
+        }
+        Ok(Self {
+            query_type: root("queryType"),
+            mutation_type: root("mutationType"),
+            subscription_type: root("subscriptionType"),
+            types,
+        })
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&NamedType> {
+        self.types.get(name)
+    }
+
+    /// Looks a field up on an object or interface, including the meta fields
+    /// every selection set may use.
+    pub(crate) fn field(&self, parent: &str, name: &str) -> Option<FieldLookup<'_>> {
+        if name == "__typename" {
+            return Some(FieldLookup::TypeName);
+        }
+        if self.query_type.as_deref() == Some(parent) && matches!(name, "__schema" | "__type") {
+            return Some(FieldLookup::Introspection);
+        }
+        self.get(parent)?
+            .fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(FieldLookup::Field)
+    }
+}
+
+pub(crate) enum FieldLookup<'a> {
+    Field(&'a Field),
+    TypeName,
+    Introspection,
+}
+
+/// Lists default to empty: servers send `null` for kinds that do not have them.
+fn list<'a>(value: &'a Value, key: &str) -> Result<&'a [Value], IntrospectionError> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(&[]),
+        Some(Value::Array(items)) => Ok(items),
+        Some(_) => Err(IntrospectionError::Malformed),
+    }
+}
+
+fn string(value: &Value, key: &str) -> Result<String, IntrospectionError> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::to_owned)
+        .ok_or(IntrospectionError::Malformed)
+}
+
+fn description(value: &Value) -> Option<String> {
+    value
+        .get("description")
+        .and_then(Value::as_str)
+        .filter(|description| !description.is_empty())
+        .map(str::to_owned)
+}
+
+fn input_value(value: &Value) -> Result<InputValue, IntrospectionError> {
+    Ok(InputValue {
+        name: string(value, "name")?,
+        description: description(value),
+        ty: TypeRef::parse(value.get("type").unwrap_or(&Value::Null))?,
+    })
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn named(name: &str) -> Value {
+        json!({ "kind": "OBJECT", "name": name, "ofType": null })
+    }
+
+    fn scalar(name: &str) -> Value {
+        json!({ "kind": "SCALAR", "name": name, "ofType": null })
+    }
+
+    /// `Query { user(id: ID!): User, users: [User!]! }`,
+    /// `User { id: ID!, name(upper: Boolean): String, friends: [User] }` and
+    /// `Mutation { rename(name: String!): User }`.
+    pub(in crate::features::request::graphql) fn introspection_response() -> Value {
+        let non_null = |inner: Value| json!({ "kind": "NON_NULL", "name": null, "ofType": inner });
+        let list = |inner: Value| json!({ "kind": "LIST", "name": null, "ofType": inner });
+        let arg = |name: &str, ty: Value| json!({ "name": name, "description": null, "type": ty });
+        let field = |name: &str, args: Vec<Value>, ty: Value| json!({ "name": name, "description": null, "args": args, "type": ty });
+        json!({
+            "data": {
+                "__schema": {
+                    "queryType": { "name": "Query" },
+                    "mutationType": { "name": "Mutation" },
+                    "subscriptionType": null,
+                    "types": [
+                        {
+                            "kind": "OBJECT",
+                            "name": "Query",
+                            "fields": [
+                                field("user", vec![arg("id", non_null(scalar("ID")))], named("User")),
+                                field("users", vec![], non_null(list(non_null(named("User"))))),
+                            ],
+                            "inputFields": null,
+                        },
+                        {
+                            "kind": "OBJECT",
+                            "name": "User",
+                            "fields": [
+                                field("id", vec![], non_null(scalar("ID"))),
+                                field("name", vec![arg("upper", scalar("Boolean"))], scalar("String")),
+                                field("friends", vec![], list(named("User"))),
+                            ],
+                            "inputFields": null,
+                        },
+                        {
+                            "kind": "OBJECT",
+                            "name": "Mutation",
+                            "fields": [
+                                field("rename", vec![arg("name", non_null(scalar("String")))], named("User")),
+                            ],
+                        },
+                        { "kind": "SCALAR", "name": "ID" },
+                        { "kind": "SCALAR", "name": "String" },
+                        { "kind": "SCALAR", "name": "Boolean" },
+                    ],
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn introspection_responses_become_a_schema_and_errors_are_reported() {
+        let schema = GraphQlSchema::from_response(&introspection_response()).unwrap();
+        assert_eq!(schema.query_type.as_deref(), Some("Query"));
+        assert_eq!(schema.subscription_type, None);
+        let users = &schema.get("Query").unwrap().fields[1];
+        assert_eq!(users.ty.to_string(), "[User!]!");
+        assert_eq!(users.ty.named(), "User");
+        assert!(schema.get("User").unwrap().kind.is_composite());
+        assert!(!schema.get("ID").unwrap().kind.is_composite());
+        assert!(matches!(
+            schema.field("Query", "__schema"),
+            Some(FieldLookup::Introspection)
+        ));
+        assert!(schema.field("User", "__schema").is_none());
+
+        assert_eq!(
+            GraphQlSchema::from_response(&json!({
+                "data": null,
+                "errors": [{ "message": "introspection is disabled" }],
+            })),
+            Err(IntrospectionError::Rejected(
+                "introspection is disabled".into()
+            ))
+        );
+        assert_eq!(
+            GraphQlSchema::from_response(&json!({ "data": {} })),
+            Err(IntrospectionError::MissingSchema)
+        );
+        assert_eq!(
+            GraphQlSchema::from_response(&json!({ "data": { "__schema": { "types": {} } } })),
+            Err(IntrospectionError::Malformed)
+        );
+    }
+}
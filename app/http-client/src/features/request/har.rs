@@ -0,0 +1,224 @@
+//! HTTP Archive (HAR 1.2) files: browser captures become saved requests,
+//! and completed sends are written back out for bug reports.
+
+use std::io;
+
+use fluent_bundle::FluentArgs;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::saved::SavedRequestError;
+use crate::foundation::I18n;
+
+pub(crate) use self::{
+    export::{ExchangeRecord, RequestHistory, export_har},
+    import::{HarImportSummary, import_har},
+};
+
+mod export;
+mod import;
+
+pub(crate) const HAR_EXTENSION: &str = "har";
+const HAR_VERSION: &str = "1.2";
+
+#[derive(Debug, Error)]
+pub(crate) enum HarError {
+    #[error("HAR file could not be read")]
+    Read(#[source] io::Error),
+    #[error("HAR file could not be written")]
+    Write(#[source] io::Error),
+    #[error("HAR file is malformed")]
+    Malformed(#[source] serde_json::Error),
+    #[error("HAR file has no requests that can be imported")]
+    NoRequests,
+    #[error(transparent)]
+    Save(#[from] SavedRequestError),
+}
+
+impl HarError {
+    pub(crate) const fn message_key(&self) -> &'static str {
+        match self {
+            Self::Read(_) => "har-read-failed",
+            Self::Write(_) => "har-write-failed",
+            Self::Malformed(_) => "har-malformed",
+            Self::NoRequests => "har-no-requests",
+            Self::Save(error) => error.message_key(),
+        }
+    }
+}
+
+/// The outcome of the last import or export.
+pub(crate) enum HarNotice {
+    Imported(HarImportSummary),
+    Exported(usize),
+    Failed(HarError),
+}
+
+impl HarNotice {
+    pub(crate) const fn is_problem(&self) -> bool {
+        matches!(self, Self::Failed(_))
+    }
+
+    pub(crate) fn message(&self, i18n: &I18n) -> String {
+        let mut args = FluentArgs::new();
+        match self {
+            Self::Imported(summary) => {
+                args.set("count", summary.imported);
+                args.set("skipped", summary.skipped);
+                let key = if summary.skipped == 0 {
+                    "har-imported"
+                } else {
+                    "har-imported-skipped"
+                };
+                i18n.t_with_args(key, &args)
+            }
+            Self::Exported(count) => {
+                args.set("count", *count);
+                i18n.t_with_args("har-exported", &args)
+            }
+            Self::Failed(error) => i18n.t(error.message_key()),
+        }
+    }
+}
+
+/// The archive model. Every field defaults so captures from tools that
+/// leave out parts of the format still import.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct HarFile {
+    log: HarLog,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct HarLog {
+    version: String,
+    creator: HarCreator,
+    entries: Vec<HarEntry>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct HarCreator {
+    name: String,
+    version: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct HarEntry {
+    started_date_time: String,
+    time: f64,
+    request: HarRequest,
+    response: HarResponse,
+    cache: HarCache,
+    timings: HarTimings,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    http_version: String,
+    cookies: Vec<HarCookie>,
+    headers: Vec<HarNameValue>,
+    query_string: Vec<HarNameValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_data: Option<HarPostData>,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct HarResponse {
+    status: u16,
+    status_text: String,
+    http_version: String,
+    cookies: Vec<HarCookie>,
+    headers: Vec<HarNameValue>,
+    content: HarContent,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct HarCache {}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct HarCookie {
+    name: String,
+    value: String,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct HarNameValue {
+    name: String,
+    value: String,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct HarPostData {
+    mime_type: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    params: Vec<HarParam>,
+    text: String,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct HarParam {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct HarContent {
+    size: i64,
+    mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+/// Phase durations in milliseconds; `-1` marks a phase that did not apply.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct HarTimings {
+    blocked: f64,
+    dns: f64,
+    connect: f64,
+    send: f64,
+    wait: f64,
+    receive: f64,
+    ssl: f64,
+}
+
+impl Default for HarTimings {
+    fn default() -> Self {
+        Self {
+            blocked: -1.,
+            dns: -1.,
+            connect: -1.,
+            send: 0.,
+            wait: 0.,
+            receive: 0.,
+            ssl: -1.,
+        }
+    }
+}
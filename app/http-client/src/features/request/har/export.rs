@@ -0,0 +1,593 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use http::{HeaderMap, Method, Version, header::CONTENT_TYPE};
+use tempfile::NamedTempFile;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use url::Url;
+
+use super::{
+    HAR_VERSION, HarCache, HarContent, HarCreator, HarEntry, HarError, HarFile, HarLog,
+    HarNameValue, HarParam, HarPostData, HarRequest, HarResponse, HarTimings,
+};
+use crate::features::request::{
+    prepared::{BodyContentType, PreparedBody, PreparedMultipartPart, PreparedRequest},
+    response::{
+        HopTiming, INLINE_PREVIEW_BYTES, ResponseData, TimingPhase, TimingSpan, waterfall_hops,
+    },
+};
+
+/// Every entry keeps its response body alive, so only the most recent
+/// sends are kept.
+const HISTORY_LIMIT: usize = 50;
+/// Values left out of exported archives, as browsers do by default, so a
+/// capture can be attached to a bug report without leaking a session.
+const REDACTED_HEADERS: [&str; 4] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+];
+const REDACTED_VALUE: &str = "<redacted>";
+
+/// What was sent, taken before the request is handed to the transport.
+///
+/// Headers the transport adds per hop, such as cookies from the jar and
+/// signatures, are not part of it.
+#[derive(Clone)]
+pub(crate) struct ExchangeRecord {
+    started_at: OffsetDateTime,
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Option<HarPostData>,
+}
+
+impl ExchangeRecord {
+    pub(crate) fn new(request: &PreparedRequest) -> Self {
+        Self {
+            started_at: OffsetDateTime::now_utc(),
+            method: request.method.clone(),
+            url: request.url.clone(),
+            headers: request.headers.clone(),
+            body: post_data(request),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct HistoryEntry {
+    record: ExchangeRecord,
+    response: Arc<ResponseData>,
+}
+
+/// The completed sends of this session, oldest first.
+#[derive(Default)]
+pub(crate) struct RequestHistory {
+    entries: VecDeque<HistoryEntry>,
+    pending: Option<ExchangeRecord>,
+}
+
+impl RequestHistory {
+    pub(crate) fn begin(&mut self, record: ExchangeRecord) {
+        self.pending = Some(record);
+    }
+
+    /// Files the pending send under its response. A send that fails or is
+    /// cancelled never completes and is replaced by the next one.
+    pub(crate) fn complete(&mut self, response: Arc<ResponseData>) {
+        let Some(record) = self.pending.take() else {
+            return;
+        };
+        if self.entries.len() == HISTORY_LIMIT {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(HistoryEntry { record, response });
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<HistoryEntry> {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+/// Writes the sends as one archive and returns how many entries it holds.
+/// Each hop of a redirect chain becomes its own entry, with the timings
+/// shown in the response panel. Response bodies are included up to the
+/// inline preview limit.
+pub(crate) async fn export_har(
+    entries: Vec<HistoryEntry>,
+    target: PathBuf,
+) -> Result<usize, HarError> {
+    let mut archived = Vec::new();
+    for entry in entries {
+        archived.extend(archive_entries(&entry).await);
+    }
+    let file = HarFile {
+        log: HarLog {
+            version: HAR_VERSION.to_owned(),
+            creator: HarCreator {
+                name: env!("CARGO_PKG_NAME").to_owned(),
+                version: env!("CARGO_PKG_VERSION").to_owned(),
+            },
+            entries: archived,
+        },
+    };
+    let written = file.log.entries.len();
+    let json = serde_json::to_vec_pretty(&file).map_err(HarError::Malformed)?;
+    tokio::task::spawn_blocking(move || write_archive(&target, &json))
+        .await
+        .map_err(|error| HarError::Write(io::Error::other(error)))??;
+    Ok(written)
+}
+
+fn write_archive(path: &Path, json: &[u8]) -> Result<(), HarError> {
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let mut staging = NamedTempFile::new_in(parent).map_err(HarError::Write)?;
+    staging.write_all(json).map_err(HarError::Write)?;
+    staging
+        .persist(path)
+        .map(drop)
+        .map_err(|error| HarError::Write(error.error))
+}
+
+async fn archive_entries(entry: &HistoryEntry) -> Vec<HarEntry> {
+    let response = &entry.response;
+    let head = response.head();
+    let timing = response.timing();
+    let mut hops = waterfall_hops(head, Some(timing));
+    if hops.is_empty() {
+        hops.push(HopTiming {
+            method: entry.record.method.clone(),
+            url: entry.record.url.clone(),
+            status: head.status,
+            reused_connection: false,
+            spans: vec![
+                TimingSpan {
+                    phase: TimingPhase::Waiting,
+                    start: Duration::ZERO,
+                    end: timing.head_after,
+                },
+                TimingSpan {
+                    phase: TimingPhase::ContentDownload,
+                    start: timing.head_after,
+                    end: timing.completed_after.max(timing.head_after),
+                },
+            ],
+        });
+    }
+    let http_version = version_name(head.version);
+    let mut content = Some(response_content(response).await);
+    let last = hops.len() - 1;
+
+    hops.iter()
+        .enumerate()
+        .map(|(index, hop)| {
+            // Only the first hop carries the recorded request and only the
+            // last one the response head and body.
+            let post_data = (index == 0).then(|| entry.record.body.clone()).flatten();
+            let request_headers = if index == 0 {
+                header_list(&entry.record.headers)
+            } else {
+                Vec::new()
+            };
+            let (response_headers, content, body_size) = if index == last {
+                (
+                    header_list(&head.headers),
+                    content.take().unwrap_or_default(),
+                    response.sizes().received_encoded_bytes as i64,
+                )
+            } else {
+                (Vec::new(), HarContent::default(), -1)
+            };
+            let redirect_url = if hop.status.is_redirection() {
+                hops.get(index + 1)
+                    .map(|next| next.url.to_string())
+                    .unwrap_or_default()
+            } else {
+                String::new()
+            };
+            let started = hop
+                .spans
+                .iter()
+                .map(|span| span.start)
+                .min()
+                .unwrap_or_default();
+            let timings = hop_timings(hop);
+            HarEntry {
+                started_date_time: (entry.record.started_at + started)
+                    .format(&Rfc3339)
+                    .unwrap_or_default(),
+                time: total_time(&timings),
+                request: HarRequest {
+                    method: hop.method.to_string(),
+                    url: hop.url.to_string(),
+                    http_version: http_version.to_owned(),
+                    cookies: Vec::new(),
+                    headers: request_headers,
+                    query_string: hop
+                        .url
+                        .query_pairs()
+                        .map(|(name, value)| HarNameValue {
+                            name: name.into_owned(),
+                            value: value.into_owned(),
+                        })
+                        .collect(),
+                    body_size: post_data
+                        .as_ref()
+                        .map_or(0, |post_data| post_data.text.len() as i64),
+                    post_data,
+                    headers_size: -1,
+                },
+                response: HarResponse {
+                    status: hop.status.as_u16(),
+                    status_text: hop.status.canonical_reason().unwrap_or_default().to_owned(),
+                    http_version: http_version.to_owned(),
+                    cookies: Vec::new(),
+                    headers: response_headers,
+                    content,
+                    redirect_url,
+                    headers_size: -1,
+                    body_size,
+                },
+                cache: HarCache {},
+                timings,
+            }
+        })
+        .collect()
+}
+
+async fn response_content(response: &Arc<ResponseData>) -> HarContent {
+    let size = response.sizes().stored_body_bytes;
+    let mut content = HarContent {
+        size: size as i64,
+        mime_type: response
+            .head()
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_owned(),
+        ..HarContent::default()
+    };
+    if size > INLINE_PREVIEW_BYTES {
+        content.comment = Some(format!(
+            "The body is left out because it is larger than {INLINE_PREVIEW_BYTES} bytes."
+        ));
+        return content;
+    }
+    match response
+        .read_lease()
+        .read_all_bounded(INLINE_PREVIEW_BYTES)
+        .await
+    {
+        Ok(bytes) => match std::str::from_utf8(&bytes) {
+            Ok(text) => content.text = Some(text.to_owned()),
+            Err(_) => {
+                content.text = Some(STANDARD.encode(&bytes));
+                content.encoding = Some("base64".to_owned());
+            }
+        },
+        Err(_) => content.comment = Some("The body could not be read.".to_owned()),
+    }
+    content
+}
+
+fn post_data(request: &PreparedRequest) -> Option<HarPostData> {
+    let declared = request
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    let implied = match &request.body_content_type {
+        BodyContentType::None => None,
+        BodyContentType::Fixed(value) => value.to_str().ok(),
+        BodyContentType::MultipartBoundary => Some("multipart/form-data"),
+    };
+    let mime_type = declared.or(implied).unwrap_or_default().to_owned();
+    match &request.body {
+        PreparedBody::None => None,
+        PreparedBody::Text(bytes) | PreparedBody::UrlEncoded(bytes) => Some(HarPostData {
+            mime_type,
+            params: Vec::new(),
+            text: String::from_utf8_lossy(bytes).into_owned(),
+        }),
+        PreparedBody::Multipart(parts) => Some(HarPostData {
+            mime_type,
+            params: parts.iter().map(multipart_param).collect(),
+            text: String::new(),
+        }),
+        // File contents are not copied into the archive.
+        PreparedBody::Binary(_) => Some(HarPostData {
+            mime_type,
+            params: Vec::new(),
+            text: String::new(),
+        }),
+    }
+}
+
+fn multipart_param(part: &PreparedMultipartPart) -> HarParam {
+    match part {
+        PreparedMultipartPart::Text {
+            name,
+            value,
+            content_type,
+        } => HarParam {
+            name: name.clone(),
+            value: Some(value.clone()),
+            file_name: None,
+            content_type: content_type.as_ref().map(ToString::to_string),
+        },
+        PreparedMultipartPart::File {
+            name,
+            file_name,
+            content_type,
+            ..
+        } => HarParam {
+            name: name.clone(),
+            value: None,
+            file_name: Some(file_name.clone()),
+            content_type: Some(content_type.to_string()),
+        },
+    }
+}
+
+fn header_list(headers: &HeaderMap) -> Vec<HarNameValue> {
+    headers
+        .iter()
+        .map(|(name, value)| HarNameValue {
+            name: name.as_str().to_owned(),
+            value: if REDACTED_HEADERS.contains(&name.as_str()) {
+                REDACTED_VALUE.to_owned()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            },
+        })
+        .collect()
+}
+
+/// A reused connection has no lookup or connect phase, which the format
+/// marks with `-1`. The format counts the TLS handshake in `connect` as
+/// well as in `ssl`.
+fn hop_timings(hop: &HopTiming) -> HarTimings {
+    let phase = |phase: TimingPhase| {
+        hop.spans
+            .iter()
+            .filter(|span| span.phase == phase)
+            .map(TimingSpan::duration)
+            .reduce(|total, duration| total + duration)
+            .map(|duration| duration.as_micros() as f64 / 1000.)
+    };
+    let ssl = phase(TimingPhase::TlsHandshake);
+    HarTimings {
+        dns: phase(TimingPhase::DnsLookup).unwrap_or(-1.),
+        connect: phase(TimingPhase::Connect)
+            .map(|connect| connect + ssl.unwrap_or(0.))
+            .unwrap_or(-1.),
+        send: phase(TimingPhase::RequestWrite).unwrap_or(0.),
+        wait: phase(TimingPhase::Waiting).unwrap_or(0.),
+        receive: phase(TimingPhase::ContentDownload).unwrap_or(0.),
+        ssl: ssl.unwrap_or(-1.),
+        ..HarTimings::default()
+    }
+}
+
+fn total_time(timings: &HarTimings) -> f64 {
+    [
+        timings.blocked,
+        timings.dns,
+        timings.connect,
+        timings.send,
+        timings.wait,
+        timings.receive,
+    ]
+    .into_iter()
+    .filter(|duration| *duration > 0.)
+    .sum()
+}
+
+const fn version_name(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_2 => "HTTP/2.0",
+        Version::HTTP_3 => "HTTP/3.0",
+        _ => "HTTP/1.1",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use http::StatusCode;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::features::request::{
+        draft::{HeaderDraft, KeyValueDraft, RequestBodyDraft, RequestDraft, UrlEncodedBodyDraft},
+        prepared::compile_request,
+        response::{
+            BodyDecoding, CompletedBody, ResponseHead, ResponseSizes, ResponseTiming, StoredBody,
+        },
+    };
+
+    fn span(phase: TimingPhase, start: u64, end: u64) -> TimingSpan {
+        TimingSpan {
+            phase,
+            start: Duration::from_millis(start),
+            end: Duration::from_millis(end),
+        }
+    }
+
+    fn response(body: StoredBody, content_type: &str) -> Arc<ResponseData> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, content_type.parse().unwrap());
+        headers.insert("set-cookie", "session=secret".parse().unwrap());
+        let mut head = ResponseHead::new(
+            StatusCode::OK,
+            Version::HTTP_2,
+            Url::parse("https://example.test/next").unwrap(),
+            headers,
+        );
+        head.hops = vec![
+            HopTiming {
+                method: Method::POST,
+                url: Url::parse("https://example.test/start?step=1").unwrap(),
+                status: StatusCode::FOUND,
+                reused_connection: false,
+                spans: vec![
+                    span(TimingPhase::DnsLookup, 0, 5),
+                    span(TimingPhase::Connect, 5, 15),
+                    span(TimingPhase::TlsHandshake, 15, 25),
+                    span(TimingPhase::Waiting, 25, 40),
+                ],
+            },
+            HopTiming {
+                method: Method::GET,
+                url: Url::parse("https://example.test/next").unwrap(),
+                status: StatusCode::OK,
+                reused_connection: true,
+                spans: vec![span(TimingPhase::Waiting, 40, 60)],
+            },
+        ];
+        let len = body.len();
+        Arc::new(ResponseData::new(
+            head,
+            ResponseTiming {
+                head_after: Duration::from_millis(60),
+                completed_after: Duration::from_millis(75),
+            },
+            CompletedBody {
+                body,
+                body_decoding: BodyDecoding::Identity,
+                sizes: ResponseSizes {
+                    declared_encoded_bytes: Some(len),
+                    received_encoded_bytes: len,
+                    stored_body_bytes: len,
+                },
+            },
+        ))
+    }
+
+    fn record() -> ExchangeRecord {
+        let request = RequestDraft {
+            url: "https://example.test/start?step=1".into(),
+            headers: vec![HeaderDraft {
+                enabled: true,
+                name: "Authorization".into(),
+                value: "Bearer secret".into(),
+            }],
+            body: RequestBodyDraft::UrlEncoded(UrlEncodedBodyDraft {
+                fields: vec![KeyValueDraft {
+                    enabled: true,
+                    key: "q".into(),
+                    value: "a b".into(),
+                }],
+            }),
+            ..RequestDraft::default()
+        };
+        let prepared = compile_request(request, &Default::default()).unwrap();
+        let mut record = ExchangeRecord::new(&prepared);
+        record.started_at = OffsetDateTime::UNIX_EPOCH;
+        record
+    }
+
+    async fn export(entries: Vec<HistoryEntry>) -> (usize, Value) {
+        let folder = tempfile::tempdir().unwrap();
+        let target = folder.path().join("requests.har");
+        let written = export_har(entries, target.clone()).await.unwrap();
+        let archive = serde_json::from_slice(&std::fs::read(target).unwrap()).unwrap();
+        (written, archive)
+    }
+
+    #[tokio::test]
+    async fn redirect_hops_become_entries_with_panel_timings() {
+        let mut history = RequestHistory::default();
+        history.complete(response(StoredBody::Empty, "text/plain"));
+        assert!(history.is_empty());
+        history.begin(record());
+        history.complete(response(
+            StoredBody::Memory(Bytes::from_static(b"{\"ok\":true}")),
+            "application/json",
+        ));
+        let (written, archive) = export(history.snapshot()).await;
+
+        assert_eq!(archive["log"]["version"], "1.2");
+        let entries = archive["log"]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(written, 2);
+
+        let first = &entries[0];
+        assert_eq!(first["startedDateTime"], "1970-01-01T00:00:00Z");
+        assert_eq!(first["request"]["method"], "POST");
+        assert_eq!(first["request"]["queryString"][0]["value"], "1");
+        assert_eq!(first["request"]["headers"][0]["value"], "<redacted>");
+        assert_eq!(
+            first["request"]["postData"]["mimeType"],
+            "application/x-www-form-urlencoded"
+        );
+        assert_eq!(first["request"]["postData"]["text"], "q=a+b");
+        assert_eq!(first["response"]["status"], 302);
+        assert_eq!(
+            first["response"]["redirectURL"],
+            "https://example.test/next"
+        );
+        assert_eq!(
+            first["timings"],
+            serde_json::json!({
+                "blocked": -1.0, "dns": 5.0, "connect": 20.0, "send": 0.0,
+                "wait": 15.0, "receive": 0.0, "ssl": 10.0,
+            })
+        );
+        assert_eq!(first["time"], 40.0);
+
+        let last = &entries[1];
+        assert_eq!(last["startedDateTime"], "1970-01-01T00:00:00.04Z");
+        assert!(last["request"].get("postData").is_none());
+        assert_eq!(last["response"]["httpVersion"], "HTTP/2.0");
+        assert_eq!(last["response"]["redirectURL"], "");
+        assert_eq!(last["response"]["headers"][1]["value"], "<redacted>");
+        assert_eq!(last["response"]["content"]["text"], "{\"ok\":true}");
+        assert_eq!(last["timings"]["dns"], -1.0);
+        assert_eq!(last["timings"]["receive"], 15.0);
+        assert_eq!(last["time"], 35.0);
+    }
+
+    #[tokio::test]
+    async fn bodies_beyond_the_preview_limit_are_left_out() {
+        let mut history = RequestHistory::default();
+        for body in [
+            Bytes::from_static(&[0xff, 0x00]),
+            Bytes::from(vec![b'a'; INLINE_PREVIEW_BYTES as usize + 1]),
+        ] {
+            history.begin(record());
+            history.complete(response(
+                StoredBody::Memory(body),
+                "application/octet-stream",
+            ));
+        }
+        let (written, archive) = export(history.snapshot()).await;
+        let entries = archive["log"]["entries"].as_array().unwrap();
+        assert_eq!(written, entries.len());
+
+        let binary = &entries[1]["response"]["content"];
+        assert_eq!(binary["text"], "/wA=");
+        assert_eq!(binary["encoding"], "base64");
+
+        let large = &entries[3]["response"]["content"];
+        assert_eq!(large["size"], INLINE_PREVIEW_BYTES + 1);
+        assert!(large.get("text").is_none());
+        assert!(large["comment"].as_str().unwrap().contains("larger than"));
+    }
+}
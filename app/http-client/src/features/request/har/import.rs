@@ -0,0 +1,376 @@
+use std::path::{Path, PathBuf};
+
+use mime::Mime;
+use url::Url;
+
+use super::{HarError, HarFile, HarPostData, HarRequest};
+use crate::features::request::{
+    draft::{
+        FormDataDraft, HeaderDraft, KeyValueDraft, MultipartPartDraft, MultipartPartValueDraft,
+        MultipartTextDraft, RequestBodyDraft, RequestDraft, TextBodyDraft, TextBodyFormat,
+        UrlEncodedBodyDraft,
+    },
+    method::HttpMethod,
+    saved::{SAVED_REQUEST_EXTENSION, save_request},
+};
+
+/// Headers the transport derives from the URL and body. Replaying the
+/// captured values would duplicate them or contradict the edited request.
+const DERIVED_HEADERS: [&str; 4] = ["host", "content-length", "connection", "transfer-encoding"];
+const MAX_NAME_CHARS: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct HarImportSummary {
+    pub(crate) imported: usize,
+    /// Entries that are not HTTP requests this client can send, such as
+    /// WebSocket upgrades, `data:` URLs and unknown methods.
+    pub(crate) skipped: usize,
+}
+
+struct ImportedRequest {
+    name: String,
+    draft: RequestDraft,
+}
+
+/// Writes one saved request per archive entry into `folder`. File names
+/// start with the entry's position so the collection runner replays them
+/// in capture order, and existing files are never overwritten.
+pub(crate) fn import_har(file: &Path, folder: &Path) -> Result<HarImportSummary, HarError> {
+    let bytes = std::fs::read(file).map_err(HarError::Read)?;
+    let (requests, skipped) = parse_har(&bytes)?;
+    if requests.is_empty() {
+        return Err(HarError::NoRequests);
+    }
+    let width = requests.len().to_string().len().max(3);
+    for (index, request) in requests.iter().enumerate() {
+        let stem = format!("{:0width$}-{}", index + 1, request.name);
+        save_request(&unused_path(folder, &stem), &request.draft)?;
+    }
+    Ok(HarImportSummary {
+        imported: requests.len(),
+        skipped,
+    })
+}
+
+fn parse_har(bytes: &[u8]) -> Result<(Vec<ImportedRequest>, usize), HarError> {
+    let file: HarFile = serde_json::from_slice(bytes).map_err(HarError::Malformed)?;
+    let total = file.log.entries.len();
+    let requests: Vec<_> = file
+        .log
+        .entries
+        .into_iter()
+        .filter_map(|entry| import_request(entry.request))
+        .collect();
+    let skipped = total - requests.len();
+    Ok((requests, skipped))
+}
+
+fn import_request(request: HarRequest) -> Option<ImportedRequest> {
+    let method = HttpMethod::ALL
+        .into_iter()
+        .find(|method| method.as_str().eq_ignore_ascii_case(request.method.trim()))?;
+    let raw_url = request.url.trim();
+    let url = Url::parse(raw_url)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))?;
+    let (body, rebuilt) = request
+        .post_data
+        .as_ref()
+        .map_or((RequestBodyDraft::None, false), import_body);
+    let headers = request
+        .headers
+        .into_iter()
+        .filter(|header| {
+            let name = header.name.to_ascii_lowercase();
+            // HTTP/2 captures list pseudo-headers such as `:authority`.
+            let dropped = name.starts_with(':')
+                || DERIVED_HEADERS.contains(&name.as_str())
+                || (rebuilt && name == "content-type");
+            !dropped
+        })
+        .map(|header| HeaderDraft {
+            enabled: true,
+            name: header.name,
+            value: header.value,
+        })
+        .collect();
+    Some(ImportedRequest {
+        name: request_name(method, &url),
+        draft: RequestDraft {
+            method,
+            url: raw_url.to_owned(),
+            headers,
+            body,
+            ..RequestDraft::default()
+        },
+    })
+}
+
+/// The body of a captured request, and whether it was rebuilt so that the
+/// captured `Content-Type` no longer matches it. Captured headers are kept
+/// otherwise, and an explicit header wins over the body format's default.
+fn import_body(post: &HarPostData) -> (RequestBodyDraft, bool) {
+    let media_type = post.mime_type.parse::<Mime>().ok();
+    let (type_, subtype) = media_type
+        .as_ref()
+        .map_or((mime::TEXT, mime::PLAIN), |media_type| {
+            (media_type.type_(), media_type.subtype())
+        });
+    match (type_, subtype) {
+        (mime::APPLICATION, mime::WWW_FORM_URLENCODED) => {
+            let fields = if post.text.is_empty() {
+                post.params
+                    .iter()
+                    .map(|param| (param.name.clone(), param.value.clone().unwrap_or_default()))
+                    .collect::<Vec<_>>()
+            } else {
+                url::form_urlencoded::parse(post.text.as_bytes())
+                    .into_owned()
+                    .collect()
+            };
+            let fields = fields
+                .into_iter()
+                .map(|(key, value)| KeyValueDraft {
+                    enabled: true,
+                    key,
+                    value,
+                })
+                .collect();
+            (
+                RequestBodyDraft::UrlEncoded(UrlEncodedBodyDraft { fields }),
+                false,
+            )
+        }
+        // Without parameters the raw text is replayed together with the
+        // captured header, whose boundary it still matches.
+        (mime::MULTIPART, mime::FORM_DATA) if !post.params.is_empty() => {
+            let parts = post
+                .params
+                .iter()
+                .map(|param| MultipartPartDraft {
+                    enabled: true,
+                    name: param.name.clone(),
+                    // The captured file is not available; it has to be
+                    // chosen again before sending.
+                    value: if param.file_name.is_some() {
+                        MultipartPartValueDraft::file()
+                    } else {
+                        MultipartPartValueDraft::Text(MultipartTextDraft {
+                            value: param.value.clone().unwrap_or_default(),
+                            content_type: param.content_type.clone(),
+                        })
+                    },
+                })
+                .collect();
+            (RequestBodyDraft::FormData(FormDataDraft { parts }), true)
+        }
+        _ if post.text.is_empty() => (RequestBodyDraft::None, false),
+        _ => (
+            RequestBodyDraft::Text(TextBodyDraft {
+                format: media_type
+                    .as_ref()
+                    .map_or(TextBodyFormat::PlainText, text_format),
+                content: post.text.clone(),
+            }),
+            false,
+        ),
+    }
+}
+
+fn text_format(media_type: &Mime) -> TextBodyFormat {
+    let subtype = media_type.subtype();
+    let suffix = media_type.suffix();
+    if subtype == mime::JSON || suffix == Some(mime::JSON) {
+        TextBodyFormat::Json
+    } else if subtype == mime::XML || suffix == Some(mime::XML) {
+        TextBodyFormat::Xml
+    } else if subtype == mime::JAVASCRIPT {
+        TextBodyFormat::JavaScript
+    } else if subtype == mime::HTML {
+        TextBodyFormat::Html
+    } else if subtype == mime::CSS {
+        TextBodyFormat::Css
+    } else {
+        TextBodyFormat::PlainText
+    }
+}
+
+/// A file-name-safe label such as `post-api.example.test-v1-items`.
+fn request_name(method: HttpMethod, url: &Url) -> String {
+    let raw = format!(
+        "{}-{}{}",
+        method.as_str(),
+        url.host_str().unwrap_or_default(),
+        url.path()
+    );
+    let mut name = String::new();
+    for character in raw.chars() {
+        if name.len() >= MAX_NAME_CHARS {
+            break;
+        }
+        if character.is_ascii_alphanumeric() || character == '.' {
+            name.push(character.to_ascii_lowercase());
+        } else if !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    name.trim_end_matches(['-', '.']).to_owned()
+}
+
+fn unused_path(folder: &Path, stem: &str) -> PathBuf {
+    let mut path = folder.join(format!("{stem}.{SAVED_REQUEST_EXTENSION}"));
+    let mut copy = 2;
+    while path.exists() {
+        path = folder.join(format!("{stem}-{copy}.{SAVED_REQUEST_EXTENSION}"));
+        copy += 1;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::request::saved::open_collection;
+
+    const CAPTURE: &str = r#"{
+      "log": {
+        "version": "1.2",
+        "creator": { "name": "WebInspector", "version": "537.36" },
+        "entries": [
+          {
+            "request": {
+              "method": "POST",
+              "url": "https://api.example.test/v1/items?draft=true",
+              "headers": [
+                { "name": ":authority", "value": "api.example.test" },
+                { "name": "content-type", "value": "application/json; charset=utf-8" },
+                { "name": "content-length", "value": "14" },
+                { "name": "authorization", "value": "Bearer abc" }
+              ],
+              "postData": { "mimeType": "application/json; charset=utf-8", "text": "{\"name\":\"a\"}" }
+            }
+          },
+          {
+            "request": {
+              "method": "post",
+              "url": "https://example.test/login",
+              "postData": {
+                "mimeType": "application/x-www-form-urlencoded",
+                "text": "user=ann&note=a%20b"
+              }
+            }
+          },
+          {
+            "request": {
+              "method": "POST",
+              "url": "https://example.test/upload",
+              "headers": [
+                { "name": "Content-Type", "value": "multipart/form-data; boundary=----x" }
+              ],
+              "postData": {
+                "mimeType": "multipart/form-data; boundary=----x",
+                "params": [
+                  { "name": "title", "value": "Report" },
+                  { "name": "file", "fileName": "report.pdf", "contentType": "application/pdf" }
+                ]
+              }
+            }
+          },
+          { "request": { "method": "GET", "url": "wss://example.test/socket" } },
+          { "request": { "method": "PROPFIND", "url": "https://example.test/dav" } }
+        ]
+      }
+    }"#;
+
+    #[test]
+    fn captured_requests_become_drafts_with_editable_bodies() {
+        let (requests, skipped) = parse_har(CAPTURE.as_bytes()).unwrap();
+        assert_eq!(skipped, 2);
+        let names: Vec<_> = requests
+            .iter()
+            .map(|request| request.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "post-api.example.test-v1-items",
+                "post-example.test-login",
+                "post-example.test-upload"
+            ]
+        );
+
+        let json = &requests[0].draft;
+        assert_eq!(json.url, "https://api.example.test/v1/items?draft=true");
+        let headers: Vec<_> = json
+            .headers
+            .iter()
+            .map(|header| header.name.as_str())
+            .collect();
+        assert_eq!(headers, ["content-type", "authorization"]);
+        assert!(matches!(
+            &json.body,
+            RequestBodyDraft::Text(TextBodyDraft { format: TextBodyFormat::Json, content })
+                if content == r#"{"name":"a"}"#
+        ));
+
+        let RequestBodyDraft::UrlEncoded(form) = &requests[1].draft.body else {
+            panic!("expected a URL-encoded body");
+        };
+        let fields: Vec<_> = form
+            .fields
+            .iter()
+            .map(|field| (field.key.as_str(), field.value.as_str()))
+            .collect();
+        assert_eq!(fields, [("user", "ann"), ("note", "a b")]);
+
+        let upload = &requests[2].draft;
+        assert!(upload.headers.is_empty());
+        let RequestBodyDraft::FormData(form) = &upload.body else {
+            panic!("expected a multipart body");
+        };
+        assert!(matches!(
+            &form.parts[0].value,
+            MultipartPartValueDraft::Text(text) if text.value == "Report"
+        ));
+        assert!(form.parts[1].value == MultipartPartValueDraft::file());
+    }
+
+    #[test]
+    fn imports_keep_capture_order_and_never_overwrite() {
+        let folder = tempfile::tempdir().unwrap();
+        let capture = folder.path().join("capture.har");
+        std::fs::write(&capture, CAPTURE).unwrap();
+        let target = folder.path().join("collection");
+        std::fs::create_dir(&target).unwrap();
+
+        let summary = import_har(&capture, &target).unwrap();
+        assert_eq!(
+            summary,
+            HarImportSummary {
+                imported: 3,
+                skipped: 2
+            }
+        );
+        let entries = open_collection(&target).unwrap();
+        let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "001-post-api.example.test-v1-items",
+                "002-post-example.test-login",
+                "003-post-example.test-upload",
+            ]
+        );
+
+        import_har(&capture, &target).unwrap();
+        let entries = open_collection(&target).unwrap();
+        assert_eq!(entries.len(), 6);
+        assert!(entries.iter().all(|entry| entry.request.is_ok()));
+
+        assert!(matches!(
+            parse_har(b"{\"log\":{\"entries\":[]}}"),
+            Ok((requests, 0)) if requests.is_empty()
+        ));
+        assert!(matches!(parse_har(b"<html>"), Err(HarError::Malformed(_))));
+    }
+}
@@ -277,9 +277,18 @@ impl HttpHeadersView {
 
 fn auth_overrides_header(auth: &RequestAuthDraft, header_name: &str) -> bool {
     match auth {
-        RequestAuthDraft::Basic(_) | RequestAuthDraft::Bearer(_) => {
-            header_name.eq_ignore_ascii_case("authorization")
-        }
+        RequestAuthDraft::Basic(_)
+        | RequestAuthDraft::Bearer(_)
+        | RequestAuthDraft::Digest(_)
+        | RequestAuthDraft::OAuth2(_) => header_name.eq_ignore_ascii_case("authorization"),
+        RequestAuthDraft::AwsSigV4(_) => [
+            "authorization",
+            "x-amz-date",
+            "x-amz-content-sha256",
+            "x-amz-security-token",
+        ]
+        .iter()
+        .any(|name| header_name.eq_ignore_ascii_case(name)),
         RequestAuthDraft::ApiKey(api_key) if api_key.location == ApiKeyLocation::Header => {
             header_name.eq_ignore_ascii_case(&api_key.name)
         }
@@ -0,0 +1,331 @@
+use serde_json::Value;
+use thiserror::Error;
+
+/// A parsed JSONPath query over the subset used by response checks.
+///
+/// Supported syntax is the root `$`, member access as `.name` or `['name']`,
+/// array indexes (negative values count from the end), wildcards as `.*` or
+/// `[*]`, and recursive descent as `..name`, `..*` or `..[0]`. Filters,
+/// slices and unions are rejected while parsing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Child(Selector),
+    Descendant(Selector),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Selector {
+    Name(String),
+    Index(i64),
+    Wildcard,
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[error("JSONPath is invalid at byte {position}")]
+pub(crate) struct JsonPathError {
+    position: usize,
+}
+
+impl JsonPathError {
+    pub(crate) const fn position(self) -> usize {
+        self.position
+    }
+}
+
+impl JsonPath {
+    pub(crate) fn parse(raw: &str) -> Result<Self, JsonPathError> {
+        let raw = raw.trim_end();
+        let mut parser = Parser {
+            raw,
+            position: raw.len() - raw.trim_start().len(),
+        };
+        if !parser.eat('$') {
+            return Err(parser.error());
+        }
+        let mut segments = Vec::new();
+        while !parser.is_done() {
+            let segment = if parser.eat_str("..") {
+                let selector = if parser.peek() == Some('[') {
+                    parser.bracket()?
+                } else {
+                    parser.dotted()?
+                };
+                Segment::Descendant(selector)
+            } else if parser.eat('.') {
+                Segment::Child(parser.dotted()?)
+            } else if parser.peek() == Some('[') {
+                Segment::Child(parser.bracket()?)
+            } else {
+                return Err(parser.error());
+            };
+            segments.push(segment);
+        }
+        Ok(Self { segments })
+    }
+
+    /// Returns every value selected by the query in document order.
+    pub(crate) fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![root];
+        for segment in &self.segments {
+            let mut next = Vec::new();
+            match segment {
+                Segment::Child(selector) => {
+                    for value in current {
+                        selector.apply(value, &mut next);
+                    }
+                }
+                Segment::Descendant(selector) => {
+                    for value in current {
+                        visit_descendants(value, &mut |value| selector.apply(value, &mut next));
+                    }
+                }
+            }
+            current = next;
+        }
+        current
+    }
+
+    /// Returns the first selected value, which is what assertions and
+    /// extractions compare against.
+    pub(crate) fn first<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        self.select(root).into_iter().next()
+    }
+}
+
+/// Appends a member selector for `name`, using the dotted form when the
+/// parser would read it back unchanged.
+pub(crate) fn push_member(path: &mut String, name: &str) {
+    let dotted = name
+        .chars()
+        .next()
+        .is_some_and(|first| first == '_' || first.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|character| character == '_' || character.is_ascii_alphanumeric());
+    if dotted {
+        path.push('.');
+        path.push_str(name);
+    } else {
+        path.push_str("['");
+        for character in name.chars() {
+            if matches!(character, '\\' | '\'') {
+                path.push('\\');
+            }
+            path.push(character);
+        }
+        path.push_str("']");
+    }
+}
+
+impl Selector {
+    fn apply<'a>(&self, value: &'a Value, out: &mut Vec<&'a Value>) {
+        match (self, value) {
+            (Self::Name(name), Value::Object(object)) => out.extend(object.get(name)),
+            (Self::Index(index), Value::Array(array)) => {
+                let index = if *index < 0 {
+                    array.len().checked_sub(index.unsigned_abs() as usize)
+                } else {
+                    Some(*index as usize)
+                };
+                out.extend(index.and_then(|index| array.get(index)));
+            }
+            (Self::Wildcard, Value::Object(object)) => out.extend(object.values()),
+            (Self::Wildcard, Value::Array(array)) => out.extend(array),
+            _ => {}
+        }
+    }
+}
+
+fn visit_descendants<'a>(value: &'a Value, visit: &mut impl FnMut(&'a Value)) {
+    visit(value);
+    match value {
+        Value::Object(object) => {
+            for child in object.values() {
+                visit_descendants(child, visit);
+            }
+        }
+        Value::Array(array) => {
+            for child in array {
+                visit_descendants(child, visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+struct Parser<'a> {
+    raw: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn is_done(&self) -> bool {
+        self.position == self.raw.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.raw[self.position..].chars().next()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, expected: &str) -> bool {
+        if self.raw[self.position..].starts_with(expected) {
+            self.position += expected.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self) -> JsonPathError {
+        JsonPathError {
+            position: self.position,
+        }
+    }
+
+    fn dotted(&mut self) -> Result<Selector, JsonPathError> {
+        if self.eat('*') {
+            return Ok(Selector::Wildcard);
+        }
+        let rest = &self.raw[self.position..];
+        let len = rest
+            .find(|character: char| matches!(character, '.' | '[') || character.is_whitespace())
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error());
+        }
+        self.position += len;
+        Ok(Selector::Name(rest[..len].to_owned()))
+    }
+
+    fn bracket(&mut self) -> Result<Selector, JsonPathError> {
+        if !self.eat('[') {
+            return Err(self.error());
+        }
+        let selector = match self.peek() {
+            Some('*') => {
+                self.position += 1;
+                Selector::Wildcard
+            }
+            Some(quote @ ('\'' | '"')) => {
+                self.position += 1;
+                Selector::Name(self.quoted(quote)?)
+            }
+            Some(_) => {
+                let rest = &self.raw[self.position..];
+                let len = rest.find(']').ok_or_else(|| self.error())?;
+                let index = rest[..len].parse().map_err(|_| self.error())?;
+                self.position += len;
+                Selector::Index(index)
+            }
+            None => return Err(self.error()),
+        };
+        if !self.eat(']') {
+            return Err(self.error());
+        }
+        Ok(selector)
+    }
+
+    fn quoted(&mut self, quote: char) -> Result<String, JsonPathError> {
+        let mut name = String::new();
+        loop {
+            let character = self.peek().ok_or_else(|| self.error())?;
+            self.position += character.len_utf8();
+            match character {
+                '\\' => {
+                    let escaped = self.peek().ok_or_else(|| self.error())?;
+                    if escaped != quote && escaped != '\\' {
+                        return Err(self.error());
+                    }
+                    self.position += escaped.len_utf8();
+                    name.push(escaped);
+                }
+                character if character == quote => return Ok(name),
+                character => name.push(character),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn document() -> Value {
+        json!({
+            "data": {
+                "token": "abc",
+                "items": [
+                    { "id": 1, "name": "first" },
+                    { "id": 2, "name": "second" }
+                ],
+                "a.b": true
+            }
+        })
+    }
+
+    fn select(path: &str) -> Vec<Value> {
+        JsonPath::parse(path)
+            .unwrap()
+            .select(&document())
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn member_index_and_quoted_selectors_resolve() {
+        assert_eq!(select("$.data.token"), [json!("abc")]);
+        assert_eq!(select("$['data']['a.b']"), [json!(true)]);
+        assert_eq!(select("$.data.items[1].name"), [json!("second")]);
+        assert_eq!(select("$.data.items[-1].id"), [json!(2)]);
+        assert!(select("$.data.items[-3]").is_empty());
+        assert!(select("$.data.missing").is_empty());
+        assert_eq!(select("$"), [document()]);
+    }
+
+    #[test]
+    fn wildcards_and_recursive_descent_keep_document_order() {
+        assert_eq!(select("$.data.items[*].id"), [json!(1), json!(2)]);
+        assert_eq!(
+            select("$.data.items.*.name"),
+            [json!("first"), json!("second")]
+        );
+        assert_eq!(select("$..id"), [json!(1), json!(2)]);
+        assert_eq!(select("$..[0].name"), [json!("first")]);
+    }
+
+    #[test]
+    fn unsupported_or_malformed_queries_are_rejected() {
+        for raw in [
+            "",
+            "data",
+            "$.",
+            "$..",
+            "$[",
+            "$[1:2]",
+            "$[?(@.id)]",
+            "$['open]",
+            "$.a b",
+            "$[\"x\\y\"]",
+        ] {
+            assert!(JsonPath::parse(raw).is_err(), "{raw:?} should be rejected");
+        }
+        assert!(JsonPath::parse("  $.data  ").is_ok());
+    }
+}
@@ -12,9 +12,13 @@ use mime::Mime;
 use thiserror::Error;
 use url::Url;
 
-use super::draft::{
-    ApiKeyLocation, HttpClientTransportSettings, MultipartPartValueDraft, RequestAuthDraft,
-    RequestBodyDraft, RequestDraft,
+use super::{
+    draft::{
+        ApiKeyLocation, ConnectionSettingsDraft, HttpClientTransportSettings,
+        HttpVersionPreference, MultipartPartValueDraft, OAuth2AuthDraft, OAuth2Grant,
+        RequestAuthDraft, RequestBodyDraft, RequestDraft,
+    },
+    graphql,
 };
 
 pub(crate) struct PreparedRequest {
@@ -24,6 +28,10 @@ pub(crate) struct PreparedRequest {
     pub(crate) body: PreparedBody,
     pub(crate) body_content_type: BodyContentType,
     pub(crate) redirect: PreparedRedirect,
+    pub(crate) auth: PreparedAuth,
+    /// Whether the cookie jar adds its cookies to each hop.
+    pub(crate) send_cookies: bool,
+    pub(crate) connection: PreparedConnection,
     pub(crate) timeout: Option<Duration>,
 }
 
@@ -37,11 +45,77 @@ impl fmt::Debug for PreparedRequest {
             .field("body", &self.body)
             .field("body_content_type", &self.body_content_type)
             .field("redirect", &self.redirect)
+            .field("auth", &self.auth)
+            .field("send_cookies", &self.send_cookies)
+            .field("connection", &self.connection)
             .field("timeout", &self.timeout)
             .finish()
     }
 }
 
+/// Client settings frozen from [`ConnectionSettingsDraft`]. The default
+/// value runs on the shared client.
+#[derive(Default, PartialEq, Eq)]
+pub(crate) struct PreparedConnection {
+    pub(crate) http_version: HttpVersionPreference,
+    pub(crate) ca_certificate: Option<PathBuf>,
+    pub(crate) client_identity: Option<ClientIdentity>,
+    pub(crate) accept_invalid_certificates: bool,
+    pub(crate) proxy: Option<PreparedProxy>,
+}
+
+impl PreparedConnection {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Debug for PreparedConnection {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("PreparedConnection")
+            .field("http_version", &self.http_version)
+            .field("ca_certificate", &self.ca_certificate.is_some())
+            .field("client_identity", &self.client_identity)
+            .field(
+                "accept_invalid_certificates",
+                &self.accept_invalid_certificates,
+            )
+            .field("proxy", &self.proxy.as_ref().map(|_| Redacted))
+            .finish()
+    }
+}
+
+#[derive(PartialEq, Eq)]
+pub(crate) enum ClientIdentity {
+    /// A certificate chain and its key, possibly in the same file.
+    Pem {
+        certificate: PathBuf,
+        key: Option<PathBuf>,
+    },
+    Pkcs12 {
+        archive: PathBuf,
+        password: String,
+    },
+}
+
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pem { .. } => formatter.write_str("Pem(<redacted>)"),
+            Self::Pkcs12 { .. } => formatter.write_str("Pkcs12(<redacted>)"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq)]
+pub(crate) struct PreparedProxy {
+    pub(crate) url: Url,
+    /// Basic credentials; `None` when no user name is set.
+    pub(crate) credentials: Option<(String, String)>,
+    pub(crate) bypass: String,
+}
+
 pub(crate) enum PreparedBody {
     None,
     Text(Vec<u8>),
@@ -104,6 +178,74 @@ pub(crate) struct PreparedRedirect {
     pub(crate) forward_authorization_cross_host: bool,
 }
 
+/// Authorization that cannot be frozen into headers at compile time.
+///
+/// Static schemes are already applied to the URL and header map and leave
+/// this as `None`. The transport worker resolves the others per hop.
+pub(crate) enum PreparedAuth {
+    None,
+    Digest(DigestCredentials),
+    OAuth2(OAuth2Config),
+    AwsSigV4(AwsSigV4Credentials),
+}
+
+impl fmt::Debug for PreparedAuth {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => formatter.write_str("None"),
+            Self::Digest(_) => formatter.write_str("Digest(<redacted>)"),
+            Self::OAuth2(config) => formatter
+                .debug_struct("OAuth2")
+                .field("grant", &config.grant.kind())
+                .finish_non_exhaustive(),
+            Self::AwsSigV4(_) => formatter.write_str("AwsSigV4(<redacted>)"),
+        }
+    }
+}
+
+pub(crate) struct DigestCredentials {
+    pub(crate) username: String,
+    pub(crate) password: String,
+}
+
+pub(crate) struct OAuth2Config {
+    pub(crate) grant: OAuth2GrantConfig,
+    pub(crate) token_url: Url,
+    pub(crate) client_id: String,
+    pub(crate) client_secret: Option<String>,
+    pub(crate) scope: Option<String>,
+}
+
+pub(crate) enum OAuth2GrantConfig {
+    ClientCredentials,
+    Password {
+        username: String,
+        password: String,
+    },
+    AuthorizationCode {
+        authorization_url: Url,
+        redirect_uri: Url,
+    },
+}
+
+impl OAuth2GrantConfig {
+    pub(crate) const fn kind(&self) -> OAuth2Grant {
+        match self {
+            Self::ClientCredentials => OAuth2Grant::ClientCredentials,
+            Self::Password { .. } => OAuth2Grant::Password,
+            Self::AuthorizationCode { .. } => OAuth2Grant::AuthorizationCode,
+        }
+    }
+}
+
+pub(crate) struct AwsSigV4Credentials {
+    pub(crate) access_key_id: String,
+    pub(crate) secret_access_key: String,
+    pub(crate) session_token: Option<String>,
+    pub(crate) region: String,
+    pub(crate) service: String,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(crate) enum RequestFieldError {
     Required,
@@ -116,6 +258,16 @@ pub(crate) enum RequestFieldError {
     UnsafeDispositionText,
     BasicUsernameContainsColon,
     ApiKeyNameRequired,
+    LoopbackRedirectRequired,
+    InvalidCredentialScope,
+    InvalidStatusRange,
+    InvalidPattern,
+    InvalidJsonPath,
+    InvalidDuration,
+    InvalidVariableName,
+    InvalidGraphQlVariables,
+    GraphQlOperationRequired,
+    UnknownGraphQlOperation,
     File(FileCheckError),
 }
 
@@ -138,6 +290,9 @@ pub(crate) struct RequestFile {
 pub(crate) enum RequestFileField {
     Binary,
     MultipartPart { draft_index: usize },
+    CaCertificate,
+    ClientCertificate,
+    ClientKey,
 }
 
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -155,10 +310,35 @@ pub(crate) enum RequestCompileError {
         field: RequestFileField,
         reason: FileCheckError,
     },
+    #[error("response check is invalid")]
+    InvalidCheck,
+    #[error("GraphQL body is invalid")]
+    InvalidGraphQl,
+    #[error("connection settings are invalid")]
+    InvalidConnection,
+    #[error("request uses unknown variables: {}", names.join(", "))]
+    UnknownVariables { names: Vec<String> },
     #[error("request draft violates an internal preparation invariant")]
     UnsupportedInvariant,
 }
 
+impl RequestCompileError {
+    pub(crate) const fn message_key(&self) -> &'static str {
+        match self {
+            Self::InvalidUrl => "request-prepare-url",
+            Self::InvalidHeader => "request-prepare-header",
+            Self::InvalidMediaType => "request-prepare-media-type",
+            Self::InvalidAuth => "request-prepare-auth",
+            Self::FileUnavailable { .. } => "request-prepare-file",
+            Self::InvalidCheck => "request-prepare-check",
+            Self::InvalidGraphQl => "request-prepare-graphql",
+            Self::InvalidConnection => "request-prepare-connection",
+            Self::UnknownVariables { .. } => "request-prepare-unknown-variables",
+            Self::UnsupportedInvariant => "request-prepare-internal",
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub(crate) enum RequestPrepareError {
     #[error("request form is invalid")]
@@ -278,6 +458,75 @@ pub(crate) fn validate_api_key_name(
     Ok(())
 }
 
+/// OAuth 2.0 native-app redirects must come back to this machine over plain
+/// HTTP. An omitted port is chosen when the listener starts.
+pub(crate) fn parse_loopback_redirect(raw: &str) -> Result<Url, RequestFieldError> {
+    let url = parse_request_url(raw)?;
+    let loopback = match url.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(address)) => address.is_loopback(),
+        Some(url::Host::Ipv6(address)) => address.is_loopback(),
+        None => false,
+    };
+    if url.scheme() != "http" || !loopback {
+        return Err(RequestFieldError::LoopbackRedirectRequired);
+    }
+    Ok(url)
+}
+
+/// Access key IDs, regions and services become part of the `/`-separated
+/// SigV4 credential scope.
+pub(crate) fn validate_credential_scope_part(raw: &str) -> Result<(), RequestFieldError> {
+    if raw.is_empty() {
+        return Err(RequestFieldError::Required);
+    }
+    if raw
+        .bytes()
+        .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.'))
+    {
+        Ok(())
+    } else {
+        Err(RequestFieldError::InvalidCredentialScope)
+    }
+}
+
+/// GraphQL variables are a JSON object; blank text sends none.
+pub(crate) fn parse_graphql_variables(
+    raw: &str,
+) -> Result<Option<serde_json::Map<String, serde_json::Value>>, RequestFieldError> {
+    if raw.trim().is_empty() {
+        return Ok(None);
+    }
+    match serde_json::from_str(raw) {
+        Ok(serde_json::Value::Object(variables)) => Ok(Some(variables)),
+        _ => Err(RequestFieldError::InvalidGraphQlVariables),
+    }
+}
+
+/// The `operationName` to send. Only a document with several operations
+/// needs one, and it must name one of them.
+pub(crate) fn resolve_graphql_operation<'a>(
+    query: &str,
+    operation_name: &'a str,
+) -> Result<Option<&'a str>, RequestFieldError> {
+    let operations = graphql::operations(query);
+    if operations.len() < 2 {
+        return Ok(None);
+    }
+    let operation_name = operation_name.trim();
+    if operation_name.is_empty() {
+        return Err(RequestFieldError::GraphQlOperationRequired);
+    }
+    if operations
+        .iter()
+        .any(|operation| operation.name.as_deref() == Some(operation_name))
+    {
+        Ok(Some(operation_name))
+    } else {
+        Err(RequestFieldError::UnknownGraphQlOperation)
+    }
+}
+
 pub(crate) fn compile_request(
     draft: RequestDraft,
     settings: &HttpClientTransportSettings,
@@ -289,6 +538,8 @@ pub(crate) fn compile_request(
         body: draft_body,
         auth,
         settings: request_settings,
+        assertions: _,
+        extractions: _,
     } = draft;
 
     let mut url = parse_request_url(&url).map_err(|_| RequestCompileError::InvalidUrl)?;
@@ -304,7 +555,7 @@ pub(crate) fn compile_request(
     }
 
     let (body, body_content_type) = compile_body(draft_body)?;
-    apply_auth(auth, &mut url, &mut headers)?;
+    let auth = apply_auth(auth, &mut url, &mut headers)?;
 
     Ok(PreparedRequest {
         method: method.to_http_method(),
@@ -318,6 +569,9 @@ pub(crate) fn compile_request(
             preserve_method: request_settings.follow_original_method,
             forward_authorization_cross_host: false,
         },
+        auth,
+        send_cookies: request_settings.send_cookies,
+        connection: compile_connection(request_settings.connection)?,
         timeout: (settings.timeout_ms() != 0).then(|| Duration::from_millis(settings.timeout_ms())),
     })
 }
@@ -409,16 +663,89 @@ fn compile_body(
                 BodyContentType::None,
             ))
         }
+        RequestBodyDraft::GraphQl(body) => {
+            let variables = parse_graphql_variables(&body.variables)
+                .map_err(|_| RequestCompileError::InvalidGraphQl)?;
+            let operation_name = resolve_graphql_operation(&body.query, &body.operation_name)
+                .map_err(|_| RequestCompileError::InvalidGraphQl)?;
+            Ok((
+                PreparedBody::Text(graphql::envelope(&body.query, variables, operation_name)),
+                BodyContentType::Fixed(HeaderValue::from_static("application/json")),
+            ))
+        }
     }
 }
 
+fn compile_connection(
+    connection: ConnectionSettingsDraft,
+) -> Result<PreparedConnection, RequestCompileError> {
+    let ca_certificate = connection
+        .ca_certificate
+        .map(|path| connection_file(&path, RequestFileField::CaCertificate))
+        .transpose()?;
+    let client_key = connection
+        .client_key
+        .map(|path| connection_file(&path, RequestFileField::ClientKey))
+        .transpose()?;
+    let client_identity = match connection.client_certificate {
+        Some(path) => {
+            let certificate = connection_file(&path, RequestFileField::ClientCertificate)?;
+            Some(if is_pkcs12(&certificate) {
+                ClientIdentity::Pkcs12 {
+                    archive: certificate,
+                    password: connection.client_certificate_password,
+                }
+            } else {
+                ClientIdentity::Pem {
+                    certificate,
+                    key: client_key,
+                }
+            })
+        }
+        None if client_key.is_some() => return Err(RequestCompileError::InvalidConnection),
+        None => None,
+    };
+    let proxy = match connection.proxy_url.trim() {
+        "" => None,
+        raw => Some(PreparedProxy {
+            url: parse_request_url(raw).map_err(|_| RequestCompileError::InvalidConnection)?,
+            credentials: (!connection.proxy_username.is_empty())
+                .then(|| (connection.proxy_username, connection.proxy_password)),
+            bypass: connection.proxy_bypass.trim().to_string(),
+        }),
+    };
+    Ok(PreparedConnection {
+        http_version: connection.http_version,
+        ca_certificate,
+        client_identity,
+        accept_invalid_certificates: connection.accept_invalid_certificates,
+        proxy,
+    })
+}
+
+fn connection_file(path: &Path, field: RequestFileField) -> Result<PathBuf, RequestCompileError> {
+    inspect_request_file(path)
+        .map(|file| file.absolute_path)
+        .map_err(|error| file_compile_error(field, error))
+}
+
+/// PKCS#12 archives are recognised by their usual extensions; everything
+/// else is read as PEM.
+pub(crate) fn is_pkcs12(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("p12") || extension.eq_ignore_ascii_case("pfx")
+        })
+}
+
 fn apply_auth(
     auth: RequestAuthDraft,
     url: &mut Url,
     headers: &mut HeaderMap,
-) -> Result<(), RequestCompileError> {
+) -> Result<PreparedAuth, RequestCompileError> {
     match auth {
-        RequestAuthDraft::None => Ok(()),
+        RequestAuthDraft::None => Ok(PreparedAuth::None),
         RequestAuthDraft::Basic(basic) => {
             validate_basic_username(&basic.username)
                 .map_err(|_| RequestCompileError::InvalidAuth)?;
@@ -427,14 +754,14 @@ fn apply_auth(
                 .map_err(|_| RequestCompileError::InvalidAuth)?;
             headers.remove(header::AUTHORIZATION);
             headers.append(header::AUTHORIZATION, value);
-            Ok(())
+            Ok(PreparedAuth::None)
         }
         RequestAuthDraft::Bearer(bearer) => {
             let value = HeaderValue::from_str(&format!("Bearer {}", bearer.token))
                 .map_err(|_| RequestCompileError::InvalidAuth)?;
             headers.remove(header::AUTHORIZATION);
             headers.append(header::AUTHORIZATION, value);
-            Ok(())
+            Ok(PreparedAuth::None)
         }
         RequestAuthDraft::ApiKey(api_key) => match api_key.location {
             ApiKeyLocation::Header => {
@@ -446,7 +773,7 @@ fn apply_auth(
                     .map_err(|_| RequestCompileError::InvalidAuth)?;
                 headers.remove(&name);
                 headers.append(name, value);
-                Ok(())
+                Ok(PreparedAuth::None)
             }
             ApiKeyLocation::Query => {
                 validate_api_key_name(&api_key.name, api_key.location)
@@ -460,12 +787,83 @@ fn apply_auth(
                     .clear()
                     .extend_pairs(retained)
                     .append_pair(&api_key.name, &api_key.value);
-                Ok(())
+                Ok(PreparedAuth::None)
             }
         },
+        RequestAuthDraft::Digest(digest) => {
+            parse_header_value(&digest.username).map_err(|_| RequestCompileError::InvalidAuth)?;
+            headers.remove(header::AUTHORIZATION);
+            Ok(PreparedAuth::Digest(DigestCredentials {
+                username: digest.username,
+                password: digest.password,
+            }))
+        }
+        RequestAuthDraft::OAuth2(oauth2) => {
+            let config = compile_oauth2(oauth2)?;
+            headers.remove(header::AUTHORIZATION);
+            Ok(PreparedAuth::OAuth2(config))
+        }
+        RequestAuthDraft::AwsSigV4(aws) => {
+            for part in [&aws.access_key_id, &aws.region, &aws.service] {
+                validate_credential_scope_part(part)
+                    .map_err(|_| RequestCompileError::InvalidAuth)?;
+            }
+            if aws.secret_access_key.is_empty() {
+                return Err(RequestCompileError::InvalidAuth);
+            }
+            let session_token = (!aws.session_token.is_empty())
+                .then(|| {
+                    parse_header_value(&aws.session_token)
+                        .map(|_| aws.session_token)
+                        .map_err(|_| RequestCompileError::InvalidAuth)
+                })
+                .transpose()?;
+            headers.remove(header::AUTHORIZATION);
+            Ok(PreparedAuth::AwsSigV4(AwsSigV4Credentials {
+                access_key_id: aws.access_key_id,
+                secret_access_key: aws.secret_access_key,
+                session_token,
+                region: aws.region,
+                service: aws.service,
+            }))
+        }
     }
 }
 
+fn compile_oauth2(oauth2: OAuth2AuthDraft) -> Result<OAuth2Config, RequestCompileError> {
+    let token_url =
+        parse_request_url(&oauth2.token_url).map_err(|_| RequestCompileError::InvalidAuth)?;
+    if oauth2.client_id.trim().is_empty() {
+        return Err(RequestCompileError::InvalidAuth);
+    }
+    let grant = match oauth2.grant {
+        OAuth2Grant::ClientCredentials => OAuth2GrantConfig::ClientCredentials,
+        OAuth2Grant::Password => {
+            if oauth2.username.is_empty() {
+                return Err(RequestCompileError::InvalidAuth);
+            }
+            OAuth2GrantConfig::Password {
+                username: oauth2.username,
+                password: oauth2.password,
+            }
+        }
+        OAuth2Grant::AuthorizationCode => OAuth2GrantConfig::AuthorizationCode {
+            authorization_url: parse_request_url(&oauth2.authorization_url)
+                .map_err(|_| RequestCompileError::InvalidAuth)?,
+            redirect_uri: parse_loopback_redirect(&oauth2.redirect_uri)
+                .map_err(|_| RequestCompileError::InvalidAuth)?,
+        },
+    };
+    let optional = |value: String| (!value.trim().is_empty()).then_some(value);
+    Ok(OAuth2Config {
+        grant,
+        token_url,
+        client_id: oauth2.client_id.trim().to_owned(),
+        client_secret: optional(oauth2.client_secret),
+        scope: optional(oauth2.scope.trim().to_owned()),
+    })
+}
+
 fn file_compile_error(field: RequestFileField, error: RequestFieldError) -> RequestCompileError {
     match error {
         RequestFieldError::File(reason) => RequestCompileError::FileUnavailable { field, reason },
@@ -479,9 +877,10 @@ mod tests {
 
     use super::*;
     use crate::features::request::draft::{
-        ApiKeyAuthDraft, BasicAuthDraft, BearerAuthDraft, BinaryBodyDraft, FormDataDraft,
-        HeaderDraft, KeyValueDraft, MultipartFileDraft, MultipartPartDraft, MultipartTextDraft,
-        RequestSettingsDraft, TextBodyDraft, TextBodyFormat, UrlEncodedBodyDraft,
+        ApiKeyAuthDraft, AwsSigV4AuthDraft, BasicAuthDraft, BearerAuthDraft, BinaryBodyDraft,
+        DigestAuthDraft, FormDataDraft, GraphQlBodyDraft, HeaderDraft, KeyValueDraft,
+        MultipartFileDraft, MultipartPartDraft, MultipartTextDraft, RequestSettingsDraft,
+        TextBodyDraft, TextBodyFormat, UrlEncodedBodyDraft,
     };
     use crate::features::request::method::HttpMethod;
 
@@ -493,6 +892,8 @@ mod tests {
             body: RequestBodyDraft::None,
             auth: RequestAuthDraft::None,
             settings: RequestSettingsDraft::default(),
+            assertions: Vec::new(),
+            extractions: Vec::new(),
         }
     }
 
@@ -519,6 +920,7 @@ mod tests {
     fn compiler_trims_url_removes_fragment_and_freezes_settings() {
         let mut request = draft();
         request.settings.follow_original_method = true;
+        request.settings.send_cookies = false;
         let settings = HttpClientTransportSettings::default();
         settings.set_timeout_ms(2500);
         let prepared = compile_request(request, &settings).unwrap();
@@ -528,6 +930,7 @@ mod tests {
         assert!(matches!(prepared.body, PreparedBody::None));
         assert_eq!(prepared.body_content_type, BodyContentType::None);
         assert_eq!(prepared.timeout, Some(Duration::from_millis(2500)));
+        assert!(!prepared.send_cookies);
         assert_eq!(
             prepared.redirect,
             PreparedRedirect {
@@ -722,6 +1125,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn graphql_bodies_compile_to_the_json_envelope() {
+        let mut request = draft();
+        request.body = RequestBodyDraft::GraphQl(GraphQlBodyDraft {
+            query: "query A { a } query B($id: ID) { b(id: $id) }".into(),
+            variables: r#"{ "id": 7 }"#.into(),
+            operation_name: "B".into(),
+        });
+        let prepared = compile_request(request.clone(), &Default::default()).unwrap();
+        let PreparedBody::Text(bytes) = prepared.body else {
+            panic!("expected a JSON body");
+        };
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap(),
+            serde_json::json!({
+                "query": "query A { a } query B($id: ID) { b(id: $id) }",
+                "variables": { "id": 7 },
+                "operationName": "B",
+            })
+        );
+        assert_eq!(
+            prepared.body_content_type,
+            BodyContentType::Fixed(HeaderValue::from_static("application/json"))
+        );
+
+        assert_eq!(parse_graphql_variables(" "), Ok(None));
+        assert_eq!(
+            parse_graphql_variables("[1]"),
+            Err(RequestFieldError::InvalidGraphQlVariables)
+        );
+        assert_eq!(
+            resolve_graphql_operation("query A { a }", "Stale"),
+            Ok(None)
+        );
+        assert_eq!(
+            resolve_graphql_operation("query A { a } query B { b }", ""),
+            Err(RequestFieldError::GraphQlOperationRequired)
+        );
+        assert_eq!(
+            resolve_graphql_operation("query A { a } query B { b }", "C"),
+            Err(RequestFieldError::UnknownGraphQlOperation)
+        );
+
+        let RequestBodyDraft::GraphQl(body) = &mut request.body else {
+            unreachable!();
+        };
+        body.operation_name = "C".into();
+        assert_eq!(
+            compile_request(request, &Default::default()).unwrap_err(),
+            RequestCompileError::InvalidGraphQl
+        );
+    }
+
     #[test]
     fn multipart_and_binary_freeze_paths_without_reading_file_bytes() {
         let directory = tempfile::tempdir().unwrap();
@@ -911,4 +1367,192 @@ mod tests {
         let diagnostic = format!("{error:?} {error}");
         assert!(!diagnostic.contains("token-secret"));
     }
+
+    #[test]
+    fn worker_resolved_auth_drops_explicit_authorization_and_freezes_credentials() {
+        let explicit = HeaderDraft {
+            enabled: true,
+            name: "Authorization".into(),
+            value: "explicit".into(),
+        };
+
+        let mut digest = draft();
+        digest.headers.push(explicit.clone());
+        digest.auth = RequestAuthDraft::Digest(DigestAuthDraft {
+            username: "user".into(),
+            password: "password".into(),
+        });
+        let prepared = compile_request(digest, &Default::default()).unwrap();
+        assert!(!prepared.headers.contains_key(header::AUTHORIZATION));
+        assert!(matches!(
+            prepared.auth,
+            PreparedAuth::Digest(DigestCredentials { ref username, .. }) if username == "user"
+        ));
+
+        let mut oauth2 = draft();
+        oauth2.headers.push(explicit.clone());
+        oauth2.auth = RequestAuthDraft::OAuth2(OAuth2AuthDraft {
+            grant: OAuth2Grant::AuthorizationCode,
+            token_url: "https://auth.example.test/token".into(),
+            client_id: " client ".into(),
+            scope: "  ".into(),
+            authorization_url: "https://auth.example.test/authorize".into(),
+            ..OAuth2AuthDraft::default()
+        });
+        let prepared = compile_request(oauth2, &Default::default()).unwrap();
+        assert!(!prepared.headers.contains_key(header::AUTHORIZATION));
+        let PreparedAuth::OAuth2(config) = prepared.auth else {
+            panic!("OAuth 2.0 must be resolved by the worker");
+        };
+        assert_eq!(config.client_id, "client");
+        assert_eq!(config.client_secret, None);
+        assert_eq!(config.scope, None);
+        assert!(matches!(
+            config.grant,
+            OAuth2GrantConfig::AuthorizationCode { ref redirect_uri, .. }
+                if redirect_uri.as_str() == "http://127.0.0.1/callback"
+        ));
+
+        let mut aws = draft();
+        aws.headers.push(explicit);
+        aws.auth = RequestAuthDraft::AwsSigV4(AwsSigV4AuthDraft {
+            access_key_id: "AKID".into(),
+            secret_access_key: "secret".into(),
+            session_token: String::new(),
+            region: "us-east-1".into(),
+            service: "execute-api".into(),
+        });
+        let prepared = compile_request(aws, &Default::default()).unwrap();
+        assert!(!prepared.headers.contains_key(header::AUTHORIZATION));
+        assert!(matches!(
+            prepared.auth,
+            PreparedAuth::AwsSigV4(AwsSigV4Credentials {
+                session_token: None,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn oauth2_redirects_and_sigv4_scope_parts_are_strict() {
+        for accepted in [
+            "http://127.0.0.1/callback",
+            "http://localhost:8400/",
+            "http://[::1]:9000/done",
+        ] {
+            assert!(parse_loopback_redirect(accepted).is_ok(), "{accepted}");
+        }
+        for rejected in [
+            "https://127.0.0.1/callback",
+            "http://example.test/callback",
+            "http://10.0.0.1/callback",
+        ] {
+            assert_eq!(
+                parse_loopback_redirect(rejected).unwrap_err(),
+                RequestFieldError::LoopbackRedirectRequired
+            );
+        }
+        assert_eq!(
+            parse_loopback_redirect("").unwrap_err(),
+            RequestFieldError::Required
+        );
+
+        assert!(validate_credential_scope_part("us-east-1").is_ok());
+        assert_eq!(
+            validate_credential_scope_part("").unwrap_err(),
+            RequestFieldError::Required
+        );
+        assert_eq!(
+            validate_credential_scope_part("us/east").unwrap_err(),
+            RequestFieldError::InvalidCredentialScope
+        );
+
+        let mut password = draft();
+        password.auth = RequestAuthDraft::OAuth2(OAuth2AuthDraft {
+            grant: OAuth2Grant::Password,
+            token_url: "https://auth.example.test/token".into(),
+            client_id: "client".into(),
+            ..OAuth2AuthDraft::default()
+        });
+        assert_eq!(
+            compile_request(password, &Default::default()).unwrap_err(),
+            RequestCompileError::InvalidAuth
+        );
+    }
+
+    #[test]
+    fn prepared_auth_debug_redacts_every_credential() {
+        let mut request = draft();
+        request.auth = RequestAuthDraft::OAuth2(OAuth2AuthDraft {
+            grant: OAuth2Grant::Password,
+            token_url: "https://auth.example.test/token-secret".into(),
+            client_id: "client-secret-id".into(),
+            client_secret: "client-secret".into(),
+            username: "resource-owner".into(),
+            password: "owner-password".into(),
+            ..OAuth2AuthDraft::default()
+        });
+        let diagnostic = format!(
+            "{:?}",
+            compile_request(request, &Default::default()).unwrap()
+        );
+        assert!(diagnostic.contains("Password"));
+        for secret in [
+            "token-secret",
+            "client-secret",
+            "resource-owner",
+            "owner-password",
+        ] {
+            assert!(!diagnostic.contains(secret), "diagnostic leaked {secret}");
+        }
+    }
+
+    #[test]
+    fn connection_settings_pick_an_identity_format_and_redact_proxy_credentials() {
+        let directory = tempfile::tempdir().unwrap();
+        let archive = directory.path().join("client.p12");
+        std::fs::write(&archive, b"archive").unwrap();
+        let mut request = draft();
+        request.settings.connection = ConnectionSettingsDraft {
+            client_certificate: Some(archive.clone()),
+            client_certificate_password: "archive-password".into(),
+            proxy_url: " http://proxy.example.test:3128 ".into(),
+            proxy_username: "proxy-user".into(),
+            proxy_password: "proxy-password".into(),
+            proxy_bypass: " localhost, .internal ".into(),
+            ..ConnectionSettingsDraft::default()
+        };
+
+        let prepared = compile_request(request, &Default::default()).unwrap();
+        assert!(!prepared.connection.is_default());
+        assert!(matches!(
+            &prepared.connection.client_identity,
+            Some(ClientIdentity::Pkcs12 { password, .. }) if password == "archive-password"
+        ));
+        let proxy = prepared.connection.proxy.as_ref().unwrap();
+        assert_eq!(proxy.url.as_str(), "http://proxy.example.test:3128/");
+        assert_eq!(proxy.bypass, "localhost, .internal");
+        let diagnostic = format!("{prepared:?}");
+        for secret in [
+            "archive-password",
+            "proxy-user",
+            "proxy-password",
+            "client.p12",
+        ] {
+            assert!(!diagnostic.contains(secret), "diagnostic leaked {secret}");
+        }
+
+        let mut orphan_key = draft();
+        orphan_key.settings.connection.client_key = Some(archive);
+        assert_eq!(
+            compile_request(orphan_key, &Default::default()).unwrap_err(),
+            RequestCompileError::InvalidConnection
+        );
+        assert!(
+            compile_request(draft(), &Default::default())
+                .unwrap()
+                .connection
+                .is_default()
+        );
+    }
 }
@@ -1,9 +1,19 @@
 mod collector;
+mod compare;
 mod data;
 mod decoding;
+mod diff;
+mod events;
+mod filter;
+mod inspector;
+mod json_tree;
 mod media;
 mod pdf;
+mod report;
 mod save;
+mod search;
+mod sse;
+mod structured;
 mod viewer;
 
 use std::{sync::Arc, time::Duration};
@@ -14,52 +24,64 @@ use gpui::{
     AnyElement, App, AppContext as _, Context, Entity, InteractiveElement as _, IntoElement,
     ObjectFit, ParentElement as _, ScrollHandle, SharedString, StatefulInteractiveElement as _,
     Styled as _, StyledImage as _, Subscription, Task, Window, div, img,
-    prelude::FluentBuilder as _, px,
+    prelude::FluentBuilder as _, px, relative,
 };
 use gpui_component::{
     ActiveTheme as _, Disableable as _, Sizable as _, StyledExt as _,
     alert::Alert,
     button::Button,
-    input::{Input, InputState},
+    input::{Input, InputEvent, InputState},
     label::Label,
     progress::Progress,
     scroll::ScrollableElement as _,
-    select::{Select, SelectEvent, SelectItem, SelectState},
+    select::{SearchableVec, Select, SelectEvent, SelectItem, SelectState},
     slider::{Slider, SliderEvent, SliderState, SliderValue},
     tab::{Tab, TabBar},
     table::{Table, TableBody, TableCell, TableHead, TableHeader, TableRow},
     v_flex,
 };
 use gpui_operation::Transition as _;
+use prost_reflect::MessageDescriptor;
 
 use super::{
     RequestView,
+    checks::CheckReport,
     runtime::{
-        BodySizeDimension, FailedAttempt, RequestProblemKind, RequestRuntime, ResponseReceipt,
+        AuthorizationProblemKind, BodySizeDimension, ConnectionProblemKind, FailedAttempt,
+        RequestProblemKind, RequestRuntime, ResponseReceipt,
     },
 };
-use crate::foundation::I18n;
+use crate::{features::grpc::LoadedDescriptors, foundation::I18n};
 
+use compare::{DiffStatus, render_diff};
 pub(crate) use data::{
-    BodyDecoding, CAPTURE_LIMIT_BYTES, CompletedBody, INLINE_PREVIEW_BYTES, ResponseData,
-    ResponseHead, ResponseProgress, ResponseReadLease, ResponseReadProblem, ResponseTiming,
+    BodyDecoding, CAPTURE_LIMIT_BYTES, CompletedBody, HopTiming, INLINE_PREVIEW_BYTES,
+    PeerCertificate, ResponseData, ResponseHead, ResponseProgress, ResponseReadLease,
+    ResponseReadProblem, ResponseTiming, TimingPhase, TimingSpan, TlsDetails,
 };
 pub(crate) use decoding::{
     ContentKind, SourceLanguage, TextDecodingProblem, classify_content_type, collect_response_body,
-    declared_encoded_bytes, decode_text, escape_header_value,
+    declared_encoded_bytes, decode_text, escape_header_value, protobuf_message_name,
 };
+use diff::ResponseDiff;
+pub(crate) use diff::diff_responses;
+use events::{EventStreamLog, render_event_table};
+use inspector::{BodyInspector, FilterOutput};
 use media::audio::AudioDriver;
 use media::{
     MediaDriverEvent, MediaDriverEvents, MediaMessage, MediaPhase, MediaProblem, MediaProblemKind,
     MediaRuntime, PreviewToken, ResponseAssetProblem, ResponseAssetProblemKind,
 };
 use pdf::{PdfPreview, PdfProblemKind, PdfViewport, PdfWorkerHandle};
+use report::{ChecksStatus, render_checks};
 pub(crate) use save::{
     ResponseSaveProblem, ResponseSaveProblemKind, initial_save_directory, save_response,
     suggested_response_name,
 };
+pub(crate) use sse::{SseParser, SseUpdate};
 pub(crate) use viewer::{
-    ResponseProjection, ResponseViewWarning, ViewerMode, project_response, resolved_viewer_mode,
+    ResponseProjection, ResponseViewWarning, ViewerMode, project_protobuf, project_response,
+    resolved_viewer_mode,
 };
 
 #[cfg(test)]
@@ -70,15 +92,30 @@ enum ResponseTab {
     #[default]
     Body,
     Headers,
+    Tests,
+    Connection,
+    Timing,
+    Diff,
 }
 
 impl ResponseTab {
-    const ALL: [Self; 2] = [Self::Body, Self::Headers];
+    const ALL: [Self; 6] = [
+        Self::Body,
+        Self::Headers,
+        Self::Tests,
+        Self::Connection,
+        Self::Timing,
+        Self::Diff,
+    ];
 
     const fn index(self) -> usize {
         match self {
             Self::Body => 0,
             Self::Headers => 1,
+            Self::Tests => 2,
+            Self::Connection => 3,
+            Self::Timing => 4,
+            Self::Diff => 5,
         }
     }
 }
@@ -108,6 +145,8 @@ impl SelectItem for ViewerModeItem {
 
 pub(super) type ViewerModeItems = Vec<ViewerModeItem>;
 
+type MessageTypeItems = SearchableVec<SharedString>;
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 enum ResponseSaveStatus {
     #[default]
@@ -125,6 +164,10 @@ pub(super) struct ResponsePane {
     tab: ResponseTab,
     mode: ViewerMode,
     pub(super) mode_state: Entity<SelectState<ViewerModeItems>>,
+    /// The protobuf message type picked for bodies whose content type does
+    /// not name one.
+    message_type: Option<SharedString>,
+    message_type_state: Entity<SelectState<MessageTypeItems>>,
     projection: Option<ResponseProjection>,
     text_editor: Option<Entity<InputState>>,
     preview_task: Option<Task<()>>,
@@ -139,7 +182,18 @@ pub(super) struct ResponsePane {
     seek_dragging: bool,
     save_status: ResponseSaveStatus,
     save_task: Option<Task<()>>,
+    checks: ChecksStatus,
+    checks_task: Option<Task<()>>,
+    /// Kept across sends so later responses can be compared with it.
+    pinned: Option<Arc<ResponseData>>,
+    diff: DiffStatus,
+    diff_task: Option<Task<()>>,
+    pub(super) event_stream: EventStreamLog,
+    inspector: BodyInspector,
     _mode_subscription: Subscription,
+    _message_type_subscription: Subscription,
+    _filter_subscription: Subscription,
+    _search_subscription: Subscription,
     _seek_subscription: Subscription,
     _volume_subscription: Subscription,
 }
@@ -169,6 +223,25 @@ impl ResponsePane {
                 this.refresh_response_projection(window, cx);
             },
         );
+        let message_type_state = cx.new(|cx| {
+            SelectState::new(MessageTypeItems::new(Vec::new()), None, window, cx).searchable(true)
+        });
+        let message_type_subscription = cx.subscribe_in(
+            &message_type_state,
+            window,
+            |this, _, event: &SelectEvent<MessageTypeItems>, window, cx| {
+                let SelectEvent::Confirm(message_type) = event else {
+                    return;
+                };
+                if this.response_pane.message_type == *message_type {
+                    return;
+                }
+                this.response_pane.message_type = message_type.clone();
+                if this.response_pane.mode == ViewerMode::Protobuf {
+                    this.refresh_response_projection(window, cx);
+                }
+            },
+        );
         let seek_state = cx.new(|_| {
             SliderState::new()
                 .min(0.)
@@ -220,10 +293,38 @@ impl ResponsePane {
                 cx.notify();
             },
         );
+        let inspector = BodyInspector::new(window, cx);
+        let filter_subscription = cx.subscribe_in(
+            inspector.filter_input(),
+            window,
+            |this, _, event: &InputEvent, window, cx| {
+                if matches!(event, InputEvent::Change) {
+                    this.response_pane.refresh_filter(window, cx);
+                    cx.notify();
+                }
+            },
+        );
+        let search_subscription = cx.subscribe_in(
+            inspector.search_input(),
+            window,
+            |this, _, event: &InputEvent, window, cx| match event {
+                InputEvent::Change => {
+                    this.response_pane.refresh_search(window, cx);
+                    cx.notify();
+                }
+                InputEvent::PressEnter { .. } => {
+                    this.response_pane.step_search(true, window, cx);
+                    cx.notify();
+                }
+                InputEvent::Focus | InputEvent::Blur => {}
+            },
+        );
         Self {
             tab: ResponseTab::Body,
             mode: ViewerMode::Auto,
             mode_state,
+            message_type: None,
+            message_type_state,
             projection: None,
             text_editor: None,
             preview_task: None,
@@ -238,29 +339,108 @@ impl ResponsePane {
             seek_dragging: false,
             save_status: ResponseSaveStatus::Idle,
             save_task: None,
+            checks: ChecksStatus::NotConfigured,
+            checks_task: None,
+            pinned: None,
+            diff: DiffStatus::Idle,
+            diff_task: None,
+            event_stream: EventStreamLog::default(),
+            inspector,
             _mode_subscription: mode_subscription,
+            _message_type_subscription: message_type_subscription,
+            _filter_subscription: filter_subscription,
+            _search_subscription: search_subscription,
             _seek_subscription: seek_subscription,
             _volume_subscription: volume_subscription,
         }
     }
 
-    pub(super) fn reset_for_send(&mut self, window: &mut Window, cx: &mut Context<RequestView>) {
-        self.tab = ResponseTab::Body;
-        self.mode = ViewerMode::Auto;
+    /// Prepares the pane for a new attempt. A resumed event stream keeps
+    /// its log, tab and viewer mode so the reconnect reads as one stream.
+    pub(super) fn reset_for_send(
+        &mut self,
+        resume: bool,
+        window: &mut Window,
+        cx: &mut Context<RequestView>,
+    ) {
         self.teardown_preview();
-        self.mode_state.update(cx, |state, cx| {
-            state.set_items(viewer_mode_items(None, cx), window, cx);
-            state.set_selected_value(&ViewerMode::Auto, window, cx);
-        });
+        if resume {
+            self.event_stream.resume();
+            self.mode_state.update(cx, |state, cx| {
+                state.set_items(viewer_mode_items(None, cx), window, cx);
+            });
+        } else {
+            self.tab = ResponseTab::Body;
+            self.mode = ViewerMode::Auto;
+            self.event_stream.clear();
+            self.mode_state.update(cx, |state, cx| {
+                state.set_items(viewer_mode_items(None, cx), window, cx);
+                state.set_selected_value(&ViewerMode::Auto, window, cx);
+            });
+        }
         if self.save_task.is_none() {
             self.save_status = ResponseSaveStatus::Idle;
         }
+        self.clear_checks();
+        self.clear_diff();
     }
 
     pub(super) fn clear_projection(&mut self) {
         self.teardown_preview();
     }
 
+    /// Marks the current send as having checks to run once it completes.
+    pub(super) fn expect_checks(&mut self) {
+        self.checks = ChecksStatus::Pending;
+    }
+
+    pub(super) fn install_checks_task(&mut self, task: Task<()>) {
+        self.checks = ChecksStatus::Pending;
+        self.checks_task = Some(task);
+    }
+
+    pub(super) fn finish_checks(&mut self, report: CheckReport) {
+        self.checks = ChecksStatus::Finished(report);
+        self.checks_task.take();
+    }
+
+    pub(super) fn clear_checks(&mut self) {
+        self.checks = ChecksStatus::NotConfigured;
+        self.checks_task.take();
+    }
+
+    pub(super) fn pinned_response(&self) -> Option<&Arc<ResponseData>> {
+        self.pinned.as_ref()
+    }
+
+    pub(super) fn set_pinned_response(&mut self, response: Option<Arc<ResponseData>>) {
+        self.pinned = response;
+        self.clear_diff();
+    }
+
+    pub(super) fn install_diff_task(&mut self, task: Task<()>) {
+        self.diff = DiffStatus::Running;
+        self.diff_task = Some(task);
+    }
+
+    pub(super) fn finish_diff(&mut self, diff: Option<ResponseDiff>) {
+        self.diff = diff.map_or(DiffStatus::Failed, DiffStatus::Finished);
+        self.diff_task.take();
+    }
+
+    pub(super) fn clear_diff(&mut self) {
+        self.diff = DiffStatus::Idle;
+        self.diff_task.take();
+    }
+
+    #[cfg(test)]
+    pub(super) fn check_report(&self) -> Option<&CheckReport> {
+        match &self.checks {
+            ChecksStatus::Finished(report) => Some(report),
+            ChecksStatus::NotConfigured | ChecksStatus::Pending => None,
+        }
+    }
+
     pub(super) fn begin_preview(
         &mut self,
         response: Arc<ResponseData>,
@@ -272,6 +452,17 @@ impl ResponsePane {
         self.mode_state.update(cx, |state, cx| {
             state.set_items(viewer_mode_items(Some(&response), cx), window, cx);
         });
+        if effective_mode == ViewerMode::Protobuf {
+            let items = message_type_items(cx);
+            let selected = self.message_type.clone();
+            self.message_type_state.update(cx, |state, cx| {
+                state.set_items(items, window, cx);
+                match &selected {
+                    Some(selected) => state.set_selected_value(selected, window, cx),
+                    None => state.set_selected_index(None, window, cx),
+                }
+            });
+        }
         let token = PreviewToken::new(response, self.mode, effective_mode, self.preview_generation);
         self.preview_token = Some(token.clone());
         token
@@ -283,6 +474,23 @@ impl ResponsePane {
             .is_some_and(|current| current.matches(token))
     }
 
+    /// The type to decode a protobuf body with: the one its content type
+    /// names, or else the one picked in the pane.
+    pub(super) fn protobuf_message(
+        &self,
+        response: &ResponseData,
+        cx: &App,
+    ) -> Option<MessageDescriptor> {
+        let LoadedDescriptors(pool) = cx.try_global::<LoadedDescriptors>()?;
+        protobuf_message_name(&response.head().headers)
+            .and_then(|name| pool.get_message_by_name(&name))
+            .or_else(|| {
+                self.message_type
+                    .as_ref()
+                    .and_then(|name| pool.get_message_by_name(name))
+            })
+    }
+
     fn teardown_preview(&mut self) {
         self.preview_generation = self
             .preview_generation
@@ -291,6 +499,7 @@ impl ResponsePane {
         self.preview_token = None;
         self.projection = None;
         self.text_editor = None;
+        self.inspector.clear();
         self.preview_task.take();
         (&mut self.media).transition(MediaMessage::Stop);
         self.pdf.stop();
@@ -328,10 +537,53 @@ impl ResponsePane {
             | ResponseProjection::Image { .. }
             | ResponseProjection::Unavailable(_) => None,
         };
+        if let (Some(ResponseProjection::Text { document, .. }), Some(editor)) =
+            (&projection, &self.text_editor)
+        {
+            let effective_mode = self
+                .preview_token
+                .as_ref()
+                .map(PreviewToken::effective_mode);
+            self.inspector
+                .install(document.clone(), effective_mode, editor, window, cx);
+        }
         self.projection = Some(projection);
         self.preview_task.take();
     }
 
+    fn refresh_filter(&mut self, window: &mut Window, cx: &mut Context<RequestView>) {
+        let (Some(ResponseProjection::Text { source, .. }), Some(editor)) =
+            (&self.projection, &self.text_editor)
+        else {
+            return;
+        };
+        self.inspector
+            .refresh_filter(Some(source.as_str()), editor, window, cx);
+    }
+
+    fn finish_filter(
+        &mut self,
+        output: FilterOutput,
+        window: &mut Window,
+        cx: &mut Context<RequestView>,
+    ) {
+        if let Some(editor) = &self.text_editor {
+            self.inspector.finish_filter(output, editor, window, cx);
+        }
+    }
+
+    fn refresh_search(&mut self, window: &mut Window, cx: &mut Context<RequestView>) {
+        if let Some(editor) = &self.text_editor {
+            self.inspector.refresh_search(editor, window, cx);
+        }
+    }
+
+    fn step_search(&mut self, forward: bool, window: &mut Window, cx: &mut Context<RequestView>) {
+        if let Some(editor) = &self.text_editor {
+            self.inspector.step_search(forward, editor, window, cx);
+        }
+    }
+
     pub(super) fn start_pdf_preview(
         &mut self,
         token: PreviewToken,
@@ -656,6 +908,14 @@ impl ResponsePane {
         };
         let content = match runtime {
             RequestRuntime::Idle => centered_status(cx.global::<I18n>().t("response-empty")),
+            RequestRuntime::Sending { .. } if self.event_stream.is_resumed() => v_flex()
+                .size_full()
+                .min_h(px(0.))
+                .p_2()
+                .gap_2()
+                .child(Label::new(cx.global::<I18n>().t("response-sending")))
+                .child(render_event_table(&self.event_stream, cx))
+                .into_any_element(),
             RequestRuntime::Sending { .. } => v_flex()
                 .size_full()
                 .items_center()
@@ -696,6 +956,11 @@ impl ResponsePane {
         cx: &mut Context<RequestView>,
     ) -> AnyElement {
         let i18n = cx.global::<I18n>();
+        let can_pin = runtime.response().is_some_and(|response| {
+            self.pinned
+                .as_ref()
+                .is_none_or(|pinned| !Arc::ptr_eq(pinned, response))
+        });
         div()
             .flex()
             .items_center()
@@ -710,6 +975,33 @@ impl ResponsePane {
                         ),
                 )
             })
+            .when(can_pin, |this| {
+                this.child(
+                    Button::new("response-pin")
+                        .label(i18n.t("button-pin-response"))
+                        .on_click(cx.listener(|this, _, window, cx| this.pin_response(window, cx))),
+                )
+            })
+            .when(self.pinned.is_some(), |this| {
+                this.child(
+                    Button::new("response-unpin")
+                        .label(i18n.t("button-unpin-response"))
+                        .on_click(cx.listener(|this, _, _, cx| {
+                            this.response_pane.set_pinned_response(None);
+                            cx.notify();
+                        })),
+                )
+            })
+            .when(self.event_stream.is_reconnecting(), |this| {
+                this.child(
+                    Button::new("response-stop-reconnecting")
+                        .label(i18n.t("button-stop-reconnecting"))
+                        .on_click(cx.listener(|this, _, _, cx| {
+                            this.response_pane.event_stream.stop_reconnect();
+                            cx.notify();
+                        })),
+                )
+            })
             .when(
                 matches!(
                     runtime,
@@ -733,6 +1025,7 @@ impl ResponsePane {
     ) -> AnyElement {
         let progress = receipt.progress;
         let message = receiving_message(progress, cx);
+        let streaming = classify_content_type(&receipt.head.headers) == ContentKind::EventStream;
         let progress_element =
             if let Some(total) = progress.declared_encoded_bytes.filter(|v| *v > 0) {
                 Progress::new("response-receiving-progress")
@@ -754,8 +1047,25 @@ impl ResponsePane {
                     .when(self.tab == ResponseTab::Body, |this| {
                         this.child(Label::new(message)).child(progress_element)
                     })
+                    .when(self.tab == ResponseTab::Body && streaming, |this| {
+                        this.child(render_event_table(&self.event_stream, cx))
+                    })
                     .when(self.tab == ResponseTab::Headers, |this| {
                         this.child(render_headers(&receipt.head, cx))
+                    })
+                    .when(self.tab == ResponseTab::Tests, |this| {
+                        this.child(render_checks(&self.checks, cx))
+                    })
+                    .when(self.tab == ResponseTab::Connection, |this| {
+                        this.child(render_connection(&receipt.head, cx))
+                    })
+                    .when(self.tab == ResponseTab::Timing, |this| {
+                        this.child(render_timing(&receipt.head, None, cx))
+                    })
+                    .when(self.tab == ResponseTab::Diff, |this| {
+                        this.child(centered_status(
+                            cx.global::<I18n>().t("response-diff-waiting"),
+                        ))
                     }),
             )
             .into_any_element()
@@ -782,6 +1092,10 @@ impl ResponsePane {
             .child(match self.tab {
                 ResponseTab::Body => self.render_body_projection(window, cx),
                 ResponseTab::Headers => render_headers(response.head(), cx),
+                ResponseTab::Tests => render_checks(&self.checks, cx),
+                ResponseTab::Connection => render_connection(response.head(), cx),
+                ResponseTab::Timing => render_timing(response.head(), Some(response.timing()), cx),
+                ResponseTab::Diff => render_diff(self.pinned.as_ref(), response, &self.diff, cx),
             })
             .into_any_element()
     }
@@ -803,14 +1117,27 @@ impl ResponsePane {
                     receipt.progress,
                     cx,
                 ))
-                .child(render_headers(&receipt.head, cx))
+                .map(|this| {
+                    if classify_content_type(&receipt.head.headers) == ContentKind::EventStream {
+                        this.child(render_event_table(&self.event_stream, cx))
+                    } else {
+                        this.child(render_headers(&receipt.head, cx))
+                    }
+                })
             })
             .into_any_element()
     }
 
     fn render_tabs(&self, cx: &mut Context<RequestView>) -> impl IntoElement {
         let i18n = cx.global::<I18n>();
-        let labels = [i18n.t("tab-response-body"), i18n.t("tab-response-headers")];
+        let labels = [
+            i18n.t("tab-response-body"),
+            i18n.t("tab-response-headers"),
+            i18n.t("tab-response-tests"),
+            i18n.t("tab-response-connection"),
+            i18n.t("tab-response-timing"),
+            i18n.t("tab-response-diff"),
+        ];
         TabBar::new("response-tabs")
             .selected_index(self.tab.index())
             .on_click(cx.listener(|this, index, _, cx| {
@@ -835,13 +1162,18 @@ impl ResponsePane {
             match mode {
                 ViewerMode::Audio => return self.render_media(cx),
                 ViewerMode::Pdf => return self.render_pdf(window, cx),
+                ViewerMode::Events => return self.render_events(cx),
                 ViewerMode::Auto
                 | ViewerMode::Text
                 | ViewerMode::Json
                 | ViewerMode::Xml
                 | ViewerMode::Hex
                 | ViewerMode::Base64
-                | ViewerMode::Image => {}
+                | ViewerMode::Image
+                | ViewerMode::Protobuf
+                | ViewerMode::MessagePack
+                | ViewerMode::Cbor
+                | ViewerMode::Form => {}
             }
         }
         let warning = self
@@ -849,9 +1181,19 @@ impl ResponsePane {
             .as_ref()
             .and_then(ResponseProjection::warning)
             .map(|warning| warning_message(warning, cx));
+        let (filter_bar, search_bar) = match (&self.projection, &self.text_editor) {
+            (Some(ResponseProjection::Text { .. }), Some(_)) => (
+                self.inspector.render_filter_bar(cx),
+                (!self.inspector.shows_tree()).then(|| self.inspector.render_search_bar(cx)),
+            ),
+            _ => (None, None),
+        };
         let projection = match &self.projection {
             None => centered_status(cx.global::<I18n>().t("response-sending")),
             Some(ResponseProjection::Empty) => centered_status(SharedString::default()),
+            Some(ResponseProjection::Text { .. }) if self.inspector.shows_tree() => {
+                self.inspector.render_tree(cx)
+            }
             Some(ResponseProjection::Text { .. }) => self
                 .text_editor
                 .as_ref()
@@ -890,6 +1232,9 @@ impl ResponsePane {
             ),
         };
 
+        let protobuf = self.mode == ViewerMode::Protobuf;
+        let message_type_placeholder = cx.global::<I18n>().t("response-protobuf-message-type");
+
         v_flex()
             .flex_1()
             .min_h(px(0.))
@@ -898,17 +1243,45 @@ impl ResponsePane {
             .gap_2()
             .child(
                 div()
+                    .flex()
                     .flex_shrink_0()
-                    .w(px(180.))
-                    .child(Select::new(&self.mode_state)),
+                    .gap_2()
+                    .child(div().w(px(180.)).child(Select::new(&self.mode_state)))
+                    .when(protobuf, |this| {
+                        this.child(
+                            div().w(px(320.)).child(
+                                Select::new(&self.message_type_state)
+                                    .placeholder(message_type_placeholder),
+                            ),
+                        )
+                    }),
             )
             .when_some(warning, |this, warning| {
                 this.child(Alert::warning("response-view-warning", warning))
             })
+            .when_some(filter_bar, |this, filter_bar| this.child(filter_bar))
+            .when_some(search_bar, |this, search_bar| this.child(search_bar))
             .child(projection)
             .into_any_element()
     }
 
+    fn render_events(&self, cx: &mut Context<RequestView>) -> AnyElement {
+        v_flex()
+            .flex_1()
+            .min_h(px(0.))
+            .overflow_hidden()
+            .p_2()
+            .gap_2()
+            .child(
+                div()
+                    .flex_shrink_0()
+                    .w(px(180.))
+                    .child(Select::new(&self.mode_state)),
+            )
+            .child(render_event_table(&self.event_stream, cx))
+            .into_any_element()
+    }
+
     fn render_media(&self, cx: &mut Context<RequestView>) -> AnyElement {
         let phase = self.media.phase();
         let problem = self
@@ -1221,11 +1594,26 @@ fn viewer_mode_items(response: Option<&ResponseData>, cx: &App) -> ViewerModeIte
             value,
             title: cx.global::<I18n>().t(viewer_mode_key(value)).into(),
             disabled: response
-                .is_some_and(|response| resolved_viewer_mode(response, value).is_none()),
+                .is_some_and(|response| resolved_viewer_mode(response, value).is_none())
+                || (value == ViewerMode::Protobuf && !cx.has_global::<LoadedDescriptors>()),
         })
         .collect()
 }
 
+/// Every message type in the loaded descriptors, in name order.
+fn message_type_items(cx: &App) -> MessageTypeItems {
+    let mut names: Vec<SharedString> = cx
+        .try_global::<LoadedDescriptors>()
+        .map(|LoadedDescriptors(pool)| {
+            pool.all_messages()
+                .map(|message| message.full_name().to_owned().into())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    MessageTypeItems::new(names)
+}
+
 fn pdf_viewport(handle: &ScrollHandle, window: &Window) -> PdfViewport {
     let measured = handle.bounds().size;
     let viewport = if measured.width > px(0.) && measured.height > px(0.) {
@@ -1253,6 +1641,11 @@ const fn viewer_mode_key(mode: ViewerMode) -> &'static str {
         ViewerMode::Image => "response-view-image",
         ViewerMode::Audio => "response-view-audio",
         ViewerMode::Pdf => "response-view-pdf",
+        ViewerMode::Events => "response-view-events",
+        ViewerMode::Protobuf => "response-view-protobuf",
+        ViewerMode::MessagePack => "response-view-msgpack",
+        ViewerMode::Cbor => "response-view-cbor",
+        ViewerMode::Form => "response-view-form",
     }
 }
 
@@ -1276,6 +1669,235 @@ fn summary_value(label: impl Into<SharedString>, value: String) -> AnyElement {
         .into_any_element()
 }
 
+/// The negotiated protocol and, for TLS, every certificate the peer
+/// presented.
+fn render_connection(head: &ResponseHead, cx: &App) -> AnyElement {
+    let i18n = cx.global::<I18n>();
+    let tls = head.tls.as_ref();
+    let connection_rows = vec![
+        (
+            i18n.t("response-connection-protocol"),
+            protocol_label(head.version),
+        ),
+        (
+            i18n.t("response-connection-tls"),
+            match tls {
+                Some(tls) => tls.version.unwrap_or("—").to_owned(),
+                None => i18n.t("response-connection-plain"),
+            },
+        ),
+    ];
+    let certificates = tls.map_or(&[][..], |tls| tls.peer_certificates.as_slice());
+    let verification_disabled = tls.is_some_and(|tls| tls.verification_disabled);
+
+    v_flex()
+        .flex_1()
+        .min_h(px(0.))
+        .gap_2()
+        .overflow_scrollbar()
+        .when(verification_disabled, |this| {
+            this.child(Alert::warning(
+                "response-verification-disabled",
+                i18n.t("response-certificate-unverified"),
+            ))
+        })
+        .child(connection_table(connection_rows, cx))
+        .children(certificates.iter().enumerate().map(|(index, certificate)| {
+            let mut args = FluentArgs::new();
+            args.set("position", index + 1);
+            args.set("count", certificates.len());
+            v_flex()
+                .gap_1()
+                .child(
+                    Label::new(i18n.t_with_args("response-certificate-position", &args))
+                        .text_sm()
+                        .font_semibold(),
+                )
+                .child(connection_table(certificate_rows(certificate, cx), cx))
+        }))
+        .into_any_element()
+}
+
+fn certificate_rows(certificate: &PeerCertificate, cx: &App) -> Vec<(String, String)> {
+    let i18n = cx.global::<I18n>();
+    vec![
+        (
+            i18n.t("response-certificate-subject"),
+            certificate.subject.clone(),
+        ),
+        (
+            i18n.t("response-certificate-issuer"),
+            certificate.issuer.clone(),
+        ),
+        (
+            i18n.t("response-certificate-alt-names"),
+            certificate.subject_alt_names.join(", "),
+        ),
+        (
+            i18n.t("response-certificate-not-before"),
+            certificate.not_before.clone(),
+        ),
+        (
+            i18n.t("response-certificate-not-after"),
+            certificate.not_after.clone(),
+        ),
+        (
+            i18n.t("response-certificate-serial"),
+            certificate.serial.clone(),
+        ),
+        (
+            i18n.t("response-certificate-sha256"),
+            certificate.sha256.clone(),
+        ),
+    ]
+}
+
+fn connection_table(rows: Vec<(String, String)>, cx: &App) -> Table {
+    Table::new().small().child(
+        TableBody::new().children(rows.into_iter().map(|(name, value)| {
+            TableRow::new()
+                .child(TableCell::new().w(px(240.)).child(Label::new(name)))
+                .child(
+                    TableCell::new()
+                        .child(Label::new(value).font_family(cx.theme().mono_font_family.clone())),
+                )
+        })),
+    )
+}
+
+/// The hops of a response with the final one's download appended once the
+/// body has been read.
+pub(crate) fn waterfall_hops(
+    head: &ResponseHead,
+    timing: Option<&ResponseTiming>,
+) -> Vec<HopTiming> {
+    let mut hops = head.hops.clone();
+    if let (Some(last), Some(timing)) = (hops.last_mut(), timing) {
+        last.spans.push(TimingSpan {
+            phase: TimingPhase::ContentDownload,
+            start: timing.head_after,
+            end: timing.completed_after.max(timing.head_after),
+        });
+    }
+    hops
+}
+
+/// Every hop as a bar on a shared time axis, with the phases listed below it.
+fn render_timing(head: &ResponseHead, timing: Option<&ResponseTiming>, cx: &App) -> AnyElement {
+    let hops = waterfall_hops(head, timing);
+    let i18n = cx.global::<I18n>();
+    if hops.is_empty() {
+        return centered_status(i18n.t("response-timing-empty"));
+    }
+    let total = hops
+        .iter()
+        .map(HopTiming::end)
+        .max()
+        .unwrap_or_default()
+        .max(Duration::from_millis(1));
+    let fraction = |duration: Duration| (duration.as_secs_f32() / total.as_secs_f32()).min(1.);
+
+    v_flex()
+        .flex_1()
+        .min_h(px(0.))
+        .gap_3()
+        .overflow_scrollbar()
+        .child(summary_value(
+            i18n.t("response-timing-total"),
+            format_duration(total),
+        ))
+        .children(hops.iter().map(|hop| {
+            let target = format!(
+                "{} {} {}{}",
+                hop.status.as_u16(),
+                hop.method,
+                hop.url.host_str().unwrap_or_default(),
+                hop.url.path()
+            );
+            // A secure connection whose handshake was not timed on its own
+            // reports both in its connect.
+            let handshake_in_connect = hop.url.scheme() == "https"
+                && !hop
+                    .spans
+                    .iter()
+                    .any(|span| span.phase == TimingPhase::TlsHandshake);
+            v_flex()
+                .gap_1()
+                .child(
+                    div()
+                        .flex()
+                        .gap_2()
+                        .child(
+                            Label::new(target)
+                                .text_xs()
+                                .font_family(cx.theme().mono_font_family.clone()),
+                        )
+                        .when(hop.reused_connection, |this| {
+                            this.child(
+                                Label::new(i18n.t("response-timing-reused"))
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground),
+                            )
+                        }),
+                )
+                .child(
+                    div()
+                        .relative()
+                        .w_full()
+                        .h(px(10.))
+                        .rounded(cx.theme().radius)
+                        .bg(cx.theme().muted)
+                        .children(hop.spans.iter().map(|span| {
+                            div()
+                                .absolute()
+                                .top_0()
+                                .bottom_0()
+                                .left(relative(fraction(span.start)))
+                                .w(relative(fraction(span.duration()).max(0.002)))
+                                .bg(phase_color(span.phase, cx))
+                        })),
+                )
+                .children(hop.spans.iter().map(|span| {
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(div().size(px(8.)).bg(phase_color(span.phase, cx)))
+                        .child(
+                            Label::new(i18n.t(phase_label(span.phase, handshake_in_connect)))
+                                .text_xs()
+                                .w(px(180.)),
+                        )
+                        .child(Label::new(format_duration(span.duration())).text_xs())
+                }))
+        }))
+        .into_any_element()
+}
+
+fn phase_label(phase: TimingPhase, handshake_in_connect: bool) -> &'static str {
+    match phase {
+        TimingPhase::DnsLookup => "response-timing-dns",
+        TimingPhase::Connect if handshake_in_connect => "response-timing-connect-tls",
+        TimingPhase::Connect => "response-timing-connect",
+        TimingPhase::TlsHandshake => "response-timing-tls",
+        TimingPhase::RequestWrite => "response-timing-request-write",
+        TimingPhase::Waiting => "response-timing-waiting",
+        TimingPhase::ContentDownload => "response-timing-download",
+    }
+}
+
+fn phase_color(phase: TimingPhase, cx: &App) -> gpui::Hsla {
+    let theme = cx.theme();
+    match phase {
+        TimingPhase::DnsLookup => theme.chart_1,
+        TimingPhase::Connect => theme.chart_2,
+        TimingPhase::TlsHandshake => theme.chart_2.opacity(0.6),
+        TimingPhase::RequestWrite => theme.chart_3,
+        TimingPhase::Waiting => theme.chart_4,
+        TimingPhase::ContentDownload => theme.chart_5,
+    }
+}
+
 fn render_headers(head: &ResponseHead, cx: &App) -> AnyElement {
     if head.headers.is_empty() {
         return centered_status(cx.global::<I18n>().t("response-headers-empty"));
@@ -1329,12 +1951,24 @@ fn receiving_message(progress: ResponseProgress, cx: &App) -> String {
     }
 }
 
-fn problem_message(kind: RequestProblemKind, cx: &App) -> String {
+pub(crate) fn problem_message(kind: RequestProblemKind, cx: &App) -> String {
     let i18n = cx.global::<I18n>();
     match kind {
         RequestProblemKind::Transport => i18n.t("request-problem-transport"),
         RequestProblemKind::Timeout => i18n.t("request-problem-timeout"),
         RequestProblemKind::Redirect(_) => i18n.t("request-problem-redirect"),
+        RequestProblemKind::Authorization(kind) => i18n.t(match kind {
+            AuthorizationProblemKind::TokenEndpoint => "request-problem-auth-token-endpoint",
+            AuthorizationProblemKind::TokenRejected => "request-problem-auth-token-rejected",
+            AuthorizationProblemKind::InvalidTokenResponse => "request-problem-auth-token-invalid",
+            AuthorizationProblemKind::Denied => "request-problem-auth-denied",
+            AuthorizationProblemKind::Callback => "request-problem-auth-callback",
+        }),
+        RequestProblemKind::Connection(kind) => i18n.t(match kind {
+            ConnectionProblemKind::CaCertificate => "request-problem-ca-certificate",
+            ConnectionProblemKind::ClientIdentity => "request-problem-client-identity",
+            ConnectionProblemKind::Proxy => "request-problem-proxy",
+        }),
         RequestProblemKind::RequestBodyRead => i18n.t("request-problem-request-body"),
         RequestProblemKind::ResponseBodyRead => i18n.t("request-problem-response-read"),
         RequestProblemKind::ResponseBodyDecode => i18n.t("request-problem-response-decode"),
@@ -1367,6 +2001,12 @@ fn warning_message(warning: ResponseViewWarning, cx: &App) -> String {
         ResponseViewWarning::InvalidJson => "response-viewer-invalid-json",
         ResponseViewWarning::InvalidImage => "response-viewer-invalid-image",
         ResponseViewWarning::ImageTooLarge => "response-image-too-large",
+        ResponseViewWarning::ProtobufTypeRequired => "response-protobuf-type-required",
+        ResponseViewWarning::ProtobufTooLarge => "response-protobuf-too-large",
+        ResponseViewWarning::InvalidProtobuf => "response-protobuf-invalid",
+        ResponseViewWarning::DocumentTooLarge => "response-document-too-large",
+        ResponseViewWarning::InvalidMessagePack => "response-msgpack-invalid",
+        ResponseViewWarning::InvalidCbor => "response-cbor-invalid",
     })
 }
 
@@ -1411,6 +2051,7 @@ mod pane_tests {
     use url::Url;
 
     use super::*;
+    use crate::features::request::CookieJar;
     use crate::foundation::i18n::init_i18n;
 
     struct FakeMediaDriver;
@@ -1470,7 +2111,7 @@ mod pane_tests {
 
     #[test]
     fn response_tabs_and_viewer_modes_have_total_stable_indices() {
-        assert_eq!(ResponseTab::ALL.map(ResponseTab::index), [0, 1]);
+        assert_eq!(ResponseTab::ALL.map(ResponseTab::index), [0, 1, 2, 3, 4, 5]);
         assert_eq!(
             ViewerMode::ALL,
             [
@@ -1483,16 +2124,61 @@ mod pane_tests {
                 ViewerMode::Image,
                 ViewerMode::Audio,
                 ViewerMode::Pdf,
+                ViewerMode::Events,
+                ViewerMode::Protobuf,
+                ViewerMode::MessagePack,
+                ViewerMode::Cbor,
+                ViewerMode::Form,
             ]
         );
     }
 
+    #[test]
+    fn the_waterfall_appends_the_download_to_the_final_hop_only() {
+        let span = |phase, start, end| TimingSpan {
+            phase,
+            start: Duration::from_millis(start),
+            end: Duration::from_millis(end),
+        };
+        let hop = |status, spans| HopTiming {
+            method: http::Method::GET,
+            url: Url::parse("https://example.test/a").unwrap(),
+            status,
+            reused_connection: false,
+            spans,
+        };
+        let mut head = ResponseHead::new(
+            StatusCode::OK,
+            Version::HTTP_11,
+            Url::parse("https://example.test/b").unwrap(),
+            HeaderMap::new(),
+        );
+        head.hops = vec![
+            hop(StatusCode::FOUND, vec![span(TimingPhase::Waiting, 0, 40)]),
+            hop(StatusCode::OK, vec![span(TimingPhase::Waiting, 45, 90)]),
+        ];
+        assert_eq!(waterfall_hops(&head, None), head.hops);
+
+        let timing = ResponseTiming {
+            head_after: Duration::from_millis(90),
+            completed_after: Duration::from_millis(130),
+        };
+        let hops = waterfall_hops(&head, Some(&timing));
+        assert_eq!(hops[0], head.hops[0]);
+        assert_eq!(
+            hops[1].spans.last(),
+            Some(&span(TimingPhase::ContentDownload, 90, 130))
+        );
+        assert_eq!(hops[1].end(), Duration::from_millis(130));
+    }
+
     #[gpui::test]
     fn text_projection_uses_a_selectable_read_only_editor_and_teardown_releases_it(
         cx: &mut TestAppContext,
     ) {
         initialize(cx);
-        let (view, cx) = cx.add_window_view(RequestView::new);
+        let (view, cx) =
+            cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
         let response = completed_response();
         let source = "{\n  \"answer\": 42\n}";
         let editor = cx.update(|window, cx| {
@@ -1507,6 +2193,7 @@ mod pane_tests {
                         source: source.into(),
                         language: SourceLanguage::Json,
                         warning: None,
+                        document: None,
                     },
                     window,
                     cx,
@@ -1533,7 +2220,7 @@ mod pane_tests {
 
         cx.update(|window, cx| {
             view.update(cx, |view, cx| {
-                view.response_pane.reset_for_send(window, cx);
+                view.response_pane.reset_for_send(false, window, cx);
                 assert!(view.response_pane.text_editor.is_none());
             })
         });
@@ -1544,7 +2231,8 @@ mod pane_tests {
         cx: &mut TestAppContext,
     ) {
         initialize(cx);
-        let (view, cx) = cx.add_window_view(RequestView::new);
+        let (view, cx) =
+            cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
         let token = cx.update(|window, cx| {
             view.update(cx, |view, cx| {
                 let token = view.response_pane.begin_preview(
@@ -1596,7 +2284,8 @@ mod pane_tests {
     #[gpui::test]
     fn media_and_pdf_preview_teardown_reject_stale_work_without_autoplay(cx: &mut TestAppContext) {
         initialize(cx);
-        let (view, cx) = cx.add_window_view(RequestView::new);
+        let (view, cx) =
+            cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
         let response = completed_response();
         let audio_task_dropped = Arc::new(AtomicBool::new(false));
 
@@ -1666,7 +2355,7 @@ mod pane_tests {
             view.update(cx, |view, cx| {
                 // `reset_for_send` is the new-Send teardown path. Stale
                 // audio completions cannot revive a stopped preview.
-                view.response_pane.reset_for_send(window, cx);
+                view.response_pane.reset_for_send(false, window, cx);
                 assert_eq!(view.response_pane.mode(), ViewerMode::Auto);
                 assert!(!view.response_pane.is_current_preview(&audio_token));
                 assert_eq!(view.response_pane.media.phase(), MediaPhase::Idle);
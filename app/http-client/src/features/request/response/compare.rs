@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use fluent_bundle::FluentArgs;
+use gpui::{
+    AnyElement, App, Hsla, IntoElement, ParentElement as _, Styled as _, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Sizable as _, StyledExt as _, h_flex,
+    label::Label,
+    scroll::ScrollableElement as _,
+    table::{Table, TableBody, TableCell, TableHead, TableHeader, TableRow},
+    v_flex,
+};
+
+use super::{
+    ResponseData, centered_status,
+    diff::{BodyDiff, Change, DiffLine, DiffRow, KeyedChange, ResponseDiff},
+    format_bytes,
+};
+use crate::foundation::I18n;
+
+/// Where the comparison of the current response with the pinned one stands.
+#[derive(Default)]
+pub(super) enum DiffStatus {
+    #[default]
+    Idle,
+    Running,
+    Finished(ResponseDiff),
+    Failed,
+}
+
+pub(super) fn render_diff(
+    pinned: Option<&Arc<ResponseData>>,
+    current: &Arc<ResponseData>,
+    status: &DiffStatus,
+    cx: &App,
+) -> AnyElement {
+    let i18n = cx.global::<I18n>();
+    let diff = match (pinned, status) {
+        (None, _) => return centered_status(i18n.t("response-diff-no-pin")),
+        (Some(pinned), _) if Arc::ptr_eq(pinned, current) => {
+            return centered_status(i18n.t("response-diff-pinned-current"));
+        }
+        (Some(_), DiffStatus::Idle | DiffStatus::Running) => {
+            return centered_status(i18n.t("response-diff-running"));
+        }
+        (Some(_), DiffStatus::Failed) => return centered_status(i18n.t("response-diff-failed")),
+        (Some(_), DiffStatus::Finished(diff)) => diff,
+    };
+
+    let status_line = match diff.status {
+        Some((before, after)) => {
+            let mut args = FluentArgs::new();
+            args.set("before", before.as_u16());
+            args.set("after", after.as_u16());
+            i18n.t_with_args("response-diff-status", &args)
+        }
+        None => i18n.t("response-diff-status-same"),
+    };
+    let section = |key: &'static str| Label::new(i18n.t(key)).font_semibold();
+    let note = |text: String| Label::new(text).text_color(cx.theme().muted_foreground);
+
+    let body = match &diff.body {
+        BodyDiff::Identical => note(i18n.t("response-diff-body-same")).into_any_element(),
+        BodyDiff::Binary { before, after } => {
+            let mut args = FluentArgs::new();
+            args.set("before", format_bytes(*before));
+            args.set("after", format_bytes(*after));
+            note(i18n.t_with_args("response-diff-body-binary", &args)).into_any_element()
+        }
+        BodyDiff::Json { changes, .. } => {
+            render_changes("response-diff-path", changes, cx).into_any_element()
+        }
+        BodyDiff::Lines { rows, .. } => render_lines(rows, cx),
+    };
+    let truncated = matches!(
+        diff.body,
+        BodyDiff::Json {
+            truncated: true,
+            ..
+        } | BodyDiff::Lines {
+            truncated: true,
+            ..
+        }
+    );
+
+    div()
+        .flex_1()
+        .min_h(px(0.))
+        .overflow_scrollbar()
+        .child(
+            v_flex()
+                .p_2()
+                .gap_2()
+                .child(Label::new(status_line))
+                .child(section("response-diff-headers"))
+                .map(|this| {
+                    if diff.headers.is_empty() {
+                        this.child(note(i18n.t("response-diff-headers-same")))
+                    } else {
+                        this.child(render_changes("response-header-name", &diff.headers, cx))
+                    }
+                })
+                .child(section("response-diff-body"))
+                .child(body)
+                .when(truncated, |this| {
+                    this.child(note(i18n.t("response-diff-truncated")))
+                }),
+        )
+        .into_any_element()
+}
+
+fn render_changes(
+    key_heading: &'static str,
+    changes: &[KeyedChange],
+    cx: &App,
+) -> impl IntoElement {
+    let i18n = cx.global::<I18n>();
+    let rows = changes.iter().map(|entry| {
+        let (before, after) = match &entry.change {
+            Change::Added(after) => (None, Some(after)),
+            Change::Removed(before) => (Some(before), None),
+            Change::Changed { before, after } => (Some(before), Some(after)),
+        };
+        let cell = |value: Option<&String>, color: Hsla| {
+            TableCell::new().child(
+                Label::new(value.cloned().unwrap_or_default())
+                    .text_color(color)
+                    .font_family(cx.theme().mono_font_family.clone()),
+            )
+        };
+        TableRow::new()
+            .child(
+                TableCell::new()
+                    .w(px(240.))
+                    .child(Label::new(entry.key.clone())),
+            )
+            .child(cell(before, cx.theme().danger))
+            .child(cell(after, cx.theme().success))
+    });
+    Table::new()
+        .small()
+        .child(
+            TableHeader::new().child(
+                TableRow::new()
+                    .child(
+                        TableHead::new()
+                            .w(px(240.))
+                            .child(Label::new(i18n.t(key_heading))),
+                    )
+                    .child(TableHead::new().child(Label::new(i18n.t("response-diff-pinned"))))
+                    .child(TableHead::new().child(Label::new(i18n.t("response-diff-current")))),
+            ),
+        )
+        .child(TableBody::new().children(rows))
+}
+
+fn render_lines(rows: &[DiffRow], cx: &App) -> AnyElement {
+    let muted = cx.theme().muted_foreground;
+    let side = |line: Option<&DiffLine>, background: Hsla| {
+        h_flex()
+            .flex_1()
+            .min_w(px(0.))
+            .gap_2()
+            .px_1()
+            .when_some(line, |this, line| {
+                this.bg(background)
+                    .child(
+                        div()
+                            .w(px(48.))
+                            .flex_shrink_0()
+                            .text_color(muted)
+                            .child(line.number.to_string()),
+                    )
+                    .child(div().flex_1().child(line.text.clone()))
+            })
+    };
+    let same = |number: usize, text: &str| {
+        side(
+            Some(&DiffLine {
+                number,
+                text: text.to_owned(),
+            }),
+            Hsla::transparent_black(),
+        )
+    };
+    v_flex()
+        .font_family(cx.theme().mono_font_family.clone())
+        .text_sm()
+        .border_1()
+        .border_color(cx.theme().border)
+        .children(rows.iter().map(|row| {
+            match row {
+                DiffRow::Same {
+                    before,
+                    after,
+                    text,
+                } => h_flex()
+                    .child(same(*before, text))
+                    .child(same(*after, text))
+                    .into_any_element(),
+                DiffRow::Changed { before, after } => h_flex()
+                    .child(side(before.as_ref(), cx.theme().danger.opacity(0.15)))
+                    .child(side(after.as_ref(), cx.theme().success.opacity(0.15)))
+                    .into_any_element(),
+                DiffRow::Skipped(count) => {
+                    let mut args = FluentArgs::new();
+                    args.set("count", *count);
+                    div()
+                        .px_2()
+                        .bg(cx.theme().muted)
+                        .text_color(muted)
+                        .child(
+                            cx.global::<I18n>()
+                                .t_with_args("response-diff-skipped", &args),
+                        )
+                        .into_any_element()
+                }
+            }
+        }))
+        .into_any_element()
+}
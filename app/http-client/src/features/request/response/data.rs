@@ -1,7 +1,7 @@
 use std::{error::Error, fmt, sync::Arc, time::Duration};
 
 use bytes::Bytes;
-use http::{HeaderMap, StatusCode, Version};
+use http::{HeaderMap, Method, StatusCode, Version};
 use tempfile::TempPath;
 use tokio::io::{AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _};
 use url::Url;
@@ -82,6 +82,11 @@ pub(crate) struct ResponseHead {
     pub(crate) version: Version,
     pub(crate) final_url: Url,
     pub(crate) headers: HeaderMap,
+    /// Present when the final hop ran over TLS.
+    pub(crate) tls: Option<TlsDetails>,
+    /// Every request sent for this response, redirects and auth challenges
+    /// included. The last entry produced this head.
+    pub(crate) hops: Vec<HopTiming>,
 }
 
 impl ResponseHead {
@@ -97,6 +102,8 @@ impl ResponseHead {
             version,
             final_url,
             headers,
+            tls: None,
+            hops: Vec::new(),
         }
     }
 }
@@ -109,10 +116,97 @@ impl fmt::Debug for ResponseHead {
             .field("version", &self.version)
             .field("final_url", &"<redacted>")
             .field("header_count", &self.headers.len())
+            .field("tls", &self.tls)
+            .field("hops", &self.hops)
             .finish()
     }
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct TlsDetails {
+    /// `TLS 1.3` and the like.
+    pub(crate) version: Option<&'static str>,
+    /// The chain the server presented, leaf first.
+    pub(crate) peer_certificates: Vec<PeerCertificate>,
+    /// The request was sent with certificate verification switched off.
+    pub(crate) verification_disabled: bool,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct PeerCertificate {
+    pub(crate) subject: String,
+    pub(crate) issuer: String,
+    pub(crate) serial: String,
+    pub(crate) not_before: String,
+    pub(crate) not_after: String,
+    pub(crate) subject_alt_names: Vec<String>,
+    pub(crate) sha256: String,
+}
+
+/// One request of a redirect chain. Span offsets are measured from the
+/// start of the whole request so hops share one axis.
+#[derive(Clone, Eq, PartialEq)]
+pub(crate) struct HopTiming {
+    pub(crate) method: Method,
+    pub(crate) url: Url,
+    pub(crate) status: StatusCode,
+    /// A pooled connection carried the request, so there was no lookup or
+    /// connect phase.
+    pub(crate) reused_connection: bool,
+    pub(crate) spans: Vec<TimingSpan>,
+}
+
+impl HopTiming {
+    pub(crate) fn end(&self) -> Duration {
+        self.spans
+            .iter()
+            .map(|span| span.end)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Debug for HopTiming {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("HopTiming")
+            .field("method", &self.method)
+            .field("url", &"<redacted>")
+            .field("status", &self.status)
+            .field("reused_connection", &self.reused_connection)
+            .field("spans", &self.spans)
+            .finish()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct TimingSpan {
+    pub(crate) phase: TimingPhase,
+    pub(crate) start: Duration,
+    pub(crate) end: Duration,
+}
+
+impl TimingSpan {
+    pub(crate) fn duration(&self) -> Duration {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum TimingPhase {
+    DnsLookup,
+    /// The TCP handshake, or the tunnel through a proxy.
+    Connect,
+    /// From the ClientHello to a secure connection, for HTTPS.
+    TlsHandshake,
+    /// Streaming the request body, up to the last byte handed to the
+    /// connection.
+    RequestWrite,
+    /// Time to first byte: from the request being sent to its head arriving.
+    Waiting,
+    ContentDownload,
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) struct ResponseTiming {
     pub(crate) head_after: Duration,
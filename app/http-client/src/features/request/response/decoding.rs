@@ -126,7 +126,7 @@ pub(crate) async fn collect_response_body<S, E, F>(
 where
     S: Stream<Item = Result<Bytes, E>> + Send + 'static,
     E: Error + Send + Sync + 'static,
-    F: FnMut(ResponseProgress) + Send,
+    F: FnMut(ResponseProgress, &[u8]) + Send,
 {
     let declared_encoded_bytes = declared_encoded_bytes(headers);
     if let Some(observed) = declared_encoded_bytes.filter(|length| *length > CAPTURE_LIMIT_BYTES) {
@@ -165,12 +165,15 @@ where
             Err(error) => return Err(map_pipeline_read_problem(error, decoder.body_decoding())),
         };
         collector.write(&buffer[..read]).await?;
-        on_progress(ResponseProgress {
-            declared_encoded_bytes,
-            received_encoded_bytes: received_encoded_bytes.load(Ordering::Relaxed),
-            stored_body_bytes: collector.len(),
-            storage: collector.storage(),
-        });
+        on_progress(
+            ResponseProgress {
+                declared_encoded_bytes,
+                received_encoded_bytes: received_encoded_bytes.load(Ordering::Relaxed),
+                stored_body_bytes: collector.len(),
+                storage: collector.storage(),
+            },
+            &buffer[..read],
+        );
     }
 
     let received_encoded_bytes = received_encoded_bytes.load(Ordering::Relaxed);
@@ -178,12 +181,15 @@ where
     let storage = collector.storage();
     let body = collector.finish().await?;
     debug_assert_eq!(body.len(), stored_body_bytes);
-    on_progress(ResponseProgress {
-        declared_encoded_bytes,
-        received_encoded_bytes,
-        stored_body_bytes,
-        storage,
-    });
+    on_progress(
+        ResponseProgress {
+            declared_encoded_bytes,
+            received_encoded_bytes,
+            stored_body_bytes,
+            storage,
+        },
+        &[],
+    );
     Ok(CompletedBody {
         body,
         body_decoding: decoder.body_decoding(),
@@ -247,6 +253,11 @@ pub(crate) enum ContentKind {
     Image,
     Audio,
     Pdf,
+    EventStream,
+    Protobuf,
+    MessagePack,
+    Cbor,
+    Form,
     Bytes,
 }
 
@@ -288,6 +299,9 @@ pub(crate) fn classify_content_type(headers: &HeaderMap) -> ContentKind {
     if essence == "application/json" || suffix == Some("json") {
         return ContentKind::Json;
     }
+    if essence == "application/cbor" || suffix == Some("cbor") {
+        return ContentKind::Cbor;
+    }
     if essence == "image/svg+xml" {
         return ContentKind::Text(SourceLanguage::Svg);
     }
@@ -317,6 +331,15 @@ pub(crate) fn classify_content_type(headers: &HeaderMap) -> ContentKind {
             ContentKind::Text(SourceLanguage::Yaml)
         }
         "text/markdown" => ContentKind::Text(SourceLanguage::Markdown),
+        "text/event-stream" => ContentKind::EventStream,
+        "application/x-protobuf"
+        | "application/protobuf"
+        | "application/vnd.google.protobuf"
+        | "application/x-google-protobuf" => ContentKind::Protobuf,
+        "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+            ContentKind::MessagePack
+        }
+        "application/x-www-form-urlencoded" => ContentKind::Form,
         _ if media_type.type_() == mime::TEXT => ContentKind::Text(SourceLanguage::Plain),
         _ => ContentKind::Bytes,
     }
@@ -358,6 +381,17 @@ pub(crate) fn decode_text(
     }
 }
 
+/// The message type a protobuf body names in its content type, as in
+/// `application/x-protobuf; messageType=pkg.Message`.
+pub(crate) fn protobuf_message_name(headers: &HeaderMap) -> Option<String> {
+    let media_type = response_media_type(headers)?;
+    media_type
+        .params()
+        .find(|(name, _)| matches!(name.as_str(), "messagetype" | "proto"))
+        .map(|(_, value)| value.as_str().trim_start_matches('.').to_owned())
+        .filter(|name| !name.is_empty())
+}
+
 fn response_media_type(headers: &HeaderMap) -> Option<Mime> {
     headers
         .get(header::CONTENT_TYPE)?
@@ -526,7 +560,7 @@ mod tests {
         collect_response_body(
             &headers,
             stream::iter([Ok::<_, TestReadProblem>(Bytes::from(bytes))]),
-            |_| {},
+            |_, _| {},
         )
         .await
         .unwrap()
@@ -594,7 +628,7 @@ mod tests {
         let decode_problem = collect_response_body(
             &gzip_headers,
             stream::iter([Ok::<_, TestReadProblem>(Bytes::from_static(b"not gzip"))]),
-            |_| {},
+            |_, _| {},
         )
         .await
         .unwrap_err();
@@ -606,7 +640,7 @@ mod tests {
         let read_problem = collect_response_body(
             &HeaderMap::new(),
             stream::iter([Err::<Bytes, _>(TestReadProblem)]),
-            |_| {},
+            |_, _| {},
         )
         .await
         .unwrap_err();
@@ -625,7 +659,7 @@ mod tests {
         let problem = collect_response_body(
             &headers,
             stream::pending::<Result<Bytes, TestReadProblem>>(),
-            |_| {},
+            |_, _| {},
         )
         .await
         .unwrap_err();
@@ -665,6 +699,14 @@ mod tests {
             ("image/svg+xml", ContentKind::Text(SourceLanguage::Svg)),
             ("application/pdf", ContentKind::Pdf),
             ("audio/ogg", ContentKind::Audio),
+            ("text/event-stream; charset=utf-8", ContentKind::EventStream),
+            ("application/x-protobuf", ContentKind::Protobuf),
+            ("application/x-msgpack", ContentKind::MessagePack),
+            ("application/vnd.example+cbor", ContentKind::Cbor),
+            (
+                "application/x-www-form-urlencoded; charset=utf-8",
+                ContentKind::Form,
+            ),
             ("video/mp4; codecs=avc1", ContentKind::Bytes),
             ("application/octet-stream", ContentKind::Bytes),
         ] {
@@ -677,6 +719,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn protobuf_message_names_come_from_content_type_parameters() {
+        let name = |media_type: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(media_type));
+            protobuf_message_name(&headers)
+        };
+        assert_eq!(
+            name("application/x-protobuf; messageType=\"greet.Reply\"").as_deref(),
+            Some("greet.Reply")
+        );
+        assert_eq!(
+            name("application/protobuf; proto=.greet.Reply").as_deref(),
+            Some("greet.Reply")
+        );
+        assert_eq!(name("application/x-protobuf"), None);
+    }
+
     #[test]
     fn text_decode_honors_charset_bom_and_truncated_multibyte_tail() {
         let mut windows_headers = HeaderMap::new();
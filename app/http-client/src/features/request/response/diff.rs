@@ -0,0 +1,644 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use http::{HeaderMap, StatusCode};
+use serde_json::Value;
+
+use super::{
+    CAPTURE_LIMIT_BYTES, ResponseData, ResponseReadProblem, decode_text, escape_header_value,
+};
+use crate::features::request::json_path::push_member;
+
+/// Unchanged lines kept around each change in a line diff.
+const CONTEXT_LINES: usize = 3;
+/// The line diff gives up on finding the shortest edit script past this
+/// many edits and shows the rest of the differing region as replaced.
+const MAX_EDIT_DISTANCE: usize = 1_000;
+/// Caps the rows of a line diff and the changes of a JSON diff.
+const MAX_DIFF_ENTRIES: usize = 5_000;
+/// Values in a JSON diff are shown compactly and cut short past this.
+const MAX_VALUE_CHARS: usize = 200;
+
+/// How a pinned response differs from a later one.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ResponseDiff {
+    /// Present when the status codes differ.
+    pub(crate) status: Option<(StatusCode, StatusCode)>,
+    pub(crate) headers: Vec<KeyedChange>,
+    pub(crate) body: BodyDiff,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum BodyDiff {
+    Identical,
+    /// Both bodies are JSON; keys are matched by name and array items by
+    /// index.
+    Json {
+        changes: Vec<KeyedChange>,
+        truncated: bool,
+    },
+    /// Both bodies are text, compared line by line.
+    Lines {
+        rows: Vec<DiffRow>,
+        truncated: bool,
+    },
+    /// At least one body is not text.
+    Binary {
+        before: u64,
+        after: u64,
+    },
+}
+
+/// A header name or JSONPath and what happened to its value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct KeyedChange {
+    pub(crate) key: String,
+    pub(crate) change: Change,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Change {
+    Added(String),
+    Removed(String),
+    Changed { before: String, after: String },
+}
+
+/// One row of a side-by-side line diff.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum DiffRow {
+    Same {
+        before: usize,
+        after: usize,
+        text: String,
+    },
+    Changed {
+        before: Option<DiffLine>,
+        after: Option<DiffLine>,
+    },
+    /// A run of unchanged lines left out between changes.
+    Skipped(usize),
+}
+
+/// A line and its one-based number.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DiffLine {
+    pub(crate) number: usize,
+    pub(crate) text: String,
+}
+
+/// Reads both bodies, from memory or their spill files, and compares the
+/// responses off the async runtime.
+pub(crate) async fn diff_responses(
+    pinned: Arc<ResponseData>,
+    current: Arc<ResponseData>,
+) -> Result<ResponseDiff, ResponseReadProblem> {
+    let before = pinned
+        .read_lease()
+        .read_all_bounded(CAPTURE_LIMIT_BYTES)
+        .await?;
+    let after = current
+        .read_lease()
+        .read_all_bounded(CAPTURE_LIMIT_BYTES)
+        .await?;
+    tokio::task::spawn_blocking(move || {
+        let (pinned, current) = (pinned.head(), current.head());
+        ResponseDiff {
+            status: (pinned.status != current.status).then_some((pinned.status, current.status)),
+            headers: diff_headers(&pinned.headers, &current.headers),
+            body: diff_bodies(&before, &pinned.headers, &after, &current.headers),
+        }
+    })
+    .await
+    .map_err(|_| ResponseReadProblem::Read)
+}
+
+pub(crate) fn diff_headers(before: &HeaderMap, after: &HeaderMap) -> Vec<KeyedChange> {
+    let names: BTreeSet<&str> = before
+        .keys()
+        .chain(after.keys())
+        .map(|name| name.as_str())
+        .collect();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let change = match (joined_values(before, name), joined_values(after, name)) {
+                (Some(before), Some(after)) if before == after => return None,
+                (Some(before), Some(after)) => Change::Changed { before, after },
+                (Some(before), None) => Change::Removed(before),
+                (None, Some(after)) => Change::Added(after),
+                (None, None) => return None,
+            };
+            Some(KeyedChange {
+                key: name.to_owned(),
+                change,
+            })
+        })
+        .collect()
+}
+
+fn joined_values(headers: &HeaderMap, name: &str) -> Option<String> {
+    let values: Vec<String> = headers
+        .get_all(name)
+        .iter()
+        .map(escape_header_value)
+        .collect();
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+pub(crate) fn diff_bodies(
+    before: &[u8],
+    before_headers: &HeaderMap,
+    after: &[u8],
+    after_headers: &HeaderMap,
+) -> BodyDiff {
+    if before == after {
+        return BodyDiff::Identical;
+    }
+    let (Ok(before_text), Ok(after_text)) = (
+        decode_text(before, before_headers, true),
+        decode_text(after, after_headers, true),
+    ) else {
+        return BodyDiff::Binary {
+            before: before.len() as u64,
+            after: after.len() as u64,
+        };
+    };
+    if let (Some(before), Some(after)) = (json_document(&before_text), json_document(&after_text)) {
+        let mut changes = Vec::new();
+        let truncated = !diff_json(&before, &after, &mut "$".to_owned(), &mut changes);
+        return if changes.is_empty() {
+            BodyDiff::Identical
+        } else {
+            BodyDiff::Json { changes, truncated }
+        };
+    }
+    let (rows, truncated) = diff_lines(&before_text, &after_text);
+    if rows.is_empty() {
+        BodyDiff::Identical
+    } else {
+        BodyDiff::Lines { rows, truncated }
+    }
+}
+
+/// Only objects and arrays get a structural diff; a bare scalar reads
+/// better as a line.
+fn json_document(source: &str) -> Option<Value> {
+    serde_json::from_str::<Value>(source)
+        .ok()
+        .filter(|value| value.is_object() || value.is_array())
+}
+
+/// Records the changes below `path`, returning `false` once the cap is hit.
+fn diff_json(
+    before: &Value,
+    after: &Value,
+    path: &mut String,
+    changes: &mut Vec<KeyedChange>,
+) -> bool {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+            for name in names {
+                let len = path.len();
+                push_member(path, name);
+                let kept = match (before.get(name), after.get(name)) {
+                    (Some(before), Some(after)) => diff_json(before, after, path, changes),
+                    (Some(before), None) => record(changes, path, Change::Removed(compact(before))),
+                    (None, Some(after)) => record(changes, path, Change::Added(compact(after))),
+                    (None, None) => true,
+                };
+                path.truncate(len);
+                if !kept {
+                    return false;
+                }
+            }
+            true
+        }
+        (Value::Array(before), Value::Array(after)) => {
+            for index in 0..before.len().max(after.len()) {
+                let len = path.len();
+                path.push_str(&format!("[{index}]"));
+                let kept = match (before.get(index), after.get(index)) {
+                    (Some(before), Some(after)) => diff_json(before, after, path, changes),
+                    (Some(before), None) => record(changes, path, Change::Removed(compact(before))),
+                    (None, Some(after)) => record(changes, path, Change::Added(compact(after))),
+                    (None, None) => true,
+                };
+                path.truncate(len);
+                if !kept {
+                    return false;
+                }
+            }
+            true
+        }
+        (before, after) if before == after => true,
+        (before, after) => record(
+            changes,
+            path,
+            Change::Changed {
+                before: compact(before),
+                after: compact(after),
+            },
+        ),
+    }
+}
+
+fn record(changes: &mut Vec<KeyedChange>, path: &str, change: Change) -> bool {
+    if changes.len() == MAX_DIFF_ENTRIES {
+        return false;
+    }
+    changes.push(KeyedChange {
+        key: path.to_owned(),
+        change,
+    });
+    true
+}
+
+fn compact(value: &Value) -> String {
+    let text = value.to_string();
+    if text.chars().count() <= MAX_VALUE_CHARS {
+        return text;
+    }
+    let mut cut: String = text.chars().take(MAX_VALUE_CHARS).collect();
+    cut.push('…');
+    cut
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit {
+    Same(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// A side-by-side diff with unchanged runs folded down to their context.
+/// Returns no rows when the texts only differ in line endings.
+fn diff_lines(before: &str, after: &str) -> (Vec<DiffRow>, bool) {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+    let prefix = before
+        .iter()
+        .zip(&after)
+        .take_while(|(before, after)| before == after)
+        .count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(before, after)| before == after)
+        .count();
+    let middle = (
+        &before[prefix..before.len() - suffix],
+        &after[prefix..after.len() - suffix],
+    );
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|index| Edit::Same(index, index)).collect();
+    match edit_script(middle.0, middle.1) {
+        Some(script) => edits.extend(script.into_iter().map(|edit| match edit {
+            Edit::Same(before, after) => Edit::Same(before + prefix, after + prefix),
+            Edit::Delete(before) => Edit::Delete(before + prefix),
+            Edit::Insert(after) => Edit::Insert(after + prefix),
+        })),
+        None => {
+            edits.extend((prefix..before.len() - suffix).map(Edit::Delete));
+            edits.extend((prefix..after.len() - suffix).map(Edit::Insert));
+        }
+    }
+    edits.extend((0..suffix).map(|offset| {
+        Edit::Same(
+            before.len() - suffix + offset,
+            after.len() - suffix + offset,
+        )
+    }));
+    side_by_side(&edits, &before, &after)
+}
+
+/// Pairs each block of deletions with the insertions that follow it and
+/// folds unchanged lines far from any change.
+fn side_by_side(edits: &[Edit], before: &[&str], after: &[&str]) -> (Vec<DiffRow>, bool) {
+    // Each entry is (before line, after line, unchanged).
+    let mut steps: Vec<(Option<usize>, Option<usize>, bool)> = Vec::with_capacity(edits.len());
+    let mut index = 0;
+    while index < edits.len() {
+        if let Edit::Same(before, after) = edits[index] {
+            steps.push((Some(before), Some(after), true));
+            index += 1;
+            continue;
+        }
+        let mut deleted = Vec::new();
+        let mut inserted = Vec::new();
+        while let Some(edit) = edits.get(index) {
+            match *edit {
+                Edit::Delete(line) => deleted.push(line),
+                Edit::Insert(line) => inserted.push(line),
+                Edit::Same(..) => break,
+            }
+            index += 1;
+        }
+        for row in 0..deleted.len().max(inserted.len()) {
+            steps.push((deleted.get(row).copied(), inserted.get(row).copied(), false));
+        }
+    }
+
+    let mut keep = vec![false; steps.len()];
+    for (position, _) in steps.iter().enumerate().filter(|(_, step)| !step.2) {
+        let start = position.saturating_sub(CONTEXT_LINES);
+        let end = (position + CONTEXT_LINES + 1).min(steps.len());
+        keep[start..end].fill(true);
+    }
+
+    let line = |lines: &[&str], number: Option<usize>| {
+        number.map(|number| DiffLine {
+            number: number + 1,
+            text: lines[number].to_owned(),
+        })
+    };
+    let mut rows = Vec::new();
+    let mut skipped = 0;
+    for (step, kept) in steps.iter().zip(&keep) {
+        if !kept {
+            skipped += 1;
+            continue;
+        }
+        if rows.len() == MAX_DIFF_ENTRIES {
+            return (rows, true);
+        }
+        if skipped > 0 {
+            rows.push(DiffRow::Skipped(skipped));
+            skipped = 0;
+        }
+        rows.push(match *step {
+            (Some(before_line), Some(after_line), true) => DiffRow::Same {
+                before: before_line + 1,
+                after: after_line + 1,
+                text: before[before_line].to_owned(),
+            },
+            (before_line, after_line, _) => DiffRow::Changed {
+                before: line(before, before_line),
+                after: line(after, after_line),
+            },
+        });
+    }
+    if skipped > 0 && !rows.is_empty() {
+        rows.push(DiffRow::Skipped(skipped));
+    }
+    (rows, false)
+}
+
+/// Myers' shortest edit script, or `None` past [`MAX_EDIT_DISTANCE`].
+fn edit_script(before: &[&str], after: &[&str]) -> Option<Vec<Edit>> {
+    let (n, m) = (before.len() as isize, after.len() as isize);
+    let limit = (before.len() + after.len()).min(MAX_EDIT_DISTANCE) as isize;
+    let offset = limit + 1;
+    let mut furthest = vec![0_isize; 2 * offset as usize + 1];
+    let at = |diagonal: isize| (diagonal + offset) as usize;
+    // The furthest points reached on each diagonal after every round.
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let mut rounds = None;
+    'search: for d in 0..=limit {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && furthest[at(k - 1)] < furthest[at(k + 1)]) {
+                furthest[at(k + 1)]
+            } else {
+                furthest[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && before[x as usize] == after[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[at(k)] = x;
+            if x >= n && y >= m {
+                trace.push(furthest[at(-d)..=at(d)].to_vec());
+                rounds = Some(d);
+                break 'search;
+            }
+        }
+        trace.push(furthest[at(-d)..=at(d)].to_vec());
+    }
+    let rounds = rounds?;
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..=rounds).rev() {
+        let previous = &trace[d as usize - 1];
+        let reached = |k: isize| previous[(k + d - 1) as usize];
+        let k = x - y;
+        let down = k == -d || (k != d && reached(k - 1) < reached(k + 1));
+        let previous_k = if down { k + 1 } else { k - 1 };
+        let previous_x = reached(previous_k);
+        let previous_y = previous_x - previous_k;
+        let (start_x, start_y) = if down {
+            (previous_x, previous_y + 1)
+        } else {
+            (previous_x + 1, previous_y)
+        };
+        while x > start_x && y > start_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Same(x as usize, y as usize));
+        }
+        edits.push(if down {
+            Edit::Insert(previous_y as usize)
+        } else {
+            Edit::Delete(previous_x as usize)
+        });
+        (x, y) = (previous_x, previous_y);
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        edits.push(Edit::Same(x as usize, y as usize));
+    }
+    edits.reverse();
+    Some(edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    fn changed(before: &str, after: &str) -> Change {
+        Change::Changed {
+            before: before.to_owned(),
+            after: after.to_owned(),
+        }
+    }
+
+    #[test]
+    fn json_bodies_are_compared_by_structure_not_key_order() {
+        let json = headers(&[("content-type", "application/json")]);
+        let same = diff_bodies(
+            br#"{"a": 1, "b": [true, null]}"#,
+            &json,
+            br#"{"b":[true,null],"a":1}"#,
+            &json,
+        );
+        assert_eq!(same, BodyDiff::Identical);
+
+        let diff = diff_bodies(
+            br#"{"user": {"name": "ann", "tags": ["a", "b"]}, "gone": 1}"#,
+            &json,
+            br#"{"user": {"tags": ["a"], "name": "bob", "e-mail": "x"}}"#,
+            &json,
+        );
+        let BodyDiff::Json { changes, truncated } = diff else {
+            panic!("expected a JSON diff, got {diff:?}");
+        };
+        assert!(!truncated);
+        let changes: Vec<(&str, Change)> = changes
+            .iter()
+            .map(|change| (change.key.as_str(), change.change.clone()))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("$.gone", Change::Removed("1".to_owned())),
+                ("$.user['e-mail']", Change::Added("\"x\"".to_owned())),
+                ("$.user.name", changed("\"ann\"", "\"bob\"")),
+                ("$.user.tags[1]", Change::Removed("\"b\"".to_owned())),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_bodies_get_a_side_by_side_line_diff_with_folded_context() {
+        let text = headers(&[]);
+        let before: String = (1..=20).map(|line| format!("line {line}\n")).collect();
+        let after = before
+            .replace("line 5\n", "line five\n")
+            .replace("line 12\n", "")
+            .replace("line 20\n", "line 20\nline 21\n");
+        let BodyDiff::Lines { rows, truncated } =
+            diff_bodies(before.as_bytes(), &text, after.as_bytes(), &text)
+        else {
+            panic!("expected a line diff");
+        };
+        assert!(!truncated);
+        let line = |number: usize, text: &str| {
+            Some(DiffLine {
+                number,
+                text: text.to_owned(),
+            })
+        };
+        assert_eq!(rows[0], DiffRow::Skipped(1));
+        assert_eq!(
+            rows[4],
+            DiffRow::Changed {
+                before: line(5, "line 5"),
+                after: line(5, "line five"),
+            }
+        );
+        assert!(rows.contains(&DiffRow::Changed {
+            before: line(12, "line 12"),
+            after: None,
+        }));
+        assert_eq!(
+            rows.last(),
+            Some(&DiffRow::Changed {
+                before: None,
+                after: line(20, "line 21"),
+            })
+        );
+        assert_eq!(
+            rows.iter()
+                .filter(|row| matches!(row, DiffRow::Same { .. }))
+                .count(),
+            15
+        );
+        assert!(rows.contains(&DiffRow::Skipped(2)));
+
+        assert_eq!(
+            diff_bodies(b"a\r\nb", &text, b"a\nb", &text),
+            BodyDiff::Identical
+        );
+    }
+
+    #[test]
+    fn the_edit_script_is_minimal_and_falls_back_past_the_limit() {
+        let before = ["a", "b", "c", "a", "b", "b", "a"];
+        let after = ["c", "b", "a", "b", "a", "c"];
+        let script = edit_script(&before, &after).unwrap();
+        let edits = script
+            .iter()
+            .filter(|edit| !matches!(edit, Edit::Same(..)))
+            .count();
+        assert_eq!(edits, 5);
+        let rebuilt: Vec<&str> = script
+            .iter()
+            .filter_map(|edit| match edit {
+                Edit::Same(before_line, _) => Some(before[*before_line]),
+                Edit::Insert(after_line) => Some(after[*after_line]),
+                Edit::Delete(_) => None,
+            })
+            .collect();
+        assert_eq!(rebuilt, after);
+
+        let before: Vec<String> = (0..MAX_EDIT_DISTANCE)
+            .map(|line| line.to_string())
+            .collect();
+        let after: Vec<String> = (0..MAX_EDIT_DISTANCE)
+            .map(|line| format!("x{line}"))
+            .collect();
+        let before: Vec<&str> = before.iter().map(String::as_str).collect();
+        let after: Vec<&str> = after.iter().map(String::as_str).collect();
+        assert_eq!(edit_script(&before, &after), None);
+        let (rows, _) = diff_lines(&before.join("\n"), &after.join("\n"));
+        assert_eq!(rows.len(), MAX_EDIT_DISTANCE);
+        assert!(rows.iter().all(|row| matches!(
+            row,
+            DiffRow::Changed {
+                before: Some(_),
+                after: Some(_)
+            }
+        )));
+    }
+
+    #[test]
+    fn headers_and_binary_bodies_are_compared() {
+        let before = headers(&[
+            ("content-type", "application/octet-stream"),
+            ("set-cookie", "a=1"),
+            ("set-cookie", "b=2"),
+            ("x-old", "1"),
+        ]);
+        let after = headers(&[
+            ("content-type", "application/octet-stream"),
+            ("set-cookie", "a=1"),
+            ("x-new", "2"),
+        ]);
+        assert_eq!(
+            diff_headers(&before, &after),
+            [
+                KeyedChange {
+                    key: "set-cookie".to_owned(),
+                    change: changed("a=1, b=2", "a=1"),
+                },
+                KeyedChange {
+                    key: "x-new".to_owned(),
+                    change: Change::Added("2".to_owned()),
+                },
+                KeyedChange {
+                    key: "x-old".to_owned(),
+                    change: Change::Removed("1".to_owned()),
+                },
+            ]
+        );
+        assert_eq!(
+            diff_bodies(&[0xff, 0xfe], &before, &[0xff], &after),
+            BodyDiff::Binary {
+                before: 2,
+                after: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use std::{collections::VecDeque, time::Duration};
+
+use fluent_bundle::FluentArgs;
+use gpui::{
+    AnyElement, App, IntoElement, ParentElement as _, SharedString, Styled as _, Task, div, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Sizable as _,
+    label::Label,
+    scroll::ScrollableElement as _,
+    table::{Table, TableBody, TableCell, TableHead, TableHeader, TableRow},
+    v_flex,
+};
+use http::StatusCode;
+
+use super::{
+    ContentKind, ResponseHead, centered_status, classify_content_type,
+    sse::{SseEvent, SseUpdate},
+};
+use crate::{
+    features::request::runtime::{RequestProblemKind, RequestRuntime},
+    foundation::I18n,
+};
+
+const EVENT_LOG_CAPACITY: usize = 2_000;
+/// The reconnection time a client uses until the server sends `retry`.
+const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_millis(3_000);
+
+struct ScheduledReconnect {
+    delay: Duration,
+    _task: Task<()>,
+}
+
+/// Events of the current stream, kept across automatic reconnects so a
+/// resumed stream reads as one log.
+#[derive(Default)]
+pub(crate) struct EventStreamLog {
+    events: VecDeque<SseEvent>,
+    dropped: usize,
+    last_event_id: Option<String>,
+    retry: Option<u64>,
+    resumed: bool,
+    reconnect: Option<ScheduledReconnect>,
+}
+
+impl EventStreamLog {
+    pub(crate) fn apply(&mut self, update: SseUpdate) {
+        for event in update.events {
+            if self.events.len() == EVENT_LOG_CAPACITY {
+                self.events.pop_front();
+                self.dropped += 1;
+            }
+            self.events.push_back(event);
+        }
+        if update.last_event_id.is_some() {
+            self.last_event_id = update.last_event_id;
+        }
+        if update.retry.is_some() {
+            self.retry = update.retry;
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Marks the next attempt as the continuation of this stream.
+    pub(crate) fn resume(&mut self) {
+        self.reconnect = None;
+        self.resumed = true;
+    }
+
+    /// The id to send as `Last-Event-ID`. An empty id resets it.
+    pub(crate) fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref().filter(|id| !id.is_empty())
+    }
+
+    pub(crate) fn schedule_reconnect(&mut self, delay: Duration, task: Task<()>) {
+        self.reconnect = Some(ScheduledReconnect { delay, _task: task });
+    }
+
+    pub(crate) fn stop_reconnect(&mut self) {
+        self.reconnect = None;
+    }
+
+    pub(crate) const fn is_resumed(&self) -> bool {
+        self.resumed
+    }
+
+    pub(crate) fn is_reconnecting(&self) -> bool {
+        self.reconnect.is_some()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn events(&self) -> impl Iterator<Item = &SseEvent> {
+        self.events.iter()
+    }
+
+    /// How long to wait before reopening the stream the runtime just ended,
+    /// or `None` when it should stay closed.
+    ///
+    /// A stream that ended normally or dropped mid-body is reopened, as is a
+    /// continuation that could not reach the server. Anything the server
+    /// answered with another status or media type is final.
+    pub(crate) fn reconnect_after(&self, runtime: &RequestRuntime) -> Option<Duration> {
+        let reopen = match runtime {
+            RequestRuntime::Ready { response } => is_open_stream(response.head()),
+            RequestRuntime::Failed { attempt } => match &attempt.receipt {
+                Some(receipt) => {
+                    is_open_stream(&receipt.head)
+                        && attempt.problem.kind() == RequestProblemKind::ResponseBodyRead
+                }
+                None => self.resumed && attempt.problem.kind() == RequestProblemKind::Transport,
+            },
+            RequestRuntime::Idle
+            | RequestRuntime::Sending { .. }
+            | RequestRuntime::Receiving { .. } => false,
+        };
+        reopen.then(|| {
+            self.retry
+                .map_or(DEFAULT_RECONNECT_DELAY, Duration::from_millis)
+        })
+    }
+}
+
+fn is_open_stream(head: &ResponseHead) -> bool {
+    head.status == StatusCode::OK
+        && classify_content_type(&head.headers) == ContentKind::EventStream
+}
+
+pub(super) fn render_event_table(log: &EventStreamLog, cx: &App) -> AnyElement {
+    let i18n = cx.global::<I18n>();
+    let mut args = FluentArgs::new();
+    args.set("count", log.events.len() + log.dropped);
+    let mut summary = vec![i18n.t_with_args("response-events-count", &args)];
+    if let Some(id) = log.last_event_id() {
+        let mut args = FluentArgs::new();
+        args.set("id", id.to_owned());
+        summary.push(i18n.t_with_args("response-events-last-id", &args));
+    }
+    if let Some(reconnect) = &log.reconnect {
+        let mut args = FluentArgs::new();
+        args.set("delay", reconnect.delay.as_millis().to_string());
+        summary.push(i18n.t_with_args("response-events-reconnecting", &args));
+    }
+    if log.dropped > 0 {
+        let mut args = FluentArgs::new();
+        args.set("count", log.dropped);
+        summary.push(i18n.t_with_args("response-events-dropped", &args));
+    }
+    let summary = Label::new(summary.join(" · "))
+        .text_xs()
+        .text_color(cx.theme().muted_foreground);
+
+    if log.events.is_empty() {
+        return v_flex()
+            .flex_1()
+            .min_h(px(0.))
+            .gap_2()
+            .child(summary)
+            .child(centered_status(i18n.t("response-events-empty")))
+            .into_any_element();
+    }
+    let rows = log.events.iter().map(|event| {
+        let data = event.pretty_data().unwrap_or_else(|| event.data.clone());
+        TableRow::new()
+            .child(
+                TableCell::new()
+                    .w(px(120.))
+                    .child(Label::new(event.id.clone().unwrap_or_default())),
+            )
+            .child(
+                TableCell::new()
+                    .w(px(120.))
+                    .child(Label::new(event.event.clone())),
+            )
+            .child(
+                TableCell::new().child(
+                    Label::new(data)
+                        .text_xs()
+                        .font_family(cx.theme().mono_font_family.clone()),
+                ),
+            )
+            .child(
+                TableCell::new().w(px(80.)).child(Label::new(
+                    event
+                        .retry
+                        .map(|retry| SharedString::from(retry.to_string()))
+                        .unwrap_or_default(),
+                )),
+            )
+    });
+    v_flex()
+        .flex_1()
+        .min_h(px(0.))
+        .gap_2()
+        .child(summary)
+        .child(
+            div().flex_1().min_h(px(0.)).overflow_scrollbar().child(
+                Table::new()
+                    .small()
+                    .child(
+                        TableHeader::new().child(
+                            TableRow::new()
+                                .child(
+                                    TableHead::new()
+                                        .w(px(120.))
+                                        .child(Label::new(i18n.t("response-events-id"))),
+                                )
+                                .child(
+                                    TableHead::new()
+                                        .w(px(120.))
+                                        .child(Label::new(i18n.t("response-events-type"))),
+                                )
+                                .child(
+                                    TableHead::new()
+                                        .child(Label::new(i18n.t("response-events-data"))),
+                                )
+                                .child(
+                                    TableHead::new()
+                                        .w(px(80.))
+                                        .child(Label::new(i18n.t("response-events-retry"))),
+                                ),
+                        ),
+                    )
+                    .child(TableBody::new().children(rows)),
+            ),
+        )
+        .into_any_element()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use bytes::Bytes;
+    use http::{HeaderMap, HeaderValue, Version, header};
+    use url::Url;
+
+    use super::*;
+    use crate::features::request::{
+        response::{
+            BodyDecoding, CompletedBody, ResponseData, ResponseProgress, ResponseSizes,
+            ResponseTiming, StoredBody,
+        },
+        runtime::{FailedAttempt, RequestProblem, ResponseReceipt},
+    };
+
+    fn head(status: StatusCode, content_type: &'static str) -> ResponseHead {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        ResponseHead::new(
+            status,
+            Version::HTTP_11,
+            Url::parse("http://example.test/events").unwrap(),
+            headers,
+        )
+    }
+
+    fn ready(head: ResponseHead) -> RequestRuntime {
+        RequestRuntime::Ready {
+            response: Arc::new(ResponseData::new(
+                head,
+                ResponseTiming {
+                    head_after: Duration::ZERO,
+                    completed_after: Duration::ZERO,
+                },
+                CompletedBody {
+                    body: StoredBody::Memory(Bytes::new()),
+                    body_decoding: BodyDecoding::Identity,
+                    sizes: ResponseSizes {
+                        declared_encoded_bytes: None,
+                        received_encoded_bytes: 0,
+                        stored_body_bytes: 0,
+                    },
+                },
+            )),
+        }
+    }
+
+    fn failed(problem: RequestProblem, head: Option<ResponseHead>) -> RequestRuntime {
+        RequestRuntime::Failed {
+            attempt: FailedAttempt {
+                problem,
+                receipt: head.map(|head| ResponseReceipt {
+                    head,
+                    progress: ResponseProgress::initial(None),
+                    head_after: Duration::ZERO,
+                }),
+                failed_after: Duration::ZERO,
+            },
+        }
+    }
+
+    fn event(id: Option<&str>) -> SseEvent {
+        SseEvent {
+            id: id.map(str::to_owned),
+            event: "message".into(),
+            data: String::new(),
+            retry: None,
+        }
+    }
+
+    #[test]
+    fn updates_keep_the_last_id_and_retry_and_bound_the_log() {
+        let mut log = EventStreamLog::default();
+        log.apply(SseUpdate {
+            events: vec![event(Some("1"))],
+            last_event_id: Some("1".into()),
+            retry: Some(250),
+        });
+        log.apply(SseUpdate {
+            events: (0..EVENT_LOG_CAPACITY).map(|_| event(None)).collect(),
+            last_event_id: None,
+            retry: None,
+        });
+        assert_eq!(log.last_event_id(), Some("1"));
+        assert_eq!(log.events().count(), EVENT_LOG_CAPACITY);
+        assert_eq!(log.dropped, 1);
+        assert_eq!(
+            log.reconnect_after(&ready(head(StatusCode::OK, "text/event-stream"))),
+            Some(Duration::from_millis(250))
+        );
+
+        log.apply(SseUpdate {
+            events: Vec::new(),
+            last_event_id: Some(String::new()),
+            retry: None,
+        });
+        assert_eq!(log.last_event_id(), None);
+
+        log.clear();
+        assert_eq!(log.events().count(), 0);
+        assert_eq!(
+            log.reconnect_after(&ready(head(StatusCode::OK, "text/event-stream"))),
+            Some(DEFAULT_RECONNECT_DELAY)
+        );
+    }
+
+    #[test]
+    fn only_open_streams_and_unreachable_continuations_are_reopened() {
+        let mut log = EventStreamLog::default();
+        let stream = || head(StatusCode::OK, "text/event-stream");
+        let dropped = || RequestProblem::response_body_read(std::io::Error::other("reset"));
+        let unreachable = || RequestProblem::transport(std::io::Error::other("refused"));
+
+        assert!(log.reconnect_after(&ready(stream())).is_some());
+        assert!(
+            log.reconnect_after(&failed(dropped(), Some(stream())))
+                .is_some()
+        );
+        assert!(
+            log.reconnect_after(&ready(head(StatusCode::NO_CONTENT, "text/event-stream")))
+                .is_none()
+        );
+        assert!(
+            log.reconnect_after(&ready(head(StatusCode::OK, "application/json")))
+                .is_none()
+        );
+        assert!(
+            log.reconnect_after(&failed(RequestProblem::timeout(), Some(stream())))
+                .is_none()
+        );
+        assert!(log.reconnect_after(&failed(unreachable(), None)).is_none());
+        assert!(log.reconnect_after(&RequestRuntime::Idle).is_none());
+
+        log.resume();
+        assert!(log.reconnect_after(&failed(unreachable(), None)).is_some());
+    }
+}
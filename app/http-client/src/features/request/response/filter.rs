@@ -0,0 +1,523 @@
+use std::cmp::Ordering;
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::features::request::json_path::{JsonPath, JsonPathError};
+
+/// An expression typed into the response filter bar. Input starting with
+/// `$` is JSONPath; anything else is read as a jq-style program.
+///
+/// The jq subset covers paths (`.`, `.name`, `.["name"]`, `.[0]`, `.[]`),
+/// recursive descent (`..`), `keys`, `length` and
+/// `select(<path> [== != < <= > >= <literal>])`, joined with `|`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum JsonFilter {
+    Path(JsonPath),
+    Jq(Vec<Stage>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Stage {
+    Path(Vec<Step>),
+    Recurse,
+    Keys,
+    Length,
+    Select(Condition),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Step {
+    Field(String),
+    Index(i64),
+    Iterate,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Condition {
+    path: Vec<Step>,
+    comparison: Option<(Comparison, Value)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub(crate) enum JsonFilterError {
+    #[error(transparent)]
+    Path(#[from] JsonPathError),
+    #[error("filter is invalid at byte {position}")]
+    Jq { position: usize },
+}
+
+impl JsonFilterError {
+    pub(crate) const fn position(self) -> usize {
+        match self {
+            Self::Path(error) => error.position(),
+            Self::Jq { position } => position,
+        }
+    }
+}
+
+impl JsonFilter {
+    pub(crate) fn parse(raw: &str) -> Result<Self, JsonFilterError> {
+        let trimmed = raw.trim_start();
+        if trimmed.starts_with('$') {
+            return Ok(Self::Path(JsonPath::parse(raw)?));
+        }
+        let mut parser = Parser {
+            raw,
+            position: raw.len() - trimmed.len(),
+        };
+        let mut stages = vec![parser.stage()?];
+        while parser.eat('|') {
+            stages.push(parser.stage()?);
+        }
+        parser.skip_whitespace();
+        if !parser.is_done() {
+            return Err(parser.error());
+        }
+        Ok(Self::Jq(stages))
+    }
+
+    /// Every output of the filter in document order.
+    pub(crate) fn apply(&self, root: &Value) -> Vec<Value> {
+        match self {
+            Self::Path(path) => path.select(root).into_iter().cloned().collect(),
+            Self::Jq(stages) => {
+                let mut current = vec![root.clone()];
+                for stage in stages {
+                    current = current
+                        .iter()
+                        .flat_map(|value| stage.apply(value))
+                        .collect();
+                }
+                current
+            }
+        }
+    }
+}
+
+/// Pretty-prints filter outputs one after another, the way jq prints a
+/// stream of results.
+pub(crate) fn render_filtered(values: &[Value]) -> String {
+    values
+        .iter()
+        .filter_map(|value| serde_json::to_string_pretty(value).ok())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl Stage {
+    fn apply(&self, value: &Value) -> Vec<Value> {
+        match self {
+            Self::Path(steps) => follow(steps, value).into_iter().cloned().collect(),
+            Self::Recurse => {
+                let mut out = Vec::new();
+                descend(value, &mut out);
+                out.into_iter().cloned().collect()
+            }
+            Self::Keys => match value {
+                Value::Object(object) => {
+                    let mut keys: Vec<_> = object.keys().cloned().collect();
+                    keys.sort();
+                    vec![Value::Array(keys.into_iter().map(Value::String).collect())]
+                }
+                Value::Array(array) => {
+                    vec![Value::Array((0..array.len()).map(Value::from).collect())]
+                }
+                _ => Vec::new(),
+            },
+            Self::Length => match value {
+                Value::Null => vec![Value::from(0)],
+                Value::Array(array) => vec![Value::from(array.len())],
+                Value::Object(object) => vec![Value::from(object.len())],
+                Value::String(text) => vec![Value::from(text.chars().count())],
+                Value::Number(number) => number
+                    .as_f64()
+                    .and_then(|number| serde_json::Number::from_f64(number.abs()))
+                    .map(Value::Number)
+                    .into_iter()
+                    .collect(),
+                Value::Bool(_) => Vec::new(),
+            },
+            Self::Select(condition) => {
+                if condition.holds(value) {
+                    vec![value.clone()]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+}
+
+impl Condition {
+    fn holds(&self, value: &Value) -> bool {
+        let subject = follow(&self.path, value)
+            .into_iter()
+            .next()
+            .unwrap_or(&NULL);
+        match &self.comparison {
+            None => !matches!(subject, Value::Null | Value::Bool(false)),
+            Some((comparison, expected)) => comparison.holds(subject, expected),
+        }
+    }
+}
+
+impl Comparison {
+    fn holds(self, left: &Value, right: &Value) -> bool {
+        let ordering = match (left, right) {
+            (Value::Number(left), Value::Number(right)) => left
+                .as_f64()
+                .zip(right.as_f64())
+                .and_then(|(left, right)| left.partial_cmp(&right)),
+            (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+            (left, right) if left == right => Some(Ordering::Equal),
+            _ => None,
+        };
+        match self {
+            Self::Eq => ordering == Some(Ordering::Equal),
+            Self::Ne => ordering != Some(Ordering::Equal),
+            Self::Lt => ordering == Some(Ordering::Less),
+            Self::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Self::Gt => ordering == Some(Ordering::Greater),
+            Self::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+static NULL: Value = Value::Null;
+
+/// Walks `steps` from `value`. Like jq, a missing member or index yields
+/// `null`; stepping into a scalar yields nothing.
+fn follow<'a>(steps: &[Step], value: &'a Value) -> Vec<&'a Value> {
+    let mut current = vec![value];
+    for step in steps {
+        let mut next = Vec::new();
+        for value in current {
+            match (step, value) {
+                (Step::Field(_) | Step::Index(_), Value::Null) => next.push(&NULL),
+                (Step::Field(name), Value::Object(object)) => {
+                    next.push(object.get(name).unwrap_or(&NULL));
+                }
+                (Step::Index(index), Value::Array(array)) => {
+                    let index = if *index < 0 {
+                        array.len().checked_sub(index.unsigned_abs() as usize)
+                    } else {
+                        Some(*index as usize)
+                    };
+                    next.push(index.and_then(|index| array.get(index)).unwrap_or(&NULL));
+                }
+                (Step::Iterate, Value::Array(array)) => next.extend(array),
+                (Step::Iterate, Value::Object(object)) => next.extend(object.values()),
+                _ => {}
+            }
+        }
+        current = next;
+    }
+    current
+}
+
+fn descend<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+    out.push(value);
+    match value {
+        Value::Array(array) => array.iter().for_each(|child| descend(child, out)),
+        Value::Object(object) => object.values().for_each(|child| descend(child, out)),
+        _ => {}
+    }
+}
+
+struct Parser<'a> {
+    raw: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.raw[self.position..]
+    }
+
+    fn is_done(&self) -> bool {
+        self.position == self.raw.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        self.skip_whitespace();
+        let Some(after) = self.rest().strip_prefix(word) else {
+            return false;
+        };
+        if after.chars().next().is_some_and(is_identifier) {
+            return false;
+        }
+        self.position += word.len();
+        true
+    }
+
+    fn error(&self) -> JsonFilterError {
+        JsonFilterError::Jq {
+            position: self.position,
+        }
+    }
+
+    fn stage(&mut self) -> Result<Stage, JsonFilterError> {
+        self.skip_whitespace();
+        if self.rest().starts_with("..") {
+            self.position += 2;
+            return Ok(Stage::Recurse);
+        }
+        if self.eat_word("keys") {
+            return Ok(Stage::Keys);
+        }
+        if self.eat_word("length") {
+            return Ok(Stage::Length);
+        }
+        if self.eat_word("select") {
+            if !self.eat('(') {
+                return Err(self.error());
+            }
+            let condition = self.condition()?;
+            if !self.eat(')') {
+                return Err(self.error());
+            }
+            return Ok(Stage::Select(condition));
+        }
+        self.path().map(Stage::Path)
+    }
+
+    fn path(&mut self) -> Result<Vec<Step>, JsonFilterError> {
+        if !self.eat('.') {
+            return Err(self.error());
+        }
+        let mut steps = Vec::new();
+        match self.peek() {
+            Some('[') => steps.push(self.bracket()?),
+            Some(character) if is_identifier(character) => steps.push(self.field()),
+            _ => return Ok(steps),
+        }
+        loop {
+            match self.peek() {
+                Some('[') => steps.push(self.bracket()?),
+                Some('.') if !self.rest().starts_with("..") => {
+                    self.position += 1;
+                    match self.peek() {
+                        Some('[') => steps.push(self.bracket()?),
+                        Some(character) if is_identifier(character) => steps.push(self.field()),
+                        _ => return Err(self.error()),
+                    }
+                }
+                _ => return Ok(steps),
+            }
+        }
+    }
+
+    fn field(&mut self) -> Step {
+        let rest = self.rest();
+        let len = rest
+            .find(|character: char| !is_identifier(character))
+            .unwrap_or(rest.len());
+        self.position += len;
+        Step::Field(rest[..len].to_owned())
+    }
+
+    fn bracket(&mut self) -> Result<Step, JsonFilterError> {
+        self.position += 1;
+        self.skip_whitespace();
+        let step = match self.peek() {
+            Some(']') => Step::Iterate,
+            Some('"') => match self.literal()? {
+                Value::String(name) => Step::Field(name),
+                _ => return Err(self.error()),
+            },
+            Some(_) => {
+                let rest = self.rest();
+                let len = rest.find(']').ok_or_else(|| self.error())?;
+                let index = rest[..len].trim().parse().map_err(|_| self.error())?;
+                self.position += len;
+                Step::Index(index)
+            }
+            None => return Err(self.error()),
+        };
+        if !self.eat(']') {
+            return Err(self.error());
+        }
+        Ok(step)
+    }
+
+    fn condition(&mut self) -> Result<Condition, JsonFilterError> {
+        let path = self.path()?;
+        self.skip_whitespace();
+        let comparison = [
+            ("==", Comparison::Eq),
+            ("!=", Comparison::Ne),
+            ("<=", Comparison::Le),
+            (">=", Comparison::Ge),
+            ("<", Comparison::Lt),
+            (">", Comparison::Gt),
+        ]
+        .into_iter()
+        .find(|(operator, _)| self.rest().starts_with(operator));
+        let Some((operator, comparison)) = comparison else {
+            return Ok(Condition {
+                path,
+                comparison: None,
+            });
+        };
+        self.position += operator.len();
+        self.skip_whitespace();
+        let expected = self.literal()?;
+        Ok(Condition {
+            path,
+            comparison: Some((comparison, expected)),
+        })
+    }
+
+    /// A JSON scalar: a string, number, `true`, `false` or `null`.
+    fn literal(&mut self) -> Result<Value, JsonFilterError> {
+        let rest = self.rest();
+        let len = if rest.starts_with('"') {
+            let mut escaped = false;
+            rest.char_indices()
+                .skip(1)
+                .find(|(_, character)| {
+                    let closes = *character == '"' && !escaped;
+                    escaped = *character == '\\' && !escaped;
+                    closes
+                })
+                .map(|(index, _)| index + 1)
+                .ok_or_else(|| self.error())?
+        } else {
+            rest.find(|character: char| {
+                matches!(character, ')' | ']' | '|') || character.is_whitespace()
+            })
+            .unwrap_or(rest.len())
+        };
+        let value: Value = serde_json::from_str(&rest[..len]).map_err(|_| self.error())?;
+        if value.is_array() || value.is_object() {
+            return Err(self.error());
+        }
+        self.position += len;
+        Ok(value)
+    }
+}
+
+fn is_identifier(character: char) -> bool {
+    character == '_' || character.is_ascii_alphanumeric()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn document() -> Value {
+        json!({
+            "data": {
+                "items": [
+                    { "id": 1, "name": "first", "tags": ["a"] },
+                    { "id": 2, "name": "second", "tags": [] },
+                    { "id": 3, "name": "third" }
+                ],
+                "total": 3,
+                "a b": true
+            }
+        })
+    }
+
+    fn run(raw: &str) -> Vec<Value> {
+        JsonFilter::parse(raw).unwrap().apply(&document())
+    }
+
+    #[test]
+    fn jq_paths_iterate_and_pipe() {
+        assert_eq!(run("."), [document()]);
+        assert_eq!(run(".data.total"), [json!(3)]);
+        assert_eq!(run(".data[\"a b\"]"), [json!(true)]);
+        assert_eq!(run(".data.items[-1].id"), [json!(3)]);
+        assert_eq!(
+            run(".data.items[].name"),
+            [json!("first"), json!("second"), json!("third")]
+        );
+        assert_eq!(run(".data.items | length"), [json!(3)]);
+        assert_eq!(run(".data | keys"), [json!(["a b", "items", "total"])]);
+        assert_eq!(run(".data.missing"), [Value::Null]);
+        assert_eq!(run(".data.total.name"), Vec::<Value>::new());
+    }
+
+    #[test]
+    fn select_compares_against_literals() {
+        assert_eq!(
+            run(".data.items[] | select(.id >= 2) | .name"),
+            [json!("second"), json!("third")]
+        );
+        assert_eq!(
+            run(".data.items[] | select(.name == \"first\") | .id"),
+            [json!(1)]
+        );
+        assert_eq!(
+            run(".data.items[] | select(.tags) | .id"),
+            [json!(1), json!(2)]
+        );
+        assert_eq!(
+            run(".data.items[] | select(.id != 1.0) | .id"),
+            [json!(2), json!(3)]
+        );
+        assert_eq!(run(".. | select(.id == 3) | .name"), [json!("third")]);
+    }
+
+    #[test]
+    fn dollar_expressions_are_json_path_and_errors_carry_a_position() {
+        assert_eq!(run("$.data.items[*].id"), [json!(1), json!(2), json!(3)]);
+        assert_eq!(JsonFilter::parse("$.data[").unwrap_err().position(), 7);
+        assert_eq!(JsonFilter::parse("  $.[x").unwrap_err().position(), 4);
+        for (raw, position) in [
+            ("data", 0),
+            (".data |", 7),
+            (".data.", 6),
+            (".items[x]", 7),
+            ("select(.id == [1])", 14),
+            (".a .b", 3),
+        ] {
+            assert_eq!(
+                JsonFilter::parse(raw),
+                Err(JsonFilterError::Jq { position }),
+                "{raw:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn multiple_results_print_one_after_another() {
+        assert_eq!(
+            render_filtered(&[json!({ "a": 1 }), json!(2)]),
+            "{\n  \"a\": 1\n}\n2"
+        );
+    }
+}
@@ -0,0 +1,467 @@
+use std::{ops::Range, sync::Arc};
+
+use fluent_bundle::FluentArgs;
+use gpui::{
+    AnyElement, AppContext as _, ClipboardItem, Context, Entity, IntoElement, ParentElement as _,
+    Styled as _, Task, UniformListScrollHandle, Window, div, prelude::FluentBuilder as _, px,
+    uniform_list,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    h_flex,
+    input::{Input, InputState},
+    label::Label,
+};
+use lsp_types::Position;
+use serde_json::Value;
+
+use super::{
+    ViewerMode,
+    filter::{JsonFilter, JsonFilterError, render_filtered},
+    json_tree::JsonTree,
+    search::{SearchSyntax, TextSearch},
+    viewer::bounded_source,
+};
+use crate::{features::request::RequestView, foundation::I18n};
+
+const TREE_ROW_HEIGHT: f32 = 24.;
+const TREE_INDENT: f32 = 14.;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum JsonLayout {
+    #[default]
+    Source,
+    Tree,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum FilterStatus {
+    #[default]
+    Off,
+    Running,
+    Applied {
+        results: usize,
+    },
+    Invalid(JsonFilterError),
+}
+
+/// What a finished filter run hands back to the view.
+pub(super) struct FilterOutput {
+    generation: u64,
+    results: usize,
+    source: String,
+    root: Arc<Value>,
+}
+
+/// The filter bar, search bar and JSON tree that sit on top of a text
+/// projection. The queries outlive a single response so a filter keeps
+/// applying as the same request is sent again.
+pub(super) struct BodyInspector {
+    filter_input: Entity<InputState>,
+    search_input: Entity<InputState>,
+    layout: JsonLayout,
+    syntax: SearchSyntax,
+    document: Option<Arc<Value>>,
+    tree: Option<JsonTree>,
+    tree_scroll: UniformListScrollHandle,
+    filter: FilterStatus,
+    filter_generation: u64,
+    filter_task: Option<Task<()>>,
+    search: TextSearch,
+}
+
+impl BodyInspector {
+    pub(super) fn new(window: &mut Window, cx: &mut Context<RequestView>) -> Self {
+        let (filter_placeholder, search_placeholder) = {
+            let i18n = cx.global::<I18n>();
+            (
+                i18n.t("response-filter-placeholder"),
+                i18n.t("response-search-placeholder"),
+            )
+        };
+        Self {
+            filter_input: cx.new(|cx| InputState::new(window, cx).placeholder(filter_placeholder)),
+            search_input: cx.new(|cx| InputState::new(window, cx).placeholder(search_placeholder)),
+            layout: JsonLayout::default(),
+            syntax: SearchSyntax::default(),
+            document: None,
+            tree: None,
+            tree_scroll: UniformListScrollHandle::new(),
+            filter: FilterStatus::Off,
+            filter_generation: 0,
+            filter_task: None,
+            search: TextSearch::default(),
+        }
+    }
+
+    pub(super) fn filter_input(&self) -> &Entity<InputState> {
+        &self.filter_input
+    }
+
+    pub(super) fn search_input(&self) -> &Entity<InputState> {
+        &self.search_input
+    }
+
+    /// Forgets the previous body; the typed queries stay.
+    pub(super) fn clear(&mut self) {
+        self.document = None;
+        self.tree = None;
+        self.filter = FilterStatus::Off;
+        self.filter_task = None;
+        self.filter_generation += 1;
+        self.search.clear();
+    }
+
+    /// Takes over a freshly installed text projection, re-running the
+    /// current filter or search against it.
+    pub(super) fn install(
+        &mut self,
+        document: Option<Arc<Value>>,
+        effective_mode: Option<ViewerMode>,
+        editor: &Entity<InputState>,
+        window: &mut Window,
+        cx: &mut Context<RequestView>,
+    ) {
+        self.clear();
+        self.syntax = if effective_mode == Some(ViewerMode::Hex) {
+            SearchSyntax::Hex
+        } else {
+            SearchSyntax::Text
+        };
+        self.tree = document.clone().map(JsonTree::new);
+        self.document = document;
+        if self.document.is_some() && !self.filter_query(cx).is_empty() {
+            self.refresh_filter(None, editor, window, cx);
+        } else {
+            self.refresh_search(editor, window, cx);
+        }
+    }
+
+    fn filter_query(&self, cx: &Context<RequestView>) -> String {
+        self.filter_input.read(cx).value().trim().to_owned()
+    }
+
+    /// Applies the filter bar to the document in the background. An empty
+    /// filter puts `unfiltered` back in the editor.
+    pub(super) fn refresh_filter(
+        &mut self,
+        unfiltered: Option<&str>,
+        editor: &Entity<InputState>,
+        window: &mut Window,
+        cx: &mut Context<RequestView>,
+    ) {
+        let Some(document) = self.document.clone() else {
+            return;
+        };
+        self.filter_generation += 1;
+        self.filter_task = None;
+        let query = self.filter_query(cx);
+        if query.is_empty() {
+            let was_filtered = self.filter != FilterStatus::Off;
+            self.filter = FilterStatus::Off;
+            self.tree = Some(JsonTree::new(document));
+            if let Some(source) = unfiltered.filter(|_| was_filtered) {
+                let source = source.to_owned();
+                editor.update(cx, |state, cx| state.set_value(source, window, cx));
+            }
+            self.refresh_search(editor, window, cx);
+            return;
+        }
+        let filter = match JsonFilter::parse(&query) {
+            Ok(filter) => filter,
+            Err(error) => {
+                self.filter = FilterStatus::Invalid(error);
+                return;
+            }
+        };
+        self.filter = FilterStatus::Running;
+        let generation = self.filter_generation;
+        let work = cx.background_spawn(async move {
+            let mut values = filter.apply(&document);
+            let results = values.len();
+            let (source, _) = bounded_source(render_filtered(&values));
+            let root = if results == 1 {
+                values.remove(0)
+            } else {
+                Value::Array(values)
+            };
+            FilterOutput {
+                generation,
+                results,
+                source,
+                root: Arc::new(root),
+            }
+        });
+        let owner = cx.entity().downgrade();
+        self.filter_task = Some(window.spawn(cx, async move |cx| {
+            let output = work.await;
+            let _ = owner.update_in(cx, |this, window, cx| {
+                this.response_pane.finish_filter(output, window, cx);
+                cx.notify();
+            });
+        }));
+    }
+
+    pub(super) fn finish_filter(
+        &mut self,
+        output: FilterOutput,
+        editor: &Entity<InputState>,
+        window: &mut Window,
+        cx: &mut Context<RequestView>,
+    ) {
+        if output.generation != self.filter_generation {
+            return;
+        }
+        self.filter_task = None;
+        self.filter = FilterStatus::Applied {
+            results: output.results,
+        };
+        self.tree = Some(JsonTree::new(output.root));
+        let source = output.source;
+        editor.update(cx, |state, cx| state.set_value(source, window, cx));
+        self.refresh_search(editor, window, cx);
+    }
+
+    pub(super) fn refresh_search(
+        &mut self,
+        editor: &Entity<InputState>,
+        window: &mut Window,
+        cx: &mut Context<RequestView>,
+    ) {
+        let query = self.search_input.read(cx).value();
+        let source = editor.read(cx).value();
+        self.search.update(&source, &query, self.syntax);
+        self.reveal(self.search.current(), editor, window, cx);
+    }
+
+    pub(super) fn step_search(
+        &mut self,
+        forward: bool,
+        editor: &Entity<InputState>,
+        window: &mut Window,
+        cx: &mut Context<RequestView>,
+    ) {
+        let range = if forward {
+            self.search.next()
+        } else {
+            self.search.previous()
+        };
+        self.reveal(range, editor, window, cx);
+    }
+
+    fn reveal(
+        &self,
+        range: Option<Range<usize>>,
+        editor: &Entity<InputState>,
+        window: &mut Window,
+        cx: &mut Context<RequestView>,
+    ) {
+        let Some(range) = range else {
+            return;
+        };
+        editor.update(cx, |state, cx| {
+            let position = cursor_position(&state.value(), range.start);
+            state.set_cursor_position(position, window, cx);
+        });
+    }
+
+    fn toggle_tree_row(&mut self, row: usize) {
+        if let Some(tree) = &mut self.tree {
+            tree.toggle(row);
+        }
+    }
+
+    pub(super) fn shows_tree(&self) -> bool {
+        self.layout == JsonLayout::Tree && self.tree.is_some()
+    }
+
+    /// The filter bar and the source/tree switch, shown for JSON documents.
+    pub(super) fn render_filter_bar(&self, cx: &mut Context<RequestView>) -> Option<AnyElement> {
+        self.document.as_ref()?;
+        let i18n = cx.global::<I18n>();
+        let (status, status_color) = match self.filter {
+            FilterStatus::Off => (None, cx.theme().muted_foreground),
+            FilterStatus::Running => (
+                Some(i18n.t("response-filter-running")),
+                cx.theme().muted_foreground,
+            ),
+            FilterStatus::Applied { results } => {
+                let mut args = FluentArgs::new();
+                args.set("count", results);
+                (
+                    Some(i18n.t_with_args("response-filter-results", &args)),
+                    cx.theme().muted_foreground,
+                )
+            }
+            FilterStatus::Invalid(error) => {
+                let mut args = FluentArgs::new();
+                args.set("position", error.position() + 1);
+                (
+                    Some(i18n.t_with_args("response-filter-invalid", &args)),
+                    cx.theme().danger,
+                )
+            }
+        };
+        let layout_button = |id: &'static str, label: String, layout: JsonLayout| {
+            let button = Button::new(id).small().label(label).on_click(cx.listener(
+                move |this, _, _, cx| {
+                    this.response_pane.inspector.layout = layout;
+                    cx.notify();
+                },
+            ));
+            if self.layout == layout {
+                button.primary()
+            } else {
+                button
+            }
+        };
+        let source = layout_button(
+            "response-layout-source",
+            i18n.t("response-layout-source"),
+            JsonLayout::Source,
+        );
+        let tree = layout_button(
+            "response-layout-tree",
+            i18n.t("response-layout-tree"),
+            JsonLayout::Tree,
+        );
+        Some(
+            h_flex()
+                .gap_2()
+                .child(h_flex().gap_1().child(source).child(tree))
+                .child(div().flex_1().child(Input::new(&self.filter_input).small()))
+                .when_some(status, |this, status| {
+                    this.child(Label::new(status).text_xs().text_color(status_color))
+                })
+                .into_any_element(),
+        )
+    }
+
+    pub(super) fn render_search_bar(&self, cx: &mut Context<RequestView>) -> AnyElement {
+        let i18n = cx.global::<I18n>();
+        let has_query = !self.search_input.read(cx).value().is_empty();
+        let count = match self.search.position() {
+            Some(current) => {
+                let mut args = FluentArgs::new();
+                args.set("current", current);
+                args.set("total", self.search.len());
+                Some(i18n.t_with_args("response-search-count", &args))
+            }
+            None if has_query => Some(i18n.t("response-search-none")),
+            None => None,
+        };
+        let stepping = self.search.len() > 1;
+        h_flex()
+            .gap_2()
+            .child(
+                div()
+                    .w(px(240.))
+                    .child(Input::new(&self.search_input).small()),
+            )
+            .when_some(count, |this, count| {
+                this.child(
+                    Label::new(count)
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground),
+                )
+            })
+            .child(
+                Button::new("response-search-previous")
+                    .small()
+                    .label(i18n.t("button-previous-match"))
+                    .disabled(!stepping)
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.response_pane.step_search(false, window, cx);
+                        cx.notify();
+                    })),
+            )
+            .child(
+                Button::new("response-search-next")
+                    .small()
+                    .label(i18n.t("button-next-match"))
+                    .disabled(!stepping)
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.response_pane.step_search(true, window, cx);
+                        cx.notify();
+                    })),
+            )
+            .into_any_element()
+    }
+
+    pub(super) fn render_tree(&self, cx: &mut Context<RequestView>) -> AnyElement {
+        let rows = self.tree.as_ref().map_or(0, |tree| tree.rows().len());
+        div()
+            .flex_1()
+            .min_h(px(0.))
+            .bg(cx.theme().input_background())
+            .font_family(cx.theme().mono_font_family.clone())
+            .text_sm()
+            .child(
+                uniform_list(
+                    "response-json-tree",
+                    rows,
+                    cx.processor(|this, range: Range<usize>, _, cx| {
+                        let Some(tree) = &this.response_pane.inspector.tree else {
+                            return Vec::new();
+                        };
+                        let copy_label = cx.global::<I18n>().t("button-copy-path");
+                        range
+                            .filter_map(|index| tree.rows().get(index).map(|row| (index, row)))
+                            .map(|(index, row)| {
+                                let toggle = row.children.map(|_| {
+                                    Button::new(("response-json-tree-toggle", index))
+                                        .ghost()
+                                        .xsmall()
+                                        .label(if row.expanded { "▾" } else { "▸" })
+                                        .on_click(cx.listener(move |this, _, _, cx| {
+                                            this.response_pane.inspector.toggle_tree_row(index);
+                                            cx.notify();
+                                        }))
+                                });
+                                let path = row.path.clone();
+                                h_flex()
+                                    .h(px(TREE_ROW_HEIGHT))
+                                    .pl(px(row.depth as f32 * TREE_INDENT))
+                                    .pr_2()
+                                    .gap_1()
+                                    .child(div().w(px(20.)).children(toggle))
+                                    .when_some(row.key.clone(), |this, key| {
+                                        this.child(
+                                            Label::new(format!("{key}:"))
+                                                .text_color(cx.theme().muted_foreground),
+                                        )
+                                    })
+                                    .child(Label::new(row.preview.clone()).flex_1().truncate())
+                                    .child(
+                                        Button::new(("response-json-tree-copy", index))
+                                            .ghost()
+                                            .xsmall()
+                                            .label(copy_label.clone())
+                                            .on_click(move |_, _, cx| {
+                                                cx.write_to_clipboard(ClipboardItem::new_string(
+                                                    path.clone(),
+                                                ));
+                                            }),
+                                    )
+                            })
+                            .collect()
+                    }),
+                )
+                .track_scroll(self.tree_scroll.clone())
+                .size_full(),
+            )
+            .into_any_element()
+    }
+}
+
+/// The editor's cursor position for a byte offset, with columns counted in
+/// UTF-16 units as LSP positions are.
+fn cursor_position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].encode_utf16().count() as u32,
+    }
+}
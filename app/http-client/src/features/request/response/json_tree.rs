@@ -0,0 +1,206 @@
+use std::{collections::HashSet, sync::Arc};
+
+use serde_json::Value;
+
+use crate::features::request::json_path::push_member;
+
+/// Strings longer than this are cut short in a row's preview.
+const MAX_PREVIEW_CHARS: usize = 120;
+
+/// A collapsible view of a JSON document. Only expanded containers
+/// contribute rows, so a large document costs as much as what is open.
+pub(crate) struct JsonTree {
+    root: Arc<Value>,
+    expanded: HashSet<String>,
+    rows: Vec<JsonTreeRow>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct JsonTreeRow {
+    pub(crate) depth: usize,
+    /// The member name or array index leading to this node; the root has
+    /// none.
+    pub(crate) key: Option<String>,
+    pub(crate) preview: String,
+    /// Child count for objects and arrays, `None` for scalars.
+    pub(crate) children: Option<usize>,
+    pub(crate) expanded: bool,
+    /// A JSONPath that selects exactly this node.
+    pub(crate) path: String,
+}
+
+impl JsonTree {
+    /// Opens the root so the first level is visible.
+    pub(crate) fn new(root: Arc<Value>) -> Self {
+        let mut tree = Self {
+            root,
+            expanded: HashSet::from(["$".to_owned()]),
+            rows: Vec::new(),
+        };
+        tree.rebuild();
+        tree
+    }
+
+    pub(crate) fn rows(&self) -> &[JsonTreeRow] {
+        &self.rows
+    }
+
+    pub(crate) fn toggle(&mut self, row: usize) {
+        let Some(row) = self.rows.get(row).filter(|row| row.children.is_some()) else {
+            return;
+        };
+        let path = row.path.clone();
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+        self.rebuild();
+    }
+
+    fn rebuild(&mut self) {
+        let mut rows = Vec::new();
+        visit(
+            &self.root,
+            0,
+            None,
+            "$".to_owned(),
+            &self.expanded,
+            &mut rows,
+        );
+        self.rows = rows;
+    }
+}
+
+fn visit(
+    value: &Value,
+    depth: usize,
+    key: Option<String>,
+    path: String,
+    expanded: &HashSet<String>,
+    rows: &mut Vec<JsonTreeRow>,
+) {
+    let children = match value {
+        Value::Array(array) => Some(array.len()),
+        Value::Object(object) => Some(object.len()),
+        _ => None,
+    };
+    let is_expanded = children.is_some() && expanded.contains(&path);
+    rows.push(JsonTreeRow {
+        depth,
+        key,
+        preview: preview(value),
+        children,
+        expanded: is_expanded,
+        path: path.clone(),
+    });
+    if !is_expanded {
+        return;
+    }
+    match value {
+        Value::Array(array) => {
+            for (index, child) in array.iter().enumerate() {
+                let child_path = format!("{path}[{index}]");
+                visit(
+                    child,
+                    depth + 1,
+                    Some(index.to_string()),
+                    child_path,
+                    expanded,
+                    rows,
+                );
+            }
+        }
+        Value::Object(object) => {
+            for (name, child) in object {
+                let mut child_path = path.clone();
+                push_member(&mut child_path, name);
+                visit(
+                    child,
+                    depth + 1,
+                    Some(name.clone()),
+                    child_path,
+                    expanded,
+                    rows,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+fn preview(value: &Value) -> String {
+    match value {
+        Value::Array(array) => format!("[{}]", array.len()),
+        Value::Object(object) => format!("{{{}}}", object.len()),
+        Value::String(text) if text.chars().count() > MAX_PREVIEW_CHARS => {
+            let cut: String = text.chars().take(MAX_PREVIEW_CHARS).collect();
+            let quoted = Value::String(cut).to_string();
+            format!("{}…", quoted.strip_suffix('"').unwrap_or(&quoted))
+        }
+        scalar => scalar.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::features::request::json_path::JsonPath;
+
+    #[test]
+    fn only_expanded_containers_contribute_rows() {
+        let document = json!({ "items": [{ "id": 1 }, { "id": 2 }], "ok": true });
+        let mut tree = JsonTree::new(Arc::new(document));
+        let summary = |tree: &JsonTree| {
+            tree.rows()
+                .iter()
+                .map(|row| (row.depth, row.key.clone(), row.preview.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            summary(&tree),
+            [
+                (0, None, "{2}".to_owned()),
+                (1, Some("items".to_owned()), "[2]".to_owned()),
+                (1, Some("ok".to_owned()), "true".to_owned()),
+            ]
+        );
+
+        tree.toggle(1);
+        tree.toggle(3);
+        assert_eq!(tree.rows().len(), 6);
+        assert_eq!(tree.rows()[3].path, "$.items[1]");
+        assert_eq!(tree.rows()[3].preview, "{1}");
+        assert_eq!(tree.rows()[4].key.as_deref(), Some("id"));
+        assert_eq!(tree.rows()[4].preview, "2");
+
+        tree.toggle(1);
+        assert_eq!(tree.rows().len(), 3);
+        tree.toggle(2);
+        assert_eq!(tree.rows().len(), 3, "scalars do not expand");
+    }
+
+    #[test]
+    fn copied_paths_select_their_node() {
+        let document = json!({ "a b": { "it's": [null, "x"] }, "plain": 1 });
+        let mut tree = JsonTree::new(Arc::new(document.clone()));
+        tree.toggle(1);
+        tree.toggle(2);
+        let row = &tree.rows()[4];
+        assert_eq!(row.path, "$['a b']['it\\'s'][1]");
+        assert_eq!(
+            JsonPath::parse(&row.path).unwrap().first(&document),
+            Some(&json!("x"))
+        );
+        assert_eq!(tree.rows()[5].path, "$.plain");
+    }
+
+    #[test]
+    fn long_strings_are_previewed_with_an_ellipsis() {
+        let tree = JsonTree::new(Arc::new(json!("x".repeat(500))));
+        let preview = &tree.rows()[0].preview;
+        assert!(preview.starts_with("\"xxx"));
+        assert!(preview.ends_with("x…"));
+        assert_eq!(preview.chars().count(), MAX_PREVIEW_CHARS + 2);
+    }
+}
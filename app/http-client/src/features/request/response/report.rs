@@ -0,0 +1,161 @@
+use fluent_bundle::FluentArgs;
+use gpui::{
+    AnyElement, App, IntoElement, ParentElement as _, SharedString, Styled as _, div,
+    prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Sizable as _,
+    label::Label,
+    scroll::ScrollableElement as _,
+    table::{Table, TableBody, TableCell, TableHead, TableHeader, TableRow},
+    v_flex,
+};
+
+use super::centered_status;
+use crate::{
+    features::request::checks::{CheckFailure, CheckReport, assertion_kind_key},
+    foundation::I18n,
+};
+
+/// Where the response checks of the current send stand.
+#[derive(Default)]
+pub(super) enum ChecksStatus {
+    #[default]
+    NotConfigured,
+    Pending,
+    Finished(CheckReport),
+}
+
+pub(super) fn render_checks(status: &ChecksStatus, cx: &App) -> AnyElement {
+    let report = match status {
+        ChecksStatus::NotConfigured => {
+            return centered_status(cx.global::<I18n>().t("response-tests-empty"));
+        }
+        ChecksStatus::Pending => {
+            return centered_status(cx.global::<I18n>().t("response-tests-running"));
+        }
+        ChecksStatus::Finished(report) => report,
+    };
+    let i18n = cx.global::<I18n>();
+    let mut args = FluentArgs::new();
+    args.set("passed", report.passed());
+    args.set("total", report.assertions.len());
+    let summary = i18n.t_with_args("response-tests-summary", &args);
+
+    let assertions = report.assertions.iter().map(|outcome| {
+        let label = &outcome.label;
+        let mut description = i18n.t(assertion_kind_key(label.kind));
+        if label.kind.uses_target() {
+            description.push(' ');
+            description.push_str(&label.target);
+        }
+        if label.kind.uses_expected() {
+            description.push(' ');
+            description.push_str(&label.expected);
+        }
+        let detail = outcome
+            .result
+            .as_ref()
+            .err()
+            .map(|failure| failure_message(failure, cx))
+            .unwrap_or_default();
+        TableRow::new()
+            .child(
+                TableCell::new()
+                    .w(px(80.))
+                    .child(result_label(outcome.result.is_ok(), cx)),
+            )
+            .child(TableCell::new().w(px(320.)).child(Label::new(description)))
+            .child(TableCell::new().child(Label::new(detail)))
+    });
+    let extractions = report.extractions.iter().map(|outcome| {
+        let value = match &outcome.result {
+            Ok(value) => SharedString::from(value.clone()),
+            Err(failure) => failure_message(failure, cx),
+        };
+        TableRow::new()
+            .child(
+                TableCell::new()
+                    .w(px(80.))
+                    .child(result_label(outcome.result.is_ok(), cx)),
+            )
+            .child(
+                TableCell::new()
+                    .w(px(320.))
+                    .child(Label::new(outcome.variable.clone())),
+            )
+            .child(TableCell::new().child(Label::new(value)))
+    });
+
+    let header = |first: &'static str, second: &'static str, third: &'static str| {
+        TableHeader::new().child(
+            TableRow::new()
+                .child(TableHead::new().w(px(80.)).child(Label::new(i18n.t(first))))
+                .child(
+                    TableHead::new()
+                        .w(px(320.))
+                        .child(Label::new(i18n.t(second))),
+                )
+                .child(TableHead::new().child(Label::new(i18n.t(third)))),
+        )
+    };
+
+    div()
+        .flex_1()
+        .min_h(px(0.))
+        .overflow_scrollbar()
+        .child(
+            v_flex()
+                .p_2()
+                .gap_2()
+                .child(Label::new(summary))
+                .when(!report.assertions.is_empty(), |this| {
+                    this.child(
+                        Table::new()
+                            .small()
+                            .child(header(
+                                "response-tests-result",
+                                "response-tests-assertion",
+                                "response-tests-detail",
+                            ))
+                            .child(TableBody::new().children(assertions)),
+                    )
+                })
+                .when(!report.extractions.is_empty(), |this| {
+                    this.child(
+                        Table::new()
+                            .small()
+                            .child(header(
+                                "response-tests-result",
+                                "response-tests-variable",
+                                "response-tests-value",
+                            ))
+                            .child(TableBody::new().children(extractions)),
+                    )
+                }),
+        )
+        .into_any_element()
+}
+
+fn result_label(passed: bool, cx: &App) -> impl IntoElement {
+    let (key, color) = if passed {
+        ("response-tests-pass", cx.theme().success)
+    } else {
+        ("response-tests-fail", cx.theme().danger)
+    };
+    Label::new(cx.global::<I18n>().t(key)).text_color(color)
+}
+
+fn failure_message(failure: &CheckFailure, cx: &App) -> SharedString {
+    let i18n = cx.global::<I18n>();
+    match failure {
+        CheckFailure::Mismatch { actual } => {
+            let mut args = FluentArgs::new();
+            args.set("actual", actual.clone());
+            i18n.t_with_args("response-tests-actual", &args).into()
+        }
+        CheckFailure::Missing => i18n.t("response-tests-missing").into(),
+        CheckFailure::BodyUnavailable => i18n.t("response-tests-body-unavailable").into(),
+        CheckFailure::BodyNotJson => i18n.t("response-tests-body-not-json").into(),
+    }
+}
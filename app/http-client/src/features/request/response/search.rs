@@ -0,0 +1,166 @@
+use std::ops::Range;
+
+/// Stops counting once this many matches are found so a one-letter query
+/// over a large body stays responsive.
+pub(crate) const MAX_SEARCH_MATCHES: usize = 10_000;
+
+/// How a query is matched against the text shown in the viewer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum SearchSyntax {
+    /// ASCII case-insensitive text.
+    #[default]
+    Text,
+    /// The hex dump layout: the query is a run of hex byte pairs, or else
+    /// text whose UTF-8 bytes are looked up.
+    Hex,
+}
+
+/// Full-text search over the viewer source with a current match that
+/// wraps around in both directions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TextSearch {
+    matches: Vec<Range<usize>>,
+    current: Option<usize>,
+}
+
+impl TextSearch {
+    /// Finds every match of `query` in `source` and selects the first.
+    pub(crate) fn update(&mut self, source: &str, query: &str, syntax: SearchSyntax) {
+        self.matches = match syntax {
+            SearchSyntax::Text => find_text(source, query),
+            SearchSyntax::Hex => find_hex(source, query),
+        };
+        self.current = (!self.matches.is_empty()).then_some(0);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.matches.clear();
+        self.current = None;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// One-based position of the current match, for display.
+    pub(crate) fn position(&self) -> Option<usize> {
+        self.current.map(|index| index + 1)
+    }
+
+    pub(crate) fn current(&self) -> Option<Range<usize>> {
+        self.current.map(|index| self.matches[index].clone())
+    }
+
+    pub(crate) fn next(&mut self) -> Option<Range<usize>> {
+        let len = self.matches.len();
+        self.current = self.current.map(|index| (index + 1) % len);
+        self.current()
+    }
+
+    pub(crate) fn previous(&mut self) -> Option<Range<usize>> {
+        let len = self.matches.len();
+        self.current = self.current.map(|index| (index + len - 1) % len);
+        self.current()
+    }
+}
+
+fn find_text(source: &str, query: &str) -> Vec<Range<usize>> {
+    let needle = query.as_bytes();
+    let haystack = source.as_bytes();
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return Vec::new();
+    }
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start + needle.len() <= haystack.len() && matches.len() < MAX_SEARCH_MATCHES {
+        if haystack[start..start + needle.len()].eq_ignore_ascii_case(needle) {
+            matches.push(start..start + needle.len());
+            start += needle.len();
+        } else {
+            start += 1;
+        }
+    }
+    matches
+}
+
+/// Every byte of a hex dump takes two digits plus one separator, so byte
+/// `n` starts at offset `3 * n` whichever separator follows it.
+fn find_hex(source: &str, query: &str) -> Vec<Range<usize>> {
+    let needle = parse_hex_query(query).unwrap_or_else(|| query.as_bytes().to_vec());
+    let bytes: Vec<u8> = source
+        .split_ascii_whitespace()
+        .map_while(|pair| u8::from_str_radix(pair, 16).ok())
+        .collect();
+    if needle.is_empty() || needle.len() > bytes.len() {
+        return Vec::new();
+    }
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start + needle.len() <= bytes.len() && matches.len() < MAX_SEARCH_MATCHES {
+        if bytes[start..start + needle.len()] == needle[..] {
+            matches.push(start * 3..(start + needle.len()) * 3 - 1);
+            start += needle.len();
+        } else {
+            start += 1;
+        }
+    }
+    matches
+}
+
+/// Reads `"0a ff"` or `"0aff"` as bytes; anything else is not a hex query.
+fn parse_hex_query(query: &str) -> Option<Vec<u8>> {
+    let digits: String = query.split_ascii_whitespace().collect();
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(digits.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_matches_ignore_ascii_case_and_navigation_wraps() {
+        let mut search = TextSearch::default();
+        search.update("Token token TOKEN", "token", SearchSyntax::Text);
+        assert_eq!(search.len(), 3);
+        assert_eq!(search.current(), Some(0..5));
+        assert_eq!(search.previous(), Some(12..17));
+        assert_eq!(search.next(), Some(0..5));
+        assert_eq!(search.next(), Some(6..11));
+        assert_eq!(search.position(), Some(2));
+
+        search.update("aaaa", "aa", SearchSyntax::Text);
+        assert_eq!(search.len(), 2, "matches do not overlap");
+        search.update("abc", "", SearchSyntax::Text);
+        assert_eq!((search.len(), search.next()), (0, None));
+    }
+
+    #[test]
+    fn hex_queries_match_bytes_across_line_breaks() {
+        let dump = "00 01 02 03 04 05 06 07 08 09 0A 0B 0C 0D 0E 0F\n10 48 69";
+        let mut search = TextSearch::default();
+        search.update(dump, "0f 10", SearchSyntax::Hex);
+        assert_eq!(search.current(), Some(45..50));
+        assert_eq!(&dump[45..50], "0F\n10");
+
+        search.update(dump, "Hi", SearchSyntax::Hex);
+        assert_eq!(search.current(), Some(51..56));
+        search.update(dump, "0B0c", SearchSyntax::Hex);
+        assert_eq!(search.current(), Some(33..38));
+        search.update(dump, "zz", SearchSyntax::Hex);
+        assert_eq!(search.len(), 0);
+    }
+
+    #[test]
+    fn match_counts_are_capped() {
+        let source = "a".repeat(MAX_SEARCH_MATCHES + 10);
+        let mut search = TextSearch::default();
+        search.update(&source, "A", SearchSyntax::Text);
+        assert_eq!(search.len(), MAX_SEARCH_MATCHES);
+    }
+}
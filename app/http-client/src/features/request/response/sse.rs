@@ -0,0 +1,258 @@
+//! Incremental `text/event-stream` parsing as specified by the HTML
+//! "server-sent events" interpretation rules.
+
+use std::mem;
+
+const DEFAULT_EVENT_TYPE: &str = "message";
+const BYTE_ORDER_MARK: &[u8] = b"\xef\xbb\xbf";
+
+/// One dispatched event.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct SseEvent {
+    /// The `id` field of this event's own block, if it had one. Blocks
+    /// without an id keep the stream's previous last event id.
+    pub(crate) id: Option<String>,
+    pub(crate) event: String,
+    pub(crate) data: String,
+    /// The `retry` field of this event's own block, in milliseconds.
+    pub(crate) retry: Option<u64>,
+}
+
+impl SseEvent {
+    /// `data` re-indented when it is a JSON document.
+    pub(crate) fn pretty_data(&self) -> Option<String> {
+        let value = serde_json::from_str::<serde_json::Value>(&self.data).ok()?;
+        serde_json::to_string_pretty(&value).ok()
+    }
+}
+
+/// What one chunk changed: new events and the stream state a reconnect
+/// needs.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct SseUpdate {
+    pub(crate) events: Vec<SseEvent>,
+    pub(crate) last_event_id: Option<String>,
+    pub(crate) retry: Option<u64>,
+}
+
+#[derive(Default)]
+pub(crate) struct SseParser {
+    line: Vec<u8>,
+    /// The previous chunk ended with CR, so a leading LF belongs to it.
+    after_cr: bool,
+    bom_checked: bool,
+    data: String,
+    has_data: bool,
+    event: String,
+    block_id: Option<String>,
+    block_retry: Option<u64>,
+    last_event_id: Option<String>,
+    retry: Option<u64>,
+}
+
+impl SseParser {
+    /// Feeds decoded body bytes. A line split across chunks is kept until
+    /// its terminator arrives, so chunk boundaries never change the result.
+    pub(crate) fn feed(&mut self, mut bytes: &[u8]) -> SseUpdate {
+        let previous_id = self.last_event_id.clone();
+        let previous_retry = self.retry;
+        let mut events = Vec::new();
+
+        if !self.bom_checked {
+            let needed = BYTE_ORDER_MARK.len().saturating_sub(self.line.len());
+            let available = needed.min(bytes.len());
+            self.line.extend_from_slice(&bytes[..available]);
+            bytes = &bytes[available..];
+            if self.line.len() < BYTE_ORDER_MARK.len() && BYTE_ORDER_MARK.starts_with(&self.line) {
+                return SseUpdate::default();
+            }
+            self.bom_checked = true;
+            let pending = mem::take(&mut self.line);
+            let pending = pending.strip_prefix(BYTE_ORDER_MARK).unwrap_or(&pending);
+            self.scan(pending, &mut events);
+        }
+        self.scan(bytes, &mut events);
+
+        SseUpdate {
+            events,
+            last_event_id: (self.last_event_id != previous_id)
+                .then(|| self.last_event_id.clone())
+                .flatten(),
+            retry: (self.retry != previous_retry)
+                .then_some(self.retry)
+                .flatten(),
+        }
+    }
+
+    pub(crate) fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    pub(crate) const fn retry(&self) -> Option<u64> {
+        self.retry
+    }
+
+    fn scan(&mut self, bytes: &[u8], events: &mut Vec<SseEvent>) {
+        for &byte in bytes {
+            let after_cr = mem::replace(&mut self.after_cr, byte == b'\r');
+            match byte {
+                b'\n' if after_cr => {}
+                b'\n' | b'\r' => {
+                    let line = mem::take(&mut self.line);
+                    self.process_line(&line, events);
+                }
+                _ => self.line.push(byte),
+            }
+        }
+    }
+
+    fn process_line(&mut self, line: &[u8], events: &mut Vec<SseEvent>) {
+        if line.is_empty() {
+            self.dispatch(events);
+            return;
+        }
+        if line.starts_with(b":") {
+            return;
+        }
+        let line = String::from_utf8_lossy(line);
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line.as_ref(), ""),
+        };
+        match field {
+            "event" => value.clone_into(&mut self.event),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+                self.has_data = true;
+            }
+            "id" if !value.contains('\0') => {
+                self.block_id = Some(value.to_owned());
+                self.last_event_id = Some(value.to_owned());
+            }
+            "retry" if !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit()) => {
+                if let Ok(retry) = value.parse() {
+                    self.block_retry = Some(retry);
+                    self.retry = Some(retry);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn dispatch(&mut self, events: &mut Vec<SseEvent>) {
+        let id = self.block_id.take();
+        let retry = self.block_retry.take();
+        let event = mem::take(&mut self.event);
+        if !mem::take(&mut self.has_data) {
+            self.data.clear();
+            return;
+        }
+        let mut data = mem::take(&mut self.data);
+        data.pop();
+        events.push(SseEvent {
+            id,
+            event: if event.is_empty() {
+                DEFAULT_EVENT_TYPE.to_owned()
+            } else {
+                event
+            },
+            data,
+            retry,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: Option<&str>, event: &str, data: &str) -> SseEvent {
+        SseEvent {
+            id: id.map(str::to_owned),
+            event: event.to_owned(),
+            data: data.to_owned(),
+            retry: None,
+        }
+    }
+
+    fn parse(chunks: &[&[u8]]) -> (Vec<SseEvent>, SseParser) {
+        let mut parser = SseParser::default();
+        let events = chunks
+            .iter()
+            .flat_map(|chunk| parser.feed(chunk).events)
+            .collect();
+        (events, parser)
+    }
+
+    #[test]
+    fn fields_comments_and_multi_line_data_follow_the_interpretation_rules() {
+        let (events, parser) = parse(&[b"\xef\xbb\xbf: keep-alive\n\
+            data: first\n\
+            data:second\n\
+            \n\
+            event: delta\n\
+            id: 7\n\
+            data\n\
+            unknown: ignored\n\
+            \n\
+            data: {\"done\": true}\n\
+            \n"]);
+        assert_eq!(
+            events,
+            [
+                event(None, "message", "first\nsecond"),
+                event(Some("7"), "delta", ""),
+                event(None, "message", "{\"done\": true}"),
+            ]
+        );
+        assert_eq!(parser.last_event_id(), Some("7"));
+        assert_eq!(
+            events[2].pretty_data().as_deref(),
+            Some("{\n  \"done\": true\n}")
+        );
+        assert_eq!(events[0].pretty_data(), None);
+    }
+
+    #[test]
+    fn chunk_boundaries_inside_lines_crlf_and_the_byte_order_mark_are_invisible() {
+        let source: &[u8] =
+            "\u{feff}id: α\r\ndata: 你好\r\n\r\nretry: 2500\rdata: x\r\r".as_bytes();
+        let (whole, _) = parse(&[source]);
+        for split in 1..source.len() {
+            let (events, parser) = parse(&[&source[..split], &source[split..]]);
+            assert_eq!(events, whole, "split at {split}");
+            assert_eq!(parser.last_event_id(), Some("α"));
+            assert_eq!(parser.retry(), Some(2500));
+        }
+        let bytewise = source.chunks(1).collect::<Vec<_>>();
+        assert_eq!(parse(&bytewise).0, whole);
+        assert_eq!(
+            whole,
+            [
+                event(Some("α"), "message", "你好"),
+                SseEvent {
+                    retry: Some(2500),
+                    ..event(None, "message", "x")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks_without_data_update_reconnect_state_without_dispatching() {
+        let mut parser = SseParser::default();
+        let update = parser.feed(b"id: 3\nretry: 100\n\nretry: soon\nid: a\0b\n\n");
+        assert!(update.events.is_empty());
+        assert_eq!(update.last_event_id.as_deref(), Some("3"));
+        assert_eq!(update.retry, Some(100));
+
+        let update = parser.feed(b"event: ping\n\ndata: tail");
+        assert_eq!(update, SseUpdate::default());
+        assert_eq!(parser.last_event_id(), Some("3"));
+
+        let update = parser.feed(b"\nid\n\n");
+        assert_eq!(update.events, [event(Some(""), "message", "tail")]);
+        assert_eq!(update.last_event_id.as_deref(), Some(""));
+    }
+}
@@ -0,0 +1,631 @@
+//! MessagePack, CBOR and `application/x-www-form-urlencoded` bodies decoded
+//! to JSON values for the response tree.
+//!
+//! Values JSON cannot hold are rendered as strings: byte strings as
+//! `h'…'` hex, non-finite floats by name and integers beyond 64 bits in
+//! decimal. Non-string map keys are replaced by their compact JSON text.
+
+use std::fmt::Write as _;
+
+use serde_json::{Map, Number, Value};
+use thiserror::Error;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+/// Matches `serde_json`'s own recursion limit for JSON bodies.
+const MAX_DEPTH: usize = 128;
+const MSGPACK_TIMESTAMP_EXT: i8 = -1;
+const CBOR_DATE_TIME_TAG: u64 = 0;
+const CBOR_EPOCH_TAG: u64 = 1;
+const CBOR_POSITIVE_BIGNUM_TAG: u64 = 2;
+const CBOR_NEGATIVE_BIGNUM_TAG: u64 = 3;
+const CBOR_SELF_DESCRIBED_TAG: u64 = 55799;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Error)]
+pub(crate) enum StructuredProblem {
+    #[error("body ends inside a value")]
+    Truncated,
+    #[error("body contains an invalid value")]
+    Invalid,
+    #[error("body nests values too deeply")]
+    TooDeep,
+    #[error("body continues after its value")]
+    TrailingBytes,
+}
+
+/// Decodes a body holding exactly one MessagePack value.
+pub(crate) fn decode_message_pack(bytes: &[u8]) -> Result<Value, StructuredProblem> {
+    let mut reader = Reader::new(bytes);
+    let value = reader.message_pack(0)?;
+    reader.finish()?;
+    Ok(value)
+}
+
+/// Decodes a body holding exactly one CBOR data item.
+pub(crate) fn decode_cbor(bytes: &[u8]) -> Result<Value, StructuredProblem> {
+    let mut reader = Reader::new(bytes);
+    let value = reader.cbor(0)?;
+    reader.finish()?;
+    Ok(value)
+}
+
+/// Decodes form fields into an object. Repeated names collect their values
+/// in an array, in body order.
+pub(crate) fn decode_form(bytes: &[u8]) -> Value {
+    let mut fields = Map::new();
+    for (name, value) in url::form_urlencoded::parse(bytes).into_owned() {
+        let value = Value::String(value);
+        match fields.get_mut(&name) {
+            Some(Value::Array(values)) => values.push(value),
+            Some(first) => *first = Value::Array(vec![first.take(), value]),
+            None => {
+                fields.insert(name, value);
+            }
+        }
+    }
+    Value::Object(fields)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+enum CborItem {
+    Value(Value),
+    Break,
+}
+
+impl<'a> Reader<'a> {
+    const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn finish(&self) -> Result<(), StructuredProblem> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(StructuredProblem::TrailingBytes)
+        }
+    }
+
+    /// Takes `len` bytes, checking the declared length against what is left
+    /// before anything is allocated for it.
+    fn take(&mut self, len: u64) -> Result<&'a [u8], StructuredProblem> {
+        let len = usize::try_from(len).map_err(|_| StructuredProblem::Truncated)?;
+        if len > self.bytes.len() {
+            return Err(StructuredProblem::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], StructuredProblem> {
+        let bytes = self.take(N as u64)?;
+        Ok(bytes.try_into().expect("take returns exactly N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, StructuredProblem> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, StructuredProblem> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, StructuredProblem> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, StructuredProblem> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    /// Every element takes at least one byte, so a count larger than the
+    /// remaining input is rejected before the collection is reserved.
+    fn element_count(&self, count: u64) -> Result<usize, StructuredProblem> {
+        usize::try_from(count)
+            .ok()
+            .filter(|count| *count <= self.bytes.len())
+            .ok_or(StructuredProblem::Truncated)
+    }
+
+    fn message_pack(&mut self, depth: usize) -> Result<Value, StructuredProblem> {
+        if depth > MAX_DEPTH {
+            return Err(StructuredProblem::TooDeep);
+        }
+        let marker = self.u8()?;
+        Ok(match marker {
+            0x00..=0x7f => Value::from(marker),
+            0x80..=0x8f => self.message_pack_map(u64::from(marker & 0x0f), depth)?,
+            0x90..=0x9f => self.message_pack_array(u64::from(marker & 0x0f), depth)?,
+            0xa0..=0xbf => self.text(u64::from(marker & 0x1f))?,
+            0xc0 => Value::Null,
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            0xc4 => {
+                let len = self.u8()?;
+                bytes_value(self.take(u64::from(len))?)
+            }
+            0xc5 => {
+                let len = self.u16()?;
+                bytes_value(self.take(u64::from(len))?)
+            }
+            0xc6 => {
+                let len = self.u32()?;
+                bytes_value(self.take(u64::from(len))?)
+            }
+            0xc7 => {
+                let len = self.u8()?;
+                self.message_pack_ext(u64::from(len))?
+            }
+            0xc8 => {
+                let len = self.u16()?;
+                self.message_pack_ext(u64::from(len))?
+            }
+            0xc9 => {
+                let len = self.u32()?;
+                self.message_pack_ext(u64::from(len))?
+            }
+            0xca => float_value(f64::from(f32::from_bits(self.u32()?))),
+            0xcb => float_value(f64::from_bits(self.u64()?)),
+            0xcc => Value::from(self.u8()?),
+            0xcd => Value::from(self.u16()?),
+            0xce => Value::from(self.u32()?),
+            0xcf => Value::from(self.u64()?),
+            0xd0 => Value::from(self.u8()? as i8),
+            0xd1 => Value::from(self.u16()? as i16),
+            0xd2 => Value::from(self.u32()? as i32),
+            0xd3 => Value::from(self.u64()? as i64),
+            0xd4 => self.message_pack_ext(1)?,
+            0xd5 => self.message_pack_ext(2)?,
+            0xd6 => self.message_pack_ext(4)?,
+            0xd7 => self.message_pack_ext(8)?,
+            0xd8 => self.message_pack_ext(16)?,
+            0xd9 => {
+                let len = self.u8()?;
+                self.text(u64::from(len))?
+            }
+            0xda => {
+                let len = self.u16()?;
+                self.text(u64::from(len))?
+            }
+            0xdb => {
+                let len = self.u32()?;
+                self.text(u64::from(len))?
+            }
+            0xdc => {
+                let len = self.u16()?;
+                self.message_pack_array(u64::from(len), depth)?
+            }
+            0xdd => {
+                let len = self.u32()?;
+                self.message_pack_array(u64::from(len), depth)?
+            }
+            0xde => {
+                let len = self.u16()?;
+                self.message_pack_map(u64::from(len), depth)?
+            }
+            0xdf => {
+                let len = self.u32()?;
+                self.message_pack_map(u64::from(len), depth)?
+            }
+            0xe0..=0xff => Value::from(marker as i8),
+            // 0xc1 is reserved and never used.
+            0xc1 => return Err(StructuredProblem::Invalid),
+        })
+    }
+
+    fn message_pack_array(&mut self, len: u64, depth: usize) -> Result<Value, StructuredProblem> {
+        let len = self.element_count(len)?;
+        let mut values = Vec::with_capacity(len);
+        for _ in 0..len {
+            values.push(self.message_pack(depth + 1)?);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn message_pack_map(&mut self, len: u64, depth: usize) -> Result<Value, StructuredProblem> {
+        let len = self.element_count(len)?;
+        let mut entries = Map::new();
+        for _ in 0..len {
+            let key = self.message_pack(depth + 1)?;
+            let value = self.message_pack(depth + 1)?;
+            entries.insert(key_text(key), value);
+        }
+        Ok(Value::Object(entries))
+    }
+
+    /// Timestamps become RFC 3339 strings; other extension types keep their
+    /// type number next to the payload.
+    fn message_pack_ext(&mut self, len: u64) -> Result<Value, StructuredProblem> {
+        let type_ = self.u8()? as i8;
+        let data = self.take(len)?;
+        if type_ == MSGPACK_TIMESTAMP_EXT
+            && let Some(timestamp) = message_pack_timestamp(data)
+        {
+            return Ok(timestamp);
+        }
+        let mut ext = Map::new();
+        ext.insert("ext".to_owned(), Value::from(type_));
+        ext.insert("data".to_owned(), bytes_value(data));
+        Ok(Value::Object(ext))
+    }
+
+    fn text(&mut self, len: u64) -> Result<Value, StructuredProblem> {
+        let bytes = self.take(len)?;
+        Ok(Value::String(String::from_utf8_lossy(bytes).into_owned()))
+    }
+
+    fn cbor(&mut self, depth: usize) -> Result<Value, StructuredProblem> {
+        match self.cbor_item(depth)? {
+            CborItem::Value(value) => Ok(value),
+            CborItem::Break => Err(StructuredProblem::Invalid),
+        }
+    }
+
+    fn cbor_item(&mut self, depth: usize) -> Result<CborItem, StructuredProblem> {
+        if depth > MAX_DEPTH {
+            return Err(StructuredProblem::TooDeep);
+        }
+        let initial = self.u8()?;
+        let major = initial >> 5;
+        let info = initial & 0x1f;
+        if major == 7 {
+            return self.cbor_simple(info).map(|value| match value {
+                Some(value) => CborItem::Value(value),
+                None => CborItem::Break,
+            });
+        }
+        let argument = self.cbor_argument(info)?;
+        Ok(CborItem::Value(match (major, argument) {
+            (0, Some(value)) => Value::from(value),
+            (1, Some(value)) => negative_integer(u128::from(value)),
+            (2, length) => bytes_value(&self.cbor_string(2, length)?),
+            (3, length) => {
+                Value::String(String::from_utf8_lossy(&self.cbor_string(3, length)?).into_owned())
+            }
+            (4, length) => self.cbor_array(length, depth)?,
+            (5, length) => self.cbor_map(length, depth)?,
+            (6, Some(tag)) => self.cbor_tagged(tag, depth)?,
+            _ => return Err(StructuredProblem::Invalid),
+        }))
+    }
+
+    /// The argument of an initial byte, or `None` for an indefinite length.
+    fn cbor_argument(&mut self, info: u8) -> Result<Option<u64>, StructuredProblem> {
+        Ok(Some(match info {
+            0..=23 => u64::from(info),
+            24 => u64::from(self.u8()?),
+            25 => u64::from(self.u16()?),
+            26 => u64::from(self.u32()?),
+            27 => self.u64()?,
+            31 => return Ok(None),
+            _ => return Err(StructuredProblem::Invalid),
+        }))
+    }
+
+    /// Major type 7. `None` is the break that ends an indefinite item.
+    fn cbor_simple(&mut self, info: u8) -> Result<Option<Value>, StructuredProblem> {
+        Ok(Some(match info {
+            20 => Value::Bool(false),
+            21 => Value::Bool(true),
+            22 | 23 => Value::Null,
+            0..=19 => Value::String(format!("simple({info})")),
+            24 => Value::String(format!("simple({})", self.u8()?)),
+            25 => float_value(half_to_f64(self.u16()?)),
+            26 => float_value(f64::from(f32::from_bits(self.u32()?))),
+            27 => float_value(f64::from_bits(self.u64()?)),
+            31 => return Ok(None),
+            _ => return Err(StructuredProblem::Invalid),
+        }))
+    }
+
+    /// A byte or text string; indefinite strings are definite chunks of the
+    /// same major type followed by a break.
+    fn cbor_string(
+        &mut self,
+        major: u8,
+        length: Option<u64>,
+    ) -> Result<Vec<u8>, StructuredProblem> {
+        if let Some(length) = length {
+            return Ok(self.take(length)?.to_vec());
+        }
+        let mut joined = Vec::new();
+        loop {
+            let initial = self.u8()?;
+            if initial == 0xff {
+                return Ok(joined);
+            }
+            if initial >> 5 != major {
+                return Err(StructuredProblem::Invalid);
+            }
+            let length = self
+                .cbor_argument(initial & 0x1f)?
+                .ok_or(StructuredProblem::Invalid)?;
+            joined.extend_from_slice(self.take(length)?);
+        }
+    }
+
+    fn cbor_array(
+        &mut self,
+        length: Option<u64>,
+        depth: usize,
+    ) -> Result<Value, StructuredProblem> {
+        let mut values = Vec::new();
+        match length {
+            Some(length) => {
+                let length = self.element_count(length)?;
+                values.reserve(length);
+                for _ in 0..length {
+                    values.push(self.cbor(depth + 1)?);
+                }
+            }
+            None => {
+                while let CborItem::Value(value) = self.cbor_item(depth + 1)? {
+                    values.push(value);
+                }
+            }
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn cbor_map(&mut self, length: Option<u64>, depth: usize) -> Result<Value, StructuredProblem> {
+        let mut entries = Map::new();
+        match length {
+            Some(length) => {
+                for _ in 0..self.element_count(length)? {
+                    let key = self.cbor(depth + 1)?;
+                    let value = self.cbor(depth + 1)?;
+                    entries.insert(key_text(key), value);
+                }
+            }
+            None => {
+                while let CborItem::Value(key) = self.cbor_item(depth + 1)? {
+                    let value = self.cbor(depth + 1)?;
+                    entries.insert(key_text(key), value);
+                }
+            }
+        }
+        Ok(Value::Object(entries))
+    }
+
+    /// Date-time, epoch and self-described tags show their content as is,
+    /// and bignums become integers; other tags wrap their content.
+    fn cbor_tagged(&mut self, tag: u64, depth: usize) -> Result<Value, StructuredProblem> {
+        let bignum = matches!(tag, CBOR_POSITIVE_BIGNUM_TAG | CBOR_NEGATIVE_BIGNUM_TAG)
+            && self.bytes.first().is_some_and(|initial| initial >> 5 == 2);
+        if bignum {
+            let initial = self.u8()?;
+            let length = self.cbor_argument(initial & 0x1f)?;
+            let magnitude = self.cbor_string(2, length)?;
+            if let Some(magnitude) = bignum_magnitude(&magnitude) {
+                return Ok(if tag == CBOR_POSITIVE_BIGNUM_TAG {
+                    u64::try_from(magnitude)
+                        .map_or_else(|_| Value::String(magnitude.to_string()), Value::from)
+                } else {
+                    negative_integer(magnitude)
+                });
+            }
+            return Ok(tagged_value(tag, bytes_value(&magnitude)));
+        }
+        let value = self.cbor(depth + 1)?;
+        Ok(match tag {
+            CBOR_DATE_TIME_TAG | CBOR_EPOCH_TAG | CBOR_SELF_DESCRIBED_TAG => value,
+            _ => tagged_value(tag, value),
+        })
+    }
+}
+
+fn tagged_value(tag: u64, value: Value) -> Value {
+    let mut tagged = Map::new();
+    tagged.insert("tag".to_owned(), Value::from(tag));
+    tagged.insert("value".to_owned(), value);
+    Value::Object(tagged)
+}
+
+fn bytes_value(bytes: &[u8]) -> Value {
+    let mut text = String::with_capacity(bytes.len() * 2 + 3);
+    text.push_str("h'");
+    for byte in bytes {
+        write!(&mut text, "{byte:02x}").expect("writing to String cannot fail");
+    }
+    text.push('\'');
+    Value::String(text)
+}
+
+/// A big-endian magnitude, when it fits in 128 bits.
+fn bignum_magnitude(bytes: &[u8]) -> Option<u128> {
+    let start = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len());
+    let digits = &bytes[start..];
+    (digits.len() <= 16).then(|| {
+        digits
+            .iter()
+            .fold(0, |magnitude, byte| magnitude << 8 | u128::from(*byte))
+    })
+}
+
+/// `-1 - magnitude`, as a number when it fits in an `i64`.
+fn negative_integer(magnitude: u128) -> Value {
+    let value = -1 - i128::try_from(magnitude).unwrap_or(i128::MAX);
+    i64::try_from(value).map_or_else(|_| Value::String(value.to_string()), Value::from)
+}
+
+fn float_value(value: f64) -> Value {
+    Number::from_f64(value).map_or_else(
+        || {
+            Value::String(
+                if value.is_nan() {
+                    "NaN"
+                } else if value > 0. {
+                    "Infinity"
+                } else {
+                    "-Infinity"
+                }
+                .to_owned(),
+            )
+        },
+        Value::Number,
+    )
+}
+
+fn half_to_f64(bits: u16) -> f64 {
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f64::from(bits & 0x3ff);
+    let magnitude = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0. => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (mantissa + 1024.) * 2f64.powi(exponent - 25),
+    };
+    if bits & 0x8000 == 0 {
+        magnitude
+    } else {
+        -magnitude
+    }
+}
+
+fn key_text(key: Value) -> String {
+    match key {
+        Value::String(key) => key,
+        key => key.to_string(),
+    }
+}
+
+/// The three timestamp layouts of the MessagePack specification.
+fn message_pack_timestamp(data: &[u8]) -> Option<Value> {
+    let (seconds, nanoseconds) = match data.len() {
+        4 => (i64::from(u32::from_be_bytes(data.try_into().ok()?)), 0),
+        8 => {
+            let packed = u64::from_be_bytes(data.try_into().ok()?);
+            ((packed & 0x3_ffff_ffff) as i64, (packed >> 34) as u32)
+        }
+        12 => (
+            i64::from_be_bytes(data[4..].try_into().ok()?),
+            u32::from_be_bytes(data[..4].try_into().ok()?),
+        ),
+        _ => return None,
+    };
+    let timestamp = i128::from(seconds) * 1_000_000_000 + i128::from(nanoseconds);
+    let formatted = OffsetDateTime::from_unix_timestamp_nanos(timestamp)
+        .ok()?
+        .format(&Rfc3339)
+        .ok()?;
+    Some(Value::String(formatted))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn message_pack_decodes_every_family_and_rejects_damaged_bodies() {
+        let body = [
+            0x87, // map of 7
+            0xa1, b'a', 0x93, 0x01, 0xff, 0xcd, 0x01, 0x00, // "a": [1, -1, 256]
+            0xa1, b'b', 0xc4, 0x02, 0xde, 0xad, // "b": bin
+            0xa1, b'c', 0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0, // "c": 1.5
+            0xa1, b'd', 0xd6, 0xff, 0x00, 0x00, 0x00, 0x00, // "d": timestamp 0
+            0xa1, b'e', 0xd4, 0x05, 0x07, // "e": fixext 1
+            0x01, 0xc3, // 1: true
+            0xa1, b'f', 0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0, // "f": i64::MIN
+        ];
+        assert_eq!(
+            decode_message_pack(&body).unwrap(),
+            json!({
+                "a": [1, -1, 256],
+                "b": "h'dead'",
+                "c": 1.5,
+                "d": "1970-01-01T00:00:00Z",
+                "e": { "ext": 5, "data": "h'07'" },
+                "1": true,
+                "f": i64::MIN,
+            })
+        );
+
+        assert_eq!(
+            decode_message_pack(&[0xdd, 0xff, 0xff, 0xff, 0xff]),
+            Err(StructuredProblem::Truncated)
+        );
+        assert_eq!(
+            decode_message_pack(&[0x01, 0x02]),
+            Err(StructuredProblem::TrailingBytes)
+        );
+        assert_eq!(
+            decode_message_pack(&[0xc1]),
+            Err(StructuredProblem::Invalid)
+        );
+        assert_eq!(
+            decode_message_pack(&[0x91; MAX_DEPTH + 2]),
+            Err(StructuredProblem::TooDeep)
+        );
+    }
+
+    #[test]
+    fn cbor_decodes_indefinite_items_tags_and_special_numbers() {
+        let body = [
+            0xbf, // indefinite map
+            0x61, b'a', 0x9f, 0x01, 0x20, 0xff, // "a": [_ 1, -1]
+            0x61, b'b', 0x5f, 0x41, 0xde, 0x41, 0xad, 0xff, // "b": (_ h'de', h'ad')
+            0x61, b'c', 0xf9, 0x3e, 0x00, // "c": 1.5 as f16
+            0x61, b'd', 0xf9, 0x7c, 0x00, // "d": Infinity
+            0x61, b'e', 0xc1, 0x00, // "e": 1(0)
+            0x61, b'f', 0xc2, 0x49, 1, 0, 0, 0, 0, 0, 0, 0, 0, // "f": 2^64
+            0x61, b'g', 0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // "g": -2^64
+            0x61, b'h', 0xd8, 0x20, 0x60, // "h": 32("")
+            0xf5, 0xf6, // true: null
+            0xff,
+        ];
+        assert_eq!(
+            decode_cbor(&body).unwrap(),
+            json!({
+                "a": [1, -1],
+                "b": "h'dead'",
+                "c": 1.5,
+                "d": "Infinity",
+                "e": 0,
+                "f": "18446744073709551616",
+                "g": "-18446744073709551616",
+                "h": { "tag": 32, "value": "" },
+                "true": null,
+            })
+        );
+        assert_eq!(
+            decode_cbor(&[0xd9, 0xd9, 0xf7, 0x01]).unwrap(),
+            json!(1),
+            "the self-described CBOR tag is transparent"
+        );
+
+        assert_eq!(decode_cbor(&[0xff]), Err(StructuredProblem::Invalid));
+        assert_eq!(
+            decode_cbor(&[0x5f, 0x61, b'a', 0xff]),
+            Err(StructuredProblem::Invalid)
+        );
+        assert_eq!(
+            decode_cbor(&[0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            Err(StructuredProblem::Truncated)
+        );
+        assert_eq!(
+            decode_cbor(&[0x81; MAX_DEPTH + 2]),
+            Err(StructuredProblem::TooDeep)
+        );
+    }
+
+    #[test]
+    fn form_fields_become_an_object_with_repeated_names_as_arrays() {
+        assert_eq!(
+            decode_form(b"user=ann&tag=a&note=a+b%21&tag=b&tag=c&empty"),
+            json!({
+                "user": "ann",
+                "tag": ["a", "b", "c"],
+                "note": "a b!",
+                "empty": "",
+            })
+        );
+        assert_eq!(decode_form(b""), json!({}));
+    }
+}
@@ -8,10 +8,13 @@ use std::{
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use gpui::RenderImage;
 use image::{AnimationDecoder as _, ImageDecoder as _, ImageFormat, Limits};
+use prost_reflect::{DynamicMessage, MessageDescriptor};
+use serde_json::Value;
 
 use super::{
     BodyDecoding, ContentKind, INLINE_PREVIEW_BYTES, ResponseData, SourceLanguage,
     TextDecodingProblem, classify_content_type, decode_text,
+    structured::{decode_cbor, decode_form, decode_message_pack},
 };
 
 const MAX_IMAGE_DIMENSION: u32 = 4096;
@@ -31,10 +34,17 @@ pub(crate) enum ViewerMode {
     Image,
     Audio,
     Pdf,
+    Events,
+    /// Decodes the body with a message type from the loaded gRPC
+    /// descriptors.
+    Protobuf,
+    MessagePack,
+    Cbor,
+    Form,
 }
 
 impl ViewerMode {
-    pub(crate) const ALL: [Self; 9] = [
+    pub(crate) const ALL: [Self; 14] = [
         Self::Auto,
         Self::Text,
         Self::Json,
@@ -44,6 +54,11 @@ impl ViewerMode {
         Self::Image,
         Self::Audio,
         Self::Pdf,
+        Self::Events,
+        Self::Protobuf,
+        Self::MessagePack,
+        Self::Cbor,
+        Self::Form,
     ];
 }
 
@@ -58,17 +73,39 @@ pub(crate) fn resolved_viewer_mode(
             _ => None,
         };
     }
-    if requested != ViewerMode::Auto {
-        return Some(requested);
+    let kind = classify_content_type(&response.head().headers);
+    match requested {
+        ViewerMode::Auto => {}
+        ViewerMode::Events if kind != ContentKind::EventStream => return None,
+        ViewerMode::Protobuf if !matches!(kind, ContentKind::Protobuf | ContentKind::Bytes) => {
+            return None;
+        }
+        ViewerMode::MessagePack
+            if !matches!(kind, ContentKind::MessagePack | ContentKind::Bytes) =>
+        {
+            return None;
+        }
+        ViewerMode::Cbor if !matches!(kind, ContentKind::Cbor | ContentKind::Bytes) => {
+            return None;
+        }
+        ViewerMode::Form if !matches!(kind, ContentKind::Form | ContentKind::Bytes) => {
+            return None;
+        }
+        requested => return Some(requested),
     }
-    Some(match classify_content_type(&response.head().headers) {
+    Some(match kind {
         ContentKind::Text(_) => ViewerMode::Text,
         ContentKind::Json => ViewerMode::Json,
         ContentKind::Xml => ViewerMode::Xml,
         ContentKind::Image => ViewerMode::Image,
         ContentKind::Audio => ViewerMode::Audio,
         ContentKind::Pdf => ViewerMode::Pdf,
-        ContentKind::Bytes => ViewerMode::Hex,
+        ContentKind::EventStream => ViewerMode::Events,
+        ContentKind::MessagePack => ViewerMode::MessagePack,
+        ContentKind::Cbor => ViewerMode::Cbor,
+        ContentKind::Form => ViewerMode::Form,
+        // Decoding needs a message type, which only the user can supply.
+        ContentKind::Protobuf | ContentKind::Bytes => ViewerMode::Hex,
     })
 }
 
@@ -80,6 +117,14 @@ pub(crate) enum ResponseViewWarning {
     InvalidJson,
     InvalidImage,
     ImageTooLarge,
+    ProtobufTypeRequired,
+    ProtobufTooLarge,
+    InvalidProtobuf,
+    /// A MessagePack, CBOR or form body is only decoded when it fits in the
+    /// preview.
+    DocumentTooLarge,
+    InvalidMessagePack,
+    InvalidCbor,
 }
 
 pub(crate) enum ResponseProjection {
@@ -88,6 +133,9 @@ pub(crate) enum ResponseProjection {
         source: String,
         language: SourceLanguage,
         warning: Option<ResponseViewWarning>,
+        /// The parsed body when a complete JSON document was shown, for the
+        /// filter bar and the tree view.
+        document: Option<Arc<Value>>,
     },
     Image {
         image: Arc<RenderImage>,
@@ -114,11 +162,13 @@ impl fmt::Debug for ResponseProjection {
                 source,
                 language,
                 warning,
+                document,
             } => formatter
                 .debug_struct("ResponseProjection::Text")
                 .field("len", &source.len())
                 .field("language", language)
                 .field("warning", warning)
+                .field("document", &document.is_some())
                 .finish(),
             Self::Image { warning, .. } => formatter
                 .debug_struct("ResponseProjection::Image")
@@ -208,12 +258,80 @@ pub(crate) async fn project_response(
                 }
             })
         }
+        EffectiveMode::MessagePack | EffectiveMode::Cbor | EffectiveMode::Form
+            if !prefix.complete =>
+        {
+            Ok(ResponseProjection::Unavailable(
+                ResponseViewWarning::DocumentTooLarge,
+            ))
+        }
+        EffectiveMode::MessagePack => Ok(match decode_message_pack(&prefix.bytes) {
+            Ok(value) => document_projection(value),
+            Err(_) => ResponseProjection::Unavailable(ResponseViewWarning::InvalidMessagePack),
+        }),
+        EffectiveMode::Cbor => Ok(match decode_cbor(&prefix.bytes) {
+            Ok(value) => document_projection(value),
+            Err(_) => ResponseProjection::Unavailable(ResponseViewWarning::InvalidCbor),
+        }),
+        EffectiveMode::Form => Ok(document_projection(decode_form(&prefix.bytes))),
         EffectiveMode::Unavailable => Ok(ResponseProjection::Unavailable(
             ResponseViewWarning::ModeUnavailable,
         )),
     }
 }
 
+/// Decodes a protobuf body as `message` and shows it as JSON.
+pub(crate) async fn project_protobuf(
+    response: Arc<ResponseData>,
+    message: Option<MessageDescriptor>,
+) -> Result<ResponseProjection, ResponseViewProblem> {
+    if response.sizes().stored_body_bytes == 0 {
+        return Ok(ResponseProjection::Empty);
+    }
+    let Some(message) = message else {
+        return Ok(ResponseProjection::Unavailable(
+            ResponseViewWarning::ProtobufTypeRequired,
+        ));
+    };
+    let prefix = response
+        .read_lease()
+        .read_prefix(INLINE_PREVIEW_BYTES as usize)
+        .await
+        .map_err(|_| ResponseViewProblem::Read)?;
+    if !prefix.complete {
+        return Ok(ResponseProjection::Unavailable(
+            ResponseViewWarning::ProtobufTooLarge,
+        ));
+    }
+    let decoded = tokio::task::spawn_blocking(move || protobuf_projection(message, &prefix.bytes))
+        .await
+        .map_err(|_| ResponseViewProblem::Read)?;
+    Ok(decoded)
+}
+
+fn protobuf_projection(message: MessageDescriptor, bytes: &[u8]) -> ResponseProjection {
+    let value = DynamicMessage::decode(message, bytes)
+        .ok()
+        .and_then(|message| serde_json::to_value(&message).ok());
+    match value {
+        Some(value) => document_projection(value),
+        None => ResponseProjection::Unavailable(ResponseViewWarning::InvalidProtobuf),
+    }
+}
+
+/// A decoded body as pretty JSON. The tree and filter bar are only offered
+/// when the whole document fits in the editor.
+fn document_projection(value: Value) -> ResponseProjection {
+    let pretty = serde_json::to_string_pretty(&value).expect("JSON values always serialize");
+    let (source, truncated) = bounded_source(pretty);
+    ResponseProjection::Text {
+        source,
+        language: SourceLanguage::Json,
+        warning: truncated.then_some(ResponseViewWarning::Truncated),
+        document: (!truncated).then(|| Arc::new(value)),
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum EffectiveMode {
     Text(SourceLanguage),
@@ -222,6 +340,9 @@ enum EffectiveMode {
     Hex,
     Base64,
     Image,
+    MessagePack,
+    Cbor,
+    Form,
     Unavailable,
 }
 
@@ -244,22 +365,38 @@ fn effective_mode(
             ContentKind::Xml => EffectiveMode::Xml,
             ContentKind::Image => EffectiveMode::Image,
             ContentKind::Audio | ContentKind::Pdf => EffectiveMode::Unavailable,
-            ContentKind::Bytes => EffectiveMode::Hex,
+            ContentKind::EventStream => EffectiveMode::Text(SourceLanguage::Plain),
+            ContentKind::MessagePack => EffectiveMode::MessagePack,
+            ContentKind::Cbor => EffectiveMode::Cbor,
+            ContentKind::Form => EffectiveMode::Form,
+            ContentKind::Protobuf | ContentKind::Bytes => EffectiveMode::Hex,
         },
         ViewerMode::Text => EffectiveMode::Text(match kind {
             ContentKind::Text(language) => language,
             ContentKind::Json => SourceLanguage::Json,
             ContentKind::Xml => SourceLanguage::Xml,
-            ContentKind::Image | ContentKind::Audio | ContentKind::Pdf | ContentKind::Bytes => {
-                SourceLanguage::Plain
-            }
+            ContentKind::Image
+            | ContentKind::Audio
+            | ContentKind::Pdf
+            | ContentKind::EventStream
+            | ContentKind::Protobuf
+            | ContentKind::MessagePack
+            | ContentKind::Cbor
+            | ContentKind::Form
+            | ContentKind::Bytes => SourceLanguage::Plain,
         }),
         ViewerMode::Json => EffectiveMode::Json,
         ViewerMode::Xml => EffectiveMode::Xml,
         ViewerMode::Hex => EffectiveMode::Hex,
         ViewerMode::Base64 => EffectiveMode::Base64,
         ViewerMode::Image => EffectiveMode::Image,
-        ViewerMode::Audio | ViewerMode::Pdf => EffectiveMode::Unavailable,
+        ViewerMode::MessagePack => EffectiveMode::MessagePack,
+        ViewerMode::Cbor => EffectiveMode::Cbor,
+        ViewerMode::Form => EffectiveMode::Form,
+        // Protobuf bodies are projected by `project_protobuf`.
+        ViewerMode::Audio | ViewerMode::Pdf | ViewerMode::Events | ViewerMode::Protobuf => {
+            EffectiveMode::Unavailable
+        }
     }
 }
 
@@ -278,6 +415,7 @@ fn text_projection(
                 language,
                 warning: preferred_warning
                     .or((!complete || output_truncated).then_some(ResponseViewWarning::Truncated)),
+                document: None,
             }
         }
         Err(TextDecodingProblem::UnknownCharset | TextDecodingProblem::InvalidBytes) => {
@@ -299,7 +437,7 @@ fn json_projection(bytes: &[u8], headers: &http::HeaderMap, complete: bool) -> R
     let Ok(source) = decode_text(bytes, headers, true) else {
         return ResponseProjection::Unavailable(ResponseViewWarning::ModeUnavailable);
     };
-    let Ok(value) = serde_json::from_str::<serde_json::Value>(&source) else {
+    let Ok(value) = serde_json::from_str::<Value>(&source) else {
         return text_projection(
             bytes,
             headers,
@@ -325,11 +463,12 @@ fn json_projection(bytes: &[u8], headers: &http::HeaderMap, complete: bool) -> R
             source,
             language: SourceLanguage::Json,
             warning: None,
+            document: Some(Arc::new(value)),
         }
     }
 }
 
-fn bounded_source(mut source: String) -> (String, bool) {
+pub(super) fn bounded_source(mut source: String) -> (String, bool) {
     let limit = INLINE_PREVIEW_BYTES as usize;
     let original_len = source.len();
     if source.len() > limit {
@@ -385,6 +524,7 @@ fn hex_projection(bytes: &[u8], complete: bool, decoding: BodyDecoding) -> Respo
         source,
         language: SourceLanguage::Plain,
         warning,
+        document: None,
     }
 }
 
@@ -405,6 +545,7 @@ fn base64_projection(bytes: &[u8], complete: bool, decoding: BodyDecoding) -> Re
         source,
         language: SourceLanguage::Plain,
         warning,
+        document: None,
     }
 }
 
@@ -588,11 +729,13 @@ mod tests {
             source,
             language,
             warning,
+            document,
         } = projection
         else {
             panic!("JSON did not produce text")
         };
         assert_eq!(warning, None);
+        assert!(document.is_some_and(|document| document["html"].is_string()));
         assert_eq!(language, SourceLanguage::Json);
         assert!(source.contains("tracker.test"));
         assert!(source.starts_with("{\n"));
@@ -634,6 +777,129 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn protobuf_bodies_need_a_message_type_and_decode_to_json() {
+        use prost_reflect::DescriptorPool;
+        use prost_types::{
+            DescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+            field_descriptor_proto::{Label, Type},
+        };
+
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_proto(FileDescriptorProto {
+            name: Some("reply.proto".to_owned()),
+            package: Some("greet".to_owned()),
+            syntax: Some("proto3".to_owned()),
+            message_type: vec![DescriptorProto {
+                name: Some("Reply".to_owned()),
+                field: vec![FieldDescriptorProto {
+                    name: Some("text".to_owned()),
+                    number: Some(1),
+                    label: Some(Label::Optional.into()),
+                    r#type: Some(Type::String.into()),
+                    json_name: Some("text".to_owned()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+        .unwrap();
+        let reply = pool.get_message_by_name("greet.Reply");
+        let body = response(
+            b"\x0a\x02hi",
+            "application/x-protobuf",
+            BodyDecoding::Identity,
+        );
+        assert_eq!(
+            resolved_viewer_mode(&body, ViewerMode::Auto),
+            Some(ViewerMode::Hex)
+        );
+        assert_eq!(
+            resolved_viewer_mode(&body, ViewerMode::Protobuf),
+            Some(ViewerMode::Protobuf)
+        );
+        assert_eq!(
+            resolved_viewer_mode(
+                &response(b"{}", "application/json", BodyDecoding::Identity),
+                ViewerMode::Protobuf
+            ),
+            None
+        );
+
+        assert!(matches!(
+            project_protobuf(body.clone(), None).await.unwrap(),
+            ResponseProjection::Unavailable(ResponseViewWarning::ProtobufTypeRequired)
+        ));
+        let ResponseProjection::Text {
+            source, document, ..
+        } = project_protobuf(body, reply.clone()).await.unwrap()
+        else {
+            panic!("protobuf did not produce text")
+        };
+        assert_eq!(source, "{\n  \"text\": \"hi\"\n}");
+        assert!(document.is_some());
+        assert!(matches!(
+            project_protobuf(
+                response(b"\xff", "application/x-protobuf", BodyDecoding::Identity),
+                reply
+            )
+            .await
+            .unwrap(),
+            ResponseProjection::Unavailable(ResponseViewWarning::InvalidProtobuf)
+        ));
+    }
+
+    #[tokio::test]
+    async fn binary_and_form_bodies_are_picked_from_the_content_type_and_shown_as_trees() {
+        for (body, content_type, mode) in [
+            (
+                &b"\x81\xa2id\x07"[..],
+                "application/msgpack",
+                ViewerMode::MessagePack,
+            ),
+            (&b"\xa1\x62id\x07"[..], "application/cbor", ViewerMode::Cbor),
+            (
+                &b"id=7"[..],
+                "application/x-www-form-urlencoded",
+                ViewerMode::Form,
+            ),
+        ] {
+            let response = response(body, content_type, BodyDecoding::Identity);
+            assert_eq!(
+                resolved_viewer_mode(&response, ViewerMode::Auto),
+                Some(mode)
+            );
+            let ResponseProjection::Text {
+                language: SourceLanguage::Json,
+                warning: None,
+                document: Some(document),
+                ..
+            } = project_response(response, ViewerMode::Auto).await.unwrap()
+            else {
+                panic!("{content_type} did not produce a document")
+            };
+            let expected = if mode == ViewerMode::Form {
+                serde_json::json!({ "id": "7" })
+            } else {
+                serde_json::json!({ "id": 7 })
+            };
+            assert_eq!(*document, expected);
+        }
+
+        let json = response(b"{}", "application/json", BodyDecoding::Identity);
+        assert_eq!(resolved_viewer_mode(&json, ViewerMode::Cbor), None);
+        assert!(matches!(
+            project_response(
+                response(b"\xc1", "application/x-msgpack", BodyDecoding::Identity),
+                ViewerMode::Auto
+            )
+            .await
+            .unwrap(),
+            ResponseProjection::Unavailable(ResponseViewWarning::InvalidMessagePack)
+        ));
+    }
+
     #[tokio::test]
     async fn bounded_png_is_decoded_to_a_render_image() {
         let mut encoded = Cursor::new(Vec::new());
@@ -743,6 +1009,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn event_table_is_offered_only_for_event_streams() {
+        let stream = response(b"data: 1\n\n", "text/event-stream", BodyDecoding::Identity);
+        assert_eq!(
+            resolved_viewer_mode(&stream, ViewerMode::Auto),
+            Some(ViewerMode::Events)
+        );
+        assert_eq!(
+            resolved_viewer_mode(&stream, ViewerMode::Text),
+            Some(ViewerMode::Text)
+        );
+        let text = response(b"data: 1\n\n", "text/plain", BodyDecoding::Identity);
+        assert_eq!(resolved_viewer_mode(&text, ViewerMode::Events), None);
+    }
+
     #[test]
     fn unsupported_content_coding_disables_parser_and_player_modes() {
         let response = response(b"encoded", "audio/mpeg", BodyDecoding::Unsupported);
@@ -753,6 +1034,7 @@ mod tests {
             ViewerMode::Image,
             ViewerMode::Audio,
             ViewerMode::Pdf,
+            ViewerMode::Events,
         ] {
             assert_eq!(resolved_viewer_mode(&response, mode), None);
         }
@@ -0,0 +1,957 @@
+use std::{
+    path::PathBuf,
+    pin::pin,
+    time::{Duration, Instant},
+};
+
+use fluent_bundle::FluentArgs;
+use futures_util::future::{Either, select};
+use gpui::{
+    AnyElement, App, AppContext as _, Context, Entity, IntoElement, ParentElement as _,
+    PathPromptOptions, Render, SharedString, Styled as _, Task, Window, div,
+    prelude::FluentBuilder as _, px, relative,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    form::{field, v_form},
+    h_flex,
+    label::Label,
+    scroll::ScrollableElement as _,
+    tab::{Tab, TabBar},
+    table::{Table, TableBody, TableCell, TableHead, TableHeader, TableRow},
+    v_flex,
+};
+use gpui_form_gpui_component::{IntegerInput, IntegerInputState};
+use http::StatusCode;
+
+use self::{
+    data_file::{DataFileError, DataRows, parse_data_file},
+    load::{LoadPlan, LoadRecorder, LoadSummary, run_load},
+    step::{ResolvedRequest, execute_step},
+};
+use super::{
+    response::problem_message,
+    runtime::RequestProblemKind,
+    saved::{CollectionEntry, open_collection},
+    transport::{CookieJar, HttpTransport},
+    variables::RequestVariables,
+};
+use crate::foundation::I18n;
+
+pub use self::headless::run_headless;
+
+mod data_file;
+mod headless;
+mod load;
+mod step;
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+const LOAD_REFRESH: Duration = Duration::from_millis(250);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum RunnerMode {
+    #[default]
+    Collection,
+    Load,
+}
+
+impl RunnerMode {
+    const ALL: [Self; 2] = [Self::Collection, Self::Load];
+
+    const fn index(self) -> usize {
+        match self {
+            Self::Collection => 0,
+            Self::Load => 1,
+        }
+    }
+}
+
+enum StepOutcome {
+    /// The saved file could not be loaded.
+    Unreadable,
+    /// The request did not compile once its variables were substituted.
+    Invalid,
+    Failed(RequestProblemKind),
+    Completed {
+        status: StatusCode,
+        elapsed: Duration,
+        checks_passed: usize,
+        checks_total: usize,
+        passed: bool,
+    },
+}
+
+impl StepOutcome {
+    const fn passed(&self) -> bool {
+        matches!(self, Self::Completed { passed: true, .. })
+    }
+}
+
+struct StepRecord {
+    iteration: usize,
+    name: String,
+    outcome: StepOutcome,
+}
+
+struct LoadRun {
+    recorder: LoadRecorder,
+    started: Instant,
+    finished_after: Option<Duration>,
+}
+
+impl LoadRun {
+    fn summary(&self) -> LoadSummary {
+        self.recorder.summary(
+            self.finished_after
+                .unwrap_or_else(|| self.started.elapsed()),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LoadProblem {
+    Invalid,
+    Unbounded,
+}
+
+/// Runs a folder of saved requests in file name order, or drives one of
+/// them with concurrent workers. Both share the request editor's cookie
+/// jar; variables extracted during a run stay within that run.
+pub(crate) struct RunnerView {
+    mode: RunnerMode,
+    transport: HttpTransport,
+    folder: Option<PathBuf>,
+    entries: Vec<CollectionEntry>,
+    folder_unreadable: bool,
+    data: Option<(PathBuf, DataRows)>,
+    data_problem: Option<DataFileError>,
+    timeout_ms: Entity<IntegerInputState<u64>>,
+    iterations: Entity<IntegerInputState<u64>>,
+    delay_ms: Entity<IntegerInputState<u64>>,
+    records: Vec<StepRecord>,
+    load_target: usize,
+    concurrency: Entity<IntegerInputState<u64>>,
+    duration_s: Entity<IntegerInputState<u64>>,
+    request_limit: Entity<IntegerInputState<u64>>,
+    load: Option<LoadRun>,
+    load_problem: Option<LoadProblem>,
+    run_task: Option<Task<()>>,
+    prompt_task: Option<Task<()>>,
+}
+
+impl RunnerView {
+    pub(crate) fn new(cookies: CookieJar, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let mut integer = |value: u64, min: u64| {
+            cx.new(|cx| {
+                let mut state = IntegerInputState::new(window, cx).min(min);
+                state.set_value(value, window, cx);
+                state
+            })
+        };
+        Self {
+            mode: RunnerMode::default(),
+            transport: HttpTransport::new(cookies),
+            folder: None,
+            entries: Vec::new(),
+            folder_unreadable: false,
+            data: None,
+            data_problem: None,
+            timeout_ms: integer(DEFAULT_TIMEOUT_MS, 0),
+            iterations: integer(1, 1),
+            delay_ms: integer(0, 0),
+            records: Vec::new(),
+            load_target: 0,
+            concurrency: integer(4, 1),
+            duration_s: integer(10, 0),
+            request_limit: integer(0, 0),
+            load: None,
+            load_problem: None,
+            run_task: None,
+            prompt_task: None,
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.run_task.is_some()
+    }
+
+    fn choose_folder(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let prompt = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: None,
+        });
+        let owner = cx.entity().downgrade();
+        self.prompt_task = Some(window.spawn(cx, async move |cx| {
+            let Ok(Ok(Some(paths))) = prompt.await else {
+                return;
+            };
+            let Some(folder) = paths.into_iter().next() else {
+                return;
+            };
+            let _ = owner.update_in(cx, |this, _, cx| {
+                this.open_folder(folder, cx);
+            });
+        }));
+    }
+
+    fn open_folder(&mut self, folder: PathBuf, cx: &mut Context<Self>) {
+        let entries = open_collection(&folder);
+        self.folder_unreadable = entries.is_err();
+        self.entries = entries.unwrap_or_default();
+        self.folder = Some(folder);
+        self.load_target = 0;
+        self.records.clear();
+        self.load = None;
+        self.load_problem = None;
+        cx.notify();
+    }
+
+    fn reload_folder(&mut self, cx: &mut Context<Self>) {
+        if let Some(folder) = self.folder.clone() {
+            let target = self.load_target;
+            self.open_folder(folder, cx);
+            self.load_target = target.min(self.entries.len().saturating_sub(1));
+        }
+    }
+
+    fn choose_data_file(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let prompt = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: None,
+        });
+        let owner = cx.entity().downgrade();
+        self.prompt_task = Some(window.spawn(cx, async move |cx| {
+            let Ok(Ok(Some(paths))) = prompt.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+            let _ = owner.update_in(cx, |this, window, cx| {
+                let parsed = std::fs::read(&path)
+                    .map_err(|_| DataFileError::Unreadable)
+                    .and_then(|bytes| parse_data_file(&bytes));
+                match parsed {
+                    Ok(rows) => {
+                        // One pass per row unless the iterations are changed.
+                        let passes = rows.len().max(1) as u64;
+                        this.iterations
+                            .update(cx, |state, cx| state.set_value(passes, window, cx));
+                        this.data = Some((path, rows));
+                        this.data_problem = None;
+                    }
+                    Err(problem) => {
+                        this.data = None;
+                        this.data_problem = Some(problem);
+                    }
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    fn clear_data_file(&mut self, cx: &mut Context<Self>) {
+        self.data = None;
+        self.data_problem = None;
+        cx.notify();
+    }
+
+    fn stop(&mut self, cx: &mut Context<Self>) {
+        self.run_task = None;
+        if let Some(load) = &mut self.load {
+            load.finished_after
+                .get_or_insert_with(|| load.started.elapsed());
+        }
+        cx.notify();
+    }
+
+    fn start_collection(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.is_running() || self.entries.is_empty() {
+            return;
+        }
+        let steps: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.name.clone(), entry.request.as_ref().ok().cloned()))
+            .collect();
+        let iterations = self.iterations.read(cx).value().max(1) as usize;
+        let delay = Duration::from_millis(self.delay_ms.read(cx).value());
+        let timeout_ms = self.timeout_ms.read(cx).value();
+        let data = self.data.as_ref().map(|(_, rows)| rows.clone());
+        let transport = self.transport.clone();
+        let owner = cx.entity().downgrade();
+        self.records.clear();
+
+        self.run_task = Some(window.spawn(cx, async move |cx| {
+            let variables = RequestVariables::default();
+            let mut first = true;
+            for iteration in 0..iterations {
+                if let Some(rows) = &data {
+                    rows.apply(iteration, &variables);
+                }
+                for (name, draft) in &steps {
+                    if !first && !delay.is_zero() {
+                        cx.background_executor().timer(delay).await;
+                    }
+                    first = false;
+                    let outcome = match draft {
+                        None => StepOutcome::Unreadable,
+                        Some(draft) => {
+                            let prepared = variables
+                                .resolve(draft.clone())
+                                .and_then(|draft| ResolvedRequest { draft, timeout_ms }.prepare());
+                            match prepared {
+                                Err(_) => StepOutcome::Invalid,
+                                Ok((prepared, checks)) => {
+                                    let step = gpui_tokio::Tokio::spawn(
+                                        cx,
+                                        execute_step(transport.clone(), prepared, checks),
+                                    );
+                                    match step.await {
+                                        Ok(Ok(response)) => {
+                                            for outcome in &response.report.extractions {
+                                                if let Ok(value) = &outcome.result {
+                                                    variables.set(
+                                                        outcome.variable.clone(),
+                                                        value.clone(),
+                                                    );
+                                                }
+                                            }
+                                            StepOutcome::Completed {
+                                                status: response.status,
+                                                elapsed: response.elapsed,
+                                                checks_passed: response.report.passed(),
+                                                checks_total: response.report.assertions.len(),
+                                                passed: response.passed(),
+                                            }
+                                        }
+                                        Ok(Err(problem)) => StepOutcome::Failed(problem.kind()),
+                                        Err(_) => StepOutcome::Failed(RequestProblemKind::Internal),
+                                    }
+                                }
+                            }
+                        }
+                    };
+                    let record = StepRecord {
+                        iteration,
+                        name: name.clone(),
+                        outcome,
+                    };
+                    if owner
+                        .update_in(cx, |this, _, cx| {
+                            this.records.push(record);
+                            cx.notify();
+                        })
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+            let _ = owner.update_in(cx, |this, _, cx| {
+                this.run_task = None;
+                cx.notify();
+            });
+        }));
+        cx.notify();
+    }
+
+    fn start_load(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.is_running() {
+            return;
+        }
+        let Some(draft) = self
+            .entries
+            .get(self.load_target)
+            .and_then(|entry| entry.request.as_ref().ok())
+            .cloned()
+        else {
+            return;
+        };
+        // The first data row, when there is one, fills the placeholders.
+        let variables = RequestVariables::default();
+        if let Some((_, rows)) = &self.data {
+            rows.apply(0, &variables);
+        }
+        let timeout_ms = self.timeout_ms.read(cx).value();
+        let target = variables
+            .resolve(draft)
+            .ok()
+            .map(|draft| ResolvedRequest { draft, timeout_ms })
+            .filter(|target| target.prepare().is_ok());
+        let seconds = self.duration_s.read(cx).value();
+        let limit = self.request_limit.read(cx).value();
+        let plan = LoadPlan {
+            concurrency: self.concurrency.read(cx).value().max(1) as usize,
+            duration: (seconds != 0).then(|| Duration::from_secs(seconds)),
+            requests: (limit != 0).then_some(limit),
+        };
+        self.load_problem = if target.is_none() {
+            Some(LoadProblem::Invalid)
+        } else if plan.duration.is_none() && plan.requests.is_none() {
+            Some(LoadProblem::Unbounded)
+        } else {
+            None
+        };
+        let Some(target) = target.filter(|_| self.load_problem.is_none()) else {
+            cx.notify();
+            return;
+        };
+
+        let recorder = LoadRecorder::default();
+        let started = Instant::now();
+        let run = gpui_tokio::Tokio::spawn(
+            cx,
+            run_load(self.transport.clone(), target, plan, recorder.clone()),
+        );
+        self.load = Some(LoadRun {
+            recorder,
+            started,
+            finished_after: None,
+        });
+        let owner = cx.entity().downgrade();
+        self.run_task = Some(window.spawn(cx, async move |cx| {
+            let mut run = pin!(run);
+            loop {
+                let tick = pin!(cx.background_executor().timer(LOAD_REFRESH));
+                match select(run.as_mut(), tick).await {
+                    Either::Left(_) => break,
+                    Either::Right(_) => {
+                        if owner.update_in(cx, |_, _, cx| cx.notify()).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            let _ = owner.update_in(cx, |this, _, cx| {
+                this.stop(cx);
+            });
+        }));
+        cx.notify();
+    }
+
+    fn render_source(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let (choose_label, reload_label, timeout_label, folder_status) = {
+            let i18n = cx.global::<I18n>();
+            let status = match &self.folder {
+                None => i18n.t("runner-no-folder"),
+                Some(_) if self.folder_unreadable => i18n.t("runner-folder-unreadable"),
+                Some(_) if self.entries.is_empty() => i18n.t("runner-folder-empty"),
+                Some(folder) => {
+                    let mut args = FluentArgs::new();
+                    args.set("count", self.entries.len());
+                    args.set("folder", folder.display().to_string());
+                    i18n.t_with_args("runner-folder-summary", &args)
+                }
+            };
+            (
+                i18n.t("runner-choose-folder"),
+                i18n.t("runner-reload-folder"),
+                i18n.t("field-timeout-ms"),
+                status,
+            )
+        };
+        let running = self.is_running();
+
+        h_flex()
+            .p_2()
+            .gap_2()
+            .child(
+                Button::new("runner-choose-folder")
+                    .label(choose_label)
+                    .disabled(running)
+                    .on_click(cx.listener(|this, _, window, cx| this.choose_folder(window, cx))),
+            )
+            .when(self.folder.is_some(), |this| {
+                this.child(
+                    Button::new("runner-reload-folder")
+                        .label(reload_label)
+                        .disabled(running)
+                        .on_click(cx.listener(|this, _, _, cx| this.reload_folder(cx))),
+                )
+            })
+            .child(
+                Label::new(folder_status)
+                    .flex_1()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .child(Label::new(timeout_label).text_sm())
+            .child(div().w(px(120.)).child(IntegerInput::new(&self.timeout_ms)))
+    }
+
+    fn run_button(&self, id: &'static str, cx: &mut Context<Self>) -> Button {
+        if self.is_running() {
+            Button::new(id)
+                .danger()
+                .label(cx.global::<I18n>().t("button-stop"))
+                .on_click(cx.listener(|this, _, _, cx| this.stop(cx)))
+        } else {
+            Button::new(id)
+                .primary()
+                .label(cx.global::<I18n>().t("button-run"))
+                .disabled(self.entries.is_empty())
+        }
+    }
+
+    fn render_collection(&self, cx: &mut Context<Self>) -> AnyElement {
+        let (data_label, choose_label, clear_label, iterations_label, delay_label, data_status) = {
+            let i18n = cx.global::<I18n>();
+            let status = match (&self.data, &self.data_problem) {
+                (Some((path, rows)), _) => {
+                    let mut args = FluentArgs::new();
+                    args.set("count", rows.len());
+                    args.set(
+                        "file",
+                        path.file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_default(),
+                    );
+                    Some(i18n.t_with_args("runner-data-rows", &args))
+                }
+                (None, Some(problem)) => Some(i18n.t(data_problem_key(problem))),
+                (None, None) => None,
+            };
+            (
+                i18n.t("runner-data-file"),
+                i18n.t("runner-choose-data-file"),
+                i18n.t("runner-clear-data-file"),
+                i18n.t("field-runner-iterations"),
+                i18n.t("field-runner-delay-ms"),
+                status,
+            )
+        };
+        let running = self.is_running();
+        let data_color = if self.data_problem.is_some() {
+            cx.theme().danger
+        } else {
+            cx.theme().muted_foreground
+        };
+
+        let controls = v_form()
+            .p_2()
+            .child(
+                field().label(data_label).child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            Button::new("runner-choose-data-file")
+                                .small()
+                                .label(choose_label)
+                                .disabled(running)
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    this.choose_data_file(window, cx)
+                                })),
+                        )
+                        .when(self.data.is_some(), |this| {
+                            this.child(
+                                Button::new("runner-clear-data-file")
+                                    .small()
+                                    .label(clear_label)
+                                    .disabled(running)
+                                    .on_click(
+                                        cx.listener(|this, _, _, cx| this.clear_data_file(cx)),
+                                    ),
+                            )
+                        })
+                        .when_some(data_status, |this, status| {
+                            this.child(Label::new(status).text_sm().text_color(data_color))
+                        }),
+                ),
+            )
+            .child(
+                h_flex()
+                    .gap_4()
+                    .items_end()
+                    .child(
+                        field()
+                            .label(iterations_label)
+                            .w(px(160.))
+                            .child(IntegerInput::new(&self.iterations)),
+                    )
+                    .child(
+                        field()
+                            .label(delay_label)
+                            .w(px(220.))
+                            .child(IntegerInput::new(&self.delay_ms)),
+                    )
+                    .child(
+                        self.run_button("runner-run-collection", cx)
+                            .when(!running, |button| {
+                                button.on_click(cx.listener(|this, _, window, cx| {
+                                    this.start_collection(window, cx)
+                                }))
+                            }),
+                    ),
+            );
+
+        v_flex()
+            .size_full()
+            .overflow_hidden()
+            .child(controls)
+            .child(self.render_records(cx))
+            .into_any_element()
+    }
+
+    fn render_records(&self, cx: &mut Context<Self>) -> AnyElement {
+        if self.records.is_empty() {
+            return centered(cx.global::<I18n>().t("runner-records-empty"));
+        }
+        let passed = self
+            .records
+            .iter()
+            .filter(|record| record.outcome.passed())
+            .count();
+        let (summary, headers) = {
+            let i18n = cx.global::<I18n>();
+            let mut args = FluentArgs::new();
+            args.set("passed", passed);
+            args.set("failed", self.records.len() - passed);
+            (
+                i18n.t_with_args("runner-summary", &args),
+                [
+                    i18n.t("runner-column-iteration"),
+                    i18n.t("runner-column-request"),
+                    i18n.t("runner-column-status"),
+                    i18n.t("runner-column-time"),
+                    i18n.t("runner-column-checks"),
+                    i18n.t("runner-column-result"),
+                ],
+            )
+        };
+        let [iteration, request, status, time, checks, result] = headers;
+        let rows = self
+            .records
+            .iter()
+            .map(|record| record_row(record, cx))
+            .collect::<Vec<_>>();
+
+        div()
+            .flex_1()
+            .min_h(px(0.))
+            .overflow_scrollbar()
+            .child(
+                v_flex().p_2().gap_2().child(Label::new(summary)).child(
+                    Table::new()
+                        .small()
+                        .child(
+                            TableHeader::new().child(
+                                TableRow::new()
+                                    .child(TableHead::new().w(px(80.)).child(Label::new(iteration)))
+                                    .child(TableHead::new().child(Label::new(request)))
+                                    .child(TableHead::new().w(px(220.)).child(Label::new(status)))
+                                    .child(TableHead::new().w(px(90.)).child(Label::new(time)))
+                                    .child(TableHead::new().w(px(80.)).child(Label::new(checks)))
+                                    .child(TableHead::new().w(px(80.)).child(Label::new(result))),
+                            ),
+                        )
+                        .child(TableBody::new().children(rows)),
+                ),
+            )
+            .into_any_element()
+    }
+
+    fn render_load(&self, cx: &mut Context<Self>) -> AnyElement {
+        let (target_label, concurrency_label, duration_label, limit_label, limits_help, problem) = {
+            let i18n = cx.global::<I18n>();
+            (
+                i18n.t("runner-load-target"),
+                i18n.t("field-runner-concurrency"),
+                i18n.t("field-runner-duration-s"),
+                i18n.t("field-runner-request-limit"),
+                i18n.t("runner-load-limits-help"),
+                self.load_problem.map(|problem| {
+                    i18n.t(match problem {
+                        LoadProblem::Invalid => "runner-step-invalid",
+                        LoadProblem::Unbounded => "runner-load-unbounded",
+                    })
+                }),
+            )
+        };
+        let running = self.is_running();
+        let targets = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.request.is_ok())
+            .map(|(index, entry)| {
+                let button = Button::new(SharedString::from(format!("runner-load-target-{index}")))
+                    .small()
+                    .label(entry.name.clone())
+                    .disabled(running)
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.load_target = index;
+                        this.load_problem = None;
+                        cx.notify();
+                    }));
+                if index == self.load_target {
+                    button.primary()
+                } else {
+                    button
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let controls = v_form()
+            .p_2()
+            .child(
+                field()
+                    .label(target_label)
+                    .child(h_flex().flex_wrap().gap_1().children(targets)),
+            )
+            .child(
+                h_flex()
+                    .gap_4()
+                    .items_end()
+                    .child(
+                        field()
+                            .label(concurrency_label)
+                            .w(px(160.))
+                            .child(IntegerInput::new(&self.concurrency)),
+                    )
+                    .child(
+                        field()
+                            .label(duration_label)
+                            .w(px(160.))
+                            .child(IntegerInput::new(&self.duration_s)),
+                    )
+                    .child(
+                        field()
+                            .label(limit_label)
+                            .w(px(160.))
+                            .child(IntegerInput::new(&self.request_limit)),
+                    )
+                    .child(
+                        self.run_button("runner-run-load", cx)
+                            .when(!running, |button| {
+                                button.on_click(
+                                    cx.listener(|this, _, window, cx| this.start_load(window, cx)),
+                                )
+                            }),
+                    ),
+            )
+            .child(
+                Label::new(limits_help)
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground),
+            )
+            .when_some(problem, |this, problem| {
+                this.child(Label::new(problem).text_sm().text_color(cx.theme().danger))
+            });
+
+        let results = match &self.load {
+            Some(load) => render_load_summary(&load.summary(), cx),
+            None => centered(cx.global::<I18n>().t("runner-load-empty")),
+        };
+        v_flex()
+            .size_full()
+            .overflow_hidden()
+            .child(controls)
+            .child(results)
+            .into_any_element()
+    }
+}
+
+impl Render for RunnerView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let labels = {
+            let i18n = cx.global::<I18n>();
+            [i18n.t("tab-runner-collection"), i18n.t("tab-runner-load")]
+        };
+        let source = self.render_source(cx);
+        let content = match self.mode {
+            RunnerMode::Collection => self.render_collection(cx),
+            RunnerMode::Load => self.render_load(cx),
+        };
+        v_flex()
+            .size_full()
+            .overflow_hidden()
+            .child(source)
+            .child(
+                TabBar::new("runner-modes")
+                    .selected_index(self.mode.index())
+                    .on_click(cx.listener(|this, index, _, cx| {
+                        if let Some(mode) = RunnerMode::ALL.get(*index).copied() {
+                            this.mode = mode;
+                            cx.notify();
+                        }
+                    }))
+                    .children(labels.into_iter().map(|label| Tab::new().label(label))),
+            )
+            .child(div().flex_1().min_h(px(0.)).child(content))
+    }
+}
+
+fn record_row(record: &StepRecord, cx: &App) -> TableRow {
+    let i18n = cx.global::<I18n>();
+    let (status, time, checks) = match &record.outcome {
+        StepOutcome::Unreadable => (i18n.t("runner-step-unreadable"), None, None),
+        StepOutcome::Invalid => (i18n.t("runner-step-invalid"), None, None),
+        StepOutcome::Failed(kind) => (problem_message(*kind, cx), None, None),
+        StepOutcome::Completed {
+            status,
+            elapsed,
+            checks_passed,
+            checks_total,
+            ..
+        } => (
+            status.to_string(),
+            Some(format_latency(*elapsed)),
+            (*checks_total != 0).then(|| format!("{checks_passed}/{checks_total}")),
+        ),
+    };
+    let (result_key, result_color) = if record.outcome.passed() {
+        ("response-tests-pass", cx.theme().success)
+    } else {
+        ("response-tests-fail", cx.theme().danger)
+    };
+
+    TableRow::new()
+        .child(
+            TableCell::new()
+                .w(px(80.))
+                .child(Label::new((record.iteration + 1).to_string())),
+        )
+        .child(TableCell::new().child(Label::new(record.name.clone())))
+        .child(
+            TableCell::new()
+                .w(px(220.))
+                .child(Label::new(status).text_xs()),
+        )
+        .child(
+            TableCell::new()
+                .w(px(90.))
+                .child(Label::new(time.unwrap_or_default())),
+        )
+        .child(
+            TableCell::new()
+                .w(px(80.))
+                .child(Label::new(checks.unwrap_or_default())),
+        )
+        .child(
+            TableCell::new()
+                .w(px(80.))
+                .child(Label::new(i18n.t(result_key)).text_color(result_color)),
+        )
+}
+
+fn render_load_summary(summary: &LoadSummary, cx: &App) -> AnyElement {
+    let i18n = cx.global::<I18n>();
+    let mut throughput = FluentArgs::new();
+    throughput.set("value", format!("{:.1}", summary.throughput()));
+    let stats = [
+        (i18n.t("runner-load-requests"), summary.requests.to_string()),
+        (
+            i18n.t("runner-load-throughput"),
+            i18n.t_with_args("runner-load-throughput-value", &throughput),
+        ),
+        (
+            i18n.t("runner-load-error-rate"),
+            format!("{:.1}%", summary.error_rate() * 100.),
+        ),
+        ("p50".to_owned(), format_latency(summary.p50)),
+        ("p95".to_owned(), format_latency(summary.p95)),
+        ("p99".to_owned(), format_latency(summary.p99)),
+    ];
+    let tallest = summary
+        .histogram
+        .iter()
+        .map(|bucket| bucket.count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let bars = summary.histogram.iter().map(|bucket| {
+        div()
+            .flex_1()
+            .h_full()
+            .flex()
+            .flex_col()
+            .justify_end()
+            .child(
+                div()
+                    .w_full()
+                    .h(relative(bucket.count as f32 / tallest as f32))
+                    .bg(cx.theme().chart_1),
+            )
+    });
+    let range = summary
+        .histogram
+        .first()
+        .zip(summary.histogram.last())
+        .map(|(first, last)| (format_latency(first.start), format_latency(last.end)));
+
+    div()
+        .flex_1()
+        .min_h(px(0.))
+        .overflow_scrollbar()
+        .child(
+            v_flex()
+                .p_2()
+                .gap_3()
+                .child(
+                    h_flex()
+                        .gap_6()
+                        .children(stats.into_iter().map(|(label, value)| {
+                            v_flex()
+                                .child(
+                                    Label::new(label)
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground),
+                                )
+                                .child(Label::new(value).text_lg())
+                        })),
+                )
+                .child(Label::new(i18n.t("runner-load-histogram")).text_sm())
+                .child(
+                    h_flex()
+                        .h(px(120.))
+                        .gap_px()
+                        .items_end()
+                        .border_b_1()
+                        .border_color(cx.theme().border)
+                        .children(bars),
+                )
+                .when_some(range, |this, (fastest, slowest)| {
+                    this.child(
+                        h_flex()
+                            .justify_between()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(fastest)
+                            .child(slowest),
+                    )
+                }),
+        )
+        .into_any_element()
+}
+
+fn centered(message: String) -> AnyElement {
+    div()
+        .flex_1()
+        .min_h(px(0.))
+        .flex()
+        .items_center()
+        .justify_center()
+        .child(Label::new(message))
+        .into_any_element()
+}
+
+const fn data_problem_key(problem: &DataFileError) -> &'static str {
+    match problem {
+        DataFileError::Unreadable => "runner-data-unreadable",
+        DataFileError::NotUtf8 => "runner-data-not-utf8",
+        DataFileError::MissingHeader => "runner-data-missing-header",
+        DataFileError::InvalidColumn(_) => "runner-data-invalid-column",
+        DataFileError::RaggedRow { .. } => "runner-data-ragged-row",
+        DataFileError::UnterminatedQuote => "runner-data-unterminated-quote",
+    }
+}
+
+fn format_latency(duration: Duration) -> String {
+    format!("{} ms", duration.as_millis())
+}
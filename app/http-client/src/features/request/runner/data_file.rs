@@ -0,0 +1,191 @@
+use thiserror::Error;
+
+use crate::features::request::variables::{RequestVariables, validate_variable_name};
+
+/// A CSV data file whose header row names the variables each iteration
+/// sets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct DataRows {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub(super) enum DataFileError {
+    #[error("data file could not be read")]
+    Unreadable,
+    #[error("data file is not UTF-8")]
+    NotUtf8,
+    #[error("data file has no header row")]
+    MissingHeader,
+    #[error("data file column {0:?} is not a variable name")]
+    InvalidColumn(String),
+    #[error("data file row {row} has {found} fields instead of {expected}")]
+    RaggedRow {
+        row: usize,
+        found: usize,
+        expected: usize,
+    },
+    #[error("data file ends inside a quoted field")]
+    UnterminatedQuote,
+}
+
+impl DataRows {
+    pub(super) fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Writes the row for `iteration` into `variables`, starting over from
+    /// the first row once every row has been used.
+    pub(super) fn apply(&self, iteration: usize, variables: &RequestVariables) {
+        let Some(row) = self.rows.get(iteration % self.rows.len().max(1)) else {
+            return;
+        };
+        for (name, value) in self.columns.iter().zip(row) {
+            variables.set(name.clone(), value.clone());
+        }
+    }
+}
+
+/// Parses RFC 4180 CSV: comma separated, optionally quoted fields with `""`
+/// for a literal quote, and either line ending. Blank lines are skipped.
+pub(super) fn parse_data_file(bytes: &[u8]) -> Result<DataRows, DataFileError> {
+    let text = std::str::from_utf8(bytes).map_err(|_| DataFileError::NotUtf8)?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = parse_records(text)?.into_iter();
+
+    let columns: Vec<String> = records
+        .next()
+        .ok_or(DataFileError::MissingHeader)?
+        .into_iter()
+        .map(|column| column.trim().to_owned())
+        .collect();
+    for column in &columns {
+        validate_variable_name(column).map_err(|_| DataFileError::InvalidColumn(column.clone()))?;
+    }
+
+    let rows = records
+        .enumerate()
+        .map(|(index, row)| {
+            if row.len() == columns.len() {
+                Ok(row)
+            } else {
+                Err(DataFileError::RaggedRow {
+                    row: index + 1,
+                    found: row.len(),
+                    expected: columns.len(),
+                })
+            }
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(DataRows { columns, rows })
+}
+
+fn parse_records(text: &str) -> Result<Vec<Vec<String>>, DataFileError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut touched = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        if quoted {
+            match char {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(char),
+            }
+            continue;
+        }
+        match char {
+            '"' => {
+                quoted = true;
+                touched = true;
+            }
+            ',' => {
+                record.push(std::mem::take(&mut field));
+                touched = true;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                if touched || !field.is_empty() {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                touched = false;
+            }
+            _ => field.push(char),
+        }
+    }
+    if quoted {
+        return Err(DataFileError::UnterminatedQuote);
+    }
+    if touched || !field.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_fields_keep_commas_quotes_and_line_breaks() {
+        let rows = parse_data_file(
+            b"\xef\xbb\xbfuser,note\r\nalice,\"hello, \"\"world\"\"\"\r\n\r\nbob,\"two\nlines\"\n",
+        )
+        .unwrap();
+        assert_eq!(rows.columns, ["user", "note"]);
+        assert_eq!(
+            rows.rows,
+            [
+                vec!["alice".to_owned(), "hello, \"world\"".to_owned()],
+                vec!["bob".to_owned(), "two\nlines".to_owned()],
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_fields_survive_and_iterations_wrap_around_the_rows() {
+        let rows = parse_data_file(b"id,token\n1,\n2,b").unwrap();
+        let variables = RequestVariables::default();
+        rows.apply(0, &variables);
+        assert_eq!(
+            variables.entries(),
+            [("id".into(), "1".into()), ("token".into(), String::new())]
+        );
+        rows.apply(3, &variables);
+        assert_eq!(
+            variables.entries(),
+            [("id".into(), "2".into()), ("token".into(), "b".into())]
+        );
+    }
+
+    #[test]
+    fn malformed_files_name_the_problem() {
+        assert_eq!(parse_data_file(b""), Err(DataFileError::MissingHeader));
+        assert_eq!(
+            parse_data_file(b"ok,not valid\n1,2"),
+            Err(DataFileError::InvalidColumn("not valid".into()))
+        );
+        assert_eq!(
+            parse_data_file(b"a,b\n1,2\n3"),
+            Err(DataFileError::RaggedRow {
+                row: 2,
+                found: 1,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            parse_data_file(b"a\n\"open"),
+            Err(DataFileError::UnterminatedQuote)
+        );
+        assert_eq!(parse_data_file(&[0xff]), Err(DataFileError::NotUtf8));
+    }
+}
@@ -0,0 +1,509 @@
+//! `http-client-run`: sends saved requests from a terminal with the same
+//! compilation, transport and checks as the runner tab, so scripts and CI
+//! can share request files with the app.
+
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use serde_json::Value;
+use thiserror::Error;
+
+use self::report::{AssertionResult, ExtractionResult, RunReport, StepProblem, StepResult};
+use super::{
+    DEFAULT_TIMEOUT_MS,
+    step::{ResolvedRequest, execute_step},
+};
+use crate::features::request::{
+    response::{
+        BodyDecoding, CAPTURE_LIMIT_BYTES, ContentKind, ResponseData, classify_content_type,
+        decode_text, escape_header_value,
+    },
+    saved::{CollectionEntry, SavedRequestError, open_collection, open_request},
+    transport::{CookieJar, HttpTransport},
+    variables::{RequestVariables, validate_variable_name},
+};
+
+mod report;
+
+const USAGE: &str = "Usage: http-client-run <request.json|folder> [--env <file>] \
+                     [--var <name>=<value>]... [--timeout-ms <ms>] [--junit <file>] \
+                     [--json <file>]";
+
+const EXIT_PASSED: i32 = 0;
+const EXIT_FAILED: i32 = 1;
+/// The arguments, a file or the runtime kept the run from starting.
+const EXIT_ERROR: i32 = 2;
+
+#[derive(Debug, Default, PartialEq)]
+struct HeadlessArguments {
+    target: PathBuf,
+    environment: Option<PathBuf>,
+    variables: Vec<(String, String)>,
+    timeout_ms: u64,
+    junit: Option<PathBuf>,
+    json: Option<PathBuf>,
+}
+
+#[derive(Debug, Error)]
+enum HeadlessError {
+    #[error("{} could not be opened: {source}", .path.display())]
+    Open {
+        path: PathBuf,
+        #[source]
+        source: SavedRequestError,
+    },
+    #[error("environment file {} could not be read", .path.display())]
+    EnvironmentRead {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("environment file {} is not a JSON object: {source}", .path.display())]
+    EnvironmentMalformed {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("environment file {} has an invalid variable {name:?}", .path.display())]
+    EnvironmentVariable { path: PathBuf, name: String },
+    #[error("report {} could not be written", .path.display())]
+    Report {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("output could not be written")]
+    Output(#[from] io::Error),
+}
+
+/// Runs `http-client-run` with its command line arguments and returns the
+/// process exit code.
+pub fn run_headless(arguments: impl Iterator<Item = String>) -> i32 {
+    let Ok(arguments) = parse_arguments(arguments) else {
+        eprintln!("{USAGE}");
+        return EXIT_ERROR;
+    };
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            eprintln!("{error}");
+            return EXIT_ERROR;
+        }
+    };
+    let transport = HttpTransport::new(CookieJar::default());
+    let stdout = io::stdout();
+    match runtime.block_on(run(&arguments, transport, &mut stdout.lock())) {
+        Ok(report) if report.passed => EXIT_PASSED,
+        Ok(_) => EXIT_FAILED,
+        Err(error) => {
+            eprintln!("{error}");
+            EXIT_ERROR
+        }
+    }
+}
+
+fn parse_arguments(mut arguments: impl Iterator<Item = String>) -> Result<HeadlessArguments, ()> {
+    let mut parsed = HeadlessArguments {
+        timeout_ms: DEFAULT_TIMEOUT_MS,
+        ..HeadlessArguments::default()
+    };
+    let mut target = None;
+    while let Some(argument) = arguments.next() {
+        if !argument.starts_with("--") {
+            if target.replace(PathBuf::from(argument)).is_some() {
+                return Err(());
+            }
+            continue;
+        }
+        let value = arguments.next().ok_or(())?;
+        match argument.as_str() {
+            "--env" if parsed.environment.is_none() => parsed.environment = Some(value.into()),
+            "--var" => {
+                let (name, value) = value.split_once('=').ok_or(())?;
+                validate_variable_name(name).map_err(|_| ())?;
+                parsed.variables.push((name.to_owned(), value.to_owned()));
+            }
+            "--timeout-ms" => parsed.timeout_ms = value.parse().map_err(|_| ())?,
+            "--junit" if parsed.junit.is_none() => parsed.junit = Some(value.into()),
+            "--json" if parsed.json.is_none() => parsed.json = Some(value.into()),
+            _ => return Err(()),
+        }
+    }
+    parsed.target = target.ok_or(())?;
+    Ok(parsed)
+}
+
+/// Reads an environment file: a JSON object whose values are strings,
+/// numbers or booleans. Non-string values are used as their JSON text.
+fn load_environment(path: &Path) -> Result<Vec<(String, String)>, HeadlessError> {
+    let bytes = std::fs::read(path).map_err(|source| HeadlessError::EnvironmentRead {
+        path: path.to_owned(),
+        source,
+    })?;
+    let values: serde_json::Map<String, Value> =
+        serde_json::from_slice(&bytes).map_err(|source| HeadlessError::EnvironmentMalformed {
+            path: path.to_owned(),
+            source,
+        })?;
+    values
+        .into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::String(text) => Some(text),
+                Value::Number(_) | Value::Bool(_) => Some(value.to_string()),
+                Value::Null | Value::Array(_) | Value::Object(_) => None,
+            };
+            match value {
+                Some(value) if validate_variable_name(&name).is_ok() => Ok((name, value)),
+                _ => Err(HeadlessError::EnvironmentVariable {
+                    path: path.to_owned(),
+                    name,
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Sends a saved request, printing its response, or every request of a
+/// collection folder in file name order. Command-line variables override
+/// the environment, and extractions carry forward like in the runner tab.
+async fn run(
+    arguments: &HeadlessArguments,
+    transport: HttpTransport,
+    out: &mut impl Write,
+) -> Result<RunReport, HeadlessError> {
+    let variables = RequestVariables::default();
+    if let Some(path) = &arguments.environment {
+        for (name, value) in load_environment(path)? {
+            variables.set(name, value);
+        }
+    }
+    for (name, value) in &arguments.variables {
+        variables.set(name.clone(), value.clone());
+    }
+
+    let name = arguments
+        .target
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let open = |source| HeadlessError::Open {
+        path: arguments.target.clone(),
+        source,
+    };
+    let single = !arguments.target.is_dir();
+    let entries = if single {
+        vec![CollectionEntry {
+            name: name.clone(),
+            request: Ok(open_request(&arguments.target).map_err(open)?),
+        }]
+    } else {
+        open_collection(&arguments.target).map_err(open)?
+    };
+
+    let started = Instant::now();
+    let mut steps = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let (step, response) = run_step(&transport, &variables, entry, arguments.timeout_ms).await;
+        if single && let Some(response) = &response {
+            print_response(response, out).await?;
+        }
+        step.print(out)?;
+        steps.push(step);
+    }
+    let report = RunReport::new(name, steps, started.elapsed());
+    report.print_summary(out)?;
+
+    let write = |path: &PathBuf, contents: Vec<u8>| {
+        std::fs::write(path, contents).map_err(|source| HeadlessError::Report {
+            path: path.clone(),
+            source,
+        })
+    };
+    if let Some(path) = &arguments.junit {
+        write(path, report.junit().into_bytes())?;
+    }
+    if let Some(path) = &arguments.json {
+        write(path, report.json())?;
+    }
+    Ok(report)
+}
+
+async fn run_step(
+    transport: &HttpTransport,
+    variables: &RequestVariables,
+    entry: CollectionEntry,
+    timeout_ms: u64,
+) -> (StepResult, Option<Arc<ResponseData>>) {
+    let mut step = StepResult::new(entry.name);
+    let draft = match entry.request {
+        Ok(draft) => draft,
+        Err(error) => {
+            step.problem = Some(StepProblem::Unreadable(error.to_string()));
+            return (step, None);
+        }
+    };
+    let prepared = variables
+        .resolve(draft)
+        .and_then(|draft| ResolvedRequest { draft, timeout_ms }.prepare());
+    let (prepared, checks) = match prepared {
+        Ok(prepared) => prepared,
+        Err(error) => {
+            step.problem = Some(StepProblem::Invalid(error.to_string()));
+            return (step, None);
+        }
+    };
+    let response = match execute_step(transport.clone(), prepared, checks).await {
+        Ok(response) => response,
+        Err(problem) => {
+            step.problem = Some(StepProblem::Failed(problem.to_string()));
+            return (step, None);
+        }
+    };
+    for outcome in &response.report.extractions {
+        if let Ok(value) = &outcome.result {
+            variables.set(outcome.variable.clone(), value.clone());
+        }
+    }
+    step.passed = response.passed();
+    step.status = Some(response.status.as_u16());
+    step.elapsed_ms = Some(response.elapsed.as_millis() as u64);
+    step.assertions = response
+        .report
+        .assertions
+        .iter()
+        .map(AssertionResult::new)
+        .collect();
+    step.extractions = response
+        .report
+        .extractions
+        .iter()
+        .map(ExtractionResult::new)
+        .collect();
+    (step, Some(response.response))
+}
+
+/// Writes the status line, headers and body. Bodies that are not text, or
+/// whose content coding could not be undone, are summarized by size.
+async fn print_response(response: &Arc<ResponseData>, out: &mut impl Write) -> io::Result<()> {
+    let head = response.head();
+    writeln!(out, "{:?} {}", head.version, head.status)?;
+    for (name, value) in &head.headers {
+        writeln!(out, "{name}: {}", escape_header_value(value))?;
+    }
+    writeln!(out)?;
+    let bytes = match response
+        .read_lease()
+        .read_all_bounded(CAPTURE_LIMIT_BYTES)
+        .await
+    {
+        Ok(bytes) => bytes,
+        Err(_) => return writeln!(out, "[the body could not be read]"),
+    };
+    if bytes.is_empty() {
+        return Ok(());
+    }
+    let textual = response.body_decoding() != BodyDecoding::Unsupported
+        && match classify_content_type(&head.headers) {
+            ContentKind::Text(_)
+            | ContentKind::Json
+            | ContentKind::Xml
+            | ContentKind::EventStream
+            | ContentKind::Form => true,
+            // Without a usable content type, print bodies that are valid UTF-8.
+            ContentKind::Bytes => std::str::from_utf8(&bytes).is_ok(),
+            ContentKind::Image
+            | ContentKind::Audio
+            | ContentKind::Pdf
+            | ContentKind::Protobuf
+            | ContentKind::MessagePack
+            | ContentKind::Cbor => false,
+        };
+    match textual
+        .then(|| decode_text(&bytes, &head.headers, true).ok())
+        .flatten()
+    {
+        Some(text) => {
+            out.write_all(text.as_bytes())?;
+            if !text.ends_with('\n') {
+                writeln!(out)?;
+            }
+        }
+        None => writeln!(out, "[{} bytes of binary data]", bytes.len())?,
+    }
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use http_client_test_server::{MockRoutes, TestServer};
+
+    use super::*;
+    use crate::features::request::{
+        draft::{
+            AssertionDraft, AssertionKind, BearerAuthDraft, ExtractionDraft, RequestAuthDraft,
+            RequestBodyDraft, RequestDraft, TextBodyDraft, TextBodyFormat,
+        },
+        method::HttpMethod,
+        saved::save_request,
+    };
+
+    fn arguments(values: &[&str]) -> Result<HeadlessArguments, ()> {
+        parse_arguments(values.iter().map(|value| (*value).to_owned()))
+    }
+
+    #[test]
+    fn arguments_need_one_target_and_valid_variables() {
+        assert_eq!(
+            arguments(&["smoke", "--var", "host=example.test", "--timeout-ms", "0"]),
+            Ok(HeadlessArguments {
+                target: "smoke".into(),
+                variables: vec![("host".into(), "example.test".into())],
+                timeout_ms: 0,
+                ..HeadlessArguments::default()
+            })
+        );
+        assert_eq!(
+            arguments(&["--junit", "junit.xml", "login.json"]).map(|parsed| parsed.timeout_ms),
+            Ok(DEFAULT_TIMEOUT_MS)
+        );
+        assert_eq!(arguments(&[]), Err(()));
+        assert_eq!(arguments(&["a", "b"]), Err(()));
+        assert_eq!(arguments(&["a", "--var", "1st=x"]), Err(()));
+        assert_eq!(arguments(&["a", "--env", "x", "--env", "y"]), Err(()));
+        assert_eq!(arguments(&["a", "--timeout-ms"]), Err(()));
+    }
+
+    #[tokio::test]
+    async fn collections_chain_variables_and_write_reports() {
+        let server = TestServer::spawn_mock(
+            MockRoutes::from_json(
+                br#"{"routes": [
+                    {
+                        "method": "POST",
+                        "path": "/login",
+                        "responses": [{"body": {"kind": "json", "value": {"token": "t-1"}}}]
+                    },
+                    {
+                        "path": "/items",
+                        "headers": {"authorization": "Bearer t-1"},
+                        "responses": [{"body": {"kind": "json", "value": {"count": 2}}}]
+                    }
+                ]}"#,
+            )
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+        let base_url = server.base_url();
+        let folder = tempfile::tempdir().unwrap();
+        let collection = folder.path().join("smoke");
+        std::fs::create_dir(&collection).unwrap();
+        save_request(
+            &collection.join("10-login.json"),
+            &RequestDraft {
+                method: HttpMethod::Post,
+                url: format!("{base_url}/login"),
+                body: RequestBodyDraft::Text(TextBodyDraft {
+                    format: TextBodyFormat::Json,
+                    content: r#"{"user":"{{user}}"}"#.into(),
+                }),
+                extractions: vec![ExtractionDraft {
+                    variable: "token".into(),
+                    expression: "$.token".into(),
+                    ..ExtractionDraft::default()
+                }],
+                ..RequestDraft::default()
+            },
+        )
+        .unwrap();
+        let items = RequestDraft {
+            url: format!("{base_url}/items"),
+            auth: RequestAuthDraft::Bearer(BearerAuthDraft {
+                token: "{{token}}".into(),
+            }),
+            assertions: vec![AssertionDraft {
+                kind: AssertionKind::JsonPathEquals,
+                target: "$.count".into(),
+                expected: "3".into(),
+                ..AssertionDraft::default()
+            }],
+            ..RequestDraft::default()
+        };
+        save_request(&collection.join("20-items.json"), &items).unwrap();
+        std::fs::write(collection.join("30-broken.json"), "{").unwrap();
+        let environment = folder.path().join("local.json");
+        std::fs::write(&environment, r#"{"user": "ada", "retries": 2}"#).unwrap();
+
+        let arguments = HeadlessArguments {
+            target: collection,
+            environment: Some(environment),
+            timeout_ms: 5_000,
+            junit: Some(folder.path().join("junit.xml")),
+            json: Some(folder.path().join("report.json")),
+            ..HeadlessArguments::default()
+        };
+        let mut output = Vec::new();
+        let report = run(&arguments, HttpTransport::new_without_proxy(), &mut output)
+            .await
+            .unwrap();
+        assert!(!report.passed);
+        let steps: Vec<_> = report
+            .steps
+            .iter()
+            .map(|step| (step.name.as_str(), step.status, step.passed))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                ("10-login", Some(200), true),
+                ("20-items", Some(200), false),
+                ("30-broken", None, false),
+            ]
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("FAIL 20-items"), "{output}");
+        assert!(output.contains("$.count equals 3: got 2"), "{output}");
+        assert!(output.contains("3 requests: 1 passed, 1 failed, 1 errors"));
+
+        let junit = std::fs::read_to_string(folder.path().join("junit.xml")).unwrap();
+        assert!(junit.contains(r#"<testsuite name="smoke" tests="3" failures="1" errors="1""#));
+        let json: Value =
+            serde_json::from_slice(&std::fs::read(folder.path().join("report.json")).unwrap())
+                .unwrap();
+        assert_eq!(json["steps"][0]["extractions"][0]["value"], "t-1");
+        assert_eq!(json["steps"][2]["problem"]["kind"], "unreadable");
+
+        // A single request prints its response.
+        let single = HeadlessArguments {
+            target: folder.path().join("single.json"),
+            variables: vec![("token".into(), "t-1".into())],
+            timeout_ms: 5_000,
+            ..HeadlessArguments::default()
+        };
+        save_request(
+            &single.target,
+            &RequestDraft {
+                assertions: Vec::new(),
+                ..items
+            },
+        )
+        .unwrap();
+        let mut output = Vec::new();
+        let report = run(&single, HttpTransport::new_without_proxy(), &mut output)
+            .await
+            .unwrap();
+        assert!(report.passed);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("HTTP/1.1 200 OK\n"), "{output}");
+        assert!(output.contains("\n\n{\"count\":2}\n"), "{output}");
+        server.shutdown().await.unwrap();
+    }
+}
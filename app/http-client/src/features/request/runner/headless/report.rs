@@ -0,0 +1,325 @@
+use std::{fmt::Write as _, io, time::Duration};
+
+use serde::Serialize;
+
+use crate::features::request::{
+    checks::{AssertionOutcome, CheckFailure, ExtractionOutcome},
+    draft::AssertionKind,
+};
+
+/// Everything a headless run produced, as written by `--json`.
+#[derive(Serialize)]
+pub(super) struct RunReport {
+    pub(super) name: String,
+    pub(super) passed: bool,
+    pub(super) elapsed_ms: u64,
+    pub(super) steps: Vec<StepResult>,
+}
+
+#[derive(Serialize)]
+pub(super) struct StepResult {
+    pub(super) name: String,
+    pub(super) status: Option<u16>,
+    pub(super) elapsed_ms: Option<u64>,
+    pub(super) passed: bool,
+    /// Why no response was checked.
+    pub(super) problem: Option<StepProblem>,
+    pub(super) assertions: Vec<AssertionResult>,
+    pub(super) extractions: Vec<ExtractionResult>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub(super) enum StepProblem {
+    /// The saved request file could not be opened.
+    Unreadable(String),
+    /// The request did not compile after variables were substituted.
+    Invalid(String),
+    /// The request was sent but no response came back.
+    Failed(String),
+}
+
+#[derive(Serialize)]
+pub(super) struct AssertionResult {
+    kind: AssertionKind,
+    target: String,
+    expected: String,
+    passed: bool,
+    actual: Option<String>,
+    failure: Option<String>,
+}
+
+#[derive(Serialize)]
+pub(super) struct ExtractionResult {
+    variable: String,
+    value: Option<String>,
+    failure: Option<String>,
+}
+
+impl RunReport {
+    pub(super) fn new(name: String, steps: Vec<StepResult>, elapsed: Duration) -> Self {
+        Self {
+            name,
+            passed: steps.iter().all(|step| step.passed),
+            elapsed_ms: elapsed.as_millis() as u64,
+            steps,
+        }
+    }
+
+    fn count(&self, outcome: impl Fn(&StepResult) -> bool) -> usize {
+        self.steps.iter().filter(|step| outcome(step)).count()
+    }
+
+    fn failures(&self) -> usize {
+        self.count(|step| step.problem.is_none() && !step.passed)
+    }
+
+    fn errors(&self) -> usize {
+        self.count(|step| step.problem.is_some())
+    }
+
+    pub(super) fn print_summary(&self, out: &mut impl io::Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "{} requests: {} passed, {} failed, {} errors ({} ms)",
+            self.steps.len(),
+            self.count(|step| step.passed),
+            self.failures(),
+            self.errors(),
+            self.elapsed_ms,
+        )
+    }
+
+    pub(super) fn json(&self) -> Vec<u8> {
+        serde_json::to_vec_pretty(self).expect("run report serializes")
+    }
+
+    /// One `testsuite` with a `testcase` per request. Failed assertions are
+    /// `failure`s, requests that never produced a response are `error`s.
+    pub(super) fn junit(&self) -> String {
+        let seconds = |ms: u64| ms as f64 / 1000.0;
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">",
+            self.steps.len(),
+            self.failures(),
+            self.errors(),
+            seconds(self.elapsed_ms),
+        );
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">",
+            escape_xml(&self.name),
+            self.steps.len(),
+            self.failures(),
+            self.errors(),
+            seconds(self.elapsed_ms),
+        );
+        for step in &self.steps {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(&step.name),
+                escape_xml(&self.name),
+                seconds(step.elapsed_ms.unwrap_or_default()),
+            );
+            if let Some(problem) = &step.problem {
+                let (kind, message) = problem.parts();
+                let _ = writeln!(
+                    xml,
+                    ">\n      <error type=\"{kind}\" message=\"{}\"/>\n    </testcase>",
+                    escape_xml(message),
+                );
+            } else if !step.passed {
+                let details = step.failure_lines().join("\n");
+                let _ = writeln!(
+                    xml,
+                    ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                    escape_xml(&step.failure_summary()),
+                    escape_xml(&details),
+                );
+            } else {
+                xml.push_str("/>\n");
+            }
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+impl StepResult {
+    pub(super) fn new(name: String) -> Self {
+        Self {
+            name,
+            status: None,
+            elapsed_ms: None,
+            passed: false,
+            problem: None,
+            assertions: Vec::new(),
+            extractions: Vec::new(),
+        }
+    }
+
+    /// Writes the verdict line followed by one line per check.
+    pub(super) fn print(&self, out: &mut impl io::Write) -> io::Result<()> {
+        if let Some(problem) = &self.problem {
+            return writeln!(out, "ERROR {}: {}", self.name, problem.parts().1);
+        }
+        writeln!(
+            out,
+            "{} {} ({}, {} ms)",
+            if self.passed { "PASS" } else { "FAIL" },
+            self.name,
+            self.status.unwrap_or_default(),
+            self.elapsed_ms.unwrap_or_default(),
+        )?;
+        for assertion in &self.assertions {
+            let mark = if assertion.passed { '✓' } else { '✗' };
+            match &assertion.failure {
+                Some(failure) => writeln!(out, "  {mark} {}: {failure}", assertion.describe())?,
+                None => writeln!(out, "  {mark} {}", assertion.describe())?,
+            }
+        }
+        for extraction in &self.extractions {
+            match (&extraction.value, &extraction.failure) {
+                (Some(value), _) => writeln!(out, "  ✓ {} = {value}", extraction.variable)?,
+                (None, failure) => writeln!(
+                    out,
+                    "  ✗ {}: {}",
+                    extraction.variable,
+                    failure.as_deref().unwrap_or_default()
+                )?,
+            }
+        }
+        Ok(())
+    }
+
+    fn failure_lines(&self) -> Vec<String> {
+        self.assertions
+            .iter()
+            .filter(|assertion| !assertion.passed)
+            .map(|assertion| {
+                let failure = assertion.failure.as_deref().unwrap_or_default();
+                format!("{}: {failure}", assertion.describe())
+            })
+            .collect()
+    }
+
+    fn failure_summary(&self) -> String {
+        if self.assertions.is_empty() {
+            format!("status {}", self.status.unwrap_or_default())
+        } else {
+            let failed = self.assertions.iter().filter(|check| !check.passed).count();
+            format!("{failed} of {} assertions failed", self.assertions.len())
+        }
+    }
+}
+
+impl StepProblem {
+    fn parts(&self) -> (&'static str, &str) {
+        match self {
+            Self::Unreadable(message) => ("unreadable", message),
+            Self::Invalid(message) => ("invalid", message),
+            Self::Failed(message) => ("failed", message),
+        }
+    }
+}
+
+impl AssertionResult {
+    pub(super) fn new(outcome: &AssertionOutcome) -> Self {
+        let label = &outcome.label;
+        let actual = match &outcome.result {
+            Err(CheckFailure::Mismatch { actual }) => Some(actual.clone()),
+            _ => None,
+        };
+        Self {
+            kind: label.kind,
+            target: label.target.clone(),
+            expected: label.expected.clone(),
+            passed: outcome.result.is_ok(),
+            actual,
+            failure: outcome.result.as_ref().err().map(failure_text),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let (target, expected) = (&self.target, &self.expected);
+        match self.kind {
+            AssertionKind::StatusRange => format!("status is {expected}"),
+            AssertionKind::HeaderPresent => format!("header {target} is present"),
+            AssertionKind::HeaderMatches => format!("header {target} matches {expected}"),
+            AssertionKind::JsonPathEquals => format!("{target} equals {expected}"),
+            AssertionKind::JsonPathMatches => format!("{target} matches {expected}"),
+            AssertionKind::ResponseTimeBelow => format!("response time is below {expected} ms"),
+        }
+    }
+}
+
+impl ExtractionResult {
+    pub(super) fn new(outcome: &ExtractionOutcome) -> Self {
+        Self {
+            variable: outcome.variable.clone(),
+            value: outcome.result.as_ref().ok().cloned(),
+            failure: outcome.result.as_ref().err().map(failure_text),
+        }
+    }
+}
+
+fn failure_text(failure: &CheckFailure) -> String {
+    match failure {
+        CheckFailure::Mismatch { actual } => format!("got {actual}"),
+        CheckFailure::Missing => "nothing matched".into(),
+        CheckFailure::BodyUnavailable => "the body could not be read".into(),
+        CheckFailure::BodyNotJson => "the body is not JSON".into(),
+    }
+}
+
+/// Escapes text for both attributes and element content. Control
+/// characters XML 1.0 cannot carry become U+FFFD.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(character),
+            character if character.is_control() => escaped.push('\u{FFFD}'),
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn junit_escapes_names_and_marks_failures_and_errors() {
+        let mut passed = StepResult::new("ok".into());
+        passed.passed = true;
+        passed.status = Some(200);
+        let mut failed = StepResult::new("a<b>&\"c\"".into());
+        failed.status = Some(500);
+        let mut broken = StepResult::new("broken".into());
+        broken.problem = Some(StepProblem::Invalid("bad\u{1}url".into()));
+        let report = RunReport::new(
+            "suite".into(),
+            vec![passed, failed, broken],
+            Duration::from_millis(1500),
+        );
+
+        let junit = report.junit();
+        assert!(junit.contains(
+            r#"<testsuite name="suite" tests="3" failures="1" errors="1" time="1.500">"#
+        ));
+        assert!(junit.contains(r#"<testcase name="ok" classname="suite" time="0.000"/>"#));
+        assert!(junit.contains(r#"name="a&lt;b&gt;&amp;&quot;c&quot;""#));
+        assert!(junit.contains(r#"<failure message="status 500">"#));
+        assert!(junit.contains("<error type=\"invalid\" message=\"bad\u{FFFD}url\"/>"));
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use tokio::task::JoinSet;
+
+use super::step::{ResolvedRequest, execute_step};
+use crate::features::request::transport::HttpTransport;
+
+const HISTOGRAM_BUCKETS: usize = 20;
+
+/// How hard and for how long to drive one request. At least one of the two
+/// limits is set; when both are, whichever is reached first ends the run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct LoadPlan {
+    pub(super) concurrency: usize,
+    pub(super) duration: Option<Duration>,
+    pub(super) requests: Option<u64>,
+}
+
+#[derive(Default)]
+struct Samples {
+    latencies: Vec<Duration>,
+    errors: u64,
+}
+
+/// Shared between the workers, which add samples, and the view, which
+/// reads a summary while the run is still going.
+#[derive(Clone, Default)]
+pub(super) struct LoadRecorder {
+    samples: Arc<Mutex<Samples>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct LoadSummary {
+    pub(super) requests: u64,
+    pub(super) errors: u64,
+    pub(super) elapsed: Duration,
+    pub(super) p50: Duration,
+    pub(super) p95: Duration,
+    pub(super) p99: Duration,
+    pub(super) histogram: Vec<HistogramBucket>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct HistogramBucket {
+    pub(super) start: Duration,
+    pub(super) end: Duration,
+    pub(super) count: u64,
+}
+
+impl LoadRecorder {
+    fn record(&self, latency: Duration, passed: bool) {
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.latencies.push(latency);
+            if !passed {
+                samples.errors += 1;
+            }
+        }
+    }
+
+    pub(super) fn summary(&self, elapsed: Duration) -> LoadSummary {
+        let (mut latencies, errors) = self
+            .samples
+            .lock()
+            .map(|samples| (samples.latencies.clone(), samples.errors))
+            .unwrap_or_default();
+        latencies.sort_unstable();
+        LoadSummary {
+            requests: latencies.len() as u64,
+            errors,
+            elapsed,
+            p50: percentile(&latencies, 50),
+            p95: percentile(&latencies, 95),
+            p99: percentile(&latencies, 99),
+            histogram: histogram(&latencies),
+        }
+    }
+}
+
+impl LoadSummary {
+    /// Completed requests per second over the whole run.
+    pub(super) fn throughput(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0. {
+            self.requests as f64 / seconds
+        } else {
+            0.
+        }
+    }
+
+    pub(super) fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.
+        } else {
+            self.errors as f64 / self.requests as f64
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted latencies.
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Equal-width buckets from the fastest to the slowest request.
+fn histogram(sorted: &[Duration]) -> Vec<HistogramBucket> {
+    let (Some(&fastest), Some(&slowest)) = (sorted.first(), sorted.last()) else {
+        return Vec::new();
+    };
+    let span = slowest - fastest;
+    if span.is_zero() {
+        return vec![HistogramBucket {
+            start: fastest,
+            end: slowest,
+            count: sorted.len() as u64,
+        }];
+    }
+    let width = span / HISTOGRAM_BUCKETS as u32;
+    let mut buckets: Vec<_> = (0..HISTOGRAM_BUCKETS)
+        .map(|index| HistogramBucket {
+            start: fastest + width * index as u32,
+            end: fastest + width * (index as u32 + 1),
+            count: 0,
+        })
+        .collect();
+    if let Some(last) = buckets.last_mut() {
+        last.end = slowest;
+    }
+    for latency in sorted {
+        let offset = (*latency - fastest).as_secs_f64() / span.as_secs_f64();
+        let index = ((offset * HISTOGRAM_BUCKETS as f64) as usize).min(HISTOGRAM_BUCKETS - 1);
+        buckets[index].count += 1;
+    }
+    buckets
+}
+
+/// Fires `target` from `plan.concurrency` workers until a limit is reached.
+/// Each worker sends its next request as soon as the previous one finishes.
+/// Dropping the future stops every worker.
+pub(super) async fn run_load(
+    transport: HttpTransport,
+    target: ResolvedRequest,
+    plan: LoadPlan,
+    recorder: LoadRecorder,
+) {
+    let deadline = plan.duration.map(|duration| Instant::now() + duration);
+    let issued = Arc::new(AtomicU64::new(0));
+    let target = Arc::new(target);
+    let mut workers = JoinSet::new();
+    for _ in 0..plan.concurrency.max(1) {
+        let (transport, target, recorder, issued) = (
+            transport.clone(),
+            target.clone(),
+            recorder.clone(),
+            issued.clone(),
+        );
+        workers.spawn(async move {
+            loop {
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    break;
+                }
+                if let Some(limit) = plan.requests
+                    && issued.fetch_add(1, Ordering::Relaxed) >= limit
+                {
+                    break;
+                }
+                let started = Instant::now();
+                let Ok((prepared, checks)) = target.prepare() else {
+                    // The target compiled before the run started, so this
+                    // only happens when a file it reads has gone away.
+                    recorder.record(started.elapsed(), false);
+                    break;
+                };
+                let passed = execute_step(transport.clone(), prepared, checks)
+                    .await
+                    .is_ok_and(|response| response.passed());
+                recorder.record(started.elapsed(), passed);
+            }
+        });
+    }
+    while workers.join_next().await.is_some() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use http_client_test_server::{RespondSpec, TestServer};
+
+    use super::*;
+    use crate::features::request::draft::RequestDraft;
+
+    fn ms(value: u64) -> Duration {
+        Duration::from_millis(value)
+    }
+
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        let recorder = LoadRecorder::default();
+        for latency in (1..=100).rev() {
+            recorder.record(ms(latency), latency % 10 != 0);
+        }
+        let summary = recorder.summary(Duration::from_secs(4));
+        assert_eq!(summary.requests, 100);
+        assert_eq!(summary.errors, 10);
+        assert_eq!(
+            (summary.p50, summary.p95, summary.p99),
+            (ms(50), ms(95), ms(99))
+        );
+        assert_eq!(summary.throughput(), 25.);
+        assert_eq!(summary.error_rate(), 0.1);
+    }
+
+    #[test]
+    fn the_histogram_spans_fastest_to_slowest_and_counts_every_request() {
+        let recorder = LoadRecorder::default();
+        for latency in [10, 10, 12, 30, 50, 110] {
+            recorder.record(ms(latency), true);
+        }
+        let histogram = recorder.summary(Duration::from_secs(1)).histogram;
+        assert_eq!(histogram.len(), HISTOGRAM_BUCKETS);
+        assert_eq!(histogram[0].start, ms(10));
+        assert_eq!(histogram[HISTOGRAM_BUCKETS - 1].end, ms(110));
+        assert_eq!(histogram.iter().map(|bucket| bucket.count).sum::<u64>(), 6);
+        assert_eq!(histogram[0].count, 3);
+        assert_eq!(histogram[HISTOGRAM_BUCKETS - 1].count, 1);
+
+        let recorder = LoadRecorder::default();
+        recorder.record(ms(7), true);
+        recorder.record(ms(7), true);
+        assert_eq!(
+            recorder.summary(Duration::from_secs(1)).histogram,
+            [HistogramBucket {
+                start: ms(7),
+                end: ms(7),
+                count: 2
+            }]
+        );
+        assert!(
+            LoadRecorder::default()
+                .summary(Duration::ZERO)
+                .histogram
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn count_limited_runs_send_exactly_that_many_requests() {
+        let server = TestServer::spawn().await.unwrap();
+        let url = |status| {
+            server
+                .respond_url(&RespondSpec {
+                    status,
+                    delay_before_headers_ms: 5,
+                    ..RespondSpec::default()
+                })
+                .unwrap()
+        };
+        let plan = LoadPlan {
+            concurrency: 3,
+            duration: None,
+            requests: Some(10),
+        };
+        for (status, errors) in [(200, 0), (503, 10)] {
+            let recorder = LoadRecorder::default();
+            let target = ResolvedRequest {
+                draft: RequestDraft {
+                    url: url(status),
+                    ..RequestDraft::default()
+                },
+                timeout_ms: 5_000,
+            };
+            run_load(
+                HttpTransport::new_without_proxy(),
+                target,
+                plan,
+                recorder.clone(),
+            )
+            .await;
+            let summary = recorder.summary(Duration::from_secs(1));
+            assert_eq!((summary.requests, summary.errors), (10, errors));
+            assert!(summary.p50 >= ms(5));
+        }
+        server.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn duration_limited_runs_stop_issuing_at_the_deadline() {
+        let server = TestServer::spawn().await.unwrap();
+        let target = ResolvedRequest {
+            draft: RequestDraft {
+                url: server
+                    .respond_url(&RespondSpec {
+                        delay_before_headers_ms: 20,
+                        ..RespondSpec::default()
+                    })
+                    .unwrap(),
+                ..RequestDraft::default()
+            },
+            timeout_ms: 5_000,
+        };
+        let recorder = LoadRecorder::default();
+        let started = Instant::now();
+        run_load(
+            HttpTransport::new_without_proxy(),
+            target,
+            LoadPlan {
+                concurrency: 2,
+                duration: Some(ms(150)),
+                requests: None,
+            },
+            recorder.clone(),
+        )
+        .await;
+        let summary = recorder.summary(started.elapsed());
+        assert!(summary.requests >= 2, "{summary:?}");
+        // Requests already in flight at the deadline still finish.
+        assert!(started.elapsed() < ms(150 + 500));
+        assert_eq!(summary.errors, 0);
+        server.shutdown().await.unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+use std::{sync::Arc, time::Duration};
+
+use http::StatusCode;
+
+use crate::features::request::{
+    checks::{CheckReport, ResponseChecks, compile_checks, run_checks},
+    draft::{HttpClientTransportSettings, RequestDraft},
+    prepared::{PreparedRequest, RequestCompileError, compile_request},
+    response::ResponseData,
+    runtime::RequestProblem,
+    transport::HttpTransport,
+};
+
+/// A draft with its variables already substituted, compiled again for every
+/// send so each request gets a fresh body.
+pub(super) struct ResolvedRequest {
+    pub(super) draft: RequestDraft,
+    pub(super) timeout_ms: u64,
+}
+
+impl ResolvedRequest {
+    pub(super) fn prepare(&self) -> Result<(PreparedRequest, ResponseChecks), RequestCompileError> {
+        let settings = HttpClientTransportSettings::default();
+        settings.set_timeout_ms(self.timeout_ms);
+        let checks = compile_checks(&self.draft.assertions, &self.draft.extractions)?;
+        let request = compile_request(self.draft.clone(), &settings)?;
+        Ok((request, checks))
+    }
+}
+
+/// What one runner request came back with.
+pub(super) struct StepResponse {
+    pub(super) status: StatusCode,
+    pub(super) elapsed: Duration,
+    pub(super) report: CheckReport,
+    pub(super) response: Arc<ResponseData>,
+}
+
+impl StepResponse {
+    /// Every assertion has to hold. A request without assertions passes
+    /// unless the server answered with a client or server error.
+    pub(super) fn passed(&self) -> bool {
+        if self.report.assertions.is_empty() {
+            !(self.status.is_client_error() || self.status.is_server_error())
+        } else {
+            self.report.passed() == self.report.assertions.len()
+        }
+    }
+}
+
+pub(super) async fn execute_step(
+    transport: HttpTransport,
+    prepared: PreparedRequest,
+    checks: ResponseChecks,
+) -> Result<StepResponse, RequestProblem> {
+    let response = transport.exchange(prepared).await?;
+    let status = response.head().status;
+    let elapsed = response.timing().completed_after;
+    let report = run_checks(checks, Arc::clone(&response)).await;
+    Ok(StepResponse {
+        status,
+        elapsed,
+        report,
+        response,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use http_client_test_server::{RespondSpec, ResponseBodySpec, TestServer};
+
+    use super::*;
+    use crate::features::request::{
+        draft::{AssertionDraft, AssertionKind, ExtractionDraft},
+        runtime::RequestProblemKind,
+    };
+
+    fn target(url: String, assertions: Vec<AssertionDraft>) -> ResolvedRequest {
+        ResolvedRequest {
+            draft: RequestDraft {
+                url,
+                assertions,
+                extractions: vec![ExtractionDraft {
+                    variable: "user_id".into(),
+                    expression: "$.id".into(),
+                    ..ExtractionDraft::default()
+                }],
+                ..RequestDraft::default()
+            },
+            timeout_ms: 5_000,
+        }
+    }
+
+    async fn send(target: &ResolvedRequest) -> Result<StepResponse, RequestProblem> {
+        let (prepared, checks) = target.prepare().unwrap();
+        execute_step(HttpTransport::new_without_proxy(), prepared, checks).await
+    }
+
+    #[tokio::test]
+    async fn steps_pass_on_their_assertions_or_else_on_the_status() {
+        let server = TestServer::spawn().await.unwrap();
+        let created = server
+            .respond_url(&RespondSpec {
+                status: 201,
+                body: ResponseBodySpec::Json {
+                    value: serde_json::json!({ "id": 7 }),
+                },
+                ..RespondSpec::default()
+            })
+            .unwrap();
+        let missing = server
+            .respond_url(&RespondSpec {
+                status: 404,
+                ..RespondSpec::default()
+            })
+            .unwrap();
+
+        let response = send(&target(created.clone(), Vec::new())).await.unwrap();
+        assert_eq!(response.status, StatusCode::CREATED);
+        assert!(response.passed());
+        assert_eq!(response.report.extractions[0].result, Ok("7".into()));
+
+        let expects_ok = AssertionDraft {
+            kind: AssertionKind::StatusRange,
+            expected: "200".into(),
+            ..AssertionDraft::default()
+        };
+        let response = send(&target(created, vec![expects_ok])).await.unwrap();
+        assert!(!response.passed());
+
+        let accepts_missing = AssertionDraft {
+            expected: "404".into(),
+            ..AssertionDraft::default()
+        };
+        assert!(
+            !send(&target(missing.clone(), Vec::new()))
+                .await
+                .unwrap()
+                .passed()
+        );
+        assert!(
+            send(&target(missing, vec![accepts_missing]))
+                .await
+                .unwrap()
+                .passed()
+        );
+        server.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_step_that_times_out_reports_the_problem() {
+        let server = TestServer::spawn().await.unwrap();
+        let slow = server
+            .respond_url(&RespondSpec {
+                delay_before_headers_ms: 500,
+                ..RespondSpec::default()
+            })
+            .unwrap();
+        let mut target = target(slow, Vec::new());
+        target.timeout_ms = 20;
+        let problem = send(&target).await.err().unwrap();
+        assert_eq!(problem.kind(), RequestProblemKind::Timeout);
+        server.shutdown().await.unwrap();
+    }
+}
@@ -291,6 +291,23 @@ pub(crate) enum RedirectProblemKind {
     HopLimit,
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum AuthorizationProblemKind {
+    TokenEndpoint,
+    TokenRejected,
+    InvalidTokenResponse,
+    Denied,
+    Callback,
+}
+
+/// A connection setting that could not be turned into a client.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ConnectionProblemKind {
+    CaCertificate,
+    ClientIdentity,
+    Proxy,
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) enum BodySizeDimension {
     Encoded,
@@ -302,6 +319,8 @@ pub(crate) enum RequestProblemKind {
     Transport,
     Timeout,
     Redirect(RedirectProblemKind),
+    Authorization(AuthorizationProblemKind),
+    Connection(ConnectionProblemKind),
     RequestBodyRead,
     ResponseBodyRead,
     ResponseBodyDecode,
@@ -337,6 +356,24 @@ impl RequestProblem {
         Self::without_source(RequestProblemKind::Redirect(kind))
     }
 
+    pub(crate) const fn authorization(kind: AuthorizationProblemKind) -> Self {
+        Self::without_source(RequestProblemKind::Authorization(kind))
+    }
+
+    pub(crate) fn authorization_transport(source: impl Error + Send + Sync + 'static) -> Self {
+        Self::with_source(
+            RequestProblemKind::Authorization(AuthorizationProblemKind::TokenEndpoint),
+            source,
+        )
+    }
+
+    pub(crate) fn connection(
+        kind: ConnectionProblemKind,
+        source: impl Error + Send + Sync + 'static,
+    ) -> Self {
+        Self::with_source(RequestProblemKind::Connection(kind), source)
+    }
+
     pub(crate) fn request_body_read(source: impl Error + Send + Sync + 'static) -> Self {
         Self::with_source(RequestProblemKind::RequestBodyRead, source)
     }
@@ -441,6 +478,8 @@ mod tests {
             version: http::Version::HTTP_11,
             final_url: url::Url::parse("https://example.test/secret?token=value").unwrap(),
             headers: http::HeaderMap::new(),
+            tls: None,
+            hops: Vec::new(),
         }
     }
 
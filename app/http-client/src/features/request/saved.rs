@@ -0,0 +1,191 @@
+use std::{
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use super::draft::RequestDraft;
+
+const FORMAT_VERSION: u32 = 1;
+pub(crate) const SAVED_REQUEST_EXTENSION: &str = "json";
+
+/// The file layout of one saved request. The draft is stored exactly as
+/// typed, placeholders and credentials included, so secrets that should not
+/// land on disk belong in `{{variables}}`.
+#[derive(Serialize, Deserialize)]
+struct SavedRequestFile {
+    version: u32,
+    request: RequestDraft,
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum SavedRequestError {
+    #[error("saved request could not be read")]
+    Read(#[source] io::Error),
+    #[error("saved request could not be written")]
+    Write(#[source] io::Error),
+    #[error("saved request is malformed")]
+    Malformed(#[source] serde_json::Error),
+    #[error("saved request uses format version {0}, which is newer than this build")]
+    UnsupportedVersion(u32),
+}
+
+impl SavedRequestError {
+    pub(crate) const fn message_key(&self) -> &'static str {
+        match self {
+            Self::Read(_) => "saved-request-read-failed",
+            Self::Write(_) => "saved-request-write-failed",
+            Self::Malformed(_) => "saved-request-malformed",
+            Self::UnsupportedVersion(_) => "saved-request-newer-version",
+        }
+    }
+}
+
+/// One request of a collection folder, named after its file.
+pub(crate) struct CollectionEntry {
+    pub(crate) name: String,
+    pub(crate) request: Result<RequestDraft, SavedRequestError>,
+}
+
+pub(crate) fn save_request(path: &Path, draft: &RequestDraft) -> Result<(), SavedRequestError> {
+    let file = SavedRequestFile {
+        version: FORMAT_VERSION,
+        request: draft.clone(),
+    };
+    let json = serde_json::to_vec_pretty(&file).map_err(SavedRequestError::Malformed)?;
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    // Writing beside the target and renaming keeps the previous version
+    // intact if the write is interrupted.
+    let mut staging = NamedTempFile::new_in(parent).map_err(SavedRequestError::Write)?;
+    staging.write_all(&json).map_err(SavedRequestError::Write)?;
+    staging
+        .persist(path)
+        .map(drop)
+        .map_err(|error| SavedRequestError::Write(error.error))
+}
+
+pub(crate) fn open_request(path: &Path) -> Result<RequestDraft, SavedRequestError> {
+    let bytes = std::fs::read(path).map_err(SavedRequestError::Read)?;
+    let file: SavedRequestFile =
+        serde_json::from_slice(&bytes).map_err(SavedRequestError::Malformed)?;
+    if file.version > FORMAT_VERSION {
+        return Err(SavedRequestError::UnsupportedVersion(file.version));
+    }
+    Ok(file.request)
+}
+
+/// Lists the saved requests directly inside `folder`, ordered by file name
+/// so a numeric prefix decides the run order. A file that fails to load is
+/// kept as an entry with its error rather than hiding the rest.
+pub(crate) fn open_collection(folder: &Path) -> Result<Vec<CollectionEntry>, SavedRequestError> {
+    let mut paths = std::fs::read_dir(folder)
+        .map_err(SavedRequestError::Read)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension == SAVED_REQUEST_EXTENSION)
+        })
+        .collect::<Vec<PathBuf>>();
+    paths.sort();
+    Ok(paths
+        .into_iter()
+        .map(|path| CollectionEntry {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            request: open_request(&path),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::request::{
+        draft::{
+            AssertionDraft, BearerAuthDraft, HeaderDraft, RequestAuthDraft, RequestBodyDraft,
+            TextBodyDraft, TextBodyFormat,
+        },
+        method::HttpMethod,
+    };
+
+    fn draft() -> RequestDraft {
+        RequestDraft {
+            method: HttpMethod::Post,
+            url: "https://{{host}}/items".into(),
+            headers: vec![HeaderDraft {
+                enabled: false,
+                name: "x-trace".into(),
+                value: "on".into(),
+            }],
+            body: RequestBodyDraft::Text(TextBodyDraft {
+                format: TextBodyFormat::Json,
+                content: r#"{"name":"{{name}}"}"#.into(),
+            }),
+            auth: RequestAuthDraft::Bearer(BearerAuthDraft {
+                token: "{{token}}".into(),
+            }),
+            assertions: vec![AssertionDraft::default()],
+            ..RequestDraft::default()
+        }
+    }
+
+    #[test]
+    fn saved_requests_round_trip_and_reject_newer_formats() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("create.json");
+        save_request(&path, &draft()).unwrap();
+        assert!(open_request(&path).unwrap() == draft());
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains(r#""method": "POST""#));
+        assert!(text.contains(r#""kind": "bearer""#));
+
+        std::fs::write(&path, text.replace(r#""version": 1"#, r#""version": 2"#)).unwrap();
+        assert!(matches!(
+            open_request(&path),
+            Err(SavedRequestError::UnsupportedVersion(2))
+        ));
+    }
+
+    #[test]
+    fn missing_fields_fall_back_to_the_draft_defaults() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("minimal.json");
+        std::fs::write(
+            &path,
+            r#"{"version":1,"request":{"url":"https://example.test","body":{"kind":"none"}}}"#,
+        )
+        .unwrap();
+        let draft = open_request(&path).unwrap();
+        assert_eq!(draft.url, "https://example.test");
+        assert_eq!(draft.method, HttpMethod::Get);
+        assert!(draft.settings.follow_redirects);
+    }
+
+    #[test]
+    fn collections_run_in_file_name_order_and_keep_broken_files() {
+        let folder = tempfile::tempdir().unwrap();
+        save_request(&folder.path().join("20-second.json"), &draft()).unwrap();
+        save_request(&folder.path().join("10-first.json"), &draft()).unwrap();
+        std::fs::write(folder.path().join("15-broken.json"), "{").unwrap();
+        std::fs::write(folder.path().join("notes.txt"), "ignored").unwrap();
+
+        let entries = open_collection(folder.path()).unwrap();
+        let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["10-first", "15-broken", "20-second"]);
+        assert!(matches!(
+            entries[1].request,
+            Err(SavedRequestError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,350 @@
+//! The request tab strip. Every tab is an independent `RequestView` with its
+//! own form, transport and response, and the open tabs are written to disk
+//! so a relaunch restores them.
+
+use std::{ffi::OsStr, path::PathBuf, time::Duration};
+
+use fluent_bundle::FluentArgs;
+use gpui::{
+    App, AppContext as _, Context, Entity, IntoElement, ParentElement as _, Render, SharedString,
+    Styled as _, Subscription, Task, Window, div, px,
+};
+use gpui_component::{
+    WindowExt as _,
+    button::{Button, ButtonVariants as _},
+    dialog::{DialogAction, DialogClose, DialogFooter},
+    label::Label,
+    tab::{Tab, TabBar},
+};
+
+use super::{RequestView, draft::RequestDraft, transport::CookieJar};
+use crate::foundation::I18n;
+
+pub(super) use self::store::StoredTab;
+use self::store::{StoredSession, load_session, store_session};
+
+mod store;
+
+/// Edits are written once typing pauses rather than on every keystroke.
+const STORE_DELAY: Duration = Duration::from_millis(500);
+const MAX_TITLE_CHARS: usize = 32;
+
+struct OpenTab {
+    view: Entity<RequestView>,
+    _form_observer: Subscription,
+}
+
+pub(crate) struct RequestSessionView {
+    cookies: CookieJar,
+    tabs: Vec<OpenTab>,
+    active: usize,
+    store_path: Option<PathBuf>,
+    store_task: Option<Task<()>>,
+}
+
+impl RequestSessionView {
+    /// Restores the tabs stored at `store_path`, or opens one empty tab.
+    /// Without a path the tabs live only as long as the window.
+    pub(crate) fn new(
+        cookies: CookieJar,
+        store_path: Option<PathBuf>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let stored = match store_path.as_deref().map(load_session) {
+            Some(Ok(session)) => session,
+            Some(Err(error)) => {
+                tracing::warn!(
+                    operation = "request-tabs-load",
+                    %error,
+                    "request tabs were not restored"
+                );
+                StoredSession::default()
+            }
+            None => StoredSession::default(),
+        };
+        let mut this = Self {
+            cookies,
+            tabs: Vec::new(),
+            active: stored.active,
+            store_path,
+            store_task: None,
+        };
+        for tab in stored.tabs {
+            this.push_tab(Some(tab), window, cx);
+        }
+        if this.tabs.is_empty() {
+            this.push_tab(None, window, cx);
+        }
+        this
+    }
+
+    fn push_tab(&mut self, stored: Option<StoredTab>, window: &mut Window, cx: &mut Context<Self>) {
+        let cookies = self.cookies.clone();
+        let view = cx.new(|cx| {
+            let mut view = RequestView::new(cookies, window, cx);
+            if let Some(stored) = stored {
+                view.restore(stored, cx);
+            }
+            view
+        });
+        // The form carries everything that is stored and shown in the strip.
+        let form = view.read(cx).form().clone();
+        let form_observer = cx.observe(&form, |this, _, cx| {
+            this.schedule_store(cx);
+            cx.notify();
+        });
+        self.tabs.push(OpenTab {
+            view,
+            _form_observer: form_observer,
+        });
+    }
+
+    fn new_tab(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.push_tab(None, window, cx);
+        self.active = self.tabs.len() - 1;
+        self.schedule_store(cx);
+        cx.notify();
+    }
+
+    fn select_tab(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index < self.tabs.len() && index != self.active {
+            self.active = index;
+            self.schedule_store(cx);
+            cx.notify();
+        }
+    }
+
+    /// Closes a clean tab right away and asks before discarding edits.
+    fn request_close(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(tab) = self.tabs.get(index) else {
+            return;
+        };
+        let view = tab.view.clone();
+        if !view.read(cx).is_dirty(cx) {
+            self.close_tab(&view, window, cx);
+            return;
+        }
+        let (title, message, cancel_label, discard_label) = {
+            let i18n = cx.global::<I18n>();
+            let mut args = FluentArgs::new();
+            args.set("name", self.tab_title(&view, cx));
+            (
+                SharedString::from(i18n.t("request-tab-unsaved-title")),
+                SharedString::from(i18n.t_with_args("request-tab-unsaved-message", &args)),
+                SharedString::from(i18n.t("button-cancel")),
+                SharedString::from(i18n.t("button-discard-changes")),
+            )
+        };
+        let owner = cx.entity().downgrade();
+        window.open_dialog(cx, move |dialog, _window, _cx| {
+            dialog
+                .title(title.clone())
+                .child(Label::new(message.clone()))
+                .footer(
+                    DialogFooter::new()
+                        .child(
+                            DialogClose::new()
+                                .child(Button::new("cancel").label(cancel_label.clone())),
+                        )
+                        .child(
+                            DialogAction::new().child(
+                                Button::new("confirm-discard")
+                                    .danger()
+                                    .label(discard_label.clone())
+                                    .on_click({
+                                        let owner = owner.clone();
+                                        let view = view.clone();
+                                        move |_, window, cx| {
+                                            window.close_dialog(cx);
+                                            let _ = owner.update(cx, |this, cx| {
+                                                this.close_tab(&view, window, cx)
+                                            });
+                                        }
+                                    }),
+                            ),
+                        ),
+                )
+        });
+    }
+
+    /// Closes `view`'s tab, which may have moved while a confirmation was
+    /// open. The last tab is replaced by an empty one.
+    fn close_tab(
+        &mut self,
+        view: &Entity<RequestView>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(index) = self.tabs.iter().position(|tab| &tab.view == view) else {
+            return;
+        };
+        self.tabs.remove(index);
+        self.active = active_after_close(self.active, index, self.tabs.len());
+        if self.tabs.is_empty() {
+            self.push_tab(None, window, cx);
+        }
+        self.schedule_store(cx);
+        cx.notify();
+    }
+
+    fn schedule_store(&mut self, cx: &mut Context<Self>) {
+        let Some(path) = self.store_path.clone() else {
+            return;
+        };
+        self.store_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(STORE_DELAY).await;
+            let Ok(session) = this.update(cx, |this, cx| this.snapshot(cx)) else {
+                return;
+            };
+            let stored = cx
+                .background_executor()
+                .spawn(async move { store_session(&path, session) })
+                .await;
+            if let Err(error) = stored {
+                tracing::warn!(
+                    operation = "request-tabs-save",
+                    %error,
+                    "request tabs were not saved"
+                );
+            }
+        }));
+    }
+
+    fn snapshot(&self, cx: &App) -> StoredSession {
+        StoredSession {
+            tabs: self
+                .tabs
+                .iter()
+                .map(|tab| tab.view.read(cx).stored_tab(cx))
+                .collect(),
+            active: self.active,
+        }
+    }
+
+    fn tab_title(&self, view: &Entity<RequestView>, cx: &App) -> String {
+        let view = view.read(cx);
+        let url = RequestDraft::URL.get(view.form(), cx);
+        tab_title(view.file().and_then(|file| file.file_stem()), &url)
+            .unwrap_or_else(|| cx.global::<I18n>().t("request-tab-untitled"))
+    }
+}
+
+impl Render for RequestSessionView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let labels: Vec<_> = self
+            .tabs
+            .iter()
+            .map(|tab| {
+                let title = self.tab_title(&tab.view, cx);
+                if tab.view.read(cx).is_dirty(cx) {
+                    format!("● {title}")
+                } else {
+                    title
+                }
+            })
+            .collect();
+        let content = self.tabs.get(self.active).map(|tab| tab.view.clone());
+        div()
+            .size_full()
+            .flex()
+            .flex_col()
+            .child(
+                TabBar::new("request-session-tabs")
+                    .selected_index(self.active)
+                    .on_click(cx.listener(|this, index, _, cx| this.select_tab(*index, cx)))
+                    .children(labels.into_iter().enumerate().map(|(index, label)| {
+                        Tab::new().label(label).suffix(
+                            Button::new(("request-tab-close", index))
+                                .ghost()
+                                .xsmall()
+                                .label("×")
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    cx.stop_propagation();
+                                    this.request_close(index, window, cx);
+                                })),
+                        )
+                    }))
+                    .suffix(
+                        Button::new("request-tab-new")
+                            .ghost()
+                            .xsmall()
+                            .label("+")
+                            .on_click(cx.listener(|this, _, window, cx| this.new_tab(window, cx))),
+                    ),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .overflow_hidden()
+                    .children(content),
+            )
+    }
+}
+
+/// The active tab after closing `closed`: the same tab when it survives,
+/// otherwise the one that took its place or, at the end, its left neighbour.
+fn active_after_close(active: usize, closed: usize, remaining: usize) -> usize {
+    if active > closed || active >= remaining {
+        active.saturating_sub(1)
+    } else {
+        active
+    }
+}
+
+/// The saved file's name, or the URL without its scheme for a request that
+/// was never saved.
+fn tab_title(file_stem: Option<&OsStr>, url: &str) -> Option<String> {
+    let title = match file_stem {
+        Some(stem) => stem.to_string_lossy().into_owned(),
+        None => {
+            let url = url.trim();
+            url.split_once("://")
+                .map_or(url, |(_, rest)| rest)
+                .to_owned()
+        }
+    };
+    if title.is_empty() {
+        return None;
+    }
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        return Some(title);
+    }
+    let mut shortened: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+    shortened.push('…');
+    Some(shortened)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closing_a_tab_keeps_the_active_tab_or_selects_its_neighbour() {
+        // Closing a tab left of the active one keeps the active tab.
+        assert_eq!(active_after_close(2, 0, 3), 1);
+        // Closing the active tab selects the one that took its place...
+        assert_eq!(active_after_close(1, 1, 3), 1);
+        // ...or its left neighbour when it was the last.
+        assert_eq!(active_after_close(3, 3, 3), 2);
+        assert_eq!(active_after_close(0, 2, 2), 0);
+        assert_eq!(active_after_close(0, 0, 0), 0);
+    }
+
+    #[test]
+    fn titles_prefer_the_file_name_and_shorten_long_urls() {
+        assert_eq!(
+            tab_title(Some(OsStr::new("login")), "https://example.test").as_deref(),
+            Some("login")
+        );
+        assert_eq!(
+            tab_title(None, " https://example.test/items ").as_deref(),
+            Some("example.test/items")
+        );
+        assert_eq!(tab_title(None, "  "), None);
+        let long = tab_title(None, "https://api.example.test/v1/organizations/42/members").unwrap();
+        assert_eq!(long.chars().count(), MAX_TITLE_CHARS);
+        assert!(long.ends_with('…'));
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+
+use crate::features::request::{
+    draft::{ConnectionSettingsDraft, RequestAuthDraft, RequestDraft},
+    saved::{SavedRequestError, open_request},
+};
+
+const FORMAT_VERSION: u32 = 1;
+
+/// The open request tabs as written to disk.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct StoredSession {
+    pub(crate) tabs: Vec<StoredTab>,
+    pub(crate) active: usize,
+}
+
+/// One tab. Both the last saved draft and the current edits are kept, so
+/// a tab with unsaved changes is still marked after a relaunch.
+///
+/// Passwords, tokens and keys are written empty; a tab opened from a saved
+/// request takes them back from that file when it is restored.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct StoredTab {
+    /// The saved request file the tab was opened from or saved to.
+    #[serde(default)]
+    pub(crate) file: Option<PathBuf>,
+    pub(crate) saved: RequestDraft,
+    pub(crate) draft: RequestDraft,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SessionFile {
+    version: u32,
+    session: StoredSession,
+}
+
+/// Reads the stored tabs. A missing file is an empty session.
+pub(crate) fn load_session(path: &Path) -> Result<StoredSession, SavedRequestError> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            return Ok(StoredSession::default());
+        }
+        Err(error) => return Err(SavedRequestError::Read(error)),
+    };
+    let file: SessionFile = serde_json::from_slice(&bytes).map_err(SavedRequestError::Malformed)?;
+    if file.version > FORMAT_VERSION {
+        return Err(SavedRequestError::UnsupportedVersion(file.version));
+    }
+    let mut session = file.session;
+    session.active = session.active.min(session.tabs.len().saturating_sub(1));
+    for tab in &mut session.tabs {
+        let Some(source) = tab.file.as_deref().and_then(|file| open_request(file).ok()) else {
+            continue;
+        };
+        restore_secrets(&mut tab.saved, &source);
+        restore_secrets(&mut tab.draft, &source);
+    }
+    Ok(session)
+}
+
+pub(crate) fn store_session(
+    path: &Path,
+    mut session: StoredSession,
+) -> Result<(), SavedRequestError> {
+    for tab in &mut session.tabs {
+        redact_secrets(&mut tab.saved);
+        redact_secrets(&mut tab.draft);
+    }
+    let file = SessionFile {
+        version: FORMAT_VERSION,
+        session,
+    };
+    let json = serde_json::to_vec(&file).map_err(SavedRequestError::Malformed)?;
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    std::fs::create_dir_all(parent).map_err(SavedRequestError::Write)?;
+    let mut staging = NamedTempFile::new_in(parent).map_err(SavedRequestError::Write)?;
+    staging.write_all(&json).map_err(SavedRequestError::Write)?;
+    staging
+        .persist(path)
+        .map(drop)
+        .map_err(|error| SavedRequestError::Write(error.error))
+}
+
+/// The credentials of `auth`, in an order that only depends on its kind.
+fn auth_secrets_mut(auth: &mut RequestAuthDraft) -> Vec<&mut String> {
+    match auth {
+        RequestAuthDraft::None => Vec::new(),
+        RequestAuthDraft::Basic(basic) => vec![&mut basic.password],
+        RequestAuthDraft::Bearer(bearer) => vec![&mut bearer.token],
+        RequestAuthDraft::ApiKey(api_key) => vec![&mut api_key.value],
+        RequestAuthDraft::Digest(digest) => vec![&mut digest.password],
+        RequestAuthDraft::OAuth2(oauth2) => vec![&mut oauth2.client_secret, &mut oauth2.password],
+        RequestAuthDraft::AwsSigV4(aws) => {
+            vec![&mut aws.secret_access_key, &mut aws.session_token]
+        }
+    }
+}
+
+fn connection_secrets_mut(connection: &mut ConnectionSettingsDraft) -> [&mut String; 2] {
+    [
+        &mut connection.client_certificate_password,
+        &mut connection.proxy_password,
+    ]
+}
+
+/// Empties every credential that is typed out. A lone `{{variable}}` is kept,
+/// since the value it stands for never reaches the tab.
+fn redact_secrets(draft: &mut RequestDraft) {
+    let mut secrets = auth_secrets_mut(&mut draft.auth);
+    secrets.extend(connection_secrets_mut(&mut draft.settings.connection));
+    for secret in secrets {
+        if !is_placeholder(secret) {
+            secret.clear();
+        }
+    }
+}
+
+/// Fills the credentials `redact_secrets` emptied from the saved request the
+/// tab belongs to. Authentication credentials are only taken while the tab
+/// still uses the same kind of authentication as the file.
+fn restore_secrets(draft: &mut RequestDraft, source: &RequestDraft) {
+    let mut source = source.clone();
+    let same_auth = std::mem::discriminant(&draft.auth) == std::mem::discriminant(&source.auth);
+    let mut pairs = connection_secrets_mut(&mut draft.settings.connection)
+        .into_iter()
+        .zip(connection_secrets_mut(&mut source.settings.connection))
+        .collect::<Vec<_>>();
+    if same_auth {
+        pairs.extend(
+            auth_secrets_mut(&mut draft.auth)
+                .into_iter()
+                .zip(auth_secrets_mut(&mut source.auth)),
+        );
+    }
+    for (secret, saved) in pairs {
+        if secret.is_empty() {
+            *secret = std::mem::take(saved);
+        }
+    }
+}
+
+fn is_placeholder(value: &str) -> bool {
+    value
+        .trim()
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+        .is_some_and(|name| !name.contains("{{") && !name.contains("}}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::request::{
+        draft::{AwsSigV4AuthDraft, BasicAuthDraft, BearerAuthDraft, OAuth2AuthDraft},
+        method::HttpMethod,
+        saved::save_request,
+    };
+
+    #[test]
+    fn sessions_round_trip_and_clamp_the_active_tab() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("state").join("request-tabs.json");
+        assert!(load_session(&path).unwrap() == StoredSession::default());
+
+        let saved = RequestDraft {
+            url: "https://example.test/items".into(),
+            ..RequestDraft::default()
+        };
+        let session = StoredSession {
+            tabs: vec![
+                StoredTab {
+                    file: Some(folder.path().join("items.json")),
+                    saved: saved.clone(),
+                    draft: RequestDraft {
+                        method: HttpMethod::Post,
+                        ..saved
+                    },
+                },
+                StoredTab {
+                    file: None,
+                    saved: RequestDraft::default(),
+                    draft: RequestDraft::default(),
+                },
+            ],
+            active: 1,
+        };
+        store_session(&path, session.clone()).unwrap();
+        assert!(load_session(&path).unwrap() == session);
+
+        store_session(
+            &path,
+            StoredSession {
+                active: 7,
+                ..session
+            },
+        )
+        .unwrap();
+        assert_eq!(load_session(&path).unwrap().active, 1);
+
+        std::fs::write(&path, r#"{"version":99,"session":{}}"#).unwrap();
+        assert!(matches!(
+            load_session(&path),
+            Err(SavedRequestError::UnsupportedVersion(99))
+        ));
+    }
+
+    #[test]
+    fn stored_tabs_leave_out_secrets_and_take_them_back_from_their_file() {
+        let folder = tempfile::tempdir().unwrap();
+        let path = folder.path().join("request-tabs.json");
+        let file = folder.path().join("signed.json");
+
+        let with_secrets = |auth| {
+            let mut draft = RequestDraft {
+                url: "https://example.test/items".into(),
+                auth,
+                ..RequestDraft::default()
+            };
+            draft.settings.connection.client_certificate_password = "secret-p12".into();
+            draft.settings.connection.proxy_password = "{{proxy_password}}".into();
+            draft
+        };
+        let signed = with_secrets(RequestAuthDraft::AwsSigV4(AwsSigV4AuthDraft {
+            access_key_id: "AKID".into(),
+            secret_access_key: "secret-aws".into(),
+            session_token: "secret-session".into(),
+            region: "us-east-1".into(),
+            service: "s3".into(),
+        }));
+        save_request(&file, &signed).unwrap();
+        let session = StoredSession {
+            tabs: vec![
+                StoredTab {
+                    file: None,
+                    saved: with_secrets(RequestAuthDraft::Basic(BasicAuthDraft {
+                        username: "ada".into(),
+                        password: "secret-basic".into(),
+                    })),
+                    draft: with_secrets(RequestAuthDraft::OAuth2(OAuth2AuthDraft {
+                        client_secret: "secret-client".into(),
+                        password: "secret-owner".into(),
+                        ..OAuth2AuthDraft::default()
+                    })),
+                },
+                StoredTab {
+                    file: Some(file.clone()),
+                    saved: signed.clone(),
+                    draft: with_secrets(RequestAuthDraft::Bearer(BearerAuthDraft {
+                        token: "secret-bearer".into(),
+                    })),
+                },
+            ],
+            active: 0,
+        };
+        store_session(&path, session).unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(!written.contains("secret-"), "{written}");
+        assert!(written.contains("{{proxy_password}}"));
+        assert!(written.contains("AKID"));
+
+        let restored = load_session(&path).unwrap();
+        let unsaved = &restored.tabs[0];
+        assert!(matches!(
+            &unsaved.saved.auth,
+            RequestAuthDraft::Basic(basic) if basic.username == "ada" && basic.password.is_empty()
+        ));
+        assert!(
+            unsaved
+                .draft
+                .settings
+                .connection
+                .client_certificate_password
+                .is_empty()
+        );
+        let opened = &restored.tabs[1];
+        assert!(opened.saved == signed);
+        assert!(matches!(
+            &opened.draft.auth,
+            RequestAuthDraft::Bearer(bearer) if bearer.token.is_empty()
+        ));
+        assert_eq!(
+            opened.draft.settings.connection.client_certificate_password,
+            "secret-p12"
+        );
+    }
+}
@@ -1,20 +1,139 @@
+use std::path::PathBuf;
+
 use gpui::{
-    AppContext as _, Context, Entity, IntoElement, ParentElement as _, Styled, Subscription, Window,
+    AnyElement, AppContext as _, Context, Entity, IntoElement, ParentElement as _, SharedString,
+    Styled, Subscription, Window, prelude::FluentBuilder as _,
 };
 use gpui_component::{
-    Disableable as _,
+    ActiveTheme as _, Disableable as _, IndexPath,
     checkbox::Checkbox,
     form::{field, v_form},
+    input::{Input, InputContentType, InputState},
+    label::Label,
+    select::{SelectItem, SelectState},
+    v_flex,
+};
+use gpui_form::{FieldDef, Form, TotalPath};
+use gpui_form_gpui_component::{FormInput, IntegerInput, IntegerInputEvent, IntegerInputState};
+
+use super::{
+    body::file_labels,
+    controls::{FormFilePathInput, FormScalarSelect},
+    draft::{
+        ConnectionSettingsDraft, HttpClientTransportSettings, HttpVersionPreference, RequestDraft,
+        RequestSettingsDraft,
+    },
 };
-use gpui_form::Form;
-use gpui_form_gpui_component::{IntegerInput, IntegerInputEvent, IntegerInputState};
+use crate::foundation::{I18n, i18n::validation_message};
+
+#[derive(Clone)]
+struct HttpVersionOption {
+    version: HttpVersionPreference,
+    title: SharedString,
+}
+
+impl SelectItem for HttpVersionOption {
+    type Value = HttpVersionPreference;
+
+    fn title(&self) -> SharedString {
+        self.title.clone()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.version
+    }
+}
 
-use super::draft::{HttpClientTransportSettings, RequestDraft, RequestSettingsDraft};
-use crate::foundation::I18n;
+/// The bound controls of the connection section.
+struct ConnectionControls {
+    http_version: FormScalarSelect<RequestDraft, Vec<HttpVersionOption>, HttpVersionPreference>,
+    ca_certificate: FormFilePathInput,
+    client_certificate: FormFilePathInput,
+    client_key: FormFilePathInput,
+    client_certificate_password: FormInput,
+    proxy_url: FormInput,
+    proxy_username: FormInput,
+    proxy_password: FormInput,
+    proxy_bypass: FormInput,
+}
+
+impl ConnectionControls {
+    fn new(
+        form: &Entity<Form<RequestDraft>>,
+        window: &mut Window,
+        cx: &mut Context<SettingsView>,
+    ) -> Self {
+        let connection = connection_path();
+        let options = http_version_options(cx);
+        let http_version = FormScalarSelect::new(
+            form,
+            connection
+                .clone()
+                .then(ConnectionSettingsDraft::HTTP_VERSION),
+            |window, cx| SelectState::new(options, Some(IndexPath::default()), window, cx),
+            window,
+            cx,
+        );
+        let mut file = |field: FieldDef<ConnectionSettingsDraft, Option<PathBuf>>| {
+            FormFilePathInput::new(
+                form,
+                connection.clone().then(field),
+                file_labels(cx),
+                window,
+                cx,
+            )
+        };
+        let ca_certificate = file(ConnectionSettingsDraft::CA_CERTIFICATE);
+        let client_certificate = file(ConnectionSettingsDraft::CLIENT_CERTIFICATE);
+        let client_key = file(ConnectionSettingsDraft::CLIENT_KEY);
+        let mut text = |field: FieldDef<ConnectionSettingsDraft, String>,
+                        placeholder: Option<&str>,
+                        masked: bool| {
+            let placeholder = placeholder.map(|key| cx.global::<I18n>().t(key));
+            FormInput::new(
+                form,
+                connection.clone().then(field),
+                move |window, cx| {
+                    let state = InputState::new(window, cx).masked(masked);
+                    match placeholder {
+                        Some(placeholder) => state.placeholder(placeholder),
+                        None => state,
+                    }
+                },
+                window,
+                cx,
+            )
+        };
+        Self {
+            http_version,
+            ca_certificate,
+            client_certificate,
+            client_key,
+            client_certificate_password: text(
+                ConnectionSettingsDraft::CLIENT_CERTIFICATE_PASSWORD,
+                None,
+                true,
+            ),
+            proxy_url: text(
+                ConnectionSettingsDraft::PROXY_URL,
+                Some("settings-proxy-url-placeholder"),
+                false,
+            ),
+            proxy_username: text(ConnectionSettingsDraft::PROXY_USERNAME, None, false),
+            proxy_password: text(ConnectionSettingsDraft::PROXY_PASSWORD, None, true),
+            proxy_bypass: text(
+                ConnectionSettingsDraft::PROXY_BYPASS,
+                Some("settings-proxy-bypass-placeholder"),
+                false,
+            ),
+        }
+    }
+}
 
 pub(super) struct SettingsView {
     form: Entity<Form<RequestDraft>>,
     timeout: Entity<IntegerInputState<u64>>,
+    connection: ConnectionControls,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -43,28 +162,193 @@ impl SettingsView {
             },
         );
         let form_observer = cx.observe(&form, |_, _, cx| cx.notify());
+        let connection = ConnectionControls::new(&form, window, cx);
         Self {
             form,
             timeout,
+            connection,
             _subscriptions: vec![timeout_subscription, form_observer],
         }
     }
 }
 
+impl SettingsView {
+    fn with_error<Value: Clone + PartialEq + 'static>(
+        &self,
+        control: impl IntoElement,
+        path: TotalPath<RequestDraft, Value>,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let error = path
+            .errors(&self.form, cx)
+            .first()
+            .map(|issue| validation_message(issue.message(), cx));
+        v_flex()
+            .w_full()
+            .gap_1()
+            .child(control)
+            .when_some(error, |this, error| {
+                this.child(Label::new(error).text_xs().text_color(cx.theme().danger))
+            })
+            .into_any_element()
+    }
+
+    fn render_connection(&self, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let connection = connection_path();
+        let insecure_path = connection
+            .clone()
+            .then(ConnectionSettingsDraft::ACCEPT_INVALID_CERTIFICATES);
+        let insecure = insecure_path.get(&self.form, cx);
+        let labels = {
+            let i18n = cx.global::<I18n>();
+            [
+                "settings-http-version",
+                "settings-ca-certificate",
+                "settings-client-certificate",
+                "settings-client-key",
+                "settings-client-certificate-password",
+                "settings-accept-invalid-certificates",
+                "settings-accept-invalid-certificates-warning",
+                "settings-proxy-url",
+                "settings-proxy-username",
+                "settings-proxy-password",
+                "settings-proxy-bypass",
+            ]
+            .map(|key| i18n.t(key))
+        };
+        let [
+            version_label,
+            ca_label,
+            certificate_label,
+            key_label,
+            password_label,
+            insecure_label,
+            insecure_warning,
+            proxy_label,
+            username_label,
+            proxy_password_label,
+            bypass_label,
+        ] = labels;
+        let controls = &self.connection;
+        let insecure_form = self.form.clone();
+        vec![
+            field()
+                .label(version_label)
+                .child(controls.http_version.element())
+                .into_any_element(),
+            field()
+                .label(ca_label)
+                .child(
+                    self.with_error(
+                        (*controls.ca_certificate).clone(),
+                        connection
+                            .clone()
+                            .then(ConnectionSettingsDraft::CA_CERTIFICATE),
+                        cx,
+                    ),
+                )
+                .into_any_element(),
+            field()
+                .label(certificate_label)
+                .child(
+                    self.with_error(
+                        (*controls.client_certificate).clone(),
+                        connection
+                            .clone()
+                            .then(ConnectionSettingsDraft::CLIENT_CERTIFICATE),
+                        cx,
+                    ),
+                )
+                .into_any_element(),
+            field()
+                .label(key_label)
+                .child(self.with_error(
+                    (*controls.client_key).clone(),
+                    connection.clone().then(ConnectionSettingsDraft::CLIENT_KEY),
+                    cx,
+                ))
+                .into_any_element(),
+            field()
+                .label(password_label)
+                .child(password_input(&controls.client_certificate_password))
+                .into_any_element(),
+            field()
+                .label_indent(false)
+                .child(
+                    v_flex()
+                        .gap_1()
+                        .child(
+                            Checkbox::new("request-accept-invalid-certificates")
+                                .label(insecure_label)
+                                .checked(insecure)
+                                .on_click(cx.listener(move |_, checked, _, cx| {
+                                    insecure_path.set(&insecure_form, *checked, cx);
+                                })),
+                        )
+                        .when(insecure, |this| {
+                            this.child(
+                                Label::new(insecure_warning)
+                                    .text_xs()
+                                    .text_color(cx.theme().danger),
+                            )
+                        }),
+                )
+                .into_any_element(),
+            field()
+                .label(proxy_label)
+                .child(self.with_error(
+                    Input::new(&controls.proxy_url).content_type(InputContentType::Url),
+                    connection.clone().then(ConnectionSettingsDraft::PROXY_URL),
+                    cx,
+                ))
+                .into_any_element(),
+            field()
+                .label(username_label)
+                .child(Input::new(&controls.proxy_username))
+                .into_any_element(),
+            field()
+                .label(proxy_password_label)
+                .child(password_input(&controls.proxy_password))
+                .into_any_element(),
+            field()
+                .label(bypass_label)
+                .child(Input::new(&controls.proxy_bypass))
+                .into_any_element(),
+        ]
+    }
+}
+
 impl gpui::Render for SettingsView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let settings = RequestDraft::ROOT.then(RequestDraft::SETTINGS);
         let follow_path = settings
             .clone()
             .then(RequestSettingsDraft::FOLLOW_REDIRECTS);
-        let preserve_path = settings.then(RequestSettingsDraft::FOLLOW_ORIGINAL_METHOD);
+        let preserve_path = settings
+            .clone()
+            .then(RequestSettingsDraft::FOLLOW_ORIGINAL_METHOD);
+        let reconnect_path = settings
+            .clone()
+            .then(RequestSettingsDraft::RECONNECT_EVENT_STREAM);
+        let cookies_path = settings.then(RequestSettingsDraft::SEND_COOKIES);
         let follow = follow_path.get(&self.form, cx);
         let preserve = preserve_path.get(&self.form, cx);
-        let (follow_label, preserve_label, timeout_label, timeout_help) = {
+        let reconnect = reconnect_path.get(&self.form, cx);
+        let send_cookies = cookies_path.get(&self.form, cx);
+        let (
+            follow_label,
+            preserve_label,
+            reconnect_label,
+            cookies_label,
+            timeout_label,
+            timeout_help,
+        ) = {
             let i18n = cx.global::<I18n>();
             (
                 i18n.t("settings-follow-redirects"),
                 i18n.t("settings-follow-original-method"),
+                i18n.t("settings-reconnect-event-stream"),
+                i18n.t("settings-send-cookies"),
                 i18n.t("field-timeout-ms"),
                 i18n.t("settings-timeout-help"),
             )
@@ -72,6 +356,8 @@ impl gpui::Render for SettingsView {
 
         let follow_form = self.form.clone();
         let preserve_form = self.form.clone();
+        let reconnect_form = self.form.clone();
+        let cookies_form = self.form.clone();
         v_form()
             .p_3()
             .child(
@@ -95,11 +381,59 @@ impl gpui::Render for SettingsView {
                         })),
                 ),
             )
+            .child(
+                field().label_indent(false).child(
+                    Checkbox::new("request-reconnect-event-stream")
+                        .label(reconnect_label)
+                        .checked(reconnect)
+                        .on_click(cx.listener(move |_, checked, _, cx| {
+                            reconnect_path.set(&reconnect_form, *checked, cx);
+                        })),
+                ),
+            )
+            .child(
+                field().label_indent(false).child(
+                    Checkbox::new("request-send-cookies")
+                        .label(cookies_label)
+                        .checked(send_cookies)
+                        .on_click(cx.listener(move |_, checked, _, cx| {
+                            cookies_path.set(&cookies_form, *checked, cx);
+                        })),
+                ),
+            )
             .child(
                 field()
                     .label(timeout_label)
                     .description(timeout_help)
                     .child(IntegerInput::new(&self.timeout)),
             )
+            .children(self.render_connection(cx))
     }
 }
+
+fn connection_path() -> TotalPath<RequestDraft, ConnectionSettingsDraft> {
+    RequestDraft::ROOT
+        .then(RequestDraft::SETTINGS)
+        .then(RequestSettingsDraft::CONNECTION)
+}
+
+fn password_input(input: &FormInput) -> Input {
+    Input::new(input)
+        .content_type(InputContentType::Password)
+        .mask_toggle()
+}
+
+fn http_version_options(cx: &gpui::App) -> Vec<HttpVersionOption> {
+    let i18n = cx.global::<I18n>();
+    [
+        (HttpVersionPreference::Automatic, "http-version-automatic"),
+        (HttpVersionPreference::Http1, "http-version-http1"),
+        (HttpVersionPreference::Http2, "http-version-http2"),
+    ]
+    .into_iter()
+    .map(|(version, key)| HttpVersionOption {
+        version,
+        title: i18n.t(key).into(),
+    })
+    .collect()
+}
@@ -13,11 +13,14 @@ use gpui_component::{
 
 use super::{
     auth::AuthView,
-    body::HttpBodyView,
+    body::{HttpBodyView, SchemaSource},
+    checks::ChecksView,
     draft::{ApiKeyLocation, HttpClientTransportSettings, RequestAuthDraft, RequestDraft},
     headers::HttpHeadersView,
     params::HttpParamsInput,
     settings::SettingsView,
+    transport::HttpTransport,
+    variables::RequestVariables,
 };
 use crate::foundation::I18n;
 use gpui_form::Form;
@@ -29,15 +32,17 @@ enum RequestTab {
     Authorization,
     Headers,
     Body,
+    Tests,
     Settings,
 }
 
 impl RequestTab {
-    const ALL: [Self; 5] = [
+    const ALL: [Self; 6] = [
         Self::Params,
         Self::Authorization,
         Self::Headers,
         Self::Body,
+        Self::Tests,
         Self::Settings,
     ];
 
@@ -47,7 +52,8 @@ impl RequestTab {
             Self::Authorization => 1,
             Self::Headers => 2,
             Self::Body => 3,
-            Self::Settings => 4,
+            Self::Tests => 4,
+            Self::Settings => 5,
         }
     }
 }
@@ -59,6 +65,7 @@ pub(super) struct RequestTabsView {
     authorization: Entity<AuthView>,
     headers: Entity<HttpHeadersView>,
     body: Entity<HttpBodyView>,
+    tests: Entity<ChecksView>,
     settings: Entity<SettingsView>,
 }
 
@@ -66,13 +73,18 @@ impl RequestTabsView {
     pub(super) fn new(
         form: Entity<Form<RequestDraft>>,
         transport_settings: HttpClientTransportSettings,
+        variables: RequestVariables,
+        transport: HttpTransport,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
         let params = HttpParamsInput::new(&form, window, cx);
         let authorization = cx.new(|cx| AuthView::new(form.clone(), window, cx));
         let headers = cx.new(|cx| HttpHeadersView::new(form.clone(), window, cx));
-        let body = cx.new(|cx| HttpBodyView::new(form.clone(), window, cx));
+        let schema_source =
+            SchemaSource::new(transport, transport_settings.clone(), variables.clone());
+        let body = cx.new(|cx| HttpBodyView::new(form.clone(), schema_source, window, cx));
+        let tests = cx.new(|cx| ChecksView::new(form.clone(), variables, window, cx));
         let settings = cx.new(|cx| SettingsView::new(form.clone(), transport_settings, window, cx));
         Self {
             form,
@@ -81,6 +93,7 @@ impl RequestTabsView {
             authorization,
             headers,
             body,
+            tests,
             settings,
         }
     }
@@ -109,6 +122,7 @@ impl RequestTabsView {
             RequestTab::Authorization => self.authorization.clone().into_any_element(),
             RequestTab::Headers => self.headers.clone().into_any_element(),
             RequestTab::Body => self.body.clone().into_any_element(),
+            RequestTab::Tests => self.tests.clone().into_any_element(),
             RequestTab::Settings => self.settings.clone().into_any_element(),
         }
     }
@@ -123,6 +137,7 @@ impl gpui::Render for RequestTabsView {
                 i18n.t("tab-authorization"),
                 i18n.t("tab-headers"),
                 i18n.t("tab-body"),
+                i18n.t("tab-tests"),
                 i18n.t("tab-settings"),
             ]
         };
@@ -213,7 +228,14 @@ mod tests {
                 ..RequestDraft::default()
             };
             let form = cx.new(|_| Form::new(draft));
-            RequestTabsView::new(form, HttpClientTransportSettings::default(), window, cx)
+            RequestTabsView::new(
+                form,
+                HttpClientTransportSettings::default(),
+                RequestVariables::default(),
+                HttpTransport::new_without_proxy(),
+                window,
+                cx,
+            )
         });
 
         cx.update(|_, cx| {
@@ -8,11 +8,12 @@ use url::Url;
 
 use super::*;
 use crate::features::request::{
+    draft::{ExtractionDraft, ExtractionSource, HeaderDraft},
     response::{
-        BodyDecoding, CompletedBody, ResponseData, ResponseHead, ResponseSizes, ResponseTiming,
-        StoredBody,
+        BodyDecoding, CompletedBody, ResponseData, ResponseHead, ResponseProgress, ResponseSizes,
+        ResponseTiming, StoredBody,
     },
-    runtime::{HttpRunMessage, RequestPhase},
+    runtime::{HttpRunMessage, RequestPhase, ResponseReceipt},
 };
 use crate::foundation::i18n::init_i18n;
 
@@ -51,12 +52,13 @@ fn completed_response(bytes: &'static [u8]) -> Arc<ResponseData> {
 #[gpui::test]
 fn page_prepare_uses_the_form_snapshot_without_rewriting_the_editor(cx: &mut TestAppContext) {
     initialize(cx);
-    let (view, cx) = cx.add_window_view(RequestView::new);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
     let form = cx.update(|_, cx| view.read(cx).form.clone());
     let original = " https://example.test/items?q=one#local-fragment ".to_owned();
 
     cx.update(|_, cx| RequestDraft::URL.set(&form, original.clone(), cx));
-    let prepared = cx.update(|_, cx| {
+    let (prepared, _) = cx.update(|_, cx| {
         view.update(cx, |view, cx| {
             view.prepare_request(cx)
                 .expect("valid request must prepare")
@@ -72,7 +74,8 @@ fn page_prepare_uses_the_form_snapshot_without_rewriting_the_editor(cx: &mut Tes
 #[gpui::test]
 fn page_prepare_reports_submit_errors_on_the_precise_url_path(cx: &mut TestAppContext) {
     initialize(cx);
-    let (view, cx) = cx.add_window_view(RequestView::new);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
     let form = cx.update(|_, cx| view.read(cx).form.clone());
 
     assert!(
@@ -89,7 +92,8 @@ fn page_prepare_reports_submit_errors_on_the_precise_url_path(cx: &mut TestAppCo
 #[gpui::test]
 fn running_send_is_rejected_before_submit_validation_or_a_second_task(cx: &mut TestAppContext) {
     initialize(cx);
-    let (view, cx) = cx.add_window_view(RequestView::new);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
     let form = cx.update(|_, cx| view.read(cx).form.clone());
 
     cx.update(|window, cx| {
@@ -113,7 +117,8 @@ fn running_send_is_rejected_before_submit_validation_or_a_second_task(cx: &mut T
 #[gpui::test]
 fn prepare_failure_preserves_the_current_terminal_response(cx: &mut TestAppContext) {
     initialize(cx);
-    let (view, cx) = cx.add_window_view(RequestView::new);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
     let response = completed_response(b"previous");
     cx.update(|window, cx| {
         view.update(cx, |view, cx| {
@@ -133,7 +138,8 @@ fn prepare_failure_preserves_the_current_terminal_response(cx: &mut TestAppConte
 #[gpui::test]
 fn accepted_send_clears_the_previous_response_before_worker_poll(cx: &mut TestAppContext) {
     initialize(cx);
-    let (view, cx) = cx.add_window_view(RequestView::new);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
     let form = cx.update(|_, cx| view.read(cx).form.clone());
     cx.update(|_, cx| RequestDraft::URL.set(&form, "http://127.0.0.1:9".into(), cx));
     cx.update(|window, cx| {
@@ -167,7 +173,8 @@ fn accepted_send_clears_the_previous_response_before_worker_poll(cx: &mut TestAp
 #[gpui::test]
 fn response_save_disables_send_until_the_save_task_finishes(cx: &mut TestAppContext) {
     initialize(cx);
-    let (view, cx) = cx.add_window_view(RequestView::new);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
 
     cx.update(|_, cx| {
         view.update(cx, |view, cx| {
@@ -186,7 +193,8 @@ fn response_save_disables_send_until_the_save_task_finishes(cx: &mut TestAppCont
 #[gpui::test]
 fn clear_response_invalidates_the_current_preview(cx: &mut TestAppContext) {
     initialize(cx);
-    let (view, cx) = cx.add_window_view(RequestView::new);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
     let response = completed_response(b"body");
 
     cx.update(|window, cx| {
@@ -212,7 +220,8 @@ fn clear_response_invalidates_the_current_preview(cx: &mut TestAppContext) {
 #[gpui::test]
 fn response_save_picker_cancel_is_silent_and_releases_its_task(cx: &mut TestAppContext) {
     initialize(cx);
-    let (view, cx) = cx.add_window_view(RequestView::new);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
     cx.update(|window, cx| {
         view.update(cx, |view, cx| {
             view.runtime = RequestRuntime::Ready {
@@ -235,7 +244,8 @@ fn response_view_mode_select_invalidates_only_the_previous_viewer_projection(
     cx: &mut TestAppContext,
 ) {
     initialize(cx);
-    let (view, cx) = cx.add_window_view(RequestView::new);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
     let response = completed_response(b"body");
     let stale_preview = cx.update(|window, cx| {
         view.update(cx, |view, cx| {
@@ -273,3 +283,171 @@ fn response_view_mode_select_invalidates_only_the_previous_viewer_projection(
         assert_eq!(view.read(cx).runtime.phase(), RequestPhase::Ready);
     });
 }
+
+#[gpui::test]
+fn finished_checks_store_extracted_variables_for_the_next_send(cx: &mut TestAppContext) {
+    initialize(cx);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
+    let form = cx.update(|_, cx| view.read(cx).form.clone());
+    cx.update(|_, cx| {
+        RequestDraft::URL.set(&form, "https://example.test/users/{{id}}".into(), cx);
+        RequestDraft::ROOT
+            .then(RequestDraft::EXTRACTIONS)
+            .append(
+                &form,
+                ExtractionDraft {
+                    enabled: true,
+                    variable: "id".into(),
+                    source: ExtractionSource::JsonPath,
+                    expression: "$.user.id".into(),
+                },
+                cx,
+            )
+            .expect("extraction row must append");
+    });
+
+    let (error, checks) = cx.update(|_, cx| {
+        let extractions = RequestDraft::ROOT.get(&form, cx).extractions;
+        let error = view.update(cx, |view, cx| view.prepare_request(cx).err());
+        (error, compile_checks(&[], &extractions).unwrap())
+    });
+    assert!(matches!(
+        error,
+        Some(RequestPrepareError::Compile(RequestCompileError::UnknownVariables { names }))
+            if names == ["id"]
+    ));
+
+    let response = completed_response(br#"{"user":{"id":42}}"#);
+    cx.update(|_, cx| {
+        view.update(cx, |_, cx| {
+            cx.spawn(async move |this, cx| {
+                let report = run_checks(checks, response).await;
+                let _ = this.update(cx, |this, cx| this.finish_checks(report, cx));
+            })
+            .detach();
+        });
+    });
+    cx.run_until_parked();
+
+    let (prepared, _) = cx.update(|_, cx| {
+        let view_state = view.read(cx);
+        let report = view_state
+            .response_pane
+            .check_report()
+            .expect("finished checks must be reported");
+        assert_eq!(report.extractions[0].result, Ok("42".to_owned()));
+        assert_eq!(
+            view_state.variables.entries(),
+            [("id".to_owned(), "42".to_owned())]
+        );
+        view.update(cx, |view, cx| {
+            view.prepare_request(cx)
+                .expect("valid request must prepare")
+        })
+    });
+    assert_eq!(prepared.url.as_str(), "https://example.test/users/42");
+}
+
+#[gpui::test]
+fn send_reports_values_that_break_the_request_and_unknown_variables(cx: &mut TestAppContext) {
+    initialize(cx);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
+    let form = cx.update(|_, cx| view.read(cx).form.clone());
+    cx.update(|_, cx| {
+        RequestDraft::URL.set(&form, "http://127.0.0.1:9/{{missing}}".into(), cx);
+        RequestDraft::ROOT
+            .then(RequestDraft::HEADERS)
+            .append(
+                &form,
+                HeaderDraft {
+                    enabled: true,
+                    name: "x-token".into(),
+                    value: "{{token}}".into(),
+                },
+                cx,
+            )
+            .expect("header row must append");
+    });
+    let send = |cx: &mut gpui::VisualTestContext| {
+        cx.update(|window, cx| {
+            view.update(cx, |view, cx| {
+                view.variables
+                    .set("token".into(), "abc\r\nx-injected: 1".into());
+                view.start_request(window, cx);
+                assert_eq!(view.runtime.phase(), RequestPhase::Idle);
+                view.send_problem
+                    .as_ref()
+                    .map(|problem| send_problem_message(problem, cx.global::<I18n>()))
+            })
+        })
+    };
+
+    let message = send(cx).expect("an unknown variable must be reported");
+    assert!(message.contains("missing"), "{message}");
+
+    cx.update(|_, cx| RequestDraft::URL.set(&form, "http://127.0.0.1:9/".into(), cx));
+    send(cx).expect("a header broken by its value must be reported");
+    cx.update(|_, cx| {
+        assert_eq!(
+            view.read(cx).send_problem,
+            Some(RequestCompileError::InvalidHeader)
+        );
+    });
+}
+
+#[gpui::test]
+fn ended_event_streams_reconnect_only_when_enabled_until_cleared(cx: &mut TestAppContext) {
+    initialize(cx);
+    let (view, cx) =
+        cx.add_window_view(|window, cx| RequestView::new(CookieJar::default(), window, cx));
+    let form = cx.update(|_, cx| view.read(cx).form.clone());
+    let finish_stream =
+        |view: &mut RequestView, window: &mut Window, cx: &mut Context<RequestView>| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                http::header::CONTENT_TYPE,
+                http::HeaderValue::from_static("text/event-stream"),
+            );
+            view.runtime = RequestRuntime::Receiving {
+                task: gpui::Task::ready(()),
+                receipt: ResponseReceipt {
+                    head: ResponseHead::new(
+                        StatusCode::OK,
+                        Version::HTTP_11,
+                        Url::parse("https://example.test/events").unwrap(),
+                        headers,
+                    ),
+                    progress: ResponseProgress::initial(None),
+                    head_after: Duration::from_millis(1),
+                },
+            };
+            view.handle_run_message(
+                HttpRunMessage::Finished {
+                    result: Ok(CompletedBody::empty(None)),
+                    finished_after: Duration::from_millis(2),
+                },
+                Some(window),
+                cx,
+            );
+        };
+
+    cx.update(|window, cx| {
+        view.update(cx, |view, cx| {
+            finish_stream(view, window, cx);
+            assert!(!view.response_pane.event_stream.is_reconnecting());
+        });
+        RequestDraft::ROOT
+            .then(RequestDraft::SETTINGS)
+            .then(RequestSettingsDraft::RECONNECT_EVENT_STREAM)
+            .set(&form, true, cx);
+        view.update(cx, |view, cx| {
+            finish_stream(view, window, cx);
+            assert!(view.response_pane.event_stream.is_reconnecting());
+
+            view.clear_response(cx);
+            assert!(!view.response_pane.event_stream.is_reconnecting());
+        });
+    });
+}
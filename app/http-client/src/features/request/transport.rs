@@ -1,16 +1,28 @@
-use std::{sync::Arc, time::Instant};
+use std::{pin::pin, sync::Arc, time::Instant};
 
 use async_channel::{Receiver, Sender};
-use reqwest::{Client, redirect::Policy};
+use futures_util::future::{Either, select};
+use reqwest::Client;
+use url::Url;
 
+pub(crate) use self::cookies::{Cookie, CookieJar, CookieKey};
+use self::{auth::OAuthTokenCache, tls::PeerChains};
 use super::{
     prepared::PreparedRequest,
-    response::{CompletedBody, ResponseHead, ResponseProgress},
-    runtime::RequestProblem,
+    response::{
+        CompletedBody, ResponseData, ResponseHead, ResponseProgress, ResponseTiming, SseUpdate,
+    },
+    runtime::{AuthorizationProblemKind, RequestProblem},
 };
 
+mod auth;
 mod body;
+mod certificate;
+mod connection;
+mod cookies;
 mod redirect;
+mod timing;
+mod tls;
 mod worker;
 
 const WORKER_EVENT_CAPACITY: usize = 8;
@@ -22,38 +34,45 @@ pub(crate) enum WorkerEvent {
         progress: ResponseProgress,
     },
     BodyProgress(ResponseProgress),
+    /// Events parsed from a `text/event-stream` body. Unlike progress these
+    /// are never coalesced and always arrive before `Finished`.
+    EventStream(SseUpdate),
     Finished {
         result: Result<CompletedBody, RequestProblem>,
         finished_after: std::time::Duration,
     },
+    /// The authorization code grant needs the user to approve access in a
+    /// browser. The worker keeps waiting on its loopback redirect.
+    AuthorizationRequested(Url),
 }
 
 #[derive(Clone)]
 pub(crate) struct HttpTransport {
     client: Result<Client, Arc<RequestProblem>>,
+    tokens: OAuthTokenCache,
+    cookies: CookieJar,
+    chains: PeerChains,
 }
 
 impl HttpTransport {
-    pub(crate) fn new() -> Self {
-        Self::from_builder(Client::builder())
-    }
-
-    fn from_builder(builder: reqwest::ClientBuilder) -> Self {
-        let client = builder
-            .redirect(Policy::none())
-            .referer(false)
-            .no_gzip()
-            .no_brotli()
-            .no_deflate()
-            .no_zstd()
-            .build()
-            .map_err(|error| Arc::new(RequestProblem::transport(error.without_url())));
-        Self { client }
+    pub(crate) fn new(cookies: CookieJar) -> Self {
+        Self::from_builder(Client::builder(), cookies)
+    }
+
+    fn from_builder(builder: reqwest::ClientBuilder, cookies: CookieJar) -> Self {
+        let chains = PeerChains::default();
+        let client = connection::shared_client(builder, &chains).map_err(Arc::new);
+        Self {
+            client,
+            tokens: OAuthTokenCache::default(),
+            cookies,
+            chains,
+        }
     }
 
     #[cfg(test)]
-    fn new_without_proxy() -> Self {
-        Self::from_builder(Client::builder().no_proxy())
+    pub(crate) fn new_without_proxy() -> Self {
+        Self::from_builder(Client::builder().no_proxy(), CookieJar::default())
     }
 
     pub(crate) fn channel() -> (Sender<WorkerEvent>, Receiver<WorkerEvent>) {
@@ -67,7 +86,25 @@ impl HttpTransport {
         let timeout = prepared.timeout;
         let result = match self.client {
             Ok(client) => {
-                let attempt = worker::execute(prepared, client, &sender, started_at);
+                let attempt = async {
+                    // Connection settings are baked into a client, so only
+                    // requests that change them pay for a fresh pool.
+                    let client = if prepared.connection.is_default() {
+                        client
+                    } else {
+                        connection::dedicated_client(&prepared.connection, &self.chains).await?
+                    };
+                    worker::execute(
+                        prepared,
+                        client,
+                        &self.tokens,
+                        &self.cookies,
+                        &self.chains,
+                        &sender,
+                        started_at,
+                    )
+                    .await
+                };
                 match timeout {
                     Some(timeout) => match tokio::time::timeout(timeout, attempt).await {
                         Ok(result) => result,
@@ -86,11 +123,62 @@ impl HttpTransport {
             })
             .await;
     }
+
+    /// Runs one request with nobody watching its progress and returns the
+    /// completed response. There is no browser to approve an authorization
+    /// code grant here, so such a request fails instead of waiting.
+    pub(crate) async fn exchange(
+        self,
+        prepared: PreparedRequest,
+    ) -> Result<Arc<ResponseData>, RequestProblem> {
+        let (sender, receiver) = Self::channel();
+        let run = pin!(self.run(prepared, sender));
+        let collect = pin!(async move {
+            let mut head = None;
+            while let Ok(event) = receiver.recv().await {
+                match event {
+                    WorkerEvent::HeadReceived {
+                        head: received,
+                        head_after,
+                        ..
+                    } => head = Some((received, head_after)),
+                    WorkerEvent::BodyProgress(_) | WorkerEvent::EventStream(_) => {}
+                    WorkerEvent::AuthorizationRequested(_) => {
+                        return Err(RequestProblem::authorization(
+                            AuthorizationProblemKind::Callback,
+                        ));
+                    }
+                    WorkerEvent::Finished {
+                        result,
+                        finished_after,
+                    } => {
+                        let body = result?;
+                        let (head, head_after) = head.ok_or_else(RequestProblem::internal)?;
+                        return Ok(Arc::new(ResponseData::new(
+                            head,
+                            ResponseTiming {
+                                head_after,
+                                completed_after: finished_after,
+                            },
+                            body,
+                        )));
+                    }
+                }
+            }
+            Err(RequestProblem::internal())
+        });
+        // The worker finishes after its terminal event is queued, so the
+        // collector still drains it when the worker wins the race.
+        match select(collect, run).await {
+            Either::Left((result, _)) => result,
+            Either::Right(((), collect)) => collect.await,
+        }
+    }
 }
 
 impl Default for HttpTransport {
     fn default() -> Self {
-        Self::new()
+        Self::new(CookieJar::default())
     }
 }
 
@@ -98,28 +186,41 @@ impl Default for HttpTransport {
 mod tests {
     use std::{io::Write as _, time::Duration};
 
-    use base64::{Engine as _, engine::general_purpose::STANDARD};
+    use base64::{
+        Engine as _,
+        engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    };
     use http::{HeaderMap, Method, header};
     use http_client_test_server::{
         AbortSpec, ContentEncoding as ServerContentEncoding, HeaderSpec, RespondSpec,
         ResponseBodySpec, ResponseFraming, TestServer,
     };
+    use rustls::pki_types::{PrivateKeyDer, pem::PemObject as _};
     use tokio::{
-        io::{AsyncReadExt as _, AsyncWriteExt as _},
+        io::{AsyncRead, AsyncReadExt as _, AsyncWriteExt as _},
         net::TcpListener,
         task::JoinHandle,
     };
+    use tokio_rustls::TlsAcceptor;
     use url::Url;
 
     use super::*;
     use crate::features::request::{
         prepared::{
-            BodyContentType, PreparedBody, PreparedMultipartPart, PreparedRedirect, PreparedRequest,
+            AwsSigV4Credentials, BodyContentType, DigestCredentials, OAuth2Config,
+            OAuth2GrantConfig, PreparedAuth, PreparedBody, PreparedConnection,
+            PreparedMultipartPart, PreparedProxy, PreparedRedirect, PreparedRequest,
         },
-        response::{ActiveBodyStorage, BodyDecoding, CAPTURE_LIMIT_BYTES, StoredBody},
-        runtime::{BodySizeDimension, RequestProblemKind},
+        response::{ActiveBodyStorage, BodyDecoding, CAPTURE_LIMIT_BYTES, StoredBody, TimingPhase},
+        runtime::{BodySizeDimension, ConnectionProblemKind, RequestProblemKind},
     };
 
+    /// A root, an intermediate and a leaf for `localhost` and `127.0.0.1`,
+    /// generated with openssl and valid for a century.
+    const TEST_ROOT: &[u8] = include_bytes!("transport/testdata/root.pem");
+    const SERVER_CHAIN: &[u8] = include_bytes!("transport/testdata/server-chain.pem");
+    const SERVER_KEY: &[u8] = include_bytes!("transport/testdata/server-key.pem");
+
     struct FixtureResponse {
         bytes: &'static [u8],
     }
@@ -143,7 +244,46 @@ mod tests {
         )
     }
 
-    async fn read_request(stream: &mut tokio::net::TcpStream) -> Vec<u8> {
+    /// Answers one request over TLS, presenting the test leaf and
+    /// intermediate.
+    async fn tls_fixture(response: FixtureResponse) -> (Url, JoinHandle<Vec<u8>>) {
+        let config = rustls::ServerConfig::builder_with_provider(Arc::new(
+            rustls::crypto::aws_lc_rs::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(
+            tls::pem_certificates(SERVER_CHAIN).unwrap(),
+            PrivateKeyDer::from_pem_slice(SERVER_KEY).unwrap(),
+        )
+        .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let task = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = acceptor.accept(stream).await.unwrap();
+            let request = read_request(&mut stream).await;
+            stream.write_all(response.bytes).await.unwrap();
+            stream.shutdown().await.unwrap();
+            request
+        });
+        (
+            Url::parse(&format!("https://{address}/start")).unwrap(),
+            task,
+        )
+    }
+
+    /// Trusts the test root for `request`. The file has to outlive the run.
+    fn trust_test_root(request: &mut PreparedRequest) -> tempfile::NamedTempFile {
+        let mut root = tempfile::NamedTempFile::new().unwrap();
+        root.write_all(TEST_ROOT).unwrap();
+        request.connection.ca_certificate = Some(root.path().to_path_buf());
+        root
+    }
+
+    async fn read_request(stream: &mut (impl AsyncRead + Unpin)) -> Vec<u8> {
         let mut request = Vec::new();
         let mut buffer = [0_u8; 4096];
         let header_end = loop {
@@ -188,7 +328,10 @@ mod tests {
                 preserve_method: false,
                 forward_authorization_cross_host: false,
             },
+            auth: PreparedAuth::None,
+            send_cookies: true,
             timeout: Some(Duration::from_secs(2)),
+            connection: PreparedConnection::default(),
         }
     }
 
@@ -198,15 +341,27 @@ mod tests {
 
     async fn run_to_terminal_with_head(
         prepared: PreparedRequest,
+    ) -> (Option<ResponseHead>, Result<CompletedBody, RequestProblem>) {
+        run_on_transport(HttpTransport::new_without_proxy(), prepared).await
+    }
+
+    async fn run_on_transport(
+        transport: HttpTransport,
+        prepared: PreparedRequest,
     ) -> (Option<ResponseHead>, Result<CompletedBody, RequestProblem>) {
         let (sender, receiver) = HttpTransport::channel();
-        let worker = tokio::spawn(HttpTransport::new_without_proxy().run(prepared, sender));
+        let worker = tokio::spawn(transport.run(prepared, sender));
         let mut head = None;
         let result = loop {
             match receiver.recv().await.unwrap() {
                 WorkerEvent::HeadReceived { head: received, .. } => head = Some(received),
-                WorkerEvent::BodyProgress(_) => assert!(head.is_some()),
+                WorkerEvent::BodyProgress(_) | WorkerEvent::EventStream(_) => {
+                    assert!(head.is_some());
+                }
                 WorkerEvent::Finished { result, .. } => break result,
+                WorkerEvent::AuthorizationRequested(_) => {
+                    panic!("unexpected browser authorization")
+                }
             }
         };
         worker.await.unwrap();
@@ -242,6 +397,168 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn every_hop_records_its_connection_and_wait_on_one_axis() {
+        let (url, server) = fixture(vec![
+            FixtureResponse {
+                bytes: b"HTTP/1.1 302 Found\r\nLocation: /final\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            },
+            FixtureResponse {
+                bytes: b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+            },
+        ])
+        .await;
+        let mut request = prepared(url, PreparedBody::None, BodyContentType::None);
+        request.method = Method::GET;
+
+        let (head, result) = run_to_terminal_with_head(request).await;
+        result.unwrap();
+        server.await.unwrap();
+        let hops = head.unwrap().hops;
+        assert_eq!(
+            hops.iter().map(|hop| hop.status).collect::<Vec<_>>(),
+            [http::StatusCode::FOUND, http::StatusCode::OK]
+        );
+        assert_eq!(hops[1].url.path(), "/final");
+        for hop in &hops {
+            assert!(!hop.reused_connection);
+            // The fixture is addressed by IP, so no lookup runs.
+            assert_eq!(
+                hop.spans.iter().map(|span| span.phase).collect::<Vec<_>>(),
+                [TimingPhase::Connect, TimingPhase::Waiting]
+            );
+            assert!(
+                hop.spans
+                    .windows(2)
+                    .all(|pair| pair[0].end <= pair[1].start)
+            );
+        }
+        assert!(hops[0].end() <= hops[1].spans[0].start);
+    }
+
+    #[tokio::test]
+    async fn https_responses_describe_every_certificate_the_server_presented() {
+        let (url, server) = tls_fixture(FixtureResponse {
+            bytes: b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+        })
+        .await;
+        let mut request = prepared(url, PreparedBody::None, BodyContentType::None);
+        request.method = Method::GET;
+        let _root = trust_test_root(&mut request);
+
+        let (head, result) = run_to_terminal_with_head(request).await;
+        assert_eq!(memory_bytes(&result.unwrap()), b"ok");
+        server.await.unwrap();
+        let tls = head.unwrap().tls.unwrap();
+        assert!(!tls.verification_disabled);
+        assert_eq!(
+            tls.peer_certificates
+                .iter()
+                .map(|certificate| certificate.subject.as_str())
+                .collect::<Vec<_>>(),
+            ["CN=localhost", "CN=HTTP Client Test Intermediate"]
+        );
+        assert_eq!(tls.peer_certificates[1].issuer, "CN=HTTP Client Test Root");
+    }
+
+    #[tokio::test]
+    async fn https_hops_time_the_tcp_connect_apart_from_the_tls_handshake() {
+        let (url, server) = tls_fixture(FixtureResponse {
+            bytes: b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+        })
+        .await;
+        let mut request = prepared(url, PreparedBody::None, BodyContentType::None);
+        request.method = Method::GET;
+        let _root = trust_test_root(&mut request);
+
+        let (head, result) = run_to_terminal_with_head(request).await;
+        result.unwrap();
+        server.await.unwrap();
+        let hops = head.unwrap().hops;
+        let spans = &hops[0].spans;
+        assert_eq!(
+            spans.iter().map(|span| span.phase).collect::<Vec<_>>(),
+            [
+                TimingPhase::Connect,
+                TimingPhase::TlsHandshake,
+                TimingPhase::Waiting
+            ]
+        );
+        assert_eq!(spans[0].end, spans[1].start);
+        assert!(spans[1].duration() > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn an_explicit_proxy_receives_absolute_form_requests_with_its_credentials() {
+        let (proxy, server) = fixture(vec![FixtureResponse {
+            bytes: b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+        }])
+        .await;
+        let mut request = prepared(
+            Url::parse("http://origin.invalid/resource?q=1").unwrap(),
+            PreparedBody::None,
+            BodyContentType::None,
+        );
+        request.method = Method::GET;
+        request.connection.proxy = Some(PreparedProxy {
+            url: proxy,
+            credentials: Some(("user".into(), "pass".into())),
+            bypass: String::new(),
+        });
+
+        let (head, result) = run_to_terminal_with_head(request).await;
+        assert_eq!(memory_bytes(&result.unwrap()), b"ok");
+        assert_eq!(head.unwrap().tls, None);
+        let requests = server.await.unwrap();
+        let first = lowercase(&requests[0]);
+        assert!(first.starts_with("get http://origin.invalid/resource?q=1 http/1.1\r\n"));
+        assert!(first.contains(&format!(
+            "proxy-authorization: basic {}\r\n",
+            STANDARD.encode("user:pass").to_ascii_lowercase()
+        )));
+    }
+
+    #[tokio::test]
+    async fn bypassed_hosts_connect_directly() {
+        let (url, server) = fixture(vec![FixtureResponse {
+            bytes: b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+        }])
+        .await;
+        let mut request = prepared(url, PreparedBody::None, BodyContentType::None);
+        request.method = Method::GET;
+        request.connection.proxy = Some(PreparedProxy {
+            url: Url::parse("http://proxy.invalid:3128").unwrap(),
+            credentials: None,
+            bypass: "example.test, 127.0.0.1".into(),
+        });
+
+        let completed = run_to_terminal(request).await.unwrap();
+        assert_eq!(memory_bytes(&completed), b"ok");
+        let requests = server.await.unwrap();
+        assert!(lowercase(&requests[0]).starts_with("get /start http/1.1\r\n"));
+    }
+
+    #[tokio::test]
+    async fn an_unreadable_ca_bundle_fails_before_connecting() {
+        let mut bundle = tempfile::NamedTempFile::new().unwrap();
+        bundle
+            .write_all(b"-----BEGIN CERTIFICATE-----\nnot base64\n-----END CERTIFICATE-----\n")
+            .unwrap();
+        let mut request = prepared(
+            Url::parse("https://origin.invalid/").unwrap(),
+            PreparedBody::None,
+            BodyContentType::None,
+        );
+        request.connection.ca_certificate = Some(bundle.path().to_path_buf());
+
+        let (head, result) = run_to_terminal_with_head(request).await;
+        assert!(head.is_none());
+        assert_eq!(
+            result.unwrap_err().kind(),
+            RequestProblemKind::Connection(ConnectionProblemKind::CaCertificate)
+        );
+    }
+
     #[tokio::test]
     async fn post_redirect_rewrites_to_get_and_explicit_headers_override_generated_ones() {
         let (url, server) = fixture(vec![
@@ -287,6 +604,50 @@ mod tests {
         assert!(second.ends_with("\r\n\r\n"));
     }
 
+    #[tokio::test]
+    async fn jar_cookies_follow_redirect_hops_and_later_runs_unless_disabled() {
+        let (url, server) = fixture(vec![
+            FixtureResponse {
+                bytes: b"HTTP/1.1 302 Found\r\nSet-Cookie: session=abc; Path=/\r\nLocation: /final\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            },
+            OK_RESPONSE,
+            OK_RESPONSE,
+            OK_RESPONSE,
+        ])
+        .await;
+        let transport = HttpTransport::new_without_proxy();
+        let mut request = prepared(url.clone(), PreparedBody::None, BodyContentType::None);
+        request.method = Method::GET;
+        let (_, result) = run_on_transport(transport.clone(), request).await;
+        result.unwrap();
+
+        let mut request = prepared(url.clone(), PreparedBody::None, BodyContentType::None);
+        request.method = Method::GET;
+        request
+            .headers
+            .insert(header::COOKIE, http::HeaderValue::from_static("theme=dark"));
+        let (_, result) = run_on_transport(transport.clone(), request).await;
+        result.unwrap();
+
+        let mut request = prepared(url, PreparedBody::None, BodyContentType::None);
+        request.method = Method::GET;
+        request.send_cookies = false;
+        let (_, result) = run_on_transport(transport.clone(), request).await;
+        result.unwrap();
+
+        let requests = server
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|request| String::from_utf8_lossy(&request).to_ascii_lowercase())
+            .collect::<Vec<_>>();
+        assert!(!requests[0].contains("cookie:"));
+        assert!(requests[1].starts_with("get /final http/1.1\r\n"));
+        assert!(requests[1].contains("cookie: session=abc\r\n"));
+        assert!(requests[2].contains("cookie: theme=dark; session=abc\r\n"));
+        assert!(!requests[3].contains("cookie:"));
+    }
+
     #[tokio::test]
     async fn cross_origin_redirect_matches_postman_header_policy_on_wire() {
         let target_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -648,7 +1009,11 @@ mod tests {
             match receiver.recv().await.unwrap() {
                 WorkerEvent::HeadReceived { .. } => panic!("response head was emitted twice"),
                 WorkerEvent::BodyProgress(_) => progress_seen = true,
+                WorkerEvent::EventStream(_) => panic!("an octet stream was parsed as events"),
                 WorkerEvent::Finished { result, .. } => break result.unwrap(),
+                WorkerEvent::AuthorizationRequested(_) => {
+                    panic!("unexpected browser authorization")
+                }
             }
         };
         assert!(progress_seen);
@@ -657,6 +1022,66 @@ mod tests {
         server.shutdown().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn event_stream_bodies_are_parsed_in_order_before_the_terminal_event() {
+        let server = TestServer::spawn().await.unwrap();
+        let body =
+            "retry: 500\n\nid: 1\ndata: {\"n\": 1}\n\n: keep-alive\nevent: note\ndata: two\n\n";
+        let mut spec = respond(
+            200,
+            ResponseBodySpec::Base64 {
+                value: STANDARD.encode(body),
+            },
+        );
+        spec.headers.push(HeaderSpec {
+            name: "content-type".into(),
+            value: "text/event-stream".into(),
+        });
+        spec.chunk_size_bytes = 5;
+        spec.framing = ResponseFraming::Chunked;
+        let request = prepared(
+            controlled_url(&server, &spec),
+            PreparedBody::None,
+            BodyContentType::None,
+        );
+
+        let (sender, receiver) = HttpTransport::channel();
+        let worker = tokio::spawn(HttpTransport::new_without_proxy().run(request, sender));
+        let mut events = Vec::new();
+        let mut last_event_id = None;
+        let mut retry = None;
+        let completed = loop {
+            match receiver.recv().await.unwrap() {
+                WorkerEvent::EventStream(update) => {
+                    events.extend(update.events);
+                    last_event_id = update.last_event_id.or(last_event_id);
+                    retry = update.retry.or(retry);
+                }
+                WorkerEvent::Finished { result, .. } => break result.unwrap(),
+                WorkerEvent::HeadReceived { .. } | WorkerEvent::BodyProgress(_) => {}
+                WorkerEvent::AuthorizationRequested(_) => {
+                    panic!("unexpected browser authorization")
+                }
+            }
+        };
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (
+                    event.id.as_deref(),
+                    event.event.as_str(),
+                    event.data.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [(Some("1"), "message", "{\"n\": 1}"), (None, "note", "two"),]
+        );
+        assert_eq!(last_event_id.as_deref(), Some("1"));
+        assert_eq!(retry, Some(500));
+        assert_eq!(memory_bytes(&completed), body.as_bytes());
+        worker.await.unwrap();
+        server.shutdown().await.unwrap();
+    }
+
     #[tokio::test]
     async fn receiving_stage_cancellation_releases_the_controlled_connection() {
         let server = TestServer::spawn().await.unwrap();
@@ -869,4 +1294,264 @@ mod tests {
         }
         assert!(terminal_seen);
     }
+
+    fn json_response(body: &str) -> FixtureResponse {
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        FixtureResponse {
+            bytes: Box::leak(response.into_bytes().into_boxed_slice()),
+        }
+    }
+
+    const OK_RESPONSE: FixtureResponse = FixtureResponse {
+        bytes: b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+    };
+
+    fn oauth2_request(url: &Url, grant: OAuth2GrantConfig) -> PreparedRequest {
+        let mut request = prepared(url.clone(), PreparedBody::None, BodyContentType::None);
+        request.method = Method::GET;
+        request.auth = PreparedAuth::OAuth2(OAuth2Config {
+            grant,
+            token_url: url.join("/token").unwrap(),
+            client_id: "client id".into(),
+            client_secret: Some("secret".into()),
+            scope: Some("read".into()),
+        });
+        request
+    }
+
+    fn lowercase(request: &[u8]) -> String {
+        String::from_utf8_lossy(request).to_ascii_lowercase()
+    }
+
+    #[tokio::test]
+    async fn digest_challenge_is_answered_once_on_the_same_hop() {
+        let (url, server) = fixture(vec![
+            FixtureResponse {
+                bytes: b"HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Digest realm=\"api\", qop=\"auth\", nonce=\"abc\", algorithm=SHA-256\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            },
+            OK_RESPONSE,
+        ])
+        .await;
+        let mut request = prepared(url, PreparedBody::None, BodyContentType::None);
+        request.method = Method::GET;
+        request.auth = PreparedAuth::Digest(DigestCredentials {
+            username: "user".into(),
+            password: "pass".into(),
+        });
+
+        let (head, result) = run_to_terminal_with_head(request).await;
+        assert_eq!(head.unwrap().status, http::StatusCode::OK);
+        assert_eq!(memory_bytes(&result.unwrap()), b"ok");
+        let requests = server.await.unwrap();
+        assert!(!lowercase(&requests[0]).contains("authorization:"));
+        let retry = String::from_utf8_lossy(&requests[1]);
+        assert!(retry.starts_with("GET /start HTTP/1.1\r\n"));
+        assert!(retry.contains("authorization: Digest username=\"user\", realm=\"api\""));
+        assert!(retry.contains("algorithm=SHA-256"));
+        assert!(retry.contains("nc=00000001"));
+    }
+
+    #[tokio::test]
+    async fn repeated_digest_rejection_is_the_final_response() {
+        let challenge: &'static [u8] = b"HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Digest realm=\"api\", qop=\"auth\", nonce=\"abc\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        let (url, server) = fixture(vec![
+            FixtureResponse { bytes: challenge },
+            FixtureResponse { bytes: challenge },
+        ])
+        .await;
+        let mut request = prepared(url, PreparedBody::None, BodyContentType::None);
+        request.method = Method::GET;
+        request.auth = PreparedAuth::Digest(DigestCredentials {
+            username: "user".into(),
+            password: "wrong".into(),
+        });
+
+        let (head, result) = run_to_terminal_with_head(request).await;
+        assert_eq!(head.unwrap().status, http::StatusCode::UNAUTHORIZED);
+        assert!(result.is_ok());
+        assert_eq!(server.await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn sigv4_signs_the_hop_and_hashes_the_frozen_body() {
+        let (url, server) = fixture(vec![OK_RESPONSE]).await;
+        let mut request = prepared(
+            url,
+            PreparedBody::Text(b"payload".to_vec()),
+            BodyContentType::Fixed(http::HeaderValue::from_static("text/plain")),
+        );
+        request.headers.insert(
+            header::AUTHORIZATION,
+            http::HeaderValue::from_static("manual"),
+        );
+        request.auth = PreparedAuth::AwsSigV4(AwsSigV4Credentials {
+            access_key_id: "AKID".into(),
+            secret_access_key: "secret".into(),
+            session_token: None,
+            region: "us-east-1".into(),
+            service: "s3".into(),
+        });
+
+        run_to_terminal(request).await.unwrap();
+        let sent = lowercase(&server.await.unwrap()[0]);
+        assert_eq!(sent.matches("authorization:").count(), 1);
+        assert!(sent.contains("authorization: aws4-hmac-sha256 credential=akid/"));
+        assert!(sent.contains("/us-east-1/s3/aws4_request, signedheaders=host;x-amz-content-sha256;x-amz-date, signature="));
+        // SHA-256 of "payload".
+        assert!(sent.contains(
+            "x-amz-content-sha256: 239f59ed55e737c77147cf55ad0c1b030b6d7ee748a7426952f9b852d5a935e5\r\n"
+        ));
+        assert!(sent.contains("x-amz-date: "));
+    }
+
+    #[tokio::test]
+    async fn client_credentials_token_is_cached_across_runs_of_one_transport() {
+        let (url, server) = fixture(vec![
+            json_response(r#"{"access_token":"first","token_type":"Bearer","expires_in":3600}"#),
+            OK_RESPONSE,
+            OK_RESPONSE,
+        ])
+        .await;
+        let transport = HttpTransport::new_without_proxy();
+        for _ in 0..2 {
+            let request = oauth2_request(&url, OAuth2GrantConfig::ClientCredentials);
+            let (_, result) = run_on_transport(transport.clone(), request).await;
+            assert_eq!(memory_bytes(&result.unwrap()), b"ok");
+        }
+
+        let requests = server.await.unwrap();
+        let token = String::from_utf8_lossy(&requests[0]);
+        assert!(token.starts_with("POST /token HTTP/1.1\r\n"));
+        // Basic credentials are form-encoded first, so the space becomes `+`.
+        assert!(token.contains(&format!(
+            "authorization: Basic {}\r\n",
+            STANDARD.encode("client+id:secret")
+        )));
+        assert!(token.ends_with("\r\n\r\ngrant_type=client_credentials&scope=read"));
+        for resource in &requests[1..] {
+            assert!(String::from_utf8_lossy(resource).contains("authorization: Bearer first\r\n"));
+        }
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_refreshed_and_a_rejected_token_endpoint_is_a_problem() {
+        let (url, server) = fixture(vec![
+            json_response(
+                r#"{"access_token":"first","token_type":"bearer","expires_in":0,"refresh_token":"r1"}"#,
+            ),
+            OK_RESPONSE,
+            json_response(r#"{"access_token":"second","token_type":"bearer"}"#),
+            OK_RESPONSE,
+        ])
+        .await;
+        let transport = HttpTransport::new_without_proxy();
+        for _ in 0..2 {
+            let request = oauth2_request(&url, OAuth2GrantConfig::ClientCredentials);
+            run_on_transport(transport.clone(), request)
+                .await
+                .1
+                .unwrap();
+        }
+        let requests = server.await.unwrap();
+        assert!(
+            String::from_utf8_lossy(&requests[2])
+                .ends_with("\r\n\r\ngrant_type=refresh_token&refresh_token=r1")
+        );
+        assert!(String::from_utf8_lossy(&requests[3]).contains("authorization: Bearer second\r\n"));
+
+        let (url, server) = fixture(vec![FixtureResponse {
+            bytes: b"HTTP/1.1 400 Bad Request\r\nContent-Type: application/json\r\nContent-Length: 26\r\nConnection: close\r\n\r\n{\"error\":\"invalid_client\"}",
+        }])
+        .await;
+        let problem = run_to_terminal(oauth2_request(&url, OAuth2GrantConfig::ClientCredentials))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            problem.kind(),
+            RequestProblemKind::Authorization(
+                crate::features::request::runtime::AuthorizationProblemKind::TokenRejected
+            )
+        );
+        assert_eq!(server.await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn authorization_code_grant_uses_pkce_through_the_loopback_redirect() {
+        let (url, server) = fixture(vec![
+            json_response(r#"{"access_token":"granted","token_type":"Bearer"}"#),
+            OK_RESPONSE,
+        ])
+        .await;
+        let request = oauth2_request(
+            &url,
+            OAuth2GrantConfig::AuthorizationCode {
+                authorization_url: Url::parse("https://auth.example/authorize").unwrap(),
+                redirect_uri: Url::parse("http://127.0.0.1/callback").unwrap(),
+            },
+        );
+
+        let (sender, receiver) = HttpTransport::channel();
+        let worker = tokio::spawn(HttpTransport::new_without_proxy().run(request, sender));
+        let WorkerEvent::AuthorizationRequested(authorize) = receiver.recv().await.unwrap() else {
+            panic!("expected a browser authorization request");
+        };
+        let query = authorize
+            .query_pairs()
+            .into_owned()
+            .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(authorize.path(), "/authorize");
+        assert_eq!(query["response_type"], "code");
+        assert_eq!(query["code_challenge_method"], "S256");
+        let mut callback = Url::parse(&query["redirect_uri"]).unwrap();
+        assert_ne!(callback.port(), None);
+        callback
+            .query_pairs_mut()
+            .append_pair("code", "the-code")
+            .append_pair("state", &query["state"]);
+        let page = Client::builder()
+            .no_proxy()
+            .build()
+            .unwrap()
+            .get(callback)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(page.status(), http::StatusCode::OK);
+
+        let result = loop {
+            match receiver.recv().await.unwrap() {
+                WorkerEvent::Finished { result, .. } => break result,
+                WorkerEvent::AuthorizationRequested(_) => {
+                    panic!("authorization was requested twice")
+                }
+                WorkerEvent::HeadReceived { .. }
+                | WorkerEvent::BodyProgress(_)
+                | WorkerEvent::EventStream(_) => {}
+            }
+        };
+        assert_eq!(memory_bytes(&result.unwrap()), b"ok");
+        worker.await.unwrap();
+
+        let requests = server.await.unwrap();
+        let token = String::from_utf8_lossy(&requests[0]).into_owned();
+        let (_, form) = token.split_once("\r\n\r\n").unwrap();
+        let form = url::form_urlencoded::parse(form.as_bytes())
+            .into_owned()
+            .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(form["grant_type"], "authorization_code");
+        assert_eq!(form["code"], "the-code");
+        assert_eq!(form["redirect_uri"], query["redirect_uri"]);
+        assert_eq!(
+            URL_SAFE_NO_PAD.encode(<sha2::Sha256 as sha2::Digest>::digest(
+                form["code_verifier"].as_bytes()
+            )),
+            query["code_challenge"]
+        );
+        assert!(
+            String::from_utf8_lossy(&requests[1]).contains("authorization: Bearer granted\r\n")
+        );
+    }
 }
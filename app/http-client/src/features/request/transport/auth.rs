@@ -0,0 +1,182 @@
+use std::{fmt::Write as _, time::SystemTime};
+
+use async_channel::Sender;
+use http::{HeaderMap, HeaderValue, Method, StatusCode, header};
+use reqwest::Client;
+use sha2::{Digest as _, Sha256};
+use tokio::io::AsyncReadExt as _;
+use url::{Origin, Url};
+
+use super::WorkerEvent;
+use crate::features::request::{
+    prepared::{PreparedAuth, PreparedBody},
+    runtime::RequestProblem,
+};
+
+mod digest;
+mod oauth2;
+mod sigv4;
+
+pub(crate) use oauth2::OAuthTokenCache;
+
+/// Applies worker-resolved authorization to each hop of one request.
+///
+/// Credentials are only sent to the origin of the frozen request URL. A
+/// redirect to any other origin continues without them.
+pub(super) struct RequestAuthorizer<'a> {
+    auth: &'a PreparedAuth,
+    origin: Origin,
+    digest: Option<digest::DigestSession>,
+    payload_sha256: Option<String>,
+    challenges: u8,
+}
+
+impl<'a> RequestAuthorizer<'a> {
+    /// Resolves everything that must happen once per request: an OAuth 2.0
+    /// access token is added to the frozen headers and a SigV4 payload hash
+    /// is computed before the first hop.
+    pub(super) async fn new(
+        auth: &'a PreparedAuth,
+        url: &Url,
+        body: &PreparedBody,
+        client: &Client,
+        tokens: &OAuthTokenCache,
+        sender: &Sender<WorkerEvent>,
+        headers: &mut HeaderMap,
+    ) -> Result<Self, RequestProblem> {
+        let mut payload_sha256 = None;
+        match auth {
+            PreparedAuth::None | PreparedAuth::Digest(_) => {}
+            PreparedAuth::OAuth2(config) => {
+                let token = oauth2::access_token(client, tokens, config, sender).await?;
+                let value = HeaderValue::try_from(format!("Bearer {token}"))
+                    .map_err(|_| RequestProblem::internal())?;
+                headers.insert(header::AUTHORIZATION, value);
+            }
+            PreparedAuth::AwsSigV4(_) => payload_sha256 = Some(payload_hash(body).await?),
+        }
+        Ok(Self {
+            auth,
+            origin: url.origin(),
+            digest: None,
+            payload_sha256,
+            challenges: 0,
+        })
+    }
+
+    /// Adds per-hop authorization. Digest responses and SigV4 signatures
+    /// depend on the hop's method and URL, so they are never reused.
+    pub(super) fn authorize(
+        &mut self,
+        method: &Method,
+        url: &Url,
+        body_available: bool,
+        headers: &mut HeaderMap,
+    ) -> Result<(), RequestProblem> {
+        if url.origin() != self.origin {
+            return Ok(());
+        }
+        match self.auth {
+            PreparedAuth::Digest(credentials) => {
+                if let Some(session) = &mut self.digest {
+                    let value = session
+                        .authorization(credentials, method, url)
+                        .map_err(|_| RequestProblem::internal())?;
+                    headers.insert(header::AUTHORIZATION, value);
+                }
+            }
+            PreparedAuth::AwsSigV4(credentials) => {
+                let payload_sha256 = match (&self.payload_sha256, body_available) {
+                    (Some(hash), true) => hash.as_str(),
+                    _ => sigv4::EMPTY_PAYLOAD_SHA256,
+                };
+                sigv4::sign(
+                    credentials,
+                    method,
+                    url,
+                    headers,
+                    payload_sha256,
+                    SystemTime::now(),
+                )
+                .map_err(|_| RequestProblem::internal())?;
+            }
+            PreparedAuth::None | PreparedAuth::OAuth2(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Returns whether the hop should be sent again with a Digest response.
+    ///
+    /// The first challenge is always answered. A second one is answered only
+    /// when the server marks the previous nonce as stale; anything else is a
+    /// rejection and the 401 becomes the final response.
+    pub(super) fn accept_challenge(
+        &mut self,
+        status: StatusCode,
+        response_headers: &HeaderMap,
+        url: &Url,
+    ) -> bool {
+        if status != StatusCode::UNAUTHORIZED
+            || url.origin() != self.origin
+            || !matches!(self.auth, PreparedAuth::Digest(_))
+        {
+            return false;
+        }
+        let Some(challenge) = digest::DigestChallenge::select(response_headers) else {
+            return false;
+        };
+        let retry = match self.challenges {
+            0 => true,
+            1 => challenge.stale(),
+            _ => false,
+        };
+        if retry {
+            self.challenges += 1;
+            self.digest = Some(digest::DigestSession::new(challenge));
+        }
+        retry
+    }
+
+    /// Resets the challenge budget after a redirect moves to a new hop.
+    pub(super) fn next_hop(&mut self) {
+        self.challenges = 0;
+    }
+}
+
+/// Hashes the frozen body once for SigV4. Streaming multipart bodies are
+/// signed as unsigned payloads instead of being read twice.
+async fn payload_hash(body: &PreparedBody) -> Result<String, RequestProblem> {
+    match body {
+        PreparedBody::None => Ok(sigv4::EMPTY_PAYLOAD_SHA256.to_owned()),
+        PreparedBody::Text(bytes) | PreparedBody::UrlEncoded(bytes) => {
+            Ok(hex(&Sha256::digest(bytes)))
+        }
+        PreparedBody::Binary(path) => {
+            let mut file = tokio::fs::File::open(path)
+                .await
+                .map_err(RequestProblem::request_body_read)?;
+            let mut hasher = Sha256::new();
+            let mut buffer = vec![0_u8; 64 * 1024];
+            loop {
+                let read = file
+                    .read(&mut buffer)
+                    .await
+                    .map_err(RequestProblem::request_body_read)?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+            }
+            Ok(hex(&hasher.finalize()))
+        }
+        PreparedBody::Multipart(_) => Ok(sigv4::UNSIGNED_PAYLOAD.to_owned()),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(&mut output, "{byte:02x}").expect("writing to String cannot fail");
+    }
+    output
+}
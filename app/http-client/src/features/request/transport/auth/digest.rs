@@ -0,0 +1,398 @@
+use std::fmt::Write as _;
+
+use http::{HeaderMap, HeaderValue, Method, header};
+use md5::Md5;
+use sha2::{Digest as _, Sha256};
+use url::Url;
+
+use super::hex;
+use crate::features::request::prepared::DigestCredentials;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum DigestAlgorithm {
+    Md5Sess,
+    Md5,
+    Sha256Sess,
+    Sha256,
+}
+
+impl DigestAlgorithm {
+    fn parse(raw: Option<&str>) -> Option<Self> {
+        match raw.map(str::to_ascii_uppercase).as_deref() {
+            None | Some("MD5") => Some(Self::Md5),
+            Some("MD5-SESS") => Some(Self::Md5Sess),
+            Some("SHA-256") => Some(Self::Sha256),
+            Some("SHA-256-SESS") => Some(Self::Sha256Sess),
+            Some(_) => None,
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Md5Sess => "MD5-sess",
+            Self::Sha256 => "SHA-256",
+            Self::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    const fn session(self) -> bool {
+        matches!(self, Self::Md5Sess | Self::Sha256Sess)
+    }
+
+    fn hash(self, input: &str) -> String {
+        match self {
+            Self::Md5 | Self::Md5Sess => hex(&Md5::digest(input.as_bytes())),
+            Self::Sha256 | Self::Sha256Sess => hex(&Sha256::digest(input.as_bytes())),
+        }
+    }
+}
+
+/// One supported `Digest` challenge from a 401 response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: DigestAlgorithm,
+    /// `true` for `qop=auth`; `false` for the RFC 2069 form without qop.
+    qop_auth: bool,
+    userhash: bool,
+    stale: bool,
+}
+
+impl DigestChallenge {
+    /// Picks the strongest supported Digest challenge from every
+    /// `WWW-Authenticate` field. Challenges that only offer `auth-int` or an
+    /// unknown algorithm are skipped.
+    pub(super) fn select(headers: &HeaderMap) -> Option<Self> {
+        headers
+            .get_all(header::WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(parse_challenges)
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("digest"))
+            .filter_map(|(_, params)| Self::from_params(&params))
+            .max_by_key(|challenge| challenge.algorithm)
+    }
+
+    fn from_params(params: &[(String, String)]) -> Option<Self> {
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        let qop_auth = match param("qop") {
+            None => false,
+            Some(qop) => {
+                if !qop.split(',').any(|option| option.trim() == "auth") {
+                    return None;
+                }
+                true
+            }
+        };
+        Some(Self {
+            realm: param("realm")?.to_owned(),
+            nonce: param("nonce")?.to_owned(),
+            opaque: param("opaque").map(str::to_owned),
+            algorithm: DigestAlgorithm::parse(param("algorithm"))?,
+            qop_auth,
+            userhash: param("userhash").is_some_and(|value| value.eq_ignore_ascii_case("true")),
+            stale: param("stale").is_some_and(|value| value.eq_ignore_ascii_case("true")),
+        })
+    }
+
+    pub(super) const fn stale(&self) -> bool {
+        self.stale
+    }
+}
+
+/// Answers one accepted challenge for the hops that stay on its origin.
+pub(super) struct DigestSession {
+    challenge: DigestChallenge,
+    nonce_count: u32,
+    cnonce: String,
+}
+
+impl DigestSession {
+    pub(super) fn new(challenge: DigestChallenge) -> Self {
+        let mut bytes = [0_u8; 16];
+        // A fixed client nonce is still a valid answer; the server nonce
+        // carries the replay protection.
+        let _ = getrandom::fill(&mut bytes);
+        Self::with_cnonce(challenge, hex(&bytes))
+    }
+
+    fn with_cnonce(challenge: DigestChallenge, cnonce: String) -> Self {
+        Self {
+            challenge,
+            nonce_count: 0,
+            cnonce,
+        }
+    }
+
+    pub(super) fn authorization(
+        &mut self,
+        credentials: &DigestCredentials,
+        method: &Method,
+        url: &Url,
+    ) -> Result<HeaderValue, http::header::InvalidHeaderValue> {
+        self.nonce_count = self.nonce_count.wrapping_add(1);
+        let challenge = &self.challenge;
+        let algorithm = challenge.algorithm;
+        let uri = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_owned(),
+        };
+        let nonce_count = format!("{:08x}", self.nonce_count);
+
+        let mut ha1 = algorithm.hash(&format!(
+            "{}:{}:{}",
+            credentials.username, challenge.realm, credentials.password
+        ));
+        if algorithm.session() {
+            ha1 = algorithm.hash(&format!("{ha1}:{}:{}", challenge.nonce, self.cnonce));
+        }
+        let ha2 = algorithm.hash(&format!("{}:{uri}", method.as_str()));
+        let response = if challenge.qop_auth {
+            algorithm.hash(&format!(
+                "{ha1}:{}:{nonce_count}:{}:auth:{ha2}",
+                challenge.nonce, self.cnonce
+            ))
+        } else {
+            algorithm.hash(&format!("{ha1}:{}:{ha2}", challenge.nonce))
+        };
+        let username = if challenge.userhash {
+            algorithm.hash(&format!("{}:{}", credentials.username, challenge.realm))
+        } else {
+            credentials.username.clone()
+        };
+
+        let mut value = format!(
+            "Digest username={}, realm={}, uri={}, algorithm={}, nonce={}",
+            quoted(&username),
+            quoted(&challenge.realm),
+            quoted(&uri),
+            algorithm.name(),
+            quoted(&challenge.nonce),
+        );
+        if challenge.qop_auth {
+            write!(
+                &mut value,
+                ", nc={nonce_count}, cnonce={}, qop=auth",
+                quoted(&self.cnonce)
+            )
+            .expect("writing to String cannot fail");
+        }
+        write!(&mut value, ", response={}", quoted(&response))
+            .expect("writing to String cannot fail");
+        if let Some(opaque) = &challenge.opaque {
+            write!(&mut value, ", opaque={}", quoted(opaque))
+                .expect("writing to String cannot fail");
+        }
+        if challenge.userhash {
+            value.push_str(", userhash=true");
+        }
+        HeaderValue::try_from(value)
+    }
+}
+
+/// Splits one `WWW-Authenticate` field into `(scheme, params)` challenges.
+/// Parameter names are lower-cased and quoted values are unescaped.
+fn parse_challenges(value: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut challenges: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    for item in split_list(value) {
+        let item = item.trim();
+        if item.is_empty() {
+            continue;
+        }
+        let equals = item.find('=');
+        let space = item.find(char::is_whitespace);
+        match (space, equals) {
+            (Some(space), equals) if equals.is_none_or(|equals| space < equals) => {
+                let mut params = Vec::new();
+                let rest = item[space..].trim();
+                if let Some(param) = parse_param(rest) {
+                    params.push(param);
+                }
+                challenges.push((item[..space].to_owned(), params));
+            }
+            (_, Some(_)) => {
+                if let (Some((_, params)), Some(param)) = (challenges.last_mut(), parse_param(item))
+                {
+                    params.push(param);
+                }
+            }
+            (_, None) => challenges.push((item.to_owned(), Vec::new())),
+        }
+    }
+    challenges
+}
+
+fn split_list(value: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (index, character) in value.char_indices() {
+        match character {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                items.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&value[start..]);
+    items
+}
+
+fn parse_param(raw: &str) -> Option<(String, String)> {
+    let (name, value) = raw.split_once('=')?;
+    let name = name.trim().to_ascii_lowercase();
+    let value = value.trim();
+    let value = match value.strip_prefix('"') {
+        Some(quoted) => {
+            let mut unescaped = String::with_capacity(quoted.len());
+            let mut characters = quoted.chars();
+            while let Some(character) = characters.next() {
+                match character {
+                    '\\' => unescaped.extend(characters.next()),
+                    '"' => break,
+                    character => unescaped.push(character),
+                }
+            }
+            unescaped
+        }
+        None => value.to_owned(),
+    };
+    (!name.is_empty()).then_some((name, value))
+}
+
+fn quoted(value: &str) -> String {
+    let mut output = String::with_capacity(value.len() + 2);
+    output.push('"');
+    for character in value.chars() {
+        if matches!(character, '"' | '\\') {
+            output.push('\\');
+        }
+        output.push(character);
+    }
+    output.push('"');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenge_headers(values: &[&'static str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(header::WWW_AUTHENTICATE, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    fn mufasa() -> DigestCredentials {
+        DigestCredentials {
+            username: "Mufasa".into(),
+            password: "Circle of Life".into(),
+        }
+    }
+
+    const RFC_7616_CNONCE: &str = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+
+    #[test]
+    fn rfc_7616_example_selects_sha_256_and_matches_both_responses() {
+        let headers = challenge_headers(&[
+            "Digest realm=\"http-auth@example.org\", qop=\"auth, auth-int\", algorithm=SHA-256, \
+             nonce=\"7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v\", \
+             opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\"",
+            "Digest realm=\"http-auth@example.org\", qop=\"auth, auth-int\", algorithm=MD5, \
+             nonce=\"7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v\", \
+             opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\"",
+        ]);
+        let url = Url::parse("http://www.example.org/dir/index.html").unwrap();
+
+        let challenge = DigestChallenge::select(&headers).unwrap();
+        assert_eq!(challenge.algorithm, DigestAlgorithm::Sha256);
+        let value = DigestSession::with_cnonce(challenge, RFC_7616_CNONCE.into())
+            .authorization(&mufasa(), &Method::GET, &url)
+            .unwrap();
+        let value = value.to_str().unwrap();
+        assert!(value.contains(
+            "response=\"753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1\""
+        ));
+        assert!(value.contains("nc=00000001"));
+        assert!(value.contains("uri=\"/dir/index.html\""));
+        assert!(value.contains("opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\""));
+
+        let md5 = DigestChallenge::from_params(
+            &parse_challenges(
+                headers
+                    .get_all(header::WWW_AUTHENTICATE)
+                    .iter()
+                    .nth(1)
+                    .unwrap()
+                    .to_str()
+                    .unwrap(),
+            )[0]
+            .1,
+        )
+        .unwrap();
+        let value = DigestSession::with_cnonce(md5, RFC_7616_CNONCE.into())
+            .authorization(&mufasa(), &Method::GET, &url)
+            .unwrap();
+        assert!(
+            value
+                .to_str()
+                .unwrap()
+                .contains("response=\"8ca523f5e9506fed4657c9700eebdbec\"")
+        );
+    }
+
+    #[test]
+    fn challenge_lists_mix_schemes_and_skip_unsupported_digest_options() {
+        let headers = challenge_headers(&[
+            "Basic realm=\"basic, with comma\", Digest realm=\"r\", nonce=\"n\", qop=\"auth-int\"",
+            "Negotiate abc==, Digest realm=\"r\", nonce=\"n2\", algorithm=SHA-512-256",
+        ]);
+        assert_eq!(DigestChallenge::select(&headers), None);
+
+        let headers = challenge_headers(&[
+            "Basic realm=\"x\", Digest realm=\"a \\\"quoted\\\" realm\", nonce=\"n\", stale=TRUE",
+        ]);
+        let challenge = DigestChallenge::select(&headers).unwrap();
+        assert_eq!(challenge.realm, "a \"quoted\" realm");
+        assert_eq!(challenge.algorithm, DigestAlgorithm::Md5);
+        assert!(!challenge.qop_auth);
+        assert!(challenge.stale());
+    }
+
+    #[test]
+    fn nonce_count_advances_and_userhash_hides_the_username() {
+        let headers = challenge_headers(&[
+            "Digest realm=\"r\", nonce=\"n\", qop=\"auth\", userhash=true, algorithm=SHA-256-sess",
+        ]);
+        let mut session =
+            DigestSession::with_cnonce(DigestChallenge::select(&headers).unwrap(), "client".into());
+        let url = Url::parse("http://example.test/path?query=1").unwrap();
+        let first = session
+            .authorization(&mufasa(), &Method::POST, &url)
+            .unwrap();
+        let second = session
+            .authorization(&mufasa(), &Method::POST, &url)
+            .unwrap();
+        assert!(first.to_str().unwrap().contains("nc=00000001"));
+        assert!(second.to_str().unwrap().contains("nc=00000002"));
+        assert!(second.to_str().unwrap().contains("uri=\"/path?query=1\""));
+        assert!(second.to_str().unwrap().contains("algorithm=SHA-256-sess"));
+        assert!(!second.to_str().unwrap().contains("Mufasa"));
+        assert!(second.to_str().unwrap().ends_with("userhash=true"));
+    }
+}
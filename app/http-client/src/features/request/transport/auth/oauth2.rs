@@ -0,0 +1,627 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_channel::Sender;
+use base64::{
+    Engine as _,
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+};
+use futures_util::{StreamExt as _, stream::FuturesUnordered};
+use http::{HeaderValue, header};
+use reqwest::Client;
+use sha2::{Digest as _, Sha256};
+use tokio::{
+    io::{AsyncReadExt as _, AsyncWriteExt as _},
+    net::{TcpListener, TcpStream},
+};
+use url::{Url, form_urlencoded};
+
+use crate::features::request::{
+    prepared::{OAuth2Config, OAuth2GrantConfig},
+    runtime::{AuthorizationProblemKind, RequestProblem},
+    transport::WorkerEvent,
+};
+
+/// Tokens are treated as expired this long before the server says so, so a
+/// token is not sent while it is about to lapse.
+const EXPIRY_SKEW: Duration = Duration::from_secs(30);
+const TOKEN_RESPONSE_LIMIT: usize = 1024 * 1024;
+const CALLBACK_REQUEST_LIMIT: usize = 16 * 1024;
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const CALLBACK_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Session-lifetime token cache shared by every run of one transport.
+///
+/// Tokens never leave memory. Entries are keyed by everything that changes
+/// which token the server would issue.
+#[derive(Clone, Default)]
+pub(crate) struct OAuthTokenCache {
+    entries: Arc<Mutex<HashMap<TokenKey, CachedToken>>>,
+}
+
+impl OAuthTokenCache {
+    fn get(&self, key: &TokenKey) -> Option<CachedToken> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(key)
+            .cloned()
+    }
+
+    fn insert(&self, key: TokenKey, token: CachedToken) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(key, token);
+    }
+
+    fn remove(&self, key: &TokenKey) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(key);
+    }
+}
+
+impl fmt::Debug for OAuthTokenCache {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self
+            .entries
+            .lock()
+            .map(|entries| entries.len())
+            .unwrap_or_default();
+        formatter
+            .debug_struct("OAuthTokenCache")
+            .field("count", &count)
+            .finish()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TokenKey {
+    token_url: String,
+    client_id: String,
+    scope: Option<String>,
+    grant: GrantKey,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum GrantKey {
+    ClientCredentials,
+    Password { username: String },
+    AuthorizationCode { authorization_url: String },
+}
+
+impl TokenKey {
+    fn new(config: &OAuth2Config) -> Self {
+        Self {
+            token_url: config.token_url.to_string(),
+            client_id: config.client_id.clone(),
+            scope: config.scope.clone(),
+            grant: match &config.grant {
+                OAuth2GrantConfig::ClientCredentials => GrantKey::ClientCredentials,
+                OAuth2GrantConfig::Password { username, .. } => GrantKey::Password {
+                    username: username.clone(),
+                },
+                OAuth2GrantConfig::AuthorizationCode {
+                    authorization_url, ..
+                } => GrantKey::AuthorizationCode {
+                    authorization_url: authorization_url.to_string(),
+                },
+            },
+        }
+    }
+}
+
+#[derive(Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: Option<Instant>,
+    refresh_token: Option<String>,
+}
+
+impl CachedToken {
+    fn is_fresh(&self, now: Instant) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| now + EXPIRY_SKEW < expires_at)
+    }
+}
+
+/// Returns a usable access token, preferring the cache, then a refresh
+/// grant, then the configured grant.
+pub(super) async fn access_token(
+    client: &Client,
+    cache: &OAuthTokenCache,
+    config: &OAuth2Config,
+    sender: &Sender<WorkerEvent>,
+) -> Result<String, RequestProblem> {
+    let key = TokenKey::new(config);
+    let cached = cache.get(&key);
+    if let Some(token) = &cached
+        && token.is_fresh(Instant::now())
+    {
+        return Ok(token.access_token.clone());
+    }
+
+    if let Some(refresh_token) = cached.and_then(|token| token.refresh_token) {
+        let refreshed = request_token(
+            client,
+            config,
+            vec![
+                ("grant_type", "refresh_token".to_owned()),
+                ("refresh_token", refresh_token.clone()),
+            ],
+        )
+        .await;
+        match refreshed {
+            Ok(mut token) => {
+                token.refresh_token.get_or_insert(refresh_token);
+                let access_token = token.access_token.clone();
+                cache.insert(key, token);
+                return Ok(access_token);
+            }
+            Err(problem) => {
+                tracing::debug!(
+                    operation = "oauth2-refresh",
+                    kind = ?problem.kind(),
+                    "refresh grant failed; requesting a new token"
+                );
+                cache.remove(&key);
+            }
+        }
+    }
+
+    let token = match &config.grant {
+        OAuth2GrantConfig::ClientCredentials => {
+            let mut params = vec![("grant_type", "client_credentials".to_owned())];
+            params.extend(config.scope.clone().map(|scope| ("scope", scope)));
+            request_token(client, config, params).await?
+        }
+        OAuth2GrantConfig::Password { username, password } => {
+            let mut params = vec![
+                ("grant_type", "password".to_owned()),
+                ("username", username.clone()),
+                ("password", password.clone()),
+            ];
+            params.extend(config.scope.clone().map(|scope| ("scope", scope)));
+            request_token(client, config, params).await?
+        }
+        OAuth2GrantConfig::AuthorizationCode {
+            authorization_url,
+            redirect_uri,
+        } => authorize_with_pkce(client, config, authorization_url, redirect_uri, sender).await?,
+    };
+    let access_token = token.access_token.clone();
+    cache.insert(key, token);
+    Ok(access_token)
+}
+
+/// Runs the authorization code grant with PKCE (RFC 7636) through a
+/// loopback redirect listener (RFC 8252).
+async fn authorize_with_pkce(
+    client: &Client,
+    config: &OAuth2Config,
+    authorization_url: &Url,
+    redirect_uri: &Url,
+    sender: &Sender<WorkerEvent>,
+) -> Result<CachedToken, RequestProblem> {
+    let (listener, redirect_uri) = bind_loopback(redirect_uri).await?;
+    let verifier = random_token()?;
+    let state = random_token()?;
+
+    let mut authorize = authorization_url.clone();
+    {
+        let mut query = authorize.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &config.client_id)
+            .append_pair("redirect_uri", redirect_uri.as_str())
+            .append_pair("state", &state)
+            .append_pair("code_challenge", &pkce_challenge(&verifier))
+            .append_pair("code_challenge_method", "S256");
+        if let Some(scope) = &config.scope {
+            query.append_pair("scope", scope);
+        }
+    }
+    sender
+        .send(WorkerEvent::AuthorizationRequested(authorize))
+        .await
+        .map_err(|_| RequestProblem::internal())?;
+
+    let code = tokio::time::timeout(
+        CALLBACK_TIMEOUT,
+        receive_code(&listener, redirect_uri.path(), &state),
+    )
+    .await
+    .map_err(|_| RequestProblem::authorization(AuthorizationProblemKind::Callback))??;
+    drop(listener);
+
+    request_token(
+        client,
+        config,
+        vec![
+            ("grant_type", "authorization_code".to_owned()),
+            ("code", code),
+            ("redirect_uri", redirect_uri.to_string()),
+            ("code_verifier", verifier),
+        ],
+    )
+    .await
+}
+
+async fn bind_loopback(redirect_uri: &Url) -> Result<(TcpListener, Url), RequestProblem> {
+    let host = match redirect_uri.host() {
+        Some(url::Host::Ipv6(address)) => address.to_string(),
+        Some(url::Host::Ipv4(address)) => address.to_string(),
+        _ => "127.0.0.1".to_owned(),
+    };
+    // Without an explicit port the system picks one and the redirect URI sent
+    // to the provider is rewritten to match, as RFC 8252 §7.3 allows.
+    let port = redirect_uri.port().unwrap_or(0);
+    let listener = TcpListener::bind((host.as_str(), port))
+        .await
+        .map_err(|_| RequestProblem::authorization(AuthorizationProblemKind::Callback))?;
+    let bound = listener
+        .local_addr()
+        .map_err(|_| RequestProblem::authorization(AuthorizationProblemKind::Callback))?;
+    let mut redirect_uri = redirect_uri.clone();
+    redirect_uri
+        .set_port(Some(bound.port()))
+        .map_err(|()| RequestProblem::internal())?;
+    Ok((listener, redirect_uri))
+}
+
+/// Accepts browser requests until one carries this flow's `state` to the
+/// redirect path. Connections are read side by side, so a speculative
+/// connection the browser never uses does not hold up the callback, and
+/// anything else that arrives, such as a favicon request or a stale
+/// callback, is answered with `400` and ignored.
+async fn receive_code(
+    listener: &TcpListener,
+    path: &str,
+    state: &str,
+) -> Result<String, RequestProblem> {
+    let mut reading = FuturesUnordered::new();
+    loop {
+        let (mut stream, target) = tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((stream, _)) = accepted else {
+                    return Err(RequestProblem::authorization(
+                        AuthorizationProblemKind::Callback,
+                    ));
+                };
+                reading.push(read_request_target(stream));
+                continue;
+            }
+            Some(read) = reading.next() => read,
+        };
+        let Some(target) = target
+            .and_then(|target| Url::parse("http://loopback").ok()?.join(&target).ok())
+            .filter(|target| target.path() == path)
+        else {
+            respond(&mut stream, "400 Bad Request", CALLBACK_FAILED_PAGE).await;
+            continue;
+        };
+
+        let pairs = target.query_pairs().collect::<HashMap<_, _>>();
+        if pairs.get("state").is_none_or(|returned| returned != state) {
+            respond(&mut stream, "400 Bad Request", CALLBACK_FAILED_PAGE).await;
+            continue;
+        }
+        let result = match (pairs.get("code"), pairs.get("error")) {
+            (Some(code), None) => Ok(code.to_string()),
+            (_, Some(_)) => Err(RequestProblem::authorization(
+                AuthorizationProblemKind::Denied,
+            )),
+            (None, None) => {
+                respond(&mut stream, "400 Bad Request", CALLBACK_FAILED_PAGE).await;
+                continue;
+            }
+        };
+        let page = if result.is_ok() {
+            CALLBACK_COMPLETE_PAGE
+        } else {
+            CALLBACK_FAILED_PAGE
+        };
+        respond(&mut stream, "200 OK", page).await;
+        return result;
+    }
+}
+
+const CALLBACK_COMPLETE_PAGE: &str = "<!doctype html><title>Authorization complete</title>\
+<p>Authorization complete. You can close this window and return to HTTP Client.</p>";
+const CALLBACK_FAILED_PAGE: &str = "<!doctype html><title>Authorization failed</title>\
+<p>Authorization did not complete. Return to HTTP Client for details.</p>";
+
+/// Reads the target of a `GET` request. A connection that stays silent for
+/// `CALLBACK_READ_TIMEOUT` is given up on.
+async fn read_request_target(mut stream: TcpStream) -> (TcpStream, Option<String>) {
+    let target = tokio::time::timeout(CALLBACK_READ_TIMEOUT, request_target(&mut stream))
+        .await
+        .ok()
+        .flatten();
+    (stream, target)
+}
+
+async fn request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut request = Vec::new();
+    let mut buffer = [0_u8; 1024];
+    while !request.windows(4).any(|part| part == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 || request.len() + read > CALLBACK_REQUEST_LIMIT {
+            return None;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+    let line = request.split(|byte| *byte == b'\n').next()?;
+    let mut parts = std::str::from_utf8(line).ok()?.split_whitespace();
+    (parts.next()? == "GET").then_some(())?;
+    parts.next().map(str::to_owned)
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, page: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{page}",
+        page.len()
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Sends one token endpoint request (RFC 6749 §4–6).
+///
+/// Confidential clients authenticate with HTTP Basic; public clients send
+/// only their `client_id` in the form body.
+async fn request_token(
+    client: &Client,
+    config: &OAuth2Config,
+    params: Vec<(&'static str, String)>,
+) -> Result<CachedToken, RequestProblem> {
+    let mut form = form_urlencoded::Serializer::new(String::new());
+    for (name, value) in &params {
+        form.append_pair(name, value);
+    }
+    let mut builder = client
+        .post(config.token_url.clone())
+        .header(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        )
+        .header(header::ACCEPT, HeaderValue::from_static("application/json"));
+    match &config.client_secret {
+        Some(secret) => {
+            let encode =
+                |value: &str| form_urlencoded::byte_serialize(value.as_bytes()).collect::<String>();
+            let credentials =
+                STANDARD.encode(format!("{}:{}", encode(&config.client_id), encode(secret)));
+            let value = HeaderValue::try_from(format!("Basic {credentials}"))
+                .map_err(|_| RequestProblem::internal())?;
+            builder = builder.header(header::AUTHORIZATION, value);
+        }
+        None => {
+            form.append_pair("client_id", &config.client_id);
+        }
+    }
+
+    let mut response = builder
+        .body(form.finish())
+        .send()
+        .await
+        .map_err(|error| RequestProblem::authorization_transport(error.without_url()))?;
+    let status = response.status();
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|error| RequestProblem::authorization_transport(error.without_url()))?
+    {
+        if body.len() + chunk.len() > TOKEN_RESPONSE_LIMIT {
+            return Err(RequestProblem::authorization(
+                AuthorizationProblemKind::InvalidTokenResponse,
+            ));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    if !status.is_success() {
+        return Err(RequestProblem::authorization(
+            AuthorizationProblemKind::TokenRejected,
+        ));
+    }
+    parse_token_response(&body, Instant::now()).ok_or(RequestProblem::authorization(
+        AuthorizationProblemKind::InvalidTokenResponse,
+    ))
+}
+
+fn parse_token_response(body: &[u8], received_at: Instant) -> Option<CachedToken> {
+    let value = serde_json::from_slice::<serde_json::Value>(body).ok()?;
+    let access_token = value.get("access_token")?.as_str()?;
+    if access_token.is_empty() || HeaderValue::from_str(&format!("Bearer {access_token}")).is_err()
+    {
+        return None;
+    }
+    // RFC 6749 requires `token_type`; a missing value is accepted because
+    // several providers omit it for bearer tokens.
+    if let Some(token_type) = value.get("token_type")
+        && !token_type
+            .as_str()
+            .is_some_and(|token_type| token_type.eq_ignore_ascii_case("bearer"))
+    {
+        return None;
+    }
+    let expires_in = match value.get("expires_in") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::Number(number)) => Some(number.as_u64()?),
+        Some(serde_json::Value::String(number)) => Some(number.trim().parse().ok()?),
+        Some(_) => return None,
+    };
+    Some(CachedToken {
+        access_token: access_token.to_owned(),
+        expires_at: expires_in.map(|seconds| received_at + Duration::from_secs(seconds)),
+        refresh_token: value
+            .get("refresh_token")
+            .and_then(serde_json::Value::as_str)
+            .filter(|token| !token.is_empty())
+            .map(str::to_owned),
+    })
+}
+
+fn random_token() -> Result<String, RequestProblem> {
+    let mut bytes = [0_u8; 32];
+    getrandom::fill(&mut bytes).map_err(|_| RequestProblem::internal())?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pkce_challenge_matches_rfc_7636_appendix_b() {
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        let verifier = random_token().unwrap();
+        assert_eq!(verifier.len(), 43);
+        assert_ne!(verifier, random_token().unwrap());
+    }
+
+    #[test]
+    fn token_responses_require_a_bearer_token_and_a_numeric_lifetime() {
+        let now = Instant::now();
+        let token = parse_token_response(
+            br#"{"access_token":"abc","token_type":"Bearer","expires_in":"60","refresh_token":"r"}"#,
+            now,
+        )
+        .unwrap();
+        assert_eq!(token.access_token, "abc");
+        assert_eq!(token.expires_at, Some(now + Duration::from_secs(60)));
+        assert_eq!(token.refresh_token.as_deref(), Some("r"));
+        assert!(token.is_fresh(now));
+        assert!(!token.is_fresh(now + Duration::from_secs(31)));
+
+        let token = parse_token_response(br#"{"access_token":"abc"}"#, now).unwrap();
+        assert!(token.is_fresh(now + Duration::from_secs(86_400)));
+
+        for rejected in [
+            &br#"{"access_token":"abc","token_type":"mac"}"#[..],
+            br#"{"access_token":"","token_type":"bearer"}"#,
+            br#"{"access_token":"a\nb"}"#,
+            br#"{"access_token":"abc","expires_in":"soon"}"#,
+            br#"{"error":"invalid_client"}"#,
+            b"not json",
+        ] {
+            assert!(parse_token_response(rejected, now).is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn loopback_listener_picks_a_port_and_ignores_unrelated_paths() {
+        let (listener, redirect) = bind_loopback(&Url::parse("http://127.0.0.1/callback").unwrap())
+            .await
+            .unwrap();
+        assert_ne!(redirect.port(), None);
+        let address = listener.local_addr().unwrap();
+
+        let browser = tokio::spawn(async move {
+            let mut pages = Vec::new();
+            for target in ["/favicon.ico", "/callback?state=expected&code=granted"] {
+                let mut stream = TcpStream::connect(address).await.unwrap();
+                stream
+                    .write_all(format!("GET {target} HTTP/1.1\r\nHost: x\r\n\r\n").as_bytes())
+                    .await
+                    .unwrap();
+                let mut page = String::new();
+                stream.read_to_string(&mut page).await.unwrap();
+                pages.push(page);
+            }
+            pages
+        });
+
+        let code = receive_code(&listener, "/callback", "expected")
+            .await
+            .unwrap();
+        assert_eq!(code, "granted");
+        let pages = browser.await.unwrap();
+        assert!(pages[0].starts_with("HTTP/1.1 400"));
+        assert!(pages[1].contains("Authorization complete"));
+    }
+
+    #[tokio::test]
+    async fn an_idle_connection_and_a_foreign_state_do_not_end_the_flow() {
+        let (listener, _) = bind_loopback(&Url::parse("http://127.0.0.1/cb").unwrap())
+            .await
+            .unwrap();
+        let address = listener.local_addr().unwrap();
+        let browser = tokio::spawn(async move {
+            // A preconnect that never sends anything.
+            let idle = TcpStream::connect(address).await.unwrap();
+            let mut pages = Vec::new();
+            for query in ["state=forged&code=stolen", "state=expected&code=granted"] {
+                let mut stream = TcpStream::connect(address).await.unwrap();
+                stream
+                    .write_all(format!("GET /cb?{query} HTTP/1.1\r\n\r\n").as_bytes())
+                    .await
+                    .unwrap();
+                let mut page = String::new();
+                stream.read_to_string(&mut page).await.unwrap();
+                pages.push(page);
+            }
+            drop(idle);
+            pages
+        });
+
+        let code = tokio::time::timeout(
+            Duration::from_secs(5),
+            receive_code(&listener, "/cb", "expected"),
+        )
+        .await
+        .expect("the idle connection held up the callback")
+        .unwrap();
+        assert_eq!(code, "granted");
+        let pages = browser.await.unwrap();
+        assert!(pages[0].starts_with("HTTP/1.1 400"));
+        assert!(pages[1].contains("Authorization complete"));
+    }
+
+    #[tokio::test]
+    async fn callback_with_an_error_is_denied() {
+        let (listener, _) = bind_loopback(&Url::parse("http://127.0.0.1/cb").unwrap())
+            .await
+            .unwrap();
+        let address = listener.local_addr().unwrap();
+        let browser = tokio::spawn(async move {
+            let mut stream = TcpStream::connect(address).await.unwrap();
+            stream
+                .write_all(b"GET /cb?state=expected&error=access_denied HTTP/1.1\r\n\r\n")
+                .await
+                .unwrap();
+            let mut page = String::new();
+            stream.read_to_string(&mut page).await.unwrap();
+            page
+        });
+        let problem = receive_code(&listener, "/cb", "expected")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            problem.kind(),
+            crate::features::request::runtime::RequestProblemKind::Authorization(
+                AuthorizationProblemKind::Denied
+            )
+        );
+        assert!(
+            browser
+                .await
+                .unwrap()
+                .contains("Authorization did not complete")
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use std::{fmt::Write as _, time::SystemTime};
+
+use hmac::{Hmac, Mac as _};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, header};
+use sha2::{Digest as _, Sha256};
+use time::OffsetDateTime;
+use url::Url;
+
+use super::hex;
+use crate::features::request::prepared::AwsSigV4Credentials;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const X_AMZ_DATE: HeaderName = HeaderName::from_static("x-amz-date");
+const X_AMZ_CONTENT_SHA256: HeaderName = HeaderName::from_static("x-amz-content-sha256");
+const X_AMZ_SECURITY_TOKEN: HeaderName = HeaderName::from_static("x-amz-security-token");
+
+/// Hash used for a hop whose body was dropped or never existed.
+pub(super) const EMPTY_PAYLOAD_SHA256: &str =
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+/// Payload marker for bodies whose bytes are produced while streaming.
+pub(super) const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Signs one hop in place. Every generated `x-amz-*` and authorization value
+/// replaces an explicit value of the same name.
+pub(super) fn sign(
+    credentials: &AwsSigV4Credentials,
+    method: &Method,
+    url: &Url,
+    headers: &mut HeaderMap,
+    payload_sha256: &str,
+    now: SystemTime,
+) -> Result<(), http::header::InvalidHeaderValue> {
+    let (amz_date, date) = timestamps(now);
+    let s3 = credentials.service == "s3";
+
+    let mut signed = vec![
+        ("host".to_owned(), host(url)),
+        ("x-amz-date".to_owned(), amz_date.clone()),
+    ];
+    if s3 {
+        signed.push(("x-amz-content-sha256".to_owned(), payload_sha256.to_owned()));
+    }
+    if let Some(token) = &credentials.session_token {
+        signed.push(("x-amz-security-token".to_owned(), token.clone()));
+    }
+    signed.sort_by(|left, right| left.0.cmp(&right.0));
+
+    let signed_headers = signed
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let mut canonical_headers = String::new();
+    for (name, value) in &signed {
+        canonical_headers.push_str(name);
+        canonical_headers.push(':');
+        canonical_headers.push_str(&collapse_whitespace(value));
+        canonical_headers.push('\n');
+    }
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method.as_str(),
+        canonical_uri(url, !s3),
+        canonical_query(url),
+        canonical_headers,
+        signed_headers,
+        payload_sha256,
+    );
+    let scope = format!(
+        "{date}/{}/{}/aws4_request",
+        credentials.region, credentials.service
+    );
+    let string_to_sign = format!(
+        "{ALGORITHM}\n{amz_date}\n{scope}\n{}",
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let signing_key = [
+        credentials.region.as_bytes(),
+        credentials.service.as_bytes(),
+        b"aws4_request",
+    ]
+    .into_iter()
+    .fold(
+        hmac(
+            format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        ),
+        |key, part| hmac(&key, part),
+    );
+    let signature = hex(&hmac(&signing_key, string_to_sign.as_bytes()));
+
+    headers.remove(header::AUTHORIZATION);
+    headers.remove(X_AMZ_DATE);
+    headers.remove(X_AMZ_CONTENT_SHA256);
+    headers.remove(X_AMZ_SECURITY_TOKEN);
+    headers.insert(X_AMZ_DATE, HeaderValue::try_from(amz_date)?);
+    if s3 {
+        headers.insert(X_AMZ_CONTENT_SHA256, HeaderValue::try_from(payload_sha256)?);
+    }
+    if let Some(token) = &credentials.session_token {
+        headers.insert(X_AMZ_SECURITY_TOKEN, HeaderValue::try_from(token.as_str())?);
+    }
+    headers.insert(
+        header::AUTHORIZATION,
+        HeaderValue::try_from(format!(
+            "{ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id
+        ))?,
+    );
+    Ok(())
+}
+
+fn timestamps(now: SystemTime) -> (String, String) {
+    let now = OffsetDateTime::from(now);
+    let date = format!(
+        "{:04}{:02}{:02}",
+        now.year(),
+        u8::from(now.month()),
+        now.day()
+    );
+    let amz_date = format!(
+        "{date}T{:02}{:02}{:02}Z",
+        now.hour(),
+        now.minute(),
+        now.second()
+    );
+    (amz_date, date)
+}
+
+fn host(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_owned(),
+    }
+}
+
+/// Non-S3 services sign each path segment encoded twice; S3 signs it once.
+fn canonical_uri(url: &Url, double_encode: bool) -> String {
+    let path = match url.path() {
+        "" => "/",
+        path => path,
+    };
+    path.split('/')
+        .map(|segment| {
+            let once = uri_encode(&percent_decode(segment));
+            if double_encode {
+                uri_encode(once.as_bytes())
+            } else {
+                once
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn canonical_query(url: &Url) -> String {
+    let Some(query) = url.query() else {
+        return String::new();
+    };
+    let mut pairs = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (
+                uri_encode(&percent_decode(name)),
+                uri_encode(&percent_decode(value)),
+            )
+        })
+        .collect::<Vec<_>>();
+    pairs.sort();
+    pairs
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn uri_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(*byte as char);
+            }
+            _ => write!(&mut encoded, "%{byte:02X}").expect("writing to String cannot fail"),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(raw: &str) -> Vec<u8> {
+    let bytes = raw.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%'
+            && let Some(byte) = bytes
+                .get(index + 1..index + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    decoded
+}
+
+fn collapse_whitespace(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn hmac(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    fn credentials(service: &str) -> AwsSigV4Credentials {
+        AwsSigV4Credentials {
+            access_key_id: "AKIDEXAMPLE".into(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".into(),
+            session_token: None,
+            region: "us-east-1".into(),
+            service: service.into(),
+        }
+    }
+
+    /// 2015-08-30T12:36:00Z, the timestamp used by the published SigV4 suite.
+    fn suite_time() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_440_938_160)
+    }
+
+    #[test]
+    fn published_get_vanilla_vector_matches() {
+        let mut headers = HeaderMap::new();
+        sign(
+            &credentials("service"),
+            &Method::GET,
+            &Url::parse("https://example.amazonaws.com/").unwrap(),
+            &mut headers,
+            EMPTY_PAYLOAD_SHA256,
+            suite_time(),
+        )
+        .unwrap();
+        assert_eq!(headers.get("x-amz-date").unwrap(), "20150830T123600Z");
+        assert_eq!(
+            headers.get(header::AUTHORIZATION).unwrap(),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+        assert!(!headers.contains_key("x-amz-content-sha256"));
+    }
+
+    #[test]
+    fn published_query_order_vector_matches() {
+        let mut headers = HeaderMap::new();
+        sign(
+            &credentials("service"),
+            &Method::GET,
+            &Url::parse("https://example.amazonaws.com/?Param2=value2&Param1=value1").unwrap(),
+            &mut headers,
+            EMPTY_PAYLOAD_SHA256,
+            suite_time(),
+        )
+        .unwrap();
+        assert!(
+            headers
+                .get(header::AUTHORIZATION)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .ends_with(
+                    "Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+                )
+        );
+    }
+
+    #[test]
+    fn s3_signs_payload_hash_and_session_token_replaces_explicit_values() {
+        let mut credentials = credentials("s3");
+        credentials.session_token = Some("session-token".into());
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("manual"));
+        headers.insert(X_AMZ_SECURITY_TOKEN, HeaderValue::from_static("stale"));
+        sign(
+            &credentials,
+            &Method::PUT,
+            &Url::parse("http://127.0.0.1:9000/bucket/a%20b.txt").unwrap(),
+            &mut headers,
+            UNSIGNED_PAYLOAD,
+            suite_time(),
+        )
+        .unwrap();
+
+        let authorization = headers
+            .get(header::AUTHORIZATION)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(
+            authorization.contains(
+                "SignedHeaders=host;x-amz-content-sha256;x-amz-date;x-amz-security-token,"
+            )
+        );
+        assert_eq!(headers.get_all(header::AUTHORIZATION).iter().count(), 1);
+        assert_eq!(headers.get(X_AMZ_CONTENT_SHA256).unwrap(), UNSIGNED_PAYLOAD);
+        assert_eq!(headers.get(X_AMZ_SECURITY_TOKEN).unwrap(), "session-token");
+    }
+
+    #[test]
+    fn canonical_path_and_query_follow_service_encoding_rules() {
+        let url = Url::parse("https://example.test/a%20b/c~d?b=2&a=x+y&a=%2F").unwrap();
+        assert_eq!(canonical_uri(&url, false), "/a%20b/c~d");
+        assert_eq!(canonical_uri(&url, true), "/a%2520b/c~d");
+        assert_eq!(canonical_query(&url), "a=%2F&a=x%2By&b=2");
+        assert_eq!(
+            canonical_uri(&Url::parse("https://example.test").unwrap(), true),
+            "/"
+        );
+    }
+
+    #[test]
+    fn empty_payload_constant_is_the_hash_of_no_bytes() {
+        assert_eq!(hex(&Sha256::digest(b"")), EMPTY_PAYLOAD_SHA256);
+    }
+}
@@ -0,0 +1,353 @@
+//! Just enough DER to describe a peer certificate. Nothing here verifies
+//! anything; rustls has already done that by the time a response arrives.
+
+use sha2::{Digest as _, Sha256};
+
+use super::super::response::PeerCertificate;
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_VERSION: u8 = 0xa0;
+const TAG_EXTENSIONS: u8 = 0xa3;
+const TAG_DNS_NAME: u8 = 0x82;
+const TAG_IP_ADDRESS: u8 = 0x87;
+const TAG_BOOLEAN: u8 = 0x01;
+
+const OID_SUBJECT_ALT_NAME: &str = "2.5.29.17";
+
+/// Describes a DER encoded X.509 certificate. The fingerprint is always
+/// available; the other fields are empty when the structure is not one
+/// this reader understands.
+pub(crate) fn describe_certificate(der: &[u8]) -> PeerCertificate {
+    let sha256 = colon_hex(&Sha256::digest(der));
+    parse_certificate(der, sha256.clone()).unwrap_or(PeerCertificate {
+        sha256,
+        ..PeerCertificate::default()
+    })
+}
+
+fn parse_certificate(der: &[u8], sha256: String) -> Option<PeerCertificate> {
+    let mut certificate = Reader::new(Reader::new(der).expect(TAG_SEQUENCE)?);
+    let mut tbs = Reader::new(certificate.expect(TAG_SEQUENCE)?);
+    if tbs.peek() == Some(TAG_VERSION) {
+        tbs.next()?;
+    }
+    let serial = colon_hex(strip_sign_byte(tbs.expect(TAG_INTEGER)?));
+    tbs.expect(TAG_SEQUENCE)?;
+    let issuer = name(tbs.expect(TAG_SEQUENCE)?)?;
+    let mut validity = Reader::new(tbs.expect(TAG_SEQUENCE)?);
+    let not_before = time(validity.next()?)?;
+    let not_after = time(validity.next()?)?;
+    let subject = name(tbs.expect(TAG_SEQUENCE)?)?;
+    tbs.expect(TAG_SEQUENCE)?;
+
+    let mut subject_alt_names = Vec::new();
+    while let Some((tag, content)) = tbs.next() {
+        if tag == TAG_EXTENSIONS {
+            subject_alt_names = alt_names(content)?;
+        }
+    }
+    Some(PeerCertificate {
+        subject,
+        issuer,
+        serial,
+        not_before,
+        not_after,
+        subject_alt_names,
+        sha256,
+    })
+}
+
+/// Renders an RDN sequence as `CN=…, O=…`, in the order it was encoded.
+fn name(content: &[u8]) -> Option<String> {
+    let mut parts = Vec::new();
+    let mut sets = Reader::new(content);
+    while let Some(set) = sets.next_tagged(TAG_SET)? {
+        let mut attributes = Reader::new(set);
+        while let Some(attribute) = attributes.next_tagged(TAG_SEQUENCE)? {
+            let mut attribute = Reader::new(attribute);
+            let oid = oid(attribute.expect(TAG_OID)?)?;
+            let (_, value) = attribute.next()?;
+            let label = match oid.as_str() {
+                "2.5.4.3" => "CN",
+                "2.5.4.6" => "C",
+                "2.5.4.7" => "L",
+                "2.5.4.8" => "ST",
+                "2.5.4.10" => "O",
+                "2.5.4.11" => "OU",
+                _ => oid.as_str(),
+            };
+            parts.push(format!("{label}={}", String::from_utf8_lossy(value)));
+        }
+    }
+    Some(parts.join(", "))
+}
+
+fn alt_names(extensions: &[u8]) -> Option<Vec<String>> {
+    let mut list = Reader::new(Reader::new(extensions).expect(TAG_SEQUENCE)?);
+    while let Some(extension) = list.next_tagged(TAG_SEQUENCE)? {
+        let mut extension = Reader::new(extension);
+        let id = oid(extension.expect(TAG_OID)?)?;
+        if extension.peek() == Some(TAG_BOOLEAN) {
+            extension.next()?;
+        }
+        let value = extension.expect(TAG_OCTET_STRING)?;
+        if id != OID_SUBJECT_ALT_NAME {
+            continue;
+        }
+        let mut names = Vec::new();
+        let mut general_names = Reader::new(Reader::new(value).expect(TAG_SEQUENCE)?);
+        while let Some((tag, content)) = general_names.next() {
+            match tag {
+                TAG_DNS_NAME => names.push(String::from_utf8_lossy(content).into_owned()),
+                TAG_IP_ADDRESS => names.extend(ip_address(content)),
+                _ => {}
+            }
+        }
+        return Some(names);
+    }
+    Some(Vec::new())
+}
+
+fn ip_address(content: &[u8]) -> Option<String> {
+    if let Ok(octets) = <[u8; 4]>::try_from(content) {
+        return Some(std::net::Ipv4Addr::from(octets).to_string());
+    }
+    <[u8; 16]>::try_from(content)
+        .ok()
+        .map(|octets| std::net::Ipv6Addr::from(octets).to_string())
+}
+
+fn oid(content: &[u8]) -> Option<String> {
+    let (&first, rest) = content.split_first()?;
+    let mut arcs = vec![u64::from(first / 40), u64::from(first % 40)];
+    let mut arc = 0_u64;
+    for &byte in rest {
+        arc = arc.checked_mul(128)? | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            arcs.push(arc);
+            arc = 0;
+        }
+    }
+    Some(
+        arcs.iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join("."),
+    )
+}
+
+/// Formats a UTCTime or GeneralizedTime as `YYYY-MM-DD HH:MM:SS UTC`.
+fn time((tag, content): (u8, &[u8])) -> Option<String> {
+    let text = std::str::from_utf8(content).ok()?.strip_suffix('Z')?;
+    let digits = match tag {
+        // RFC 5280 4.1.2.5.1: two digit years from 50 belong to the 1900s.
+        TAG_UTC_TIME => {
+            let year = text.get(..2)?.parse::<u32>().ok()?;
+            let century = if year >= 50 { "19" } else { "20" };
+            format!("{century}{text}")
+        }
+        TAG_GENERALIZED_TIME => text.to_string(),
+        _ => return None,
+    };
+    if digits.len() != 14 || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{} {}:{}:{} UTC",
+        &digits[..4],
+        &digits[4..6],
+        &digits[6..8],
+        &digits[8..10],
+        &digits[10..12],
+        &digits[12..14]
+    ))
+}
+
+fn strip_sign_byte(integer: &[u8]) -> &[u8] {
+    match integer {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        _ => integer,
+    }
+}
+
+fn colon_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.first().copied()
+    }
+
+    /// Reads one TLV. Only single-byte tags and definite lengths occur in
+    /// DER certificates.
+    fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let (&tag, rest) = self.bytes.split_first()?;
+        let (&first, rest) = rest.split_first()?;
+        let (length, rest) = if first < 0x80 {
+            (usize::from(first), rest)
+        } else {
+            let count = usize::from(first & 0x7f);
+            if count == 0 || count > 4 || rest.len() < count {
+                return None;
+            }
+            let (length, rest) = rest.split_at(count);
+            let length = length
+                .iter()
+                .fold(0_usize, |length, byte| (length << 8) | usize::from(*byte));
+            (length, rest)
+        };
+        if rest.len() < length {
+            return None;
+        }
+        let (content, rest) = rest.split_at(length);
+        self.bytes = rest;
+        Some((tag, content))
+    }
+
+    fn expect(&mut self, tag: u8) -> Option<&'a [u8]> {
+        self.next()
+            .filter(|(actual, _)| *actual == tag)
+            .map(|(_, content)| content)
+    }
+
+    /// The next element when it has `tag`, `Some(None)` at the end, and
+    /// `None` for anything else.
+    fn next_tagged(&mut self, tag: u8) -> Option<Option<&'a [u8]>> {
+        if self.bytes.is_empty() {
+            return Some(None);
+        }
+        self.expect(tag).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![tag];
+        if content.len() < 0x80 {
+            encoded.push(content.len() as u8);
+        } else {
+            encoded.push(0x82);
+            encoded.extend((content.len() as u16).to_be_bytes());
+        }
+        encoded.extend_from_slice(content);
+        encoded
+    }
+
+    fn seq(parts: &[Vec<u8>]) -> Vec<u8> {
+        tlv(TAG_SEQUENCE, &parts.concat())
+    }
+
+    fn rdn(oid: &[u8], value: &str) -> Vec<u8> {
+        tlv(
+            TAG_SET,
+            &seq(&[tlv(TAG_OID, oid), tlv(0x0c, value.as_bytes())]),
+        )
+    }
+
+    fn certificate(validity: Vec<u8>) -> Vec<u8> {
+        let names = seq(&[
+            tlv(TAG_DNS_NAME, b"api.example.test"),
+            tlv(TAG_IP_ADDRESS, &[127, 0, 0, 1]),
+        ]);
+        let extensions = tlv(
+            TAG_EXTENSIONS,
+            &seq(&[
+                seq(&[
+                    tlv(TAG_OID, &[0x55, 0x1d, 0x13]),
+                    tlv(TAG_BOOLEAN, &[0xff]),
+                    tlv(TAG_OCTET_STRING, &seq(&[])),
+                ]),
+                seq(&[
+                    tlv(TAG_OID, &[0x55, 0x1d, 0x11]),
+                    tlv(TAG_OCTET_STRING, &names),
+                ]),
+            ]),
+        );
+        let tbs = seq(&[
+            tlv(TAG_VERSION, &tlv(TAG_INTEGER, &[2])),
+            tlv(TAG_INTEGER, &[0x00, 0x9f, 0x01]),
+            seq(&[tlv(
+                TAG_OID,
+                &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02],
+            )]),
+            seq(&[
+                rdn(&[0x55, 0x04, 0x06], "CN"),
+                rdn(&[0x55, 0x04, 0x0a], "Test CA"),
+            ]),
+            validity,
+            seq(&[rdn(&[0x55, 0x04, 0x03], "api.example.test")]),
+            seq(&[]),
+            extensions,
+        ]);
+        seq(&[tbs, seq(&[]), tlv(0x03, &[0])])
+    }
+
+    #[test]
+    fn certificate_fields_and_alt_names_are_described() {
+        let der = certificate(seq(&[
+            tlv(TAG_UTC_TIME, b"250102030405Z"),
+            tlv(TAG_GENERALIZED_TIME, b"20991231235959Z"),
+        ]));
+
+        let described = describe_certificate(&der);
+
+        assert_eq!(described.subject, "CN=api.example.test");
+        assert_eq!(described.issuer, "C=CN, O=Test CA");
+        assert_eq!(described.serial, "9F:01");
+        assert_eq!(described.not_before, "2025-01-02 03:04:05 UTC");
+        assert_eq!(described.not_after, "2099-12-31 23:59:59 UTC");
+        assert_eq!(
+            described.subject_alt_names,
+            ["api.example.test", "127.0.0.1"]
+        );
+        assert_eq!(described.sha256.len(), 32 * 3 - 1);
+    }
+
+    #[test]
+    fn unreadable_certificates_keep_their_fingerprint() {
+        let der = certificate(seq(&[tlv(TAG_UTC_TIME, b"not a time")]));
+
+        let described = describe_certificate(&der);
+
+        assert_eq!(described.subject, "");
+        assert_eq!(described.sha256, colon_hex(&Sha256::digest(&der)));
+    }
+
+    #[test]
+    fn two_digit_years_follow_the_x509_pivot() {
+        assert_eq!(
+            time((TAG_UTC_TIME, b"491231000000Z")).as_deref(),
+            Some("2049-12-31 00:00:00 UTC")
+        );
+        assert_eq!(
+            time((TAG_UTC_TIME, b"500101000000Z")).as_deref(),
+            Some("1950-01-01 00:00:00 UTC")
+        );
+        assert_eq!(
+            oid(&[0x2a, 0x86, 0x48, 0xce, 0x3d]).as_deref(),
+            Some("1.2.840.10045")
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use reqwest::{Client, ClientBuilder, NoProxy, Proxy, redirect::Policy};
+use rustls::ClientConfig;
+
+use super::{
+    timing::{ConnectTiming, TimedResolver},
+    tls::{self, PeerChains, TlsSettings},
+};
+use crate::features::request::{
+    draft::HttpVersionPreference,
+    prepared::{ClientIdentity, PreparedConnection},
+    runtime::{ConnectionProblemKind, RequestProblem},
+};
+
+/// Builds the client shared by every request with default connection
+/// settings.
+pub(super) fn shared_client(
+    builder: ClientBuilder,
+    chains: &PeerChains,
+) -> Result<Client, RequestProblem> {
+    let tls = TlsSettings::default().client_config(chains)?;
+    configure(builder, tls)
+        .build()
+        .map_err(|error| RequestProblem::transport(error.without_url()))
+}
+
+/// Settings every client shares. Redirects, cookies and decoding are all
+/// handled by the worker so that each hop can be observed, and lookups and
+/// connects report to the hop's timing.
+fn configure(builder: ClientBuilder, tls: ClientConfig) -> ClientBuilder {
+    builder
+        .tls_backend_preconfigured(tls)
+        .tls_info(true)
+        .dns_resolver(TimedResolver)
+        .connector_layer(ConnectTiming)
+        .redirect(Policy::none())
+        .referer(false)
+        .no_gzip()
+        .no_brotli()
+        .no_deflate()
+        .no_zstd()
+}
+
+/// Builds a client for one request whose connection settings differ from
+/// the defaults. Certificate files are read again on every send so an
+/// edited bundle takes effect without restarting.
+pub(super) async fn dedicated_client(
+    connection: &PreparedConnection,
+    chains: &PeerChains,
+) -> Result<Client, RequestProblem> {
+    let mut settings = TlsSettings {
+        http_version: connection.http_version,
+        accept_invalid_certificates: connection.accept_invalid_certificates,
+        ..TlsSettings::default()
+    };
+
+    if let Some(path) = &connection.ca_certificate {
+        let bundle = read(path, ConnectionProblemKind::CaCertificate).await?;
+        settings.extra_roots = tls::pem_certificates(&bundle).map_err(|error| {
+            RequestProblem::connection(ConnectionProblemKind::CaCertificate, error)
+        })?;
+    }
+
+    if let Some(identity) = &connection.client_identity {
+        let problem =
+            |error| RequestProblem::connection(ConnectionProblemKind::ClientIdentity, error);
+        let pem = match identity {
+            ClientIdentity::Pem { certificate, key } => {
+                let mut pem = read(certificate, ConnectionProblemKind::ClientIdentity).await?;
+                if let Some(key) = key {
+                    pem.push(b'\n');
+                    pem.extend(read(key, ConnectionProblemKind::ClientIdentity).await?);
+                }
+                pem
+            }
+            ClientIdentity::Pkcs12 { archive, password } => {
+                let der = read(archive, ConnectionProblemKind::ClientIdentity).await?;
+                tls::pkcs12_to_pem(&der, password).map_err(problem)?
+            }
+        };
+        settings.identity = Some(tls::pem_identity(&pem).map_err(problem)?);
+    }
+
+    let mut builder = configure(Client::builder(), settings.client_config(chains)?);
+    builder = match connection.http_version {
+        HttpVersionPreference::Automatic => builder,
+        HttpVersionPreference::Http1 => builder.http1_only(),
+        HttpVersionPreference::Http2 => builder.http2_prior_knowledge(),
+    };
+
+    if let Some(proxy) = &connection.proxy {
+        let problem = |error| RequestProblem::connection(ConnectionProblemKind::Proxy, error);
+        let mut configured = Proxy::all(proxy.url.as_str())
+            .map_err(problem)?
+            .no_proxy(NoProxy::from_string(&proxy.bypass));
+        if let Some((username, password)) = &proxy.credentials {
+            configured = configured.basic_auth(username, password);
+        }
+        builder = builder.proxy(configured);
+    }
+
+    builder
+        .build()
+        .map_err(|error| RequestProblem::transport(error.without_url()))
+}
+
+async fn read(path: &Path, kind: ConnectionProblemKind) -> Result<Vec<u8>, RequestProblem> {
+    tokio::fs::read(path)
+        .await
+        .map_err(|error| RequestProblem::connection(kind, error))
+}
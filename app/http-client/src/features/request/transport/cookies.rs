@@ -0,0 +1,766 @@
+//! The cookie jar shared by every request, following the storage and
+//! retrieval rules of RFC 6265.
+
+use std::{
+    net::IpAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use http::{HeaderMap, HeaderValue, header};
+use serde_json::{Map, Value, json};
+use url::Url;
+
+/// Identifies a stored cookie. Storing a cookie with the same name, domain
+/// and path replaces the previous one.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct CookieKey {
+    pub(crate) domain: String,
+    pub(crate) path: String,
+    pub(crate) name: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Cookie {
+    pub(crate) name: String,
+    pub(crate) value: String,
+    /// Lower case and without a leading dot.
+    pub(crate) domain: String,
+    /// Sent to `domain` only instead of to its subdomains as well, because
+    /// the server did not name a `Domain`.
+    pub(crate) host_only: bool,
+    pub(crate) path: String,
+    /// Unix seconds. Session cookies have none and are not persisted.
+    pub(crate) expires: Option<i64>,
+    pub(crate) secure: bool,
+    pub(crate) http_only: bool,
+    /// Disabled cookies stay in the jar but are not sent.
+    pub(crate) enabled: bool,
+}
+
+impl Cookie {
+    pub(crate) fn key(&self) -> CookieKey {
+        CookieKey {
+            domain: self.domain.clone(),
+            path: self.path.clone(),
+            name: self.name.clone(),
+        }
+    }
+
+    fn is_expired(&self, now: i64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn matches(&self, url: &Url, host: &str) -> bool {
+        let domain_matches = if self.host_only {
+            host == self.domain
+        } else {
+            domain_match(host, &self.domain)
+        };
+        domain_matches
+            && path_match(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+    }
+}
+
+#[derive(Default)]
+struct CookieStore {
+    /// Kept in creation order, which breaks ties when cookies are sent.
+    cookies: Vec<Cookie>,
+}
+
+impl CookieStore {
+    /// Applies one `Set-Cookie` value received from `url`. Returns whether
+    /// the jar changed.
+    fn receive(&mut self, url: &Url, set_cookie: &str, now: i64) -> bool {
+        let Some(host) = request_host(url) else {
+            return false;
+        };
+        let Some(parsed) = parse_set_cookie(set_cookie, now) else {
+            return false;
+        };
+        let (domain, host_only) = match parsed.domain {
+            // RFC 6265 section 5.3 step 5: a public suffix such as `com` or
+            // `co.uk` may only name the host that set the cookie.
+            Some(domain) if is_public_suffix(&domain) => {
+                if domain != host {
+                    return false;
+                }
+                (host, true)
+            }
+            Some(domain) if domain_match(&host, &domain) => (domain, false),
+            Some(_) => return false,
+            None => (host, true),
+        };
+        if parsed.secure && url.scheme() != "https" {
+            return false;
+        }
+        let cookie = Cookie {
+            name: parsed.name,
+            value: parsed.value,
+            domain,
+            host_only,
+            path: parsed.path.unwrap_or_else(|| default_path(url.path())),
+            expires: parsed.expires,
+            secure: parsed.secure,
+            http_only: parsed.http_only,
+            enabled: true,
+        };
+        let existing = self.position(&cookie.key());
+        if cookie.is_expired(now) {
+            return existing.map(|index| self.cookies.remove(index)).is_some();
+        }
+        match existing {
+            // A replaced cookie keeps its creation order and, so that a
+            // login flow cannot silently undo it, its enabled switch.
+            Some(index) => {
+                let enabled = self.cookies[index].enabled;
+                self.cookies[index] = Cookie { enabled, ..cookie };
+            }
+            None => self.cookies.push(cookie),
+        }
+        true
+    }
+
+    /// The `Cookie` header for a request to `url`: longer paths first, then
+    /// older cookies first.
+    fn header(&self, url: &Url, now: i64) -> Option<String> {
+        let host = request_host(url)?;
+        let mut matching = self
+            .cookies
+            .iter()
+            .filter(|cookie| {
+                cookie.enabled && !cookie.is_expired(now) && cookie.matches(url, &host)
+            })
+            .collect::<Vec<_>>();
+        matching.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+        let pairs = matching
+            .into_iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>();
+        (!pairs.is_empty()).then(|| pairs.join("; "))
+    }
+
+    fn position(&self, key: &CookieKey) -> Option<usize> {
+        self.cookies.iter().position(|cookie| {
+            cookie.name == key.name && cookie.domain == key.domain && cookie.path == key.path
+        })
+    }
+
+    fn remove_expired(&mut self, now: i64) -> bool {
+        let before = self.cookies.len();
+        self.cookies.retain(|cookie| !cookie.is_expired(now));
+        self.cookies.len() != before
+    }
+}
+
+/// A thread-safe handle to the jar. Clones share the same cookies.
+#[derive(Clone, Default)]
+pub(crate) struct CookieJar {
+    store: Arc<Mutex<CookieStore>>,
+    file: Option<Arc<PathBuf>>,
+}
+
+impl CookieJar {
+    /// A jar that reads persistent cookies from `file` and writes them back
+    /// after every change. A missing or unreadable file starts empty.
+    pub(crate) fn persistent(file: PathBuf) -> Self {
+        let cookies = std::fs::read(&file)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+            .map(|value| from_json(&value))
+            .unwrap_or_default();
+        let jar = Self {
+            store: Arc::new(Mutex::new(CookieStore { cookies })),
+            file: Some(Arc::new(file)),
+        };
+        if jar.lock().remove_expired(unix_now()) {
+            jar.save();
+        }
+        jar
+    }
+
+    /// Stores every `Set-Cookie` header of a response received from `url`.
+    pub(crate) fn receive(&self, url: &Url, headers: &HeaderMap) {
+        let now = unix_now();
+        let mut changed = false;
+        {
+            let mut store = self.lock();
+            for value in headers.get_all(header::SET_COOKIE) {
+                if let Ok(value) = value.to_str() {
+                    changed |= store.receive(url, value, now);
+                }
+            }
+        }
+        if changed {
+            self.save();
+        }
+    }
+
+    /// The `Cookie` header value the jar adds to a request to `url`.
+    pub(crate) fn header(&self, url: &Url) -> Option<HeaderValue> {
+        let value = self.lock().header(url, unix_now())?;
+        HeaderValue::from_str(&value).ok()
+    }
+
+    /// Every unexpired cookie, ordered by domain, path and name.
+    pub(crate) fn cookies(&self) -> Vec<Cookie> {
+        let now = unix_now();
+        let mut cookies = self
+            .lock()
+            .cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now))
+            .cloned()
+            .collect::<Vec<_>>();
+        cookies.sort_by_key(Cookie::key);
+        cookies
+    }
+
+    /// Adds a cookie, or replaces the one stored under `previous`.
+    pub(crate) fn upsert(&self, previous: Option<&CookieKey>, cookie: Cookie) {
+        {
+            let mut store = self.lock();
+            let replaced = previous.and_then(|key| store.position(key));
+            let duplicate = store
+                .position(&cookie.key())
+                .filter(|index| Some(*index) != replaced);
+            match (replaced, duplicate) {
+                (Some(index), duplicate) => {
+                    store.cookies[index] = cookie;
+                    if let Some(duplicate) = duplicate {
+                        store.cookies.remove(duplicate);
+                    }
+                }
+                (None, Some(index)) => store.cookies[index] = cookie,
+                (None, None) => store.cookies.push(cookie),
+            }
+        }
+        self.save();
+    }
+
+    pub(crate) fn set_enabled(&self, key: &CookieKey, enabled: bool) {
+        {
+            let mut store = self.lock();
+            let Some(index) = store.position(key) else {
+                return;
+            };
+            store.cookies[index].enabled = enabled;
+        }
+        self.save();
+    }
+
+    pub(crate) fn remove(&self, key: &CookieKey) {
+        {
+            let mut store = self.lock();
+            let Some(index) = store.position(key) else {
+                return;
+            };
+            store.cookies.remove(index);
+        }
+        self.save();
+    }
+
+    pub(crate) fn remove_domain(&self, domain: &str) {
+        self.lock().cookies.retain(|cookie| cookie.domain != domain);
+        self.save();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CookieStore> {
+        self.store
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn save(&self) {
+        let Some(file) = &self.file else {
+            return;
+        };
+        let json = to_json(&self.lock().cookies);
+        let written = file
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(file.as_ref(), json.to_string()));
+        if let Err(error) = written {
+            tracing::warn!(operation = "cookie-jar-save", %error, "cookie jar was not saved");
+        }
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// The canonical host a cookie can be bound to, or `None` for URLs that
+/// cannot carry cookies.
+fn request_host(url: &Url) -> Option<String> {
+    matches!(url.scheme(), "http" | "https")
+        .then(|| url.host_str())
+        .flatten()
+        .map(str::to_ascii_lowercase)
+}
+
+/// RFC 6265 section 5.1.3. IP addresses only match themselves.
+fn domain_match(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.len() > domain.len()
+            && host.ends_with(domain)
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+            && host.trim_matches(['[', ']']).parse::<IpAddr>().is_err())
+}
+
+/// Whether `domain` is a registry suffix rather than a site. Unlisted
+/// top-level names like `test` or `localhost` count as suffixes too.
+fn is_public_suffix(domain: &str) -> bool {
+    psl::suffix_str(domain) == Some(domain)
+}
+
+/// RFC 6265 section 5.1.4.
+fn path_match(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/')
+                || request_path.as_bytes().get(cookie_path.len()) == Some(&b'/')))
+}
+
+/// The directory of the request path, used when a cookie names no `Path`.
+fn default_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => "/".to_owned(),
+        Some(index) => request_path[..index].to_owned(),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct SetCookie {
+    name: String,
+    value: String,
+    domain: Option<String>,
+    path: Option<String>,
+    expires: Option<i64>,
+    secure: bool,
+    http_only: bool,
+}
+
+/// RFC 6265 section 5.2. Unknown and malformed attributes are ignored;
+/// `Max-Age` wins over `Expires` wherever it appears.
+fn parse_set_cookie(raw: &str, now: i64) -> Option<SetCookie> {
+    let mut parts = raw.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let mut cookie = SetCookie {
+        name: name.to_owned(),
+        value: value.trim().to_owned(),
+        domain: None,
+        path: None,
+        expires: None,
+        secure: false,
+        http_only: false,
+    };
+    let mut max_age = None;
+    for attribute in parts {
+        let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "expires" => {
+                if let Some(expires) = parse_cookie_date(value) {
+                    cookie.expires = Some(expires);
+                }
+            }
+            "max-age" => {
+                let digits = value.strip_prefix('-').unwrap_or(value);
+                if !digits.is_empty() && digits.bytes().all(|byte| byte.is_ascii_digit()) {
+                    let seconds = value.parse::<i64>().unwrap_or(i64::MAX);
+                    max_age = Some(if seconds <= 0 {
+                        i64::MIN
+                    } else {
+                        now.saturating_add(seconds)
+                    });
+                }
+            }
+            "domain" if !value.is_empty() => {
+                cookie.domain = Some(value.trim_start_matches('.').to_ascii_lowercase());
+            }
+            "path" => {
+                cookie.path = value.starts_with('/').then(|| value.to_owned());
+            }
+            "secure" => cookie.secure = true,
+            "httponly" => cookie.http_only = true,
+            _ => {}
+        }
+    }
+    if max_age.is_some() {
+        cookie.expires = max_age;
+    }
+    Some(cookie)
+}
+
+/// RFC 6265 section 5.1.1, which accepts the many date formats servers send
+/// in `Expires`.
+fn parse_cookie_date(raw: &str) -> Option<i64> {
+    let is_delimiter =
+        |character: char| matches!(character, '\t' | ' '..='/' | ';'..='@' | '['..='`' | '{'..='~');
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+    for token in raw.split(is_delimiter).filter(|token| !token.is_empty()) {
+        if time.is_none()
+            && let Some(parsed) = parse_time(token)
+        {
+            time = Some(parsed);
+        } else if day.is_none()
+            && let Some(parsed) = leading_number(token, 1, 2)
+        {
+            day = Some(parsed);
+        } else if month.is_none()
+            && let Some(parsed) = parse_month(token)
+        {
+            month = Some(parsed);
+        } else if year.is_none()
+            && let Some(parsed) = leading_number(token, 2, 4)
+        {
+            year = Some(parsed);
+        }
+    }
+    let (hour, minute, second) = time?;
+    let (day, month, mut year) = (day?, month?, year?);
+    if (70..=99).contains(&year) {
+        year += 1900;
+    } else if year <= 69 {
+        year += 2000;
+    }
+    let days_in_month = match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if year < 1601 || day == 0 || day > days_in_month || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    let days = days_from_civil(year as i64, month, day as i64);
+    Some(days * 86_400 + i64::from(hour) * 3_600 + i64::from(minute) * 60 + i64::from(second))
+}
+
+/// `hh:mm:ss` with one or two digits each, optionally followed by text.
+fn parse_time(token: &str) -> Option<(u32, u32, u32)> {
+    let mut fields = token.splitn(3, ':');
+    let hour = leading_number(fields.next()?, 1, 2)?;
+    let minute = exact_number(fields.next()?)?;
+    let second = leading_number(fields.next()?, 1, 2)?;
+    Some((hour, minute, second))
+}
+
+fn exact_number(field: &str) -> Option<u32> {
+    (matches!(field.len(), 1 | 2) && field.bytes().all(|byte| byte.is_ascii_digit()))
+        .then(|| field.parse().ok())
+        .flatten()
+}
+
+/// Between `min` and `max` digits, followed by nothing or a non-digit.
+fn leading_number(token: &str, min: usize, max: usize) -> Option<u32> {
+    let digits = token.bytes().take_while(u8::is_ascii_digit).count();
+    (min..=max)
+        .contains(&digits)
+        .then(|| token[..digits].parse().ok())
+        .flatten()
+}
+
+fn parse_month(token: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let prefix = token.get(..3)?.to_ascii_lowercase();
+    MONTHS
+        .iter()
+        .position(|month| *month == prefix)
+        .map(|index| index as u32 + 1)
+}
+
+/// Days since 1970-01-01 in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Only persistent cookies are written; session cookies end with the app.
+fn to_json(cookies: &[Cookie]) -> Value {
+    Value::Array(
+        cookies
+            .iter()
+            .filter_map(|cookie| {
+                Some(json!({
+                    "name": cookie.name,
+                    "value": cookie.value,
+                    "domain": cookie.domain,
+                    "hostOnly": cookie.host_only,
+                    "path": cookie.path,
+                    "expires": cookie.expires?,
+                    "secure": cookie.secure,
+                    "httpOnly": cookie.http_only,
+                    "enabled": cookie.enabled,
+                }))
+            })
+            .collect(),
+    )
+}
+
+fn from_json(value: &Value) -> Vec<Cookie> {
+    let string = |object: &Map<String, Value>, key: &str| {
+        object.get(key).and_then(Value::as_str).map(str::to_owned)
+    };
+    let flag = |object: &Map<String, Value>, key: &str| {
+        object.get(key).and_then(Value::as_bool).unwrap_or(false)
+    };
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object)
+        .filter_map(|object| {
+            Some(Cookie {
+                name: string(object, "name").filter(|name| !name.is_empty())?,
+                value: string(object, "value")?,
+                domain: string(object, "domain")?,
+                host_only: flag(object, "hostOnly"),
+                path: string(object, "path").filter(|path| path.starts_with('/'))?,
+                expires: Some(object.get("expires")?.as_i64()?),
+                secure: flag(object, "secure"),
+                http_only: flag(object, "httpOnly"),
+                enabled: object
+                    .get("enabled")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn url(raw: &str) -> Url {
+        Url::parse(raw).unwrap()
+    }
+
+    fn store(entries: &[(&str, &str)]) -> CookieStore {
+        let mut store = CookieStore::default();
+        for (from, set_cookie) in entries {
+            store.receive(&url(from), set_cookie, NOW);
+        }
+        store
+    }
+
+    #[test]
+    fn cookie_dates_accept_the_formats_servers_send() {
+        let expected = Some(784_111_777);
+        assert_eq!(parse_cookie_date("Sun, 06 Nov 1994 08:49:37 GMT"), expected);
+        assert_eq!(
+            parse_cookie_date("Sunday, 06-Nov-94 08:49:37 GMT"),
+            expected
+        );
+        assert_eq!(parse_cookie_date("Sun Nov  6 08:49:37 1994"), expected);
+        assert_eq!(parse_cookie_date("6 november 1994 8:49:37"), expected);
+        assert_eq!(parse_cookie_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(parse_cookie_date("Wed, 29 Feb 2023 00:00:00 GMT"), None);
+        assert_eq!(parse_cookie_date("Sun, 06 Nov 1994"), None);
+        assert_eq!(parse_cookie_date("Sun, 06 Nov 1994 24:00:00 GMT"), None);
+    }
+
+    #[test]
+    fn set_cookie_attributes_follow_the_parsing_algorithm() {
+        assert_eq!(
+            parse_set_cookie(
+                " sid = a b ; Max-Age=60; Expires=Thu, 01 Jan 1970 00:00:00 GMT; \
+                 Domain=.Example.TEST; Path=/app; Secure; HttpOnly; SameSite=Lax",
+                NOW,
+            ),
+            Some(SetCookie {
+                name: "sid".into(),
+                value: "a b".into(),
+                domain: Some("example.test".into()),
+                path: Some("/app".into()),
+                expires: Some(NOW + 60),
+                secure: true,
+                http_only: true,
+            })
+        );
+        let relative = parse_set_cookie("a=1; Path=relative; Max-Age=soon", NOW).unwrap();
+        assert_eq!((relative.path, relative.expires), (None, None));
+        assert_eq!(
+            parse_set_cookie("a=1; Max-Age=0", NOW).unwrap().expires,
+            Some(i64::MIN)
+        );
+        assert_eq!(parse_set_cookie("no-value", NOW), None);
+        assert_eq!(parse_set_cookie("=orphan", NOW), None);
+    }
+
+    #[test]
+    fn domain_and_path_matching_decide_what_is_sent() {
+        let store = store(&[
+            ("https://example.test/login", "host=1"),
+            (
+                "https://example.test/login",
+                "shared=2; Domain=example.test",
+            ),
+            ("https://example.test/login", "docs=3; Path=/docs"),
+            ("https://example.test/", "secure=4; Secure"),
+            ("https://example.test/", "elsewhere=5; Domain=other.test"),
+        ]);
+        assert_eq!(
+            store
+                .header(&url("https://example.test/docs/page"), NOW)
+                .as_deref(),
+            Some("docs=3; host=1; shared=2; secure=4")
+        );
+        assert_eq!(
+            store
+                .header(&url("http://api.example.test/"), NOW)
+                .as_deref(),
+            Some("shared=2")
+        );
+        assert_eq!(
+            store
+                .header(&url("https://example.test/docsify"), NOW)
+                .as_deref(),
+            Some("host=1; shared=2; secure=4")
+        );
+        assert_eq!(store.header(&url("https://other.test/"), NOW), None);
+
+        assert_eq!(default_path("/login/form"), "/login");
+        assert_eq!(default_path("/login"), "/");
+        assert!(!domain_match("1.2.3.4", "2.3.4"));
+        assert!(!domain_match("notexample.test", "example.test"));
+    }
+
+    #[test]
+    fn public_suffix_domains_are_refused_unless_they_name_the_host() {
+        let store = store(&[
+            ("https://shop.example.co.uk/", "registry=1; Domain=co.uk"),
+            ("https://api.example.test/", "tld=2; Domain=.test"),
+            (
+                "https://shop.example.co.uk/",
+                "site=3; Domain=example.co.uk",
+            ),
+            ("http://localhost/", "local=4; Domain=localhost"),
+        ]);
+        assert_eq!(
+            store
+                .header(&url("https://other.example.co.uk/"), NOW)
+                .as_deref(),
+            Some("site=3")
+        );
+        assert_eq!(store.header(&url("https://other.co.uk/"), NOW), None);
+        assert_eq!(store.header(&url("https://api.other.test/"), NOW), None);
+        assert_eq!(
+            store.header(&url("http://localhost/"), NOW).as_deref(),
+            Some("local=4")
+        );
+        assert!(store.cookies.iter().all(|cookie| cookie.name != "registry"));
+        assert!(
+            store
+                .cookies
+                .iter()
+                .find(|cookie| cookie.name == "local")
+                .unwrap()
+                .host_only
+        );
+    }
+
+    #[test]
+    fn replacing_and_expiring_a_cookie_keep_the_jar_consistent() {
+        let mut store = store(&[
+            ("http://example.test/", "a=1"),
+            ("http://example.test/", "b=2"),
+            ("http://example.test/", "secure=3; Secure"),
+        ]);
+        store.cookies[0].enabled = false;
+        store.receive(&url("http://example.test/"), "a=changed", NOW);
+        assert_eq!(store.cookies.len(), 2);
+        assert_eq!(store.cookies[0].value, "changed");
+        assert!(!store.cookies[0].enabled);
+        assert_eq!(
+            store.header(&url("http://example.test/"), NOW).as_deref(),
+            Some("b=2")
+        );
+
+        assert!(store.receive(&url("http://example.test/"), "b=; Max-Age=0", NOW));
+        assert_eq!(store.header(&url("http://example.test/"), NOW), None);
+        store.receive(&url("http://example.test/"), "c=3; Max-Age=10", NOW);
+        store.cookies[0].enabled = true;
+        assert_eq!(
+            store
+                .header(&url("http://example.test/"), NOW + 5)
+                .as_deref(),
+            Some("a=changed; c=3")
+        );
+        assert_eq!(
+            store
+                .header(&url("http://example.test/"), NOW + 10)
+                .as_deref(),
+            Some("a=changed")
+        );
+        assert!(store.remove_expired(NOW + 10));
+        assert_eq!(store.cookies.len(), 1);
+    }
+
+    #[test]
+    fn persistent_jars_round_trip_and_edits_replace_by_key() {
+        let directory = tempfile::tempdir().unwrap();
+        let file = directory.path().join("jar").join("cookies.json");
+        let jar = CookieJar::persistent(file.clone());
+        let mut headers = HeaderMap::new();
+        headers.append(header::SET_COOKIE, "session=1".parse().unwrap());
+        headers.append(
+            header::SET_COOKIE,
+            "remember=yes; Max-Age=3600; Path=/".parse().unwrap(),
+        );
+        jar.receive(&url("https://example.test/"), &headers);
+        assert_eq!(jar.cookies().len(), 2);
+
+        let reloaded = CookieJar::persistent(file.clone());
+        let cookies = reloaded.cookies();
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].name, "remember");
+
+        let key = cookies[0].key();
+        reloaded.upsert(
+            Some(&key),
+            Cookie {
+                path: "/account".into(),
+                value: "no".into(),
+                ..cookies[0].clone()
+            },
+        );
+        reloaded.set_enabled(
+            &CookieKey {
+                path: "/account".into(),
+                ..key.clone()
+            },
+            false,
+        );
+        let cookies = CookieJar::persistent(file).cookies();
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(
+            (cookies[0].path.as_str(), cookies[0].enabled),
+            ("/account", false)
+        );
+        reloaded.remove_domain("example.test");
+        assert!(reloaded.cookies().is_empty());
+    }
+}
@@ -0,0 +1,423 @@
+use std::{
+    error::Error,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use http::{Method, StatusCode};
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use rustls::{
+    NamedGroup,
+    client::{ClientSessionStore, Tls12ClientSessionValue, Tls13ClientSessionValue},
+    pki_types::ServerName,
+};
+use tower_layer::Layer;
+use tower_service::Service;
+use url::Url;
+
+use crate::features::request::response::{HopTiming, TimingPhase, TimingSpan};
+
+tokio::task_local! {
+    /// The hop whose `send` is being polled. The resolver and the connector
+    /// are shared by every request on a client, so they find the hop to
+    /// charge through the task instead of through their own state.
+    static CURRENT_HOP: HopClock;
+}
+
+#[derive(Clone, Copy, Default)]
+struct HopMarks {
+    dns: Option<(Instant, Instant)>,
+    connect: Option<(Instant, Instant)>,
+    /// When the ClientHello was written, which is when the TCP stream
+    /// was established.
+    tls_start: Option<Instant>,
+    body_sent: Option<Instant>,
+}
+
+/// Collects the phase boundaries of one request as the HTTP stack reaches
+/// them.
+#[derive(Clone)]
+pub(super) struct HopClock {
+    sent_at: Instant,
+    marks: Arc<Mutex<HopMarks>>,
+}
+
+impl HopClock {
+    pub(super) fn start() -> Self {
+        Self {
+            sent_at: Instant::now(),
+            marks: Arc::default(),
+        }
+    }
+
+    /// Runs `send` with this clock visible to the resolver and connector.
+    pub(super) async fn scope<F: Future>(&self, send: F) -> F::Output {
+        CURRENT_HOP.scope(self.clone(), send).await
+    }
+
+    fn current() -> Option<Self> {
+        CURRENT_HOP.try_with(Self::clone).ok()
+    }
+
+    fn update(&self, update: impl FnOnce(&mut HopMarks)) {
+        if let Ok(mut marks) = self.marks.lock() {
+            update(&mut marks);
+        }
+    }
+
+    /// Wraps a request body so the clock learns when its last byte was
+    /// handed to the connection.
+    pub(super) fn watch_body(&self, body: reqwest::Body) -> reqwest::Body {
+        if body.is_end_stream() {
+            return body;
+        }
+        reqwest::Body::wrap(WatchedBody {
+            inner: body,
+            clock: self.clone(),
+        })
+    }
+
+    /// Turns the marks into spans once the response head has arrived.
+    pub(super) fn finish(
+        &self,
+        request_started: Instant,
+        method: Method,
+        url: Url,
+        status: StatusCode,
+    ) -> HopTiming {
+        let head_at = Instant::now();
+        let marks = self.marks.lock().map(|marks| *marks).unwrap_or_default();
+        hop_timing(
+            request_started,
+            self.sent_at,
+            head_at,
+            &marks,
+            HopIdentity {
+                method,
+                url,
+                status,
+            },
+        )
+    }
+}
+
+struct HopIdentity {
+    method: Method,
+    url: Url,
+    status: StatusCode,
+}
+
+fn hop_timing(
+    origin: Instant,
+    sent_at: Instant,
+    head_at: Instant,
+    marks: &HopMarks,
+    identity: HopIdentity,
+) -> HopTiming {
+    let offset = |at: Instant| at.saturating_duration_since(origin);
+    let mut spans = Vec::new();
+    let mut push = |phase, start: Duration, end: Duration| {
+        spans.push(TimingSpan {
+            phase,
+            start,
+            end: end.max(start),
+        });
+    };
+
+    // A lookup without a finished connection belongs to a connect that
+    // lost the race against a pooled connection and kept going in the
+    // background.
+    let connect = marks.connect;
+    let mut ready = sent_at;
+    if let Some((connect_start, connect_end)) = connect {
+        let mut tcp_start = connect_start;
+        if let Some((dns_start, dns_end)) = marks.dns {
+            push(TimingPhase::DnsLookup, offset(dns_start), offset(dns_end));
+            tcp_start = tcp_start.max(dns_end);
+        }
+        match marks
+            .tls_start
+            .filter(|tls_start| (tcp_start..=connect_end).contains(tls_start))
+        {
+            Some(tls_start) => {
+                push(TimingPhase::Connect, offset(tcp_start), offset(tls_start));
+                push(
+                    TimingPhase::TlsHandshake,
+                    offset(tls_start),
+                    offset(connect_end),
+                );
+            }
+            None => push(TimingPhase::Connect, offset(tcp_start), offset(connect_end)),
+        }
+        ready = connect_end;
+    }
+    let mut waiting_start = ready;
+    if let Some(body_sent) = marks.body_sent.filter(|sent| *sent > ready) {
+        push(TimingPhase::RequestWrite, offset(ready), offset(body_sent));
+        waiting_start = body_sent;
+    }
+    push(TimingPhase::Waiting, offset(waiting_start), offset(head_at));
+
+    HopTiming {
+        method: identity.method,
+        url: identity.url,
+        status: identity.status,
+        reused_connection: connect.is_none(),
+        spans,
+    }
+}
+
+struct WatchedBody {
+    inner: reqwest::Body,
+    clock: HopClock,
+}
+
+impl HttpBody for WatchedBody {
+    type Data = Bytes;
+    type Error = reqwest::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let polled = Pin::new(&mut self.inner).poll_frame(cx);
+        // HTTP/1 stops polling once the declared length has been written,
+        // so the end is recorded as soon as the body reports it.
+        if matches!(polled, Poll::Ready(None)) || self.inner.is_end_stream() {
+            let sent = Instant::now();
+            self.clock.update(|marks| {
+                marks.body_sent.get_or_insert(sent);
+            });
+        }
+        polled
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// The system resolver, timed for the hop that asked for the lookup.
+pub(super) struct TimedResolver;
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let clock = HopClock::current();
+        let host = name.as_str().to_owned();
+        Box::pin(async move {
+            let started = Instant::now();
+            let addresses: Vec<_> = tokio::net::lookup_host((host.as_str(), 0))
+                .await
+                .map_err(|error| Box::new(error) as Box<dyn Error + Send + Sync>)?
+                .collect();
+            if let Some(clock) = clock {
+                let finished = Instant::now();
+                clock.update(|marks| marks.dns = Some((started, finished)));
+            }
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// rustls' session store, which it consults while writing the ClientHello.
+/// That is the first thing that happens on a freshly connected TCP stream,
+/// so the store marks the end of the connect and the start of the
+/// handshake for the current hop.
+#[derive(Debug)]
+pub(super) struct TimedSessionStore(pub(super) Arc<dyn ClientSessionStore>);
+
+impl TimedSessionStore {
+    fn handshake_started(&self) {
+        if let Some(clock) = HopClock::current() {
+            let started = Instant::now();
+            clock.update(|marks| {
+                marks.tls_start.get_or_insert(started);
+            });
+        }
+    }
+}
+
+impl ClientSessionStore for TimedSessionStore {
+    fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+        self.0.set_kx_hint(server_name, group);
+    }
+
+    fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+        self.handshake_started();
+        self.0.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+        self.0.set_tls12_session(server_name, value);
+    }
+
+    fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+        self.handshake_started();
+        self.0.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+        self.0.remove_tls12_session(server_name);
+    }
+
+    fn insert_tls13_ticket(
+        &self,
+        server_name: ServerName<'static>,
+        value: Tls13ClientSessionValue,
+    ) {
+        self.0.insert_tls13_ticket(server_name, value);
+    }
+
+    fn take_tls13_ticket(
+        &self,
+        server_name: &ServerName<'static>,
+    ) -> Option<Tls13ClientSessionValue> {
+        self.handshake_started();
+        self.0.take_tls13_ticket(server_name)
+    }
+}
+
+/// Times every new connection, including the lookup it starts with.
+#[derive(Clone, Copy)]
+pub(super) struct ConnectTiming;
+
+impl<S> Layer<S> for ConnectTiming {
+    type Service = TimedConnector<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimedConnector(inner)
+    }
+}
+
+#[derive(Clone)]
+pub(super) struct TimedConnector<S>(S);
+
+impl<S, Request> Service<Request> for TimedConnector<S>
+where
+    S: Service<Request>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let clock = HopClock::current();
+        let started = Instant::now();
+        let connecting = self.0.call(request);
+        Box::pin(async move {
+            let connection = connecting.await;
+            if let Some(clock) = clock.filter(|_| connection.is_ok()) {
+                let finished = Instant::now();
+                clock.update(|marks| marks.connect = Some((started, finished)));
+            }
+            connection
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity() -> HopIdentity {
+        HopIdentity {
+            method: Method::POST,
+            url: Url::parse("https://example.test/upload").unwrap(),
+            status: StatusCode::OK,
+        }
+    }
+
+    #[test]
+    fn a_new_connection_splits_into_lookup_connect_write_and_wait() {
+        let origin = Instant::now();
+        let at = |ms| origin + Duration::from_millis(ms);
+        let marks = HopMarks {
+            dns: Some((at(12), at(20))),
+            connect: Some((at(11), at(50))),
+            tls_start: None,
+            body_sent: Some(at(70)),
+        };
+
+        let hop = hop_timing(origin, at(10), at(120), &marks, identity());
+        assert!(!hop.reused_connection);
+        let spans: Vec<_> = hop
+            .spans
+            .iter()
+            .map(|span| (span.phase, span.start.as_millis(), span.end.as_millis()))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                (TimingPhase::DnsLookup, 12, 20),
+                (TimingPhase::Connect, 20, 50),
+                (TimingPhase::RequestWrite, 50, 70),
+                (TimingPhase::Waiting, 70, 120),
+            ]
+        );
+        assert_eq!(hop.end(), Duration::from_millis(120));
+    }
+
+    #[test]
+    fn a_secure_connection_splits_its_connect_from_the_handshake() {
+        let origin = Instant::now();
+        let at = |ms| origin + Duration::from_millis(ms);
+        let marks = HopMarks {
+            dns: None,
+            connect: Some((at(2), at(40))),
+            tls_start: Some(at(15)),
+            body_sent: None,
+        };
+
+        let hop = hop_timing(origin, at(2), at(60), &marks, identity());
+        let spans: Vec<_> = hop
+            .spans
+            .iter()
+            .map(|span| (span.phase, span.start.as_millis(), span.end.as_millis()))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                (TimingPhase::Connect, 2, 15),
+                (TimingPhase::TlsHandshake, 15, 40),
+                (TimingPhase::Waiting, 40, 60),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_pooled_connection_only_waits_and_ignores_stray_lookups() {
+        let origin = Instant::now();
+        let at = |ms| origin + Duration::from_millis(ms);
+        let marks = HopMarks {
+            dns: Some((at(5), at(9))),
+            connect: None,
+            tls_start: Some(at(6)),
+            body_sent: None,
+        };
+
+        let hop = hop_timing(origin, at(4), at(30), &marks, identity());
+        assert!(hop.reused_connection);
+        assert_eq!(
+            hop.spans,
+            [TimingSpan {
+                phase: TimingPhase::Waiting,
+                start: Duration::from_millis(4),
+                end: Duration::from_millis(30),
+            }]
+        );
+    }
+}
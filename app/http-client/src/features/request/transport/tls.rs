@@ -0,0 +1,338 @@
+//! The rustls configuration behind every client. reqwest only reports the
+//! leaf certificate of a connection, so the configuration is built here
+//! with a verifier that remembers each chain it is shown, and with a
+//! session store that tells the hop timing when the handshake begins.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use p12_keystore::KeyStore;
+use rustls::{
+    ClientConfig, DigitallySignedStruct, SignatureScheme,
+    client::{
+        ClientSessionMemoryCache, Resumption,
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    },
+    crypto::{
+        CryptoProvider, WebPkiSupportedAlgorithms, aws_lc_rs, verify_tls12_signature,
+        verify_tls13_signature,
+    },
+    pki_types::{
+        CertificateDer, PrivateKeyDer, ServerName, UnixTime,
+        pem::{self, PemObject as _},
+    },
+};
+use rustls_platform_verifier::Verifier;
+use thiserror::Error;
+
+use super::timing::TimedSessionStore;
+use crate::features::request::{
+    draft::HttpVersionPreference,
+    runtime::{ConnectionProblemKind, RequestProblem},
+};
+
+/// How many chains are kept before the oldest are forgotten together.
+const REMEMBERED_CHAINS: usize = 64;
+/// rustls' own default for the number of servers whose sessions are kept.
+const REMEMBERED_SESSIONS: usize = 256;
+
+/// The certificate chains servers presented during verification, keyed by
+/// their leaf. A response on a pooled connection finds the chain of the
+/// handshake that opened it this way.
+#[derive(Clone, Default)]
+pub(super) struct PeerChains {
+    chains: Arc<Mutex<HashMap<Vec<u8>, Arc<[Vec<u8>]>>>>,
+}
+
+impl PeerChains {
+    fn remember(&self, end_entity: &CertificateDer<'_>, intermediates: &[CertificateDer<'_>]) {
+        let chain = std::iter::once(end_entity)
+            .chain(intermediates)
+            .map(|certificate| certificate.to_vec())
+            .collect::<Arc<[_]>>();
+        let mut chains = self
+            .chains
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if chains.len() >= REMEMBERED_CHAINS && !chains.contains_key(end_entity.as_ref()) {
+            chains.clear();
+        }
+        chains.insert(end_entity.to_vec(), chain);
+    }
+
+    /// The chain that ends in `leaf`, leaf first. Only the leaf is known
+    /// when its handshake was never verified here.
+    pub(super) fn chain(&self, leaf: &[u8]) -> Vec<Vec<u8>> {
+        self.chains
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(leaf)
+            .map_or_else(|| vec![leaf.to_vec()], |chain| chain.to_vec())
+    }
+}
+
+/// What a client's TLS configuration is built from.
+#[derive(Default)]
+pub(super) struct TlsSettings {
+    pub(super) http_version: HttpVersionPreference,
+    /// Trusted next to the platform's roots.
+    pub(super) extra_roots: Vec<CertificateDer<'static>>,
+    pub(super) identity: Option<ClientIdentityDer>,
+    pub(super) accept_invalid_certificates: bool,
+}
+
+/// A client certificate chain, leaf first, and its private key.
+pub(super) struct ClientIdentityDer {
+    chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+}
+
+impl TlsSettings {
+    pub(super) fn client_config(self, chains: &PeerChains) -> Result<ClientConfig, RequestProblem> {
+        let provider = CryptoProvider::get_default()
+            .cloned()
+            .unwrap_or_else(|| Arc::new(aws_lc_rs::default_provider()));
+        let verifier: Arc<dyn ServerCertVerifier> = if self.accept_invalid_certificates {
+            Arc::new(AcceptAnyCertificate(
+                provider.signature_verification_algorithms,
+            ))
+        } else if self.extra_roots.is_empty() {
+            Arc::new(Verifier::new(provider.clone()).map_err(RequestProblem::transport)?)
+        } else {
+            Arc::new(
+                Verifier::new_with_extra_roots(self.extra_roots, provider.clone()).map_err(
+                    |error| RequestProblem::connection(ConnectionProblemKind::CaCertificate, error),
+                )?,
+            )
+        };
+        let builder = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(RequestProblem::transport)?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(RecordingVerifier {
+                inner: verifier,
+                chains: chains.clone(),
+            }));
+        let mut config = match self.identity {
+            Some(identity) => builder
+                .with_client_auth_cert(identity.chain, identity.key)
+                .map_err(|error| {
+                    RequestProblem::connection(ConnectionProblemKind::ClientIdentity, error)
+                })?,
+            None => builder.with_no_client_auth(),
+        };
+        config.alpn_protocols = match self.http_version {
+            HttpVersionPreference::Automatic => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+            HttpVersionPreference::Http1 => vec![b"http/1.1".to_vec()],
+            HttpVersionPreference::Http2 => vec![b"h2".to_vec()],
+        };
+        config.resumption = Resumption::store(Arc::new(TimedSessionStore(Arc::new(
+            ClientSessionMemoryCache::new(REMEMBERED_SESSIONS),
+        ))));
+        Ok(config)
+    }
+}
+
+#[derive(Debug, Error)]
+pub(super) enum IdentityError {
+    #[error("client identity is not valid PEM")]
+    Pem(#[from] pem::Error),
+    #[error("client identity has no certificate")]
+    NoCertificate,
+    #[error("PKCS#12 archive could not be opened: {0}")]
+    Pkcs12(String),
+    #[error("PKCS#12 archive has no private key")]
+    NoPrivateKey,
+}
+
+/// Every certificate of a PEM bundle.
+pub(super) fn pem_certificates(bundle: &[u8]) -> Result<Vec<CertificateDer<'static>>, pem::Error> {
+    CertificateDer::pem_slice_iter(bundle).collect()
+}
+
+/// A certificate chain and the private key that belongs to it, in any
+/// order and possibly with other PEM blocks between them.
+pub(super) fn pem_identity(pem: &[u8]) -> Result<ClientIdentityDer, IdentityError> {
+    let chain = pem_certificates(pem)?;
+    if chain.is_empty() {
+        return Err(IdentityError::NoCertificate);
+    }
+    let key = PrivateKeyDer::from_pem_slice(pem)?;
+    Ok(ClientIdentityDer { chain, key })
+}
+
+/// Unlocks a PKCS#12 archive and writes its key and certificate chain as
+/// PEM, so both kinds of identity reach rustls the same way.
+pub(super) fn pkcs12_to_pem(archive: &[u8], password: &str) -> Result<Vec<u8>, IdentityError> {
+    let keystore = KeyStore::from_pkcs12(archive, password)
+        .map_err(|error| IdentityError::Pkcs12(error.to_string()))?;
+    let (_, key_chain) = keystore
+        .private_key_chain()
+        .ok_or(IdentityError::NoPrivateKey)?;
+    let mut pem = pem_block("PRIVATE KEY", key_chain.key());
+    for certificate in key_chain.chain() {
+        pem.push_str(&pem_block("CERTIFICATE", certificate.as_der()));
+    }
+    Ok(pem.into_bytes())
+}
+
+fn pem_block(label: &str, der: &[u8]) -> String {
+    let encoded = STANDARD.encode(der);
+    let mut block = format!("-----BEGIN {label}-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        block.push_str(&String::from_utf8_lossy(line));
+        block.push('\n');
+    }
+    block.push_str(&format!("-----END {label}-----\n"));
+    block
+}
+
+/// Hands every verification to `inner` and keeps the chain of each one
+/// that succeeds.
+struct RecordingVerifier {
+    inner: Arc<dyn ServerCertVerifier>,
+    chains: PeerChains,
+}
+
+impl fmt::Debug for RecordingVerifier {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("RecordingVerifier")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+        self.chains.remember(end_entity, intermediates);
+        Ok(verified)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner
+            .verify_tls12_signature(message, certificate, signature)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner
+            .verify_tls13_signature(message, certificate, signature)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// Trusts any certificate, for requests that switch verification off. The
+/// handshake signatures are still checked so the connection is at least
+/// with whoever holds the presented key.
+#[derive(Debug)]
+struct AcceptAnyCertificate(WebPkiSupportedAlgorithms);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, certificate, signature, &self.0)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        certificate: &CertificateDer<'_>,
+        signature: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, certificate, signature, &self.0)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVER_CHAIN: &[u8] = include_bytes!("testdata/server-chain.pem");
+    const SERVER_KEY: &[u8] = include_bytes!("testdata/server-key.pem");
+    /// `server-chain.pem` and `server-key.pem`, locked with `fixture`.
+    const IDENTITY_P12: &[u8] = include_bytes!("testdata/identity.p12");
+
+    #[test]
+    fn pkcs12_archives_convert_to_the_same_identity_as_their_pem_files() {
+        let pem = pkcs12_to_pem(IDENTITY_P12, "fixture").unwrap();
+        let converted = pem_identity(&pem).unwrap();
+
+        let expected = pem_certificates(SERVER_CHAIN).unwrap();
+        assert_eq!(converted.chain, expected);
+        assert!(matches!(converted.key, PrivateKeyDer::Pkcs8(_)));
+        assert!(matches!(
+            pkcs12_to_pem(IDENTITY_P12, "wrong"),
+            Err(IdentityError::Pkcs12(_))
+        ));
+        assert!(matches!(
+            pem_identity(SERVER_KEY),
+            Err(IdentityError::NoCertificate)
+        ));
+    }
+
+    #[test]
+    fn chains_are_found_by_their_leaf() {
+        let certificates = pem_certificates(SERVER_CHAIN).unwrap();
+        let chains = PeerChains::default();
+        assert_eq!(chains.chain(&certificates[0]), [certificates[0].to_vec()]);
+
+        chains.remember(&certificates[0], &certificates[1..]);
+        assert_eq!(
+            chains.chain(&certificates[0]),
+            certificates
+                .iter()
+                .map(|certificate| certificate.to_vec())
+                .collect::<Vec<_>>()
+        );
+    }
+}
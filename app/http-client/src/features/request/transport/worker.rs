@@ -1,17 +1,25 @@
 use std::time::{Duration, Instant};
 
 use async_channel::{Sender, TrySendError};
-use http::{HeaderMap, Method, StatusCode, header};
+use futures_util::future::join;
+use http::{HeaderMap, HeaderValue, Method, StatusCode, header};
 use reqwest::Client;
 
 use super::{
-    WorkerEvent, body,
+    WorkerEvent,
+    auth::{OAuthTokenCache, RequestAuthorizer},
+    body,
+    certificate::describe_certificate,
+    cookies::CookieJar,
     redirect::{RedirectError, RedirectState},
+    timing::HopClock,
+    tls::PeerChains,
 };
 use crate::features::request::{
     prepared::{PreparedBody, PreparedRequest},
     response::{
-        CompletedBody, ResponseHead, ResponseProgress, collect_response_body,
+        CompletedBody, ContentKind, HopTiming, ResponseHead, ResponseProgress, SseParser,
+        SseUpdate, TlsDetails, classify_content_type, collect_response_body,
         declared_encoded_bytes,
     },
     runtime::{RedirectProblemKind, RequestProblem},
@@ -22,6 +30,9 @@ const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
 pub(super) async fn execute(
     prepared: PreparedRequest,
     client: Client,
+    tokens: &OAuthTokenCache,
+    cookies: &CookieJar,
+    chains: &PeerChains,
     sender: &Sender<WorkerEvent>,
     started_at: Instant,
 ) -> Result<crate::features::request::response::CompletedBody, RequestProblem> {
@@ -32,10 +43,16 @@ pub(super) async fn execute(
         body,
         body_content_type,
         redirect,
+        auth,
+        send_cookies,
         timeout: _,
+        connection,
     } = prepared;
     let mut body_available = !matches!(body, PreparedBody::None);
     let mut redirects = RedirectState::new(redirect, &url);
+    let mut hops = Vec::new();
+    let mut authorizer =
+        RequestAuthorizer::new(&auth, &url, &body, &client, tokens, sender, &mut headers).await?;
 
     loop {
         let mut builder = client.request(method.clone(), url.clone());
@@ -47,10 +64,28 @@ pub(super) async fn execute(
         // Generated body headers are applied first. The frozen explicit map
         // then replaces every same-name generated value while preserving its
         // own duplicate values.
-        builder = builder.headers(headers.clone());
+        let mut hop_headers = headers.clone();
+        if send_cookies && let Some(stored) = cookies.header(&url) {
+            add_stored_cookies(&mut hop_headers, stored);
+        }
+        authorizer.authorize(&method, &url, body_available, &mut hop_headers)?;
+        builder = builder.headers(hop_headers);
 
-        let response = builder.send().await.map_err(classify_send_error)?;
+        let mut request = builder.build().map_err(classify_send_error)?;
+        let clock = HopClock::start();
+        if let Some(body) = request.body_mut().take() {
+            *request.body_mut() = Some(clock.watch_body(body));
+        }
+        let response = clock
+            .scope(client.execute(request))
+            .await
+            .map_err(classify_send_error)?;
         let status = response.status();
+        hops.push(clock.finish(started_at, method.clone(), url.clone(), status));
+        cookies.receive(&url, response.headers());
+        if authorizer.accept_challenge(status, response.headers(), &url) {
+            continue;
+        }
         if let Some(next) = redirects
             .next(
                 status,
@@ -68,16 +103,40 @@ pub(super) async fn execute(
             url = next.url;
             method = next.method;
             body_available = next.keep_body;
+            authorizer.next_hop();
             continue;
         }
 
-        return receive_final(response, &method, sender, started_at).await;
+        return receive_final(
+            response,
+            &method,
+            connection.accept_invalid_certificates,
+            chains,
+            hops,
+            sender,
+            started_at,
+        )
+        .await;
+    }
+}
+
+fn tls_version_label(version: reqwest::tls::Version) -> Option<&'static str> {
+    use reqwest::tls::Version;
+    match version {
+        Version::TLS_1_0 => Some("TLS 1.0"),
+        Version::TLS_1_1 => Some("TLS 1.1"),
+        Version::TLS_1_2 => Some("TLS 1.2"),
+        Version::TLS_1_3 => Some("TLS 1.3"),
+        _ => None,
     }
 }
 
 async fn receive_final(
     response: reqwest::Response,
     method: &Method,
+    verification_disabled: bool,
+    chains: &PeerChains,
+    hops: Vec<HopTiming>,
     sender: &Sender<WorkerEvent>,
     started_at: Instant,
 ) -> Result<CompletedBody, RequestProblem> {
@@ -88,6 +147,24 @@ async fn receive_final(
         version: response.version(),
         final_url: response.url().clone(),
         headers: response.headers().clone(),
+        tls: response
+            .extensions()
+            .get::<reqwest::tls::TlsInfo>()
+            .map(|info| TlsDetails {
+                version: info.version().and_then(tls_version_label),
+                peer_certificates: info
+                    .peer_certificate()
+                    .map(|leaf| {
+                        chains
+                            .chain(leaf)
+                            .iter()
+                            .map(|der| describe_certificate(der))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                verification_disabled,
+            }),
+        hops,
     };
     let initial = ResponseProgress::initial(declared_encoded_bytes);
     sender
@@ -106,7 +183,7 @@ async fn receive_final(
     let headers = response.headers().clone();
     let mut last_progress = None;
     let mut last_sent_at = None;
-    let result = collect_response_body(&headers, response.bytes_stream(), |progress| {
+    let mut on_progress = |progress| {
         last_progress = Some(progress);
         let now = Instant::now();
         if last_sent_at.is_none_or(|previous| now.duration_since(previous) >= PROGRESS_INTERVAL) {
@@ -116,8 +193,35 @@ async fn receive_final(
                 Err(TrySendError::Closed(_)) => {}
             }
         }
-    })
-    .await;
+    };
+    let result = if classify_content_type(&headers) == ContentKind::EventStream {
+        // Progress may be coalesced, but parsed events may not. The body
+        // callback cannot wait for channel capacity, so updates queue here
+        // and are forwarded in order before the terminal event.
+        let (updates, pending) = async_channel::unbounded();
+        let mut parser = SseParser::default();
+        let collect =
+            collect_response_body(&headers, response.bytes_stream(), move |progress, chunk| {
+                let update = parser.feed(chunk);
+                if update != SseUpdate::default() {
+                    let _ = updates.try_send(update);
+                }
+                on_progress(progress);
+            });
+        let forward = async {
+            while let Ok(update) = pending.recv().await {
+                if sender.send(WorkerEvent::EventStream(update)).await.is_err() {
+                    break;
+                }
+            }
+        };
+        join(collect, forward).await.0
+    } else {
+        collect_response_body(&headers, response.bytes_stream(), |progress, _| {
+            on_progress(progress);
+        })
+        .await
+    };
 
     if result.is_err()
         && let Some(progress) = last_progress
@@ -137,6 +241,24 @@ fn response_has_no_message_body(method: &Method, status: StatusCode) -> bool {
         || (*method == Method::CONNECT && status.is_success())
 }
 
+/// Appends jar cookies after any the request sets explicitly, keeping a
+/// single `Cookie` header as HTTP/1.1 requires.
+fn add_stored_cookies(headers: &mut HeaderMap, stored: HeaderValue) {
+    let mut combined = Vec::new();
+    for explicit in headers.get_all(header::COOKIE) {
+        combined.extend_from_slice(explicit.as_bytes());
+        combined.extend_from_slice(b"; ");
+    }
+    if combined.is_empty() {
+        headers.insert(header::COOKIE, stored);
+        return;
+    }
+    combined.extend_from_slice(stored.as_bytes());
+    if let Ok(value) = HeaderValue::from_bytes(&combined) {
+        headers.insert(header::COOKIE, value);
+    }
+}
+
 fn strip_body_headers(headers: &mut HeaderMap) {
     headers.remove(header::CONTENT_LENGTH);
     headers.remove(header::CONTENT_TYPE);
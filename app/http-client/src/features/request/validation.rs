@@ -4,17 +4,22 @@ use gpui_form::{
 use http::HeaderValue;
 
 use super::{
+    checks::{parse_json_path, parse_pattern, parse_status_range, parse_time_limit},
     draft::{
-        ApiKeyAuthDraft, ApiKeyLocation, BasicAuthDraft, BearerAuthDraft, BinaryBodyDraft,
-        FormDataDraft, HeaderDraft, MultipartFileDraft, MultipartPartDraft,
-        MultipartPartValueDraft, MultipartTextDraft, RequestAuthDraft, RequestBodyDraft,
-        RequestDraft,
+        ApiKeyAuthDraft, ApiKeyLocation, AssertionDraft, AssertionKind, AwsSigV4AuthDraft,
+        BasicAuthDraft, BearerAuthDraft, BinaryBodyDraft, ConnectionSettingsDraft, DigestAuthDraft,
+        ExtractionDraft, ExtractionSource, FormDataDraft, GraphQlBodyDraft, HeaderDraft,
+        MultipartFileDraft, MultipartPartDraft, MultipartPartValueDraft, MultipartTextDraft,
+        OAuth2AuthDraft, OAuth2Grant, RequestAuthDraft, RequestBodyDraft, RequestDraft,
+        RequestSettingsDraft,
     },
     prepared::{
-        RequestFieldError, inspect_request_file, parse_header_name, parse_header_value,
-        parse_media_type, parse_request_url, validate_api_key_name, validate_basic_username,
-        validate_disposition_text,
+        RequestFieldError, inspect_request_file, parse_graphql_variables, parse_header_name,
+        parse_header_value, parse_loopback_redirect, parse_media_type, parse_request_url,
+        resolve_graphql_operation, validate_api_key_name, validate_basic_username,
+        validate_credential_scope_part, validate_disposition_text,
     },
+    variables::validate_variable_name,
 };
 
 pub(crate) struct RequestValidator;
@@ -33,6 +38,9 @@ impl Validator<RequestDraft> for RequestValidator {
         validate_headers(&request, out);
         validate_body(&request, out);
         validate_auth(&request, out);
+        validate_connection(&request, out);
+        validate_assertions(&request, out);
+        validate_extractions(&request, out);
     }
 }
 
@@ -131,9 +139,60 @@ fn validate_body(
                 _ => {}
             }
         }
+        RequestBodyDraft::GraphQl(_) => {
+            let Some(graphql) = request.case(body, RequestBodyDraft::GRAPH_QL) else {
+                return;
+            };
+            validate_graphql(request, graphql, out);
+        }
     }
 }
 
+fn validate_graphql<'a>(
+    request: &ValidationRequest<'a, RequestDraft>,
+    graphql: gpui_form::ValidationDynamicPath<'a, RequestDraft, GraphQlBodyDraft>,
+    out: &mut ValidationSink<'_, RequestDraft>,
+) {
+    let query = graphql.clone().then(GraphQlBodyDraft::QUERY);
+    let query_text = request
+        .try_get(&query)
+        .map(String::as_str)
+        .unwrap_or_default();
+    if request.includes(&query) && query_text.trim().is_empty() {
+        out.at(query).error(
+            "request-graphql-query-required",
+            ValidationMessage::key("request-graphql-query-required"),
+        );
+    }
+
+    let variables = graphql.clone().then(GraphQlBodyDraft::VARIABLES);
+    if request.includes(&variables)
+        && request
+            .try_get(&variables)
+            .is_ok_and(|value| parse_graphql_variables(value).is_err())
+    {
+        out.at(variables).error(
+            "request-graphql-variables-invalid",
+            ValidationMessage::key("request-graphql-variables-invalid"),
+        );
+    }
+
+    let operation_name = graphql.then(GraphQlBodyDraft::OPERATION_NAME);
+    if !request.includes(&operation_name) {
+        return;
+    }
+    let Ok(name) = request.try_get(&operation_name) else {
+        return;
+    };
+    let key = match resolve_graphql_operation(query_text, name) {
+        Err(RequestFieldError::GraphQlOperationRequired) => "request-graphql-operation-required",
+        Err(_) => "request-graphql-operation-unknown",
+        Ok(_) => return,
+    };
+    out.at(operation_name)
+        .error(key, ValidationMessage::key(key));
+}
+
 fn validate_multipart<'a>(
     request: &ValidationRequest<'a, RequestDraft>,
     form_data: gpui_form::ValidationDynamicPath<'a, RequestDraft, FormDataDraft>,
@@ -286,7 +345,356 @@ fn validate_auth(
             };
             validate_api_key(request, api_key, out);
         }
+        RequestAuthDraft::Digest(_) => {
+            let Some(digest) = request.case(auth, RequestAuthDraft::DIGEST) else {
+                return;
+            };
+            let username = digest.then(DigestAuthDraft::USERNAME);
+            if request.includes(&username)
+                && request
+                    .try_get(&username)
+                    .is_ok_and(|value| parse_header_value(value).is_err())
+            {
+                out.at(username).error(
+                    "request-auth-value-invalid",
+                    ValidationMessage::key("request-auth-value-invalid"),
+                );
+            }
+        }
+        RequestAuthDraft::OAuth2(_) => {
+            let Some(oauth2) = request.case(auth, RequestAuthDraft::O_AUTH2) else {
+                return;
+            };
+            validate_oauth2(request, oauth2, out);
+        }
+        RequestAuthDraft::AwsSigV4(_) => {
+            let Some(aws) = request.case(auth, RequestAuthDraft::AWS_SIG_V4) else {
+                return;
+            };
+            validate_aws_sigv4(request, aws, out);
+        }
+    }
+}
+
+/// Checks a URL field that is only required by the selected grant. Empty
+/// values on shared required fields are left to the form schema.
+fn validate_auth_url<'a>(
+    request: &ValidationRequest<'a, RequestDraft>,
+    path: gpui_form::ValidationDynamicPath<'a, RequestDraft, String>,
+    required: bool,
+    out: &mut ValidationSink<'_, RequestDraft>,
+) {
+    if !request.includes(&path) {
+        return;
     }
+    let Ok(value) = request.try_get(&path) else {
+        return;
+    };
+    let (code, key) = match parse_request_url(value) {
+        Ok(_) => return,
+        Err(RequestFieldError::Required) if required => ("required", "gpui-form-error-required"),
+        Err(RequestFieldError::Required) => return,
+        Err(RequestFieldError::UnsupportedUrlScheme) => {
+            ("request-url-scheme-invalid", "request-url-scheme-invalid")
+        }
+        Err(_) => ("request-url-invalid", "request-url-invalid"),
+    };
+    out.at(path).error(code, ValidationMessage::key(key));
+}
+
+fn validate_oauth2<'a>(
+    request: &ValidationRequest<'a, RequestDraft>,
+    oauth2: gpui_form::ValidationDynamicPath<'a, RequestDraft, OAuth2AuthDraft>,
+    out: &mut ValidationSink<'_, RequestDraft>,
+) {
+    validate_auth_url(
+        request,
+        oauth2.clone().then(OAuth2AuthDraft::TOKEN_URL),
+        false,
+        out,
+    );
+    let Ok(grant) = request.try_get(&oauth2.clone().then(OAuth2AuthDraft::GRANT)) else {
+        return;
+    };
+    match grant {
+        OAuth2Grant::ClientCredentials => {}
+        OAuth2Grant::Password => {
+            let username = oauth2.then(OAuth2AuthDraft::USERNAME);
+            if request.includes(&username)
+                && request
+                    .try_get(&username)
+                    .is_ok_and(|value| value.is_empty())
+            {
+                out.at(username).error(
+                    "required",
+                    ValidationMessage::key("gpui-form-error-required"),
+                );
+            }
+        }
+        OAuth2Grant::AuthorizationCode => {
+            validate_auth_url(
+                request,
+                oauth2.clone().then(OAuth2AuthDraft::AUTHORIZATION_URL),
+                true,
+                out,
+            );
+            let redirect = oauth2.then(OAuth2AuthDraft::REDIRECT_URI);
+            if request.includes(&redirect)
+                && request
+                    .try_get(&redirect)
+                    .is_ok_and(|value| parse_loopback_redirect(value).is_err())
+            {
+                out.at(redirect).error(
+                    "request-oauth2-redirect-loopback",
+                    ValidationMessage::key("request-oauth2-redirect-loopback"),
+                );
+            }
+        }
+    }
+}
+
+fn validate_aws_sigv4<'a>(
+    request: &ValidationRequest<'a, RequestDraft>,
+    aws: gpui_form::ValidationDynamicPath<'a, RequestDraft, AwsSigV4AuthDraft>,
+    out: &mut ValidationSink<'_, RequestDraft>,
+) {
+    for field in [
+        AwsSigV4AuthDraft::ACCESS_KEY_ID,
+        AwsSigV4AuthDraft::REGION,
+        AwsSigV4AuthDraft::SERVICE,
+    ] {
+        let path = aws.clone().then(field);
+        if request.includes(&path)
+            && request.try_get(&path).is_ok_and(|value| {
+                validate_credential_scope_part(value)
+                    == Err(RequestFieldError::InvalidCredentialScope)
+            })
+        {
+            out.at(path).error(
+                "request-aws-scope-invalid",
+                ValidationMessage::key("request-aws-scope-invalid"),
+            );
+        }
+    }
+
+    let session_token = aws.then(AwsSigV4AuthDraft::SESSION_TOKEN);
+    if request.includes(&session_token)
+        && request
+            .try_get(&session_token)
+            .is_ok_and(|value| parse_header_value(value).is_err())
+    {
+        out.at(session_token).error(
+            "request-auth-value-invalid",
+            ValidationMessage::key("request-auth-value-invalid"),
+        );
+    }
+}
+
+fn validate_assertions(
+    request: &ValidationRequest<'_, RequestDraft>,
+    out: &mut ValidationSink<'_, RequestDraft>,
+) {
+    let assertions = RequestDraft::ROOT.then(RequestDraft::ASSERTIONS);
+    for assertion in request.items(&assertions) {
+        let enabled = assertion.clone().then(AssertionDraft::ENABLED);
+        if !request.try_get(&enabled).is_ok_and(|enabled| *enabled) {
+            continue;
+        }
+        let Ok(kind) = request.try_get(&assertion.clone().then(AssertionDraft::KIND)) else {
+            continue;
+        };
+        let target = assertion.clone().then(AssertionDraft::TARGET);
+        let expected = assertion.then(AssertionDraft::EXPECTED);
+        match kind {
+            AssertionKind::StatusRange => {
+                validate_check_field(
+                    request,
+                    expected,
+                    |value| parse_status_range(value).map(drop),
+                    out,
+                );
+            }
+            AssertionKind::HeaderPresent => {
+                validate_check_field(request, target, check_header_name, out);
+            }
+            AssertionKind::HeaderMatches => {
+                validate_check_field(request, target, check_header_name, out);
+                validate_check_field(
+                    request,
+                    expected,
+                    |value| parse_pattern(value).map(drop),
+                    out,
+                );
+            }
+            AssertionKind::JsonPathEquals => {
+                validate_check_field(
+                    request,
+                    target,
+                    |value| parse_json_path(value).map(drop),
+                    out,
+                );
+            }
+            AssertionKind::JsonPathMatches => {
+                validate_check_field(
+                    request,
+                    target,
+                    |value| parse_json_path(value).map(drop),
+                    out,
+                );
+                validate_check_field(
+                    request,
+                    expected,
+                    |value| parse_pattern(value).map(drop),
+                    out,
+                );
+            }
+            AssertionKind::ResponseTimeBelow => {
+                validate_check_field(
+                    request,
+                    expected,
+                    |value| parse_time_limit(value).map(drop),
+                    out,
+                );
+            }
+        }
+    }
+}
+
+fn validate_connection(
+    request: &ValidationRequest<'_, RequestDraft>,
+    out: &mut ValidationSink<'_, RequestDraft>,
+) {
+    let connection = RequestDraft::ROOT
+        .then(RequestDraft::SETTINGS)
+        .then(RequestSettingsDraft::CONNECTION);
+    for file in [
+        ConnectionSettingsDraft::CA_CERTIFICATE,
+        ConnectionSettingsDraft::CLIENT_CERTIFICATE,
+        ConnectionSettingsDraft::CLIENT_KEY,
+    ] {
+        let file = connection.clone().then(file);
+        if request.includes(&file)
+            && request
+                .get(&file)
+                .as_deref()
+                .is_some_and(|path| inspect_request_file(path).is_err())
+        {
+            out.at(file).error(
+                "request-file-unavailable",
+                ValidationMessage::key("request-file-unavailable"),
+            );
+        }
+    }
+
+    let key = connection.clone().then(ConnectionSettingsDraft::CLIENT_KEY);
+    let certificate = connection
+        .clone()
+        .then(ConnectionSettingsDraft::CLIENT_CERTIFICATE);
+    if request.includes(&key) && request.get(&key).is_some() && request.get(&certificate).is_none()
+    {
+        out.at(key).error(
+            "request-client-certificate-required",
+            ValidationMessage::key("request-client-certificate-required"),
+        );
+    }
+
+    let proxy = connection.then(ConnectionSettingsDraft::PROXY_URL);
+    let proxy_url = request.get(&proxy).trim();
+    if request.includes(&proxy) && !proxy_url.is_empty() && parse_request_url(proxy_url).is_err() {
+        out.at(proxy).error(
+            "request-proxy-url-invalid",
+            ValidationMessage::key("request-proxy-url-invalid"),
+        );
+    }
+}
+
+fn validate_extractions(
+    request: &ValidationRequest<'_, RequestDraft>,
+    out: &mut ValidationSink<'_, RequestDraft>,
+) {
+    let extractions = RequestDraft::ROOT.then(RequestDraft::EXTRACTIONS);
+    for extraction in request.items(&extractions) {
+        let enabled = extraction.clone().then(ExtractionDraft::ENABLED);
+        if !request.try_get(&enabled).is_ok_and(|enabled| *enabled) {
+            continue;
+        }
+        validate_check_field(
+            request,
+            extraction.clone().then(ExtractionDraft::VARIABLE),
+            validate_variable_name,
+            out,
+        );
+        let Ok(source) = request.try_get(&extraction.clone().then(ExtractionDraft::SOURCE)) else {
+            continue;
+        };
+        let expression = extraction.then(ExtractionDraft::EXPRESSION);
+        match source {
+            ExtractionSource::JsonPath => {
+                validate_check_field(
+                    request,
+                    expression,
+                    |value| parse_json_path(value).map(drop),
+                    out,
+                );
+            }
+            ExtractionSource::Header => {
+                validate_check_field(request, expression, check_header_name, out);
+            }
+        }
+    }
+}
+
+fn check_header_name(value: &str) -> Result<(), RequestFieldError> {
+    if value.trim().is_empty() {
+        return Err(RequestFieldError::Required);
+    }
+    parse_header_name(value.trim()).map(drop)
+}
+
+/// Reports the first problem of one assertion or extraction field. Fields a
+/// row's kind does not use are never passed here.
+fn validate_check_field<'a>(
+    request: &ValidationRequest<'a, RequestDraft>,
+    path: gpui_form::ValidationDynamicPath<'a, RequestDraft, String>,
+    check: impl FnOnce(&str) -> Result<(), RequestFieldError>,
+    out: &mut ValidationSink<'_, RequestDraft>,
+) {
+    if !request.includes(&path) {
+        return;
+    }
+    let Ok(value) = request.try_get(&path) else {
+        return;
+    };
+    let Err(error) = check(value) else {
+        return;
+    };
+    let (code, key) = match error {
+        RequestFieldError::Required => ("required", "gpui-form-error-required"),
+        RequestFieldError::InvalidHeaderName => {
+            ("request-header-name-invalid", "request-header-name-invalid")
+        }
+        RequestFieldError::InvalidStatusRange => (
+            "request-check-status-invalid",
+            "request-check-status-invalid",
+        ),
+        RequestFieldError::InvalidPattern => (
+            "request-check-pattern-invalid",
+            "request-check-pattern-invalid",
+        ),
+        RequestFieldError::InvalidJsonPath => (
+            "request-check-json-path-invalid",
+            "request-check-json-path-invalid",
+        ),
+        RequestFieldError::InvalidDuration => (
+            "request-check-duration-invalid",
+            "request-check-duration-invalid",
+        ),
+        _ => (
+            "request-variable-name-invalid",
+            "request-variable-name-invalid",
+        ),
+    };
+    out.at(path).error(code, ValidationMessage::key(key));
 }
 
 fn validate_api_key<'a>(
@@ -358,6 +766,8 @@ mod tests {
             body: RequestBodyDraft::None,
             auth: RequestAuthDraft::None,
             settings: RequestSettingsDraft::default(),
+            assertions: Vec::new(),
+            extractions: Vec::new(),
         }
     }
 
@@ -691,6 +1101,132 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    fn graphql_issues_name_the_variables_and_the_operation(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let mut draft = valid_draft();
+            draft.body = RequestBodyDraft::GraphQl(GraphQlBodyDraft {
+                query: "query A { a } query B { b }".into(),
+                variables: "[1]".into(),
+                operation_name: String::new(),
+            });
+            let form = form(draft, cx);
+            assert!(form.update(cx, |form, cx| form.prepare(cx)).is_err());
+            let graphql = RequestDraft::BODY
+                .case(RequestBodyDraft::GRAPH_QL)
+                .resolve(&form, cx)
+                .unwrap()
+                .unwrap();
+            let code = |path: gpui_form::DynamicPath<RequestDraft, String>| {
+                path.try_errors(&form, cx)
+                    .unwrap()
+                    .first()
+                    .map(|issue| issue.code().to_owned())
+            };
+            assert_eq!(code(graphql.clone().then(GraphQlBodyDraft::QUERY)), None);
+            assert_eq!(
+                code(graphql.clone().then(GraphQlBodyDraft::VARIABLES)).as_deref(),
+                Some("request-graphql-variables-invalid")
+            );
+            assert_eq!(
+                code(graphql.clone().then(GraphQlBodyDraft::OPERATION_NAME)).as_deref(),
+                Some("request-graphql-operation-required")
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn worker_auth_issues_follow_the_selected_grant(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let mut code_draft = valid_draft();
+            code_draft.auth = RequestAuthDraft::OAuth2(OAuth2AuthDraft {
+                grant: OAuth2Grant::AuthorizationCode,
+                token_url: "ftp://auth.example/token".into(),
+                client_id: "client".into(),
+                redirect_uri: "https://app.example/callback".into(),
+                ..OAuth2AuthDraft::default()
+            });
+            let code_form = form(code_draft, cx);
+            assert!(code_form.update(cx, |form, cx| form.prepare(cx)).is_err());
+            let oauth2 = RequestDraft::AUTH
+                .case(RequestAuthDraft::O_AUTH2)
+                .resolve(&code_form, cx)
+                .unwrap()
+                .unwrap();
+            for (field, code) in [
+                (OAuth2AuthDraft::TOKEN_URL, "request-url-scheme-invalid"),
+                (OAuth2AuthDraft::AUTHORIZATION_URL, "required"),
+                (
+                    OAuth2AuthDraft::REDIRECT_URI,
+                    "request-oauth2-redirect-loopback",
+                ),
+            ] {
+                assert_eq!(
+                    oauth2
+                        .clone()
+                        .then(field)
+                        .try_errors(&code_form, cx)
+                        .unwrap()[0]
+                        .code(),
+                    code
+                );
+            }
+
+            // Grant-specific fields of another grant are kept but not checked.
+            let mut credentials_draft = valid_draft();
+            credentials_draft.auth = RequestAuthDraft::OAuth2(OAuth2AuthDraft {
+                token_url: "https://auth.example/token".into(),
+                client_id: "client".into(),
+                redirect_uri: "https://app.example/callback".into(),
+                ..OAuth2AuthDraft::default()
+            });
+            let credentials_form = form(credentials_draft, cx);
+            assert!(
+                credentials_form
+                    .update(cx, |form, cx| form.prepare(cx))
+                    .is_ok()
+            );
+
+            let mut aws_draft = valid_draft();
+            aws_draft.auth = RequestAuthDraft::AwsSigV4(AwsSigV4AuthDraft {
+                access_key_id: "AKID".into(),
+                secret_access_key: "secret".into(),
+                session_token: "bad\ntoken".into(),
+                region: "us east 1".into(),
+                service: "s3".into(),
+            });
+            let aws_form = form(aws_draft, cx);
+            assert!(aws_form.update(cx, |form, cx| form.prepare(cx)).is_err());
+            let aws = RequestDraft::AUTH
+                .case(RequestAuthDraft::AWS_SIG_V4)
+                .resolve(&aws_form, cx)
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                aws.clone()
+                    .then(AwsSigV4AuthDraft::REGION)
+                    .try_errors(&aws_form, cx)
+                    .unwrap()[0]
+                    .code(),
+                "request-aws-scope-invalid"
+            );
+            assert!(
+                aws.clone()
+                    .then(AwsSigV4AuthDraft::SERVICE)
+                    .try_errors(&aws_form, cx)
+                    .unwrap()
+                    .is_empty()
+            );
+            assert_eq!(
+                aws.then(AwsSigV4AuthDraft::SESSION_TOKEN)
+                    .try_errors(&aws_form, cx)
+                    .unwrap()[0]
+                    .code(),
+                "request-auth-value-invalid"
+            );
+        });
+    }
+
     #[gpui::test]
     fn compiler_rechecks_a_file_after_form_validation(cx: &mut TestAppContext) {
         cx.update(|cx| {
@@ -796,4 +1332,79 @@ mod tests {
             assert!(prepared.redirect.preserve_method);
         });
     }
+
+    #[gpui::test]
+    fn check_issues_follow_each_row_kind_and_skip_disabled_rows(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let assertion = |enabled, kind, target: &str, expected: &str| AssertionDraft {
+                enabled,
+                kind,
+                target: target.into(),
+                expected: expected.into(),
+            };
+            let mut draft = valid_draft();
+            draft.assertions = vec![
+                assertion(true, AssertionKind::StatusRange, "ignored", "abc"),
+                assertion(true, AssertionKind::HeaderPresent, " ", "ignored"),
+                assertion(true, AssertionKind::JsonPathMatches, "$.a", "("),
+                assertion(false, AssertionKind::JsonPathEquals, "not a path", ""),
+                assertion(true, AssertionKind::ResponseTimeBelow, "", "0"),
+            ];
+            draft.extractions = vec![
+                ExtractionDraft {
+                    enabled: true,
+                    variable: "1token".into(),
+                    source: ExtractionSource::Header,
+                    expression: "x-token".into(),
+                },
+                ExtractionDraft {
+                    enabled: true,
+                    variable: "token".into(),
+                    source: ExtractionSource::JsonPath,
+                    expression: "token".into(),
+                },
+            ];
+            let form = form(draft, cx);
+            assert!(form.update(cx, |form, cx| form.prepare(cx)).is_err());
+
+            let codes = |path: gpui_form::DynamicPath<RequestDraft, String>, cx: &gpui::App| {
+                path.try_errors(&form, cx)
+                    .unwrap()
+                    .iter()
+                    .map(|issue| issue.code().to_owned())
+                    .collect::<Vec<_>>()
+            };
+            let assertions = RequestDraft::ASSERTIONS.items(&form, cx);
+            assert!(codes(assertions[0].clone().then(AssertionDraft::TARGET), cx).is_empty());
+            assert_eq!(
+                codes(assertions[0].clone().then(AssertionDraft::EXPECTED), cx),
+                ["request-check-status-invalid"]
+            );
+            assert_eq!(
+                codes(assertions[1].clone().then(AssertionDraft::TARGET), cx),
+                ["required"]
+            );
+            assert!(codes(assertions[2].clone().then(AssertionDraft::TARGET), cx).is_empty());
+            assert_eq!(
+                codes(assertions[2].clone().then(AssertionDraft::EXPECTED), cx),
+                ["request-check-pattern-invalid"]
+            );
+            assert!(codes(assertions[3].clone().then(AssertionDraft::TARGET), cx).is_empty());
+            assert_eq!(
+                codes(assertions[4].clone().then(AssertionDraft::EXPECTED), cx),
+                ["request-check-duration-invalid"]
+            );
+
+            let extractions = RequestDraft::EXTRACTIONS.items(&form, cx);
+            assert_eq!(
+                codes(extractions[0].clone().then(ExtractionDraft::VARIABLE), cx),
+                ["request-variable-name-invalid"]
+            );
+            assert!(codes(extractions[0].clone().then(ExtractionDraft::EXPRESSION), cx).is_empty());
+            assert_eq!(
+                codes(extractions[1].clone().then(ExtractionDraft::EXPRESSION), cx),
+                ["request-check-json-path-invalid"]
+            );
+        });
+    }
 }
@@ -0,0 +1,341 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    rc::Rc,
+};
+
+use super::{
+    draft::{MultipartPartValueDraft, RequestAuthDraft, RequestBodyDraft, RequestDraft},
+    prepared::{RequestCompileError, RequestFieldError},
+};
+
+/// Page-owned variables that chain one response into later requests.
+///
+/// Like the transport settings this is not part of the Form: values are
+/// written by response extractions, not edited as request fields. Cloning
+/// shares the same storage.
+#[derive(Clone, Default)]
+pub(crate) struct RequestVariables {
+    values: Rc<RefCell<BTreeMap<String, String>>>,
+}
+
+impl RequestVariables {
+    pub(crate) fn set(&self, name: String, value: String) {
+        self.values.borrow_mut().insert(name, value);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.values.borrow_mut().clear();
+    }
+
+    pub(crate) fn entries(&self) -> Vec<(String, String)> {
+        self.values
+            .borrow()
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Replaces `{{name}}` placeholders in every sent text field of a
+    /// prepared draft; disabled rows are not sent and are left as they are.
+    /// A placeholder naming a variable that has no value fails the whole
+    /// draft, naming every such variable, instead of being sent as written.
+    /// Braces around anything that is not a variable name are left alone.
+    ///
+    /// Substitution runs after validation, so the result is checked again
+    /// when it is compiled: a value that puts a line break in a header, for
+    /// one, fails there.
+    pub(crate) fn resolve(
+        &self,
+        mut draft: RequestDraft,
+    ) -> Result<RequestDraft, RequestCompileError> {
+        let values = self.values.borrow();
+        let mut unknown = BTreeSet::new();
+        let mut apply = |text: &mut String| substitute(text, &values, &mut unknown);
+
+        apply(&mut draft.url);
+        for header in draft.headers.iter_mut().filter(|header| header.enabled) {
+            apply(&mut header.name);
+            apply(&mut header.value);
+        }
+        match &mut draft.body {
+            RequestBodyDraft::None | RequestBodyDraft::Binary(_) => {}
+            RequestBodyDraft::Text(text) => apply(&mut text.content),
+            RequestBodyDraft::UrlEncoded(url_encoded) => {
+                for field in url_encoded.fields.iter_mut().filter(|field| field.enabled) {
+                    apply(&mut field.key);
+                    apply(&mut field.value);
+                }
+            }
+            RequestBodyDraft::FormData(form_data) => {
+                for part in form_data.parts.iter_mut().filter(|part| part.enabled) {
+                    apply(&mut part.name);
+                    if let MultipartPartValueDraft::Text(text) = &mut part.value {
+                        apply(&mut text.value);
+                    }
+                }
+            }
+            RequestBodyDraft::GraphQl(graphql) => {
+                apply(&mut graphql.query);
+                apply(&mut graphql.variables);
+            }
+        }
+        match &mut draft.auth {
+            RequestAuthDraft::None => {}
+            RequestAuthDraft::Basic(basic) => {
+                apply(&mut basic.username);
+                apply(&mut basic.password);
+            }
+            RequestAuthDraft::Bearer(bearer) => apply(&mut bearer.token),
+            RequestAuthDraft::ApiKey(api_key) => {
+                apply(&mut api_key.name);
+                apply(&mut api_key.value);
+            }
+            RequestAuthDraft::Digest(digest) => {
+                apply(&mut digest.username);
+                apply(&mut digest.password);
+            }
+            RequestAuthDraft::OAuth2(oauth2) => {
+                for field in [
+                    &mut oauth2.token_url,
+                    &mut oauth2.client_id,
+                    &mut oauth2.client_secret,
+                    &mut oauth2.scope,
+                    &mut oauth2.username,
+                    &mut oauth2.password,
+                    &mut oauth2.authorization_url,
+                    &mut oauth2.redirect_uri,
+                ] {
+                    apply(field);
+                }
+            }
+            RequestAuthDraft::AwsSigV4(aws) => {
+                for field in [
+                    &mut aws.access_key_id,
+                    &mut aws.secret_access_key,
+                    &mut aws.session_token,
+                    &mut aws.region,
+                    &mut aws.service,
+                ] {
+                    apply(field);
+                }
+            }
+        }
+        let connection = &mut draft.settings.connection;
+        for field in [
+            &mut connection.client_certificate_password,
+            &mut connection.proxy_url,
+            &mut connection.proxy_username,
+            &mut connection.proxy_password,
+            &mut connection.proxy_bypass,
+        ] {
+            apply(field);
+        }
+        for assertion in draft
+            .assertions
+            .iter_mut()
+            .filter(|assertion| assertion.enabled)
+        {
+            apply(&mut assertion.target);
+            apply(&mut assertion.expected);
+        }
+        for extraction in draft
+            .extractions
+            .iter_mut()
+            .filter(|extraction| extraction.enabled)
+        {
+            apply(&mut extraction.expression);
+        }
+        if unknown.is_empty() {
+            Ok(draft)
+        } else {
+            Err(RequestCompileError::UnknownVariables {
+                names: unknown.into_iter().collect(),
+            })
+        }
+    }
+}
+
+/// Variable names start with a letter or `_` and continue with letters,
+/// digits, `_`, `-` or `.`.
+pub(crate) fn validate_variable_name(raw: &str) -> Result<(), RequestFieldError> {
+    let mut characters = raw.chars();
+    let Some(first) = characters.next() else {
+        return Err(RequestFieldError::Required);
+    };
+    if (first.is_ascii_alphabetic() || first == '_')
+        && characters.all(|character| {
+            character.is_ascii_alphanumeric() || matches!(character, '_' | '-' | '.')
+        })
+    {
+        Ok(())
+    } else {
+        Err(RequestFieldError::InvalidVariableName)
+    }
+}
+
+fn substitute(
+    text: &mut String,
+    values: &BTreeMap<String, String>,
+    unknown: &mut BTreeSet<String>,
+) {
+    if !text.contains("{{") {
+        return;
+    }
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            output.push_str("{{");
+            rest = after;
+            break;
+        };
+        let name = after[..end].trim();
+        if validate_variable_name(name).is_err() {
+            output.push_str("{{");
+            rest = after;
+            continue;
+        }
+        match values.get(name) {
+            Some(value) => output.push_str(value),
+            None => {
+                unknown.insert(name.to_owned());
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+    *text = output;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::request::draft::{
+        AssertionDraft, BearerAuthDraft, HeaderDraft, TextBodyDraft,
+    };
+
+    fn substituted(raw: &str) -> (String, Vec<String>) {
+        let values = BTreeMap::from([
+            ("token".to_owned(), "abc".to_owned()),
+            ("user.id".to_owned(), "42".to_owned()),
+            ("nested".to_owned(), "{{token}}".to_owned()),
+        ]);
+        let mut text = raw.to_owned();
+        let mut unknown = BTreeSet::new();
+        substitute(&mut text, &values, &mut unknown);
+        (text, unknown.into_iter().collect())
+    }
+
+    #[test]
+    fn placeholders_are_replaced_once_and_unknown_names_are_reported() {
+        let replaced = |raw| {
+            let (text, unknown) = substituted(raw);
+            assert!(unknown.is_empty(), "{raw}: {unknown:?}");
+            text
+        };
+        assert_eq!(replaced("Bearer {{token}}"), "Bearer abc");
+        assert_eq!(replaced("/users/{{ user.id }}/{{token}}"), "/users/42/abc");
+        assert_eq!(replaced("{{token"), "{{token");
+        assert_eq!(replaced("{{{{token}}"), "{{abc");
+        assert_eq!(replaced("{{nested}}"), "{{token}}");
+        assert_eq!(replaced("{{ not a name }}"), "{{ not a name }}");
+        assert_eq!(replaced("no placeholders"), "no placeholders");
+
+        assert_eq!(
+            substituted("{{missing}}/{{token}}/{{other}}/{{missing}}").1,
+            ["missing", "other"]
+        );
+    }
+
+    #[test]
+    fn variable_names_are_identifier_like() {
+        assert_eq!(validate_variable_name(""), Err(RequestFieldError::Required));
+        for valid in ["token", "_private", "user.id", "api-key2"] {
+            assert!(validate_variable_name(valid).is_ok(), "{valid}");
+        }
+        for invalid in ["1token", "has space", "{{token}}", "é"] {
+            assert_eq!(
+                validate_variable_name(invalid),
+                Err(RequestFieldError::InvalidVariableName),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn resolve_covers_sent_fields_and_shares_storage_between_clones() {
+        let variables = RequestVariables::default();
+        variables.clone().set("token".into(), "abc".into());
+        let mut draft = RequestDraft {
+            url: "https://example.test/{{token}}".into(),
+            headers: vec![HeaderDraft {
+                enabled: true,
+                name: "x-token".into(),
+                value: "{{token}}".into(),
+            }],
+            body: RequestBodyDraft::Text(TextBodyDraft {
+                content: "{\"token\":\"{{token}}\"}".into(),
+                ..TextBodyDraft::default()
+            }),
+            auth: RequestAuthDraft::Bearer(BearerAuthDraft {
+                token: "{{token}}".into(),
+            }),
+            assertions: vec![AssertionDraft {
+                expected: "{{token}}".into(),
+                ..AssertionDraft::default()
+            }],
+            ..RequestDraft::default()
+        };
+        draft.settings.connection.proxy_password = "{{token}}".into();
+
+        let resolved = variables.resolve(draft).unwrap();
+
+        assert_eq!(resolved.url, "https://example.test/abc");
+        assert_eq!(resolved.headers[0].value, "abc");
+        assert!(matches!(
+            resolved.body,
+            RequestBodyDraft::Text(TextBodyDraft { content, .. }) if content == "{\"token\":\"abc\"}"
+        ));
+        assert!(matches!(
+            resolved.auth,
+            RequestAuthDraft::Bearer(BearerAuthDraft { token }) if token == "abc"
+        ));
+        assert_eq!(resolved.assertions[0].expected, "abc");
+        assert_eq!(resolved.settings.connection.proxy_password, "abc");
+
+        variables.clear();
+        assert!(variables.entries().is_empty());
+    }
+
+    #[test]
+    fn resolve_names_every_unknown_variable_across_fields() {
+        let variables = RequestVariables::default();
+        variables.set("token".into(), "abc".into());
+        let draft = RequestDraft {
+            url: "https://example.test/{{user}}".into(),
+            headers: vec![
+                HeaderDraft {
+                    enabled: true,
+                    name: "x-token".into(),
+                    value: "{{token}} {{session}}".into(),
+                },
+                HeaderDraft {
+                    enabled: false,
+                    name: "x-draft".into(),
+                    value: "{{unused}}".into(),
+                },
+            ],
+            ..RequestDraft::default()
+        };
+
+        assert_eq!(
+            variables.resolve(draft).err(),
+            Some(RequestCompileError::UnknownVariables {
+                names: vec!["session".into(), "user".into()],
+            })
+        );
+    }
+}
@@ -0,0 +1,722 @@
+use bytes::Bytes;
+use fluent_bundle::FluentArgs;
+use gpui::{
+    App, AppContext as _, Context, Entity, IntoElement, ParentElement as _, Render, SharedString,
+    Styled as _, Subscription, Task, Window, div, prelude::FluentBuilder as _, px,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable as _, Sizable as _,
+    button::{Button, ButtonVariants as _},
+    checkbox::Checkbox,
+    input::{Input, InputState},
+    label::Label,
+    resizable::{resizable_panel, v_resizable},
+    scroll::ScrollableElement as _,
+    select::{Select, SelectEvent, SelectItem, SelectState},
+    table::{Table, TableBody, TableCell, TableHead, TableHeader, TableRow},
+    v_flex,
+};
+use time::UtcOffset;
+
+use self::{
+    compose::{ComposeError, MessageFormat, compose},
+    connect::{ConnectError, parse_connect},
+    log::{
+        Direction, DirectionFilter, LogEntry, LogFilter, LoggedFrame, MessageLog, format_timestamp,
+    },
+    session::{
+        SessionCommand, SessionEvent, SessionProblem, command_channel, event_channel, run_session,
+    },
+};
+use crate::foundation::I18n;
+
+mod compose;
+mod connect;
+mod log;
+mod session;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+enum SocketState {
+    #[default]
+    Disconnected,
+    Connecting,
+    Open {
+        protocol: Option<String>,
+    },
+    /// A close frame was sent and the peer's answer is awaited.
+    Closing,
+}
+
+/// The last thing that went wrong, shown under the connection line until
+/// the next attempt.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum SocketNotice {
+    Connect(ConnectError),
+    Compose(ComposeError),
+    Session(SessionProblem),
+}
+
+struct ActiveSession {
+    commands: async_channel::Sender<SessionCommand>,
+    _task: Task<()>,
+}
+
+#[derive(Clone)]
+struct FormatOption {
+    value: MessageFormat,
+    title: SharedString,
+}
+
+impl SelectItem for FormatOption {
+    type Value = MessageFormat;
+
+    fn title(&self) -> SharedString {
+        self.title.clone()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.value
+    }
+}
+
+type FormatOptions = Vec<FormatOption>;
+
+#[derive(Clone)]
+struct DirectionOption {
+    value: DirectionFilter,
+    title: SharedString,
+}
+
+impl SelectItem for DirectionOption {
+    type Value = DirectionFilter;
+
+    fn title(&self) -> SharedString {
+        self.title.clone()
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.value
+    }
+}
+
+type DirectionOptions = Vec<DirectionOption>;
+
+pub(crate) struct WebSocketView {
+    url: Entity<InputState>,
+    protocols: Entity<InputState>,
+    headers: Entity<InputState>,
+    composer: Entity<InputState>,
+    format: MessageFormat,
+    format_state: Entity<SelectState<FormatOptions>>,
+    query: Entity<InputState>,
+    direction_state: Entity<SelectState<DirectionOptions>>,
+    filter: LogFilter,
+    log: MessageLog,
+    state: SocketState,
+    notice: Option<SocketNotice>,
+    session: Option<ActiveSession>,
+    offset: UtcOffset,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl WebSocketView {
+    pub(crate) fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let (url_placeholder, protocols_placeholder, headers_placeholder, query_placeholder) = {
+            let i18n = cx.global::<I18n>();
+            (
+                i18n.t("websocket-url-placeholder"),
+                i18n.t("websocket-protocols-placeholder"),
+                i18n.t("websocket-headers-placeholder"),
+                i18n.t("websocket-filter-placeholder"),
+            )
+        };
+        let url = cx.new(|cx| InputState::new(window, cx).placeholder(url_placeholder));
+        let protocols = cx.new(|cx| InputState::new(window, cx).placeholder(protocols_placeholder));
+        let headers = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .placeholder(headers_placeholder)
+        });
+        let composer = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line(true)
+                .code_editor(format_editor_language(MessageFormat::default()))
+                .line_number(true)
+        });
+        let query = cx.new(|cx| InputState::new(window, cx).placeholder(query_placeholder));
+        let format_options = format_options(cx);
+        let format_state = cx.new(|cx| {
+            SelectState::new(
+                format_options,
+                Some(gpui_component::IndexPath::default().row(0)),
+                window,
+                cx,
+            )
+        });
+        let direction_options = direction_options(cx);
+        let direction_state = cx.new(|cx| {
+            SelectState::new(
+                direction_options,
+                Some(gpui_component::IndexPath::default().row(0)),
+                window,
+                cx,
+            )
+        });
+
+        let subscriptions = vec![
+            cx.subscribe_in(
+                &format_state,
+                window,
+                |this, _, event: &SelectEvent<FormatOptions>, _, cx| {
+                    let SelectEvent::Confirm(Some(format)) = event else {
+                        return;
+                    };
+                    this.format = *format;
+                    this.composer.update(cx, |state, cx| {
+                        state.set_highlighter(format_editor_language(*format), cx);
+                    });
+                    cx.notify();
+                },
+            ),
+            cx.subscribe_in(
+                &direction_state,
+                window,
+                |this, _, event: &SelectEvent<DirectionOptions>, _, cx| {
+                    let SelectEvent::Confirm(Some(direction)) = event else {
+                        return;
+                    };
+                    this.filter.direction = *direction;
+                    cx.notify();
+                },
+            ),
+            cx.observe(&query, |this, query, cx| {
+                this.filter.query = query.read(cx).value().to_string();
+                cx.notify();
+            }),
+        ];
+
+        Self {
+            url,
+            protocols,
+            headers,
+            composer,
+            format: MessageFormat::default(),
+            format_state,
+            query,
+            direction_state,
+            filter: LogFilter::default(),
+            log: MessageLog::default(),
+            state: SocketState::Disconnected,
+            notice: None,
+            session: None,
+            offset: UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn connect(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.session.is_some() {
+            return;
+        }
+        let request = parse_connect(
+            &self.url.read(cx).value(),
+            &self.headers.read(cx).value(),
+            &self.protocols.read(cx).value(),
+        );
+        let request = match request {
+            Ok(request) => request,
+            Err(error) => {
+                self.notice = Some(SocketNotice::Connect(error));
+                cx.notify();
+                return;
+            }
+        };
+
+        let (commands, command_receiver) = command_channel();
+        let (event_sender, events) = event_channel();
+        let owner = cx.entity().downgrade();
+        let task = window.spawn(cx, async move |cx| {
+            let worker =
+                gpui_tokio::Tokio::spawn(cx, run_session(request, command_receiver, event_sender));
+            let mut ended = false;
+            while let Ok(event) = events.recv().await {
+                ended = matches!(event, SessionEvent::Ended(_));
+                if owner
+                    .update_in(cx, |this, _, cx| this.handle_session_event(event, cx))
+                    .is_err()
+                {
+                    return;
+                }
+                if ended {
+                    break;
+                }
+            }
+            let worker_result = worker.await;
+            if !ended {
+                tracing::debug!(
+                    operation = "websocket-session",
+                    worker_join_failed = worker_result.is_err(),
+                    "session worker ended without a terminal event"
+                );
+                let _ = owner.update_in(cx, |this, _, cx| {
+                    this.handle_session_event(
+                        SessionEvent::Ended(Err(SessionProblem::Connection)),
+                        cx,
+                    );
+                });
+            }
+        });
+
+        self.session = Some(ActiveSession {
+            commands,
+            _task: task,
+        });
+        self.state = SocketState::Connecting;
+        self.notice = None;
+        self.log.clear();
+        cx.notify();
+    }
+
+    /// Starts the closing handshake on an open socket. A socket that is
+    /// still connecting, or whose peer never answers the close, is dropped.
+    fn disconnect(&mut self, cx: &mut Context<Self>) {
+        match self.state {
+            SocketState::Open { .. } => {
+                self.command(SessionCommand::Close);
+                self.state = SocketState::Closing;
+            }
+            SocketState::Connecting | SocketState::Closing => {
+                self.session = None;
+                self.state = SocketState::Disconnected;
+            }
+            SocketState::Disconnected => return,
+        }
+        cx.notify();
+    }
+
+    fn send(&mut self, cx: &mut Context<Self>) {
+        if !self.is_open() {
+            return;
+        }
+        match compose(self.format, &self.composer.read(cx).value()) {
+            Ok(frame) => {
+                self.notice = None;
+                self.command(SessionCommand::Send(frame));
+            }
+            Err(error) => self.notice = Some(SocketNotice::Compose(error)),
+        }
+        cx.notify();
+    }
+
+    fn ping(&mut self, cx: &mut Context<Self>) {
+        if self.is_open() {
+            self.command(SessionCommand::Ping(Bytes::new()));
+            cx.notify();
+        }
+    }
+
+    fn clear_log(&mut self, cx: &mut Context<Self>) {
+        self.log.clear();
+        cx.notify();
+    }
+
+    fn command(&self, command: SessionCommand) {
+        if let Some(session) = &self.session {
+            // An unbounded channel only refuses once the session has ended,
+            // and that end arrives as its own event.
+            let _ = session.commands.try_send(command);
+        }
+    }
+
+    fn handle_session_event(&mut self, event: SessionEvent, cx: &mut Context<Self>) {
+        match event {
+            SessionEvent::Opened { protocol } => {
+                self.state = SocketState::Open { protocol };
+            }
+            SessionEvent::Logged(entry) => self.log.push(entry),
+            SessionEvent::Ended(result) => {
+                self.session = None;
+                self.state = SocketState::Disconnected;
+                if let Err(problem) = result {
+                    self.notice = Some(SocketNotice::Session(problem));
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    const fn is_open(&self) -> bool {
+        matches!(self.state, SocketState::Open { .. })
+    }
+
+    fn render_connection(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let i18n = cx.global::<I18n>();
+        let status = match &self.state {
+            SocketState::Disconnected => i18n.t("websocket-state-disconnected"),
+            SocketState::Connecting => i18n.t("websocket-state-connecting"),
+            SocketState::Open { protocol: None } => i18n.t("websocket-state-open"),
+            SocketState::Open {
+                protocol: Some(protocol),
+            } => {
+                let mut args = FluentArgs::new();
+                args.set("protocol", protocol.clone());
+                i18n.t_with_args("websocket-state-open-protocol", &args)
+            }
+            SocketState::Closing => i18n.t("websocket-state-closing"),
+        };
+        let notice = self.notice.map(|notice| notice_message(notice, cx));
+        let connect_button = if self.state == SocketState::Disconnected {
+            Button::new("websocket-connect")
+                .primary()
+                .label(cx.global::<I18n>().t("button-connect"))
+                .on_click(cx.listener(|this, _, window, cx| this.connect(window, cx)))
+        } else {
+            Button::new("websocket-disconnect")
+                .danger()
+                .label(cx.global::<I18n>().t("button-disconnect"))
+                .on_click(cx.listener(|this, _, _, cx| this.disconnect(cx)))
+        };
+        let editable = self.state == SocketState::Disconnected;
+
+        v_flex()
+            .p_2()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_1()
+                            .child(Input::new(&self.url).disabled(!editable)),
+                    )
+                    .child(
+                        div()
+                            .w(px(220.))
+                            .child(Input::new(&self.protocols).disabled(!editable)),
+                    )
+                    .child(connect_button),
+            )
+            .child(
+                div()
+                    .h(px(72.))
+                    .child(Input::new(&self.headers).h_full().disabled(!editable)),
+            )
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        Label::new(status)
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground),
+                    )
+                    .when_some(notice, |this, notice| {
+                        this.child(Label::new(notice).text_sm().text_color(cx.theme().danger))
+                    }),
+            )
+    }
+
+    fn render_composer(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let (send_label, ping_label) = {
+            let i18n = cx.global::<I18n>();
+            (i18n.t("button-send"), i18n.t("button-ping"))
+        };
+        let open = self.is_open();
+        v_flex()
+            .flex_1()
+            .min_h(px(0.))
+            .p_2()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(div().w(px(180.)).child(Select::new(&self.format_state)))
+                    .child(div().flex_1())
+                    .child(
+                        Button::new("websocket-ping")
+                            .label(ping_label)
+                            .disabled(!open)
+                            .on_click(cx.listener(|this, _, _, cx| this.ping(cx))),
+                    )
+                    .child(
+                        Button::new("websocket-send")
+                            .primary()
+                            .label(send_label)
+                            .disabled(!open)
+                            .on_click(cx.listener(|this, _, _, cx| this.send(cx))),
+                    ),
+            )
+            .child(
+                div().flex_1().min_h(px(0.)).overflow_hidden().child(
+                    Input::new(&self.composer)
+                        .size_full()
+                        .font_family(cx.theme().mono_font_family.clone()),
+                ),
+            )
+    }
+
+    fn render_log(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let (hide_control_label, clear_label) = {
+            let i18n = cx.global::<I18n>();
+            (
+                i18n.t("websocket-filter-hide-control"),
+                i18n.t("button-clear-log"),
+            )
+        };
+        let toolbar = div()
+            .flex()
+            .flex_shrink_0()
+            .items_center()
+            .gap_2()
+            .p_2()
+            .child(div().flex_1().child(Input::new(&self.query)))
+            .child(div().w(px(140.)).child(Select::new(&self.direction_state)))
+            .child(
+                Checkbox::new("websocket-hide-control")
+                    .label(hide_control_label)
+                    .checked(self.filter.hide_control)
+                    .on_click(cx.listener(|this, checked, _, cx| {
+                        this.filter.hide_control = *checked;
+                        cx.notify();
+                    })),
+            )
+            .child(
+                Button::new("websocket-clear-log")
+                    .label(clear_label)
+                    .disabled(self.log.is_empty())
+                    .on_click(cx.listener(|this, _, _, cx| this.clear_log(cx))),
+            );
+
+        let cx: &App = cx;
+        let i18n = cx.global::<I18n>();
+        let rows = self
+            .log
+            .filtered(&self.filter)
+            .map(|entry| log_row(entry, self.offset, cx))
+            .collect::<Vec<_>>();
+        let body =
+            if rows.is_empty() {
+                let key = if self.log.is_empty() {
+                    "websocket-log-empty"
+                } else {
+                    "websocket-log-no-match"
+                };
+                div()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .child(Label::new(i18n.t(key)))
+                    .into_any_element()
+            } else {
+                let dropped = (self.log.dropped() > 0).then(|| {
+                    let mut args = FluentArgs::new();
+                    args.set("count", self.log.dropped());
+                    i18n.t_with_args("websocket-log-dropped", &args)
+                });
+                div()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .overflow_scrollbar()
+                    .child(
+                        v_flex()
+                            .p_2()
+                            .gap_2()
+                            .when_some(dropped, |this, dropped| {
+                                this.child(
+                                    Label::new(dropped)
+                                        .text_xs()
+                                        .text_color(cx.theme().muted_foreground),
+                                )
+                            })
+                            .child(
+                                Table::new()
+                                    .small()
+                                    .child(
+                                        TableHeader::new().child(
+                                            TableRow::new()
+                                                .child(TableHead::new().w(px(110.)).child(
+                                                    Label::new(i18n.t("websocket-log-time")),
+                                                ))
+                                                .child(TableHead::new().w(px(90.)).child(
+                                                    Label::new(i18n.t("websocket-log-direction")),
+                                                ))
+                                                .child(TableHead::new().w(px(80.)).child(
+                                                    Label::new(i18n.t("websocket-log-kind")),
+                                                ))
+                                                .child(TableHead::new().child(Label::new(
+                                                    i18n.t("websocket-log-payload"),
+                                                ))),
+                                        ),
+                                    )
+                                    .child(TableBody::new().children(rows)),
+                            ),
+                    )
+                    .into_any_element()
+            };
+
+        v_flex()
+            .size_full()
+            .min_h(px(0.))
+            .overflow_hidden()
+            .child(toolbar)
+            .child(body)
+    }
+}
+
+impl Render for WebSocketView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let connection = self.render_connection(cx);
+        let composer = self.render_composer(cx);
+        let log = self.render_log(cx);
+        v_flex()
+            .size_full()
+            .overflow_hidden()
+            .child(connection)
+            .child(
+                v_resizable("websocket-composer-log")
+                    .child(
+                        resizable_panel()
+                            .size(px(200.))
+                            .size_range(px(120.)..px(480.))
+                            .child(composer),
+                    )
+                    .child(resizable_panel().child(log)),
+            )
+    }
+}
+
+fn log_row(entry: &LogEntry, offset: UtcOffset, cx: &App) -> TableRow {
+    let i18n = cx.global::<I18n>();
+    let direction = match entry.direction {
+        Direction::Sent => i18n.t("websocket-direction-sent"),
+        Direction::Received => i18n.t("websocket-direction-received"),
+    };
+    let kind = i18n.t(frame_kind_key(&entry.frame));
+    let payload = match &entry.frame {
+        LoggedFrame::Close(Some(info)) => {
+            let mut args = FluentArgs::new();
+            args.set("code", info.code);
+            args.set("reason", info.reason.clone());
+            i18n.t_with_args("websocket-close-detail", &args)
+        }
+        LoggedFrame::Close(None) => i18n.t("websocket-close-no-status"),
+        frame => frame.preview(),
+    };
+    let color = if entry.frame.is_control() {
+        cx.theme().muted_foreground
+    } else {
+        cx.theme().foreground
+    };
+    TableRow::new()
+        .child(
+            TableCell::new()
+                .w(px(110.))
+                .child(Label::new(format_timestamp(entry.at, offset)).text_xs()),
+        )
+        .child(TableCell::new().w(px(90.)).child(Label::new(direction)))
+        .child(TableCell::new().w(px(80.)).child(Label::new(kind)))
+        .child(
+            TableCell::new().child(
+                Label::new(payload)
+                    .text_color(color)
+                    .font_family(cx.theme().mono_font_family.clone()),
+            ),
+        )
+}
+
+fn notice_message(notice: SocketNotice, cx: &App) -> String {
+    let i18n = cx.global::<I18n>();
+    let mut args = FluentArgs::new();
+    let key = match notice {
+        SocketNotice::Connect(ConnectError::UrlRequired) => "websocket-url-required",
+        SocketNotice::Connect(ConnectError::InvalidUrl) => "websocket-url-invalid",
+        SocketNotice::Connect(ConnectError::UnsupportedScheme) => "websocket-url-scheme",
+        SocketNotice::Connect(ConnectError::InvalidHeader { line }) => {
+            args.set("line", line);
+            "websocket-header-invalid"
+        }
+        SocketNotice::Connect(ConnectError::ReservedHeader { line }) => {
+            args.set("line", line);
+            "websocket-header-reserved"
+        }
+        SocketNotice::Connect(ConnectError::InvalidProtocol) => "websocket-protocol-invalid",
+        SocketNotice::Compose(ComposeError::InvalidJson { line, column }) => {
+            args.set("line", line);
+            args.set("column", column);
+            "websocket-message-json-invalid"
+        }
+        SocketNotice::Compose(ComposeError::InvalidHex) => "websocket-message-hex-invalid",
+        SocketNotice::Session(SessionProblem::Connect) => "websocket-problem-connect",
+        SocketNotice::Session(SessionProblem::Tls) => "websocket-problem-tls",
+        SocketNotice::Session(SessionProblem::Rejected(status)) => {
+            args.set("status", status);
+            "websocket-problem-rejected"
+        }
+        SocketNotice::Session(SessionProblem::Handshake) => "websocket-problem-handshake",
+        SocketNotice::Session(SessionProblem::Protocol) => "websocket-problem-protocol",
+        SocketNotice::Session(SessionProblem::Connection) => "websocket-problem-connection",
+    };
+    i18n.t_with_args(key, &args)
+}
+
+const fn frame_kind_key(frame: &LoggedFrame) -> &'static str {
+    match frame {
+        LoggedFrame::Text(_) => "websocket-kind-text",
+        LoggedFrame::Binary(_) => "websocket-kind-binary",
+        LoggedFrame::Ping(_) => "websocket-kind-ping",
+        LoggedFrame::Pong(_) => "websocket-kind-pong",
+        LoggedFrame::Close(_) => "websocket-kind-close",
+    }
+}
+
+const fn format_editor_language(format: MessageFormat) -> &'static str {
+    match format {
+        MessageFormat::Json => "json",
+        MessageFormat::Text | MessageFormat::Binary => "plaintext",
+    }
+}
+
+const fn format_i18n_key(format: MessageFormat) -> &'static str {
+    match format {
+        MessageFormat::Text => "websocket-format-text",
+        MessageFormat::Json => "websocket-format-json",
+        MessageFormat::Binary => "websocket-format-binary",
+    }
+}
+
+const fn direction_i18n_key(direction: DirectionFilter) -> &'static str {
+    match direction {
+        DirectionFilter::All => "websocket-direction-all",
+        DirectionFilter::Sent => "websocket-direction-sent",
+        DirectionFilter::Received => "websocket-direction-received",
+    }
+}
+
+fn format_options(cx: &App) -> FormatOptions {
+    let i18n = cx.global::<I18n>();
+    MessageFormat::ALL
+        .into_iter()
+        .map(|value| FormatOption {
+            value,
+            title: i18n.t(format_i18n_key(value)).into(),
+        })
+        .collect()
+}
+
+fn direction_options(cx: &App) -> DirectionOptions {
+    let i18n = cx.global::<I18n>();
+    DirectionFilter::ALL
+        .into_iter()
+        .map(|value| DirectionOption {
+            value,
+            title: i18n.t(direction_i18n_key(value)).into(),
+        })
+        .collect()
+}
@@ -0,0 +1,114 @@
+use bytes::Bytes;
+use thiserror::Error;
+
+/// How the composer text becomes a frame.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum MessageFormat {
+    #[default]
+    Text,
+    /// Validated JSON, sent as a text frame exactly as written.
+    Json,
+    /// Hexadecimal bytes; whitespace between digits is ignored.
+    Binary,
+}
+
+impl MessageFormat {
+    pub(crate) const ALL: [Self; 3] = [Self::Text, Self::Json, Self::Binary];
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum OutgoingFrame {
+    Text(String),
+    Binary(Bytes),
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub(crate) enum ComposeError {
+    #[error("message is not valid JSON (line {line}, column {column})")]
+    InvalidJson { line: usize, column: usize },
+    #[error("binary message must be pairs of hexadecimal digits")]
+    InvalidHex,
+}
+
+pub(crate) fn compose(format: MessageFormat, raw: &str) -> Result<OutgoingFrame, ComposeError> {
+    match format {
+        MessageFormat::Text => Ok(OutgoingFrame::Text(raw.to_owned())),
+        MessageFormat::Json => {
+            serde_json::from_str::<serde_json::Value>(raw).map_err(|error| {
+                ComposeError::InvalidJson {
+                    line: error.line(),
+                    column: error.column(),
+                }
+            })?;
+            Ok(OutgoingFrame::Text(raw.to_owned()))
+        }
+        MessageFormat::Binary => parse_hex(raw).map(OutgoingFrame::Binary),
+    }
+}
+
+fn parse_hex(raw: &str) -> Result<Bytes, ComposeError> {
+    let digits = raw
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .map(|byte| {
+            char::from(byte)
+                .to_digit(16)
+                .map(|digit| digit as u8)
+                .ok_or(ComposeError::InvalidHex)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if digits.len() % 2 != 0 {
+        return Err(ComposeError::InvalidHex);
+    }
+    Ok(digits
+        .chunks_exact(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect::<Vec<_>>()
+        .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_is_validated_but_sent_as_written() {
+        let raw = "{ \"type\": \"subscribe\" }";
+        assert_eq!(
+            compose(MessageFormat::Json, raw),
+            Ok(OutgoingFrame::Text(raw.to_owned()))
+        );
+        assert_eq!(
+            compose(MessageFormat::Json, "{\n  \"open\": "),
+            Err(ComposeError::InvalidJson {
+                line: 2,
+                column: 10
+            })
+        );
+        assert_eq!(
+            compose(MessageFormat::Text, "{ not json"),
+            Ok(OutgoingFrame::Text("{ not json".to_owned()))
+        );
+    }
+
+    #[test]
+    fn binary_messages_are_hex_pairs() {
+        assert_eq!(
+            compose(MessageFormat::Binary, "00 ff\n0A"),
+            Ok(OutgoingFrame::Binary(Bytes::from_static(&[
+                0x00, 0xff, 0x0a
+            ])))
+        );
+        assert_eq!(
+            compose(MessageFormat::Binary, ""),
+            Ok(OutgoingFrame::Binary(Bytes::new()))
+        );
+        for invalid in ["abc", "zz", "0x10"] {
+            assert_eq!(
+                compose(MessageFormat::Binary, invalid),
+                Err(ComposeError::InvalidHex),
+                "{invalid}"
+            );
+        }
+    }
+}
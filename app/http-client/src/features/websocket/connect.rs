@@ -0,0 +1,190 @@
+use http::{
+    HeaderMap, HeaderName, HeaderValue,
+    header::{
+        CONNECTION, HOST, SEC_WEBSOCKET_ACCEPT, SEC_WEBSOCKET_EXTENSIONS, SEC_WEBSOCKET_KEY,
+        SEC_WEBSOCKET_PROTOCOL, SEC_WEBSOCKET_VERSION, UPGRADE,
+    },
+};
+use thiserror::Error;
+use url::Url;
+
+/// Headers the handshake owns. Subprotocols have their own field.
+static RESERVED_HEADERS: [HeaderName; 8] = [
+    CONNECTION,
+    HOST,
+    SEC_WEBSOCKET_ACCEPT,
+    SEC_WEBSOCKET_EXTENSIONS,
+    SEC_WEBSOCKET_KEY,
+    SEC_WEBSOCKET_PROTOCOL,
+    SEC_WEBSOCKET_VERSION,
+    UPGRADE,
+];
+
+/// A validated handshake, frozen when Connect is pressed.
+#[derive(Clone, Debug)]
+pub(crate) struct ConnectRequest {
+    pub(crate) url: Url,
+    pub(crate) headers: HeaderMap,
+    pub(crate) protocols: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub(crate) enum ConnectError {
+    #[error("socket URL is required")]
+    UrlRequired,
+    #[error("socket URL is invalid")]
+    InvalidUrl,
+    #[error("socket URL must use ws or wss")]
+    UnsupportedScheme,
+    #[error("handshake header on line {line} is invalid")]
+    InvalidHeader { line: usize },
+    #[error("handshake header on line {line} is managed by the connection")]
+    ReservedHeader { line: usize },
+    #[error("subprotocol list is invalid")]
+    InvalidProtocol,
+}
+
+pub(crate) fn parse_connect(
+    url: &str,
+    headers: &str,
+    protocols: &str,
+) -> Result<ConnectRequest, ConnectError> {
+    Ok(ConnectRequest {
+        url: parse_socket_url(url)?,
+        headers: parse_header_lines(headers)?,
+        protocols: parse_protocols(protocols)?,
+    })
+}
+
+fn parse_socket_url(raw: &str) -> Result<Url, ConnectError> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err(ConnectError::UrlRequired);
+    }
+    let mut url = Url::parse(raw).map_err(|_| ConnectError::InvalidUrl)?;
+    if !matches!(url.scheme(), "ws" | "wss") {
+        return Err(ConnectError::UnsupportedScheme);
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(ConnectError::InvalidUrl);
+    }
+    url.set_fragment(None);
+    Ok(url)
+}
+
+/// Reads one `Name: value` header per line. Blank lines are skipped and
+/// repeated names are sent as repeated headers.
+fn parse_header_lines(raw: &str) -> Result<HeaderMap, ConnectError> {
+    let mut headers = HeaderMap::new();
+    for (index, line) in raw.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = || ConnectError::InvalidHeader { line: line_number };
+        let (name, value) = line.split_once(':').ok_or_else(invalid)?;
+        let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| invalid())?;
+        if RESERVED_HEADERS.contains(&name) {
+            return Err(ConnectError::ReservedHeader { line: line_number });
+        }
+        let value = HeaderValue::from_str(value.trim()).map_err(|_| invalid())?;
+        headers.append(name, value);
+    }
+    Ok(headers)
+}
+
+fn parse_protocols(raw: &str) -> Result<Vec<String>, ConnectError> {
+    let mut protocols: Vec<String> = Vec::new();
+    for protocol in raw.split(',').map(str::trim) {
+        if protocol.is_empty() {
+            continue;
+        }
+        if !protocol.bytes().all(is_token_byte) {
+            return Err(ConnectError::InvalidProtocol);
+        }
+        if !protocols.iter().any(|existing| existing == protocol) {
+            protocols.push(protocol.to_owned());
+        }
+    }
+    Ok(protocols)
+}
+
+/// RFC 9110 `tchar`.
+const fn is_token_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric()
+        || matches!(
+            byte,
+            b'!' | b'#'
+                | b'$'
+                | b'%'
+                | b'&'
+                | b'\''
+                | b'*'
+                | b'+'
+                | b'-'
+                | b'.'
+                | b'^'
+                | b'_'
+                | b'`'
+                | b'|'
+                | b'~'
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn socket_urls_require_a_websocket_scheme_and_host() {
+        assert_eq!(parse_socket_url(" "), Err(ConnectError::UrlRequired));
+        assert_eq!(
+            parse_socket_url("https://example.test/socket"),
+            Err(ConnectError::UnsupportedScheme)
+        );
+        assert_eq!(
+            parse_socket_url("ws://:80/socket"),
+            Err(ConnectError::InvalidUrl)
+        );
+        assert_eq!(
+            parse_socket_url(" wss://example.test/socket?room=1#local ")
+                .unwrap()
+                .as_str(),
+            "wss://example.test/socket?room=1"
+        );
+    }
+
+    #[test]
+    fn header_lines_report_the_offending_line() {
+        let headers =
+            parse_header_lines("Authorization: Bearer abc\n\nX-Tag: one\nx-tag: two").unwrap();
+        assert_eq!(headers["authorization"], "Bearer abc");
+        assert_eq!(headers.get_all("x-tag").iter().count(), 2);
+
+        assert_eq!(
+            parse_header_lines("X-Ok: 1\nmissing colon"),
+            Err(ConnectError::InvalidHeader { line: 2 })
+        );
+        assert_eq!(
+            parse_header_lines("bad name: 1"),
+            Err(ConnectError::InvalidHeader { line: 1 })
+        );
+        assert_eq!(
+            parse_header_lines("X-Ok: 1\nSec-WebSocket-Key: abc"),
+            Err(ConnectError::ReservedHeader { line: 2 })
+        );
+    }
+
+    #[test]
+    fn subprotocols_are_deduplicated_tokens() {
+        assert_eq!(
+            parse_protocols("graphql-ws, chat.v2,,graphql-ws").unwrap(),
+            ["graphql-ws", "chat.v2"]
+        );
+        assert!(parse_protocols("").unwrap().is_empty());
+        assert_eq!(
+            parse_protocols("chat v2"),
+            Err(ConnectError::InvalidProtocol)
+        );
+    }
+}
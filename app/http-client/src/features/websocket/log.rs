@@ -0,0 +1,274 @@
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+use time::{OffsetDateTime, UtcOffset};
+
+/// Older entries are dropped once a session has logged this many frames.
+const LOG_CAPACITY: usize = 2_000;
+/// Binary payloads are previewed as hex up to this many bytes.
+const BINARY_PREVIEW_BYTES: usize = 64;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Direction {
+    Sent,
+    Received,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct CloseInfo {
+    pub(crate) code: u16,
+    pub(crate) reason: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum LoggedFrame {
+    Text(String),
+    Binary(Bytes),
+    Ping(Bytes),
+    Pong(Bytes),
+    /// A close frame; `None` when the peer sent no status code.
+    Close(Option<CloseInfo>),
+}
+
+impl LoggedFrame {
+    pub(crate) const fn is_control(&self) -> bool {
+        matches!(self, Self::Ping(_) | Self::Pong(_) | Self::Close(_))
+    }
+
+    /// The payload as shown in the log and searched by the filter.
+    pub(crate) fn preview(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Binary(bytes) | Self::Ping(bytes) | Self::Pong(bytes) => hex_preview(bytes),
+            Self::Close(Some(info)) => info.reason.clone(),
+            Self::Close(None) => String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct LogEntry {
+    pub(crate) at: OffsetDateTime,
+    pub(crate) direction: Direction,
+    pub(crate) frame: LoggedFrame,
+}
+
+impl LogEntry {
+    pub(crate) fn now(direction: Direction, frame: LoggedFrame) -> Self {
+        Self {
+            at: OffsetDateTime::now_utc(),
+            direction,
+            frame,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum DirectionFilter {
+    #[default]
+    All,
+    Sent,
+    Received,
+}
+
+impl DirectionFilter {
+    pub(crate) const ALL: [Self; 3] = [Self::All, Self::Sent, Self::Received];
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LogFilter {
+    pub(crate) direction: DirectionFilter,
+    pub(crate) hide_control: bool,
+    /// Case-insensitive text the payload must contain.
+    pub(crate) query: String,
+}
+
+impl LogFilter {
+    pub(crate) fn matches(&self, entry: &LogEntry) -> bool {
+        let direction = match self.direction {
+            DirectionFilter::All => true,
+            DirectionFilter::Sent => entry.direction == Direction::Sent,
+            DirectionFilter::Received => entry.direction == Direction::Received,
+        };
+        if !direction || (self.hide_control && entry.frame.is_control()) {
+            return false;
+        }
+        let query = self.query.trim();
+        if query.is_empty() {
+            return true;
+        }
+        let query = query.to_lowercase();
+        let mut haystack = entry.frame.preview().to_lowercase();
+        if let LoggedFrame::Close(Some(info)) = &entry.frame {
+            haystack.push(' ');
+            haystack.push_str(&info.code.to_string());
+        }
+        haystack.contains(&query)
+    }
+}
+
+/// The frames of the current session, newest last.
+#[derive(Default)]
+pub(crate) struct MessageLog {
+    entries: VecDeque<LogEntry>,
+    dropped: usize,
+}
+
+impl MessageLog {
+    pub(crate) fn push(&mut self, entry: LogEntry) {
+        if self.entries.len() == LOG_CAPACITY {
+            self.entries.pop_front();
+            self.dropped += 1;
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.dropped = 0;
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// How many old entries were discarded to stay within the capacity.
+    pub(crate) const fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    pub(crate) fn filtered<'a>(
+        &'a self,
+        filter: &'a LogFilter,
+    ) -> impl Iterator<Item = &'a LogEntry> + 'a {
+        self.entries.iter().filter(|entry| filter.matches(entry))
+    }
+}
+
+/// `HH:MM:SS.mmm` in the given offset.
+pub(crate) fn format_timestamp(at: OffsetDateTime, offset: UtcOffset) -> String {
+    let local = at.to_offset(offset);
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        local.hour(),
+        local.minute(),
+        local.second(),
+        local.millisecond()
+    )
+}
+
+fn hex_preview(bytes: &[u8]) -> String {
+    let mut preview = bytes
+        .iter()
+        .take(BINARY_PREVIEW_BYTES)
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if bytes.len() > BINARY_PREVIEW_BYTES {
+        preview.push_str(" …");
+    }
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 01:04:05.678 UTC on the epoch day.
+    fn instant() -> OffsetDateTime {
+        OffsetDateTime::UNIX_EPOCH + time::Duration::milliseconds(3_845_678)
+    }
+
+    fn entry(direction: Direction, frame: LoggedFrame) -> LogEntry {
+        LogEntry {
+            at: instant(),
+            direction,
+            frame,
+        }
+    }
+
+    #[test]
+    fn filters_combine_direction_control_frames_and_text() {
+        let entries = [
+            entry(Direction::Sent, LoggedFrame::Text("Hello World".into())),
+            entry(
+                Direction::Received,
+                LoggedFrame::Binary(Bytes::from_static(&[0xab, 0x01])),
+            ),
+            entry(Direction::Received, LoggedFrame::Ping(Bytes::new())),
+            entry(
+                Direction::Received,
+                LoggedFrame::Close(Some(CloseInfo {
+                    code: 4001,
+                    reason: "done".into(),
+                })),
+            ),
+        ];
+        let matching = |filter: &LogFilter| {
+            entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| filter.matches(entry))
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matching(&LogFilter::default()), [0, 1, 2, 3]);
+        assert_eq!(
+            matching(&LogFilter {
+                direction: DirectionFilter::Received,
+                hide_control: true,
+                ..LogFilter::default()
+            }),
+            [1]
+        );
+        assert_eq!(
+            matching(&LogFilter {
+                query: " world ".into(),
+                ..LogFilter::default()
+            }),
+            [0]
+        );
+        assert_eq!(
+            matching(&LogFilter {
+                query: "AB 01".into(),
+                ..LogFilter::default()
+            }),
+            [1]
+        );
+        assert_eq!(
+            matching(&LogFilter {
+                query: "4001".into(),
+                ..LogFilter::default()
+            }),
+            [3]
+        );
+    }
+
+    #[test]
+    fn log_keeps_the_newest_entries_within_capacity() {
+        let mut log = MessageLog::default();
+        for index in 0..LOG_CAPACITY + 2 {
+            log.push(entry(Direction::Sent, LoggedFrame::Text(index.to_string())));
+        }
+        let filter = LogFilter::default();
+        let first = log.filtered(&filter).next().unwrap();
+        assert_eq!(first.frame, LoggedFrame::Text("2".into()));
+        assert_eq!(log.dropped(), 2);
+
+        log.clear();
+        assert!(log.is_empty());
+        assert_eq!(log.dropped(), 0);
+    }
+
+    #[test]
+    fn previews_and_timestamps_are_compact() {
+        let long = LoggedFrame::Binary(Bytes::from(vec![0; BINARY_PREVIEW_BYTES + 1]));
+        assert!(long.preview().ends_with("00 …"));
+        assert_eq!(LoggedFrame::Close(None).preview(), "");
+        assert_eq!(
+            format_timestamp(instant(), UtcOffset::from_hms(8, 0, 0).unwrap()),
+            "09:04:05.678"
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use async_channel::{Receiver, Sender};
+use bytes::Bytes;
+use futures_util::{SinkExt as _, StreamExt as _};
+use http::{HeaderValue, header::SEC_WEBSOCKET_PROTOCOL};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{
+        self, Message,
+        client::IntoClientRequest as _,
+        protocol::{CloseFrame, frame::coding::CloseCode},
+    },
+};
+
+use super::{
+    compose::OutgoingFrame,
+    connect::ConnectRequest,
+    log::{CloseInfo, Direction, LogEntry, LoggedFrame},
+};
+
+const SESSION_EVENT_CAPACITY: usize = 64;
+
+pub(crate) enum SessionCommand {
+    Send(OutgoingFrame),
+    Ping(Bytes),
+    /// Starts the closing handshake with a normal (1000) close.
+    Close,
+}
+
+pub(crate) enum SessionEvent {
+    Opened { protocol: Option<String> },
+    Logged(LogEntry),
+    Ended(Result<(), SessionProblem>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum SessionProblem {
+    /// The server could not be reached.
+    Connect,
+    Tls,
+    /// The server answered the upgrade with another HTTP status.
+    Rejected(u16),
+    /// The upgrade response was not a valid WebSocket handshake.
+    Handshake,
+    /// The open connection broke the WebSocket protocol.
+    Protocol,
+    /// The open connection failed before a closing handshake.
+    Connection,
+}
+
+pub(crate) fn command_channel() -> (Sender<SessionCommand>, Receiver<SessionCommand>) {
+    async_channel::unbounded()
+}
+
+pub(crate) fn event_channel() -> (Sender<SessionEvent>, Receiver<SessionEvent>) {
+    async_channel::bounded(SESSION_EVENT_CAPACITY)
+}
+
+/// Runs one socket until it closes, fails or its command sender is dropped,
+/// then emits exactly one `Ended` event unless its receiver has been dropped.
+pub(crate) async fn run_session(
+    request: ConnectRequest,
+    commands: Receiver<SessionCommand>,
+    events: Sender<SessionEvent>,
+) {
+    let result = session(request, &commands, &events).await;
+    let _ = events.send(SessionEvent::Ended(result)).await;
+}
+
+async fn session(
+    request: ConnectRequest,
+    commands: &Receiver<SessionCommand>,
+    events: &Sender<SessionEvent>,
+) -> Result<(), SessionProblem> {
+    let mut handshake = request
+        .url
+        .as_str()
+        .into_client_request()
+        .map_err(|_| SessionProblem::Handshake)?;
+    for (name, value) in &request.headers {
+        handshake.headers_mut().append(name, value.clone());
+    }
+    if !request.protocols.is_empty() {
+        let protocols = HeaderValue::from_str(&request.protocols.join(", "))
+            .map_err(|_| SessionProblem::Handshake)?;
+        handshake
+            .headers_mut()
+            .insert(SEC_WEBSOCKET_PROTOCOL, protocols);
+    }
+
+    let (mut socket, response) = connect_async(handshake).await.map_err(handshake_problem)?;
+    let protocol = response
+        .headers()
+        .get(SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    if events
+        .send(SessionEvent::Opened { protocol })
+        .await
+        .is_err()
+    {
+        return Ok(());
+    }
+
+    let mut closing = false;
+    loop {
+        tokio::select! {
+            command = commands.recv(), if !closing => {
+                let Ok(command) = command else {
+                    // The page is gone; nobody is left to watch the handshake.
+                    let _ = socket.close(None).await;
+                    return Ok(());
+                };
+                let (message, frame) = outgoing(command);
+                closing = matches!(frame, LoggedFrame::Close(_));
+                socket.send(message).await.map_err(connection_problem)?;
+                if !log(events, Direction::Sent, frame).await {
+                    return Ok(());
+                }
+            }
+            message = socket.next() => {
+                let message = match message {
+                    Some(Ok(message)) => message,
+                    None
+                    | Some(Err(
+                        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed,
+                    )) => return Ok(()),
+                    Some(Err(error)) => return Err(connection_problem(error)),
+                };
+                let Some(frame) = incoming(message) else {
+                    continue;
+                };
+                // The protocol layer answers pings and a peer-initiated close
+                // with the same payload; log those replies as sent frames.
+                let reply = match &frame {
+                    LoggedFrame::Ping(payload) => Some(LoggedFrame::Pong(payload.clone())),
+                    LoggedFrame::Close(info) if !closing => Some(LoggedFrame::Close(info.clone())),
+                    LoggedFrame::Text(_)
+                    | LoggedFrame::Binary(_)
+                    | LoggedFrame::Pong(_)
+                    | LoggedFrame::Close(_) => None,
+                };
+                closing |= matches!(frame, LoggedFrame::Close(_));
+                if !log(events, Direction::Received, frame).await {
+                    return Ok(());
+                }
+                if let Some(reply) = reply
+                    && !log(events, Direction::Sent, reply).await
+                {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn outgoing(command: SessionCommand) -> (Message, LoggedFrame) {
+    match command {
+        SessionCommand::Send(OutgoingFrame::Text(text)) => {
+            (Message::text(text.clone()), LoggedFrame::Text(text))
+        }
+        SessionCommand::Send(OutgoingFrame::Binary(bytes)) => {
+            (Message::Binary(bytes.clone()), LoggedFrame::Binary(bytes))
+        }
+        SessionCommand::Ping(payload) => {
+            (Message::Ping(payload.clone()), LoggedFrame::Ping(payload))
+        }
+        SessionCommand::Close => (
+            Message::Close(Some(CloseFrame {
+                code: CloseCode::Normal,
+                reason: "".into(),
+            })),
+            LoggedFrame::Close(Some(CloseInfo {
+                code: CloseCode::Normal.into(),
+                reason: String::new(),
+            })),
+        ),
+    }
+}
+
+fn incoming(message: Message) -> Option<LoggedFrame> {
+    Some(match message {
+        Message::Text(text) => LoggedFrame::Text(text.as_str().to_owned()),
+        Message::Binary(bytes) => LoggedFrame::Binary(bytes),
+        Message::Ping(payload) => LoggedFrame::Ping(payload),
+        Message::Pong(payload) => LoggedFrame::Pong(payload),
+        Message::Close(frame) => LoggedFrame::Close(frame.map(|frame| CloseInfo {
+            code: frame.code.into(),
+            reason: frame.reason.as_str().to_owned(),
+        })),
+        // Raw frames are only produced when writing; reading yields messages.
+        Message::Frame(_) => return None,
+    })
+}
+
+async fn log(events: &Sender<SessionEvent>, direction: Direction, frame: LoggedFrame) -> bool {
+    events
+        .send(SessionEvent::Logged(LogEntry::now(direction, frame)))
+        .await
+        .is_ok()
+}
+
+fn handshake_problem(error: tungstenite::Error) -> SessionProblem {
+    match error {
+        tungstenite::Error::Http(response) => SessionProblem::Rejected(response.status().as_u16()),
+        tungstenite::Error::Tls(_) => SessionProblem::Tls,
+        tungstenite::Error::Protocol(_) | tungstenite::Error::HttpFormat(_) => {
+            SessionProblem::Handshake
+        }
+        _ => SessionProblem::Connect,
+    }
+}
+
+fn connection_problem(error: tungstenite::Error) -> SessionProblem {
+    match error {
+        tungstenite::Error::Protocol(_)
+        | tungstenite::Error::Capacity(_)
+        | tungstenite::Error::Utf8(_) => SessionProblem::Protocol,
+        _ => SessionProblem::Connection,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::HeaderMap;
+    use http_client_test_server::TestServer;
+    use tokio::time::timeout;
+    use url::Url;
+
+    use super::*;
+
+    struct Session {
+        commands: Sender<SessionCommand>,
+        events: Receiver<SessionEvent>,
+    }
+
+    impl Session {
+        fn start(url: &str, headers: HeaderMap, protocols: &[&str]) -> Self {
+            let request = ConnectRequest {
+                url: Url::parse(url).unwrap(),
+                headers,
+                protocols: protocols
+                    .iter()
+                    .map(|protocol| (*protocol).to_owned())
+                    .collect(),
+            };
+            let (commands, command_receiver) = command_channel();
+            let (event_sender, events) = event_channel();
+            tokio::spawn(run_session(request, command_receiver, event_sender));
+            Self { commands, events }
+        }
+
+        async fn next(&self) -> SessionEvent {
+            timeout(Duration::from_secs(5), self.events.recv())
+                .await
+                .expect("session event arrives in time")
+                .expect("session emits until it ends")
+        }
+
+        async fn logged(&self) -> (Direction, LoggedFrame) {
+            match self.next().await {
+                SessionEvent::Logged(entry) => (entry.direction, entry.frame),
+                SessionEvent::Opened { .. } => panic!("unexpected open event"),
+                SessionEvent::Ended(result) => panic!("session ended early: {result:?}"),
+            }
+        }
+
+        async fn send_text(&self, text: &str) {
+            self.commands
+                .send(SessionCommand::Send(OutgoingFrame::Text(text.to_owned())))
+                .await
+                .unwrap();
+        }
+    }
+
+    fn text(direction: Direction, text: &str) -> (Direction, LoggedFrame) {
+        (direction, LoggedFrame::Text(text.to_owned()))
+    }
+
+    fn close(direction: Direction, code: u16, reason: &str) -> (Direction, LoggedFrame) {
+        (
+            direction,
+            LoggedFrame::Close(Some(CloseInfo {
+                code,
+                reason: reason.to_owned(),
+            })),
+        )
+    }
+
+    #[tokio::test]
+    async fn echo_session_logs_data_ping_pong_and_a_client_close() {
+        let server = TestServer::spawn().await.unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-test-token", HeaderValue::from_static("fixture"));
+        let session = Session::start(&server.websocket_url(), headers, &["echo.v1", "echo.v2"]);
+
+        assert!(matches!(
+            session.next().await,
+            SessionEvent::Opened { protocol: Some(protocol) } if protocol == "echo.v1"
+        ));
+
+        session.send_text("hello").await;
+        assert_eq!(session.logged().await, text(Direction::Sent, "hello"));
+        assert_eq!(session.logged().await, text(Direction::Received, "hello"));
+
+        session.send_text("!header x-test-token fixture").await;
+        session.logged().await;
+        assert_eq!(session.logged().await, text(Direction::Received, "match"));
+
+        let bytes = Bytes::from_static(&[0, 0xff]);
+        session
+            .commands
+            .send(SessionCommand::Send(OutgoingFrame::Binary(bytes.clone())))
+            .await
+            .unwrap();
+        session.logged().await;
+        assert_eq!(
+            session.logged().await,
+            (Direction::Received, LoggedFrame::Binary(bytes))
+        );
+
+        session.send_text("!ping beat").await;
+        session.logged().await;
+        let beat = Bytes::from_static(b"beat");
+        assert_eq!(
+            session.logged().await,
+            (Direction::Received, LoggedFrame::Ping(beat.clone()))
+        );
+        assert_eq!(
+            session.logged().await,
+            (Direction::Sent, LoggedFrame::Pong(beat))
+        );
+        assert_eq!(
+            session.logged().await,
+            text(Direction::Received, "pong beat")
+        );
+
+        session
+            .commands
+            .send(SessionCommand::Ping(Bytes::from_static(b"mine")))
+            .await
+            .unwrap();
+        session.logged().await;
+        assert_eq!(
+            session.logged().await,
+            (
+                Direction::Received,
+                LoggedFrame::Pong(Bytes::from_static(b"mine"))
+            )
+        );
+
+        session.commands.send(SessionCommand::Close).await.unwrap();
+        assert_eq!(session.logged().await, close(Direction::Sent, 1000, ""));
+        assert_eq!(session.logged().await, close(Direction::Received, 1000, ""));
+        assert!(matches!(session.next().await, SessionEvent::Ended(Ok(()))));
+        server.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn server_close_code_and_reason_are_logged_and_echoed() {
+        let server = TestServer::spawn().await.unwrap();
+        let session = Session::start(&server.websocket_url(), HeaderMap::new(), &[]);
+
+        assert!(matches!(
+            session.next().await,
+            SessionEvent::Opened { protocol: None }
+        ));
+        session.send_text("!close 4001 done").await;
+        session.logged().await;
+        assert_eq!(
+            session.logged().await,
+            close(Direction::Received, 4001, "done")
+        );
+        assert_eq!(session.logged().await, close(Direction::Sent, 4001, "done"));
+        assert!(matches!(session.next().await, SessionEvent::Ended(Ok(()))));
+        server.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn handshake_failures_report_the_http_status_or_connect_failure() {
+        let server = TestServer::spawn().await.unwrap();
+        let missing = server.websocket_url().replace("/v1/websocket", "/missing");
+        let rejected = Session::start(&missing, HeaderMap::new(), &[]);
+        assert!(matches!(
+            rejected.next().await,
+            SessionEvent::Ended(Err(SessionProblem::Rejected(404)))
+        ));
+        let unreachable = server.websocket_url();
+        server.shutdown().await.unwrap();
+
+        let refused = Session::start(&unreachable, HeaderMap::new(), &[]);
+        assert!(matches!(
+            refused.next().await,
+            SessionEvent::Ended(Err(SessionProblem::Connect))
+        ));
+    }
+}
@@ -0,0 +1,121 @@
+use gpui::{
+    AppContext as _, Context, Entity, IntoElement, ParentElement as _, Render, Styled as _, Window,
+    div, px,
+};
+use gpui_component::{
+    Root,
+    tab::{Tab, TabBar},
+};
+
+use super::{CookieManagerView, GrpcView, RequestSessionView, RunnerView, WebSocketView};
+use crate::features::request::CookieJar;
+use crate::foundation::I18n;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum WorkspaceMode {
+    #[default]
+    Http,
+    Runner,
+    WebSocket,
+    Grpc,
+    Cookies,
+}
+
+impl WorkspaceMode {
+    const ALL: [Self; 5] = [
+        Self::Http,
+        Self::Runner,
+        Self::WebSocket,
+        Self::Grpc,
+        Self::Cookies,
+    ];
+
+    const fn index(self) -> usize {
+        match self {
+            Self::Http => 0,
+            Self::Runner => 1,
+            Self::WebSocket => 2,
+            Self::Grpc => 3,
+            Self::Cookies => 4,
+        }
+    }
+}
+
+/// Switches between the HTTP request editor, the collection runner, the
+/// WebSocket and gRPC clients and the cookie manager. Every view stays
+/// alive so an open socket or a running call survives a look at another
+/// mode.
+pub struct WorkspaceView {
+    mode: WorkspaceMode,
+    request: Entity<RequestSessionView>,
+    runner: Entity<RunnerView>,
+    websocket: Entity<WebSocketView>,
+    grpc: Entity<GrpcView>,
+    cookies: Entity<CookieManagerView>,
+}
+
+impl WorkspaceView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let data_dir = dirs_next::data_local_dir().map(|dir| dir.join(crate::APP_NAME));
+        // Requests, runs and the cookie manager share one jar.
+        let jar = data_dir
+            .as_ref()
+            .map(|dir| CookieJar::persistent(dir.join("cookies.json")))
+            .unwrap_or_default();
+        let request_tabs = data_dir.map(|dir| dir.join("request-tabs.json"));
+        Self {
+            mode: WorkspaceMode::default(),
+            request: cx.new(|cx| RequestSessionView::new(jar.clone(), request_tabs, window, cx)),
+            runner: cx.new(|cx| RunnerView::new(jar.clone(), window, cx)),
+            websocket: cx.new(|cx| WebSocketView::new(window, cx)),
+            grpc: cx.new(|cx| GrpcView::new(window, cx)),
+            cookies: cx.new(|cx| CookieManagerView::new(jar, window, cx)),
+        }
+    }
+}
+
+impl Render for WorkspaceView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let labels = {
+            let i18n = cx.global::<I18n>();
+            [
+                i18n.t("mode-http"),
+                i18n.t("mode-runner"),
+                i18n.t("mode-websocket"),
+                i18n.t("mode-grpc"),
+                i18n.t("mode-cookies"),
+            ]
+        };
+        let content = match self.mode {
+            WorkspaceMode::Http => self.request.clone().into_any_element(),
+            WorkspaceMode::Runner => self.runner.clone().into_any_element(),
+            WorkspaceMode::WebSocket => self.websocket.clone().into_any_element(),
+            WorkspaceMode::Grpc => self.grpc.clone().into_any_element(),
+            WorkspaceMode::Cookies => self.cookies.clone().into_any_element(),
+        };
+        div()
+            .size_full()
+            .flex()
+            .flex_col()
+            .child(
+                TabBar::new("workspace-modes")
+                    .selected_index(self.mode.index())
+                    .on_click(cx.listener(|this, index, _, cx| {
+                        if let Some(mode) = WorkspaceMode::ALL.get(*index).copied() {
+                            this.mode = mode;
+                            cx.notify();
+                        }
+                    }))
+                    .children(labels.into_iter().map(|label| Tab::new().label(label))),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .min_h(px(0.))
+                    .overflow_hidden()
+                    .child(content),
+            )
+            // Closing a request tab with unsaved edits asks in a dialog.
+            .children(Root::render_dialog_layer(window, cx))
+    }
+}
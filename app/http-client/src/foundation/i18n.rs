@@ -17,14 +17,14 @@ enum Locale {
     ZhCn,
 }
 
-pub(crate) struct I18n {
+pub struct I18n {
     locale: Locale,
     bundles: HashMap<Locale, FluentBundle<FluentResource>>,
 }
 
 impl Global for I18n {}
 
-pub(crate) fn init_i18n(cx: &mut App) {
+pub fn init_i18n(cx: &mut App) {
     cx.set_global(I18n::new(detect_locale()));
 }
 
@@ -37,7 +37,7 @@ impl I18n {
         Self { locale, bundles }
     }
 
-    pub(crate) fn t(&self, key: &str) -> String {
+    pub fn t(&self, key: &str) -> String {
         self.translate(key, None)
     }
 
@@ -214,13 +214,16 @@ mod tests {
         "button-clear-file",
         "button-move-up",
         "button-move-down",
+        "button-clear-variables",
         "tab-params",
         "tab-authorization",
         "tab-headers",
         "tab-body",
+        "tab-tests",
         "tab-settings",
         "tab-response-body",
         "tab-response-headers",
+        "tab-response-tests",
         "response-title",
         "response-empty",
         "response-sending",
@@ -245,6 +248,7 @@ mod tests {
         "response-view-image",
         "response-view-audio",
         "response-view-pdf",
+        "response-view-events",
         "response-media-loading",
         "response-media-play",
         "response-media-pause",
@@ -262,6 +266,15 @@ mod tests {
         "response-pdf-encrypted",
         "response-pdf-too-large",
         "response-pdf-render-failed",
+        "response-events-count",
+        "response-events-last-id",
+        "response-events-reconnecting",
+        "response-events-dropped",
+        "response-events-empty",
+        "response-events-id",
+        "response-events-type",
+        "response-events-data",
+        "response-events-retry",
         "response-preview-truncated",
         "response-decoding-unsupported",
         "response-viewer-mode-unavailable",
@@ -270,6 +283,20 @@ mod tests {
         "response-image-too-large",
         "response-save-complete",
         "response-save-failed",
+        "response-tests-empty",
+        "response-tests-running",
+        "response-tests-summary",
+        "response-tests-result",
+        "response-tests-assertion",
+        "response-tests-detail",
+        "response-tests-variable",
+        "response-tests-value",
+        "response-tests-pass",
+        "response-tests-fail",
+        "response-tests-actual",
+        "response-tests-missing",
+        "response-tests-body-unavailable",
+        "response-tests-body-not-json",
         "request-problem-transport",
         "request-problem-timeout",
         "request-problem-redirect",
@@ -280,6 +307,11 @@ mod tests {
         "request-problem-too-large-encoded",
         "request-problem-too-large-stored",
         "request-problem-internal",
+        "request-problem-auth-token-endpoint",
+        "request-problem-auth-token-rejected",
+        "request-problem-auth-token-invalid",
+        "request-problem-auth-denied",
+        "request-problem-auth-callback",
         "field-method",
         "field-url",
         "field-name",
@@ -290,6 +322,21 @@ mod tests {
         "field-username",
         "field-password",
         "field-token",
+        "field-grant-type",
+        "field-token-url",
+        "field-authorization-url",
+        "field-redirect-uri",
+        "field-client-id",
+        "field-client-secret",
+        "field-scope",
+        "field-access-key-id",
+        "field-secret-access-key",
+        "field-session-token",
+        "field-region",
+        "field-service",
+        "field-variable",
+        "field-check-target",
+        "field-check-expected",
         "field-location",
         "field-timeout-ms",
         "params-invalid-url-disabled",
@@ -298,12 +345,43 @@ mod tests {
         "body-urlencoded",
         "body-text",
         "body-binary",
+        "body-graphql",
         "text-format-plain",
         "text-format-json",
         "text-format-javascript",
         "text-format-html",
         "text-format-xml",
         "text-format-css",
+        "graphql-query",
+        "graphql-variables",
+        "graphql-operation",
+        "graphql-fetch-schema",
+        "graphql-schema-none",
+        "graphql-schema-loading",
+        "graphql-schema-loaded",
+        "graphql-schema-prepare",
+        "graphql-schema-authorization",
+        "graphql-schema-status",
+        "graphql-schema-not-json",
+        "graphql-schema-rejected",
+        "graphql-schema-missing",
+        "graphql-schema-malformed",
+        "graphql-expected-definition",
+        "graphql-expected-name",
+        "graphql-expected-punctuator",
+        "graphql-expected-value",
+        "graphql-expected-end",
+        "graphql-anonymous-operation",
+        "graphql-duplicate-operation",
+        "graphql-duplicate-fragment",
+        "graphql-unknown-fragment",
+        "graphql-undefined-variable",
+        "graphql-unsupported-operation",
+        "graphql-unknown-type",
+        "graphql-unknown-field",
+        "graphql-unknown-argument",
+        "graphql-selection-required",
+        "graphql-selection-not-allowed",
         "multipart-text",
         "multipart-file",
         "multipart-file-not-selected",
@@ -311,6 +389,13 @@ mod tests {
         "auth-basic",
         "auth-bearer",
         "auth-api-key",
+        "auth-digest",
+        "auth-oauth2",
+        "auth-aws-sigv4",
+        "auth-oauth2-grant-client-credentials",
+        "auth-oauth2-grant-password",
+        "auth-oauth2-grant-authorization-code",
+        "auth-oauth2-redirect-help",
         "auth-location-header",
         "auth-location-query",
         "auth-generated-override",
@@ -318,7 +403,21 @@ mod tests {
         "body-content-type-override",
         "settings-follow-redirects",
         "settings-follow-original-method",
+        "settings-reconnect-event-stream",
         "settings-timeout-help",
+        "checks-assertions",
+        "checks-extractions",
+        "checks-variables",
+        "checks-variables-hint",
+        "checks-variables-empty",
+        "checks-kind-status",
+        "checks-kind-header-present",
+        "checks-kind-header-matches",
+        "checks-kind-json-equals",
+        "checks-kind-json-matches",
+        "checks-kind-time-below",
+        "checks-source-json-path",
+        "checks-source-header",
         "request-url-invalid",
         "request-url-scheme-invalid",
         "request-header-name-invalid",
@@ -331,8 +430,313 @@ mod tests {
         "request-file-name-invalid",
         "request-basic-username-colon",
         "request-auth-value-invalid",
+        "request-oauth2-redirect-loopback",
+        "request-aws-scope-invalid",
+        "request-check-status-invalid",
+        "request-check-pattern-invalid",
+        "request-check-json-path-invalid",
+        "request-check-duration-invalid",
+        "request-variable-name-invalid",
+        "request-graphql-query-required",
+        "request-graphql-variables-invalid",
+        "request-graphql-operation-required",
+        "request-graphql-operation-unknown",
+        "request-prepare-url",
+        "request-prepare-header",
+        "request-prepare-media-type",
+        "request-prepare-auth",
+        "request-prepare-file",
+        "request-prepare-check",
+        "request-prepare-graphql",
+        "request-prepare-connection",
+        "request-prepare-unknown-variables",
+        "request-prepare-internal",
         "request-api-key-name-required",
         "request-api-key-name-invalid",
+        "button-connect",
+        "button-disconnect",
+        "button-ping",
+        "button-clear-log",
+        "button-stop-reconnecting",
+        "mode-http",
+        "mode-websocket",
+        "websocket-url-placeholder",
+        "websocket-protocols-placeholder",
+        "websocket-headers-placeholder",
+        "websocket-filter-placeholder",
+        "websocket-filter-hide-control",
+        "websocket-state-disconnected",
+        "websocket-state-connecting",
+        "websocket-state-open",
+        "websocket-state-open-protocol",
+        "websocket-state-closing",
+        "websocket-format-text",
+        "websocket-format-json",
+        "websocket-format-binary",
+        "websocket-direction-all",
+        "websocket-direction-sent",
+        "websocket-direction-received",
+        "websocket-kind-text",
+        "websocket-kind-binary",
+        "websocket-kind-ping",
+        "websocket-kind-pong",
+        "websocket-kind-close",
+        "websocket-close-detail",
+        "websocket-close-no-status",
+        "websocket-log-time",
+        "websocket-log-direction",
+        "websocket-log-kind",
+        "websocket-log-payload",
+        "websocket-log-empty",
+        "websocket-log-no-match",
+        "websocket-log-dropped",
+        "websocket-url-required",
+        "websocket-url-invalid",
+        "websocket-url-scheme",
+        "websocket-header-invalid",
+        "websocket-header-reserved",
+        "websocket-protocol-invalid",
+        "websocket-message-json-invalid",
+        "websocket-message-hex-invalid",
+        "websocket-problem-connect",
+        "websocket-problem-tls",
+        "websocket-problem-rejected",
+        "websocket-problem-handshake",
+        "websocket-problem-protocol",
+        "websocket-problem-connection",
+        "button-add-cookie",
+        "button-save-cookie",
+        "button-cancel-edit",
+        "button-edit-cookie",
+        "button-delete-cookie",
+        "button-delete-domain-cookies",
+        "mode-cookies",
+        "settings-send-cookies",
+        "cookies-filter-placeholder",
+        "cookies-domain-placeholder",
+        "cookies-path-placeholder",
+        "cookies-name-placeholder",
+        "cookies-value-placeholder",
+        "cookies-expires-placeholder",
+        "cookies-empty",
+        "cookies-no-match",
+        "cookies-domain-count",
+        "cookies-enabled",
+        "cookies-name",
+        "cookies-value",
+        "cookies-path",
+        "cookies-expires",
+        "cookies-flags",
+        "cookies-session",
+        "cookies-host-only",
+        "cookies-secure",
+        "cookies-http-only",
+        "cookies-domain-required",
+        "cookies-name-required",
+        "cookies-name-invalid",
+        "cookies-value-invalid",
+        "cookies-path-invalid",
+        "cookies-expires-invalid",
+        "tab-response-connection",
+        "request-problem-ca-certificate",
+        "request-problem-client-identity",
+        "request-problem-proxy",
+        "request-client-certificate-required",
+        "request-proxy-url-invalid",
+        "settings-http-version",
+        "settings-ca-certificate",
+        "settings-client-certificate",
+        "settings-client-key",
+        "settings-client-certificate-password",
+        "settings-accept-invalid-certificates",
+        "settings-accept-invalid-certificates-warning",
+        "settings-proxy-url",
+        "settings-proxy-url-placeholder",
+        "settings-proxy-username",
+        "settings-proxy-password",
+        "settings-proxy-bypass",
+        "settings-proxy-bypass-placeholder",
+        "http-version-automatic",
+        "http-version-http1",
+        "http-version-http2",
+        "response-connection-protocol",
+        "response-connection-tls",
+        "response-connection-plain",
+        "response-certificate-subject",
+        "response-certificate-issuer",
+        "response-certificate-alt-names",
+        "response-certificate-not-before",
+        "response-certificate-not-after",
+        "response-certificate-serial",
+        "response-certificate-sha256",
+        "response-certificate-position",
+        "response-certificate-unverified",
+        "tab-response-timing",
+        "response-timing-empty",
+        "response-timing-total",
+        "response-timing-reused",
+        "response-timing-dns",
+        "response-timing-connect",
+        "response-timing-connect-tls",
+        "response-timing-tls",
+        "response-timing-request-write",
+        "response-timing-waiting",
+        "response-timing-download",
+        "button-open-request",
+        "button-run",
+        "button-save-request",
+        "button-stop",
+        "field-runner-concurrency",
+        "field-runner-delay-ms",
+        "field-runner-duration-s",
+        "field-runner-iterations",
+        "field-runner-request-limit",
+        "mode-runner",
+        "runner-choose-data-file",
+        "runner-choose-folder",
+        "runner-clear-data-file",
+        "runner-column-checks",
+        "runner-column-iteration",
+        "runner-column-request",
+        "runner-column-result",
+        "runner-column-status",
+        "runner-column-time",
+        "runner-data-file",
+        "runner-data-invalid-column",
+        "runner-data-missing-header",
+        "runner-data-not-utf8",
+        "runner-data-ragged-row",
+        "runner-data-rows",
+        "runner-data-unreadable",
+        "runner-data-unterminated-quote",
+        "runner-folder-empty",
+        "runner-folder-summary",
+        "runner-folder-unreadable",
+        "runner-load-empty",
+        "runner-load-error-rate",
+        "runner-load-histogram",
+        "runner-load-limits-help",
+        "runner-load-requests",
+        "runner-load-target",
+        "runner-load-throughput",
+        "runner-load-throughput-value",
+        "runner-load-unbounded",
+        "runner-no-folder",
+        "runner-records-empty",
+        "runner-reload-folder",
+        "runner-step-invalid",
+        "runner-step-unreadable",
+        "runner-summary",
+        "saved-request-malformed",
+        "saved-request-newer-version",
+        "saved-request-read-failed",
+        "saved-request-write-failed",
+        "tab-runner-collection",
+        "tab-runner-load",
+        "button-copy-path",
+        "button-next-match",
+        "button-previous-match",
+        "response-filter-invalid",
+        "response-filter-placeholder",
+        "response-filter-results",
+        "response-filter-running",
+        "response-layout-source",
+        "response-layout-tree",
+        "response-search-count",
+        "response-search-none",
+        "response-search-placeholder",
+        "button-pin-response",
+        "button-unpin-response",
+        "response-diff-body",
+        "response-diff-body-binary",
+        "response-diff-body-same",
+        "response-diff-current",
+        "response-diff-failed",
+        "response-diff-headers",
+        "response-diff-headers-same",
+        "response-diff-no-pin",
+        "response-diff-path",
+        "response-diff-pinned",
+        "response-diff-pinned-current",
+        "response-diff-running",
+        "response-diff-skipped",
+        "response-diff-status",
+        "response-diff-status-same",
+        "response-diff-truncated",
+        "response-diff-waiting",
+        "tab-response-diff",
+        "button-load-protos",
+        "button-reflect",
+        "mode-grpc",
+        "response-view-protobuf",
+        "response-protobuf-message-type",
+        "response-protobuf-type-required",
+        "response-protobuf-too-large",
+        "response-protobuf-invalid",
+        "grpc-target-placeholder",
+        "grpc-metadata-placeholder",
+        "grpc-deadline-ms",
+        "grpc-service-placeholder",
+        "grpc-method-placeholder",
+        "grpc-method-unary",
+        "grpc-method-server-streaming",
+        "grpc-method-client-streaming",
+        "grpc-method-bidirectional",
+        "grpc-method-unsupported",
+        "grpc-schema-empty",
+        "grpc-schema-loading",
+        "grpc-schema-files",
+        "grpc-schema-reflection",
+        "grpc-response-empty",
+        "grpc-call-running",
+        "grpc-call-status",
+        "grpc-call-status-message",
+        "grpc-call-messages",
+        "grpc-call-messages-hidden",
+        "grpc-metadata-name",
+        "grpc-metadata-value",
+        "grpc-target-required",
+        "grpc-target-invalid",
+        "grpc-target-scheme",
+        "grpc-metadata-invalid",
+        "grpc-metadata-reserved",
+        "grpc-message-json-invalid",
+        "grpc-message-undecodable",
+        "grpc-schema-compile",
+        "grpc-schema-invalid",
+        "grpc-schema-no-services",
+        "grpc-reflection-unsupported",
+        "grpc-reflection-status",
+        "grpc-reflection-server",
+        "grpc-reflection-invalid",
+        "grpc-problem-connect",
+        "grpc-problem-deadline",
+        "grpc-problem-http-status",
+        "grpc-problem-not-grpc",
+        "grpc-problem-compressed",
+        "grpc-problem-too-large",
+        "grpc-problem-truncated",
+        "grpc-problem-connection",
+        "grpc-problem-missing-status",
+        "button-import-har",
+        "button-export-har",
+        "har-imported",
+        "har-imported-skipped",
+        "har-exported",
+        "har-read-failed",
+        "har-write-failed",
+        "har-malformed",
+        "har-no-requests",
+        "response-view-msgpack",
+        "response-view-cbor",
+        "response-view-form",
+        "response-document-too-large",
+        "response-msgpack-invalid",
+        "response-cbor-invalid",
+        "button-discard-changes",
+        "request-tab-untitled",
+        "request-tab-unsaved-title",
+        "request-tab-unsaved-message",
     ];
 
     #[test]
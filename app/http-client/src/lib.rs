@@ -0,0 +1,13 @@
+//! The request views and machinery behind the desktop app and the
+//! `http-client-run` command, which share one copy of the transport,
+//! compilation and checks.
+
+pub use errors::{HttpClientError, HttpClientResult};
+pub use features::{request::run_headless, workspace::WorkspaceView};
+pub use foundation::i18n::{I18n, init_i18n};
+
+mod errors;
+mod features;
+mod foundation;
+
+pub static APP_NAME: &str = "top.sushao.http-client";
@@ -1,20 +1,10 @@
-use errors::HttpClientResult;
-use features::RequestView;
-use foundation::I18n;
+use ::http_client::{APP_NAME, HttpClientError, HttpClientResult, I18n, WorkspaceView, init_i18n};
 use gpui::*;
 use gpui_component::Root;
 use std::{fs::create_dir_all, path::PathBuf};
 use tracing::{Level, event, level_filters::LevelFilter};
 use tracing_subscriber::{Layer, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::errors::HttpClientError;
-
-mod errors;
-mod features;
-mod foundation;
-
-static APP_NAME: &str = "top.sushao.http-client";
-
 actions!(feiwen, [Quit]);
 
 fn quit(_: &Quit, cx: &mut App) {
@@ -28,7 +18,7 @@ fn init(cx: &mut App) {
     cx.bind_keys([KeyBinding::new("cmd-q", Quit, None)]);
     cx.activate(true);
     cx.on_action(quit);
-    foundation::i18n::init_i18n(cx);
+    init_i18n(cx);
 }
 
 fn get_logs_dir() -> HttpClientResult<PathBuf> {
@@ -90,7 +80,7 @@ fn main() -> HttpClientResult<()> {
                 ..Default::default()
             },
             |window, cx| {
-                let view = cx.new(|cx| RequestView::new(window, cx));
+                let view = cx.new(|cx| WorkspaceView::new(window, cx));
                 cx.new(|cx| Root::new(view, window, cx))
             },
         ) {
@@ -124,6 +124,12 @@ impl<M: FormSchema> Form<M> {
         self.value != self.baseline
     }
 
+    /// The value last installed with `new` or `rebase`, which `is_dirty`
+    /// compares against.
+    pub fn baseline(&self) -> &M {
+        &self.baseline
+    }
+
     pub fn validation_report(&self) -> ValidationReport {
         let mut report = self.validation.clone();
         report.issues.retain(ValidationIssue::is_active);
@@ -116,3 +116,25 @@ fn rebase_if_current_rejects_stale_and_cross_session_versions_without_mutation(
 
     drop(second);
 }
+
+#[gpui::test]
+fn baseline_follows_only_current_rebases(cx: &mut TestAppContext) {
+    cx.update(|cx| {
+        let form = cx.new(|_| Form::new(draft("initial")));
+        let stale = form.update(cx, |form, cx| form.prepare(cx)).unwrap();
+        assert!(Draft::VALUE.set(&form, "edited".into(), cx));
+        assert!(form.read(cx).is_dirty());
+        assert_eq!(form.read(cx).baseline(), &draft("initial"));
+
+        let current = form.update(cx, |form, cx| form.prepare(cx)).unwrap();
+        assert!(form.update(cx, |form, cx| {
+            form.rebase_if_current(current.version(), draft("saved"), cx)
+        }));
+        assert_eq!(form.read(cx).baseline(), &draft("saved"));
+
+        assert!(!form.update(cx, |form, cx| {
+            form.rebase_if_current(stale.version(), draft("stale"), cx)
+        }));
+        assert_eq!(form.read(cx).baseline(), &draft("saved"));
+    });
+}
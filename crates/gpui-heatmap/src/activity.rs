@@ -19,7 +19,7 @@ use gpui_component::{
 use time::{Date, Duration};
 
 const ROWS: usize = 7;
-const LEVEL_COUNT: usize = 5;
+pub(crate) const LEVEL_COUNT: usize = 5;
 const MAX_ACTIVITY_LEVEL: u8 = (LEVEL_COUNT - 1) as u8;
 const MONTH_LABEL_HEIGHT: Pixels = px(20.);
 const FOOTER_GAP: Pixels = px(8.);
@@ -304,7 +304,7 @@ impl RenderOnce for ActivityHeatmap {
     }
 }
 
-fn child_id(parent: &ElementId, child: &'static str) -> ElementId {
+pub(crate) fn child_id(parent: &ElementId, child: &'static str) -> ElementId {
     (parent.clone(), child).into()
 }
 
@@ -319,13 +319,13 @@ fn series_dates(series: &ActivityHeatmapSeries) -> impl Iterator<Item = Date> +
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-struct HeatmapMetrics {
-    cell: Pixels,
-    gap: Pixels,
+pub(crate) struct HeatmapMetrics {
+    pub(crate) cell: Pixels,
+    pub(crate) gap: Pixels,
 }
 
 impl HeatmapMetrics {
-    fn for_size(size: Size) -> Self {
+    pub(crate) fn for_size(size: Size) -> Self {
         match size {
             Size::XSmall => Self::new(8., 2.),
             Size::Small => Self::new(10., 2.),
@@ -349,7 +349,7 @@ impl HeatmapMetrics {
         }
     }
 
-    fn stride(self) -> Pixels {
+    pub(crate) fn stride(self) -> Pixels {
         self.cell + self.gap
     }
 }
@@ -433,7 +433,7 @@ impl HeatmapLayout {
     }
 }
 
-fn dimension(count: usize, metrics: HeatmapMetrics) -> Pixels {
+pub(crate) fn dimension(count: usize, metrics: HeatmapMetrics) -> Pixels {
     if count == 0 {
         return px(0.);
     }
@@ -473,7 +473,7 @@ fn month_markers(series: &ActivityHeatmapSeries, layout: &HeatmapLayout) -> Vec<
         .collect()
 }
 
-fn activity_level(value: u64, max_value: u64) -> u8 {
+pub(crate) fn activity_level(value: u64, max_value: u64) -> u8 {
     if value == 0 {
         return 0;
     }
@@ -484,7 +484,7 @@ fn activity_level(value: u64, max_value: u64) -> u8 {
     level as u8
 }
 
-fn level_palette(cx: &App) -> [Hsla; LEVEL_COUNT] {
+pub(crate) fn level_palette(cx: &App) -> [Hsla; LEVEL_COUNT] {
     let active = cx.theme().chart_1;
     [
         cx.theme().secondary,
@@ -7,7 +7,11 @@
 //! they provide.
 
 mod activity;
+mod matrix;
 
 pub use activity::{
     ActivityHeatmap, ActivityHeatmapLabels, ActivityHeatmapSeries, ActivityHeatmapSeriesError,
 };
+pub use matrix::{
+    MatrixHeatmap, MatrixHeatmapLabels, MatrixHeatmapSeries, MatrixHeatmapSeriesError,
+};
@@ -0,0 +1,526 @@
+use std::{error::Error, fmt};
+
+use gpui::prelude::FluentBuilder as _;
+use gpui::{
+    AnyElement, App, BorderStyle, Bounds, ElementId, InteractiveElement as _, IntoElement,
+    ParentElement as _, Pixels, Point, RenderOnce, Role, ScrollHandle, SharedString,
+    Size as GpuiSize, StatefulInteractiveElement as _, StyleRefinement, Styled, Window, div, point,
+    px, quad,
+};
+use gpui_component::{
+    ActiveTheme as _, Sizable, Size, StyledExt as _,
+    plot::{
+        IntoPlot, Plot,
+        tooltip::{Tooltip, TooltipState},
+    },
+    scroll::Scrollbar,
+    v_flex,
+};
+
+use crate::activity::{HeatmapMetrics, activity_level, child_id, dimension, level_palette};
+
+const ROW_LABEL_WIDTH: Pixels = px(96.);
+const ROW_LABEL_GAP: Pixels = px(8.);
+const FOOTER_GAP: Pixels = px(8.);
+
+/// A labelled grid of values, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatrixHeatmapSeries {
+    rows: Vec<SharedString>,
+    columns: Vec<SharedString>,
+    values: Vec<u64>,
+    max_value: u64,
+}
+
+/// Validation errors for [`MatrixHeatmapSeries`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatrixHeatmapSeriesError {
+    /// At least one row and one column are required.
+    Empty,
+    /// The number of values is not the number of rows times the number of columns.
+    ShapeMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for MatrixHeatmapSeriesError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => formatter.write_str("matrix heatmap series cannot be empty"),
+            Self::ShapeMismatch { expected, actual } => write!(
+                formatter,
+                "matrix heatmap series expects {expected} values but got {actual}"
+            ),
+        }
+    }
+}
+
+impl Error for MatrixHeatmapSeriesError {}
+
+impl MatrixHeatmapSeries {
+    /// Constructs a series from row and column labels and their values in row-major order.
+    pub fn try_new(
+        rows: Vec<SharedString>,
+        columns: Vec<SharedString>,
+        values: Vec<u64>,
+    ) -> Result<Self, MatrixHeatmapSeriesError> {
+        if rows.is_empty() || columns.is_empty() {
+            return Err(MatrixHeatmapSeriesError::Empty);
+        }
+        let expected = rows.len() * columns.len();
+        if values.len() != expected {
+            return Err(MatrixHeatmapSeriesError::ShapeMismatch {
+                expected,
+                actual: values.len(),
+            });
+        }
+        let max_value = values.iter().copied().max().unwrap_or_default();
+
+        Ok(Self {
+            rows,
+            columns,
+            values,
+            max_value,
+        })
+    }
+
+    pub fn rows(&self) -> &[SharedString] {
+        &self.rows
+    }
+
+    pub fn columns(&self) -> &[SharedString] {
+        &self.columns
+    }
+
+    pub fn values(&self) -> &[u64] {
+        &self.values
+    }
+
+    pub fn value(&self, row: usize, column: usize) -> Option<u64> {
+        if row >= self.rows.len() || column >= self.columns.len() {
+            return None;
+        }
+        self.values.get(row * self.columns.len() + column).copied()
+    }
+
+    pub fn max_value(&self) -> u64 {
+        self.max_value
+    }
+}
+
+/// Caller-owned labels used by [`MatrixHeatmap`].
+#[derive(Clone)]
+pub struct MatrixHeatmapLabels {
+    pub less: SharedString,
+    pub more: SharedString,
+    pub value: SharedString,
+}
+
+/// A reusable, product-neutral heatmap of a labelled row-by-column grid.
+///
+/// Rows are labelled beside the grid; a cell's tooltip names both its row and its column.
+#[derive(IntoElement)]
+pub struct MatrixHeatmap {
+    id: ElementId,
+    series: MatrixHeatmapSeries,
+    labels: MatrixHeatmapLabels,
+    accessible_summary: SharedString,
+    caption: Option<SharedString>,
+    size: Size,
+    style: StyleRefinement,
+    value_labels: Vec<SharedString>,
+}
+
+impl MatrixHeatmap {
+    pub fn new(
+        id: impl Into<ElementId>,
+        series: MatrixHeatmapSeries,
+        labels: MatrixHeatmapLabels,
+        accessible_summary: impl Into<SharedString>,
+    ) -> Self {
+        let value_labels = series
+            .values()
+            .iter()
+            .map(|value| value.to_string().into())
+            .collect();
+
+        Self {
+            id: id.into(),
+            series,
+            labels,
+            accessible_summary: accessible_summary.into(),
+            caption: None,
+            size: Size::default(),
+            style: StyleRefinement::default(),
+            value_labels,
+        }
+    }
+
+    pub fn caption(mut self, caption: impl Into<SharedString>) -> Self {
+        self.caption = Some(caption.into());
+        self
+    }
+
+    /// Formats every exact value immediately and stores only the resulting owned labels.
+    pub fn format_value(mut self, formatter: impl Fn(u64) -> SharedString) -> Self {
+        self.value_labels = self
+            .series
+            .values()
+            .iter()
+            .copied()
+            .map(formatter)
+            .collect();
+        self
+    }
+}
+
+impl Styled for MatrixHeatmap {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl Sizable for MatrixHeatmap {
+    fn with_size(mut self, size: impl Into<Size>) -> Self {
+        self.size = size.into();
+        self
+    }
+}
+
+impl RenderOnce for MatrixHeatmap {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let metrics = HeatmapMetrics::for_size(self.size);
+        let layout = MatrixLayout::new(&self.series, metrics);
+        let plot_id = child_id(&self.id, "plot");
+        let scroll_state_id = child_id(&self.id, "scroll-state");
+        let scroll_root_id = child_id(&self.id, "scroll-root");
+        let scroll_area_id = child_id(&self.id, "scroll-area");
+        let scroll_content_id = child_id(&self.id, "scroll-content");
+        let scrollbar_id = child_id(&self.id, "scrollbar");
+
+        let scroll_state =
+            window.use_keyed_state(scroll_state_id, cx, |_, _| ScrollHandle::default());
+        let scroll_handle = scroll_state.read(cx).clone();
+        let muted = cx.theme().muted_foreground;
+        let border = cx.theme().border;
+        let palette = level_palette(cx);
+        let legend_cell = metrics.cell.min(px(12.));
+        let legend_radius = cx.theme().radius.min(legend_cell / 4.);
+        let content_width = ROW_LABEL_WIDTH + ROW_LABEL_GAP + layout.grid_width;
+
+        let row_labels = v_flex()
+            .w(ROW_LABEL_WIDTH)
+            .flex_none()
+            .gap(metrics.gap)
+            .text_xs()
+            .text_color(muted)
+            .children(self.series.rows().iter().map(|row| {
+                div()
+                    .h(metrics.cell)
+                    .line_height(metrics.cell)
+                    .overflow_hidden()
+                    .text_ellipsis()
+                    .whitespace_nowrap()
+                    .child(row.clone())
+            }));
+
+        let plot = MatrixHeatmapPlot {
+            id: plot_id,
+            series: self.series,
+            layout: layout.clone(),
+            value_labels: self.value_labels,
+            value_label: self.labels.value.clone(),
+        };
+        let grid = div().w(layout.grid_width).h(layout.grid_height).child(plot);
+
+        let legend = div()
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap_1()
+            .text_xs()
+            .text_color(muted)
+            .child(self.labels.less)
+            .children(palette.into_iter().map(|color| {
+                div()
+                    .size(legend_cell)
+                    .rounded(legend_radius)
+                    .border_1()
+                    .border_color(border)
+                    .bg(color)
+            }))
+            .child(self.labels.more);
+
+        let footer = div()
+            .w(content_width)
+            .flex()
+            .flex_row()
+            .flex_wrap()
+            .items_center()
+            .justify_between()
+            .gap(FOOTER_GAP)
+            .text_xs()
+            .text_color(muted)
+            .when_some(self.caption, |this, caption| this.child(caption))
+            .child(legend);
+
+        let content = v_flex()
+            .id(scroll_content_id)
+            .w(content_width)
+            .flex_none()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap(ROW_LABEL_GAP)
+                    .child(row_labels)
+                    .child(grid),
+            )
+            .child(footer);
+
+        let scroll_area = div()
+            .id(scroll_area_id)
+            .w_full()
+            .flex()
+            .flex_row()
+            .track_scroll(&scroll_handle)
+            .overflow_x_scroll()
+            .child(content);
+
+        let scroll_root = div()
+            .id(scroll_root_id)
+            .relative()
+            .w_full()
+            .overflow_hidden()
+            .child(scroll_area)
+            .when(!window.is_inspector_picking(cx), |this| {
+                this.child(
+                    div()
+                        .absolute()
+                        .inset_0()
+                        .child(Scrollbar::horizontal(&scroll_handle).id(scrollbar_id)),
+                )
+            });
+
+        div()
+            .id(self.id)
+            .role(Role::Image)
+            .aria_label(self.accessible_summary)
+            .w_full()
+            .refine_style(&self.style)
+            .child(scroll_root)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct MatrixLayout {
+    rows: usize,
+    columns: usize,
+    metrics: HeatmapMetrics,
+    grid_width: Pixels,
+    grid_height: Pixels,
+}
+
+impl MatrixLayout {
+    fn new(series: &MatrixHeatmapSeries, metrics: HeatmapMetrics) -> Self {
+        let rows = series.rows.len();
+        let columns = series.columns.len();
+        Self {
+            rows,
+            columns,
+            metrics,
+            grid_width: dimension(columns, metrics),
+            grid_height: dimension(rows, metrics),
+        }
+    }
+
+    fn cell_bounds(&self, index: usize, origin: Point<Pixels>) -> Bounds<Pixels> {
+        let stride = self.metrics.stride();
+        let (row, column) = (index / self.columns, index % self.columns);
+        Bounds {
+            origin: origin + point(stride * column as f32, stride * row as f32),
+            size: GpuiSize::new(self.metrics.cell, self.metrics.cell),
+        }
+    }
+
+    fn hit_test(&self, position: Point<Pixels>, plot_size: GpuiSize<Pixels>) -> Option<usize> {
+        if position.x < px(0.)
+            || position.y < px(0.)
+            || position.x >= plot_size.width
+            || position.y >= plot_size.height
+        {
+            return None;
+        }
+        let stride = self.metrics.stride().as_f32();
+        let x = position.x.as_f32();
+        let y = position.y.as_f32();
+        let column = (x / stride).floor() as usize;
+        let row = (y / stride).floor() as usize;
+        if column >= self.columns || row >= self.rows {
+            return None;
+        }
+        if x - column as f32 * stride >= self.metrics.cell.as_f32()
+            || y - row as f32 * stride >= self.metrics.cell.as_f32()
+        {
+            return None;
+        }
+        Some(row * self.columns + column)
+    }
+}
+
+#[derive(IntoPlot)]
+struct MatrixHeatmapPlot {
+    id: ElementId,
+    series: MatrixHeatmapSeries,
+    layout: MatrixLayout,
+    value_labels: Vec<SharedString>,
+    value_label: SharedString,
+}
+
+impl MatrixHeatmapPlot {
+    fn cell_title(&self, index: usize) -> Option<SharedString> {
+        let row = self.series.rows.get(index / self.layout.columns)?;
+        let column = self.series.columns.get(index % self.layout.columns)?;
+        Some(format!("{row} × {column}").into())
+    }
+}
+
+impl Plot for MatrixHeatmapPlot {
+    fn paint(&mut self, bounds: Bounds<Pixels>, window: &mut Window, cx: &mut App) {
+        let palette = level_palette(cx);
+        let radius = cx.theme().radius.min(self.layout.metrics.cell / 4.);
+        for (index, value) in self.series.values().iter().copied().enumerate() {
+            let level = usize::from(activity_level(value, self.series.max_value()));
+            window.paint_quad(quad(
+                self.layout.cell_bounds(index, bounds.origin),
+                radius,
+                palette[level],
+                px(1.),
+                cx.theme().border,
+                BorderStyle::default(),
+            ));
+        }
+    }
+
+    fn id(&self) -> Option<ElementId> {
+        Some(self.id.clone())
+    }
+
+    fn tooltip_state(
+        &self,
+        position: Point<Pixels>,
+        bounds: Bounds<Pixels>,
+        _cx: &App,
+    ) -> Option<TooltipState> {
+        let index = self.layout.hit_test(position, bounds.size)?;
+        let cell = self.layout.cell_bounds(index, point(px(0.), px(0.)));
+        Some(TooltipState::new(index, cell.center(), Vec::new()))
+    }
+
+    fn tooltip(
+        &self,
+        state: &TooltipState,
+        cursor: Point<Pixels>,
+        bounds: Bounds<Pixels>,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Option<AnyElement> {
+        let value = *self.series.values().get(state.index)?;
+        let level = usize::from(activity_level(value, self.series.max_value()));
+        Some(
+            Tooltip::new(cursor, bounds.size)
+                .title(self.cell_title(state.index)?)
+                .row(
+                    level_palette(cx)[level],
+                    self.value_label.clone(),
+                    self.value_labels.get(state.index)?.clone(),
+                )
+                .into_any_element(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(names: &[&'static str]) -> Vec<SharedString> {
+        names.iter().map(|name| SharedString::from(*name)).collect()
+    }
+
+    fn series(
+        rows: &[&'static str],
+        columns: &[&'static str],
+        values: Vec<u64>,
+    ) -> MatrixHeatmapSeries {
+        MatrixHeatmapSeries::try_new(labels(rows), labels(columns), values).unwrap()
+    }
+
+    #[test]
+    fn series_validates_shape_and_caches_max() {
+        assert_eq!(
+            MatrixHeatmapSeries::try_new(Vec::new(), labels(&["a"]), Vec::new()),
+            Err(MatrixHeatmapSeriesError::Empty)
+        );
+        assert_eq!(
+            MatrixHeatmapSeries::try_new(labels(&["a", "b"]), labels(&["x", "y"]), vec![1, 2, 3]),
+            Err(MatrixHeatmapSeriesError::ShapeMismatch {
+                expected: 4,
+                actual: 3
+            })
+        );
+        let series = series(&["a", "b"], &["x", "y", "z"], vec![1, 2, 3, 4, 9, 6]);
+        assert_eq!(series.max_value(), 9);
+        assert_eq!(series.value(1, 1), Some(9));
+        assert_eq!(series.value(0, 2), Some(3));
+        assert_eq!(series.value(2, 0), None);
+        assert_eq!(series.value(0, 3), None);
+    }
+
+    #[test]
+    fn layout_places_cells_row_by_row_and_hit_tests_them() {
+        let series = series(&["a", "b"], &["x", "y", "z"], vec![0; 6]);
+        let layout = MatrixLayout::new(&series, HeatmapMetrics::for_size(Size::Medium));
+        assert_eq!(layout.grid_width, px(42.));
+        assert_eq!(layout.grid_height, px(27.));
+        assert_eq!(
+            layout.cell_bounds(4, point(px(0.), px(0.))).origin,
+            point(px(15.), px(15.))
+        );
+
+        let size = GpuiSize::new(layout.grid_width, layout.grid_height);
+        assert_eq!(layout.hit_test(point(px(1.), px(1.)), size), Some(0));
+        assert_eq!(layout.hit_test(point(px(31.), px(16.)), size), Some(5));
+        assert_eq!(layout.hit_test(point(px(12.5), px(1.)), size), None);
+        assert_eq!(layout.hit_test(point(px(1.), px(-1.)), size), None);
+        assert_eq!(
+            layout.hit_test(point(layout.grid_width, px(1.)), size),
+            None
+        );
+    }
+
+    #[test]
+    fn tooltips_name_the_row_and_column_with_formatted_values() {
+        let heatmap = MatrixHeatmap::new(
+            "matrix",
+            series(&["a", "b"], &["x", "y"], vec![1, 2, 3, 4]),
+            MatrixHeatmapLabels {
+                less: "Less".into(),
+                more: "More".into(),
+                value: "Value".into(),
+            },
+            "summary",
+        )
+        .format_value(|value| format!("{value} exact").into());
+        assert_eq!(heatmap.value_labels[2], "3 exact");
+
+        let plot = MatrixHeatmapPlot {
+            id: child_id(&heatmap.id, "plot"),
+            layout: MatrixLayout::new(&heatmap.series, HeatmapMetrics::for_size(Size::Medium)),
+            series: heatmap.series,
+            value_labels: heatmap.value_labels,
+            value_label: heatmap.labels.value,
+        };
+        assert_eq!(plot.cell_title(2), Some("b × x".into()));
+        assert_eq!(plot.cell_title(4), None);
+    }
+}
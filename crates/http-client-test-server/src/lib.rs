@@ -3,19 +3,22 @@
 mod abort;
 mod contract;
 mod echo;
+mod mock;
 mod respond;
 mod server;
+mod websocket;
 
 pub use contract::{
     AbortSpec, ContentEncoding, HeaderSpec, RespondSpec, ResponseBodySpec, ResponseFraming,
     SpecUrlError,
 };
+pub use mock::{AfterLast, LoggedRequest, MockRoute, MockRoutes, MockRoutesError};
 pub use server::{ServerError, ServerErrorKind, TestServer};
 
 /// Runs the standalone loopback server until Ctrl-C is received.
 ///
 /// This entry point is shared with the binary and is not intended as consumer API.
 #[doc(hidden)]
-pub async fn run_cli(port: u16) -> Result<(), ServerError> {
-    server::run_cli(port).await
+pub async fn run_cli(port: u16, routes: Option<MockRoutes>) -> Result<(), ServerError> {
+    server::run_cli(port, routes).await
 }
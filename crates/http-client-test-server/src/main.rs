@@ -1,28 +1,57 @@
-use std::{env, process::ExitCode};
+use std::{env, path::PathBuf, process::ExitCode};
 
-const USAGE: &str = "Usage: http-client-test-server [--port <u16>]";
+use http_client_test_server::MockRoutes;
 
-fn parse_port<I>(mut arguments: I) -> Result<u16, ()>
+const USAGE: &str = "Usage: http-client-test-server [--port <u16>] [--routes <file>]";
+
+#[derive(Debug, Default, PartialEq)]
+struct Arguments {
+    port: u16,
+    routes: Option<PathBuf>,
+}
+
+fn parse_arguments<I>(mut arguments: I) -> Result<Arguments, ()>
 where
     I: Iterator<Item = String>,
 {
-    match (arguments.next(), arguments.next(), arguments.next()) {
-        (None, None, None) => Ok(0),
-        (Some(flag), Some(value), None) if flag == "--port" => value.parse().map_err(|_| ()),
-        _ => Err(()),
+    let mut port = None;
+    let mut routes = None;
+    while let Some(flag) = arguments.next() {
+        let value = arguments.next().ok_or(())?;
+        match flag.as_str() {
+            "--port" if port.is_none() => port = Some(value.parse().map_err(|_| ())?),
+            "--routes" if routes.is_none() => routes = Some(PathBuf::from(value)),
+            _ => return Err(()),
+        }
     }
+    Ok(Arguments {
+        port: port.unwrap_or(0),
+        routes,
+    })
 }
 
 #[tokio::main]
 async fn main() -> ExitCode {
-    let port = match parse_port(env::args().skip(1)) {
-        Ok(port) => port,
+    let arguments = match parse_arguments(env::args().skip(1)) {
+        Ok(arguments) => arguments,
         Err(()) => {
             eprintln!("{USAGE}");
             return ExitCode::from(2);
         }
     };
-    match http_client_test_server::run_cli(port).await {
+    let routes = match arguments
+        .routes
+        .as_deref()
+        .map(MockRoutes::load)
+        .transpose()
+    {
+        Ok(routes) => routes,
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match http_client_test_server::run_cli(arguments.port, routes).await {
         Ok(()) => ExitCode::SUCCESS,
         Err(error) => {
             eprintln!("{error}");
@@ -33,19 +62,32 @@ async fn main() -> ExitCode {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_port;
+    use super::{Arguments, parse_arguments};
+
+    fn parse(arguments: &[&str]) -> Result<Arguments, ()> {
+        parse_arguments(arguments.iter().map(|argument| (*argument).to_owned()))
+    }
 
     #[test]
-    fn parses_only_the_frozen_cli_shape() {
-        assert_eq!(parse_port([].into_iter()), Ok(0));
+    fn parses_only_the_documented_cli_shape() {
+        assert_eq!(parse(&[]), Ok(Arguments::default()));
         assert_eq!(
-            parse_port(["--port".to_owned(), "8080".to_owned()].into_iter()),
-            Ok(8080)
+            parse(&["--port", "8080"]),
+            Ok(Arguments {
+                port: 8080,
+                routes: None,
+            })
         );
-        assert_eq!(parse_port(["--port".to_owned()].into_iter()), Err(()));
         assert_eq!(
-            parse_port(["--host".to_owned(), "127.0.0.1".to_owned()].into_iter()),
-            Err(())
+            parse(&["--routes", "routes.json", "--port", "8080"]),
+            Ok(Arguments {
+                port: 8080,
+                routes: Some("routes.json".into()),
+            })
         );
+        assert_eq!(parse(&["--port"]), Err(()));
+        assert_eq!(parse(&["--port", "1", "--port", "2"]), Err(()));
+        assert_eq!(parse(&["--routes"]), Err(()));
+        assert_eq!(parse(&["--host", "127.0.0.1"]), Err(()));
     }
 }
@@ -0,0 +1,273 @@
+//! Route-file driven mock mode. Matched requests are answered with the same
+//! `RespondSpec` machinery as `/v1/respond`, and every request is kept in a
+//! bounded log served under `/__mock/`.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Mutex, PoisonError},
+};
+
+use bytes::Bytes;
+use hyper::{
+    Method, Request, Response, StatusCode,
+    body::Incoming,
+    header::{CONTENT_LENGTH, CONTENT_TYPE, HeaderValue},
+};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    HeaderSpec,
+    contract::{
+        ControlCode, ControlError, REQUEST_BODY_LIMIT, declared_content_length, read_bounded_body,
+    },
+    server::{
+        ServerBody, WireError, control_error_response, empty_body, empty_response, full_body,
+    },
+};
+
+pub use self::routes::{AfterLast, MockRoute, MockRoutes, MockRoutesError};
+use self::routes::{TemplateContext, query_pairs};
+
+mod routes;
+
+const LOG_CAPACITY: usize = 1024;
+const LOGGED_BODY_LIMIT: usize = 64 * 1024;
+
+/// One request received in mock mode, as listed by `GET /__mock/requests`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LoggedRequest {
+    /// Counts requests over the server's lifetime; clearing the log keeps it.
+    pub sequence: u64,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: Vec<HeaderSpec>,
+    /// The body as lossy UTF-8, cut after 64 KiB.
+    pub body: String,
+    pub body_length: u64,
+    /// The matched route, or `None` when the request was answered with `404`.
+    pub route: Option<usize>,
+    /// The position of the served response in the route's sequence.
+    pub response: Option<usize>,
+}
+
+impl fmt::Debug for LoggedRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoggedRequest")
+            .field("sequence", &self.sequence)
+            .field("method", &self.method)
+            .field("path", &self.path)
+            .field("header_count", &self.headers.len())
+            .field("body_length", &self.body_length)
+            .field("route", &self.route)
+            .field("response", &self.response)
+            .finish()
+    }
+}
+
+#[derive(Serialize)]
+struct RequestLog<'a> {
+    requests: Vec<&'a LoggedRequest>,
+}
+
+#[derive(Default)]
+struct MockState {
+    calls: Vec<u64>,
+    log: VecDeque<LoggedRequest>,
+    next_sequence: u64,
+}
+
+impl MockState {
+    fn record(&mut self, mut request: LoggedRequest) {
+        request.sequence = self.next_sequence;
+        self.next_sequence += 1;
+        if self.log.len() == LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        self.log.push_back(request);
+    }
+}
+
+pub(crate) struct MockServer {
+    routes: MockRoutes,
+    state: Mutex<MockState>,
+}
+
+impl MockServer {
+    pub(crate) fn new(routes: MockRoutes) -> Self {
+        let state = MockState {
+            calls: vec![0; routes.len()],
+            ..MockState::default()
+        };
+        Self {
+            routes,
+            state: Mutex::new(state),
+        }
+    }
+
+    pub(crate) async fn handle(
+        &self,
+        request: Request<Incoming>,
+        cancellation: CancellationToken,
+    ) -> Result<Response<ServerBody>, WireError> {
+        match request.uri().path() {
+            "/__mock/requests" => Ok(self.requests(&request)),
+            "/__mock/reset" => Ok(self.reset(&request)),
+            _ => self.serve(request, cancellation).await,
+        }
+    }
+
+    async fn serve(
+        &self,
+        request: Request<Incoming>,
+        cancellation: CancellationToken,
+    ) -> Result<Response<ServerBody>, WireError> {
+        let (parts, body) = request.into_parts();
+        let body = match read_bounded_body(
+            body,
+            declared_content_length(&parts.headers),
+            REQUEST_BODY_LIMIT,
+            ControlCode::RequestBodyTooLarge,
+        )
+        .await
+        {
+            Ok(body) => body,
+            Err(error) => return Ok(control_error_response(error)),
+        };
+        let query = query_pairs(parts.uri.query());
+        let matched = self
+            .routes
+            .find(&parts.method, parts.uri.path(), &query, &parts.headers);
+
+        let spec = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            let selected = matched.map(|matched| {
+                let route = matched.index;
+                let call = state.calls[route];
+                state.calls[route] += 1;
+                let (response, spec) = matched.route.response(call);
+                let context = TemplateContext {
+                    method: &parts.method,
+                    params: &matched.params,
+                    query: &query,
+                    headers: &parts.headers,
+                };
+                (route, response, context.render_spec(spec))
+            });
+            state.record(LoggedRequest {
+                sequence: 0,
+                method: parts.method.to_string(),
+                path: parts.uri.path().to_owned(),
+                query: parts.uri.query().map(str::to_owned),
+                headers: parts
+                    .headers
+                    .iter()
+                    .map(|(name, value)| HeaderSpec {
+                        name: name.as_str().to_owned(),
+                        value: String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    })
+                    .collect(),
+                body: String::from_utf8_lossy(&body[..body.len().min(LOGGED_BODY_LIMIT)])
+                    .into_owned(),
+                body_length: body.len() as u64,
+                route: selected.as_ref().map(|(route, _, _)| *route),
+                response: selected.as_ref().map(|(_, response, _)| *response),
+            });
+            selected.map(|(_, _, spec)| spec)
+        };
+
+        match spec {
+            Some(spec) => {
+                crate::respond::serve(spec, parts.method == Method::HEAD, cancellation).await
+            }
+            None => Ok(json_response(
+                StatusCode::NOT_FOUND,
+                Bytes::from_static(b"{\"code\":\"no_matching_route\"}"),
+            )),
+        }
+    }
+
+    /// Lists the log, optionally narrowed by `method`, `path` and `route`,
+    /// or clears it.
+    fn requests(&self, request: &Request<Incoming>) -> Response<ServerBody> {
+        if request.method() == Method::DELETE {
+            self.state
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .log
+                .clear();
+            return no_content_response();
+        }
+        if request.method() != Method::GET {
+            return empty_response(StatusCode::METHOD_NOT_ALLOWED, Some("GET, DELETE"));
+        }
+        let mut method = None;
+        let mut path = None;
+        let mut route = None;
+        for (name, value) in query_pairs(request.uri().query()) {
+            match name.as_str() {
+                "method" if method.is_none() => method = Some(value.to_ascii_uppercase()),
+                "path" if path.is_none() => path = Some(value),
+                "route" if route.is_none() => match value.parse::<usize>() {
+                    Ok(index) => route = Some(index),
+                    Err(_) => {
+                        return control_error_response(ControlError::invalid(
+                            ControlCode::InvalidRequest,
+                        ));
+                    }
+                },
+                _ => {
+                    return control_error_response(ControlError::invalid(
+                        ControlCode::InvalidRequest,
+                    ));
+                }
+            }
+        }
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let log = RequestLog {
+            requests: state
+                .log
+                .iter()
+                .filter(|logged| {
+                    method
+                        .as_ref()
+                        .is_none_or(|method| &logged.method == method)
+                })
+                .filter(|logged| path.as_ref().is_none_or(|path| &logged.path == path))
+                .filter(|logged| route.is_none_or(|route| logged.route == Some(route)))
+                .collect(),
+        };
+        let json = serde_json::to_vec(&log).expect("request log serializes");
+        json_response(StatusCode::OK, Bytes::from(json))
+    }
+
+    /// Clears the log and starts every route's sequence over.
+    fn reset(&self, request: &Request<Incoming>) -> Response<ServerBody> {
+        if request.method() != Method::POST {
+            return empty_response(StatusCode::METHOD_NOT_ALLOWED, Some("POST"));
+        }
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.log.clear();
+        state.calls.fill(0);
+        no_content_response()
+    }
+}
+
+fn json_response(status: StatusCode, bytes: Bytes) -> Response<ServerBody> {
+    let length = HeaderValue::from_str(&bytes.len().to_string()).expect("decimal length is valid");
+    let mut response = Response::new(full_body(bytes));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response.headers_mut().insert(CONTENT_LENGTH, length);
+    response
+}
+
+fn no_content_response() -> Response<ServerBody> {
+    let mut response = Response::new(empty_body());
+    *response.status_mut() = StatusCode::NO_CONTENT;
+    response
+}
@@ -0,0 +1,456 @@
+use std::{collections::BTreeMap, fmt, io, path::Path};
+
+use hyper::{HeaderMap, Method, header::HeaderName};
+use serde::{Deserialize, Serialize};
+
+use crate::{RespondSpec, ResponseBodySpec};
+
+/// One entry of a route file: what a request must look like and the
+/// responses it receives on successive matching calls.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockRoute {
+    /// Any method matches when absent.
+    #[serde(default)]
+    pub method: Option<String>,
+    /// Literal segments, `{name}` for one captured segment and a final
+    /// `{name*}` for the rest of the path.
+    pub path: String,
+    /// Headers that must be present with exactly these values.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Query parameters that must be present with exactly these values.
+    #[serde(default)]
+    pub query: BTreeMap<String, String>,
+    /// Header values and JSON body strings may contain `{{path.<name>}}`,
+    /// `{{query.<name>}}`, `{{header.<name>}}` and `{{method}}`.
+    pub responses: Vec<RespondSpec>,
+    #[serde(default)]
+    pub after_last: AfterLast,
+}
+
+impl fmt::Debug for MockRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockRoute")
+            .field("method", &self.method)
+            .field("path", &self.path)
+            .field("header_matcher_count", &self.headers.len())
+            .field("query_matcher_count", &self.query.len())
+            .field("response_count", &self.responses.len())
+            .field("after_last", &self.after_last)
+            .finish()
+    }
+}
+
+/// What a route serves once every response has been used.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AfterLast {
+    /// Keep serving the last response.
+    #[default]
+    Repeat,
+    /// Start over from the first response.
+    Cycle,
+}
+
+/// Failure to load a route file. Route numbers count from zero in file order.
+#[derive(Debug, thiserror::Error)]
+pub enum MockRoutesError {
+    #[error("route file could not be read: {0}")]
+    Read(#[source] io::Error),
+    #[error("route file is malformed: {0}")]
+    Malformed(#[source] serde_json::Error),
+    #[error("route {route} has an invalid method")]
+    InvalidMethod { route: usize },
+    #[error("route {route} has an invalid path pattern")]
+    InvalidPath { route: usize },
+    #[error("route {route} matches an invalid header name")]
+    InvalidHeader { route: usize },
+    #[error("route {route} has no responses")]
+    NoResponses { route: usize },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RouteFile {
+    routes: Vec<MockRoute>,
+}
+
+/// Validated routes, tried in order; the first match serves the request.
+pub struct MockRoutes {
+    routes: Vec<CompiledRoute>,
+}
+
+impl MockRoutes {
+    pub fn new(routes: Vec<MockRoute>) -> Result<Self, MockRoutesError> {
+        let routes = routes
+            .into_iter()
+            .enumerate()
+            .map(|(index, route)| CompiledRoute::new(index, route))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { routes })
+    }
+
+    /// Parses a route file of the form `{"routes": [...]}`.
+    pub fn from_json(bytes: &[u8]) -> Result<Self, MockRoutesError> {
+        let file: RouteFile = serde_json::from_slice(bytes).map_err(MockRoutesError::Malformed)?;
+        Self::new(file.routes)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, MockRoutesError> {
+        Self::from_json(&std::fs::read(path).map_err(MockRoutesError::Read)?)
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    pub(super) fn find(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &[(String, String)],
+        headers: &HeaderMap,
+    ) -> Option<RouteMatch<'_>> {
+        self.routes.iter().enumerate().find_map(|(index, route)| {
+            route
+                .matches(method, path, query, headers)
+                .map(|params| RouteMatch {
+                    index,
+                    route,
+                    params,
+                })
+        })
+    }
+}
+
+impl fmt::Debug for MockRoutes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockRoutes")
+            .field("route_count", &self.routes.len())
+            .finish()
+    }
+}
+
+/// The first route matching a request, with its path captures.
+pub(super) struct RouteMatch<'a> {
+    pub(super) index: usize,
+    pub(super) route: &'a CompiledRoute,
+    pub(super) params: Vec<(String, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Rest(String),
+}
+
+pub(super) struct CompiledRoute {
+    method: Option<Method>,
+    segments: Vec<Segment>,
+    headers: Vec<(HeaderName, String)>,
+    query: Vec<(String, String)>,
+    responses: Vec<RespondSpec>,
+    after_last: AfterLast,
+}
+
+impl CompiledRoute {
+    fn new(index: usize, route: MockRoute) -> Result<Self, MockRoutesError> {
+        let method = route
+            .method
+            .map(|method| Method::from_bytes(method.to_ascii_uppercase().as_bytes()))
+            .transpose()
+            .map_err(|_| MockRoutesError::InvalidMethod { route: index })?;
+        let segments =
+            parse_pattern(&route.path).ok_or(MockRoutesError::InvalidPath { route: index })?;
+        let headers = route
+            .headers
+            .into_iter()
+            .map(|(name, value)| {
+                HeaderName::from_bytes(name.as_bytes())
+                    .map(|name| (name, value))
+                    .map_err(|_| MockRoutesError::InvalidHeader { route: index })
+            })
+            .collect::<Result<_, _>>()?;
+        if route.responses.is_empty() {
+            return Err(MockRoutesError::NoResponses { route: index });
+        }
+        Ok(Self {
+            method,
+            segments,
+            headers,
+            query: route.query.into_iter().collect(),
+            responses: route.responses,
+            after_last: route.after_last,
+        })
+    }
+
+    fn matches(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &[(String, String)],
+        headers: &HeaderMap,
+    ) -> Option<Vec<(String, String)>> {
+        if self
+            .method
+            .as_ref()
+            .is_some_and(|expected| expected != method)
+        {
+            return None;
+        }
+        let header_matches = self.headers.iter().all(|(name, expected)| {
+            headers
+                .get_all(name)
+                .iter()
+                .any(|value| value.as_bytes() == expected.as_bytes())
+        });
+        let query_matches = self
+            .query
+            .iter()
+            .all(|expected| query.iter().any(|pair| pair == expected));
+        if !header_matches || !query_matches {
+            return None;
+        }
+        match_path(&self.segments, path)
+    }
+
+    /// The response for the route's `call`-th match, counting from zero.
+    pub(super) fn response(&self, call: u64) -> (usize, &RespondSpec) {
+        let count = self.responses.len() as u64;
+        let index = match self.after_last {
+            AfterLast::Repeat => call.min(count - 1),
+            AfterLast::Cycle => call % count,
+        } as usize;
+        (index, &self.responses[index])
+    }
+}
+
+fn parse_pattern(pattern: &str) -> Option<Vec<Segment>> {
+    let parts: Vec<&str> = pattern.strip_prefix('/')?.split('/').collect();
+    let last = parts.len() - 1;
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(position, part)| {
+            let Some(name) = part
+                .strip_prefix('{')
+                .and_then(|part| part.strip_suffix('}'))
+            else {
+                return (!part.contains(['{', '}'])).then(|| Segment::Literal(part.to_owned()));
+            };
+            let (name, rest) = match name.strip_suffix('*') {
+                Some(name) => (name, true),
+                None => (name, false),
+            };
+            if name.is_empty() || name.contains(['{', '}', '*']) || (rest && position != last) {
+                return None;
+            }
+            Some(if rest {
+                Segment::Rest(name.to_owned())
+            } else {
+                Segment::Param(name.to_owned())
+            })
+        })
+        .collect()
+}
+
+fn match_path(segments: &[Segment], path: &str) -> Option<Vec<(String, String)>> {
+    let parts: Vec<&str> = path.strip_prefix('/')?.split('/').collect();
+    let mut params = Vec::new();
+    for (position, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::Rest(name) => {
+                let rest = parts.get(position..).unwrap_or_default().join("/");
+                params.push((name.clone(), decode_component(&rest, false)));
+                return Some(params);
+            }
+            Segment::Literal(literal) => {
+                if parts.get(position) != Some(&literal.as_str()) {
+                    return None;
+                }
+            }
+            Segment::Param(name) => {
+                let part = parts.get(position).filter(|part| !part.is_empty())?;
+                params.push((name.clone(), decode_component(part, false)));
+            }
+        }
+    }
+    (parts.len() == segments.len()).then_some(params)
+}
+
+/// Decoded `name=value` pairs in request order.
+pub(super) fn query_pairs(query: Option<&str>) -> Vec<(String, String)> {
+    query
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode_component(name, true), decode_component(value, true))
+        })
+        .collect()
+}
+
+/// Percent-decodes `value`, leaving malformed escapes as they are.
+fn decode_component(value: &str, plus_as_space: bool) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| bytes.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (escaped, bytes[index]) {
+            (Some(byte), _) => {
+                decoded.push(byte);
+                index += 3;
+                continue;
+            }
+            (None, b'+') if plus_as_space => decoded.push(b' '),
+            (None, byte) => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// What templates in a matched route's responses can refer to.
+pub(super) struct TemplateContext<'a> {
+    pub(super) method: &'a Method,
+    pub(super) params: &'a [(String, String)],
+    pub(super) query: &'a [(String, String)],
+    pub(super) headers: &'a HeaderMap,
+}
+
+impl TemplateContext<'_> {
+    /// Fills the templates in `spec`'s header values and JSON body strings.
+    pub(super) fn render_spec(&self, spec: &RespondSpec) -> RespondSpec {
+        let mut spec = spec.clone();
+        for header in &mut spec.headers {
+            header.value = self.render(&header.value);
+        }
+        if let ResponseBodySpec::Json { value } = &mut spec.body {
+            self.render_value(value);
+        }
+        spec
+    }
+
+    fn render_value(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(text) => *text = self.render(text),
+            serde_json::Value::Array(items) => {
+                items.iter_mut().for_each(|item| self.render_value(item));
+            }
+            serde_json::Value::Object(fields) => {
+                fields
+                    .values_mut()
+                    .for_each(|field| self.render_value(field));
+            }
+            _ => {}
+        }
+    }
+
+    /// Replaces known `{{...}}` expressions. A known expression without a
+    /// value becomes empty; anything else is kept as written.
+    fn render(&self, template: &str) -> String {
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            let Some(length) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let end = start + 2 + length + 2;
+            rendered.push_str(&rest[..start]);
+            match self.lookup(rest[start + 2..end - 2].trim()) {
+                Some(value) => rendered.push_str(&value),
+                None => rendered.push_str(&rest[start..end]),
+            }
+            rest = &rest[end..];
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+
+    fn lookup(&self, expression: &str) -> Option<String> {
+        if expression == "method" {
+            return Some(self.method.to_string());
+        }
+        let (source, name) = expression.split_once('.')?;
+        let find = |pairs: &[(String, String)]| {
+            pairs
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        };
+        match source {
+            "path" => Some(find(self.params)),
+            "query" => Some(find(self.query)),
+            "header" => Some(
+                self.headers
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_owned(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn patterns_capture_segments_and_reject_malformed_placeholders() {
+        let pattern = parse_pattern("/users/{id}/files/{path*}").unwrap();
+        assert_eq!(
+            match_path(&pattern, "/users/a%20b/files/docs/readme.md"),
+            Some(vec![
+                ("id".to_owned(), "a b".to_owned()),
+                ("path".to_owned(), "docs/readme.md".to_owned()),
+            ])
+        );
+        assert_eq!(match_path(&pattern, "/users//files/x"), None);
+        assert_eq!(match_path(&pattern, "/accounts/1/files/x"), None);
+
+        let exact = parse_pattern("/items").unwrap();
+        assert_eq!(match_path(&exact, "/items"), Some(Vec::new()));
+        assert_eq!(match_path(&exact, "/items/"), None);
+
+        for malformed in ["items", "/{}", "/{rest*}/tail", "/a{id}", "/{a{b}}"] {
+            assert_eq!(parse_pattern(malformed), None, "{malformed}");
+        }
+        assert_eq!(
+            query_pairs(Some("q=a+b%2Fc&flag&=x")),
+            [("q", "a b/c"), ("flag", ""), ("", "x")].map(|(n, v)| (n.to_owned(), v.to_owned()))
+        );
+    }
+
+    #[test]
+    fn templates_fill_known_expressions_and_keep_the_rest() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from_static("r-1"));
+        let context = TemplateContext {
+            method: &Method::PUT,
+            params: &[("id".to_owned(), "42".to_owned())],
+            query: &[("page".to_owned(), "2".to_owned())],
+            headers: &headers,
+        };
+        assert_eq!(
+            context.render("{{method}} {{ path.id }}?page={{query.page}}&sort={{query.sort}}"),
+            "PUT 42?page=2&sort="
+        );
+        assert_eq!(
+            context.render("{{header.X-Request-Id}} {{other}} {{open"),
+            "r-1 {{other}} {{open"
+        );
+    }
+}
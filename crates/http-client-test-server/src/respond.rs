@@ -127,7 +127,17 @@ pub(crate) async fn handle(
         }
     };
 
-    let prepared = match prepare(parsed).await {
+    serve(parsed, method == Method::HEAD, cancellation).await
+}
+
+/// Validates `spec` and answers with it. Mock mode serves its routes
+/// through here as well.
+pub(crate) async fn serve(
+    spec: RespondSpec,
+    is_head: bool,
+    cancellation: CancellationToken,
+) -> Result<Response<ServerBody>, WireError> {
+    let prepared = match prepare(spec).await {
         Ok(prepared) => prepared,
         Err(error) => return Ok(control_error_response(error)),
     };
@@ -141,7 +151,7 @@ pub(crate) async fn handle(
         return Err(WireError::Cancelled);
     }
 
-    Ok(prepared.into_response(method == Method::HEAD, cancellation))
+    Ok(prepared.into_response(is_head, cancellation))
 }
 
 struct PreparedResponse {
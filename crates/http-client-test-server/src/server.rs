@@ -3,6 +3,8 @@ use std::{
     fmt,
     io::{self, Write as _},
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    pin::pin,
+    sync::Arc,
     time::Duration,
 };
 
@@ -11,11 +13,11 @@ use http_body_util::{BodyExt as _, Empty, Full, combinators::UnsyncBoxBody};
 use hyper::{
     Request, Response, StatusCode,
     body::Incoming,
-    header::{ALLOW, CONTENT_LENGTH, CONTENT_TYPE, HeaderValue},
+    header::{ALLOW, CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, HeaderValue},
     server::conn::http1,
     service::service_fn,
 };
-use hyper_util::{rt::TokioIo, server::graceful::GracefulShutdown};
+use hyper_util::rt::TokioIo;
 use tokio::{
     net::{TcpListener, TcpStream},
     sync::Semaphore,
@@ -25,8 +27,9 @@ use tokio::{
 use tokio_util::{sync::CancellationToken, task::AbortOnDropHandle};
 
 use crate::{
-    AbortSpec, RespondSpec, SpecUrlError,
+    AbortSpec, MockRoutes, RespondSpec, SpecUrlError,
     contract::{ControlError, encode_spec},
+    mock::MockServer,
 };
 
 const ACTIVE_CONNECTION_LIMIT: usize = 64;
@@ -140,10 +143,16 @@ pub struct TestServer {
 
 impl TestServer {
     pub async fn spawn() -> Result<Self, ServerError> {
-        Self::spawn_on_port(0).await
+        Self::spawn_on_port(0, None).await
     }
 
-    async fn spawn_on_port(port: u16) -> Result<Self, ServerError> {
+    /// Spawns a server that answers from `routes` instead of the `/v1/`
+    /// contract. `/healthz` is still served.
+    pub async fn spawn_mock(routes: MockRoutes) -> Result<Self, ServerError> {
+        Self::spawn_on_port(0, Some(routes)).await
+    }
+
+    async fn spawn_on_port(port: u16, routes: Option<MockRoutes>) -> Result<Self, ServerError> {
         let address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
         let listener = TcpListener::bind(address)
             .await
@@ -153,7 +162,9 @@ impl TestServer {
             .map_err(|_| ServerError::new(ServerErrorKind::Bind))?;
         let cancellation = CancellationToken::new();
         let owner_cancellation = cancellation.clone();
-        let owner = tokio::spawn(async move { accept_owner(listener, owner_cancellation).await });
+        let mock = routes.map(|routes| Arc::new(MockServer::new(routes)));
+        let owner =
+            tokio::spawn(async move { accept_owner(listener, owner_cancellation, mock).await });
         Ok(Self {
             base_url: format!("http://127.0.0.1:{}", actual_address.port()),
             cancellation,
@@ -173,6 +184,19 @@ impl TestServer {
         ))
     }
 
+    /// The `ws://` URL of the echo socket.
+    pub fn websocket_url(&self) -> String {
+        format!(
+            "ws://{}/v1/websocket",
+            self.base_url.trim_start_matches("http://")
+        )
+    }
+
+    /// The request log of a mock server; see [`TestServer::spawn_mock`].
+    pub fn requests_url(&self) -> String {
+        format!("{}/__mock/requests", self.base_url)
+    }
+
     pub fn abort_url(&self, spec: &AbortSpec) -> Result<String, SpecUrlError> {
         Ok(format!(
             "{}/v1/abort?spec={}",
@@ -203,8 +227,8 @@ impl Drop for TestServer {
     }
 }
 
-pub(crate) async fn run_cli(port: u16) -> Result<(), ServerError> {
-    let server = TestServer::spawn_on_port(port).await?;
+pub(crate) async fn run_cli(port: u16, routes: Option<MockRoutes>) -> Result<(), ServerError> {
+    let server = TestServer::spawn_on_port(port, routes).await?;
     {
         let stdout = io::stdout();
         let mut stdout = stdout.lock();
@@ -223,9 +247,9 @@ pub(crate) async fn run_cli(port: u16) -> Result<(), ServerError> {
 async fn accept_owner(
     listener: TcpListener,
     cancellation: CancellationToken,
+    mock: Option<Arc<MockServer>>,
 ) -> Result<(), ServerError> {
-    let graceful = GracefulShutdown::new();
-    let semaphore = std::sync::Arc::new(Semaphore::new(ACTIVE_CONNECTION_LIMIT));
+    let semaphore = Arc::new(Semaphore::new(ACTIVE_CONNECTION_LIMIT));
     let mut connections = JoinSet::new();
     let mut terminal_error = None;
 
@@ -245,11 +269,12 @@ async fn accept_owner(
                             drop(stream);
                             continue;
                         };
-                        let watcher = graceful.watcher();
                         let connection_cancellation = cancellation.clone();
+                        let connection_mock = mock.clone();
                         connections.spawn(async move {
                             let _permit = permit;
-                            serve_connection(stream, watcher, connection_cancellation).await;
+                            serve_connection(stream, connection_cancellation, connection_mock)
+                                .await;
                         });
                     }
                     Err(_) => {
@@ -262,7 +287,6 @@ async fn accept_owner(
     }
 
     let graceful_drain = async {
-        graceful.shutdown().await;
         while let Some(result) = connections.join_next().await {
             if result.is_err_and(|error| error.is_panic()) {
                 terminal_error.get_or_insert(ServerErrorKind::TaskPanicked);
@@ -288,21 +312,51 @@ async fn accept_owner(
 
 async fn serve_connection(
     stream: TcpStream,
-    watcher: hyper_util::server::graceful::Watcher,
     cancellation: CancellationToken,
+    mock: Option<Arc<MockServer>>,
 ) {
     let io = TokioIo::new(stream);
-    let service = service_fn(move |request| route(request, cancellation.clone()));
+    let shutdown = cancellation.clone();
+    let service = service_fn(move |request| route(request, cancellation.clone(), mock.clone()));
     let mut builder = http1::Builder::new();
-    builder.keep_alive(false).half_close(true);
-    let connection = builder.serve_connection(io, service);
-    let _expected_connection_result = watcher.watch(connection).await;
+    builder.half_close(true);
+    // Upgradeable connections cannot be handed to a graceful-shutdown
+    // watcher, so the connection watches the server's cancellation itself.
+    let mut connection = pin!(builder.serve_connection(io, service).with_upgrades());
+    tokio::select! {
+        _ = connection.as_mut() => return,
+        () = shutdown.cancelled() => connection.as_mut().graceful_shutdown(),
+    }
+    let _expected_connection_result = connection.await;
 }
 
 async fn route(
     request: Request<Incoming>,
     cancellation: CancellationToken,
+    mock: Option<Arc<MockServer>>,
 ) -> Result<Response<ServerBody>, WireError> {
+    let mut response = dispatch(request, cancellation, mock.as_deref()).await?;
+    // Each exchange still gets its own connection. This is spelled out per
+    // response because disabling keep-alive on the builder would also add
+    // `close` to the `Connection: upgrade` of a WebSocket handshake.
+    if response.status() != StatusCode::SWITCHING_PROTOCOLS {
+        response
+            .headers_mut()
+            .insert(CONNECTION, HeaderValue::from_static("close"));
+    }
+    Ok(response)
+}
+
+async fn dispatch(
+    request: Request<Incoming>,
+    cancellation: CancellationToken,
+    mock: Option<&MockServer>,
+) -> Result<Response<ServerBody>, WireError> {
+    if let Some(mock) = mock
+        && request.uri().path() != "/healthz"
+    {
+        return mock.handle(request, cancellation).await;
+    }
     match request.uri().path() {
         "/healthz" => {
             if request.method() != hyper::Method::GET {
@@ -327,6 +381,7 @@ async fn route(
         "/v1/respond" => crate::respond::handle(request, cancellation).await,
         "/v1/abort" => crate::abort::handle(request, cancellation).await,
         "/v1/echo" => Ok(crate::echo::handle(request).await),
+        "/v1/websocket" => Ok(crate::websocket::handle(request, cancellation).await),
         _ => Ok(empty_response(StatusCode::NOT_FOUND, None)),
     }
 }
@@ -0,0 +1,232 @@
+use futures_util::{SinkExt as _, StreamExt as _};
+use hyper::{
+    Method, Request, Response, StatusCode,
+    body::Incoming,
+    header::{
+        CONNECTION, HeaderMap, HeaderName, HeaderValue, SEC_WEBSOCKET_ACCEPT, SEC_WEBSOCKET_KEY,
+        SEC_WEBSOCKET_PROTOCOL, SEC_WEBSOCKET_VERSION, UPGRADE,
+    },
+};
+use hyper_util::rt::TokioIo;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::{
+    WebSocketStream,
+    tungstenite::{
+        Message,
+        handshake::derive_accept_key,
+        protocol::{CloseFrame, Role, frame::coding::CloseCode},
+    },
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    contract::{ControlCode, ControlError},
+    server::{ServerBody, control_error_response, empty_body, empty_response},
+};
+
+/// A text command understood by the echo socket. Every other text frame is
+/// echoed unchanged.
+#[derive(Debug, Eq, PartialEq)]
+enum Command<'a> {
+    /// `!close <code> [reason]` closes from the server side.
+    Close { code: u16, reason: &'a str },
+    /// `!ping [payload]` sends a ping; the client's pong is answered with
+    /// `pong <payload>`.
+    Ping(&'a str),
+    /// `!header <name> <value>` answers `match`, `mismatch` or `missing`
+    /// for a handshake request header without revealing its value.
+    Header { name: &'a str, value: &'a str },
+}
+
+pub(crate) async fn handle(
+    request: Request<Incoming>,
+    cancellation: CancellationToken,
+) -> Response<ServerBody> {
+    if request.method() != Method::GET {
+        return empty_response(StatusCode::METHOD_NOT_ALLOWED, Some("GET"));
+    }
+    let Some(accept) = accept_key(request.headers()) else {
+        return control_error_response(ControlError::invalid(ControlCode::InvalidRequest));
+    };
+    let protocol = first_protocol(request.headers());
+    let handshake_headers = request.headers().clone();
+
+    tokio::spawn(async move {
+        let Ok(upgraded) = hyper::upgrade::on(request).await else {
+            return;
+        };
+        let socket =
+            WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Server, None).await;
+        serve_socket(socket, &handshake_headers, cancellation).await;
+    });
+
+    let mut response = Response::new(empty_body());
+    *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+    let headers = response.headers_mut();
+    headers.insert(CONNECTION, HeaderValue::from_static("upgrade"));
+    headers.insert(UPGRADE, HeaderValue::from_static("websocket"));
+    headers.insert(
+        SEC_WEBSOCKET_ACCEPT,
+        HeaderValue::from_str(&accept).expect("accept key is base64"),
+    );
+    if let Some(protocol) = protocol {
+        headers.insert(SEC_WEBSOCKET_PROTOCOL, protocol);
+    }
+    response
+}
+
+fn accept_key(headers: &HeaderMap) -> Option<String> {
+    let has_token = |name: HeaderName, token: &str| {
+        headers.get_all(name).iter().any(|value| {
+            value.to_str().is_ok_and(|value| {
+                value
+                    .split(',')
+                    .any(|part| part.trim().eq_ignore_ascii_case(token))
+            })
+        })
+    };
+    if !has_token(CONNECTION, "upgrade")
+        || !has_token(UPGRADE, "websocket")
+        || headers.get(SEC_WEBSOCKET_VERSION)? != "13"
+    {
+        return None;
+    }
+    let key = headers.get(SEC_WEBSOCKET_KEY)?;
+    Some(derive_accept_key(key.as_bytes()))
+}
+
+fn first_protocol(headers: &HeaderMap) -> Option<HeaderValue> {
+    headers
+        .get_all(SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .find(|protocol| !protocol.is_empty())
+        .and_then(|protocol| HeaderValue::from_str(protocol).ok())
+}
+
+async fn serve_socket<S>(
+    mut socket: WebSocketStream<S>,
+    handshake_headers: &HeaderMap,
+    cancellation: CancellationToken,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        let message = tokio::select! {
+            _ = cancellation.cancelled() => {
+                let _ = socket
+                    .close(Some(CloseFrame {
+                        code: CloseCode::Away,
+                        reason: "".into(),
+                    }))
+                    .await;
+                return;
+            }
+            message = socket.next() => message,
+        };
+        let reply = match message {
+            Some(Ok(Message::Text(text))) => Some(text_reply(text.as_str(), handshake_headers)),
+            Some(Ok(Message::Binary(bytes))) => Some(Message::Binary(bytes)),
+            Some(Ok(Message::Pong(payload))) => Some(Message::text(format!(
+                "pong {}",
+                String::from_utf8_lossy(&payload)
+            ))),
+            // Pings and the closing handshake are answered by the protocol
+            // layer while the stream keeps being polled.
+            Some(Ok(Message::Ping(_) | Message::Close(_) | Message::Frame(_))) => None,
+            Some(Err(_)) | None => return,
+        };
+        if let Some(reply) = reply
+            && socket.send(reply).await.is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn text_reply(text: &str, handshake_headers: &HeaderMap) -> Message {
+    match parse_command(text) {
+        Some(Command::Close { code, reason }) => Message::Close(Some(CloseFrame {
+            code: CloseCode::from(code),
+            reason: reason.to_owned().into(),
+        })),
+        Some(Command::Ping(payload)) => Message::Ping(payload.as_bytes().to_vec().into()),
+        Some(Command::Header { name, value }) => {
+            let verdict = match handshake_headers.get(name) {
+                None => "missing",
+                Some(actual) if actual.as_bytes() == value.as_bytes() => "match",
+                Some(_) => "mismatch",
+            };
+            Message::text(verdict)
+        }
+        None => Message::text(text.to_owned()),
+    }
+}
+
+fn parse_command(text: &str) -> Option<Command<'_>> {
+    let rest = text.strip_prefix('!')?;
+    let (name, arguments) = rest.split_once(' ').unwrap_or((rest, ""));
+    match name {
+        "close" => {
+            let (code, reason) = arguments.split_once(' ').unwrap_or((arguments, ""));
+            let code = code
+                .parse()
+                .ok()
+                .filter(|code| sendable_close_code(*code))?;
+            Some(Command::Close { code, reason })
+        }
+        "ping" => Some(Command::Ping(arguments)),
+        "header" => {
+            let (name, value) = arguments.split_once(' ')?;
+            Some(Command::Header { name, value })
+        }
+        _ => None,
+    }
+}
+
+/// Close codes an endpoint may put on the wire (RFC 6455 section 7.4).
+const fn sendable_close_code(code: u16) -> bool {
+    matches!(code, 1000..=1003 | 1007..=1014 | 3000..=4999)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_exact_and_everything_else_is_echoed() {
+        assert_eq!(
+            parse_command("!close 4001 going away"),
+            Some(Command::Close {
+                code: 4001,
+                reason: "going away"
+            })
+        );
+        assert_eq!(
+            parse_command("!close 1000"),
+            Some(Command::Close {
+                code: 1000,
+                reason: ""
+            })
+        );
+        assert_eq!(parse_command("!ping abc"), Some(Command::Ping("abc")));
+        assert_eq!(
+            parse_command("!header x-token secret value"),
+            Some(Command::Header {
+                name: "x-token",
+                value: "secret value"
+            })
+        );
+        for echoed in [
+            "!close 1005",
+            "!close nope",
+            "!header x-token",
+            "!unknown",
+            "hello",
+        ] {
+            assert_eq!(parse_command(echoed), None, "{echoed}");
+        }
+    }
+}
@@ -10,10 +10,10 @@ use base64::{
     engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
 };
 use bytes::Bytes;
-use futures_util::{future::join_all, stream};
+use futures_util::{SinkExt as _, StreamExt as _, future::join_all, stream};
 use http_client_test_server::{
-    AbortSpec, ContentEncoding, HeaderSpec, RespondSpec, ResponseBodySpec, ResponseFraming,
-    SpecUrlError, TestServer,
+    AbortSpec, ContentEncoding, HeaderSpec, MockRoutes, MockRoutesError, RespondSpec,
+    ResponseBodySpec, ResponseFraming, SpecUrlError, TestServer,
 };
 use reqwest::{Client, Method, StatusCode, header};
 use tokio::{
@@ -21,6 +21,10 @@ use tokio::{
     net::TcpStream,
     time::{Instant, sleep, timeout},
 };
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{Message, client::IntoClientRequest as _, protocol::frame::coding::CloseCode},
+};
 
 const REQUEST_BODY_LIMIT: usize = 64 * 1024 * 1024;
 const QUERY_SPEC_LIMIT: usize = 8 * 1024;
@@ -106,7 +110,7 @@ async fn cli_rejects_bad_arguments_and_prints_exact_readiness_line() -> Result<(
     assert!(invalid.stdout.is_empty());
     assert_eq!(
         String::from_utf8(invalid.stderr)?,
-        "Usage: http-client-test-server [--port <u16>]\n"
+        "Usage: http-client-test-server [--port <u16>] [--routes <file>]\n"
     );
 
     let mut child = ChildGuard::new(
@@ -675,6 +679,68 @@ async fn echo_is_exact_and_rejects_declared_overflow_without_partial_data()
     Ok(())
 }
 
+#[tokio::test]
+async fn websocket_echoes_frames_and_answers_control_commands() -> Result<(), Box<dyn Error>> {
+    let server = TestServer::spawn().await?;
+    let mut request = server.websocket_url().into_client_request()?;
+    request
+        .headers_mut()
+        .insert("x-test-token", "fixture".parse()?);
+    request
+        .headers_mut()
+        .insert(header::SEC_WEBSOCKET_PROTOCOL, "echo.v1, echo.v2".parse()?);
+    let (mut socket, response) = connect_async(request).await?;
+    let protocol = response
+        .headers()
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .map(|value| value.to_str().map(str::to_owned))
+        .transpose()?;
+
+    socket.send(Message::text("hello")).await?;
+    let text = socket.next().await.transpose()?;
+    socket
+        .send(Message::Binary(Bytes::from_static(&[0, 0xff])))
+        .await?;
+    let binary = socket.next().await.transpose()?;
+    socket
+        .send(Message::text("!header x-test-token fixture"))
+        .await?;
+    let header_match = socket.next().await.transpose()?;
+    socket.send(Message::text("!ping beat")).await?;
+    let ping = socket.next().await.transpose()?;
+    let pong_report = socket.next().await.transpose()?;
+    socket.send(Message::text("!close 4001 done")).await?;
+    let close = socket.next().await.transpose()?;
+    let end = socket.next().await.transpose()?;
+
+    let plain_get = test_client()?
+        .get(server.websocket_url().replacen("ws", "http", 1))
+        .send()
+        .await?;
+    let plain_status = plain_get.status();
+    let plain_body = plain_get.text().await?;
+    server.shutdown().await?;
+
+    assert_eq!(protocol.as_deref(), Some("echo.v1"));
+    assert_eq!(text, Some(Message::text("hello")));
+    assert_eq!(
+        binary,
+        Some(Message::Binary(Bytes::from_static(&[0, 0xff])))
+    );
+    assert_eq!(header_match, Some(Message::text("match")));
+    assert_eq!(ping, Some(Message::Ping(Bytes::from_static(b"beat"))));
+    assert_eq!(pong_report, Some(Message::text("pong beat")));
+    assert!(matches!(
+        close,
+        Some(Message::Close(Some(frame)))
+            if frame.code == CloseCode::from(4001) && frame.reason.as_str() == "done"
+    ));
+    assert_eq!(end, None);
+    assert_eq!(plain_status, StatusCode::BAD_REQUEST);
+    assert_eq!(plain_body, r#"{"code":"invalid_request"}"#);
+    Ok(())
+}
+
 #[tokio::test]
 async fn query_and_post_control_boundaries_are_exact() -> Result<(), Box<dyn Error>> {
     let server = TestServer::spawn().await?;
@@ -734,6 +800,248 @@ async fn query_and_post_control_boundaries_are_exact() -> Result<(), Box<dyn Err
     Ok(())
 }
 
+#[tokio::test]
+async fn mock_routes_match_template_and_advance_sequences() -> Result<(), Box<dyn Error>> {
+    let routes = MockRoutes::from_json(
+        br#"{"routes": [
+            {
+                "method": "GET",
+                "path": "/users/{id}",
+                "headers": {"authorization": "Bearer token"},
+                "responses": [
+                    {"status": 500},
+                    {"body": {"kind": "json", "value": {"id": "{{path.id}}", "view": "{{query.view}}"}}}
+                ]
+            },
+            {
+                "method": "post",
+                "path": "/login",
+                "query": {"next": "/home"},
+                "responses": [{
+                    "status": 302,
+                    "headers": [
+                        {"name": "location", "value": "{{query.next}}"},
+                        {"name": "set-cookie", "value": "session={{header.x-user}}; Path=/"}
+                    ]
+                }]
+            },
+            {"path": "/flaky", "responses": [{"status": 503}, {"status": 200}], "after_last": "cycle"}
+        ]}"#,
+    )?;
+    let server = TestServer::spawn_mock(routes).await?;
+    let base_url = server.base_url().to_owned();
+    let client = Client::builder()
+        .no_proxy()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    let user = |query: &'static str| {
+        client
+            .get(format!("{base_url}/users/a%20b{query}"))
+            .bearer_auth("token")
+            .send()
+    };
+    assert_eq!(user("").await?.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    for _ in 0..2 {
+        let response = user("?view=full").await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&response.bytes().await?)?,
+            serde_json::json!({"id": "a b", "view": "full"})
+        );
+    }
+    let unauthorized = client.get(format!("{base_url}/users/1")).send().await?;
+    assert_eq!(unauthorized.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        &unauthorized.bytes().await?[..],
+        b"{\"code\":\"no_matching_route\"}"
+    );
+
+    let login = client
+        .post(format!("{base_url}/login?next=%2Fhome"))
+        .header("x-user", "ada")
+        .send()
+        .await?;
+    assert_eq!(login.status(), StatusCode::FOUND);
+    assert_eq!(login.headers()[header::LOCATION], "/home");
+    assert_eq!(login.headers()[header::SET_COOKIE], "session=ada; Path=/");
+    let wrong_method = client
+        .get(format!("{base_url}/login?next=%2Fhome"))
+        .send()
+        .await?;
+    assert_eq!(wrong_method.status(), StatusCode::NOT_FOUND);
+
+    let mut flaky = Vec::new();
+    for _ in 0..4 {
+        flaky.push(
+            client
+                .put(format!("{base_url}/flaky"))
+                .send()
+                .await?
+                .status(),
+        );
+    }
+    assert_eq!(
+        flaky,
+        [StatusCode::SERVICE_UNAVAILABLE, StatusCode::OK].repeat(2)
+    );
+
+    let health = client.get(format!("{base_url}/healthz")).send().await?;
+    assert_eq!(&health.bytes().await?[..], b"ok\n");
+    let contract = client.post(format!("{base_url}/v1/echo")).send().await?;
+    assert_eq!(contract.status(), StatusCode::NOT_FOUND);
+    server.shutdown().await?;
+
+    for (file, expected) in [
+        (
+            &br#"{"routes": [{"path": "/x", "responses": []}]}"#[..],
+            "route 0 has no responses",
+        ),
+        (
+            br#"{"routes": [{"path": "/", "responses": [{}]}, {"path": "x", "responses": [{}]}]}"#,
+            "route 1 has an invalid path pattern",
+        ),
+        (
+            br#"{"routes": [{"path": "/{a}/{rest*}/b", "responses": [{}]}]}"#,
+            "route 0 has an invalid path pattern",
+        ),
+        (
+            br#"{"routes": [{"method": "G T", "path": "/", "responses": [{}]}]}"#,
+            "route 0 has an invalid method",
+        ),
+    ] {
+        assert_eq!(
+            MockRoutes::from_json(file).unwrap_err().to_string(),
+            expected
+        );
+    }
+    assert!(matches!(
+        MockRoutes::from_json(br#"{"routes": [], "extra": 1}"#),
+        Err(MockRoutesError::Malformed(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn mock_request_log_is_queryable_and_resettable() -> Result<(), Box<dyn Error>> {
+    let routes = MockRoutes::from_json(
+        br#"{"routes": [{"path": "/orders", "responses": [{"status": 500}, {"status": 201}]}]}"#,
+    )?;
+    let server = TestServer::spawn_mock(routes).await?;
+    let base_url = server.base_url().to_owned();
+    let client = test_client()?;
+    let log = |query: &'static str| {
+        let request = client.get(format!("{}{query}", server.requests_url()));
+        async move {
+            let body = request.send().await?.bytes().await?;
+            let log: serde_json::Value = serde_json::from_slice(&body)?;
+            Ok::<_, Box<dyn Error>>(log["requests"].as_array().cloned().unwrap_or_default())
+        }
+    };
+
+    client
+        .post(format!("{base_url}/orders?source=web"))
+        .header("x-trace", "t-1")
+        .body("{\"item\":1}")
+        .send()
+        .await?;
+    client.get(format!("{base_url}/missing")).send().await?;
+
+    let requests = log("").await?;
+    assert_eq!(requests.len(), 2);
+    let order = &requests[0];
+    assert_eq!(order["sequence"], 0);
+    assert_eq!(order["method"], "POST");
+    assert_eq!(order["path"], "/orders");
+    assert_eq!(order["query"], "source=web");
+    assert_eq!(order["body"], "{\"item\":1}");
+    assert_eq!(order["body_length"], 10);
+    assert_eq!(order["route"], 0);
+    assert_eq!(order["response"], 0);
+    assert!(
+        order["headers"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!({"name": "x-trace", "value": "t-1"}))
+    );
+    assert_eq!(requests[1]["route"], serde_json::Value::Null);
+    assert_eq!(log("?path=%2Fmissing").await?.len(), 1);
+    assert_eq!(log("?method=post&route=0").await?.len(), 1);
+    let bad_filter = client
+        .get(format!("{}?status=200", server.requests_url()))
+        .send()
+        .await?;
+    assert_eq!(bad_filter.status(), StatusCode::BAD_REQUEST);
+
+    let cleared = client.delete(server.requests_url()).send().await?;
+    assert_eq!(cleared.status(), StatusCode::NO_CONTENT);
+    assert!(log("").await?.is_empty());
+    let second = client.post(format!("{base_url}/orders")).send().await?;
+    assert_eq!(second.status(), StatusCode::CREATED);
+    assert_eq!(log("").await?[0]["sequence"], 2);
+
+    let reset = client
+        .post(format!("{base_url}/__mock/reset"))
+        .send()
+        .await?;
+    assert_eq!(reset.status(), StatusCode::NO_CONTENT);
+    assert!(log("").await?.is_empty());
+    let restarted = client.post(format!("{base_url}/orders")).send().await?;
+    assert_eq!(restarted.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    server.shutdown().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn cli_serves_a_route_file_and_reports_invalid_ones() -> Result<(), Box<dyn Error>> {
+    let binary = env!("CARGO_BIN_EXE_http-client-test-server");
+    let folder = std::env::temp_dir().join(format!("mock-routes-{}", std::process::id()));
+    std::fs::create_dir_all(&folder)?;
+    let invalid_path = folder.join("invalid.json");
+    std::fs::write(
+        &invalid_path,
+        r#"{"routes": [{"path": "/", "responses": []}]}"#,
+    )?;
+    let invalid = Command::new(binary)
+        .arg("--routes")
+        .arg(&invalid_path)
+        .output()?;
+    assert_eq!(invalid.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(invalid.stderr)?,
+        "route 0 has no responses\n"
+    );
+
+    let routes_path = folder.join("routes.json");
+    std::fs::write(
+        &routes_path,
+        r#"{"routes": [{"path": "/ping", "responses": [{"body": {"kind": "json", "value": "pong"}}]}]}"#,
+    )?;
+    let mut child = ChildGuard::new(
+        Command::new(binary)
+            .arg("--routes")
+            .arg(&routes_path)
+            .stdout(Stdio::piped())
+            .spawn()?,
+    );
+    let mut line = String::new();
+    std::io::BufReader::new(child.take_stdout()).read_line(&mut line)?;
+    let base_url = line
+        .strip_prefix("HTTP_CLIENT_TEST_SERVER=")
+        .and_then(|line| line.strip_suffix('\n'))
+        .expect("CLI prints the frozen readiness assignment");
+    let pong = test_client()?
+        .get(format!("{base_url}/ping"))
+        .send()
+        .await?
+        .bytes()
+        .await?;
+    assert_eq!(&pong[..], b"\"pong\"");
+    drop(child);
+    std::fs::remove_dir_all(&folder)?;
+    Ok(())
+}
+
 fn respond_spec_with_serialized_length(target: usize) -> RespondSpec {
     let mut spec = RespondSpec {
         headers: vec![HeaderSpec {